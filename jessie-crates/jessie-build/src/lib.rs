@@ -1,6 +1,8 @@
+use jessie_css::parser::Parser;
+use jessie_css::printer::minify;
 use jessie_lib::appinfo::AppInfo;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 pub fn build() {
     let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
         .expect("jessie-build : error finding environment variable CARGO_MANIFEST_DIR");
@@ -21,4 +23,60 @@ pub fn build() {
     fs::write(out_path, generated_str)
         .expect("jessie-build : error writing auto generated file appinfo.rs");
     println!("cargo::rerun-if-changed=config.ron");
+
+    embed_stylesheets(Path::new(&manifest_dir), Path::new(&out_dir));
+}
+
+///Bakes the `.css` files under the `styles` directory of the app into `stylesheets.rs`,
+///a `STYLESHEETS` slice of their paths relative to `styles` with their sources, for the `EmbeddedLoader` of jessie-css.
+///Release builds embed the stylesheets minified.
+fn embed_stylesheets(manifest_dir: &Path, out_dir: &Path) {
+    let release = std::env::var("PROFILE").is_ok_and(|v| v == "release");
+    let styles_dir = manifest_dir.join("styles");
+    let mut stylesheets = vec![];
+    if styles_dir.is_dir() {
+        collect_stylesheets(&styles_dir, &styles_dir, &mut stylesheets);
+    }
+    stylesheets.sort();
+
+    let entries: String = stylesheets
+        .iter()
+        .map(|(path, file)| match release {
+            true => {
+                let source =
+                    fs::read_to_string(file).expect("jessie-build : error reading a stylesheet");
+                let minified = minify(&Parser::new(&source).parse_stylesheet());
+                format!("    ({path:?}, {minified:?}),\n")
+            }
+            false => format!("    ({path:?}, include_str!({file:?})),\n"),
+        })
+        .collect();
+    fs::write(
+        out_dir.join("stylesheets.rs"),
+        format!("pub static STYLESHEETS: &[(&str, &str)] = &[\n{entries}];\n"),
+    )
+    .expect("jessie-build : error writing auto generated file stylesheets.rs");
+    println!("cargo::rerun-if-changed=styles");
+}
+
+fn collect_stylesheets(styles_dir: &Path, dir: &Path, out: &mut Vec<(String, PathBuf)>) {
+    let entries = fs::read_dir(dir).expect("jessie-build : error reading the styles directory");
+    for entry in entries {
+        let path = entry
+            .expect("jessie-build : error reading the styles directory")
+            .path();
+        if path.is_dir() {
+            collect_stylesheets(styles_dir, &path, out);
+        } else if path.extension().is_some_and(|v| v == "css") {
+            // The paths use `/` on every platform, like the urls of `@import`.
+            let relative = path
+                .strip_prefix(styles_dir)
+                .expect("jessie-build : stylesheet outside of the styles directory")
+                .components()
+                .map(|v| v.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.push((relative, path));
+        }
+    }
 }
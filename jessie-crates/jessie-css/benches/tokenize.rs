@@ -0,0 +1,75 @@
+//! Tokenizes a large stylesheet and reports the time and the heap allocations it took, next to a baseline that owns the
+//! value of every token like the tokenizer did before it borrowed them from the input.
+//! Run it with `cargo bench`.
+use jessie_css::{CSSToken, Tokenizer};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+///An allocator that counts the allocations made through it.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const REPEAT: usize = 100;
+const ITERATIONS: u32 = 20;
+
+///Runs `tokenize` once to count its allocations and then `ITERATIONS` times to time it, and prints both.
+fn measure<'a, T>(name: &str, input: &'a str, tokenize: impl Fn(&'a str) -> Vec<T>) -> usize {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let tokens = tokenize(input).len();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(tokenize(std::hint::black_box(input)));
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+
+    println!(
+        "{name}: {} bytes, {} tokens, {} allocations ({} bytes allocated), {:?} per run",
+        input.len(),
+        tokens,
+        allocations,
+        bytes,
+        elapsed
+    );
+    allocations
+}
+
+fn main() {
+    let input = include_str!("theme.css").repeat(REPEAT);
+
+    let borrowed = measure("tokenize", &input, |input| Tokenizer::new(input).tokenize());
+    // The baseline of a tokenizer that copies every token value into its own `String`.
+    let owned = measure("tokenize with owned values", &input, |input| {
+        Tokenizer::new(input)
+            .tokenize()
+            .into_iter()
+            .map(CSSToken::into_owned)
+            .collect()
+    });
+    println!(
+        "borrowing the values makes {} fewer allocations ({:.1}x fewer)",
+        owned - borrowed,
+        owned as f64 / borrowed.max(1) as f64
+    );
+}
@@ -0,0 +1,14 @@
+//! Runs every entry point of the parser on arbitrary input, which must not panic whatever the input is.
+//! Run with `cargo fuzz run parse` from `jessie-crates/jessie-css`.
+#![no_main]
+use jessie_css::parser::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    Parser::new(input).parse_stylesheet();
+    Parser::new(input).parse_list_of_declarations();
+    Parser::new(input).parse_block_contents();
+    let _ = Parser::new(input).parse_rule();
+    let _ = Parser::new(input).parse_declaration();
+    let _ = Parser::new(input).parse_component_value();
+});
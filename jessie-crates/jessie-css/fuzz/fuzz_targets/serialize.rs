@@ -0,0 +1,21 @@
+//! Checks that serializing the tokens of arbitrary input gives back the same tokens, and that serializing component
+//! values is stable. Run with `cargo fuzz run serialize` from `jessie-crates/jessie-css`.
+#![no_main]
+use jessie_css::parser::Parser;
+use jessie_css::serializer::{serialize_component_values, serialize_tokens};
+use jessie_css::{CSSToken, Tokenizer};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    // U+0000 is serialized as U+FFFD, but the tokenizer does not do the input preprocessing that replaces it.
+    let input = input.replace('\0', "\u{fffd}");
+    let mut tokens = Tokenizer::new(&input).tokenize();
+    // Two <whitespace-token>s in a row are written as one.
+    tokens.dedup_by(|a, b| *a == CSSToken::WhitespaceToken && *b == CSSToken::WhitespaceToken);
+    let serialized = serialize_tokens(&tokens);
+    assert_eq!(Tokenizer::new(&serialized).tokenize(), tokens);
+
+    let once = serialize_component_values(&Parser::new(&input).parse_list_of_component_values());
+    let twice = serialize_component_values(&Parser::new(&once).parse_list_of_component_values());
+    assert_eq!(once, twice);
+});
@@ -0,0 +1,25 @@
+//! Tokenizes arbitrary input, checking that the tokenizer ends with exactly one <EOF-token> and that the token spans
+//! stay inside the input. Run with `cargo fuzz run tokenize` from `jessie-crates/jessie-css`.
+#![no_main]
+use jessie_css::{CSSToken, Tokenizer};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let tokens = Tokenizer::new(input).tokenize_with_spans();
+    assert_eq!(tokens.last().map(|v| &v.token), Some(&CSSToken::EOFToken));
+    assert_eq!(
+        tokens
+            .iter()
+            .filter(|v| v.token == CSSToken::EOFToken)
+            .count(),
+        1
+    );
+    let mut offset = 0;
+    for token in &tokens {
+        assert!(token.span.start.offset >= offset);
+        assert!(token.span.start.offset <= token.span.end.offset);
+        assert!(input.is_char_boundary(token.span.end.offset));
+        offset = token.span.end.offset;
+    }
+    assert_eq!(offset, input.len());
+});
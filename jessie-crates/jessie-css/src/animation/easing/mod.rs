@@ -0,0 +1,322 @@
+//! The easing functions of https://www.w3.org/TR/css-easing-2/, like `ease-in-out`, `steps(4, jump-end)`
+//! and `linear(0, 0.25 75%, 1)`, which map the progress of an animation to the progress of its values.
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::ComponentValue;
+use crate::values::{Parse, ToCss, ValueParser, keyword_enum, unexpected, write_number};
+
+keyword_enum! {
+    ///Where the jumps of `steps()` are.
+    pub enum StepPosition {
+        JumpStart = "jump-start",
+        JumpEnd = "jump-end",
+        JumpNone = "jump-none",
+        JumpBoth = "jump-both",
+        Start = "start",
+        End = "end",
+    }
+}
+
+///A point of a `linear()` easing function, with its input filled in if it was left out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearStop {
+    pub input: f32,
+    pub output: f32,
+}
+
+///An <easing-function>.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EasingFunction {
+    ///`linear` without stops, or `linear()` with its stops in order.
+    Linear(Vec<LinearStop>),
+    CubicBezier(f32, f32, f32, f32),
+    Steps(u32, StepPosition),
+}
+
+impl Default for EasingFunction {
+    ///`ease`, the initial value of `transition-timing-function` and `animation-timing-function`.
+    fn default() -> Self {
+        Self::EASE
+    }
+}
+
+impl EasingFunction {
+    pub const LINEAR: EasingFunction = EasingFunction::Linear(vec![]);
+    pub const EASE: EasingFunction = EasingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    pub const EASE_IN: EasingFunction = EasingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    pub const EASE_OUT: EasingFunction = EasingFunction::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    pub const EASE_IN_OUT: EasingFunction = EasingFunction::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    ///Maps the input progress of an animation, usually in `0..=1`, to its output progress.
+    pub fn apply(&self, progress: f32) -> f32 {
+        match self {
+            EasingFunction::Linear(stops) => linear(stops, progress),
+            EasingFunction::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(*x1, *y1, *x2, *y2, progress)
+            }
+            EasingFunction::Steps(steps, position) => step(*steps, *position, progress),
+        }
+    }
+}
+
+///Evaluates `linear()` with https://www.w3.org/TR/css-easing-2/#linear-easing-function-output,
+///extrapolating from the first or last two stops outside of them.
+fn linear(stops: &[LinearStop], progress: f32) -> f32 {
+    if stops.len() < 2 {
+        return progress;
+    }
+    let last = stops.len() - 1;
+    let start = match stops.iter().rposition(|v| v.input <= progress) {
+        Some(i) if i == last => last - 1,
+        Some(i) => i,
+        None => 0,
+    };
+    let (a, b) = (stops[start], stops[start + 1]);
+    if a.input == b.input {
+        return b.output;
+    }
+    a.output + (b.output - a.output) * (progress - a.input) / (b.input - a.input)
+}
+
+///Evaluates a cubic Bézier curve from `(0, 0)` to `(1, 1)` at `x`, continuing it in a straight line outside of `0..=1`.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let curve = |t: f32, p1: f32, p2: f32| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+    };
+    if x <= 0.0 {
+        // The tangent at the start, or the line to the other control point if it is vertical.
+        let slope = match (x1, x2) {
+            (x1, _) if x1 > 0.0 => y1 / x1,
+            (_, x2) if y2 == 0.0 && x2 > 0.0 => y2 / x2,
+            _ => 0.0,
+        };
+        return slope * x;
+    }
+    if x >= 1.0 {
+        let slope = match (x1, x2) {
+            (_, x2) if x2 < 1.0 => (y2 - 1.0) / (x2 - 1.0),
+            (x1, _) if y1 == 1.0 && x1 < 1.0 => (y1 - 1.0) / (x1 - 1.0),
+            _ => 0.0,
+        };
+        return 1.0 + slope * (x - 1.0);
+    }
+    // `x` of the curve grows monotonically with `t` because the control points are in `0..=1`, so `t` can be bisected.
+    let (mut low, mut high) = (0.0f32, 1.0f32);
+    let mut t = x;
+    for _ in 0..32 {
+        let found = curve(t, x1, x2);
+        if (found - x).abs() < 1e-6 {
+            break;
+        }
+        match found < x {
+            true => low = t,
+            false => high = t,
+        }
+        t = (low + high) / 2.0;
+    }
+    curve(t, y1, y2)
+}
+
+///Evaluates `steps()` with https://www.w3.org/TR/css-easing-2/#step-easing-functions
+fn step(steps: u32, position: StepPosition, progress: f32) -> f32 {
+    let steps = steps as f32;
+    let mut current = (progress * steps).floor();
+    if matches!(
+        position,
+        StepPosition::JumpStart | StepPosition::Start | StepPosition::JumpBoth
+    ) {
+        current += 1.0;
+    }
+    let jumps = match position {
+        StepPosition::JumpBoth => steps + 1.0,
+        StepPosition::JumpNone => steps - 1.0,
+        _ => steps,
+    };
+    if progress >= 0.0 && current < 0.0 {
+        current = 0.0;
+    }
+    if progress <= 1.0 && current > jumps {
+        current = jumps;
+    }
+    current / jumps
+}
+
+impl Parse for EasingFunction {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        let function = match value {
+            ComponentValue::PreservedToken(token) => {
+                let CSSToken::IdentToken { value: keyword } = &token.token else {
+                    return Err(unexpected(value, "an easing function like `ease-in`"));
+                };
+                return match keyword.to_ascii_lowercase().as_str() {
+                    "linear" => Ok(EasingFunction::LINEAR),
+                    "ease" => Ok(EasingFunction::EASE),
+                    "ease-in" => Ok(EasingFunction::EASE_IN),
+                    "ease-out" => Ok(EasingFunction::EASE_OUT),
+                    "ease-in-out" => Ok(EasingFunction::EASE_IN_OUT),
+                    "step-start" => Ok(EasingFunction::Steps(1, StepPosition::JumpStart)),
+                    "step-end" => Ok(EasingFunction::Steps(1, StepPosition::JumpEnd)),
+                    _ => Err(unexpected(value, "an easing function like `ease-in`")),
+                };
+            }
+            ComponentValue::Function(function) => function,
+            _ => return Err(unexpected(value, "an easing function like `ease-in`")),
+        };
+        let error = |message: &str| {
+            CssDiagnostic::new(CssDiagnosticKind::InvalidValue, function.span, message)
+        };
+        let mut arguments = ValueParser::new(&function.value);
+        let out = match function.name.to_ascii_lowercase().as_str() {
+            "cubic-bezier" => {
+                let mut points = [0.0; 4];
+                for (i, point) in points.iter_mut().enumerate() {
+                    if i > 0 {
+                        arguments.expect_comma()?;
+                    }
+                    *point = arguments.expect_number()?;
+                }
+                let [x1, y1, x2, y2] = points;
+                if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+                    return Err(error(
+                        "the x coordinates of `cubic-bezier()` are between 0 and 1",
+                    ));
+                }
+                EasingFunction::CubicBezier(x1, y1, x2, y2)
+            }
+            "steps" => {
+                let steps = arguments.expect_integer()?;
+                let position = match arguments.try_parse(ValueParser::expect_comma) {
+                    Ok(()) => StepPosition::parse(&mut arguments)?,
+                    Err(_) => StepPosition::End,
+                };
+                let least = match position {
+                    StepPosition::JumpNone => 2,
+                    _ => 1,
+                };
+                if steps < least {
+                    return Err(error(&format!(
+                        "`steps()` with `{}` takes at least {least} step{}",
+                        position.keyword(),
+                        if least == 1 { "" } else { "s" }
+                    )));
+                }
+                EasingFunction::Steps(steps as u32, position)
+            }
+            "linear" => {
+                let stops = arguments.parse_comma_separated(parse_linear_stop)?;
+                let stops: Vec<_> = stops.into_iter().flatten().collect();
+                if stops.len() < 2 {
+                    return Err(error("`linear()` takes at least two points"));
+                }
+                EasingFunction::Linear(resolve_linear_stops(stops))
+            }
+            _ => return Err(unexpected(value, "an easing function like `ease-in`")),
+        };
+        arguments.expect_exhausted()?;
+        Ok(out)
+    }
+}
+
+///Parses `<number> && <percentage>{0,2}`, which is one point for every percentage or one without an input.
+fn parse_linear_stop(input: &mut ValueParser) -> Result<Vec<(f32, Option<f32>)>, CssDiagnostic> {
+    let mut inputs = vec![];
+    let mut output = None;
+    while !input.is_exhausted() && inputs.len() + output.is_some() as usize <= 3 {
+        if output.is_none()
+            && let Ok(number) = input.try_parse(ValueParser::expect_number)
+        {
+            output = Some(number);
+        } else if inputs.len() < 2
+            && let Ok(percentage) = input.try_parse(ValueParser::expect_percentage)
+        {
+            inputs.push(percentage);
+        } else {
+            break;
+        }
+    }
+    let Some(output) = output else {
+        let value = input.consume()?;
+        return Err(unexpected(value, "a number"));
+    };
+    Ok(match inputs.as_slice() {
+        [] => vec![(output, None)],
+        inputs => inputs.iter().map(|v| (output, Some(*v))).collect(),
+    })
+}
+
+///Fills in the inputs of the points of `linear()` that have none,
+///see https://www.w3.org/TR/css-easing-2/#linear-easing-function-parsing
+fn resolve_linear_stops(stops: Vec<(f32, Option<f32>)>) -> Vec<LinearStop> {
+    let mut inputs: Vec<_> = stops.iter().map(|v| v.1).collect();
+    let last = inputs.len() - 1;
+    inputs[0].get_or_insert(0.0);
+    inputs[last].get_or_insert(1.0);
+    // An input can not go back, so it is at least the largest input before it.
+    let mut largest = f32::MIN;
+    for input in inputs.iter_mut().flatten() {
+        largest = largest.max(*input);
+        *input = largest;
+    }
+    // Runs of points without an input are spread evenly between the points around them.
+    let mut i = 0;
+    while i < inputs.len() {
+        if inputs[i].is_some() {
+            i += 1;
+            continue;
+        }
+        let start = i - 1;
+        let end = (i..inputs.len())
+            .find(|&v| inputs[v].is_some())
+            .unwrap_or(last);
+        let (from, to) = (inputs[start].unwrap_or(0.0), inputs[end].unwrap_or(1.0));
+        for (j, input) in inputs[i..end].iter_mut().enumerate() {
+            let fraction = (j + 1) as f32 / (end - start) as f32;
+            *input = Some(from + (to - from) * fraction);
+        }
+        i = end;
+    }
+    stops
+        .into_iter()
+        .zip(inputs)
+        .map(|((output, _), input)| LinearStop {
+            input: input.unwrap_or_default(),
+            output,
+        })
+        .collect()
+}
+
+impl ToCss for EasingFunction {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            EasingFunction::Linear(stops) if stops.is_empty() => out.push_str("linear"),
+            EasingFunction::Linear(stops) => {
+                out.push_str("linear(");
+                for (i, stop) in stops.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_number(stop.output, out);
+                    out.push(' ');
+                    write_number(stop.input * 100.0, out);
+                    out.push('%');
+                }
+                out.push(')');
+            }
+            EasingFunction::CubicBezier(x1, y1, x2, y2) => {
+                out.push_str("cubic-bezier(");
+                for (i, value) in [x1, y1, x2, y2].into_iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_number(*value, out);
+                }
+                out.push(')');
+            }
+            EasingFunction::Steps(steps, position) => {
+                out.push_str(&format!("steps({steps}, {})", position.keyword()));
+            }
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! Interpolation of typed values, see https://www.w3.org/TR/css-values-4/#combining-values
+//! Transitions and animations go through [`interpolate_property`], which picks the typed value by the [`AnimationType`] of a property.
+use crate::parser::{ComponentValue, Parser};
+use crate::properties;
+use crate::values::calc::{Calc, CalcCategory, CalcNode};
+use crate::values::color::spaces::ColorSpace;
+use crate::values::color::{Color, ColorMix, HueInterpolation, interpolate};
+use crate::values::filter::{FilterFunction, FilterList};
+use crate::values::font::{FontSize, FontWeight};
+use crate::values::length::{
+    BoxPosition, CornerRadius, Length, LengthContext, LengthPercentage, LengthPercentageOrAuto,
+    LengthPercentageOrNone, LengthUnit, LineWidth,
+};
+use crate::values::shadow::{Shadow, ShadowList};
+use crate::values::transform::{Matrix, TransformFunction, TransformList, TransformOrigin};
+use crate::values::{AlphaValue, Integer, Number, Parse, ToCss, ZIndex, parse_value};
+
+///A typed value that can be interpolated.
+pub trait Animate: Sized {
+    ///Returns the value `progress` of the way from `self` to `to`, or `None` if the two can not be interpolated,
+    ///which makes the animation discrete. `progress` can be outside of `0..=1` for easing functions that overshoot.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self>;
+}
+
+fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress
+}
+
+impl Animate for f32 {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some(lerp(*self, *to, progress))
+    }
+}
+
+impl Animate for Number {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        self.0.animate(&to.0, progress).map(Number)
+    }
+}
+
+impl Animate for Integer {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some(Integer(
+            lerp(self.0 as f32, to.0 as f32, progress).round() as i32
+        ))
+    }
+}
+
+impl Animate for ZIndex {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        match (self, to) {
+            (ZIndex::Integer(from), ZIndex::Integer(to)) => Integer(*from)
+                .animate(&Integer(*to), progress)
+                .map(|v| ZIndex::Integer(v.0)),
+            _ => None,
+        }
+    }
+}
+
+impl Animate for AlphaValue {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some(AlphaValue(lerp(self.0, to.0, progress).clamp(0.0, 1.0)))
+    }
+}
+
+impl Animate for Length {
+    ///Only lengths with the same unit interpolate as a [`Length`], see [`LengthPercentage`] for the others.
+    ///A zero has whatever unit the other length has.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let unit = match (self.unit, to.unit) {
+            (from, to) if from == to => from,
+            (_, unit) if self.value == 0.0 => unit,
+            (unit, _) if to.value == 0.0 => unit,
+            _ => return None,
+        };
+        Some(Length {
+            value: lerp(self.value, to.value, progress),
+            unit,
+        })
+    }
+}
+
+impl LengthPercentage {
+    fn to_calc_node(&self) -> CalcNode {
+        match self {
+            LengthPercentage::Length(length) => CalcNode::Length(*length),
+            LengthPercentage::Percentage(percentage) => CalcNode::Percentage(*percentage),
+            LengthPercentage::Calc(calc) => calc.root.clone(),
+        }
+    }
+}
+
+impl Animate for LengthPercentage {
+    ///Values that do not share a unit are interpolated as a `calc()`, like `10px` and `50%` halfway being `calc(5px + 25%)`.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        match (self, to) {
+            (LengthPercentage::Length(from), LengthPercentage::Length(to))
+                if from.unit == to.unit =>
+            {
+                from.animate(to, progress).map(LengthPercentage::Length)
+            }
+            (LengthPercentage::Percentage(from), LengthPercentage::Percentage(to)) => {
+                Some(LengthPercentage::Percentage(lerp(*from, *to, progress)))
+            }
+            (from, to) => Some(LengthPercentage::from_calc(Calc::mix(
+                from.to_calc_node(),
+                to.to_calc_node(),
+                progress,
+                CalcCategory::LengthPercentage,
+            ))),
+        }
+    }
+}
+
+impl Animate for LengthPercentageOrAuto {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        match (self, to) {
+            (
+                LengthPercentageOrAuto::LengthPercentage(from),
+                LengthPercentageOrAuto::LengthPercentage(to),
+            ) => from
+                .animate(to, progress)
+                .map(LengthPercentageOrAuto::LengthPercentage),
+            _ => None,
+        }
+    }
+}
+
+impl Animate for LengthPercentageOrNone {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        match (self, to) {
+            (
+                LengthPercentageOrNone::LengthPercentage(from),
+                LengthPercentageOrNone::LengthPercentage(to),
+            ) => from
+                .animate(to, progress)
+                .map(LengthPercentageOrNone::LengthPercentage),
+            _ => None,
+        }
+    }
+}
+
+impl Animate for LineWidth {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        self.0.animate(&to.0, progress).map(LineWidth)
+    }
+}
+
+impl Animate for CornerRadius {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some(CornerRadius {
+            horizontal: self.horizontal.animate(&to.horizontal, progress)?,
+            vertical: self.vertical.animate(&to.vertical, progress)?,
+        })
+    }
+}
+
+impl Animate for BoxPosition {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some(BoxPosition {
+            x: self.x.animate(&to.x, progress)?,
+            y: self.y.animate(&to.y, progress)?,
+        })
+    }
+}
+
+impl Animate for TransformOrigin {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some(TransformOrigin {
+            position: self.position.animate(&to.position, progress)?,
+            z: self.z.animate(&to.z, progress)?,
+        })
+    }
+}
+
+impl Animate for TransformFunction {
+    ///Functions of the same kind interpolate their arguments, see https://www.w3.org/TR/css-transforms-2/#interpolation-of-transform-functions
+    ///Rotations around different axes and `perspective(none)` only interpolate as part of a matrix, which is left to [`TransformList`].
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let lerp = |from: &f32, to: &f32| lerp(*from, *to, progress);
+        Some(match (self, to) {
+            (
+                TransformFunction::Translate(x1, y1, z1),
+                TransformFunction::Translate(x2, y2, z2),
+            ) => TransformFunction::Translate(
+                x1.animate(x2, progress)?,
+                y1.animate(y2, progress)?,
+                z1.animate(z2, progress)?,
+            ),
+            (TransformFunction::Scale(x1, y1, z1), TransformFunction::Scale(x2, y2, z2)) => {
+                TransformFunction::Scale(lerp(x1, x2), lerp(y1, y2), lerp(z1, z2))
+            }
+            (
+                TransformFunction::Rotate(axis1, angle1),
+                TransformFunction::Rotate(axis2, angle2),
+            ) => {
+                let normalize = |axis: &[f32; 3]| {
+                    let length = axis.iter().map(|v| v * v).sum::<f32>().sqrt();
+                    axis.map(|v| v / length)
+                };
+                // A rotation by 0 is the same around any axis.
+                let axis = match (*angle1, *angle2) {
+                    (_, 0.0) => *axis1,
+                    (0.0, _) => *axis2,
+                    _ if normalize(axis1) == normalize(axis2) => *axis1,
+                    _ => return None,
+                };
+                TransformFunction::Rotate(axis, lerp(angle1, angle2))
+            }
+            (TransformFunction::Skew(x1, y1), TransformFunction::Skew(x2, y2)) => {
+                TransformFunction::Skew(lerp(x1, x2), lerp(y1, y2))
+            }
+            (TransformFunction::Perspective(None), TransformFunction::Perspective(None)) => {
+                TransformFunction::Perspective(None)
+            }
+            (
+                TransformFunction::Perspective(Some(from)),
+                TransformFunction::Perspective(Some(to)),
+            ) => TransformFunction::Perspective(Some(from.animate(to, progress)?)),
+            (TransformFunction::Matrix(from), TransformFunction::Matrix(to)) => {
+                TransformFunction::Matrix(interpolate_matrix(*from, *to, progress))
+            }
+            (TransformFunction::Matrix3d(from), TransformFunction::Matrix3d(to)) => {
+                let from = Matrix(*from).to_2d()?;
+                let to = Matrix(*to).to_2d()?;
+                TransformFunction::Matrix3d(
+                    Matrix::from_2d(interpolate_matrix(from, to, progress)).0,
+                )
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl Animate for TransformList {
+    ///Lists whose functions are of the same kinds interpolate function by function, the shorter one padded with functions that do nothing,
+    ///like https://www.w3.org/TR/css-transforms-2/#interpolation-of-transforms asks for. Other lists interpolate as a `matrix()`
+    ///when both are 2D and only have lengths in pixels, since anything else depends on layout. They are discrete otherwise.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let functions: Option<Vec<TransformFunction>> = (0..self.0.len().max(to.0.len()))
+            .map(|i| match (self.0.get(i), to.0.get(i)) {
+                (Some(from), Some(to)) => from.animate(to, progress),
+                (Some(from), None) => from.animate(&from.identity(), progress),
+                (None, Some(to)) => to.identity().animate(to, progress),
+                (None, None) => None,
+            })
+            .collect();
+        if let Some(functions) = functions {
+            return Some(TransformList(functions));
+        }
+        let from = absolute_matrix(self)?;
+        let to = absolute_matrix(to)?;
+        Some(TransformList(vec![TransformFunction::Matrix(
+            interpolate_matrix(from, to, progress),
+        )]))
+    }
+}
+
+///The `matrix()` of a transform list that does not depend on layout, or `None` if it does or is 3D.
+fn absolute_matrix(list: &TransformList) -> Option<[f32; 6]> {
+    let px = |length: &Length| length.unit == LengthUnit::Px;
+    let absolute = list.0.iter().all(|function| match function {
+        TransformFunction::Translate(
+            LengthPercentage::Length(x),
+            LengthPercentage::Length(y),
+            z,
+        ) => px(x) && px(y) && px(z),
+        TransformFunction::Translate(..) => false,
+        TransformFunction::Perspective(Some(distance)) => px(distance),
+        _ => true,
+    });
+    if !absolute {
+        return None;
+    }
+    list.to_matrix(&LengthContext::new(0.0, 0.0), 0.0, 0.0)
+        .to_2d()
+}
+
+///A 2D matrix taken apart into a translation, a rotation in degrees, a scale and what is left,
+///which is a skew, see https://www.w3.org/TR/css-transforms-1/#decomposing-a-2d-matrix
+struct DecomposedMatrix {
+    translate: [f32; 2],
+    angle: f32,
+    scale: [f32; 2],
+    ///The columns of what is left of the matrix once it is unrotated and unscaled.
+    rest: [f32; 4],
+}
+
+impl DecomposedMatrix {
+    ///Decomposes `matrix(a, b, c, d, e, f)`, which is `translate() rotate() rest scale()`.
+    fn new([a, b, c, d, e, f]: [f32; 6]) -> Self {
+        let mut scale = [a.hypot(b), c.hypot(d)];
+        // A negative determinant means one of the axes is flipped.
+        if a * d - b * c < 0.0 {
+            match a < d {
+                true => scale[0] = -scale[0],
+                false => scale[1] = -scale[1],
+            }
+        }
+        let normalize = |x: f32, y: f32, scale: f32| match scale {
+            0.0 => [x, y],
+            scale => [x / scale, y / scale],
+        };
+        let [x0, y0] = normalize(a, b, scale[0]);
+        let [x1, y1] = normalize(c, d, scale[1]);
+        let angle = y0.atan2(x0);
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            translate: [e, f],
+            angle: angle.to_degrees(),
+            scale,
+            rest: [
+                cos * x0 + sin * y0,
+                cos * y0 - sin * x0,
+                cos * x1 + sin * y1,
+                cos * y1 - sin * x1,
+            ],
+        }
+    }
+
+    fn recompose(&self) -> [f32; 6] {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let [x0, y0, x1, y1] = self.rest;
+        let [scale_x, scale_y] = self.scale;
+        let [e, f] = self.translate;
+        [
+            (cos * x0 - sin * y0) * scale_x,
+            (sin * x0 + cos * y0) * scale_x,
+            (cos * x1 - sin * y1) * scale_y,
+            (sin * x1 + cos * y1) * scale_y,
+            e,
+            f,
+        ]
+    }
+}
+
+///Interpolates two `matrix()` values, see https://www.w3.org/TR/css-transforms-1/#interpolation-of-decomposed-2d-matrix-values
+fn interpolate_matrix(from: [f32; 6], to: [f32; 6], progress: f32) -> [f32; 6] {
+    let mut from = DecomposedMatrix::new(from);
+    let mut to = DecomposedMatrix::new(to);
+    // Axes flipped differently are turned into a rotation by 180deg.
+    if (from.scale[0] < 0.0 && to.scale[1] < 0.0) || (from.scale[1] < 0.0 && to.scale[0] < 0.0) {
+        from.scale = from.scale.map(|v| -v);
+        from.angle += if from.angle < 0.0 { 180.0 } else { -180.0 };
+    }
+    // Don't rotate the long way around.
+    for angle in [&mut from.angle, &mut to.angle] {
+        if *angle == 0.0 {
+            *angle = 360.0;
+        }
+    }
+    if (from.angle - to.angle).abs() > 180.0 {
+        match from.angle > to.angle {
+            true => from.angle -= 360.0,
+            false => to.angle -= 360.0,
+        }
+    }
+    let lerp = |from: f32, to: f32| lerp(from, to, progress);
+    DecomposedMatrix {
+        translate: [0, 1].map(|i| lerp(from.translate[i], to.translate[i])),
+        angle: lerp(from.angle, to.angle) % 360.0,
+        scale: [0, 1].map(|i| lerp(from.scale[i], to.scale[i])),
+        rest: [0, 1, 2, 3].map(|i| lerp(from.rest[i], to.rest[i])),
+    }
+    .recompose()
+}
+
+impl Animate for Shadow {
+    ///Inset shadows only interpolate with inset shadows.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        if self.inset != to.inset {
+            return None;
+        }
+        let blur = self.blur.animate(&to.blur, progress)?;
+        Some(Shadow {
+            color: self.color.animate(&to.color, progress)?,
+            offset_x: self.offset_x.animate(&to.offset_x, progress)?,
+            offset_y: self.offset_y.animate(&to.offset_y, progress)?,
+            blur: Length {
+                value: blur.value.max(0.0),
+                ..blur
+            },
+            spread: self.spread.animate(&to.spread, progress)?,
+            inset: self.inset,
+        })
+    }
+}
+
+impl Animate for ShadowList {
+    ///The shorter list is padded with transparent shadows, see https://www.w3.org/TR/css-backgrounds-3/#box-shadow
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        (0..self.0.len().max(to.0.len()))
+            .map(|i| match (self.0.get(i), to.0.get(i)) {
+                (Some(from), Some(to)) => from.animate(to, progress),
+                (Some(from), None) => from.animate(&Shadow::transparent(from.inset), progress),
+                (None, Some(to)) => Shadow::transparent(to.inset).animate(to, progress),
+                (None, None) => None,
+            })
+            .collect::<Option<_>>()
+            .map(ShadowList)
+    }
+}
+
+impl Animate for FilterFunction {
+    ///Only functions of the same kind interpolate, and `url()` does not.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let amount = |from: &f32, to: &f32| lerp(*from, *to, progress).max(0.0);
+        let clamped = |from: &f32, to: &f32| amount(from, to).min(1.0);
+        Some(match (self, to) {
+            (FilterFunction::Blur(from), FilterFunction::Blur(to)) => {
+                let blur = from.animate(to, progress)?;
+                FilterFunction::Blur(Length {
+                    value: blur.value.max(0.0),
+                    ..blur
+                })
+            }
+            (FilterFunction::Brightness(from), FilterFunction::Brightness(to)) => {
+                FilterFunction::Brightness(amount(from, to))
+            }
+            (FilterFunction::Contrast(from), FilterFunction::Contrast(to)) => {
+                FilterFunction::Contrast(amount(from, to))
+            }
+            (FilterFunction::Grayscale(from), FilterFunction::Grayscale(to)) => {
+                FilterFunction::Grayscale(clamped(from, to))
+            }
+            (FilterFunction::HueRotate(from), FilterFunction::HueRotate(to)) => {
+                FilterFunction::HueRotate(lerp(*from, *to, progress))
+            }
+            (FilterFunction::Invert(from), FilterFunction::Invert(to)) => {
+                FilterFunction::Invert(clamped(from, to))
+            }
+            (FilterFunction::Opacity(from), FilterFunction::Opacity(to)) => {
+                FilterFunction::Opacity(clamped(from, to))
+            }
+            (FilterFunction::Saturate(from), FilterFunction::Saturate(to)) => {
+                FilterFunction::Saturate(amount(from, to))
+            }
+            (FilterFunction::Sepia(from), FilterFunction::Sepia(to)) => {
+                FilterFunction::Sepia(clamped(from, to))
+            }
+            (FilterFunction::DropShadow(from), FilterFunction::DropShadow(to)) => {
+                FilterFunction::DropShadow(from.animate(to, progress)?)
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl Animate for FilterList {
+    ///Lists whose functions are of the same kinds interpolate function by function, the shorter one padded with the
+    ///functions that do nothing, see https://www.w3.org/TR/filter-effects-1/#interpolation-of-filters
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        (0..self.0.len().max(to.0.len()))
+            .map(|i| match (self.0.get(i), to.0.get(i)) {
+                (Some(from), Some(to)) => from.animate(to, progress),
+                (Some(from), None) => from.animate(&from.initial()?, progress),
+                (None, Some(to)) => to.initial()?.animate(to, progress),
+                (None, None) => None,
+            })
+            .collect::<Option<_>>()
+            .map(FilterList)
+    }
+}
+
+impl Animate for FontWeight {
+    ///`bolder` and `lighter` depend on the parent, so they do not interpolate.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        match (self, to) {
+            (FontWeight::Absolute(from), FontWeight::Absolute(to)) => Some(FontWeight::Absolute(
+                lerp(*from, *to, progress).clamp(1.0, 1000.0),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Animate for FontSize {
+    ///The absolute size keywords interpolate as their size in pixels. `larger` and `smaller` depend on the parent, so they do not.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let length = |size: &FontSize| match size {
+            FontSize::Absolute(size) => Some(LengthPercentage::Length(Length::px(size.to_px()))),
+            FontSize::LengthPercentage(value) => Some(value.clone()),
+            FontSize::Larger | FontSize::Smaller => None,
+        };
+        length(self)?
+            .animate(&length(to)?, progress)
+            .map(FontSize::LengthPercentage)
+    }
+}
+
+impl Animate for Color {
+    ///Colors are interpolated in Oklab like https://www.w3.org/TR/css-color-4/#interpolation asks for.
+    ///`currentcolor` is only known once the color is used, so colors with it become a `color-mix()`.
+    ///Colors can not overshoot, so `progress` is clamped to `0..=1`.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let progress = progress.clamp(0.0, 1.0);
+        Some(match (self, to) {
+            (Color::Absolute(from), Color::Absolute(to)) => Color::Absolute(interpolate(
+                ColorSpace::Oklab,
+                HueInterpolation::Shorter,
+                from,
+                to,
+                progress,
+            )),
+            (from, to) => Color::Mix(Box::new(ColorMix {
+                space: ColorSpace::Oklab,
+                hue: HueInterpolation::Shorter,
+                first: from.clone(),
+                first_percentage: Some(1.0 - progress),
+                second: to.clone(),
+                second_percentage: Some(progress),
+            })),
+        })
+    }
+}
+
+///How the values of a property are interpolated, which is the typed value they are interpolated as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnimationType {
+    ///The value flips from the start to the end halfway through, and transitions do not start at all.
+    #[default]
+    Discrete,
+    Number,
+    Integer,
+    ZIndex,
+    AlphaValue,
+    LengthPercentage,
+    LengthPercentageOrAuto,
+    LengthPercentageOrNone,
+    LineWidth,
+    CornerRadius,
+    Color,
+    FontSize,
+    FontWeight,
+    TransformList,
+    TransformOrigin,
+    ShadowList,
+    FilterList,
+}
+
+impl AnimationType {
+    ///Interpolates between two computed values, returning `None` if they do not interpolate.
+    pub fn interpolate(
+        self,
+        from: &[ComponentValue],
+        to: &[ComponentValue],
+        progress: f32,
+    ) -> Option<Vec<ComponentValue<'static>>> {
+        match self {
+            AnimationType::Discrete => None,
+            AnimationType::Number => animate_as::<Number>(from, to, progress),
+            AnimationType::Integer => animate_as::<Integer>(from, to, progress),
+            AnimationType::ZIndex => animate_as::<ZIndex>(from, to, progress),
+            AnimationType::AlphaValue => animate_as::<AlphaValue>(from, to, progress),
+            AnimationType::LengthPercentage => animate_as::<LengthPercentage>(from, to, progress),
+            AnimationType::LengthPercentageOrAuto => {
+                animate_as::<LengthPercentageOrAuto>(from, to, progress)
+            }
+            AnimationType::LengthPercentageOrNone => {
+                animate_as::<LengthPercentageOrNone>(from, to, progress)
+            }
+            AnimationType::LineWidth => animate_as::<LineWidth>(from, to, progress),
+            AnimationType::CornerRadius => animate_as::<CornerRadius>(from, to, progress),
+            AnimationType::Color => animate_as::<Color>(from, to, progress),
+            AnimationType::FontSize => animate_as::<FontSize>(from, to, progress),
+            AnimationType::FontWeight => animate_as::<FontWeight>(from, to, progress),
+            AnimationType::TransformList => animate_as::<TransformList>(from, to, progress),
+            AnimationType::TransformOrigin => animate_as::<TransformOrigin>(from, to, progress),
+            AnimationType::ShadowList => animate_as::<ShadowList>(from, to, progress),
+            AnimationType::FilterList => animate_as::<FilterList>(from, to, progress),
+        }
+    }
+}
+
+///Parses both values as `T`, interpolates them and writes the result back as component values.
+fn animate_as<T: Parse + ToCss + Animate>(
+    from: &[ComponentValue],
+    to: &[ComponentValue],
+    progress: f32,
+) -> Option<Vec<ComponentValue<'static>>> {
+    let from = parse_value::<T>(from).ok()?;
+    let to = parse_value::<T>(to).ok()?;
+    let text = from.animate(&to, progress)?.to_css_string();
+    Some(
+        Parser::new(&text)
+            .parse_list_of_component_values()
+            .into_iter()
+            .map(ComponentValue::into_owned)
+            .collect(),
+    )
+}
+
+///Whether the values of a property interpolate, as opposed to flipping halfway through. Transitions only start for those.
+pub fn interpolates(name: &str, from: &[ComponentValue], to: &[ComponentValue]) -> bool {
+    properties::lookup(name).is_some_and(|v| v.animation_type.interpolate(from, to, 0.5).is_some())
+}
+
+///Interpolates between two computed values of a property.
+///Values that do not interpolate are discrete, being `from` before the halfway point and `to` from there on.
+pub fn interpolate_property(
+    name: &str,
+    from: &[ComponentValue],
+    to: &[ComponentValue],
+    progress: f32,
+) -> Vec<ComponentValue<'static>> {
+    let animation_type = properties::lookup(name)
+        .map(|v| v.animation_type)
+        .unwrap_or_default();
+    match animation_type.interpolate(from, to, progress) {
+        Some(value) => value,
+        None => {
+            let value = if progress < 0.5 { from } else { to };
+            value
+                .iter()
+                .cloned()
+                .map(ComponentValue::into_owned)
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! The `@keyframes` rule of https://www.w3.org/TR/css-animations-1/#keyframes, which animations take their values from.
+use crate::CSSToken;
+use crate::animation::easing::EasingFunction;
+use crate::animation::interpolate::interpolate_property;
+use crate::cascade::ComputedStyle;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{AtRule, ComponentValue, Declaration, DeclarationOrAtRule, Parser, Rule};
+use crate::properties::custom::{contains_var, is_custom_ident, substitute_var};
+use crate::properties::{self, shorthands};
+use crate::values::{ValueParser, parse_value, parse_value_with};
+use std::collections::BTreeMap;
+
+///A `@keyframes name { ... }` rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes<'a> {
+    pub name: String,
+    ///The keyframes in the order they appear, with one keyframe for every selector of a rule like `from, 50% { ... }`.
+    pub keyframes: Vec<Keyframe<'a>>,
+}
+
+///The declarations an animation reaches at an offset of its keyframes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe<'a> {
+    ///Between 0 for `from` and 1 for `to`.
+    pub offset: f32,
+    ///The validated declarations, with shorthands expanded unless they have a `var()`.
+    pub declarations: Vec<Declaration<'a>>,
+    ///The `animation-timing-function` of the keyframe, which eases the way to the next keyframe.
+    pub easing: Option<EasingFunction>,
+}
+
+impl<'a> Keyframes<'a> {
+    ///Parses a `@keyframes` rule. Invalid keyframes and declarations are dropped and reported in `diagnostics`,
+    ///and so are `!important` declarations and the `animation-*` properties other than `animation-timing-function`, which are ignored in keyframes.
+    pub fn parse(
+        rule: &AtRule<'a>,
+        diagnostics: &mut Vec<CssDiagnostic>,
+    ) -> Result<Self, CssDiagnostic> {
+        let error = |message: &str| {
+            CssDiagnostic::new(CssDiagnosticKind::InvalidAtRule, rule.span, message)
+        };
+        let name = parse_value_with(&rule.prelude, |input| {
+            let value = input.consume()?;
+            match value.token() {
+                Some(CSSToken::IdentToken { value })
+                    if is_custom_ident(value) && !value.eq_ignore_ascii_case("none") =>
+                {
+                    Ok(value.to_string())
+                }
+                Some(CSSToken::StringToken { string }) => Ok(string.to_string()),
+                _ => Err(error("`@keyframes` needs a name like `fade-in`")),
+            }
+        })
+        .map_err(|_| error("`@keyframes` needs a name like `fade-in`"))?;
+        let Some(block) = &rule.block else {
+            return Err(error("`@keyframes` needs a block of keyframes"));
+        };
+
+        let mut keyframes = vec![];
+        let mut parser = Parser::from_component_values(&block.value);
+        let rules = parser.parse_list_of_rules();
+        diagnostics.append(&mut parser.take_diagnostics());
+        for rule in rules {
+            let rule = match rule {
+                Rule::QualifiedRule(rule) => rule,
+                Rule::AtRule(rule) => {
+                    diagnostics.push(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidAtRule,
+                        rule.span,
+                        format!("`@{}` is not allowed in `@keyframes`", rule.name),
+                    ));
+                    continue;
+                }
+            };
+            let offsets = match parse_value_with(&rule.prelude, |input| {
+                input.parse_comma_separated(parse_keyframe_selector)
+            }) {
+                Ok(offsets) => offsets,
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    continue;
+                }
+            };
+            let mut parser = Parser::from_component_values(&rule.block.value);
+            let mut easing = None;
+            let mut declarations = vec![];
+            for item in parser.parse_list_of_declarations() {
+                let DeclarationOrAtRule::Declaration(declaration) = item else {
+                    continue;
+                };
+                let name = declaration.name.to_ascii_lowercase();
+                if declaration.important {
+                    continue;
+                }
+                if name == "animation-timing-function" {
+                    match parse_value::<EasingFunction>(&declaration.value) {
+                        Ok(value) => easing = Some(value),
+                        Err(diagnostic) => diagnostics.push(diagnostic),
+                    }
+                    continue;
+                }
+                if name.starts_with("animation") {
+                    continue;
+                }
+                match properties::validate_declaration(declaration) {
+                    Ok(valid) => declarations.extend(valid),
+                    Err(diagnostic) => diagnostics.push(diagnostic),
+                }
+            }
+            diagnostics.append(&mut parser.take_diagnostics());
+            for offset in offsets {
+                keyframes.push(Keyframe {
+                    offset,
+                    declarations: declarations.clone(),
+                    easing: easing.clone(),
+                });
+            }
+        }
+        Ok(Keyframes { name, keyframes })
+    }
+
+    ///Resolves the keyframes for an element, substituting `var()` with the custom properties of its `style`.
+    ///The values of a property are sorted by their offset, and at the same offset the later keyframe wins.
+    pub fn resolve(&self, style: &ComputedStyle) -> ResolvedKeyframes {
+        let mut keyframes: Vec<&Keyframe> = self.keyframes.iter().collect();
+        keyframes.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        let mut properties: BTreeMap<&'static str, Vec<PropertyKeyframe>> = BTreeMap::new();
+        for keyframe in keyframes {
+            for (name, value) in keyframe
+                .declarations
+                .iter()
+                .flat_map(|v| substitute_keyframe_declaration(v, style))
+            {
+                let stops = properties.entry(name).or_default();
+                if stops.last().is_some_and(|v| v.offset == keyframe.offset) {
+                    stops.pop();
+                }
+                stops.push(PropertyKeyframe {
+                    offset: keyframe.offset,
+                    value,
+                    easing: keyframe.easing.clone(),
+                });
+            }
+        }
+        ResolvedKeyframes { properties }
+    }
+}
+
+///Parses `from`, `to` or a percentage in `0%..=100%` into an offset.
+fn parse_keyframe_selector(input: &mut ValueParser) -> Result<f32, CssDiagnostic> {
+    if input.try_parse(|v| v.expect_keyword("from")).is_ok() {
+        return Ok(0.0);
+    }
+    if input.try_parse(|v| v.expect_keyword("to")).is_ok() {
+        return Ok(1.0);
+    }
+    let value = input.consume()?;
+    match value.token() {
+        Some(CSSToken::PercentageToken {
+            value: percentage, ..
+        }) if (0.0..=100.0).contains(percentage) => Ok(*percentage as f32 / 100.0),
+        _ => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidSelector,
+            value.span(),
+            format!(
+                "expected `from`, `to` or a percentage between 0% and 100%, found `{}`",
+                crate::serializer::serialize_component_values(std::slice::from_ref(value))
+            ),
+        )),
+    }
+}
+
+///Substitutes the `var()` of a keyframe declaration and expands it if it is a shorthand,
+///returning the longhands it sets. Declarations that are invalid once substituted set nothing.
+fn substitute_keyframe_declaration(
+    declaration: &Declaration,
+    style: &ComputedStyle,
+) -> Vec<(&'static str, Vec<ComponentValue<'static>>)> {
+    let owned =
+        |value: Vec<ComponentValue>| value.into_iter().map(ComponentValue::into_owned).collect();
+    let value = match contains_var(&declaration.value) {
+        true => {
+            let lookup = &mut |name: &str| style.get(name).map(<[_]>::to_vec);
+            match substitute_var(&declaration.value, lookup) {
+                Some(value) => value,
+                None => return vec![],
+            }
+        }
+        false => declaration.value.clone(),
+    };
+    if let Some(property) = properties::lookup(&declaration.name) {
+        return match (property.validate)(&value) {
+            Ok(()) => vec![(property.name, owned(value))],
+            Err(_) => vec![],
+        };
+    }
+    let Some(shorthand) = shorthands::lookup(&declaration.name) else {
+        return vec![];
+    };
+    let declaration = Declaration {
+        value,
+        ..declaration.clone()
+    };
+    shorthand
+        .expand(&declaration)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| Some((properties::lookup(&v.name)?.name, owned(v.value))))
+        .collect()
+}
+
+///The value a property reaches at an offset of the keyframes of an animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyKeyframe {
+    pub offset: f32,
+    pub value: Vec<ComponentValue<'static>>,
+    pub easing: Option<EasingFunction>,
+}
+
+///The keyframes of an animation resolved for an element, by property.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResolvedKeyframes {
+    pub properties: BTreeMap<&'static str, Vec<PropertyKeyframe>>,
+}
+
+impl ResolvedKeyframes {
+    ///Writes the values of the animated properties at `progress` into `style`, see [`crate::animation::AnimationDefinition::progress`].
+    ///A property without a keyframe at 0% or 100% animates from or to the value it has in `style`,
+    ///and keyframes without an easing of their own use `easing`.
+    pub fn apply(&self, style: &mut ComputedStyle, progress: f32, easing: &EasingFunction) {
+        for (&name, stops) in &self.properties {
+            let underlying = style.get(name).unwrap_or_default();
+            let implicit = |offset: f32| PropertyKeyframe {
+                offset,
+                value: underlying
+                    .iter()
+                    .cloned()
+                    .map(ComponentValue::into_owned)
+                    .collect(),
+                easing: None,
+            };
+            let first = match stops.first() {
+                Some(v) if v.offset == 0.0 => None,
+                _ => Some(implicit(0.0)),
+            };
+            let last = match stops.last() {
+                Some(v) if v.offset == 1.0 => None,
+                _ => Some(implicit(1.0)),
+            };
+            let stops: Vec<&PropertyKeyframe> =
+                first.iter().chain(stops).chain(last.iter()).collect();
+            // The interval that `progress` is in, going on past the first and last keyframe for easings that overshoot.
+            let start = stops
+                .iter()
+                .rposition(|v| v.offset <= progress)
+                .unwrap_or(0)
+                .min(stops.len() - 2);
+            let (from, to) = (stops[start], stops[start + 1]);
+            let local = match to.offset > from.offset {
+                true => (progress - from.offset) / (to.offset - from.offset),
+                false => 1.0,
+            };
+            let eased = from.easing.as_ref().unwrap_or(easing).apply(local);
+            let value = interpolate_property(name, &from.value, &to.value, eased);
+            style.set(name, value);
+        }
+    }
+}
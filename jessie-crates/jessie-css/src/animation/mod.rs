@@ -0,0 +1,381 @@
+//! CSS transitions and animations, see https://www.w3.org/TR/css-transitions-1/ and https://www.w3.org/TR/css-animations-1/
+//! The `transition-*` and `animation-*` properties are read from a computed style into [`TransitionDefinition`]s and [`AnimationDefinition`]s,
+//! and [`timeline::ElementAnimations`] runs them over time, writing the animated values back into the style of the element.
+use crate::CSSToken;
+use crate::cascade::ComputedStyle;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::properties::custom::is_custom_ident;
+use crate::serializer::{serialize_identifier, serialize_string};
+use crate::values::{
+    CommaSeparated, Parse, ToCss, ValueParser, expect_non_negative, keyword_enum, unexpected,
+    write_number,
+};
+use easing::EasingFunction;
+pub mod easing;
+pub mod interpolate;
+pub mod keyframes;
+pub mod timeline;
+
+///A <time>, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Time(pub f32);
+
+impl Time {
+    ///Parses a time that is not negative, like the value of `transition-duration`.
+    pub fn parse_non_negative(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let time = Time::parse(input)?;
+        expect_non_negative(time.0, span)?;
+        Ok(time)
+    }
+}
+
+impl Parse for Time {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        let Some(CSSToken::DimensionToken {
+            value: time, unit, ..
+        }) = value.token()
+        else {
+            return Err(unexpected(value, "a time like `200ms`"));
+        };
+        let time = *time as f32;
+        match unit.to_ascii_lowercase().as_str() {
+            "s" => Ok(Time(time)),
+            "ms" => Ok(Time(time / 1000.0)),
+            _ => Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                value.span(),
+                format!("`{unit}` is not a time unit, expected `s` or `ms`"),
+            )),
+        }
+    }
+}
+
+impl ToCss for Time {
+    fn to_css(&self, out: &mut String) {
+        write_number(self.0, out);
+        out.push('s');
+    }
+}
+
+///The `animation-iteration-count` property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IterationCount {
+    Infinite,
+    Number(f32),
+}
+
+impl Parse for IterationCount {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("infinite")).is_ok() {
+            return Ok(IterationCount::Infinite);
+        }
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let count = input.expect_number()?;
+        expect_non_negative(count, span)?;
+        Ok(IterationCount::Number(count))
+    }
+}
+
+impl ToCss for IterationCount {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            IterationCount::Infinite => out.push_str("infinite"),
+            IterationCount::Number(count) => write_number(*count, out),
+        }
+    }
+}
+
+keyword_enum! {
+    ///The `animation-direction` property, which iterations play backwards.
+    pub enum AnimationDirection {
+        Normal = "normal",
+        Reverse = "reverse",
+        Alternate = "alternate",
+        AlternateReverse = "alternate-reverse",
+    }
+}
+
+keyword_enum! {
+    ///The `animation-fill-mode` property, whether an animation applies before it starts and after it ends.
+    pub enum FillMode {
+        None = "none",
+        Forwards = "forwards",
+        Backwards = "backwards",
+        Both = "both",
+    }
+}
+
+keyword_enum! {
+    pub enum PlayState {
+        Running = "running",
+        Paused = "paused",
+    }
+}
+
+///An item of the `transition-property` property.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TransitionProperty {
+    ///Only allowed on its own, for no transitions at all.
+    None,
+    All,
+    ///A property by its name, which does not have to be one the engine knows about.
+    Property(String),
+}
+
+impl TransitionProperty {
+    ///Whether the item is `all` or names the property.
+    pub fn applies_to(&self, property: &str) -> bool {
+        match self {
+            TransitionProperty::None => false,
+            TransitionProperty::All => true,
+            TransitionProperty::Property(name) => name.eq_ignore_ascii_case(property),
+        }
+    }
+}
+
+impl Parse for TransitionProperty {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let (ident, span) = input.expect_ident()?;
+        if ident.eq_ignore_ascii_case("none") {
+            return Ok(TransitionProperty::None);
+        }
+        if ident.eq_ignore_ascii_case("all") {
+            return Ok(TransitionProperty::All);
+        }
+        match is_custom_ident(ident) {
+            true => Ok(TransitionProperty::Property(ident.to_ascii_lowercase())),
+            false => Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                span,
+                format!("`{ident}` can not be the name of a property"),
+            )),
+        }
+    }
+}
+
+impl ToCss for TransitionProperty {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            TransitionProperty::None => out.push_str("none"),
+            TransitionProperty::All => out.push_str("all"),
+            TransitionProperty::Property(name) => serialize_identifier(name, out),
+        }
+    }
+}
+
+///An item of the `animation-name` property, the name of the `@keyframes` rule it runs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnimationName {
+    ///No animation, which keeps the place of the item in the other `animation-*` lists.
+    None,
+    Name(String),
+}
+
+impl Parse for AnimationName {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        match value.token() {
+            Some(CSSToken::IdentToken { value: ident }) if ident.eq_ignore_ascii_case("none") => {
+                Ok(AnimationName::None)
+            }
+            Some(CSSToken::IdentToken { value: ident }) if is_custom_ident(ident) => {
+                Ok(AnimationName::Name(ident.to_string()))
+            }
+            Some(CSSToken::StringToken { string }) => Ok(AnimationName::Name(string.to_string())),
+            _ => Err(unexpected(value, "the name of a `@keyframes` rule")),
+        }
+    }
+}
+
+impl ToCss for AnimationName {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            AnimationName::None => out.push_str("none"),
+            AnimationName::Name(name) if is_custom_ident(name) && name != "none" => {
+                serialize_identifier(name, out)
+            }
+            AnimationName::Name(name) => serialize_string(name, out),
+        }
+    }
+}
+
+///Reads a comma separated list property, or the default of its item if it does not parse.
+fn list<T: Parse + Clone>(style: &ComputedStyle, name: &str, default: T) -> Vec<T> {
+    style
+        .get_as::<CommaSeparated<T>>(name)
+        .map(|v| v.0)
+        .unwrap_or_else(|| vec![default])
+}
+
+///Returns the `i`th item of a list, which repeats if it is shorter than the list of names it goes with.
+fn cycle<T: Clone>(list: &[T], i: usize) -> T {
+    list[i % list.len()].clone()
+}
+
+///A transition of one property, or all of them, from an item of the `transition-*` lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionDefinition {
+    pub property: TransitionProperty,
+    pub duration: Time,
+    pub easing: EasingFunction,
+    pub delay: Time,
+}
+
+impl TransitionDefinition {
+    ///Reads the transitions of an element, one for every item of `transition-property`.
+    pub fn from_style(style: &ComputedStyle) -> Vec<Self> {
+        let properties = list(style, "transition-property", TransitionProperty::All);
+        let durations = list(style, "transition-duration", Time(0.0));
+        let easings = list(style, "transition-timing-function", EasingFunction::EASE);
+        let delays = list(style, "transition-delay", Time(0.0));
+        properties
+            .into_iter()
+            .enumerate()
+            .filter(|(_, property)| *property != TransitionProperty::None)
+            .map(|(i, property)| TransitionDefinition {
+                property,
+                duration: cycle(&durations, i),
+                easing: cycle(&easings, i),
+                delay: cycle(&delays, i),
+            })
+            .collect()
+    }
+
+    ///Finds the transition of a property, the last item that applies to it winning.
+    pub fn find<'d>(transitions: &'d [Self], property: &str) -> Option<&'d Self> {
+        transitions
+            .iter()
+            .rev()
+            .find(|v| v.property.applies_to(property))
+    }
+
+    ///The sum of the duration, which can not be negative, and the delay, which can.
+    ///Only a transition with a combined duration above 0 starts.
+    pub fn combined_duration(&self) -> f32 {
+        self.duration.0.max(0.0) + self.delay.0
+    }
+}
+
+///An animation from an item of the `animation-*` lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationDefinition {
+    pub name: String,
+    pub duration: Time,
+    ///The easing of the keyframes that do not have an `animation-timing-function` of their own.
+    pub easing: EasingFunction,
+    pub delay: Time,
+    pub iteration_count: IterationCount,
+    pub direction: AnimationDirection,
+    pub fill_mode: FillMode,
+    pub play_state: PlayState,
+}
+
+///Where in its lifetime an animation is, see https://www.w3.org/TR/web-animations-1/#animation-effect-phases-and-states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationPhase {
+    ///The delay has not passed yet.
+    Before,
+    Active,
+    ///Every iteration has played.
+    After,
+}
+
+impl AnimationDefinition {
+    ///Reads the animations of an element, one for every item of `animation-name` that is not `none`.
+    pub fn from_style(style: &ComputedStyle) -> Vec<Self> {
+        let names = list(style, "animation-name", AnimationName::None);
+        let durations = list(style, "animation-duration", Time(0.0));
+        let easings = list(style, "animation-timing-function", EasingFunction::EASE);
+        let delays = list(style, "animation-delay", Time(0.0));
+        let counts = list(
+            style,
+            "animation-iteration-count",
+            IterationCount::Number(1.0),
+        );
+        let directions = list(style, "animation-direction", AnimationDirection::Normal);
+        let fill_modes = list(style, "animation-fill-mode", FillMode::None);
+        let play_states = list(style, "animation-play-state", PlayState::Running);
+        names
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, name)| match name {
+                AnimationName::None => None,
+                AnimationName::Name(name) => Some(AnimationDefinition {
+                    name,
+                    duration: cycle(&durations, i),
+                    easing: cycle(&easings, i),
+                    delay: cycle(&delays, i),
+                    iteration_count: cycle(&counts, i),
+                    direction: cycle(&directions, i),
+                    fill_mode: cycle(&fill_modes, i),
+                    play_state: cycle(&play_states, i),
+                }),
+            })
+            .collect()
+    }
+
+    ///The time all iterations take together.
+    pub fn active_duration(&self) -> f32 {
+        match (self.duration.0, self.iteration_count) {
+            (duration, _) if duration <= 0.0 => 0.0,
+            (_, IterationCount::Infinite) => f32::INFINITY,
+            (duration, IterationCount::Number(count)) => duration * count,
+        }
+    }
+
+    ///The phase of the animation `time` seconds after it started.
+    pub fn phase(&self, time: f32) -> AnimationPhase {
+        if time < self.delay.0 {
+            AnimationPhase::Before
+        } else if time >= self.delay.0 + self.active_duration() {
+            AnimationPhase::After
+        } else {
+            AnimationPhase::Active
+        }
+    }
+
+    ///Returns how far into the keyframes the animation is `time` seconds after it started, from 0 at `from` to 1 at `to`,
+    ///following the direction of the current iteration. Returns `None` when the animation does not apply,
+    ///which is before and after it runs unless its fill mode says otherwise.
+    pub fn progress(&self, time: f32) -> Option<f32> {
+        let phase = self.phase(time);
+        let count = match self.iteration_count {
+            IterationCount::Infinite => f32::INFINITY,
+            IterationCount::Number(count) => count,
+        };
+        let overall = match phase {
+            AnimationPhase::Before
+                if matches!(self.fill_mode, FillMode::Backwards | FillMode::Both) =>
+            {
+                0.0
+            }
+            AnimationPhase::Active => (time - self.delay.0) / self.duration.0,
+            AnimationPhase::After
+                if matches!(self.fill_mode, FillMode::Forwards | FillMode::Both) =>
+            {
+                count
+            }
+            _ => return None,
+        };
+        // An iteration that ends exactly when the animation does is at its end rather than at the start of the next one.
+        let (iteration, simple) = match overall {
+            overall if !overall.is_finite() => (0.0, 1.0),
+            overall
+                if phase == AnimationPhase::After && overall != 0.0 && overall.fract() == 0.0 =>
+            {
+                (overall - 1.0, 1.0)
+            }
+            overall => (overall.floor(), overall.fract()),
+        };
+        let even = iteration % 2.0 == 0.0;
+        let forwards = match self.direction {
+            AnimationDirection::Normal => true,
+            AnimationDirection::Reverse => false,
+            AnimationDirection::Alternate => even,
+            AnimationDirection::AlternateReverse => !even,
+        };
+        Some(if forwards { simple } else { 1.0 - simple })
+    }
+}
@@ -0,0 +1,269 @@
+//! The transitions and animations running on an element. Times are in seconds of whatever clock the caller uses,
+//! as an `f64` so a clock that started long ago does not lose precision.
+use crate::animation::easing::EasingFunction;
+use crate::animation::interpolate::{AnimationType, interpolate_property, interpolates};
+use crate::animation::keyframes::ResolvedKeyframes;
+use crate::animation::{AnimationDefinition, AnimationPhase, PlayState, TransitionDefinition};
+use crate::cascade::{Cascade, ComputedStyle};
+use crate::parser::ComponentValue;
+use crate::properties::PROPERTIES;
+use crate::serializer::serialize_component_values;
+use std::collections::HashMap;
+
+type Value = Vec<ComponentValue<'static>>;
+
+///Runs the transitions and animations of an element.
+///Every time the style of the element is computed, [`ElementAnimations::update`] starts and stops them,
+///and [`ElementAnimations::apply`] writes their values into the style.
+#[derive(Debug, Clone, Default)]
+pub struct ElementAnimations {
+    ///The values of the properties that can transition at the last update, without any transition or animation applied.
+    after_change: HashMap<&'static str, Value>,
+    transitions: Vec<RunningTransition>,
+    ///In the order of `animation-name`, later animations overriding earlier ones.
+    animations: Vec<RunningAnimation>,
+}
+
+#[derive(Debug, Clone)]
+struct RunningTransition {
+    property: &'static str,
+    from: Value,
+    to: Value,
+    ///When the transition started, before its delay.
+    start: f64,
+    delay: f32,
+    duration: f32,
+    easing: EasingFunction,
+    ///What a transition going back to where this one came from has to match, see https://www.w3.org/TR/css-transitions-1/#reversing
+    reversing_adjusted_start: Value,
+    reversing_shortening_factor: f32,
+}
+
+impl RunningTransition {
+    ///The progress of the transition at `now` after easing, from 0 at `from` to 1 at `to`.
+    fn output_progress(&self, now: f64) -> f32 {
+        let elapsed = (now - self.start) as f32 - self.delay;
+        let progress = match self.duration > 0.0 {
+            true => (elapsed / self.duration).clamp(0.0, 1.0),
+            false => (elapsed >= 0.0) as u8 as f32,
+        };
+        self.easing.apply(progress)
+    }
+
+    fn value(&self, now: f64) -> Value {
+        interpolate_property(
+            self.property,
+            &self.from,
+            &self.to,
+            self.output_progress(now),
+        )
+    }
+
+    fn is_finished(&self, now: f64) -> bool {
+        (now - self.start) as f32 >= self.delay + self.duration
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RunningAnimation {
+    definition: AnimationDefinition,
+    ///Empty if there was no `@keyframes` rule with the name of the animation when it started.
+    keyframes: ResolvedKeyframes,
+    start: f64,
+    ///When the animation was paused, if it is.
+    paused_at: Option<f64>,
+}
+
+impl RunningAnimation {
+    ///The time since the animation started, which stands still while it is paused.
+    fn local_time(&self, now: f64) -> f32 {
+        (self.paused_at.unwrap_or(now) - self.start) as f32
+    }
+}
+
+///Whether two computed values are the same. Values from different declarations differ in their spans,
+///so they are compared as text if they are not equal outright.
+fn same_value(a: &[ComponentValue], b: &[ComponentValue]) -> bool {
+    a == b || serialize_component_values(a) == serialize_component_values(b)
+}
+
+impl ElementAnimations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Starts and stops transitions and animations for the newly computed `style` of the element, at the time `now`.
+    ///`style` should not have any animated values applied yet, and `cascade` is where `@keyframes` rules are looked up.
+    ///Transitions only start for changes between updates, so nothing transitions at the first update.
+    pub fn update(&mut self, style: &ComputedStyle, cascade: &Cascade, now: f64) {
+        self.tick(now);
+        self.update_transitions(style, now);
+        self.update_animations(style, cascade, now);
+    }
+
+    ///Drops the transitions that have ended.
+    pub fn tick(&mut self, now: f64) {
+        self.transitions.retain(|v| !v.is_finished(now));
+    }
+
+    fn update_transitions(&mut self, style: &ComputedStyle, now: f64) {
+        let definitions = TransitionDefinition::from_style(style);
+        // A transition stops once its property no longer transitions.
+        self.transitions
+            .retain(|v| TransitionDefinition::find(&definitions, v.property).is_some());
+        let first = self.after_change.is_empty();
+        for property in PROPERTIES {
+            if property.animation_type == AnimationType::Discrete {
+                continue;
+            }
+            let Some(new) = style.get(property.name) else {
+                continue;
+            };
+            let old = self.after_change.get(property.name);
+            if old.is_some_and(|v| same_value(v, new)) {
+                continue;
+            }
+            let owned: Value = new
+                .iter()
+                .cloned()
+                .map(ComponentValue::into_owned)
+                .collect();
+            let old = self.after_change.insert(property.name, owned.clone());
+            let (false, Some(old)) = (first, old) else {
+                continue;
+            };
+            self.start_transition(&definitions, property.name, old, owned, now);
+        }
+    }
+
+    ///Starts a transition of `property` from `old` to `new`, or stops the one it has if it does not transition.
+    ///Follows https://www.w3.org/TR/css-transitions-1/#starting
+    fn start_transition(
+        &mut self,
+        definitions: &[TransitionDefinition],
+        property: &'static str,
+        old: Value,
+        new: Value,
+        now: f64,
+    ) {
+        let running = self.transitions.iter().position(|v| v.property == property);
+        if let Some(i) = running
+            && same_value(&self.transitions[i].to, &new)
+        {
+            return;
+        }
+        let running = running.map(|i| self.transitions.remove(i));
+        let Some(definition) = TransitionDefinition::find(definitions, property) else {
+            return;
+        };
+        // A running transition carries on from where it is.
+        let from = match &running {
+            Some(running) => running.value(now),
+            None => old,
+        };
+        if definition.combined_duration() <= 0.0 || !interpolates(property, &from, &new) {
+            return;
+        }
+        let mut duration = definition.duration.0.max(0.0);
+        let mut delay = definition.delay.0;
+        let mut reversing_adjusted_start = from.clone();
+        let mut reversing_shortening_factor = 1.0;
+        // Going back to where a transition came from takes as long as getting to where it is took.
+        if let Some(running) = running
+            && same_value(&running.reversing_adjusted_start, &new)
+        {
+            reversing_shortening_factor = (running.output_progress(now)
+                * running.reversing_shortening_factor
+                + (1.0 - running.reversing_shortening_factor))
+                .abs()
+                .clamp(0.0, 1.0);
+            duration *= reversing_shortening_factor;
+            if delay < 0.0 {
+                delay *= reversing_shortening_factor;
+            }
+            reversing_adjusted_start = running.to;
+        }
+        self.transitions.push(RunningTransition {
+            property,
+            from,
+            to: new,
+            start: now,
+            delay,
+            duration,
+            easing: definition.easing.clone(),
+            reversing_adjusted_start,
+            reversing_shortening_factor,
+        });
+    }
+
+    fn update_animations(&mut self, style: &ComputedStyle, cascade: &Cascade, now: f64) {
+        let mut previous = std::mem::take(&mut self.animations);
+        for definition in AnimationDefinition::from_style(style) {
+            // An animation keeps running as long as its name stays in `animation-name`.
+            let existing = previous
+                .iter()
+                .position(|v| v.definition.name == definition.name)
+                .map(|i| previous.remove(i));
+            let mut animation = match existing {
+                Some(animation) => animation,
+                None => RunningAnimation {
+                    keyframes: cascade
+                        .keyframes(&definition.name)
+                        .map(|v| v.resolve(style))
+                        .unwrap_or_default(),
+                    definition: definition.clone(),
+                    start: now,
+                    paused_at: None,
+                },
+            };
+            match (definition.play_state, animation.paused_at) {
+                (PlayState::Paused, None) => animation.paused_at = Some(now),
+                (PlayState::Running, Some(paused_at)) => {
+                    animation.start += now - paused_at;
+                    animation.paused_at = None;
+                }
+                _ => {}
+            }
+            animation.definition = definition;
+            self.animations.push(animation);
+        }
+    }
+
+    ///Writes the values of the running animations and transitions at `now` into `style`,
+    ///transitions overriding animations like they do in the cascade.
+    pub fn apply(&self, style: &mut ComputedStyle, now: f64) {
+        for animation in &self.animations {
+            let definition = &animation.definition;
+            if let Some(progress) = definition.progress(animation.local_time(now)) {
+                animation
+                    .keyframes
+                    .apply(style, progress, &definition.easing);
+            }
+        }
+        for transition in &self.transitions {
+            style.set(transition.property, transition.value(now));
+        }
+    }
+
+    ///Whether the values of the element can still change over time without a style change,
+    ///which is when it has to be drawn again every frame.
+    pub fn is_running(&self, now: f64) -> bool {
+        let transitions = self.transitions.iter().any(|v| !v.is_finished(now));
+        let animations = self.animations.iter().any(|v| {
+            v.paused_at.is_none()
+                && !v.keyframes.properties.is_empty()
+                && v.definition.phase(v.local_time(now)) != AnimationPhase::After
+        });
+        transitions || animations
+    }
+
+    ///The names of the animations of the element, in the order of `animation-name`.
+    pub fn animation_names(&self) -> impl Iterator<Item = &str> {
+        self.animations.iter().map(|v| v.definition.name.as_str())
+    }
+
+    ///The properties that are transitioning.
+    pub fn transitioning_properties(&self) -> impl Iterator<Item = &'static str> {
+        self.transitions.iter().map(|v| v.property)
+    }
+}
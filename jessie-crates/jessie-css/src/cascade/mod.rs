@@ -0,0 +1,988 @@
+use crate::CSSToken;
+use crate::animation::keyframes::Keyframes;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::font_face::FontFace;
+use crate::import::LoadedStylesheet;
+use crate::matching::{AncestorFilter, Element, matches_selector};
+use crate::media::container::ContainerQuery;
+use crate::media::{MediaEnvironment, MediaQueryList};
+use crate::nesting::{self, FlatRule};
+use crate::parser::{ComponentValue, Declaration, Rule, Stylesheet};
+use crate::properties::custom::{PropertyRegistration, contains_var, substitute_var};
+use crate::properties::{self, PROPERTIES, PropertyDefinition, shorthands};
+use crate::selectors::{PseudoElement, SelectorList, Specificity};
+use crate::span::Span;
+use crate::style_attribute::DeclarationList;
+use crate::supports::SupportsCondition;
+use crate::values::{Parse, parse_value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+///Where a stylesheet comes from, see https://www.w3.org/TR/css-cascade-5/#cascading-origins
+///Later origins win for normal declarations, earlier ones for `!important` declarations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Origin {
+    ///The default styles of the engine.
+    UserAgent,
+    ///The styles the user of the app configured.
+    User,
+    ///The styles of the app itself, including inline styles.
+    Author,
+}
+
+///The keywords every property accepts, see https://www.w3.org/TR/css-cascade-5/#defaulting-keywords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CssWideKeyword {
+    Initial,
+    Inherit,
+    Unset,
+    Revert,
+    RevertLayer,
+}
+
+impl CssWideKeyword {
+    ///Returns the keyword if the value of a declaration is only a CSS-wide keyword.
+    pub fn parse(value: &[ComponentValue]) -> Option<Self> {
+        let mut values = value.iter().filter(|v| !v.is_whitespace());
+        let Some(CSSToken::IdentToken { value }) = values.next()?.token() else {
+            return None;
+        };
+        if values.next().is_some() {
+            return None;
+        }
+        [
+            ("initial", CssWideKeyword::Initial),
+            ("inherit", CssWideKeyword::Inherit),
+            ("unset", CssWideKeyword::Unset),
+            ("revert", CssWideKeyword::Revert),
+            ("revert-layer", CssWideKeyword::RevertLayer),
+        ]
+        .into_iter()
+        .find(|(name, _)| value.eq_ignore_ascii_case(name))
+        .map(|(_, keyword)| keyword)
+    }
+}
+
+///Collects the style rules of stylesheets and resolves the [`ComputedStyle`] of elements from them,
+///following https://www.w3.org/TR/css-cascade-5/
+pub struct Cascade<'a> {
+    rules: Vec<CascadeRule<'a>>,
+    ///The cascade layers of all origins. The first three are the implicit outer layers of the origins, in the order of [`Origin`].
+    layers: Vec<Layer<'a>>,
+    ///The custom properties registered with `@property`, by name.
+    registrations: Registrations<'a>,
+    ///The `@keyframes` rules, see [`Cascade::keyframes`].
+    keyframes: Vec<KeyframesRule<'a>>,
+    ///The `@font-face` rules with the conditions they are nested in, see [`Cascade::font_faces`].
+    font_faces: Vec<(FontFace, Vec<usize>)>,
+    ///The conditions of the `@media` and `@container` rules that style rules are nested in.
+    conditions: Vec<RuleCondition>,
+    ///Whether each condition that is a media query list matches [`Cascade::environment`], so they are not evaluated for every element.
+    media_matches: Vec<bool>,
+    environment: MediaEnvironment,
+    diagnostics: Vec<CssDiagnostic>,
+    ///The errors of the stylesheets added with [`Cascade::add_loaded_stylesheet`], see [`Cascade::loaded_diagnostics`].
+    loaded_diagnostics: Vec<LoadedDiagnostic>,
+}
+
+///An error found while cascading a stylesheet added with [`Cascade::add_loaded_stylesheet`] or one it imports,
+///with the url of the stylesheet the span points into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedDiagnostic {
+    ///The [`LoadedStylesheet::url`] of the stylesheet.
+    pub url: String,
+    pub diagnostic: CssDiagnostic,
+}
+
+type Registrations<'a> = HashMap<Cow<'a, str>, PropertyRegistration<'a>>;
+
+///A style rule flattened out of its stylesheet.
+struct CascadeRule<'a> {
+    selectors: SelectorList<'a>,
+    declarations: Vec<Declaration<'a>>,
+    origin: Origin,
+    layer: usize,
+    ///The indices of the conditions the rule is nested in, all of which have to hold for it to apply.
+    conditions: Vec<usize>,
+}
+
+///A `@keyframes` rule with where it is in the cascade, which decides between rules with the same name.
+struct KeyframesRule<'a> {
+    keyframes: Keyframes<'a>,
+    origin: Origin,
+    layer: usize,
+    conditions: Vec<usize>,
+}
+
+///The prelude of a conditional group rule.
+enum RuleCondition {
+    Media(MediaQueryList),
+    Container(ContainerQuery),
+}
+
+///A cascade layer of https://www.w3.org/TR/css-cascade-5/#layering
+struct Layer<'a> {
+    ///`None` for anonymous layers and the outer layers of the origins.
+    name: Option<Cow<'a, str>>,
+    children: Vec<usize>,
+    ///The position of the layer in the layer order of its origin.
+    ///The sublayers of a layer come before the layer itself, so its own declarations win over the ones of its sublayers.
+    rank: usize,
+}
+
+///A declaration that applies to the element whose style is resolved, with what the cascade sorts it by.
+#[derive(Clone, Copy)]
+struct Candidate<'c, 'a> {
+    declaration: &'c Declaration<'a>,
+    origin: Origin,
+    ///`None` for inline styles, which are not in any layer.
+    layer: Option<usize>,
+    precedence: Precedence,
+}
+
+///The cascade sort order of https://www.w3.org/TR/css-cascade-5/#cascade-sort, the highest precedence wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Precedence {
+    ///The origin, with important declarations in reverse origin order above all normal ones.
+    origin: u8,
+    inline: bool,
+    ///The rank of the layer, negated for important declarations which reverses the layer order.
+    layer: isize,
+    specificity: Specificity,
+    ///The order the declaration appears in, `(rule, declaration)`.
+    order: (usize, usize),
+}
+
+impl Default for Cascade<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Cascade<'a> {
+    pub fn new() -> Self {
+        let root = || Layer {
+            name: None,
+            children: vec![],
+            rank: 0,
+        };
+        Self {
+            rules: vec![],
+            layers: vec![root(), root(), root()],
+            registrations: HashMap::new(),
+            keyframes: vec![],
+            font_faces: vec![],
+            conditions: vec![],
+            media_matches: vec![],
+            environment: MediaEnvironment::default(),
+            diagnostics: vec![],
+            loaded_diagnostics: vec![],
+        }
+    }
+
+    ///Returns the errors found in the stylesheets added so far, like invalid selectors and unknown properties.
+    pub fn diagnostics(&self) -> &[CssDiagnostic] {
+        &self.diagnostics
+    }
+
+    ///Returns the errors found in the stylesheets added so far, leaving none behind.
+    pub fn take_diagnostics(&mut self) -> Vec<CssDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    ///Returns the errors found in the stylesheets added with [`Cascade::add_loaded_stylesheet`] so far, in source order,
+    ///which can come from any of the stylesheets they import.
+    pub fn loaded_diagnostics(&self) -> &[LoadedDiagnostic] {
+        &self.loaded_diagnostics
+    }
+
+    ///Returns the errors of [`Cascade::loaded_diagnostics`], leaving none behind.
+    pub fn take_loaded_diagnostics(&mut self) -> Vec<LoadedDiagnostic> {
+        std::mem::take(&mut self.loaded_diagnostics)
+    }
+
+    ///Returns the registration of a custom property by `@property`, if it has one.
+    pub fn registration(&self, name: &str) -> Option<&PropertyRegistration<'a>> {
+        self.registrations.get(name)
+    }
+
+    ///Returns the `@keyframes` rule with `name` that animations run.
+    ///Of several rules with the same name, the one in the later origin or layer wins, and then the one that comes last.
+    ///Rules in a `@media` rule that does not match are skipped.
+    pub fn keyframes(&self, name: &str) -> Option<&Keyframes<'a>> {
+        self.keyframes
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.keyframes.name == name)
+            .filter(|(_, v)| self.media_conditions_hold(&v.conditions))
+            .max_by_key(|(i, v)| (v.origin, self.layers[v.layer].rank, *i))
+            .map(|(_, v)| &v.keyframes)
+    }
+
+    ///Returns the `@font-face` rules in the order they were added, skipping the ones in a `@media` rule that does not match.
+    ///Font faces are not in a cascade layer, so picking one is left to the font matching of whoever renders text.
+    pub fn font_faces(&self) -> impl Iterator<Item = &FontFace> {
+        self.font_faces
+            .iter()
+            .filter(|(_, conditions)| self.media_conditions_hold(conditions))
+            .map(|(face, _)| face)
+    }
+
+    ///Whether the media queries of `conditions` match, container queries being left out.
+    fn media_conditions_hold(&self, conditions: &[usize]) -> bool {
+        conditions.iter().all(|&condition| {
+            !matches!(self.conditions[condition], RuleCondition::Media(_))
+                || self.media_matches[condition]
+        })
+    }
+
+    ///Returns the environment `@media` rules are evaluated against, see [`Cascade::set_environment`].
+    pub fn environment(&self) -> &MediaEnvironment {
+        &self.environment
+    }
+
+    ///Sets the environment `@media` rules are evaluated against, like when the window is resized.
+    ///It is [`MediaEnvironment::default`] until it is set.
+    pub fn set_environment(&mut self, environment: MediaEnvironment) {
+        self.environment = environment;
+        self.evaluate_media();
+    }
+
+    ///Adds the style rules of a stylesheet. Stylesheets added later come later in the source order.
+    ///Nested style rules are flattened first, see [`nesting::flatten`].
+    ///`@import` rules are ignored, see [`Cascade::add_loaded_stylesheet`] for stylesheets that import others.
+    pub fn add_stylesheet(&mut self, stylesheet: &Stylesheet<'a>, origin: Origin) {
+        self.add_rule_list(&stylesheet.rules, origin, origin as usize, &[]);
+        self.rank_layers();
+        self.evaluate_media();
+    }
+
+    ///Adds the style rules of a stylesheet with the rules of the stylesheets it imports in place of its `@import` rules,
+    ///in the layer and under the media queries of the rule.
+    ///The parse errors of the stylesheets are in [`LoadedStylesheet::diagnostics`], and the errors found while
+    ///cascading them in [`Cascade::loaded_diagnostics`] along with the stylesheet they are in, not in [`Cascade::diagnostics`].
+    pub fn add_loaded_stylesheet(&mut self, stylesheet: &LoadedStylesheet, origin: Origin) {
+        let diagnostics = std::mem::take(&mut self.diagnostics);
+        self.add_imported(stylesheet, origin, origin as usize, &[]);
+        self.diagnostics = diagnostics;
+        self.rank_layers();
+        self.evaluate_media();
+    }
+
+    fn add_imported(
+        &mut self,
+        stylesheet: &LoadedStylesheet,
+        origin: Origin,
+        layer: usize,
+        conditions: &[usize],
+    ) {
+        let mut start = 0;
+        for import in &stylesheet.imports {
+            self.add_rule_list(
+                &stylesheet.stylesheet.rules[start..import.index],
+                origin,
+                layer,
+                conditions,
+            );
+            start = import.index + 1;
+            let Some(imported) = &import.stylesheet else {
+                continue;
+            };
+            let inner = match &import.rule.layer {
+                Some(prelude) => match parse_layer_names(prelude) {
+                    Some(names) if names.len() <= 1 => match names.into_iter().next() {
+                        Some(name) => self.declare_layer(layer, name),
+                        None => self.push_layer(layer, None),
+                    },
+                    _ => {
+                        self.diagnostics.push(CssDiagnostic::new(
+                            CssDiagnosticKind::InvalidAtRule,
+                            import.rule.span,
+                            "`layer()` takes a single layer name like `base` or `theme.dark`",
+                        ));
+                        continue;
+                    }
+                },
+                None => layer,
+            };
+            let mut inner_conditions = conditions.to_vec();
+            if !import.rule.media.iter().all(ComponentValue::is_whitespace) {
+                self.conditions
+                    .push(RuleCondition::Media(MediaQueryList::parse(
+                        &import.rule.media,
+                        &mut self.diagnostics,
+                    )));
+                inner_conditions.push(self.conditions.len() - 1);
+            }
+            self.move_loaded_diagnostics(&stylesheet.url);
+            self.add_imported(imported, origin, inner, &inner_conditions);
+        }
+        self.add_rule_list(
+            &stylesheet.stylesheet.rules[start..],
+            origin,
+            layer,
+            conditions,
+        );
+        self.move_loaded_diagnostics(&stylesheet.url);
+    }
+
+    ///Moves the errors found so far to [`Cascade::loaded_diagnostics`], as errors of the stylesheet at `url`.
+    fn move_loaded_diagnostics(&mut self, url: &str) {
+        self.loaded_diagnostics
+            .extend(
+                self.diagnostics
+                    .drain(..)
+                    .map(|diagnostic| LoadedDiagnostic {
+                        url: url.to_owned(),
+                        diagnostic,
+                    }),
+            );
+    }
+
+    fn add_rule_list(
+        &mut self,
+        rules: &[Rule<'a>],
+        origin: Origin,
+        layer: usize,
+        conditions: &[usize],
+    ) {
+        // One rule at a time, so the errors of a rule come before the ones of the rules after it.
+        for rule in rules {
+            let rules = nesting::flatten(std::slice::from_ref(rule), &mut self.diagnostics);
+            self.add_rules(rules, origin, layer, conditions);
+        }
+    }
+
+    fn add_rules(
+        &mut self,
+        rules: Vec<FlatRule<'a>>,
+        origin: Origin,
+        layer: usize,
+        conditions: &[usize],
+    ) {
+        for rule in rules {
+            match rule {
+                FlatRule::Style(rule) => {
+                    let declarations =
+                        validate_declarations(rule.declarations, &mut self.diagnostics);
+                    self.rules.push(CascadeRule {
+                        selectors: rule.selectors,
+                        declarations,
+                        origin,
+                        layer,
+                        conditions: conditions.to_vec(),
+                    });
+                }
+                FlatRule::Group(rule) if rule.name.eq_ignore_ascii_case("layer") => {
+                    // `@layer name { ... }` or `@layer { ... }` for an anonymous layer.
+                    let inner = match parse_layer_names(&rule.prelude) {
+                        Some(names) if names.len() <= 1 => match names.into_iter().next() {
+                            Some(name) => self.declare_layer(layer, name),
+                            None => self.push_layer(layer, None),
+                        },
+                        Some(_) => {
+                            self.diagnostics.push(CssDiagnostic::new(
+                                CssDiagnosticKind::InvalidAtRule,
+                                rule.span,
+                                "`@layer` with a block takes at most one layer name",
+                            ));
+                            continue;
+                        }
+                        None => {
+                            self.diagnostics.push(invalid_layer_names(rule.span));
+                            continue;
+                        }
+                    };
+                    self.add_rules(rule.rules, origin, inner, conditions);
+                }
+                FlatRule::Group(rule)
+                    if rule.name.eq_ignore_ascii_case("media")
+                        || rule.name.eq_ignore_ascii_case("container") =>
+                {
+                    let condition = match rule.name.eq_ignore_ascii_case("media") {
+                        true => RuleCondition::Media(MediaQueryList::parse(
+                            &rule.prelude,
+                            &mut self.diagnostics,
+                        )),
+                        false => {
+                            match ContainerQuery::parse(&rule.prelude, &mut self.diagnostics) {
+                                Ok(query) => RuleCondition::Container(query),
+                                Err(diagnostic) => {
+                                    self.diagnostics.push(diagnostic);
+                                    continue;
+                                }
+                            }
+                        }
+                    };
+                    self.conditions.push(condition);
+                    let mut inner = conditions.to_vec();
+                    inner.push(self.conditions.len() - 1);
+                    self.add_rules(rule.rules, origin, layer, &inner);
+                }
+                // Feature queries do not change, so the condition is evaluated once.
+                FlatRule::Group(rule) if rule.name.eq_ignore_ascii_case("supports") => {
+                    match SupportsCondition::parse(&rule.prelude) {
+                        Ok(condition) => {
+                            if condition.evaluate() {
+                                self.add_rules(rule.rules, origin, layer, conditions);
+                            }
+                        }
+                        Err(diagnostic) => self.diagnostics.push(diagnostic),
+                    }
+                }
+                // `@layer a, b;` only declares the order of the layers.
+                FlatRule::AtRule(rule) if rule.name.eq_ignore_ascii_case("layer") => {
+                    match parse_layer_names(&rule.prelude) {
+                        Some(names) if !names.is_empty() => {
+                            for name in names {
+                                self.declare_layer(layer, name);
+                            }
+                        }
+                        Some(_) => self.diagnostics.push(CssDiagnostic::new(
+                            CssDiagnosticKind::InvalidAtRule,
+                            rule.span,
+                            "`@layer` without a block takes at least one layer name",
+                        )),
+                        None => self.diagnostics.push(invalid_layer_names(rule.span)),
+                    }
+                }
+                // A later registration of the same name replaces an earlier one, whatever layer it is in.
+                FlatRule::AtRule(rule) if rule.name.eq_ignore_ascii_case("property") => {
+                    match PropertyRegistration::parse(&rule) {
+                        Ok(registration) => {
+                            self.registrations
+                                .insert(registration.name.clone(), registration);
+                        }
+                        Err(diagnostic) => self.diagnostics.push(diagnostic),
+                    }
+                }
+                FlatRule::AtRule(rule) if rule.name.eq_ignore_ascii_case("keyframes") => {
+                    match Keyframes::parse(&rule, &mut self.diagnostics) {
+                        Ok(keyframes) => self.keyframes.push(KeyframesRule {
+                            keyframes,
+                            origin,
+                            layer,
+                            conditions: conditions.to_vec(),
+                        }),
+                        Err(diagnostic) => self.diagnostics.push(diagnostic),
+                    }
+                }
+                FlatRule::AtRule(rule) if rule.name.eq_ignore_ascii_case("font-face") => {
+                    match FontFace::parse(&rule, &mut self.diagnostics) {
+                        Ok(face) => self.font_faces.push((face, conditions.to_vec())),
+                        Err(diagnostic) => self.diagnostics.push(diagnostic),
+                    }
+                }
+                FlatRule::AtRule(rule)
+                    if rule.name.eq_ignore_ascii_case("media")
+                        || rule.name.eq_ignore_ascii_case("container")
+                        || rule.name.eq_ignore_ascii_case("supports") =>
+                {
+                    self.diagnostics.push(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidAtRule,
+                        rule.span,
+                        format!("`@{}` needs a block of rules", rule.name),
+                    ));
+                }
+                // Other at-rules do not contribute style rules.
+                FlatRule::Group(_) | FlatRule::AtRule(_) => {}
+            }
+        }
+    }
+
+    ///Returns the layer `name` in `parent`, declaring it (and the layers it is nested in) if it does not exist yet.
+    fn declare_layer(&mut self, parent: usize, name: Vec<Cow<'a, str>>) -> usize {
+        name.into_iter().fold(parent, |parent, name| {
+            let existing = self.layers[parent]
+                .children
+                .iter()
+                .copied()
+                .find(|&v| self.layers[v].name.as_ref() == Some(&name));
+            match existing {
+                Some(layer) => layer,
+                None => self.push_layer(parent, Some(name)),
+            }
+        })
+    }
+
+    fn push_layer(&mut self, parent: usize, name: Option<Cow<'a, str>>) -> usize {
+        let index = self.layers.len();
+        self.layers.push(Layer {
+            name,
+            children: vec![],
+            rank: 0,
+        });
+        self.layers[parent].children.push(index);
+        index
+    }
+
+    fn evaluate_media(&mut self) {
+        self.media_matches = self
+            .conditions
+            .iter()
+            .map(|condition| match condition {
+                RuleCondition::Media(queries) => queries.matches(&self.environment),
+                RuleCondition::Container(_) => true,
+            })
+            .collect();
+    }
+
+    ///Whether all of `conditions` hold for the element, `pseudo_element` being one of its pseudo-elements if given.
+    fn conditions_hold<E: Element>(
+        &self,
+        conditions: &[usize],
+        element: &E,
+        pseudo_element: Option<PseudoElement>,
+    ) -> bool {
+        conditions
+            .iter()
+            .all(|&condition| match &self.conditions[condition] {
+                RuleCondition::Media(_) => self.media_matches[condition],
+                // A pseudo-element can query its originating element.
+                RuleCondition::Container(query) => match pseudo_element {
+                    Some(_) => query.matches(Some(element.clone())),
+                    None => query.matches(element.parent()),
+                },
+            })
+    }
+
+    ///Ranks the layers in post-order, so sublayers come before their parent in the order they were declared.
+    fn rank_layers(&mut self) {
+        fn visit(layers: &mut [Layer], layer: usize, rank: &mut usize) {
+            for i in 0..layers[layer].children.len() {
+                let child = layers[layer].children[i];
+                visit(layers, child, rank);
+            }
+            layers[layer].rank = *rank;
+            *rank += 1;
+        }
+        for origin in 0..3 {
+            visit(&mut self.layers, origin, &mut 0);
+        }
+    }
+
+    ///Resolves the computed style of an element.
+    ///`inline` are the declarations of its `style` attribute, see [`crate::style_attribute::parse_declaration_list`],
+    ///`parent` the computed style of its parent, which is `None` for the root element.
+    ///`filter` should hold the ancestors of `element` if given, see [`AncestorFilter`].
+    pub fn compute<E: Element>(
+        &self,
+        element: &E,
+        inline: Option<&DeclarationList<'a>>,
+        parent: Option<&ComputedStyle<'a>>,
+        filter: Option<&AncestorFilter>,
+    ) -> ComputedStyle<'a> {
+        let inline = inline.map_or(&[][..], DeclarationList::declarations);
+        let candidates = self.collect(element, None, inline, filter);
+        ComputedStyle::resolve(
+            self.cascaded_values(candidates),
+            parent,
+            &self.registrations,
+        )
+    }
+
+    ///Resolves the computed style of a pseudo-element of an element, which inherits from the style of the element.
+    pub fn compute_pseudo_element<E: Element>(
+        &self,
+        element: &E,
+        pseudo_element: PseudoElement,
+        originating: &ComputedStyle<'a>,
+        filter: Option<&AncestorFilter>,
+    ) -> ComputedStyle<'a> {
+        let candidates = self.collect(element, Some(pseudo_element), &[], filter);
+        ComputedStyle::resolve(
+            self.cascaded_values(candidates),
+            Some(originating),
+            &self.registrations,
+        )
+    }
+
+    ///Collects the declarations that apply to the element, grouped by property.
+    fn collect<'c, E: Element>(
+        &'c self,
+        element: &E,
+        pseudo_element: Option<PseudoElement>,
+        inline: &'c [Declaration<'a>],
+        filter: Option<&AncestorFilter>,
+    ) -> HashMap<&'c str, Vec<Candidate<'c, 'a>>> {
+        let mut out: HashMap<&str, Vec<Candidate>> = HashMap::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !self.conditions_hold(&rule.conditions, element, pseudo_element) {
+                continue;
+            }
+            let specificity = rule
+                .selectors
+                .selectors
+                .iter()
+                .filter(|v| v.pseudo_element() == pseudo_element)
+                .filter(|v| matches_selector(v, element, filter))
+                .map(|v| v.specificity())
+                .max();
+            let Some(specificity) = specificity else {
+                continue;
+            };
+            let rank = self.layers[rule.layer].rank as isize;
+            for (i, declaration) in rule.declarations.iter().enumerate() {
+                let candidate = Candidate {
+                    declaration,
+                    origin: rule.origin,
+                    layer: Some(rule.layer),
+                    precedence: Precedence {
+                        origin: origin_precedence(rule.origin, declaration.important),
+                        inline: false,
+                        layer: if declaration.important { -rank } else { rank },
+                        specificity,
+                        order: (index, i),
+                    },
+                };
+                push_candidate(&mut out, candidate);
+            }
+        }
+        for (i, declaration) in inline.iter().enumerate() {
+            let candidate = Candidate {
+                declaration,
+                origin: Origin::Author,
+                layer: None,
+                precedence: Precedence {
+                    origin: origin_precedence(Origin::Author, declaration.important),
+                    inline: true,
+                    layer: 0,
+                    specificity: Specificity::default(),
+                    order: (self.rules.len(), i),
+                },
+            };
+            push_candidate(&mut out, candidate);
+        }
+        out
+    }
+
+    ///Picks the winning declaration of every property, rolling back over `revert` and `revert-layer`.
+    ///A property whose declarations all roll back has no cascaded value.
+    fn cascaded_values<'c>(
+        &self,
+        candidates: HashMap<&'c str, Vec<Candidate<'c, 'a>>>,
+    ) -> HashMap<&'c str, &'c Declaration<'a>> {
+        let mut out = HashMap::new();
+        for (name, mut candidates) in candidates {
+            candidates.sort_by_key(|v| std::cmp::Reverse(v.precedence));
+            let mut rolled_back: Vec<&Candidate> = vec![];
+            for candidate in &candidates {
+                let skipped =
+                    rolled_back
+                        .iter()
+                        .any(|v| match CssWideKeyword::parse(&v.declaration.value) {
+                            Some(CssWideKeyword::Revert) => candidate.origin == v.origin,
+                            _ => {
+                                candidate.precedence.origin == v.precedence.origin
+                                    && candidate.layer == v.layer
+                            }
+                        });
+                if skipped {
+                    continue;
+                }
+                match CssWideKeyword::parse(&candidate.declaration.value) {
+                    Some(CssWideKeyword::Revert | CssWideKeyword::RevertLayer) => {
+                        rolled_back.push(candidate);
+                    }
+                    _ => {
+                        out.insert(name, candidate.declaration);
+                        break;
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+///Adds a candidate for the property it declares.
+///A shorthand is only left after validation if it has a `var()`, and is a candidate for all of its longhands until it is substituted.
+fn push_candidate<'c, 'a>(
+    candidates: &mut HashMap<&'c str, Vec<Candidate<'c, 'a>>>,
+    candidate: Candidate<'c, 'a>,
+) {
+    let name = candidate.declaration.name.as_ref();
+    match shorthands::lookup(name) {
+        Some(shorthand) => {
+            for longhand in shorthand.longhands {
+                candidates.entry(longhand).or_default().push(candidate);
+            }
+        }
+        None => candidates.entry(name).or_default().push(candidate),
+    }
+}
+
+///Drops the declarations with unknown properties or invalid values and expands shorthands, see [`properties::validate_declaration`].
+fn validate_declarations<'a>(
+    declarations: Vec<Declaration<'a>>,
+    diagnostics: &mut Vec<CssDiagnostic>,
+) -> Vec<Declaration<'a>> {
+    let mut out = vec![];
+    for declaration in declarations {
+        match properties::validate_declaration(declaration) {
+            Ok(declarations) => out.extend(declarations),
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
+    }
+    out
+}
+
+fn origin_precedence(origin: Origin, important: bool) -> u8 {
+    match (origin, important) {
+        (Origin::UserAgent, false) => 0,
+        (Origin::User, false) => 1,
+        (Origin::Author, false) => 2,
+        (Origin::Author, true) => 3,
+        (Origin::User, true) => 4,
+        (Origin::UserAgent, true) => 5,
+    }
+}
+
+fn invalid_layer_names(span: Span) -> CssDiagnostic {
+    CssDiagnostic::new(
+        CssDiagnosticKind::InvalidAtRule,
+        span,
+        "expected a comma separated list of layer names like `base, theme.dark`",
+    )
+}
+
+///Parses the prelude of an `@layer` rule into its comma separated layer names, each split at its dots.
+///An empty prelude results in no names.
+fn parse_layer_names<'a>(prelude: &[ComponentValue<'a>]) -> Option<Vec<Vec<Cow<'a, str>>>> {
+    if prelude.iter().all(ComponentValue::is_whitespace) {
+        return Some(vec![]);
+    }
+    let mut names = vec![];
+    for mut name in prelude.split(|v| v.token() == Some(&CSSToken::CommaToken)) {
+        while let [first, rest @ ..] = name
+            && first.is_whitespace()
+        {
+            name = rest;
+        }
+        while let [rest @ .., last] = name
+            && last.is_whitespace()
+        {
+            name = rest;
+        }
+        // A layer name is `<ident> [ '.' <ident> ]*` without any whitespace in between.
+        if name.len() % 2 == 0 {
+            return None;
+        }
+        let mut parts = vec![];
+        for (i, value) in name.iter().enumerate() {
+            match value.token()? {
+                CSSToken::IdentToken { value } if i % 2 == 0 => parts.push(value.clone()),
+                CSSToken::DelimToken { value: '.' } if i % 2 == 1 => {}
+                _ => return None,
+            }
+        }
+        names.push(parts);
+    }
+    Some(names)
+}
+
+///The computed values of the properties of an element, as component values.
+///Every property of the [`crate::properties`] registry has a value, and custom properties only when one applies.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ComputedStyle<'a> {
+    values: BTreeMap<Cow<'a, str>, Vec<ComponentValue<'a>>>,
+}
+
+impl<'a> ComputedStyle<'a> {
+    ///The style of an element without any declarations and without a parent, where every property has its initial value.
+    pub fn initial() -> Self {
+        Self::resolve(HashMap::new(), None, &HashMap::new())
+    }
+
+    ///Returns the value of a property by its name, which is lowercase except for custom properties.
+    pub fn get(&self, name: &str) -> Option<&[ComponentValue<'a>]> {
+        self.values.get(name).map(Vec::as_slice)
+    }
+
+    ///Returns the value of a property as a typed value, or `None` if it has no value or does not parse as `T`.
+    pub fn get_as<T: Parse>(&self, name: &str) -> Option<T> {
+        parse_value(self.get(name)?).ok()
+    }
+
+    ///Sets the value of a property, like transitions and animations do. The value is not validated.
+    pub fn set(&mut self, name: impl Into<Cow<'a, str>>, value: Vec<ComponentValue<'a>>) {
+        self.values.insert(name.into(), value);
+    }
+
+    ///Returns the properties and their values, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[ComponentValue<'a>])> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_ref(), value.as_slice()))
+    }
+
+    ///Applies https://www.w3.org/TR/css-cascade-5/#defaulting to the cascaded values, substituting `var()` on the way.
+    fn resolve(
+        cascaded: HashMap<&str, &Declaration<'a>>,
+        parent: Option<&ComputedStyle<'a>>,
+        registrations: &Registrations<'a>,
+    ) -> Self {
+        let mut values = resolve_custom_properties(&cascaded, parent, registrations);
+        for property in PROPERTIES {
+            let initial = || property.initial_value().to_vec();
+            let inherit = || match parent.and_then(|v| v.get(property.name)) {
+                Some(value) => value.to_vec(),
+                None => initial(),
+            };
+            let unset = || match property.inherited {
+                true => inherit(),
+                false => initial(),
+            };
+            // A value that is invalid once `var()` is substituted behaves like `unset`.
+            let value = cascaded
+                .get(property.name)
+                .and_then(|v| substitute_declaration(v, property, &values));
+            let value = match value.as_ref().map(|v| (v, CssWideKeyword::parse(v))) {
+                Some((value, None)) => value.clone(),
+                Some((_, Some(CssWideKeyword::Initial))) => initial(),
+                Some((_, Some(CssWideKeyword::Inherit))) => inherit(),
+                _ => unset(),
+            };
+            values.insert(Cow::Borrowed(property.name), value);
+        }
+        Self { values }
+    }
+}
+
+///Substitutes the `var()` of the declaration that sets `property`, which can be one of its shorthands.
+///Returns `None` if the result is invalid at computed-value time.
+fn substitute_declaration<'a>(
+    declaration: &Declaration<'a>,
+    property: &PropertyDefinition,
+    custom: &BTreeMap<Cow<'a, str>, Vec<ComponentValue<'a>>>,
+) -> Option<Vec<ComponentValue<'a>>> {
+    if !contains_var(&declaration.value) {
+        return Some(declaration.value.clone());
+    }
+    let value = substitute_var(&declaration.value, &mut |name| custom.get(name).cloned())?;
+    if let Some(shorthand) = shorthands::lookup(&declaration.name) {
+        let declaration = Declaration {
+            value,
+            ..declaration.clone()
+        };
+        return shorthand
+            .expand(&declaration)
+            .ok()?
+            .into_iter()
+            .find(|v| v.name == property.name)
+            .map(|v| v.value);
+    }
+    (property.validate)(&value).ok()?;
+    Some(value)
+}
+
+///Resolves the values of the custom properties of an element.
+///They are inherited unless registered otherwise, and have no value by default, which is the guaranteed-invalid value.
+fn resolve_custom_properties<'a>(
+    cascaded: &HashMap<&str, &Declaration<'a>>,
+    parent: Option<&ComputedStyle<'a>>,
+    registrations: &Registrations<'a>,
+) -> BTreeMap<Cow<'a, str>, Vec<ComponentValue<'a>>> {
+    let inherited = |name: &str| parent.and_then(|v| v.values.get(name)).cloned();
+    let initial = |name: &str| {
+        registrations
+            .get(name)
+            .and_then(|v| v.initial_value.clone())
+    };
+    let mut specified = BTreeMap::new();
+    if let Some(parent) = parent {
+        for (name, value) in &parent.values {
+            let inherits = registrations.get(name.as_ref()).is_none_or(|v| v.inherits);
+            if properties::is_custom_property(name) && inherits {
+                specified.insert(name.clone(), value.clone());
+            }
+        }
+    }
+    for (name, registration) in registrations {
+        if let Some(value) = &registration.initial_value
+            && !specified.contains_key(name)
+        {
+            specified.insert(name.clone(), value.clone());
+        }
+    }
+    for (name, declaration) in cascaded {
+        if !properties::is_custom_property(name) {
+            continue;
+        }
+        let value = match CssWideKeyword::parse(&declaration.value) {
+            None => Some(declaration.value.clone()),
+            Some(CssWideKeyword::Initial) => initial(name),
+            Some(CssWideKeyword::Inherit) => inherited(name),
+            // `unset` keeps the inherited or initial value the property already has.
+            Some(_) => continue,
+        };
+        match value {
+            Some(value) => specified.insert(declaration.name.clone(), value),
+            None => specified.remove(*name),
+        };
+    }
+
+    let mut resolver = CustomPropertyResolver {
+        specified: &specified,
+        registrations,
+        parent,
+        computed: HashMap::new(),
+        stack: vec![],
+    };
+    specified
+        .keys()
+        .filter_map(|name| Some((name.clone(), resolver.get(name)?)))
+        .collect()
+}
+
+///Substitutes the `var()` of custom properties, which can refer to each other,
+///making every property of a cycle invalid at computed-value time like https://www.w3.org/TR/css-variables-1/#cycles
+struct CustomPropertyResolver<'r, 'a> {
+    ///The values of the custom properties before substitution.
+    specified: &'r BTreeMap<Cow<'a, str>, Vec<ComponentValue<'a>>>,
+    registrations: &'r Registrations<'a>,
+    parent: Option<&'r ComputedStyle<'a>>,
+    ///The values after substitution, `None` for the properties that are invalid at computed-value time.
+    computed: HashMap<&'r str, Option<Vec<ComponentValue<'a>>>>,
+    ///The properties whose `var()` are being substituted, each referring to the next.
+    stack: Vec<&'r str>,
+}
+
+impl<'a> CustomPropertyResolver<'_, 'a> {
+    fn get(&mut self, name: &str) -> Option<Vec<ComponentValue<'a>>> {
+        let specified = self.specified;
+        let (name, value) = specified.get_key_value(name)?;
+        let name = name.as_ref();
+        if let Some(computed) = self.computed.get(name) {
+            return computed.clone();
+        }
+        if let Some(i) = self.stack.iter().position(|v| *v == name) {
+            for &property in &self.stack[i..] {
+                self.computed.insert(property, None);
+            }
+            return None;
+        }
+        let mut out = Some(value.clone());
+        if contains_var(value) {
+            self.stack.push(name);
+            out = substitute_var(value, &mut |v| self.get(v));
+            self.stack.pop();
+        }
+        // A property in a cycle stays invalid, even if a fallback got around the cycle.
+        if let Some(None) = self.computed.get(name) {
+            return None;
+        }
+        // A registered property that does not match its syntax behaves like `unset`.
+        if let Some(registration) = self.registrations.get(name)
+            && out.as_ref().is_none_or(|v| !registration.syntax.matches(v))
+        {
+            out = match registration.inherits {
+                true => self.parent.and_then(|v| v.values.get(name)).cloned(),
+                false => None,
+            }
+            .or_else(|| registration.initial_value.clone());
+        }
+        self.computed.insert(name, out.clone());
+        out
+    }
+}
@@ -0,0 +1,156 @@
+use crate::span::Span;
+use std::fmt;
+
+///A parse error found while tokenizing or parsing a stylesheet.
+///CSS parsing never stops at an error, so these are collected and the parsing keeps going with the spec's error recovery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssDiagnostic {
+    pub kind: CssDiagnosticKind,
+    pub span: Span,
+    pub message: String,
+}
+
+///The kinds of parse errors defined by https://www.w3.org/TR/css-syntax-3/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CssDiagnosticKind {
+    ///A comment was still open at the end of the input.
+    EOFInComment,
+    ///A string was still open at the end of the input.
+    UnterminatedString,
+    ///A string contained an unescaped newline, which turns it into a <bad-string-token>.
+    NewlineInString,
+    ///A `url(` was still open at the end of the input.
+    UnterminatedURL,
+    ///A `url(` contained a quote, a parenthesis, a non-printable code point, whitespace or an invalid escape, which turns it into a <bad-url-token>.
+    BadURL,
+    ///A `\` that does not start a valid escape.
+    InvalidEscape,
+    ///A block, function or rule was still open at the end of the input.
+    UnexpectedEOF,
+    ///A token that can not start anything at its position, like a number at the start of a declaration.
+    UnexpectedToken,
+    ///A declaration whose name is not followed by a `:`.
+    InvalidDeclaration,
+    ///A selector that does not follow the grammar of https://www.w3.org/TR/selectors-4/, which makes the whole selector list invalid.
+    InvalidSelector,
+    ///A declaration of a property that is not in the [`crate::properties`] registry, which is dropped.
+    UnknownProperty,
+    ///A declaration whose value does not follow the grammar of its property, which is dropped.
+    InvalidValue,
+    ///An at-rule whose prelude or block does not follow its grammar, which is dropped.
+    InvalidAtRule,
+    ///A media or container query that does not follow its grammar or tests an unknown feature, which never matches.
+    InvalidMediaQuery,
+    ///An `@import` whose stylesheet could not be loaded, which is ignored.
+    ImportFailed,
+    ///An `@import` of a stylesheet that imports the stylesheet the rule is in, directly or not, which is ignored.
+    ImportCycle,
+}
+
+impl CssDiagnosticKind {
+    ///A short description of the error, used when no more specific message is given.
+    pub fn description(&self) -> &'static str {
+        match self {
+            CssDiagnosticKind::EOFInComment => "unterminated comment",
+            CssDiagnosticKind::UnterminatedString => "unterminated string",
+            CssDiagnosticKind::NewlineInString => "newline in string",
+            CssDiagnosticKind::UnterminatedURL => "unterminated url",
+            CssDiagnosticKind::BadURL => "bad url",
+            CssDiagnosticKind::InvalidEscape => "invalid escape",
+            CssDiagnosticKind::UnexpectedEOF => "unexpected end of input",
+            CssDiagnosticKind::UnexpectedToken => "unexpected token",
+            CssDiagnosticKind::InvalidDeclaration => "invalid declaration",
+            CssDiagnosticKind::InvalidSelector => "invalid selector",
+            CssDiagnosticKind::UnknownProperty => "unknown property",
+            CssDiagnosticKind::InvalidValue => "invalid value",
+            CssDiagnosticKind::InvalidAtRule => "invalid at-rule",
+            CssDiagnosticKind::InvalidMediaQuery => "invalid media query",
+            CssDiagnosticKind::ImportFailed => "failed import",
+            CssDiagnosticKind::ImportCycle => "import cycle",
+        }
+    }
+}
+
+impl CssDiagnostic {
+    pub fn new(kind: CssDiagnosticKind, span: Span, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            span,
+            message: message.into(),
+        }
+    }
+
+    ///Renders the diagnostic like rustc does, with the offending part of `source` underlined.
+    ///`source` should be the input the span was created from, `file_name` is only used in the header.
+    ///```text
+    ///error: unterminated string
+    /// --> theme.css:2:12
+    ///  |
+    ///2 |   content: "abc
+    ///  |            ^^^^ the string is missing its closing `"`
+    ///```
+    pub fn render(&self, source: &str, file_name: &str) -> String {
+        let start = self.span.start;
+        let end = self.span.end;
+        let gutter = end.line.to_string().len();
+        let mut out = format!(
+            "error: {}\n{:gutter$}--> {}:{}:{}\n{:gutter$} |\n",
+            self.kind.description(),
+            "",
+            file_name,
+            start.line,
+            start.column,
+            "",
+        );
+
+        let lines: Vec<&str> = source
+            .split('\n')
+            .skip(start.line - 1)
+            .take(end.line - start.line + 1)
+            .collect();
+        for (i, line) in lines.iter().enumerate() {
+            let number = start.line + i;
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let length = line.chars().count();
+            // Underline from the start column on the first line and up to the end column on the last one.
+            let from = if number == start.line {
+                start.column - 1
+            } else {
+                0
+            };
+            let to = if number == end.line {
+                end.column - 1
+            } else {
+                length
+            };
+            // Empty spans, like the end of the input, still get a single caret.
+            let carets = to.saturating_sub(from).max(1);
+            out.push_str(&format!("{number:>gutter$} | {line}\n"));
+            out.push_str(&format!(
+                "{:gutter$} | {:from$}{}",
+                "",
+                "",
+                "^".repeat(carets)
+            ));
+            if i + 1 == lines.len() {
+                out.push(' ');
+                out.push_str(&self.message);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl fmt::Display for CssDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.span.start.line,
+            self.span.start.column,
+            self.kind.description(),
+            self.message
+        )
+    }
+}
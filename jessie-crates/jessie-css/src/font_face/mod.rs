@@ -0,0 +1,343 @@
+//! The `@font-face` rule of https://www.w3.org/TR/css-fonts-4/#font-face-rule, which makes a font file available under a family name.
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{AtRule, ComponentValue, DeclarationOrAtRule, Parser};
+use crate::span::Span;
+use crate::values::font::{FontStretch, FontWeight, GenericFamily};
+use crate::values::{Parse, ValueParser, keyword_enum, parse_value_with, unexpected};
+use std::ops::RangeInclusive;
+
+///The largest code point, which `unicode-range` ranges are clamped to.
+const MAX_CODE_POINT: u32 = 0x10FFFF;
+
+///The descriptors of https://www.w3.org/TR/css-fonts-4/#font-face-rule that are valid but not used for picking a face.
+const IGNORED_DESCRIPTORS: [&str; 8] = [
+    "font-feature-settings",
+    "font-variation-settings",
+    "font-named-instance",
+    "font-language-override",
+    "ascent-override",
+    "descent-override",
+    "line-gap-override",
+    "size-adjust",
+];
+
+///A `@font-face { ... }` rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFace {
+    ///The family name `font-family` refers to the face by.
+    pub family: String,
+    ///Where the font can be loaded from, in the order they should be tried in.
+    pub sources: Vec<FontSource>,
+    ///The range of weights the face covers, a single weight unless it is a variable font.
+    ///`auto` is taken as `normal`, since the font file is not looked into.
+    pub weight: (f32, f32),
+    pub style: FontFaceStyle,
+    ///The range of widths the face covers, as percentages of the normal width.
+    pub stretch: (f32, f32),
+    ///The code points the face has glyphs for, every code point if empty.
+    pub unicode_range: Vec<RangeInclusive<u32>>,
+    pub display: FontDisplay,
+    pub span: Span,
+}
+
+///An entry of the `src` descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    ///A font file, with the format and the font technologies it needs if they are given, lowercased.
+    Url {
+        url: String,
+        format: Option<String>,
+        tech: Vec<String>,
+    },
+    ///A font installed on the system, by its full name or postscript name.
+    Local(String),
+}
+
+///The `font-style` descriptor, which can be a range of oblique angles unlike the property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontFaceStyle {
+    Normal,
+    Italic,
+    ///The range of slant angles in degrees.
+    Oblique(f32, f32),
+}
+
+keyword_enum! {
+    ///The `font-display` descriptor, how text is drawn while the font is loading, see https://www.w3.org/TR/css-fonts-4/#font-display-desc
+    pub enum FontDisplay {
+        Auto = "auto",
+        Block = "block",
+        Swap = "swap",
+        Fallback = "fallback",
+        Optional = "optional",
+    }
+}
+
+impl FontFace {
+    ///Parses a `@font-face` rule. Invalid descriptors are dropped and reported in `diagnostics`,
+    ///but a face without a valid `font-family` and `src` is an error.
+    pub fn parse(
+        rule: &AtRule,
+        diagnostics: &mut Vec<CssDiagnostic>,
+    ) -> Result<Self, CssDiagnostic> {
+        let error = |message: &str| {
+            CssDiagnostic::new(CssDiagnosticKind::InvalidAtRule, rule.span, message)
+        };
+        if !rule.prelude.iter().all(ComponentValue::is_whitespace) {
+            return Err(error("`@font-face` does not take a prelude"));
+        }
+        let Some(block) = &rule.block else {
+            return Err(error("`@font-face` needs a block of descriptors"));
+        };
+
+        let mut family = None;
+        let mut sources = None;
+        let mut face = FontFace {
+            family: String::new(),
+            sources: vec![],
+            weight: (FontWeight::NORMAL, FontWeight::NORMAL),
+            style: FontFaceStyle::Normal,
+            stretch: (100.0, 100.0),
+            unicode_range: vec![],
+            display: FontDisplay::Auto,
+            span: rule.span,
+        };
+        let mut parser = Parser::from_component_values(&block.value);
+        let items = parser.parse_list_of_declarations();
+        diagnostics.append(&mut parser.take_diagnostics());
+        for item in items {
+            let declaration = match item {
+                DeclarationOrAtRule::Declaration(declaration) => declaration,
+                DeclarationOrAtRule::AtRule(rule) => {
+                    diagnostics.push(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidAtRule,
+                        rule.span,
+                        format!("`@{}` is not allowed in `@font-face`", rule.name),
+                    ));
+                    continue;
+                }
+            };
+            let name = declaration.name.to_ascii_lowercase();
+            let value = &declaration.value;
+            let parsed = match name.as_str() {
+                _ if declaration.important => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidDeclaration,
+                    declaration.span,
+                    "descriptors can not be `!important`",
+                )),
+                "font-family" => parse_value_with(value, parse_family).map(|v| family = Some(v)),
+                "src" => parse_sources(value, declaration.span).map(|v| sources = Some(v)),
+                "font-weight" => parse_value_with(value, |input| parse_range(input, parse_weight))
+                    .map(|v| face.weight = v.unwrap_or((FontWeight::NORMAL, FontWeight::NORMAL))),
+                "font-style" => parse_value_with(value, parse_style).map(|v| face.style = v),
+                "font-stretch" | "font-width" => parse_value_with(value, |input| {
+                    parse_range(input, |input| FontStretch::parse(input).map(|v| v.0))
+                })
+                .map(|v| face.stretch = v.unwrap_or((100.0, 100.0))),
+                "unicode-range" => parse_value_with(value, |input| {
+                    input.parse_comma_separated(parse_unicode_range)
+                })
+                .map(|v| face.unicode_range = v),
+                "font-display" => parse_value_with(value, |input| FontDisplay::parse(input))
+                    .map(|v| face.display = v),
+                _ if IGNORED_DESCRIPTORS.contains(&name.as_str()) => Ok(()),
+                _ => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::UnknownProperty,
+                    declaration.span,
+                    format!("`{}` is not a descriptor of `@font-face`", declaration.name),
+                )),
+            };
+            if let Err(diagnostic) = parsed {
+                diagnostics.push(diagnostic);
+            }
+        }
+        face.family = family.ok_or_else(|| error("`@font-face` needs a `font-family`"))?;
+        face.sources = sources.ok_or_else(|| error("`@font-face` needs a `src`"))?;
+        Ok(face)
+    }
+
+    ///Whether the face has a glyph for `character` according to its `unicode-range`.
+    pub fn covers(&self, character: char) -> bool {
+        self.unicode_range.is_empty()
+            || self
+                .unicode_range
+                .iter()
+                .any(|range| range.contains(&(character as u32)))
+    }
+}
+
+///A family name, which unlike in `font-family` can not be a generic family.
+fn parse_family(input: &mut ValueParser) -> Result<String, CssDiagnostic> {
+    let value = input.consume()?;
+    match value.token() {
+        Some(CSSToken::StringToken { string }) => Ok(string.to_string()),
+        Some(CSSToken::IdentToken { value: ident }) => {
+            let mut idents = vec![ident.to_string()];
+            while let Some(CSSToken::IdentToken { value }) = input.peek().and_then(|v| v.token()) {
+                idents.push(value.to_string());
+                input.consume()?;
+            }
+            if let [ident] = &idents[..]
+                && GenericFamily::from_keyword(ident).is_some()
+            {
+                return Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidValue,
+                    value.span(),
+                    format!("`{ident}` is a generic family, which a font face can not be"),
+                ));
+            }
+            Ok(idents.join(" "))
+        }
+        _ => Err(unexpected(value, "a font family name")),
+    }
+}
+
+///Parses `src`, dropping the entries that are invalid like https://www.w3.org/TR/css-fonts-4/#src-desc asks for.
+fn parse_sources(values: &[ComponentValue], span: Span) -> Result<Vec<FontSource>, CssDiagnostic> {
+    let sources: Vec<FontSource> = values
+        .split(|v| v.token() == Some(&CSSToken::CommaToken))
+        .filter_map(|source| parse_value_with(source, parse_source).ok())
+        .collect();
+    if sources.is_empty() {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            "expected `url()` or `local()` font sources",
+        ));
+    }
+    Ok(sources)
+}
+
+fn parse_source(input: &mut ValueParser) -> Result<FontSource, CssDiagnostic> {
+    let value = input.consume()?;
+    let url = match value {
+        ComponentValue::PreservedToken(token) => match &token.token {
+            CSSToken::URLToken { value } => value.to_string(),
+            _ => return Err(unexpected(value, "`url()` or `local()`")),
+        },
+        ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("local") => {
+            return parse_value_with(&function.value, parse_family).map(FontSource::Local);
+        }
+        ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("url") => {
+            parse_value_with(&function.value, |input| {
+                let value = input.consume()?;
+                match value.token() {
+                    Some(CSSToken::StringToken { string }) => Ok(string.to_string()),
+                    _ => Err(unexpected(value, "a url string")),
+                }
+            })?
+        }
+        _ => return Err(unexpected(value, "`url()` or `local()`")),
+    };
+
+    let mut format = None;
+    let mut tech = vec![];
+    while let Some(value) = input.peek() {
+        let ComponentValue::Function(function) = value else {
+            return Err(unexpected(input.consume()?, "`format()` or `tech()`"));
+        };
+        input.consume()?;
+        if function.name.eq_ignore_ascii_case("format") && format.is_none() && tech.is_empty() {
+            format = Some(parse_value_with(&function.value, |input| {
+                let value = input.consume()?;
+                match value.token() {
+                    Some(CSSToken::StringToken { string }) => Ok(string.to_ascii_lowercase()),
+                    Some(CSSToken::IdentToken { value }) => Ok(value.to_ascii_lowercase()),
+                    _ => Err(unexpected(value, "a font format like `woff2`")),
+                }
+            })?);
+        } else if function.name.eq_ignore_ascii_case("tech") && tech.is_empty() {
+            tech = parse_value_with(&function.value, |input| {
+                input.parse_comma_separated(|input| {
+                    input.expect_ident().map(|(v, _)| v.to_ascii_lowercase())
+                })
+            })?;
+        } else {
+            return Err(unexpected(value, "`format()` or `tech()`"));
+        }
+    }
+    Ok(FontSource::Url { url, format, tech })
+}
+
+///Parses `auto` or one or two values, which are swapped if the first is larger. `auto` results in `None`.
+fn parse_range(
+    input: &mut ValueParser,
+    mut parse: impl FnMut(&mut ValueParser) -> Result<f32, CssDiagnostic>,
+) -> Result<Option<(f32, f32)>, CssDiagnostic> {
+    if input.try_parse(|v| v.expect_keyword("auto")).is_ok() {
+        return Ok(None);
+    }
+    let first = parse(input)?;
+    let second = match input.is_exhausted() {
+        true => first,
+        false => parse(input)?,
+    };
+    Ok(Some((first.min(second), first.max(second))))
+}
+
+///An absolute font weight, as `bolder` and `lighter` have nothing to be relative to.
+fn parse_weight(input: &mut ValueParser) -> Result<f32, CssDiagnostic> {
+    let span = input.peek().map(|v| v.span()).unwrap_or_default();
+    match FontWeight::parse(input)? {
+        FontWeight::Absolute(weight) => Ok(weight),
+        FontWeight::Bolder | FontWeight::Lighter => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            "`@font-face` needs an absolute font weight like `bold` or `600`",
+        )),
+    }
+}
+
+fn parse_style(input: &mut ValueParser) -> Result<FontFaceStyle, CssDiagnostic> {
+    let (ident, span) = input.expect_ident()?;
+    match ident.to_ascii_lowercase().as_str() {
+        "auto" | "normal" => Ok(FontFaceStyle::Normal),
+        "italic" => Ok(FontFaceStyle::Italic),
+        "oblique" if input.is_exhausted() => Ok(FontFaceStyle::Oblique(14.0, 14.0)),
+        "oblique" => {
+            let first = parse_oblique_angle(input)?;
+            let second = match input.is_exhausted() {
+                true => first,
+                false => parse_oblique_angle(input)?,
+            };
+            Ok(FontFaceStyle::Oblique(first.min(second), first.max(second)))
+        }
+        _ => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            format!("expected `normal`, `italic` or `oblique`, found `{ident}`"),
+        )),
+    }
+}
+
+fn parse_oblique_angle(input: &mut ValueParser) -> Result<f32, CssDiagnostic> {
+    let span = input.peek().map(|v| v.span()).unwrap_or_default();
+    let angle = input.expect_angle()?;
+    match (-90.0..=90.0).contains(&angle) {
+        true => Ok(angle),
+        false => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            "an oblique angle is between -90deg and 90deg",
+        )),
+    }
+}
+
+fn parse_unicode_range(input: &mut ValueParser) -> Result<RangeInclusive<u32>, CssDiagnostic> {
+    let value = input.consume()?;
+    match value.token() {
+        // https://www.w3.org/TR/css-syntax-3/#urange-syntax
+        Some(CSSToken::UnicodeRangeToken { start, end })
+            if start <= end && *start <= MAX_CODE_POINT =>
+        {
+            Ok(*start..=(*end).min(MAX_CODE_POINT))
+        }
+        Some(CSSToken::UnicodeRangeToken { .. }) => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            value.span(),
+            "the start of a unicode range has to be a code point that is not after its end",
+        )),
+        _ => Err(unexpected(value, "a unicode range like `U+0-7F`")),
+    }
+}
@@ -0,0 +1,416 @@
+//! `@import` of https://www.w3.org/TR/css-cascade-5/#at-import, which splits a stylesheet across several files.
+//! [`load_stylesheet`] loads a stylesheet with everything it imports through a [`StylesheetLoader`],
+//! and [`crate::cascade::Cascade::add_loaded_stylesheet`] adds the imported rules in place of the `@import` rules.
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{AtRule, ComponentValue, Parser, Rule, Stylesheet};
+use crate::span::Span;
+use crate::supports::SupportsCondition;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+///Where the stylesheets `@import` refers to are loaded from.
+pub trait StylesheetLoader {
+    ///Resolves the url of an `@import` against the url of the stylesheet the rule is in.
+    ///Urls are paths relative to the importing stylesheet by default, see [`resolve_path`].
+    fn resolve(&self, url: &str, base: &str) -> String {
+        resolve_path(url, base)
+    }
+
+    ///Loads the source of the stylesheet at a resolved url.
+    fn load(&mut self, url: &str) -> Result<Cow<'static, str>, LoadError>;
+}
+
+///Why a stylesheet could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    NotFound,
+    Io(std::io::Error),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NotFound => write!(f, "no such stylesheet"),
+            LoadError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+///Loads stylesheets from the files under a root directory, for native builds.
+pub struct FileSystemLoader {
+    root: PathBuf,
+}
+
+impl FileSystemLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl StylesheetLoader for FileSystemLoader {
+    fn load(&mut self, url: &str) -> Result<Cow<'static, str>, LoadError> {
+        match std::fs::read_to_string(self.root.join(url)) {
+            Ok(source) => Ok(Cow::Owned(source)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Err(LoadError::NotFound),
+            Err(error) => Err(LoadError::Io(error)),
+        }
+    }
+}
+
+///Loads stylesheets baked into the binary, like the `STYLESHEETS` jessie-build generates from the `styles` directory of an app.
+pub struct EmbeddedLoader {
+    ///The path of every stylesheet relative to the root directory, with its source.
+    files: &'static [(&'static str, &'static str)],
+}
+
+impl EmbeddedLoader {
+    pub fn new(files: &'static [(&'static str, &'static str)]) -> Self {
+        Self { files }
+    }
+}
+
+impl StylesheetLoader for EmbeddedLoader {
+    fn load(&mut self, url: &str) -> Result<Cow<'static, str>, LoadError> {
+        self.files
+            .iter()
+            .find(|(path, _)| *path == url)
+            .map(|(_, source)| Cow::Borrowed(*source))
+            .ok_or(LoadError::NotFound)
+    }
+}
+
+///Resolves a path relative to the directory of `base`, or to the root directory if it starts with a `/`.
+///The result has no `.` and `..` segments, so the same stylesheet always resolves to the same url.
+pub fn resolve_path(url: &str, base: &str) -> String {
+    let dir = match url.starts_with('/') {
+        true => "",
+        false => base.rsplit_once('/').map_or("", |(dir, _)| dir),
+    };
+    let mut segments = vec![];
+    for segment in dir.split('/').chain(url.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+///An `@import url layer(name) supports(condition) media-queries;` rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRule<'a> {
+    ///The url as written, before it is resolved against the importing stylesheet.
+    pub url: Cow<'a, str>,
+    ///The name of the layer the stylesheet is imported into, which is empty for `layer` without a name, an anonymous layer.
+    ///`None` if the stylesheet is not imported into a layer.
+    pub layer: Option<Vec<ComponentValue<'a>>>,
+    ///The condition of `supports()`, which has to hold for the stylesheet to be imported.
+    pub supports: Option<Vec<ComponentValue<'a>>>,
+    ///The media query list the imported rules apply under, empty if they always apply.
+    pub media: Vec<ComponentValue<'a>>,
+    pub span: Span,
+}
+
+impl<'a> ImportRule<'a> {
+    pub fn parse(rule: &AtRule<'a>) -> Result<Self, CssDiagnostic> {
+        let error = |message: &str| {
+            CssDiagnostic::new(CssDiagnosticKind::InvalidAtRule, rule.span, message)
+        };
+        if rule.block.is_some() {
+            return Err(error("`@import` can not have a block"));
+        }
+        let mut prelude = skip_whitespace(&rule.prelude);
+        let url = match prelude.first() {
+            Some(ComponentValue::PreservedToken(token)) => match &token.token {
+                CSSToken::StringToken { string } => string.clone(),
+                CSSToken::URLToken { value } => value.clone(),
+                _ => return Err(error("`@import` needs a url or a string")),
+            },
+            Some(ComponentValue::Function(function))
+                if function.name.eq_ignore_ascii_case("url") =>
+            {
+                let mut values = function.value.iter().filter(|v| !v.is_whitespace());
+                match (values.next().and_then(|v| v.token()), values.next()) {
+                    (Some(CSSToken::StringToken { string }), None) => string.clone(),
+                    _ => return Err(error("`url()` needs a single string")),
+                }
+            }
+            _ => return Err(error("`@import` needs a url or a string")),
+        };
+        prelude = skip_whitespace(&prelude[1..]);
+
+        let layer = match prelude.first() {
+            Some(ComponentValue::PreservedToken(token)) if matches!(&token.token, CSSToken::IdentToken { value } if value.eq_ignore_ascii_case("layer")) => {
+                Some(vec![])
+            }
+            Some(ComponentValue::Function(function))
+                if function.name.eq_ignore_ascii_case("layer") =>
+            {
+                if function.value.iter().all(ComponentValue::is_whitespace) {
+                    return Err(error("`layer()` needs a layer name"));
+                }
+                Some(function.value.clone())
+            }
+            _ => None,
+        };
+        if layer.is_some() {
+            prelude = skip_whitespace(&prelude[1..]);
+        }
+
+        let supports = match prelude.first() {
+            Some(ComponentValue::Function(function))
+                if function.name.eq_ignore_ascii_case("supports") =>
+            {
+                Some(function.value.clone())
+            }
+            _ => None,
+        };
+        if supports.is_some() {
+            prelude = skip_whitespace(&prelude[1..]);
+        }
+        Ok(Self {
+            url,
+            layer,
+            supports,
+            media: prelude.to_vec(),
+            span: rule.span,
+        })
+    }
+
+    ///Detaches the rule from the input it was parsed from.
+    pub fn into_owned(self) -> ImportRule<'static> {
+        let owned = |values: Vec<ComponentValue>| {
+            values
+                .into_iter()
+                .map(ComponentValue::into_owned)
+                .collect::<Vec<_>>()
+        };
+        ImportRule {
+            url: Cow::Owned(self.url.into_owned()),
+            layer: self.layer.map(owned),
+            supports: self.supports.map(owned),
+            media: owned(self.media),
+            span: self.span,
+        }
+    }
+}
+
+fn skip_whitespace<'v, 'a>(mut values: &'v [ComponentValue<'a>]) -> &'v [ComponentValue<'a>] {
+    while let [first, rest @ ..] = values
+        && first.is_whitespace()
+    {
+        values = rest;
+    }
+    values
+}
+
+///A stylesheet loaded by [`load_stylesheet`], with the stylesheets its `@import` rules load.
+#[derive(Debug, Clone)]
+pub struct LoadedStylesheet {
+    ///The resolved url of the stylesheet.
+    pub url: String,
+    pub source: Cow<'static, str>,
+    pub stylesheet: Stylesheet<'static>,
+    ///The valid `@import` rules of the stylesheet in order.
+    pub imports: Vec<Import>,
+    ///The parse errors of the stylesheet and the errors of its `@import` rules, with spans into [`LoadedStylesheet::source`].
+    pub diagnostics: Vec<CssDiagnostic>,
+}
+
+///An `@import` rule of a [`LoadedStylesheet`] with the stylesheet it loaded.
+#[derive(Debug, Clone)]
+pub struct Import {
+    ///The index of the rule in the rules of the importing stylesheet.
+    pub index: usize,
+    pub rule: ImportRule<'static>,
+    ///`None` if the stylesheet could not be loaded, would import itself or has a false `supports()` condition,
+    ///in which case the rule is ignored. A stylesheet imported several times is shared by its imports.
+    pub stylesheet: Option<Arc<LoadedStylesheet>>,
+}
+
+impl LoadedStylesheet {
+    ///The stylesheet and the stylesheets it imports, directly or not, each once even if it is imported several times.
+    pub fn stylesheets(&self) -> Vec<&LoadedStylesheet> {
+        let mut out = vec![self];
+        let mut i = 0;
+        while let Some(stylesheet) = out.get(i) {
+            for import in &stylesheet.imports {
+                if let Some(imported) = &import.stylesheet
+                    && !out.iter().any(|v| v.url == imported.url)
+                {
+                    out.push(imported);
+                }
+            }
+            i += 1;
+        }
+        out
+    }
+
+    ///Renders the diagnostics of the stylesheet and all the stylesheets it imports, each against its own source.
+    pub fn render_diagnostics(&self) -> String {
+        self.stylesheets()
+            .into_iter()
+            .flat_map(|stylesheet| {
+                stylesheet
+                    .diagnostics
+                    .iter()
+                    .map(|v| v.render(&stylesheet.source, &stylesheet.url))
+            })
+            .collect()
+    }
+}
+
+///Loads the stylesheet at `url` and, recursively, the stylesheets it imports.
+///Only the stylesheet itself failing to load is an error, imports that fail to load or form a cycle are reported in [`LoadedStylesheet::diagnostics`].
+pub fn load_stylesheet(
+    loader: &mut impl StylesheetLoader,
+    url: &str,
+) -> Result<LoadedStylesheet, LoadError> {
+    let url = resolve_path(url, "");
+    let source = loader.load(&url)?;
+    let mut loading = Loading {
+        importers: vec![],
+        loaded: HashMap::new(),
+    };
+    Ok(load_imports(loader, url, source, &mut loading).0)
+}
+
+///What the stylesheets a [`load_stylesheet`] call loads share.
+struct Loading {
+    ///The urls of the stylesheets being loaded, each imported by the one before it, to detect cycles.
+    importers: Vec<String>,
+    ///The stylesheets loaded so far by resolved url, so that one imported along several paths is only loaded and parsed once.
+    ///The ones that import a stylesheet importing them are not kept, since which of their imports form a cycle depends on the path.
+    loaded: HashMap<String, Arc<LoadedStylesheet>>,
+}
+
+///Parses a loaded stylesheet and loads its imports.
+///Also returns the index in [`Loading::importers`] of the first stylesheet that it or the stylesheets it imports import again,
+///`usize::MAX` if none does.
+fn load_imports(
+    loader: &mut impl StylesheetLoader,
+    url: String,
+    source: Cow<'static, str>,
+    loading: &mut Loading,
+) -> (LoadedStylesheet, usize) {
+    let mut parser = Parser::new(&source);
+    let stylesheet = parser.parse_stylesheet().into_owned();
+    let mut diagnostics = parser.take_diagnostics();
+    loading.importers.push(url.clone());
+    let mut cycle_start = usize::MAX;
+
+    let mut imports = vec![];
+    // `@import` has to come before all other rules but `@charset` and `@layer` statements, see https://www.w3.org/TR/css-cascade-5/#at-import
+    let mut other_rules = false;
+    for (index, rule) in stylesheet.rules.iter().enumerate() {
+        let rule = match rule {
+            Rule::AtRule(rule) if rule.name.eq_ignore_ascii_case("import") => rule,
+            Rule::AtRule(rule)
+                if rule.name.eq_ignore_ascii_case("charset")
+                    || rule.name.eq_ignore_ascii_case("layer") && rule.block.is_none() =>
+            {
+                continue;
+            }
+            _ => {
+                other_rules = true;
+                continue;
+            }
+        };
+        if other_rules {
+            diagnostics.push(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidAtRule,
+                rule.span,
+                "`@import` has to come before all other rules but `@charset` and `@layer` statements",
+            ));
+            continue;
+        }
+        let rule = match ImportRule::parse(rule) {
+            Ok(rule) => rule,
+            Err(diagnostic) => {
+                diagnostics.push(diagnostic);
+                continue;
+            }
+        };
+        // A stylesheet whose `supports()` condition is false is not loaded at all.
+        if let Some(supports) = &rule.supports {
+            match SupportsCondition::parse_import(supports) {
+                Ok(condition) if !condition.evaluate() => {
+                    imports.push(Import {
+                        index,
+                        rule: rule.into_owned(),
+                        stylesheet: None,
+                    });
+                    continue;
+                }
+                Ok(_) => {}
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    continue;
+                }
+            }
+        }
+        let resolved = loader.resolve(&rule.url, &url);
+        let stylesheet = if let Some(start) = loading.importers.iter().position(|v| *v == resolved)
+        {
+            cycle_start = cycle_start.min(start);
+            let cycle = loading.importers[start..]
+                .iter()
+                .chain([&resolved])
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            diagnostics.push(CssDiagnostic::new(
+                CssDiagnosticKind::ImportCycle,
+                rule.span,
+                format!("`{resolved}` imports itself through {cycle}"),
+            ));
+            None
+        } else if let Some(loaded) = loading.loaded.get(&resolved) {
+            Some(loaded.clone())
+        } else {
+            match loader.load(&resolved) {
+                Ok(source) => {
+                    let (loaded, start) = load_imports(loader, resolved.clone(), source, loading);
+                    let loaded = Arc::new(loaded);
+                    // It is at `importers.len()` while it is loaded, so a cycle starting before that goes through this stylesheet.
+                    if start >= loading.importers.len() {
+                        loading.loaded.insert(resolved, loaded.clone());
+                    }
+                    cycle_start = cycle_start.min(start);
+                    Some(loaded)
+                }
+                Err(error) => {
+                    diagnostics.push(CssDiagnostic::new(
+                        CssDiagnosticKind::ImportFailed,
+                        rule.span,
+                        format!("could not load `{resolved}`: {error}"),
+                    ));
+                    None
+                }
+            }
+        };
+        imports.push(Import {
+            index,
+            rule: rule.into_owned(),
+            stylesheet,
+        });
+    }
+    loading.importers.pop();
+    let loaded = LoadedStylesheet {
+        url,
+        source,
+        stylesheet,
+        imports,
+        diagnostics,
+    };
+    (loaded, cycle_start)
+}
@@ -0,0 +1,308 @@
+//! Reparses only the part of a stylesheet an edit touched, for editing styles while the app is running.
+//! The top-level rules before the edit are kept as they are, the input is tokenized and parsed again from the end
+//! of the last of them until the parser ends a rule where a rule ended before the edit too, and the rules after that
+//! are kept with their spans moved to where they are now.
+use crate::Tokenizer;
+use crate::diagnostics::CssDiagnostic;
+use crate::matching::{Element, matches_selector_list};
+use crate::nesting::{self, FlatRule};
+use crate::parser::{ComponentValue, Parser, Rule, SimpleBlock, Stylesheet};
+use crate::selectors::SelectorList;
+use crate::span::{SourceLocation, Span};
+use crate::stream::TokenStream;
+use std::ops::Range;
+
+///The group rules whose prelude only decides whether the style rules in them apply, so a change to them only affects
+///the elements those style rules match. A changed `@layer` block can reorder the layers of other rules.
+const CONDITIONAL_GROUP_RULES: [&str; 5] =
+    ["media", "container", "supports", "scope", "starting-style"];
+
+///A stylesheet along with its source, which can be edited without parsing all of it again.
+#[derive(Debug, Clone)]
+pub struct IncrementalStylesheet {
+    source: String,
+    stylesheet: Stylesheet<'static>,
+    diagnostics: Vec<CssDiagnostic>,
+}
+
+///Replaces the bytes of `range` in the source with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+///The top-level rules an edit changed. Rules that were parsed again but came out the same, other than where they are,
+///are not part of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StylesheetChange {
+    ///The rules that were replaced, as they were before the edit.
+    pub removed: Vec<Rule<'static>>,
+    ///The indices of the rules that replaced them in [`IncrementalStylesheet::stylesheet`].
+    pub inserted: Range<usize>,
+}
+
+///The elements whose style a [`StylesheetChange`] can change, see [`StylesheetChange::invalidation`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invalidation<'a> {
+    ///Only the elements one of the selectors of a removed or inserted style rule matches.
+    Selectors(Vec<SelectorList<'a>>),
+    ///Every element, like when a `@property` or a `@keyframes` rule changed.
+    All,
+}
+
+///Moves the locations after an edit to where they are once it is applied.
+#[derive(Debug, Clone, Copy)]
+struct Shift {
+    ///Where the edit ended before it was applied.
+    from: SourceLocation,
+    ///Where the replacement ends.
+    to: SourceLocation,
+}
+
+impl IncrementalStylesheet {
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let mut parser = Parser::new(&source);
+        let stylesheet = parser.parse_stylesheet().into_owned();
+        let mut diagnostics = parser.take_diagnostics();
+        diagnostics.sort_by_key(|v| v.span.start.offset);
+        Self {
+            source,
+            stylesheet,
+            diagnostics,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn stylesheet(&self) -> &Stylesheet<'static> {
+        &self.stylesheet
+    }
+
+    ///Returns the parse errors of the current source that [`Parser::diagnostics`] would return after parsing all of it,
+    ///sorted by where they start.
+    pub fn diagnostics(&self) -> &[CssDiagnostic] {
+        &self.diagnostics
+    }
+
+    ///Applies the edit to the source and reparses the rules it touched, returning which rules changed.
+    ///# Panics
+    ///If the range of the edit is out of the source or does not start and end on a char boundary, like [`String::replace_range`].
+    pub fn edit(&mut self, edit: TextEdit) -> StylesheetChange {
+        let TextEdit { range, replacement } = edit;
+        let rules = std::mem::take(&mut self.stylesheet.rules);
+
+        // The rules that end before the edit stay the same, as every top-level rule ends with a token the input after
+        // it can not change, like a `}` or a `;`. The last rule can end because the input ran out, so it is always
+        // parsed again if the edit comes after it.
+        let first = rules
+            .iter()
+            .position(|v| v.span().end.offset >= range.start)
+            .unwrap_or(rules.len())
+            .min(rules.len().saturating_sub(1));
+        let start = match first {
+            0 => SourceLocation::default(),
+            first => rules[first - 1].span().end,
+        };
+        let shift = Shift {
+            from: start.advance(&self.source[start.offset..range.end]),
+            to: start
+                .advance(&self.source[start.offset..range.start])
+                .advance(&replacement),
+        };
+        let old_rules_end: Vec<usize> = rules.iter().map(|v| v.span().end.offset).collect();
+        self.source.replace_range(range, &replacement);
+
+        // Once the parser ends a rule after the edit at the same place a rule ended before it, the input that follows
+        // is the same as before, so the rules that follow are the same too.
+        let mut parser = Parser::from_token_stream(TokenStream::from_tokenizer(
+            Tokenizer::starting_at(&self.source, start),
+        ));
+        let mut inserted = vec![];
+        let mut resync = None;
+        while let Some(rule) = parser.consume_next_rule(true) {
+            let end = rule.span().end;
+            inserted.push(rule.into_owned());
+            if end.offset < shift.to.offset {
+                continue;
+            }
+            let old_end = end.offset - shift.to.offset + shift.from.offset;
+            if let Ok(index) = old_rules_end[first..].binary_search(&old_end) {
+                resync = Some((first + index + 1, end));
+                break;
+            }
+        }
+        let mut diagnostics = parser.take_diagnostics();
+
+        // The parse errors before the reparsed input stay, the ones after it are moved, and the ones in between are the
+        // ones the parser found again.
+        let (old_end, new_end) = match resync {
+            Some((index, end)) => (rules[index - 1].span().end.offset, end.offset),
+            None => (usize::MAX, usize::MAX),
+        };
+        diagnostics.retain(|v| v.span.start.offset < new_end);
+        diagnostics.sort_by_key(|v| v.span.start.offset);
+        let (before, after): (Vec<_>, Vec<_>) = std::mem::take(&mut self.diagnostics)
+            .into_iter()
+            .filter(|v| v.span.start.offset < start.offset || v.span.start.offset >= old_end)
+            .partition(|v| v.span.start.offset < start.offset);
+        self.diagnostics = before;
+        self.diagnostics.append(&mut diagnostics);
+        self.diagnostics.extend(after.into_iter().map(|mut v| {
+            v.span = shift.span(v.span);
+            v
+        }));
+
+        let mut rules = rules.into_iter();
+        let mut out: Vec<Rule<'static>> = rules.by_ref().take(first).collect();
+        let removed_end = resync.map_or(old_rules_end.len(), |(index, _)| index);
+        let mut removed: Vec<Rule<'static>> = rules.by_ref().take(removed_end - first).collect();
+        let kept = rules.map(|mut v| {
+            shift.rule(&mut v);
+            v
+        });
+
+        // Rules that were parsed again without changing are not reported.
+        let same_start = removed
+            .iter()
+            .zip(&inserted)
+            .take_while(|(old, new)| shift.moved(old) == **new)
+            .count();
+        let same_end = removed[same_start..]
+            .iter()
+            .rev()
+            .zip(inserted[same_start..].iter().rev())
+            .take_while(|(old, new)| shift.moved(old) == **new)
+            .count();
+        removed.truncate(removed.len() - same_end);
+        removed.drain(..same_start);
+        let change = StylesheetChange {
+            removed,
+            inserted: out.len() + same_start..out.len() + inserted.len() - same_end,
+        };
+
+        out.append(&mut inserted);
+        out.extend(kept);
+        self.stylesheet.rules = out;
+        change
+    }
+}
+
+impl StylesheetChange {
+    ///Returns the elements whose style the change can change, given the stylesheet the change was made to.
+    ///Nested style rules are matched with the selectors they have once nesting is desugared.
+    pub fn invalidation<'a>(&'a self, stylesheet: &'a Stylesheet<'static>) -> Invalidation<'a> {
+        let mut diagnostics = vec![];
+        let mut rules = nesting::flatten(&self.removed, &mut diagnostics);
+        rules.extend(nesting::flatten(
+            &stylesheet.rules[self.inserted.clone()],
+            &mut diagnostics,
+        ));
+        let mut selectors = vec![];
+        match collect_selectors(rules, &mut selectors) {
+            true => Invalidation::Selectors(selectors),
+            false => Invalidation::All,
+        }
+    }
+}
+
+///Collects the selectors of the style rules, returning `false` if a rule can affect elements none of them match.
+fn collect_selectors<'a>(rules: Vec<FlatRule<'a>>, out: &mut Vec<SelectorList<'a>>) -> bool {
+    for rule in rules {
+        match rule {
+            FlatRule::Style(rule) => out.push(rule.selectors),
+            FlatRule::Group(rule)
+                if CONDITIONAL_GROUP_RULES
+                    .iter()
+                    .any(|v| rule.name.eq_ignore_ascii_case(v)) =>
+            {
+                if !collect_selectors(rule.rules, out) {
+                    return false;
+                }
+            }
+            FlatRule::Group(_) | FlatRule::AtRule(_) => return false,
+        }
+    }
+    true
+}
+
+impl Invalidation<'_> {
+    ///Whether the style of the element has to be computed again.
+    pub fn affects<E: Element>(&self, element: &E) -> bool {
+        match self {
+            Invalidation::Selectors(selectors) => selectors
+                .iter()
+                .any(|v| matches_selector_list(v, element, None)),
+            Invalidation::All => true,
+        }
+    }
+}
+
+impl Shift {
+    ///Locations before the end of the edit are left as they are, which is only right for the ones before the edit.
+    ///The others are in text that was replaced, so nothing points to them anymore.
+    fn location(&self, location: SourceLocation) -> SourceLocation {
+        if location.offset < self.from.offset {
+            return location;
+        }
+        SourceLocation {
+            offset: location.offset - self.from.offset + self.to.offset,
+            line: location.line - self.from.line + self.to.line,
+            column: match location.line == self.from.line {
+                true => location.column - self.from.column + self.to.column,
+                false => location.column,
+            },
+        }
+    }
+
+    fn span(&self, span: Span) -> Span {
+        Span {
+            start: self.location(span.start),
+            end: self.location(span.end),
+        }
+    }
+
+    fn moved(&self, rule: &Rule<'static>) -> Rule<'static> {
+        let mut rule = rule.clone();
+        self.rule(&mut rule);
+        rule
+    }
+
+    fn rule(&self, rule: &mut Rule) {
+        match rule {
+            Rule::QualifiedRule(rule) => {
+                rule.span = self.span(rule.span);
+                self.values(&mut rule.prelude);
+                self.block(&mut rule.block);
+            }
+            Rule::AtRule(rule) => {
+                rule.span = self.span(rule.span);
+                self.values(&mut rule.prelude);
+                if let Some(block) = &mut rule.block {
+                    self.block(block);
+                }
+            }
+        }
+    }
+
+    fn block(&self, block: &mut SimpleBlock) {
+        block.span = self.span(block.span);
+        self.values(&mut block.value);
+    }
+
+    fn values(&self, values: &mut [ComponentValue]) {
+        for value in values {
+            match value {
+                ComponentValue::PreservedToken(token) => token.span = self.span(token.span),
+                ComponentValue::Function(function) => {
+                    function.span = self.span(function.span);
+                    self.values(&mut function.value);
+                }
+                ComponentValue::SimpleBlock(block) => self.block(block),
+            }
+        }
+    }
+}
@@ -1,30 +1,135 @@
+use diagnostics::{CssDiagnostic, CssDiagnosticKind};
 use putbackpeekmore::PutBackPeekMore;
+use span::{SourceLocation, Span};
 use std::{borrow::Cow, str::Chars};
+pub mod animation;
+pub mod cascade;
+pub mod diagnostics;
+pub mod font_face;
+pub mod import;
+pub mod incremental;
+pub mod matching;
+pub mod media;
+pub mod nesting;
+pub mod parser;
+pub mod printer;
+pub mod properties;
+pub mod selectors;
+pub mod serializer;
+pub mod span;
+pub mod stream;
+pub mod style_attribute;
+pub mod supports;
 mod tests;
+pub mod values;
 
 pub struct Tokenizer<'a> {
+    input: &'a str,
     process: PutBackPeekMore<Chars<'a>, 7>,
-    parse_error: bool,
+    ///The byte offset of the next input code point.
+    position: usize,
+    ///The location of the last token boundary, used to compute line and column numbers without rescanning the input.
+    location: SourceLocation,
+    ///The location the token that is being consumed starts at.
+    token_start: SourceLocation,
+    diagnostics: Vec<CssDiagnostic>,
+    ///Whether the <EOF-token> was returned by the [`Iterator`] implementation.
+    finished: bool,
+    ///Whether `U+` followed by a hex digit or `?` starts a <unicode-range-token>, see [`Tokenizer::allow_unicode_ranges`].
+    unicode_ranges_allowed: bool,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
+            input,
             process: PutBackPeekMore::new(input.chars()),
-            parse_error: false,
+            position: 0,
+            location: SourceLocation::default(),
+            token_start: SourceLocation::default(),
+            diagnostics: vec![],
+            finished: false,
+            unicode_ranges_allowed: false,
         }
     }
 
-    pub fn consume_token(&mut self) -> CSSToken {
+    ///Creates a tokenizer that starts at `location` instead of the start of `input`, to tokenize only a part of it
+    ///like [`crate::incremental`] does. `location` has to be where a token of `input` starts or ends.
+    ///The spans of the tokens point into the whole `input`.
+    pub fn starting_at(input: &'a str, location: SourceLocation) -> Self {
+        Self {
+            process: PutBackPeekMore::new(input[location.offset..].chars()),
+            position: location.offset,
+            location,
+            token_start: location,
+            ..Self::new(input)
+        }
+    }
+
+    ///Makes the tokenizer return <unicode-range-token>s, which is only done for the value of the `unicode-range` descriptor of `@font-face`,
+    ///see https://drafts.csswg.org/css-syntax-3/#consume-unicode-range-value
+    ///Otherwise `u+a` would not be a selector anymore.
+    pub fn allow_unicode_ranges(&mut self) {
+        self.unicode_ranges_allowed = true;
+    }
+
+    ///Returns the input this tokenizer was created with, which is what [`Span`]s point into.
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn consume_code_point(&mut self) -> Option<char> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-the-next-input-code-point
+        let next = self.process.next();
+        if let Some(v) = next {
+            self.position += v.len_utf8();
+        }
+        next
+    }
+
+    pub fn reconsume_code_point(&mut self, input: Option<char>) {
+        // https://www.w3.org/TR/css-syntax-3/#reconsume-the-current-input-code-point
+        if let Some(v) = input {
+            self.position -= v.len_utf8();
+        }
+        self.process.put_back(input);
+    }
+
+    pub fn location(&mut self) -> SourceLocation {
+        // Tokens only ever move forward, so the line and column can be advanced from the last computed location.
+        // Reconsuming can still step back over it, in which case it is computed again from the start.
+        if self.position < self.location.offset {
+            self.location = SourceLocation::default();
+        }
+        self.location = self
+            .location
+            .advance(&self.input[self.location.offset..self.position]);
+        self.location
+    }
+
+    pub fn next_token(&mut self) -> SpannedToken<'a> {
+        let token = self.consume_token();
+        SpannedToken {
+            token,
+            span: Span {
+                start: self.token_start,
+                end: self.location(),
+            },
+        }
+    }
+
+    pub fn consume_token(&mut self) -> CSSToken<'a> {
         // https://www.w3.org/TR/css-syntax-3/#consume-token
 
         // This section describes how to consume a token from a stream of code points. It will return a single token of any type.
 
         // Consume comments.
         self.consume_comments();
+        // Comments are not part of any token, so the token starts after them.
+        self.token_start = self.location();
 
         // Consume the next input code point.
-        match self.process.next() {
+        match self.consume_code_point() {
             Some(v) => {
                 // whitespace
                 if Self::is_whitespace(v) {
@@ -74,12 +179,14 @@ impl<'a> Tokenizer<'a> {
                 }
                 // U+002B PLUS SIGN (+)
                 if v == '\u{002b}' {
+                    // The checks below look at the current input code point, so reconsume it before peeking.
+                    self.reconsume_code_point(Some(v));
                     // If the input stream starts with a number, reconsume the current input code point, consume a numeric token, and return it.
                     if self.would_start_number() {
-                        self.process.put_back(Some(v));
                         return self.consume_numeric_token();
                     }
                     // Otherwise, return a <delim-token> with its value set to the current input code point.
+                    self.consume_code_point();
                     return CSSToken::DelimToken { value: v };
                 }
                 // U+002C COMMA (,)
@@ -89,33 +196,39 @@ impl<'a> Tokenizer<'a> {
                 }
                 // U+002D HYPHEN-MINUS (-)
                 if v == '\u{002d}' {
+                    // The checks below look at the current input code point, so reconsume it before peeking.
+                    self.reconsume_code_point(Some(v));
                     // If the input stream starts with a number, reconsume the current input code point, consume a numeric token, and return it.
                     if self.would_start_number() {
-                        self.process.put_back(Some(v));
                         return self.consume_numeric_token();
                     }
                     // Otherwise, if the next 2 input code points are U+002D HYPHEN-MINUS U+003E GREATER-THAN SIGN (->), consume them and return a <CDC-token>.
-                    if self.peek_twin() == (Some('\u{002d}'), Some('\u{003e}')) {
-                        self.process.next();
-                        self.process.next();
+                    if self.process.peek_value(3)
+                        == [Some('\u{002d}'), Some('\u{002d}'), Some('\u{003e}')]
+                    {
+                        self.consume_code_point();
+                        self.consume_code_point();
+                        self.consume_code_point();
                         return CSSToken::CDCToken;
                     }
                     // Otherwise, if the input stream starts with an ident sequence, reconsume the current input code point, consume an ident-like token, and return it.
                     if self.would_start_ident_sequence() {
-                        self.process.put_back(Some(v));
                         return self.consume_ident_like_token();
                     }
                     // Otherwise, return a <delim-token> with its value set to the current input code point.
+                    self.consume_code_point();
                     return CSSToken::DelimToken { value: v };
                 }
                 // U+002E FULL STOP (.)
                 if v == '\u{002e}' {
+                    // The check below looks at the current input code point, so reconsume it before peeking.
+                    self.reconsume_code_point(Some(v));
                     // If the input stream starts with a number, reconsume the current input code point, consume a numeric token, and return it.
                     if self.would_start_number() {
-                        self.process.put_back(Some(v));
                         return self.consume_numeric_token();
                     }
                     // Otherwise, return a <delim-token> with its value set to the current input code point.
+                    self.consume_code_point();
                     return CSSToken::DelimToken { value: v };
                 }
                 // U+003A COLON (:)
@@ -134,9 +247,9 @@ impl<'a> Tokenizer<'a> {
                     if self.process.peek_value(3)
                         == [Some('\u{0021}'), Some('\u{002d}'), Some('\u{002d}')]
                     {
-                        self.process.next();
-                        self.process.next();
-                        self.process.next();
+                        self.consume_code_point();
+                        self.consume_code_point();
+                        self.consume_code_point();
                         return CSSToken::CDOToken;
                     }
                     // Otherwise, return a <delim-token> with its value set to the current input code point.
@@ -160,15 +273,19 @@ impl<'a> Tokenizer<'a> {
                 }
                 // U+005C REVERSE SOLIDUS (\)
                 if v == '\u{005c}' {
+                    // The check below looks at the current input code point, so reconsume it before peeking.
+                    self.reconsume_code_point(Some(v));
                     // If the input stream starts with a valid escape, reconsume the current input code point, consume an ident-like token, and return it.
-                    if let &Some(second) = self.process.peek()
-                        && Self::is_valid_escape(Some(v), Some(second))
-                    {
-                        self.process.put_back(Some(v));
+                    let (first, second) = self.peek_twin();
+                    if Self::is_valid_escape(first, second) {
                         return self.consume_ident_like_token();
                     }
                     // Otherwise, this is a parse error. Return a <delim-token> with its value set to the current input code point.
-                    self.parse_error();
+                    self.consume_code_point();
+                    self.parse_error(
+                        CssDiagnosticKind::InvalidEscape,
+                        "a `\\` followed by a newline does not escape anything outside of strings",
+                    );
                     return CSSToken::DelimToken { value: v };
                 }
                 // U+005D RIGHT SQUARE BRACKET (])
@@ -189,13 +306,28 @@ impl<'a> Tokenizer<'a> {
                 // digit
                 if Self::is_digit(v) {
                     // Reconsume the current input code point, consume a numeric token, and return it.
-                    self.process.put_back(Some(v));
+                    self.reconsume_code_point(Some(v));
                     return self.consume_numeric_token();
                 }
+                // U+0055 LATIN CAPITAL LETTER U (U) or U+0075 LATIN SMALL LETTER U (u)
+                // If unicode ranges allowed is true and the input stream would start a unicode-range, reconsume the current input code point, consume a unicode-range token, and return it.
+                if self.unicode_ranges_allowed && (v == 'U' || v == 'u') {
+                    // Peeking can refill the buffer of `process`, after which the current input code point can not be
+                    // put back anymore, so it is reconsumed before peeking like for `+`.
+                    self.reconsume_code_point(Some(v));
+                    let starts_unicode_range = matches!(
+                        *self.process.peek_value(3),
+                        [_, Some('+'), Some(third)] if Self::is_hex_digit(third) || third == '?'
+                    );
+                    self.consume_code_point();
+                    if starts_unicode_range {
+                        return self.consume_unicode_range_token();
+                    }
+                }
                 // ident-start code point
                 if Self::is_ident_start_code_point(v) {
                     // Reconsume the current input code point, consume an ident-like token, and return it.
-                    self.process.put_back(Some(v));
+                    self.reconsume_code_point(Some(v));
                     return self.consume_ident_like_token();
                 }
                 // anything else
@@ -209,10 +341,20 @@ impl<'a> Tokenizer<'a> {
     }
 
     pub fn is_parse_error(&mut self) -> bool {
-        self.parse_error
+        !self.diagnostics.is_empty()
+    }
+
+    ///Returns the parse errors found so far.
+    pub fn diagnostics(&self) -> &[CssDiagnostic] {
+        &self.diagnostics
     }
 
-    pub fn consume_ident_like_token(&mut self) -> CSSToken {
+    ///Returns the parse errors found so far, leaving none behind.
+    pub fn take_diagnostics(&mut self) -> Vec<CssDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub fn consume_ident_like_token(&mut self) -> CSSToken<'a> {
         // https://www.w3.org/TR/css-syntax-3/#consume-ident-like-token
         // This section describes how to consume an ident-like token from a stream of code points. It returns an <ident-token>, <function-token>, <url-token>, or <bad-url-token>.
 
@@ -220,34 +362,33 @@ impl<'a> Tokenizer<'a> {
         let string = self.consume_ident_sequence();
 
         // If string’s value is an ASCII case-insensitive match for "url", and the next input code point is U+0028 LEFT PARENTHESIS ((),
-        if &string.to_lowercase() == "url" && self.process.peek() == &Some('\u{0028}') {
+        if string.eq_ignore_ascii_case("url") && self.process.peek() == &Some('\u{0028}') {
             //  consume it.
-            self.process.next();
+            self.consume_code_point();
 
             // 	While the next two input code points are whitespace, consume the next input code point.
             while let (Some(first), Some(second)) = self.peek_twin() {
                 if !(Self::is_whitespace(first) && Self::is_whitespace(second)) {
                     break;
                 }
-                self.process.next();
+                self.consume_code_point();
             }
 
             // If the next one or two input code points are U+0022 QUOTATION MARK ("), U+0027 APOSTROPHE ('), or whitespace followed by U+0022 QUOTATION MARK (") or U+0027 APOSTROPHE ('),
-            if let (Some(first), Some(second)) = self.peek_twin() {
-                if (first == '\u{0022}' || first == '\u{0027}')
-                    || (Self::is_whitespace(first)
-                        && (second == '\u{0022}' || second == '\u{0027}'))
-                {
-                    // then create a <function-token> with its value set to string and return it.
-                    return CSSToken::FunctionToken { value: string };
-                }
-                // 	Otherwise, consume a url token, and return it.
-                return self.consume_url_token();
+            let (first, second) = self.peek_twin();
+            if matches!(first, Some('\u{0022}' | '\u{0027}'))
+                || (first.is_some_and(Self::is_whitespace)
+                    && matches!(second, Some('\u{0022}' | '\u{0027}')))
+            {
+                // then create a <function-token> with its value set to string and return it.
+                return CSSToken::FunctionToken { value: string };
             }
+            // 	Otherwise, consume a url token, and return it.
+            return self.consume_url_token();
         }
         // Otherwise, if the next input code point is U+0028 LEFT PARENTHESIS ((), consume it.
         if self.process.peek() == &Some('\u{0028}') {
-            self.process.next();
+            self.consume_code_point();
             // Create a <function-token> with its value set to string and return it.
             return CSSToken::FunctionToken { value: string };
         }
@@ -255,34 +396,37 @@ impl<'a> Tokenizer<'a> {
         CSSToken::IdentToken { value: string }
     }
 
-    pub fn consume_url_token(&mut self) -> CSSToken {
+    pub fn consume_url_token(&mut self) -> CSSToken<'a> {
         // https://www.w3.org/TR/css-syntax-3/#consume-url-token
         // This section describes how to consume a url token from a stream of code points. It returns either a <url-token> or a <bad-url-token>.
 
-        // Initially create a <url-token> with its value set to the empty string.
-        let mut url_token_val = String::new();
-
         //  Consume as much whitespace as possible.
         self.consume_whitespace();
 
+        // Initially create a <url-token> with its value set to the empty string.
+        let mut url_token_val = TokenValue::new(self.position);
+
         // Repeatedly consume the next input code point from the stream:
         loop {
-            let next = self.process.next();
+            let next = self.consume_code_point();
 
             // U+0029 RIGHT PARENTHESIS ())
             if next == Some('\u{0029}') {
                 // Return the <url-token>.
                 return CSSToken::URLToken {
-                    value: url_token_val,
+                    value: url_token_val.into_cow(self.input),
                 };
             }
 
             // EOF
             if next.is_none() {
                 // This is a parse error. Return the <url-token>.
-                self.parse_error();
+                self.parse_error(
+                    CssDiagnosticKind::UnterminatedURL,
+                    "the url is missing its closing `)`",
+                );
                 return CSSToken::URLToken {
-                    value: url_token_val,
+                    value: url_token_val.into_cow(self.input),
                 };
             }
 
@@ -296,15 +440,22 @@ impl<'a> Tokenizer<'a> {
                 if matches!(peek, None | Some('\u{0029}')) {
                     // 	consume it and return the <url-token>  (if EOF was encountered, this is a parse error);
                     if peek.is_none() {
-                        self.parse_error();
+                        self.parse_error(
+                            CssDiagnosticKind::UnterminatedURL,
+                            "the url is missing its closing `)`",
+                        );
                     }
-                    self.process.next();
+                    self.consume_code_point();
                     return CSSToken::URLToken {
-                        value: url_token_val,
+                        value: url_token_val.into_cow(self.input),
                     };
                 }
 
                 // otherwise, consume the remnants of a bad url, create a <bad-url-token>, and return it.
+                self.parse_error(
+                    CssDiagnosticKind::BadURL,
+                    "unquoted urls can not contain whitespace, use a quoted string instead",
+                );
                 self.consume_remnants_of_bad_url();
                 return CSSToken::BadURLToken;
             }
@@ -320,7 +471,13 @@ impl<'a> Tokenizer<'a> {
                     || Self::is_none_printable_code_point(v))
             {
                 // This is a parse error. Consume the remnants of a bad url, create a <bad-url-token>, and return it.
-                self.parse_error();
+                self.parse_error(
+                    CssDiagnosticKind::BadURL,
+                    format!(
+                        "unquoted urls can not contain {:?}, use a quoted string instead",
+                        v
+                    ),
+                );
                 self.consume_remnants_of_bad_url();
                 return CSSToken::BadURLToken;
             }
@@ -331,11 +488,16 @@ impl<'a> Tokenizer<'a> {
                 if let &Some(v) = self.process.peek()
                     && Self::is_valid_escape(next, Some(v))
                 {
-                    url_token_val.push(self.consume_escaped_code_point());
+                    let escaped = self.consume_escaped_code_point();
+                    url_token_val.push(self.input, escaped);
+                    continue;
                 }
 
                 // Otherwise, this is a parse error. Consume the remnants of a bad url, create a <bad-url-token>, and return it.
-                self.parse_error();
+                self.parse_error(
+                    CssDiagnosticKind::BadURL,
+                    "a `\\` followed by a newline does not escape anything in a url",
+                );
                 self.consume_remnants_of_bad_url();
                 return CSSToken::BadURLToken;
             }
@@ -343,7 +505,7 @@ impl<'a> Tokenizer<'a> {
             // anything else
             if let Some(v) = next {
                 // Append the current input code point to the <url-token>’s value.
-                url_token_val.push(v);
+                url_token_val.push_verbatim(v, self.position);
             }
         }
     }
@@ -361,7 +523,7 @@ impl<'a> Tokenizer<'a> {
         // https://www.w3.org/TR/css-syntax-3/#consume-remnants-of-bad-url
         loop {
             // Repeatedly consume the next input code point from the stream:
-            let next = self.process.next();
+            let next = self.consume_code_point();
 
             // U+0029 RIGHT PARENTHESIS ())
             // EOF
@@ -382,7 +544,7 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    pub fn consume_numeric_token(&mut self) -> CSSToken {
+    pub fn consume_numeric_token(&mut self) -> CSSToken<'a> {
         // https://www.w3.org/TR/css-syntax-3/#consume-numeric-token
 
         // This section describes how to consume a numeric token from a stream of code points. It returns either a <number-token>, <percentage-token>, or <dimension-token>.
@@ -398,18 +560,20 @@ impl<'a> Tokenizer<'a> {
             return CSSToken::DimensionToken {
                 flag: number.r#type,
                 value: number.value,
+                signed: number.signed,
                 unit: self.consume_ident_sequence(),
             };
         }
 
         // Otherwise, if the next input code point is U+0025 PERCENTAGE SIGN (%), consume it.
         if self.process.peek() == &Some('\u{0025}') {
-            self.process.next();
+            self.consume_code_point();
 
             // 	Create a <percentage-token> with the same value as number, and return it.
             return CSSToken::PercentageToken {
                 flag: number.r#type,
                 value: number.value,
+                signed: number.signed,
             };
         }
 
@@ -417,6 +581,7 @@ impl<'a> Tokenizer<'a> {
         CSSToken::NumberToken {
             flag: number.r#type,
             value: number.value,
+            signed: number.signed,
         }
     }
 
@@ -426,14 +591,16 @@ impl<'a> Tokenizer<'a> {
 
         //  Initially set type to "integer". Let repr be the empty string.
         let mut r#type = NumberType::Integer;
-        let mut repr = String::new();
+        // Numbers can not contain escapes, so repr is always the part of the input consumed so far.
+        let repr = self.position;
 
         // If the next input code point is U+002B PLUS SIGN (+) or U+002D HYPHEN-MINUS (-), consume it and append it to repr.
+        let mut signed = false;
         if let &Some(v) = self.process.peek()
             && (v == '\u{002b}' || v == '\u{002d}')
         {
-            self.process.next();
-            repr.push(v);
+            self.consume_code_point();
+            signed = true;
         }
 
         // While the next input code point is a digit, consume it and append it to repr.
@@ -441,8 +608,7 @@ impl<'a> Tokenizer<'a> {
             if !Self::is_digit(v) {
                 break;
             }
-            self.process.next();
-            repr.push(v);
+            self.consume_code_point();
         }
 
         // If the next 2 input code points are U+002E FULL STOP (.) followed by a digit, then:
@@ -450,11 +616,9 @@ impl<'a> Tokenizer<'a> {
             && (first == '\u{002e}' && Self::is_digit(second))
         {
             // Consume them.
-            self.process.next();
-            self.process.next();
+            self.consume_code_point();
+            self.consume_code_point();
             // Append them to repr.
-            repr.push(first);
-            repr.push(second);
             // Set type to "number".
             r#type = NumberType::Number;
 
@@ -463,25 +627,26 @@ impl<'a> Tokenizer<'a> {
                 if !Self::is_digit(v) {
                     break;
                 }
-                self.process.next();
-                repr.push(v);
+                self.consume_code_point();
             }
         }
 
         // If the next 2 or 3 input code points are U+0045 LATIN CAPITAL LETTER E (E) or U+0065 LATIN SMALL LETTER E (e), optionally followed by U+002D HYPHEN-MINUS (-) or U+002B PLUS SIGN (+), followed by a digit, then:
-        if let &[Some(first), Some(second), Some(third)] = self.process.peek_value(3)
-            && ((Self::is_e(first) && Self::is_digit(second))
-                || (Self::is_e(first) && Self::is_plus_or_minus(second) && Self::is_digit(third)))
-        {
+        // The third code point is not there when the exponent is a single digit at the end of the input, like in `1e3`.
+        let exponent = match *self.process.peek_value(3) {
+            [Some(first), Some(second), _] if Self::is_e(first) && Self::is_digit(second) => 2,
+            [Some(first), Some(second), Some(third)]
+                if Self::is_e(first) && Self::is_plus_or_minus(second) && Self::is_digit(third) =>
+            {
+                3
+            }
+            _ => 0,
+        };
+        if exponent > 0 {
             // Consume them.
             // Append them to repr
-            self.process.next();
-            self.process.next();
-            repr.push(first);
-            repr.push(second);
-            if Self::is_e(first) && Self::is_plus_or_minus(second) && Self::is_digit(third) {
-                self.process.next();
-                repr.push(third);
+            for _ in 0..exponent {
+                self.consume_code_point();
             }
 
             // Set type to "number".
@@ -492,20 +657,20 @@ impl<'a> Tokenizer<'a> {
                 if !Self::is_digit(v) {
                     break;
                 }
-                self.process.next();
-                repr.push(v);
+                self.consume_code_point();
             }
         }
 
         // Convert repr to a number, and set the value to the returned value.
         // Return value and type.
         Number {
-            value: Self::string_to_number(repr),
+            value: Self::string_to_number(&self.input[repr..self.position]),
             r#type,
+            signed,
         }
     }
 
-    pub fn string_to_number(input: String) -> f64 {
+    pub fn string_to_number(input: &str) -> f64 {
         // https://www.w3.org/TR/css-syntax-3/#convert-string-to-number
         // This section describes how to convert a string to a number. It returns a number.
         let mut iter = input.chars().peekable();
@@ -538,7 +703,11 @@ impl<'a> Tokenizer<'a> {
         }
 
         // If there is at least one digit, let i be the number formed by interpreting the digits as a base-10 integer; otherwise, let i be the number 0.
-        let i = integer_part.parse::<f64>().unwrap_or(0.);
+        // The numbers are kept as digits, see the end.
+        let i = match integer_part.is_empty() {
+            true => "0",
+            false => integer_part.as_str(),
+        };
 
         // A decimal point: a single U+002E FULL STOP (.), or the empty string.
         let mut _decimal_point = None;
@@ -560,8 +729,10 @@ impl<'a> Tokenizer<'a> {
         }
 
         // If there is at least one digit, let f be the number formed by interpreting the digits as a base-10 integer and d be the number of digits; otherwise, let f and d be the number 0.
-        let f = fractional_part.parse::<f64>().unwrap_or(0.);
-        let d = fractional_part.len() as f64;
+        let f = match fractional_part.is_empty() {
+            true => "0",
+            false => fractional_part.as_str(),
+        };
 
         // An exponent indicator: a single U+0045 LATIN CAPITAL LETTER E (E) or U+0065 LATIN SMALL LETTER E (e), or the empty string.
         let mut _exponent_indicator = None;
@@ -583,11 +754,9 @@ impl<'a> Tokenizer<'a> {
         }
 
         // Let t be the number -1 if the sign is U+002D HYPHEN-MINUS (-); otherwise, let t be the number 1.
-        let t = {
-            match _exponent_sign {
-                Some('\u{002d}') => -1.,
-                _ => 1.,
-            }
+        let t = match _exponent_sign {
+            Some('\u{002d}') => "-",
+            _ => "",
         };
 
         // An exponent: zero or more digits.
@@ -601,10 +770,17 @@ impl<'a> Tokenizer<'a> {
         }
 
         // If there is at least one digit, let e be the number formed by interpreting the digits as a base-10 integer; otherwise, let e be the number 0.
-        let e = exponent.parse::<f64>().unwrap_or(0.);
+        let e = match exponent.is_empty() {
+            true => "0",
+            false => exponent.as_str(),
+        };
 
         // Return the number s·(i + f·10-d)·10te.
-        s * (i + (f * 10.0_f64.powf(-d))) * 10.0_f64.powf(t * e)
+        // Computing it with powers of ten compounds their rounding errors and gives NaN for `0e400`, so the digits are
+        // handed to the parser of the standard library instead, which rounds that number correctly.
+        let value = format!("{i}.{f}e{t}{e}").parse::<f64>().unwrap_or(0.);
+        // Numbers too large for an f64 are clamped to the largest one, since infinities can not be serialized.
+        (s * value).clamp(-f64::MAX, f64::MAX)
     }
 
     pub fn is_plus_or_minus(input: char) -> bool {
@@ -669,35 +845,36 @@ impl<'a> Tokenizer<'a> {
         false
     }
 
-    pub fn consume_ident_sequence(&mut self) -> String {
+    pub fn consume_ident_sequence(&mut self) -> Cow<'a, str> {
         // https://www.w3.org/TR/css-syntax-3/#consume-name
 
         // Let result initially be an empty string.
-        let mut result = String::new();
+        let mut result = TokenValue::new(self.position);
 
         // Repeatedly consume the next input code point from the stream:
+        // The code points are peeked before being consumed, which stands in for the "reconsume" step below.
         loop {
-            let next = self.process.next();
+            let (first, second) = self.peek_twin();
 
             // ident code point
-            if let Some(v) = next
+            if let Some(v) = first
                 && Self::is_ident_code_point(Some(v))
             {
                 // Append the code point to result.
-                result.push(v);
+                self.consume_code_point();
+                result.push_verbatim(v, self.position);
             }
             // the stream starts with a valid escape
-            else if let &Some(v) = self.process.peek()
-                && Self::is_valid_escape(next, Some(v))
-            {
+            else if Self::is_valid_escape(first, second) {
                 // Consume an escaped code point. Append the returned code point to result.
-                result.push(self.consume_escaped_code_point());
+                self.consume_code_point();
+                let escaped = self.consume_escaped_code_point();
+                result.push(self.input, escaped);
             }
             // anything else
             else {
                 // Reconsume the current input code point. Return result.
-                self.process.put_back(next);
-                return result;
+                return result.into_cow(self.input);
             }
         }
     }
@@ -757,7 +934,7 @@ impl<'a> Tokenizer<'a> {
     pub fn is_ident_start_code_point(input: char) -> bool {
         // https://www.w3.org/TR/css-syntax-3/#tokenizer-definitions
         // A letter, a non-ASCII code point, or U+005F LOW LINE (_).
-        Self::is_letter(input) || Self::is_none_ascii(input) || input == '\u{0080}'
+        Self::is_letter(input) || Self::is_none_ascii(input) || input == '\u{005f}'
     }
 
     pub fn is_letter(input: char) -> bool {
@@ -769,13 +946,13 @@ impl<'a> Tokenizer<'a> {
     pub fn is_uppercase_letter(input: char) -> bool {
         // https://www.w3.org/TR/css-syntax-3/#tokenizer-definitions
         // A code point between U+0041 LATIN CAPITAL LETTER A (A) and U+005A LATIN CAPITAL LETTER Z (Z) inclusive.
-        ('\u{0041}'..='\u{005a}').contains(&input)
+        input.is_ascii_uppercase()
     }
 
     pub fn is_lowercase_letter(input: char) -> bool {
         // https://www.w3.org/TR/css-syntax-3/#tokenizer-definitions
         // A code point between U+0061 LATIN SMALL LETTER A (a) and U+007A LATIN SMALL LETTER Z (z) inclusive.
-        ('\u{0061}'..='\u{007a}').contains(&input)
+        input.is_ascii_lowercase()
     }
 
     pub fn is_none_ascii(input: char) -> bool {
@@ -784,35 +961,45 @@ impl<'a> Tokenizer<'a> {
         input >= '\u{0080}'
     }
 
-    pub fn consume_string_token(&mut self, code_point: char) -> CSSToken {
+    pub fn consume_string_token(&mut self, code_point: char) -> CSSToken<'a> {
         // https://www.w3.org/TR/css-syntax-3/#consume-string-token
         // This section describes how to consume a string token from a stream of code points. It returns either a <string-token> or <bad-string-token>.
 
         //This algorithm may be called with an ending code point, which denotes the code point that ends the string. If an ending code point is not specified, the current input code point is used.
 
         //Initially create a <string-token> with its value set to the empty string.
-        let mut out = String::new();
+        let mut out = TokenValue::new(self.position);
 
         // Repeatedly consume the next input code point from the stream:
         loop {
-            let next = self.process.next();
+            let next = self.consume_code_point();
             // ending code point
             if next == Some(code_point) {
                 // Return the <string-token>.
-                return CSSToken::StringToken { string: out };
+                return CSSToken::StringToken {
+                    string: out.into_cow(self.input),
+                };
             }
 
             //EOF
             if next.is_none() {
                 // This is a parse error. Return the <string-token>.
-                self.parse_error();
-                return CSSToken::StringToken { string: out };
+                self.parse_error(
+                    CssDiagnosticKind::UnterminatedString,
+                    format!("the string is missing its closing `{code_point}`"),
+                );
+                return CSSToken::StringToken {
+                    string: out.into_cow(self.input),
+                };
             }
             // newline
             if next == Some('\u{000a}') {
                 // This is a parse error. Reconsume the current input code point, create a <bad-string-token>, and return it.
-                self.parse_error();
-                self.process.put_back(Some('\u{000a}'));
+                self.reconsume_code_point(Some('\u{000a}'));
+                self.parse_error(
+                    CssDiagnosticKind::NewlineInString,
+                    "strings can not contain a newline, escape it with `\\` to continue the string on the next line",
+                );
                 return CSSToken::BadStringToken;
             }
             // U+005C REVERSE SOLIDUS (\)
@@ -825,14 +1012,17 @@ impl<'a> Tokenizer<'a> {
                 else if let &Some(v) = self.process.peek()
                     && Self::is_newline(v)
                 {
-                    self.process.next();
+                    self.consume_code_point();
+                    // The newline is left out of the value, so it is no longer a slice of the input.
+                    out.make_owned(self.input);
                     continue;
                 }
                 // Otherwise, (the stream starts with a valid escape) consume an escaped code point and append the returned code point to the <string-token>’s value.
                 else if let &Some(v) = self.process.peek()
                     && Self::is_valid_escape(Some('\u{005c}'), Some(v))
                 {
-                    out.push(self.consume_escaped_code_point());
+                    let escaped = self.consume_escaped_code_point();
+                    out.push(self.input, escaped);
                     continue;
                 }
             }
@@ -840,7 +1030,7 @@ impl<'a> Tokenizer<'a> {
             // anything else
             // Append the current input code point to the <string-token>’s value.
             if let Some(v) = next {
-                out.push(v);
+                out.push_verbatim(v, self.position);
             }
         }
     }
@@ -858,11 +1048,63 @@ impl<'a> Tokenizer<'a> {
         (first, second)
     }
 
+    pub fn consume_unicode_range_token(&mut self) -> CSSToken<'a> {
+        // https://drafts.csswg.org/css-syntax-3/#consume-unicode-range-token
+        // The `u` was already consumed. Consume the `+`.
+        self.consume_code_point();
+
+        // Consume as many hex digits as possible, but no more than 6. If less than 6 hex digits were consumed, consume as many U+003F QUESTION MARK (?) code points as possible, but no more than enough to make the total of hex digits and U+003F QUESTION MARK (?) code points equal to 6.
+        let mut first_segment = self.consume_up_to(6, Self::is_hex_digit);
+        let question_marks = self.consume_up_to(6 - first_segment.len(), |v| v == '?');
+        if !question_marks.is_empty() {
+            // If first segment contains any question mark code points, then:
+            // Replace the question marks in first segment with U+0030 DIGIT ZERO (0) code points, and interpret the result as a hexadecimal number. Let this be start of range.
+            // Replace the question marks in first segment with U+0046 LATIN CAPITAL LETTER F (F) code points, and interpret the result as a hexadecimal number. Let this be end of range.
+            let start = format!("{first_segment}{}", "0".repeat(question_marks.len()));
+            first_segment.push_str(&"F".repeat(question_marks.len()));
+            return CSSToken::UnicodeRangeToken {
+                start: u32::from_str_radix(&start, 16).unwrap_or(0),
+                end: u32::from_str_radix(&first_segment, 16).unwrap_or(0),
+            };
+        }
+        // Otherwise, interpret first segment as a hexadecimal number, and let the result be start of range.
+        let start = u32::from_str_radix(&first_segment, 16).unwrap_or(0);
+
+        // If the next 2 input code points are U+002D HYPHEN-MINUS (-) followed by a hex digit, then:
+        // Consume the next input code point. Consume as many hex digits as possible, but no more than 6. Interpret the consumed code points as a hexadecimal number. Let this be the end of range.
+        let (first, second) = self.peek_twin();
+        if first == Some('-') && second.is_some_and(Self::is_hex_digit) {
+            self.consume_code_point();
+            let end = self.consume_up_to(6, Self::is_hex_digit);
+            return CSSToken::UnicodeRangeToken {
+                start,
+                end: u32::from_str_radix(&end, 16).unwrap_or(0),
+            };
+        }
+        // Otherwise, set the end of range to the start of range.
+        CSSToken::UnicodeRangeToken { start, end: start }
+    }
+
+    ///Consumes as many code points matching `matches` as possible, but no more than `limit`.
+    fn consume_up_to(&mut self, limit: usize, matches: impl Fn(char) -> bool) -> String {
+        let mut out = String::new();
+        while out.len() < limit {
+            match self.consume_code_point() {
+                Some(v) if matches(v) => out.push(v),
+                v => {
+                    self.reconsume_code_point(v);
+                    break;
+                }
+            }
+        }
+        out
+    }
+
     pub fn consume_escaped_code_point(&mut self) -> char {
         // https://www.w3.org/TR/css-syntax-3/#consume-escaped-code-point
 
         // Consume the next input code point.
-        let next = self.process.next();
+        let next = self.consume_code_point();
 
         // hex digit
         if let Some(v) = next
@@ -874,17 +1116,17 @@ impl<'a> Tokenizer<'a> {
 
             while let &Some(peek) = self.process.peek()
                 && Self::is_hex_digit(peek)
-                && digits.len() <= 6
+                && digits.len() < 6
             {
                 digits.push(peek);
-                self.process.next();
+                self.consume_code_point();
             }
 
             // If the next input code point is whitespace, consume it as well.
             if let &Some(peek) = self.process.peek()
                 && Self::is_whitespace(peek)
             {
-                self.process.next();
+                self.consume_code_point();
             }
 
             // Interpret the hex digits as a hexadecimal number.
@@ -904,13 +1146,20 @@ impl<'a> Tokenizer<'a> {
         // EOF
         if next.is_none() {
             // This is a parse error. Return U+FFFD REPLACEMENT CHARACTER (�).
-            self.parse_error();
+            self.parse_error(
+                CssDiagnosticKind::InvalidEscape,
+                "the input ended in the middle of an escape",
+            );
             return '\u{fffd}';
         }
 
         // anything else
         // Return the current input code point.
-        next.unwrap()
+        // U+0000 would have been replaced with U+FFFD by https://www.w3.org/TR/css-syntax-3/#input-preprocessing
+        match next {
+            Some('\0') => '\u{fffd}',
+            next => next.unwrap(),
+        }
     }
 
     pub fn max_allowed_code_point() -> u32 {
@@ -944,7 +1193,7 @@ impl<'a> Tokenizer<'a> {
     pub fn is_digit(input: char) -> bool {
         // https://www.w3.org/TR/css-syntax-3/#tokenizer-definitions
         // A code point between U+0030 DIGIT ZERO (0) and U+0039 DIGIT NINE (9) inclusive.
-        ('\u{0030}'..='\u{0039}').contains(&input)
+        input.is_ascii_digit()
     }
 
     pub fn is_hex_digit(input: char) -> bool {
@@ -977,8 +1226,7 @@ impl<'a> Tokenizer<'a> {
             if !Self::is_whitespace(v) {
                 break;
             }
-            println!("consuming {v}");
-            self.process.next();
+            self.consume_code_point();
         }
     }
 
@@ -997,41 +1245,56 @@ impl<'a> Tokenizer<'a> {
             return;
         }
 
-        self.process.next();
-        self.process.next();
+        let start = self.location();
+        self.consume_code_point();
+        self.consume_code_point();
 
         loop {
             (first, second) = self.peek_twin();
 
             if first.is_none() || second.is_none() {
                 // If the preceding paragraph ended by consuming an EOF code point, this is a parse error.
-                self.parse_error();
+                self.consume_code_point();
+                self.token_start = start;
+                self.parse_error(
+                    CssDiagnosticKind::EOFInComment,
+                    "the comment is missing its closing `*/`",
+                );
                 return;
             }
 
             if first == Some('\u{002a}') && second == Some('\u{002f}') {
-                println!("i was true");
-                self.process.next();
-                self.process.next();
+                self.consume_code_point();
+                self.consume_code_point();
                 self.consume_comments();
                 // Return nothing.
                 return;
             }
 
-            self.process.next();
+            self.consume_code_point();
         }
     }
 
-    pub fn parse_error(&mut self) {
-        self.parse_error = true;
+    pub fn parse_error(&mut self, kind: CssDiagnosticKind, message: impl Into<String>) {
+        // The error points at everything consumed for the current token so far.
+        let span = Span {
+            start: self.token_start,
+            end: self.location(),
+        };
+        self.diagnostics
+            .push(CssDiagnostic::new(kind, span, message));
     }
 
-    pub fn tokenize(&mut self) -> Vec<CSSToken> {
+    pub fn tokenize(&mut self) -> Vec<CSSToken<'a>> {
+        self.by_ref().collect()
+    }
+
+    pub fn tokenize_with_spans(&mut self) -> Vec<SpannedToken<'a>> {
         let mut out = vec![];
         loop {
-            let tok = self.consume_token();
-            if tok == CSSToken::EOFToken {
-                out.push(CSSToken::EOFToken);
+            let tok = self.next_token();
+            if tok.token == CSSToken::EOFToken {
+                out.push(tok);
                 break;
             }
             out.push(tok);
@@ -1040,17 +1303,88 @@ impl<'a> Tokenizer<'a> {
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum CSSToken {
+///Yields the tokens of the input one at a time, ending after the <EOF-token>.
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = CSSToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let token = self.next_token().token;
+        self.finished = token == CSSToken::EOFToken;
+        Some(token)
+    }
+}
+
+///A [`CSSToken`] along with the part of the input it was consumed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken<'a> {
+    pub token: CSSToken<'a>,
+    pub span: Span,
+}
+
+///The string value of a token, which borrows from the input for as long as it is a verbatim slice of it.
+///Only escapes make it allocate, since the code points they produce are not in the input.
+struct TokenValue {
+    start: usize,
+    end: usize,
+    owned: Option<String>,
+}
+
+impl TokenValue {
+    fn new(start: usize) -> Self {
+        Self {
+            start,
+            end: start,
+            owned: None,
+        }
+    }
+
+    ///Appends a code point that was consumed as-is and ends at the byte offset `end`.
+    fn push_verbatim(&mut self, input: char, end: usize) {
+        match &mut self.owned {
+            Some(owned) => owned.push(input),
+            None => self.end = end,
+        }
+    }
+
+    ///Appends a code point that is not a slice of the input, like an escaped one.
+    fn push(&mut self, source: &str, input: char) {
+        self.make_owned(source);
+        if let Some(owned) = &mut self.owned {
+            owned.push(input);
+        }
+    }
+
+    ///Copies the value out of the input, for when the next code points are not contiguous with it.
+    fn make_owned(&mut self, source: &str) {
+        if self.owned.is_none() {
+            self.owned = Some(source[self.start..self.end].to_string());
+        }
+    }
+
+    fn into_cow(self, source: &str) -> Cow<'_, str> {
+        match self.owned {
+            Some(owned) => Cow::Owned(owned),
+            None => Cow::Borrowed(&source[self.start..self.end]),
+        }
+    }
+}
+
+///A token of https://www.w3.org/TR/css-syntax-3/#tokenization
+///The string values borrow from the input whenever they appear in it verbatim, see [`CSSToken::into_owned`] to detach them from it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CSSToken<'a> {
     EOFToken,
     WhitespaceToken,
     StringToken {
-        string: String,
+        string: Cow<'a, str>,
     },
     BadStringToken,
     HashToken {
         flag: HashTokenFlag,
-        value: String,
+        value: Cow<'a, str>,
     },
     DelimToken {
         value: char,
@@ -1060,41 +1394,128 @@ pub enum CSSToken {
     NumberToken {
         flag: NumberType,
         value: f64,
+        ///Whether the number was written with a leading `+` or `-`, which the An+B microsyntax cares about.
+        signed: bool,
     },
     PercentageToken {
         flag: NumberType,
         value: f64,
+        ///Whether the number was written with a leading `+` or `-`, which the An+B microsyntax cares about.
+        signed: bool,
     },
     DimensionToken {
         flag: NumberType,
         value: f64,
-        unit: String,
+        ///Whether the number was written with a leading `+` or `-`, which the An+B microsyntax cares about.
+        signed: bool,
+        unit: Cow<'a, str>,
     },
     CommaToken,
     CDCToken,
     FunctionToken {
-        value: String,
+        value: Cow<'a, str>,
     },
     URLToken {
-        value: String,
+        value: Cow<'a, str>,
     },
     BadURLToken,
     IdentToken {
-        value: String,
+        value: Cow<'a, str>,
     },
     ColonToken,
     SemicolonToken,
     CDOToken,
     AtKeywordToken {
-        value: String,
+        value: Cow<'a, str>,
     },
     LeftSquareBracketToken,
     RightSquareBracketToken,
     LeftCurlyBracketToken,
     RightCurlyBracketToken,
+    ///`U+0-7F` or `U+4??`, only produced for the value of the `unicode-range` descriptor, see [`Tokenizer::allow_unicode_ranges`].
+    UnicodeRangeToken {
+        start: u32,
+        end: u32,
+    },
+}
+
+impl CSSToken<'_> {
+    ///Copies the borrowed string values out of the input, so the token can outlive it.
+    pub fn into_owned(self) -> CSSToken<'static> {
+        let owned = |value: Cow<'_, str>| Cow::Owned(value.into_owned());
+        match self {
+            CSSToken::EOFToken => CSSToken::EOFToken,
+            CSSToken::WhitespaceToken => CSSToken::WhitespaceToken,
+            CSSToken::StringToken { string } => CSSToken::StringToken {
+                string: owned(string),
+            },
+            CSSToken::BadStringToken => CSSToken::BadStringToken,
+            CSSToken::HashToken { flag, value } => CSSToken::HashToken {
+                flag,
+                value: owned(value),
+            },
+            CSSToken::DelimToken { value } => CSSToken::DelimToken { value },
+            CSSToken::LeftParenthesisToken => CSSToken::LeftParenthesisToken,
+            CSSToken::RightParenthesisToken => CSSToken::RightParenthesisToken,
+            CSSToken::NumberToken {
+                flag,
+                value,
+                signed,
+            } => CSSToken::NumberToken {
+                flag,
+                value,
+                signed,
+            },
+            CSSToken::PercentageToken {
+                flag,
+                value,
+                signed,
+            } => CSSToken::PercentageToken {
+                flag,
+                value,
+                signed,
+            },
+            CSSToken::DimensionToken {
+                flag,
+                value,
+                signed,
+                unit,
+            } => CSSToken::DimensionToken {
+                flag,
+                value,
+                signed,
+                unit: owned(unit),
+            },
+            CSSToken::CommaToken => CSSToken::CommaToken,
+            CSSToken::CDCToken => CSSToken::CDCToken,
+            CSSToken::FunctionToken { value } => CSSToken::FunctionToken {
+                value: owned(value),
+            },
+            CSSToken::URLToken { value } => CSSToken::URLToken {
+                value: owned(value),
+            },
+            CSSToken::BadURLToken => CSSToken::BadURLToken,
+            CSSToken::IdentToken { value } => CSSToken::IdentToken {
+                value: owned(value),
+            },
+            CSSToken::ColonToken => CSSToken::ColonToken,
+            CSSToken::SemicolonToken => CSSToken::SemicolonToken,
+            CSSToken::CDOToken => CSSToken::CDOToken,
+            CSSToken::AtKeywordToken { value } => CSSToken::AtKeywordToken {
+                value: owned(value),
+            },
+            CSSToken::LeftSquareBracketToken => CSSToken::LeftSquareBracketToken,
+            CSSToken::RightSquareBracketToken => CSSToken::RightSquareBracketToken,
+            CSSToken::LeftCurlyBracketToken => CSSToken::LeftCurlyBracketToken,
+            CSSToken::RightCurlyBracketToken => CSSToken::RightCurlyBracketToken,
+            CSSToken::UnicodeRangeToken { start, end } => {
+                CSSToken::UnicodeRangeToken { start, end }
+            }
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashTokenFlag {
     Id,
     Unrestricted,
@@ -1104,9 +1525,10 @@ pub enum HashTokenFlag {
 pub struct Number {
     value: f64,
     r#type: NumberType,
+    signed: bool,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NumberType {
     Integer,
     Number,
@@ -0,0 +1,514 @@
+use crate::media::container::QueryContainer;
+use crate::selectors::{
+    AnB, AttributeCase, AttributeOperator, AttributeSelector, Combinator, CompoundSelector,
+    NamespacePrefix, PseudoClass, RelativeSelector, Selector, SelectorList, SimpleSelector,
+};
+use std::ops::BitOr;
+
+///An element of a tree that selectors can be matched against.
+///This is all the matcher needs to know about a tree, so any tree of components can implement it, not only a DOM.
+///Implementations are expected to be cheap handles to a node, like a reference or an index into the tree.
+pub trait Element: Sized + Clone + PartialEq {
+    fn parent(&self) -> Option<Self>;
+    fn prev_sibling(&self) -> Option<Self>;
+    fn next_sibling(&self) -> Option<Self>;
+    fn first_child(&self) -> Option<Self>;
+    ///The name type selectors are compared against, like `button`. It is compared ASCII case-insensitively.
+    fn local_name(&self) -> &str;
+    fn id(&self) -> Option<&str>;
+    fn classes(&self) -> impl Iterator<Item = &str>;
+    fn attribute(&self, name: &str) -> Option<&str>;
+    fn state(&self) -> ElementState;
+    ///Whether the element has no children and no text, for `:empty`.
+    fn is_empty(&self) -> bool;
+
+    ///The namespace of the element, for type selectors like `svg|rect`.
+    fn namespace(&self) -> Option<&str> {
+        None
+    }
+
+    fn has_class(&self, name: &str) -> bool {
+        self.classes().any(|v| v == name)
+    }
+
+    ///The element as a query container for the `@container` rules of its descendants,
+    ///see [`QueryContainer::from_style`]. `None` if it is not one, or has not been laid out yet.
+    fn query_container(&self) -> Option<QueryContainer> {
+        None
+    }
+}
+
+///The dynamic state of an element that the user action and input pseudo-classes match against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ElementState(u32);
+
+impl ElementState {
+    pub const HOVER: Self = Self(1 << 0);
+    pub const ACTIVE: Self = Self(1 << 1);
+    pub const FOCUS: Self = Self(1 << 2);
+    pub const FOCUS_VISIBLE: Self = Self(1 << 3);
+    pub const DISABLED: Self = Self(1 << 4);
+    pub const CHECKED: Self = Self(1 << 5);
+    pub const INDETERMINATE: Self = Self(1 << 6);
+    pub const REQUIRED: Self = Self(1 << 7);
+    pub const READ_ONLY: Self = Self(1 << 8);
+    pub const PLACEHOLDER_SHOWN: Self = Self(1 << 9);
+    pub const DEFAULT: Self = Self(1 << 10);
+    pub const INVALID: Self = Self(1 << 11);
+    ///The element is a hyperlink, which makes it match `:any-link` and either `:link` or `:visited`.
+    pub const LINK: Self = Self(1 << 12);
+    pub const VISITED: Self = Self(1 << 13);
+    pub const TARGET: Self = Self(1 << 14);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for ElementState {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+///Whether any selector of the list matches the element.
+///`filter` should hold the ancestors of `element` if given, see [`AncestorFilter`].
+pub fn matches_selector_list<E: Element>(
+    list: &SelectorList,
+    element: &E,
+    filter: Option<&AncestorFilter>,
+) -> bool {
+    list.selectors
+        .iter()
+        .any(|v| matches_selector(v, element, filter))
+}
+
+///Whether the selector matches the element, matching its compound selectors from right to left.
+///Pseudo-elements are ignored, the caller decides what a selector with [`Selector::pseudo_element`] applies to.
+///`filter` should hold the ancestors of `element` if given, see [`AncestorFilter`].
+pub fn matches_selector<E: Element>(
+    selector: &Selector,
+    element: &E,
+    filter: Option<&AncestorFilter>,
+) -> bool {
+    if let Some(filter) = filter
+        && !filter.might_match(selector)
+    {
+        return false;
+    }
+    let last = selector.compounds.len() - 1;
+    matches_complex(selector, last, element, None) == MatchResult::Matched
+}
+
+///The outcome of matching part of a complex selector, which tells the caller how far back it has to go to try again.
+///See https://github.com/servo/servo/blob/main/components/selectors/matching.rs for how these prune the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchResult {
+    Matched,
+    ///Try a sibling further back, or give up on this sibling chain.
+    NotMatchedAndRestartFromClosestLaterSibling,
+    ///Trying another sibling can not help, only an ancestor further up can.
+    NotMatchedAndRestartFromClosestDescendant,
+    ///No other element can make this selector match.
+    NotMatchedGlobally,
+}
+
+///The element a relative selector of `:has()` is anchored to, and how its first compound selector relates to it.
+type Anchor<'e, E> = Option<(&'e E, Combinator)>;
+
+fn matches_complex<E: Element>(
+    selector: &Selector,
+    index: usize,
+    element: &E,
+    anchor: Anchor<E>,
+) -> MatchResult {
+    if !matches_compound(&selector.compounds[index], element) {
+        return MatchResult::NotMatchedAndRestartFromClosestLaterSibling;
+    }
+    if index == 0 {
+        return match anchor {
+            Some((anchor, combinator)) if !is_related(element, anchor, combinator) => {
+                MatchResult::NotMatchedAndRestartFromClosestLaterSibling
+            }
+            _ => MatchResult::Matched,
+        };
+    }
+
+    let combinator = selector.combinators[index - 1];
+    let (mut next, not_found) = match combinator {
+        Combinator::NextSibling | Combinator::SubsequentSibling => (
+            element.prev_sibling(),
+            MatchResult::NotMatchedAndRestartFromClosestDescendant,
+        ),
+        Combinator::Child | Combinator::Descendant => {
+            (element.parent(), MatchResult::NotMatchedGlobally)
+        }
+    };
+    loop {
+        let Some(candidate) = next else {
+            return not_found;
+        };
+        let result = matches_complex(selector, index - 1, &candidate, anchor);
+        match (result, combinator) {
+            (MatchResult::Matched, _) => return result,
+            (MatchResult::NotMatchedGlobally, _) | (_, Combinator::NextSibling) => return result,
+            (_, Combinator::Child) => {
+                return MatchResult::NotMatchedAndRestartFromClosestDescendant;
+            }
+            (
+                MatchResult::NotMatchedAndRestartFromClosestDescendant,
+                Combinator::SubsequentSibling,
+            ) => return result,
+            _ => {}
+        }
+        next = match combinator {
+            Combinator::SubsequentSibling => candidate.prev_sibling(),
+            _ => candidate.parent(),
+        };
+    }
+}
+
+///Whether `element` relates to `anchor` the way `combinator` describes.
+fn is_related<E: Element>(element: &E, anchor: &E, combinator: Combinator) -> bool {
+    match combinator {
+        Combinator::Child => element.parent().as_ref() == Some(anchor),
+        Combinator::Descendant => ancestors(element).any(|v| &v == anchor),
+        Combinator::NextSibling => element.prev_sibling().as_ref() == Some(anchor),
+        Combinator::SubsequentSibling => prev_siblings(element).any(|v| &v == anchor),
+    }
+}
+
+fn ancestors<E: Element>(element: &E) -> impl Iterator<Item = E> {
+    std::iter::successors(element.parent(), E::parent)
+}
+
+fn prev_siblings<E: Element>(element: &E) -> impl Iterator<Item = E> {
+    std::iter::successors(element.prev_sibling(), E::prev_sibling)
+}
+
+fn next_siblings<E: Element>(element: &E) -> impl Iterator<Item = E> {
+    std::iter::successors(element.next_sibling(), E::next_sibling)
+}
+
+fn children<E: Element>(element: &E) -> impl Iterator<Item = E> {
+    std::iter::successors(element.first_child(), E::next_sibling)
+}
+
+///Calls `f` on every descendant of `element` in tree order, stopping as soon as it returns `true`.
+fn any_descendant<E: Element>(element: &E, f: &mut impl FnMut(&E) -> bool) -> bool {
+    children(element).any(|child| f(&child) || any_descendant(&child, f))
+}
+
+fn matches_compound<E: Element>(compound: &CompoundSelector, element: &E) -> bool {
+    compound
+        .selectors
+        .iter()
+        .all(|v| matches_simple(v, element))
+}
+
+fn matches_namespace<E: Element>(namespace: &Option<NamespacePrefix>, element: &E) -> bool {
+    match namespace {
+        None | Some(NamespacePrefix::Any) => true,
+        Some(NamespacePrefix::None) => element.namespace().is_none(),
+        Some(NamespacePrefix::Named(name)) => element.namespace() == Some(name),
+    }
+}
+
+fn matches_simple<E: Element>(selector: &SimpleSelector, element: &E) -> bool {
+    match selector {
+        SimpleSelector::Type { namespace, name } => {
+            matches_namespace(namespace, element) && element.local_name().eq_ignore_ascii_case(name)
+        }
+        SimpleSelector::Universal { namespace } => matches_namespace(namespace, element),
+        SimpleSelector::Id(id) => element.id() == Some(id),
+        SimpleSelector::Class(class) => element.has_class(class),
+        SimpleSelector::Attribute(attribute) => matches_attribute(attribute, element),
+        SimpleSelector::PseudoClass(pseudo_class) => matches_pseudo_class(pseudo_class, element),
+        SimpleSelector::PseudoElement(_) => true,
+        // A `&` that was not replaced by the selectors of a parent rule is `:scope`.
+        SimpleSelector::Nesting => element.parent().is_none(),
+    }
+}
+
+fn matches_attribute<E: Element>(selector: &AttributeSelector, element: &E) -> bool {
+    // Elements only have attributes without a namespace.
+    if let Some(NamespacePrefix::Named(_)) = selector.namespace {
+        return false;
+    }
+    let Some(value) = element.attribute(&selector.name) else {
+        return false;
+    };
+    let Some(matcher) = &selector.matcher else {
+        return true;
+    };
+    let (value, expected) = match matcher.case {
+        AttributeCase::Insensitive => (
+            value.to_ascii_lowercase(),
+            matcher.value.to_ascii_lowercase(),
+        ),
+        AttributeCase::Default | AttributeCase::Sensitive => {
+            (value.to_string(), matcher.value.to_string())
+        }
+    };
+    // https://www.w3.org/TR/selectors-4/#attribute-representation
+    match matcher.operator {
+        AttributeOperator::Equals => value == expected,
+        AttributeOperator::Includes => {
+            !expected.is_empty()
+                && !expected.contains(char::is_whitespace)
+                && value.split_ascii_whitespace().any(|v| v == expected)
+        }
+        AttributeOperator::DashMatch => {
+            value == expected
+                || value
+                    .strip_prefix(&expected)
+                    .is_some_and(|v| v.starts_with('-'))
+        }
+        // [att^=""], [att$=""] and [att*=""] represent nothing.
+        AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(&expected),
+        AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(&expected),
+        AttributeOperator::Substring => !expected.is_empty() && value.contains(&expected),
+    }
+}
+
+fn matches_pseudo_class<E: Element>(selector: &PseudoClass, element: &E) -> bool {
+    let state = element.state();
+    match selector {
+        PseudoClass::Hover => state.contains(ElementState::HOVER),
+        PseudoClass::Active => state.contains(ElementState::ACTIVE),
+        PseudoClass::Focus => state.contains(ElementState::FOCUS),
+        PseudoClass::FocusVisible => state.contains(ElementState::FOCUS_VISIBLE),
+        PseudoClass::FocusWithin => {
+            state.contains(ElementState::FOCUS)
+                || any_descendant(element, &mut |v| v.state().contains(ElementState::FOCUS))
+        }
+        PseudoClass::Enabled => !state.contains(ElementState::DISABLED),
+        PseudoClass::Disabled => state.contains(ElementState::DISABLED),
+        PseudoClass::Checked => state.contains(ElementState::CHECKED),
+        PseudoClass::Indeterminate => state.contains(ElementState::INDETERMINATE),
+        PseudoClass::Required => state.contains(ElementState::REQUIRED),
+        PseudoClass::Optional => !state.contains(ElementState::REQUIRED),
+        PseudoClass::ReadOnly => state.contains(ElementState::READ_ONLY),
+        PseudoClass::ReadWrite => !state.contains(ElementState::READ_ONLY),
+        PseudoClass::PlaceholderShown => state.contains(ElementState::PLACEHOLDER_SHOWN),
+        PseudoClass::Default => state.contains(ElementState::DEFAULT),
+        PseudoClass::Valid => !state.contains(ElementState::INVALID),
+        PseudoClass::Invalid => state.contains(ElementState::INVALID),
+        PseudoClass::Link => {
+            state.contains(ElementState::LINK) && !state.contains(ElementState::VISITED)
+        }
+        PseudoClass::Visited => state.contains(ElementState::LINK | ElementState::VISITED),
+        PseudoClass::AnyLink => state.contains(ElementState::LINK),
+        PseudoClass::Target => state.contains(ElementState::TARGET),
+        // Without a scoping root, :scope is the root of the tree.
+        PseudoClass::Root | PseudoClass::Scope => element.parent().is_none(),
+        PseudoClass::Empty => element.is_empty(),
+        // Every element of the tree is a known component.
+        PseudoClass::Defined => true,
+        PseudoClass::FirstChild => element.prev_sibling().is_none(),
+        PseudoClass::LastChild => element.next_sibling().is_none(),
+        PseudoClass::OnlyChild => {
+            element.prev_sibling().is_none() && element.next_sibling().is_none()
+        }
+        PseudoClass::FirstOfType => !prev_siblings(element).any(|v| same_type(&v, element)),
+        PseudoClass::LastOfType => !next_siblings(element).any(|v| same_type(&v, element)),
+        PseudoClass::OnlyOfType => {
+            !prev_siblings(element).any(|v| same_type(&v, element))
+                && !next_siblings(element).any(|v| same_type(&v, element))
+        }
+        PseudoClass::Not(list) => !matches_selector_list(list, element, None),
+        PseudoClass::Is(list) | PseudoClass::Where(list) => {
+            matches_selector_list(list, element, None)
+        }
+        PseudoClass::Has(list) => list.iter().any(|v| matches_relative(v, element)),
+        PseudoClass::NthChild { anb, of } => {
+            matches_nth(anb, element, prev_siblings(element), of.as_ref())
+        }
+        PseudoClass::NthLastChild { anb, of } => {
+            matches_nth(anb, element, next_siblings(element), of.as_ref())
+        }
+        PseudoClass::NthOfType(anb) => matches_nth(
+            anb,
+            element,
+            prev_siblings(element).filter(|v| same_type(v, element)),
+            None,
+        ),
+        PseudoClass::NthLastOfType(anb) => matches_nth(
+            anb,
+            element,
+            next_siblings(element).filter(|v| same_type(v, element)),
+            None,
+        ),
+    }
+}
+
+fn same_type<E: Element>(a: &E, b: &E) -> bool {
+    a.local_name().eq_ignore_ascii_case(b.local_name()) && a.namespace() == b.namespace()
+}
+
+///Matches `:nth-*()`, where `siblings` are the siblings that are counted before the element.
+fn matches_nth<E: Element>(
+    anb: &AnB,
+    element: &E,
+    siblings: impl Iterator<Item = E>,
+    of: Option<&SelectorList>,
+) -> bool {
+    let matches_of = |v: &E| of.is_none_or(|of| matches_selector_list(of, v, None));
+    if !matches_of(element) {
+        return false;
+    }
+    let index = siblings.filter(|v| matches_of(v)).count() + 1;
+    anb.matches(i32::try_from(index).unwrap_or(i32::MAX))
+}
+
+fn matches_relative<E: Element>(selector: &RelativeSelector, anchor: &E) -> bool {
+    let last = selector.selector.compounds.len() - 1;
+    let anchor_to = Some((anchor, selector.combinator));
+    let mut matches = |candidate: &E| {
+        matches_complex(&selector.selector, last, candidate, anchor_to) == MatchResult::Matched
+    };
+    // The subject of the relative selector is a descendant of the anchor or of one of its later siblings.
+    match selector.combinator {
+        Combinator::Child | Combinator::Descendant => any_descendant(anchor, &mut matches),
+        Combinator::NextSibling | Combinator::SubsequentSibling => {
+            next_siblings(anchor).any(|v| matches(&v) || any_descendant(&v, &mut matches))
+        }
+    }
+}
+
+///How many counters the filter has, which has to be a power of two.
+const FILTER_SIZE: usize = 1 << 12;
+
+///A counting bloom filter of the ids, classes and local names of the ancestors of the element being matched.
+///Selectors like `.sidebar a` can then be rejected without walking up the tree when no ancestor has the `sidebar` class.
+///It gives false positives but never false negatives, so it only ever speeds up matching.
+///
+///When walking the tree top-down, [`AncestorFilter::push`] an element before matching its children and [`AncestorFilter::pop`] it after.
+pub struct AncestorFilter {
+    counters: Box<[u8; FILTER_SIZE]>,
+    ///The hashes inserted for each pushed element, so they can be removed again.
+    hashes: Vec<u32>,
+    ///Where the hashes of each pushed element start in `hashes`.
+    elements: Vec<usize>,
+}
+
+impl Default for AncestorFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AncestorFilter {
+    pub fn new() -> Self {
+        Self {
+            counters: Box::new([0; FILTER_SIZE]),
+            hashes: vec![],
+            elements: vec![],
+        }
+    }
+
+    ///Adds an element as the innermost ancestor.
+    pub fn push<E: Element>(&mut self, element: &E) {
+        let start = self.hashes.len();
+        self.elements.push(start);
+        self.hashes.push(hash(
+            HashKind::LocalName,
+            &element.local_name().to_ascii_lowercase(),
+        ));
+        if let Some(id) = element.id() {
+            self.hashes.push(hash(HashKind::Id, id));
+        }
+        for class in element.classes() {
+            self.hashes.push(hash(HashKind::Class, class));
+        }
+        for i in start..self.hashes.len() {
+            for index in indices(self.hashes[i]) {
+                // A full counter can not tell how many elements share it anymore, so it stays full.
+                self.counters[index] = self.counters[index].saturating_add(1);
+            }
+        }
+    }
+
+    ///Removes the innermost ancestor.
+    pub fn pop(&mut self) {
+        let Some(start) = self.elements.pop() else {
+            return;
+        };
+        for hash in self.hashes.drain(start..) {
+            for index in indices(hash) {
+                let counter = &mut self.counters[index];
+                if *counter != u8::MAX {
+                    *counter -= 1;
+                }
+            }
+        }
+    }
+
+    ///How many ancestors were pushed.
+    pub fn depth(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn might_contain(&self, hash: u32) -> bool {
+        indices(hash).iter().all(|&v| self.counters[v] != 0)
+    }
+
+    ///Whether the ancestors could match the parts of the selector that have to match an ancestor.
+    fn might_match(&self, selector: &Selector) -> bool {
+        // `combinators[i]` joins `compounds[i]` to the one on its right, so a child or descendant combinator there makes `compounds[i]` an ancestor of the subject.
+        // That holds even further left of a sibling combinator, since siblings share their ancestors.
+        selector
+            .compounds
+            .iter()
+            .zip(&selector.combinators)
+            .filter(|(_, combinator)| {
+                matches!(combinator, Combinator::Child | Combinator::Descendant)
+            })
+            .flat_map(|(compound, _)| &compound.selectors)
+            .all(|v| match v {
+                SimpleSelector::Type { name, .. } => {
+                    self.might_contain(hash(HashKind::LocalName, &name.to_ascii_lowercase()))
+                }
+                SimpleSelector::Id(id) => self.might_contain(hash(HashKind::Id, id)),
+                SimpleSelector::Class(class) => self.might_contain(hash(HashKind::Class, class)),
+                _ => true,
+            })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum HashKind {
+    LocalName,
+    Id,
+    Class,
+}
+
+///FNV-1a, seeded with the kind so an id and a class with the same name do not collide.
+fn hash(kind: HashKind, value: &str) -> u32 {
+    let mut hash = 0x811c9dc5u32;
+    for byte in std::iter::once(kind as u8).chain(value.bytes()) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+///The two counters a hash maps to.
+fn indices(hash: u32) -> [usize; 2] {
+    let mask = FILTER_SIZE as u32 - 1;
+    [(hash & mask) as usize, ((hash >> 16) & mask) as usize]
+}
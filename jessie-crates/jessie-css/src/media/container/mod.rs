@@ -0,0 +1,190 @@
+//! The container queries of https://www.w3.org/TR/css-contain-3/#container-queries that `@container` rules are conditional on.
+//! They test the size of the nearest ancestor that is a query container, which is only known once it is laid out.
+use crate::CSSToken;
+use crate::cascade::ComputedStyle;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::matching::Element;
+use crate::media::{
+    FeatureDefinition, FeatureType, FeatureValue, QueryCondition, QueryEnvironment, orientation,
+};
+use crate::parser::ComponentValue;
+use crate::properties::custom::is_custom_ident;
+use crate::values::length::LengthContext;
+use crate::values::{Parse, ValueParser, keyword_enum, parse_value_with};
+
+keyword_enum! {
+    ///The `container-type` property, which makes an element a query container for the sizes it does not depend on its contents for.
+    pub enum ContainerType {
+        Normal = "normal",
+        Size = "size",
+        InlineSize = "inline-size",
+    }
+}
+
+///The `container-name` property, the names `@container` rules can select a container by. `none` is no names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContainerName(pub Vec<String>);
+
+impl Parse for ContainerName {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+            return Ok(ContainerName(vec![]));
+        }
+        let mut names = vec![parse_container_name(input)?];
+        while let Ok(name) = input.try_parse(parse_container_name) {
+            names.push(name);
+        }
+        Ok(ContainerName(names))
+    }
+}
+
+///Parses a <custom-ident> that can name a container, which excludes the keywords of the `@container` prelude.
+fn parse_container_name(input: &mut ValueParser) -> Result<String, CssDiagnostic> {
+    let (name, span) = input.expect_ident()?;
+    let reserved = ["none", "and", "or", "not"]
+        .iter()
+        .any(|v| name.eq_ignore_ascii_case(v));
+    match !reserved && is_custom_ident(name) {
+        true => Ok(name.to_string()),
+        false => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            format!("`{name}` can not be the name of a container"),
+        )),
+    }
+}
+
+///The features `@container` rules can ask about. Only horizontal writing modes are supported,
+///so the inline size is the width and the block size the height.
+pub static CONTAINER_FEATURES: &[FeatureDefinition] = &[
+    FeatureDefinition::new("width", FeatureType::Length),
+    FeatureDefinition::new("height", FeatureType::Length),
+    FeatureDefinition::new("inline-size", FeatureType::Length),
+    FeatureDefinition::new("block-size", FeatureType::Length),
+    FeatureDefinition::new("aspect-ratio", FeatureType::Ratio),
+    FeatureDefinition::new(
+        "orientation",
+        FeatureType::Discrete(&["portrait", "landscape"]),
+    ),
+];
+
+///An element that the `@container` rules of its descendants are evaluated against, see [`Element::query_container`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryContainer {
+    pub names: Vec<String>,
+    ///Never [`ContainerType::Normal`].
+    pub container_type: ContainerType,
+    ///The width of the content box of the container in CSS pixels.
+    pub width: f32,
+    ///The height of the content box of the container in CSS pixels.
+    pub height: f32,
+    ///What relative lengths in queries are relative to, with the font size of the container.
+    pub context: LengthContext,
+}
+
+impl QueryContainer {
+    ///Makes a container from the computed style of an element and the size its content box was laid out at.
+    ///Returns `None` if the `container-type` of the element is `normal`.
+    pub fn from_style(
+        style: &ComputedStyle,
+        width: f32,
+        height: f32,
+        context: LengthContext,
+    ) -> Option<Self> {
+        let container_type = style
+            .get_as::<ContainerType>("container-type")
+            .filter(|v| *v != ContainerType::Normal)?;
+        let names = style
+            .get_as::<ContainerName>("container-name")
+            .unwrap_or_default()
+            .0;
+        Some(Self {
+            names,
+            container_type,
+            width,
+            height,
+            context,
+        })
+    }
+}
+
+impl QueryEnvironment for QueryContainer {
+    fn feature(&self, name: &str) -> Option<FeatureValue> {
+        // An `inline-size` container does not know its height, which may depend on its contents.
+        let block = self.container_type == ContainerType::Size;
+        let value = match name {
+            "width" | "inline-size" => FeatureValue::Number(self.width),
+            "height" | "block-size" if block => FeatureValue::Number(self.height),
+            "aspect-ratio" if block => FeatureValue::Number(self.width / self.height),
+            "orientation" if block => FeatureValue::Keyword(orientation(self.width, self.height)),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn length_context(&self) -> LengthContext {
+        self.context
+    }
+}
+
+///The prelude of `@container`, like `sidebar (width >= 400px)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerQuery {
+    ///The name the container has to have, if any.
+    pub name: Option<String>,
+    pub condition: QueryCondition,
+}
+
+impl ContainerQuery {
+    ///Parses `[ <container-name> ]? <container-condition>`, reporting unknown features in `diagnostics`.
+    pub fn parse(
+        prelude: &[ComponentValue],
+        diagnostics: &mut Vec<CssDiagnostic>,
+    ) -> Result<Self, CssDiagnostic> {
+        parse_value_with(prelude, |input| {
+            let name = match input.peek().and_then(|v| v.token()) {
+                Some(CSSToken::IdentToken { value }) if !value.eq_ignore_ascii_case("not") => {
+                    Some(parse_container_name(input)?)
+                }
+                _ => None,
+            };
+            let condition = QueryCondition::parse(input, CONTAINER_FEATURES, true, diagnostics)?;
+            Ok(ContainerQuery { name, condition })
+        })
+        .map_err(|diagnostic| CssDiagnostic {
+            kind: CssDiagnosticKind::InvalidMediaQuery,
+            ..diagnostic
+        })
+    }
+
+    ///Whether the query can be evaluated against `container`: it has the name of the query,
+    ///and its type lets it answer every feature the condition tests.
+    pub fn selects(&self, container: &QueryContainer) -> bool {
+        let named = match &self.name {
+            Some(name) => container.names.iter().any(|v| v == name),
+            None => true,
+        };
+        named
+            && self
+                .condition
+                .features()
+                .iter()
+                .all(|v| container.feature(&v.name).is_some())
+    }
+
+    ///Evaluates the query against the nearest container it [selects](Self::selects) among `ancestor` and its ancestors,
+    ///which should be the parent of an element, or the originating element of a pseudo-element.
+    ///Without such a container the query does not match.
+    pub fn matches<E: Element>(&self, ancestor: Option<E>) -> bool {
+        let mut ancestor = ancestor;
+        while let Some(element) = ancestor {
+            if let Some(container) = element.query_container()
+                && self.selects(&container)
+            {
+                return self.condition.evaluate(&container) == Some(true);
+            }
+            ancestor = element.parent();
+        }
+        false
+    }
+}
@@ -0,0 +1,772 @@
+//! The media queries of https://www.w3.org/TR/mediaqueries-4/ that `@media` rules are conditional on.
+//! The conditions they are made of are shared with the container queries of [`container`].
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::ComponentValue;
+use crate::span::Span;
+use crate::values::calc::{Calc, CalcCategory, CalcNode};
+use crate::values::length::{Length, LengthContext};
+use crate::values::{Parse, ValueParser, keyword_enum, parse_value_with, unexpected};
+pub mod container;
+
+///What kind of device a document is shown on. Unknown media types like `tv` never match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    Unknown,
+}
+
+keyword_enum! {
+    ///The color scheme the user prefers, for `prefers-color-scheme`.
+    pub enum ColorScheme {
+        Light = "light",
+        Dark = "dark",
+    }
+}
+
+keyword_enum! {
+    ///How accurate a pointing device is, for `pointer`. A finger is `coarse`, a mouse `fine`.
+    pub enum Pointer {
+        None = "none",
+        Coarse = "coarse",
+        Fine = "fine",
+    }
+}
+
+keyword_enum! {
+    ///Whether a pointing device can hover over elements, for `hover`.
+    pub enum Hover {
+        None = "none",
+        Hover = "hover",
+    }
+}
+
+///What media queries are evaluated against, filled in by the app from its window and the platform it runs on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MediaEnvironment {
+    pub media_type: MediaType,
+    ///The width of the viewport in CSS pixels.
+    pub width: f32,
+    ///The height of the viewport in CSS pixels.
+    pub height: f32,
+    ///The number of device pixels per CSS pixel.
+    pub resolution: f32,
+    pub color_scheme: ColorScheme,
+    ///Whether the user asked for less motion, for `prefers-reduced-motion`.
+    pub reduced_motion: bool,
+    ///The primary pointing device. `any-pointer` and `any-hover` are answered with it too.
+    pub pointer: Pointer,
+    pub hover: Hover,
+}
+
+impl MediaEnvironment {
+    ///A light screen of the given size with a mouse, at one device pixel per CSS pixel.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            media_type: MediaType::Screen,
+            width,
+            height,
+            resolution: 1.0,
+            color_scheme: ColorScheme::Light,
+            reduced_motion: false,
+            pointer: Pointer::Fine,
+            hover: Hover::Hover,
+        }
+    }
+}
+
+impl Default for MediaEnvironment {
+    ///A 1024x768 desktop screen, see [`MediaEnvironment::new`].
+    fn default() -> Self {
+        Self::new(1024.0, 768.0)
+    }
+}
+
+impl QueryEnvironment for MediaEnvironment {
+    fn feature(&self, name: &str) -> Option<FeatureValue> {
+        let value = match name {
+            "width" => FeatureValue::Number(self.width),
+            "height" => FeatureValue::Number(self.height),
+            "aspect-ratio" => FeatureValue::Number(self.width / self.height),
+            "orientation" => FeatureValue::Keyword(orientation(self.width, self.height)),
+            "resolution" => FeatureValue::Number(self.resolution),
+            "prefers-color-scheme" => FeatureValue::Keyword(self.color_scheme.keyword()),
+            "prefers-reduced-motion" => FeatureValue::Keyword(match self.reduced_motion {
+                true => "reduce",
+                false => "no-preference",
+            }),
+            "pointer" | "any-pointer" => FeatureValue::Keyword(self.pointer.keyword()),
+            "hover" | "any-hover" => FeatureValue::Keyword(self.hover.keyword()),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn length_context(&self) -> LengthContext {
+        LengthContext::new(self.width, self.height)
+    }
+}
+
+///The `orientation` of a box, which is `portrait` for a square one.
+fn orientation(width: f32, height: f32) -> &'static str {
+    match height >= width {
+        true => "portrait",
+        false => "landscape",
+    }
+}
+
+///What query conditions are evaluated against, a [`MediaEnvironment`] or a [`container::QueryContainer`].
+pub trait QueryEnvironment {
+    ///The value of a feature, or `None` if it is not known here.
+    ///Lengths are in CSS pixels, resolutions in dppx and ratios are divided out.
+    fn feature(&self, name: &str) -> Option<FeatureValue>;
+    ///What relative lengths in queries are relative to.
+    fn length_context(&self) -> LengthContext;
+}
+
+///The value of a feature in a [`QueryEnvironment`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeatureValue {
+    Number(f32),
+    Keyword(&'static str),
+}
+
+///The values a feature takes, which decides how it can be queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureType {
+    ///A length like `600px`.
+    Length,
+    ///A ratio like `16/9`, or a single number which is divided by 1.
+    Ratio,
+    ///A resolution like `2dppx`, `2x` or `192dpi`.
+    Resolution,
+    ///One of a few keywords, which can only be compared with `:`.
+    Discrete(&'static [&'static str]),
+}
+
+///A feature that queries can ask about, see https://www.w3.org/TR/mediaqueries-4/#mq-features
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureDefinition {
+    pub name: &'static str,
+    pub feature_type: FeatureType,
+}
+
+impl FeatureDefinition {
+    const fn new(name: &'static str, feature_type: FeatureType) -> Self {
+        Self { name, feature_type }
+    }
+
+    ///Whether the feature can be compared with the range syntax and the `min-` and `max-` prefixes.
+    pub fn is_range(&self) -> bool {
+        !matches!(self.feature_type, FeatureType::Discrete(_))
+    }
+}
+
+///The features `@media` rules can ask about.
+pub static MEDIA_FEATURES: &[FeatureDefinition] = &[
+    FeatureDefinition::new("width", FeatureType::Length),
+    FeatureDefinition::new("height", FeatureType::Length),
+    FeatureDefinition::new("aspect-ratio", FeatureType::Ratio),
+    FeatureDefinition::new(
+        "orientation",
+        FeatureType::Discrete(&["portrait", "landscape"]),
+    ),
+    FeatureDefinition::new("resolution", FeatureType::Resolution),
+    FeatureDefinition::new(
+        "prefers-color-scheme",
+        FeatureType::Discrete(ColorScheme::KEYWORDS),
+    ),
+    FeatureDefinition::new(
+        "prefers-reduced-motion",
+        FeatureType::Discrete(&["no-preference", "reduce"]),
+    ),
+    FeatureDefinition::new("pointer", FeatureType::Discrete(Pointer::KEYWORDS)),
+    FeatureDefinition::new("any-pointer", FeatureType::Discrete(Pointer::KEYWORDS)),
+    FeatureDefinition::new("hover", FeatureType::Discrete(Hover::KEYWORDS)),
+    FeatureDefinition::new("any-hover", FeatureType::Discrete(Hover::KEYWORDS)),
+];
+
+///How the value of a feature is compared with the value in a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    ///The comparison with the sides swapped, so `400px < width` becomes `width > 400px`.
+    fn flip(self) -> Self {
+        match self {
+            Comparison::Less => Comparison::Greater,
+            Comparison::LessOrEqual => Comparison::GreaterOrEqual,
+            Comparison::Equal => Comparison::Equal,
+            Comparison::GreaterOrEqual => Comparison::LessOrEqual,
+            Comparison::Greater => Comparison::Less,
+        }
+    }
+
+    fn holds(self, feature: f32, value: f32) -> bool {
+        match self {
+            Comparison::Less => feature < value,
+            Comparison::LessOrEqual => feature <= value,
+            Comparison::Equal => feature == value,
+            Comparison::GreaterOrEqual => feature >= value,
+            Comparison::Greater => feature > value,
+        }
+    }
+}
+
+///A value a feature is compared with in a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    Length(Length),
+    ///A math function with lengths of different units, like `calc(50vw + 1rem)`.
+    Calc(Box<Calc>),
+    ///`numerator / denominator`.
+    Ratio(f32, f32),
+    ///A resolution in dppx.
+    Resolution(f32),
+    ///A lowercase keyword.
+    Keyword(String),
+}
+
+impl QueryValue {
+    ///The value as a number comparable with a [`FeatureValue::Number`], or `None` for keywords.
+    fn to_number(&self, context: &LengthContext) -> Option<f32> {
+        match self {
+            QueryValue::Length(length) => Some(length.to_px(context)),
+            QueryValue::Calc(calc) => Some(calc.to_px(context, 0.0)),
+            QueryValue::Ratio(numerator, denominator) => Some(numerator / denominator),
+            QueryValue::Resolution(dppx) => Some(*dppx),
+            QueryValue::Keyword(_) => None,
+        }
+    }
+}
+
+///A test of a single feature, like `(width >= 600px)`, `(400px < width < 800px)` or `(hover)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureQuery {
+    ///The lowercase name of the feature, without a `min-` or `max-` prefix.
+    pub name: String,
+    ///What the value of the feature is compared with, as `feature <comparison> value`.
+    ///Empty for a test in a boolean context like `(hover)`, which is true unless the value is 0, `none` or `no-preference`.
+    pub comparisons: Vec<(Comparison, QueryValue)>,
+}
+
+impl FeatureQuery {
+    fn evaluate(&self, environment: &impl QueryEnvironment) -> Option<bool> {
+        let feature = environment.feature(&self.name)?;
+        if self.comparisons.is_empty() {
+            return Some(match feature {
+                FeatureValue::Number(value) => value != 0.0,
+                FeatureValue::Keyword(keyword) => !matches!(keyword, "none" | "no-preference"),
+            });
+        }
+        let context = environment.length_context();
+        self.comparisons
+            .iter()
+            .map(|(comparison, value)| match (feature, value) {
+                (FeatureValue::Keyword(feature), QueryValue::Keyword(value)) => {
+                    Some(feature.eq_ignore_ascii_case(value))
+                }
+                (FeatureValue::Number(feature), value) => value
+                    .to_number(&context)
+                    .map(|value| comparison.holds(feature, value)),
+                _ => None,
+            })
+            .try_fold(true, |all, holds| Some(all && holds?))
+    }
+}
+
+///A condition of a media or container query, like `(width >= 600px) and (not (hover))`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryCondition {
+    Feature(FeatureQuery),
+    Not(Box<QueryCondition>),
+    And(Vec<QueryCondition>),
+    Or(Vec<QueryCondition>),
+    ///Something in parentheses that is not a feature the engine knows, or a function like `style()`.
+    ///It is neither true nor false, see https://www.w3.org/TR/mediaqueries-4/#evaluating
+    Unknown,
+}
+
+impl QueryCondition {
+    ///Parses `<media-condition>`, reporting unknown features in `diagnostics`.
+    ///`features` are the features known where the condition is used, and `allow_or` is false for `<media-condition-without-or>`.
+    pub fn parse(
+        input: &mut ValueParser,
+        features: &[FeatureDefinition],
+        allow_or: bool,
+        diagnostics: &mut Vec<CssDiagnostic>,
+    ) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("not")).is_ok() {
+            let condition = parse_in_parens(input, features, diagnostics)?;
+            return Ok(QueryCondition::Not(Box::new(condition)));
+        }
+        let first = parse_in_parens(input, features, diagnostics)?;
+        let operator = match input.peek().and_then(|v| v.token()) {
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("and") => "and",
+            Some(CSSToken::IdentToken { value })
+                if allow_or && value.eq_ignore_ascii_case("or") =>
+            {
+                "or"
+            }
+            _ => return Ok(first),
+        };
+        let mut conditions = vec![first];
+        while input.try_parse(|v| v.expect_keyword(operator)).is_ok() {
+            conditions.push(parse_in_parens(input, features, diagnostics)?);
+        }
+        if let Some(value) = input.peek()
+            && let Some(CSSToken::IdentToken { value: ident }) = value.token()
+            && (ident.eq_ignore_ascii_case("and") || ident.eq_ignore_ascii_case("or"))
+        {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidMediaQuery,
+                value.span(),
+                "`and` and `or` can not be mixed without parentheses",
+            ));
+        }
+        Ok(match operator {
+            "and" => QueryCondition::And(conditions),
+            _ => QueryCondition::Or(conditions),
+        })
+    }
+
+    ///Evaluates the condition with the three-valued logic of https://www.w3.org/TR/mediaqueries-4/#evaluating,
+    ///`None` being unknown. Queries treat an unknown result as false.
+    pub fn evaluate(&self, environment: &impl QueryEnvironment) -> Option<bool> {
+        match self {
+            QueryCondition::Feature(feature) => feature.evaluate(environment),
+            QueryCondition::Not(condition) => condition.evaluate(environment).map(|v| !v),
+            QueryCondition::And(conditions) => {
+                let results: Vec<_> = conditions.iter().map(|v| v.evaluate(environment)).collect();
+                match results.contains(&Some(false)) {
+                    true => Some(false),
+                    false => results.into_iter().try_fold(true, |_, v| v),
+                }
+            }
+            QueryCondition::Or(conditions) => {
+                let results: Vec<_> = conditions.iter().map(|v| v.evaluate(environment)).collect();
+                match results.contains(&Some(true)) {
+                    true => Some(true),
+                    false => results.into_iter().try_fold(false, |_, v| v),
+                }
+            }
+            QueryCondition::Unknown => None,
+        }
+    }
+
+    ///Returns the features the condition tests.
+    pub fn features(&self) -> Vec<&FeatureQuery> {
+        match self {
+            QueryCondition::Feature(feature) => vec![feature],
+            QueryCondition::Not(condition) => condition.features(),
+            QueryCondition::And(conditions) | QueryCondition::Or(conditions) => conditions
+                .iter()
+                .flat_map(QueryCondition::features)
+                .collect(),
+            QueryCondition::Unknown => vec![],
+        }
+    }
+}
+
+///Parses `<media-in-parens>`: a condition or a feature in parentheses, or a function.
+///Whatever does not parse inside the parentheses is [`QueryCondition::Unknown`] and reported in `diagnostics`.
+fn parse_in_parens(
+    input: &mut ValueParser,
+    features: &[FeatureDefinition],
+    diagnostics: &mut Vec<CssDiagnostic>,
+) -> Result<QueryCondition, CssDiagnostic> {
+    let value = input.consume()?;
+    let block = match value {
+        ComponentValue::SimpleBlock(block)
+            if block.associated_token == CSSToken::LeftParenthesisToken =>
+        {
+            block
+        }
+        ComponentValue::Function(_) => return Ok(QueryCondition::Unknown),
+        _ => {
+            return Err(unexpected(
+                value,
+                "a condition in parentheses like `(width >= 600px)`",
+            ));
+        }
+    };
+    let mut inner = ValueParser::new(&block.value);
+    let nested = match inner.peek() {
+        Some(ComponentValue::SimpleBlock(_)) => true,
+        Some(value) => matches!(
+            value.token(),
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("not")
+        ),
+        None => false,
+    };
+    let out = match nested {
+        true => QueryCondition::parse(&mut inner, features, true, diagnostics),
+        false => parse_feature(&mut inner, &block.value, features, block.span),
+    }
+    .and_then(|condition| inner.expect_exhausted().map(|_| condition));
+    match out {
+        Ok(condition) => Ok(condition),
+        Err(diagnostic) => {
+            diagnostics.push(CssDiagnostic {
+                kind: CssDiagnosticKind::InvalidMediaQuery,
+                ..diagnostic
+            });
+            Ok(QueryCondition::Unknown)
+        }
+    }
+}
+
+///Parses `<media-feature>` from the contents of its parentheses, `values`.
+fn parse_feature(
+    input: &mut ValueParser,
+    values: &[ComponentValue],
+    features: &[FeatureDefinition],
+    span: Span,
+) -> Result<QueryCondition, CssDiagnostic> {
+    let feature = |name: &str, span: Span| {
+        features
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidMediaQuery,
+                    span,
+                    format!("unknown feature `{name}`, it never matches"),
+                )
+            })
+    };
+    let range = |definition: &FeatureDefinition, span: Span| match definition.is_range() {
+        true => Ok(()),
+        false => Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidMediaQuery,
+            span,
+            format!(
+                "`{}` is not a range feature and can only be compared with `:`",
+                definition.name
+            ),
+        )),
+    };
+    let name_first = matches!(
+        input.peek().and_then(|v| v.token()),
+        Some(CSSToken::IdentToken { .. })
+    );
+    if name_first {
+        let (name, name_span) = input.expect_ident()?;
+        let name = name.to_ascii_lowercase();
+        // `(hover)`
+        if input.is_exhausted() {
+            let definition = feature(&name, name_span)?;
+            return Ok(feature_query(definition, vec![]));
+        }
+        // `(min-width: 600px)`
+        if matches!(
+            input.peek().and_then(|v| v.token()),
+            Some(CSSToken::ColonToken)
+        ) {
+            input.consume()?;
+            let (comparison, base) = match (name.strip_prefix("min-"), name.strip_prefix("max-")) {
+                (Some(base), _) => (Comparison::GreaterOrEqual, base),
+                (_, Some(base)) => (Comparison::LessOrEqual, base),
+                _ => (Comparison::Equal, name.as_str()),
+            };
+            let definition = feature(base, name_span)?;
+            if comparison != Comparison::Equal {
+                range(definition, name_span)?;
+            }
+            let value = parse_query_value(input, definition)?;
+            return Ok(feature_query(definition, vec![(comparison, value)]));
+        }
+        // `(width >= 600px)`
+        let definition = feature(&name, name_span)?;
+        range(definition, name_span)?;
+        let comparison = parse_comparison(input)?;
+        let value = parse_query_value(input, definition)?;
+        return Ok(feature_query(definition, vec![(comparison, value)]));
+    }
+    // `(600px <= width)` or `(400px < width < 800px)`, where the name is the first keyword.
+    let (name, name_span) = values
+        .iter()
+        .find_map(|v| match v.token() {
+            Some(CSSToken::IdentToken { value }) => Some((value.to_ascii_lowercase(), v.span())),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            CssDiagnostic::new(
+                CssDiagnosticKind::InvalidMediaQuery,
+                span,
+                "expected a feature like `(width >= 600px)`",
+            )
+        })?;
+    let definition = feature(&name, name_span)?;
+    range(definition, name_span)?;
+    let first = parse_query_value(input, definition)?;
+    let first_comparison = parse_comparison(input)?;
+    input.expect_ident()?;
+    let mut comparisons = vec![(first_comparison.flip(), first)];
+    if !input.is_exhausted() {
+        let comparison_span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let second_comparison = parse_comparison(input)?;
+        let direction = |v: Comparison| match v {
+            Comparison::Less | Comparison::LessOrEqual => Some(true),
+            Comparison::Greater | Comparison::GreaterOrEqual => Some(false),
+            Comparison::Equal => None,
+        };
+        if direction(first_comparison).is_none()
+            || direction(first_comparison) != direction(second_comparison)
+        {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidMediaQuery,
+                comparison_span,
+                "both comparisons of a range have to point the same way, like `400px <= width < 800px`",
+            ));
+        }
+        comparisons.push((second_comparison, parse_query_value(input, definition)?));
+    }
+    Ok(feature_query(definition, comparisons))
+}
+
+fn feature_query(
+    definition: &FeatureDefinition,
+    comparisons: Vec<(Comparison, QueryValue)>,
+) -> QueryCondition {
+    QueryCondition::Feature(FeatureQuery {
+        name: definition.name.to_string(),
+        comparisons,
+    })
+}
+
+///Parses `<mf-comparison>`, where `<=` and `>=` can not have whitespace in between.
+fn parse_comparison(input: &mut ValueParser) -> Result<Comparison, CssDiagnostic> {
+    let value = input.consume()?;
+    let delim = match value.token() {
+        Some(CSSToken::DelimToken { value }) if matches!(value, '<' | '>' | '=') => *value,
+        _ => return Err(unexpected(value, "`<`, `<=`, `>`, `>=` or `=`")),
+    };
+    let or_equal = delim != '='
+        && input
+            .try_parse(|v| {
+                let equals = v.consume()?;
+                match equals.token() {
+                    Some(CSSToken::DelimToken { value: '=' })
+                        if equals.span().start == value.span().end =>
+                    {
+                        Ok(())
+                    }
+                    _ => Err(unexpected(equals, "`=`")),
+                }
+            })
+            .is_ok();
+    Ok(match (delim, or_equal) {
+        ('<', false) => Comparison::Less,
+        ('<', true) => Comparison::LessOrEqual,
+        ('>', false) => Comparison::Greater,
+        ('>', true) => Comparison::GreaterOrEqual,
+        _ => Comparison::Equal,
+    })
+}
+
+///Parses the value a feature is compared with, which has to be of the type of the feature.
+fn parse_query_value(
+    input: &mut ValueParser,
+    definition: &FeatureDefinition,
+) -> Result<QueryValue, CssDiagnostic> {
+    match definition.feature_type {
+        FeatureType::Length if input.at_math_function() => {
+            let calc = Calc::parse(input, CalcCategory::Length)?;
+            Ok(match calc.root {
+                CalcNode::Length(length) => QueryValue::Length(length),
+                _ => QueryValue::Calc(Box::new(calc)),
+            })
+        }
+        FeatureType::Length => Length::parse(input).map(QueryValue::Length),
+        FeatureType::Ratio => {
+            let span = input.peek().map(|v| v.span()).unwrap_or_default();
+            let numerator = input.expect_number()?;
+            let denominator = match input.try_parse(|v| v.expect_delim('/')) {
+                Ok(()) => input.expect_number()?,
+                Err(_) => 1.0,
+            };
+            if numerator < 0.0 || denominator < 0.0 {
+                return Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidMediaQuery,
+                    span,
+                    "the parts of a ratio can not be negative",
+                ));
+            }
+            Ok(QueryValue::Ratio(numerator, denominator))
+        }
+        FeatureType::Resolution => {
+            let value = input.consume()?;
+            match value.token() {
+                Some(CSSToken::DimensionToken {
+                    value: resolution,
+                    unit,
+                    ..
+                }) => resolution_to_dppx(*resolution as f32, unit),
+                _ => None,
+            }
+            .map(QueryValue::Resolution)
+            .ok_or_else(|| unexpected(value, "a resolution like `2dppx`"))
+        }
+        FeatureType::Discrete(keywords) => {
+            let (ident, span) = input.expect_ident()?;
+            match keywords.iter().find(|v| ident.eq_ignore_ascii_case(v)) {
+                Some(keyword) => Ok(QueryValue::Keyword(keyword.to_string())),
+                None => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidMediaQuery,
+                    span,
+                    format!(
+                        "`{}` is one of `{}`, found `{ident}`",
+                        definition.name,
+                        keywords.join("`, `")
+                    ),
+                )),
+            }
+        }
+    }
+}
+
+///Converts a resolution to dppx, or returns `None` if `unit` is not one of the <resolution> units.
+pub fn resolution_to_dppx(resolution: f32, unit: &str) -> Option<f32> {
+    let unit = unit.to_ascii_lowercase();
+    match unit.as_str() {
+        "dppx" | "x" => Some(resolution),
+        "dpi" => Some(resolution / 96.0),
+        "dpcm" => Some(resolution * 2.54 / 96.0),
+        _ => None,
+    }
+}
+
+///A media query like `screen and (width >= 600px)` or `(prefers-color-scheme: dark)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    ///Whether the query starts with `not`, which negates the media type and the condition together.
+    pub negated: bool,
+    pub media_type: MediaType,
+    pub condition: Option<QueryCondition>,
+}
+
+impl MediaQuery {
+    ///`not all`, which invalid media queries are replaced with so they never match.
+    pub const NEVER: MediaQuery = MediaQuery {
+        negated: true,
+        media_type: MediaType::All,
+        condition: None,
+    };
+
+    ///Parses `<media-query>`, reporting unknown features in `diagnostics`.
+    pub fn parse(
+        input: &mut ValueParser,
+        diagnostics: &mut Vec<CssDiagnostic>,
+    ) -> Result<Self, CssDiagnostic> {
+        // A query that is only a condition starts with parentheses, optionally after `not`.
+        let mut lookahead = input.clone();
+        let _ = lookahead.try_parse(|v| v.expect_keyword("not"));
+        if matches!(
+            lookahead.peek(),
+            Some(ComponentValue::SimpleBlock(_) | ComponentValue::Function(_))
+        ) {
+            return Ok(MediaQuery {
+                negated: false,
+                media_type: MediaType::All,
+                condition: Some(QueryCondition::parse(
+                    input,
+                    MEDIA_FEATURES,
+                    true,
+                    diagnostics,
+                )?),
+            });
+        }
+        let negated = input.try_parse(|v| v.expect_keyword("not")).is_ok();
+        if !negated {
+            let _ = input.try_parse(|v| v.expect_keyword("only"));
+        }
+        let (media_type, span) = input.expect_ident()?;
+        let media_type = match media_type.to_ascii_lowercase().as_str() {
+            "all" => MediaType::All,
+            "screen" => MediaType::Screen,
+            "print" => MediaType::Print,
+            reserved @ ("not" | "only" | "and" | "or" | "layer") => {
+                return Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidMediaQuery,
+                    span,
+                    format!("`{reserved}` can not be a media type"),
+                ));
+            }
+            _ => MediaType::Unknown,
+        };
+        let condition = match input.try_parse(|v| v.expect_keyword("and")) {
+            Ok(()) => Some(QueryCondition::parse(
+                input,
+                MEDIA_FEATURES,
+                false,
+                diagnostics,
+            )?),
+            Err(_) => None,
+        };
+        Ok(MediaQuery {
+            negated,
+            media_type,
+            condition,
+        })
+    }
+
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        let media_type = match self.media_type {
+            MediaType::All => true,
+            MediaType::Unknown => false,
+            media_type => media_type == environment.media_type,
+        };
+        let result = match (media_type, &self.condition) {
+            (false, _) => Some(false),
+            (true, Some(condition)) => condition.evaluate(environment),
+            (true, None) => Some(true),
+        };
+        result.is_some_and(|v| v != self.negated)
+    }
+}
+
+///A comma separated list of media queries, like the prelude of `@media`.
+///It matches if any of its queries does, and an empty list matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaQueryList {
+    pub queries: Vec<MediaQuery>,
+}
+
+impl MediaQueryList {
+    ///Parses `<media-query-list>`. Invalid queries are reported in `diagnostics` and replaced with [`MediaQuery::NEVER`],
+    ///which leaves the other queries of the list working.
+    pub fn parse(values: &[ComponentValue], diagnostics: &mut Vec<CssDiagnostic>) -> Self {
+        if values.iter().all(ComponentValue::is_whitespace) {
+            return Self::default();
+        }
+        let queries = values
+            .split(|v| matches!(v.token(), Some(CSSToken::CommaToken)))
+            .map(|query| {
+                match parse_value_with(query, |input| MediaQuery::parse(input, diagnostics)) {
+                    Ok(query) => query,
+                    Err(diagnostic) => {
+                        diagnostics.push(CssDiagnostic {
+                            kind: CssDiagnosticKind::InvalidMediaQuery,
+                            ..diagnostic
+                        });
+                        MediaQuery::NEVER
+                    }
+                }
+            })
+            .collect();
+        Self { queries }
+    }
+
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|v| v.matches(environment))
+    }
+}
@@ -0,0 +1,355 @@
+//! Desugars the nested style rules of https://www.w3.org/TR/css-nesting-1/ into flat style rules the cascade can use.
+//! `.card { color: red; & > .title { color: blue } }` becomes `.card { color: red }` followed by `.card > .title { color: blue }`,
+//! with `&` replaced by the selectors of the parent rule so the specificity stays the one of `:is()` the spec asks for.
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{AtRule, ComponentValue, Declaration, DeclarationOrRule, Parser, Rule};
+use crate::selectors::{
+    CompoundSelector, PseudoClass, RelativeSelector, Selector, SelectorList, SimpleSelector,
+};
+use crate::span::Span;
+use std::borrow::Cow;
+
+///The at-rules whose block holds rules, which can be nested in style rules and can hold nested style rules themselves.
+pub(crate) const GROUP_RULES: [&str; 6] = [
+    "media",
+    "container",
+    "supports",
+    "layer",
+    "scope",
+    "starting-style",
+];
+
+///A style rule without nested rules, whose selectors have no `&` left other than at the top level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleRule<'a> {
+    pub selectors: SelectorList<'a>,
+    ///The declarations as written, which are not validated yet.
+    pub declarations: Vec<Declaration<'a>>,
+    pub span: Span,
+}
+
+///An at-rule whose block holds rules, like `@media` or `@layer`, with the rules of its block flattened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupRule<'a> {
+    pub name: Cow<'a, str>,
+    pub prelude: Vec<ComponentValue<'a>>,
+    pub rules: Vec<FlatRule<'a>>,
+    pub span: Span,
+}
+
+///A rule of a stylesheet once nesting is desugared, see [`flatten`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlatRule<'a> {
+    Style(StyleRule<'a>),
+    Group(GroupRule<'a>),
+    ///Any other at-rule, like `@keyframes` or a `@layer` statement, which holds no style rules.
+    AtRule(AtRule<'a>),
+}
+
+///Flattens the rules of a stylesheet, moving nested style rules after the rule they are nested in and resolving their `&`.
+///Nested group rules like `@media` stay around the rules nested in them.
+///Declarations that come after a nested rule apply after it too, like https://www.w3.org/TR/css-nesting-1/#nested-declarations-rule
+pub fn flatten<'a>(rules: &[Rule<'a>], diagnostics: &mut Vec<CssDiagnostic>) -> Vec<FlatRule<'a>> {
+    let mut out = vec![];
+    for rule in rules {
+        flatten_rule(rule, None, diagnostics, &mut out);
+    }
+    out
+}
+
+fn flatten_rule<'a>(
+    rule: &Rule<'a>,
+    parent: Option<&SelectorList<'a>>,
+    diagnostics: &mut Vec<CssDiagnostic>,
+    out: &mut Vec<FlatRule<'a>>,
+) {
+    match rule {
+        Rule::QualifiedRule(rule) => {
+            let selectors = match parent {
+                None => SelectorList::parse(&rule.prelude),
+                Some(parent) => {
+                    SelectorList::parse_nested(&rule.prelude).map(|v| resolve_list(&v, parent))
+                }
+            };
+            // An invalid selector drops the rule along with the rules nested in it.
+            let selectors = match selectors {
+                Ok(selectors) => selectors,
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    return;
+                }
+            };
+            let mut parser = Parser::from_component_values(&rule.block.value);
+            let contents = parser.parse_block_contents();
+            diagnostics.append(&mut parser.take_diagnostics());
+            flatten_contents(contents, &selectors, Some(rule.span), diagnostics, out);
+        }
+        Rule::AtRule(rule) => {
+            let group = GROUP_RULES
+                .iter()
+                .any(|v| rule.name.eq_ignore_ascii_case(v));
+            let block = match &rule.block {
+                Some(block) if group => block,
+                _ if parent.is_none() || group => {
+                    out.push(FlatRule::AtRule(rule.clone()));
+                    return;
+                }
+                _ => {
+                    diagnostics.push(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidAtRule,
+                        rule.span,
+                        format!("`@{}` can not be nested in a style rule", rule.name),
+                    ));
+                    return;
+                }
+            };
+            let mut parser = Parser::from_component_values(&block.value);
+            let mut rules = vec![];
+            match parent {
+                None => {
+                    for rule in parser.parse_list_of_rules() {
+                        flatten_rule(&rule, None, diagnostics, &mut rules);
+                    }
+                }
+                // The declarations directly in a group rule nested in a style rule apply to the elements of the style rule.
+                Some(parent) => {
+                    let contents = parser.parse_block_contents();
+                    flatten_contents(contents, parent, None, diagnostics, &mut rules);
+                }
+            }
+            diagnostics.append(&mut parser.take_diagnostics());
+            out.push(FlatRule::Group(GroupRule {
+                name: rule.name.clone(),
+                prelude: rule.prelude.clone(),
+                rules,
+                span: rule.span,
+            }));
+        }
+    }
+}
+
+///Flattens the contents of the block of a style rule with `selectors`, or of a group rule nested in it if `span` is `None`.
+///The declarations before the first nested rule belong to the style rule itself, and the ones after a nested rule to a rule of their own after it.
+fn flatten_contents<'a>(
+    contents: Vec<DeclarationOrRule<'a>>,
+    selectors: &SelectorList<'a>,
+    span: Option<Span>,
+    diagnostics: &mut Vec<CssDiagnostic>,
+    out: &mut Vec<FlatRule<'a>>,
+) {
+    let mut own = span.is_some();
+    let mut declarations = vec![];
+    let flush =
+        |declarations: &mut Vec<Declaration<'a>>, own: bool, out: &mut Vec<FlatRule<'a>>| {
+            if declarations.is_empty() {
+                return;
+            }
+            let span = match span {
+                Some(span) if own => span,
+                _ => declarations[0]
+                    .span
+                    .to(declarations[declarations.len() - 1].span),
+            };
+            let selectors = match own {
+                true => selectors.clone(),
+                false => nested_declarations_selectors(selectors),
+            };
+            out.push(FlatRule::Style(StyleRule {
+                selectors,
+                declarations: std::mem::take(declarations),
+                span,
+            }));
+        };
+    for item in contents {
+        match item {
+            DeclarationOrRule::Declaration(declaration) => declarations.push(declaration),
+            DeclarationOrRule::Rule(rule) => {
+                flush(&mut declarations, own, out);
+                own = false;
+                flatten_rule(&rule, Some(selectors), diagnostics, out);
+            }
+        }
+    }
+    flush(&mut declarations, own, out);
+}
+
+///The selectors of the declarations that come after a nested rule, which match like `&`.
+///Unlike `&` they can match the pseudo-elements of the parent rule, so those are kept as they are.
+fn nested_declarations_selectors<'a>(parent: &SelectorList<'a>) -> SelectorList<'a> {
+    if parent.selectors.len() == 1 {
+        return parent.clone();
+    }
+    let (pseudo_elements, elements): (Vec<_>, Vec<_>) = parent
+        .selectors
+        .iter()
+        .cloned()
+        .partition(|v| v.pseudo_element().is_some());
+    let mut selectors = pseudo_elements;
+    if !elements.is_empty() {
+        let span = elements[0].span;
+        selectors.insert(
+            0,
+            Selector {
+                compounds: vec![CompoundSelector {
+                    selectors: vec![SimpleSelector::PseudoClass(PseudoClass::Is(SelectorList {
+                        selectors: elements,
+                    }))],
+                }],
+                combinators: vec![],
+                span,
+            },
+        );
+    }
+    SelectorList { selectors }
+}
+
+///Replaces the `&` of the selectors of a nested style rule with the selectors of its parent.
+pub fn resolve_list<'a>(list: &SelectorList<'a>, parent: &SelectorList<'a>) -> SelectorList<'a> {
+    SelectorList {
+        selectors: list
+            .selectors
+            .iter()
+            .map(|v| resolve_selector(v, parent))
+            .collect(),
+    }
+}
+
+///Replaces the `&` of a selector with the selectors of the parent rule.
+///`&` is `:is()` of the parent selectors, but where that would give the same elements and specificity the parent selector is written out instead,
+///so `.a .b { & > .c {} }` becomes `.a .b > .c` and `.a { .b & {} }` becomes `.b .a`.
+///`&` can not match pseudo-elements, so parent selectors with one are left out.
+pub fn resolve_selector<'a>(selector: &Selector<'a>, parent: &SelectorList<'a>) -> Selector<'a> {
+    let parent = Parent::new(parent);
+    let starts_with_nesting = selector.compounds[0]
+        .selectors
+        .contains(&SimpleSelector::Nesting);
+    match parent.single {
+        // A `&` in the first compound selector can be replaced by the whole parent selector,
+        // since whatever it is combined with only relates to the element `&` matches.
+        Some(single) if starts_with_nesting && parent.compound().is_none() => {
+            let mut first = selector.compounds[0].clone();
+            let i = first
+                .selectors
+                .iter()
+                .position(|v| *v == SimpleSelector::Nesting)
+                .unwrap_or_default();
+            first.selectors.remove(i);
+            let mut compounds = single.compounds.clone();
+            let last = compounds.len() - 1;
+            compounds[last]
+                .selectors
+                .extend(parent.resolve_compound(&first).selectors);
+            compounds.extend(
+                selector.compounds[1..]
+                    .iter()
+                    .map(|v| parent.resolve_compound(v)),
+            );
+            let mut combinators = single.combinators.clone();
+            combinators.extend(selector.combinators.iter().copied());
+            Selector {
+                compounds,
+                combinators,
+                span: selector.span,
+            }
+        }
+        _ => parent.resolve(selector),
+    }
+}
+
+///The selectors `&` stands for.
+struct Parent<'p, 'a> {
+    ///The parent selectors without the ones that select a pseudo-element.
+    selectors: SelectorList<'a>,
+    ///The parent selector if there is only one.
+    single: Option<&'p Selector<'a>>,
+}
+
+impl<'p, 'a> Parent<'p, 'a> {
+    fn new(parent: &'p SelectorList<'a>) -> Self {
+        let selectors = SelectorList {
+            selectors: parent
+                .selectors
+                .iter()
+                .filter(|v| v.pseudo_element().is_none())
+                .cloned()
+                .collect(),
+        };
+        let single = match parent.selectors.as_slice() {
+            [single] if single.pseudo_element().is_none() => Some(single),
+            _ => None,
+        };
+        Self { selectors, single }
+    }
+
+    ///The compound selector of the parent if it is a single one, which can stand in for `&` anywhere.
+    fn compound(&self) -> Option<&'p CompoundSelector<'a>> {
+        match self.single?.compounds.as_slice() {
+            [compound] => Some(compound),
+            _ => None,
+        }
+    }
+
+    fn resolve(&self, selector: &Selector<'a>) -> Selector<'a> {
+        Selector {
+            compounds: selector
+                .compounds
+                .iter()
+                .map(|v| self.resolve_compound(v))
+                .collect(),
+            combinators: selector.combinators.clone(),
+            span: selector.span,
+        }
+    }
+
+    fn resolve_list(&self, list: &SelectorList<'a>) -> SelectorList<'a> {
+        SelectorList {
+            selectors: list.selectors.iter().map(|v| self.resolve(v)).collect(),
+        }
+    }
+
+    fn resolve_compound(&self, compound: &CompoundSelector<'a>) -> CompoundSelector<'a> {
+        let mut selectors = vec![];
+        for selector in &compound.selectors {
+            match selector {
+                SimpleSelector::Nesting => match self.compound() {
+                    Some(compound) => selectors.extend(compound.selectors.iter().cloned()),
+                    None => selectors.push(SimpleSelector::PseudoClass(PseudoClass::Is(
+                        self.selectors.clone(),
+                    ))),
+                },
+                SimpleSelector::PseudoClass(pseudo_class) => {
+                    selectors.push(SimpleSelector::PseudoClass(
+                        self.resolve_pseudo_class(pseudo_class),
+                    ));
+                }
+                selector => selectors.push(selector.clone()),
+            }
+        }
+        CompoundSelector { selectors }
+    }
+
+    fn resolve_pseudo_class(&self, pseudo_class: &PseudoClass<'a>) -> PseudoClass<'a> {
+        match pseudo_class {
+            PseudoClass::Not(list) => PseudoClass::Not(self.resolve_list(list)),
+            PseudoClass::Is(list) => PseudoClass::Is(self.resolve_list(list)),
+            PseudoClass::Where(list) => PseudoClass::Where(self.resolve_list(list)),
+            PseudoClass::Has(list) => PseudoClass::Has(
+                list.iter()
+                    .map(|v| RelativeSelector {
+                        combinator: v.combinator,
+                        selector: self.resolve(&v.selector),
+                    })
+                    .collect(),
+            ),
+            PseudoClass::NthChild { anb, of } => PseudoClass::NthChild {
+                anb: *anb,
+                of: of.as_ref().map(|v| self.resolve_list(v)),
+            },
+            PseudoClass::NthLastChild { anb, of } => PseudoClass::NthLastChild {
+                anb: *anb,
+                of: of.as_ref().map(|v| self.resolve_list(v)),
+            },
+            pseudo_class => pseudo_class.clone(),
+        }
+    }
+}
@@ -0,0 +1,1046 @@
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::nesting::GROUP_RULES;
+use crate::span::Span;
+use crate::stream::TokenStream;
+use crate::{CSSToken, SpannedToken, Tokenizer};
+use std::borrow::Cow;
+
+///A parser that turns the tokens of a [`crate::Tokenizer`] into CSS rules, declarations and component values.
+///It implements the parsing algorithms of https://www.w3.org/TR/css-syntax-3/#parsing
+pub struct Parser<'a> {
+    tokens: TokenStream<'a>,
+    diagnostics: Vec<CssDiagnostic>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        // https://www.w3.org/TR/css-syntax-3/#normalize-into-a-token-stream
+        // If input is a string, then tokenize input, and return the result.
+        // The tokens are only tokenized as the parser gets to them.
+        Self::from_token_stream(TokenStream::new(input))
+    }
+
+    pub fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Self {
+        // https://www.w3.org/TR/css-syntax-3/#normalize-into-a-token-stream
+        // If input is a list of CSS tokens, return input.
+        Self::from_token_stream(TokenStream::from_tokens(tokens))
+    }
+
+    ///Creates a parser over already parsed component values, for example to parse the declarations in the block of a style rule.
+    pub fn from_component_values(values: &[ComponentValue<'a>]) -> Self {
+        Self::from_token_stream(TokenStream::from_component_values(values))
+    }
+
+    pub fn from_token_stream(tokens: TokenStream<'a>) -> Self {
+        Self {
+            tokens,
+            diagnostics: vec![],
+        }
+    }
+
+    pub fn is_parse_error(&mut self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+
+    ///Returns the parse errors found so far, including the ones of the tokenizer for the tokens consumed so far.
+    pub fn diagnostics(&self) -> &[CssDiagnostic] {
+        &self.diagnostics
+    }
+
+    ///Returns the parse errors found so far, leaving none behind.
+    pub fn take_diagnostics(&mut self) -> Vec<CssDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub fn parse_error(&mut self, kind: CssDiagnosticKind, span: Span, message: impl Into<String>) {
+        self.diagnostics
+            .push(CssDiagnostic::new(kind, span, message));
+    }
+
+    pub fn next_token(&mut self) -> &CSSToken<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#next-input-token
+        // The token or component value following the current input token in the list of tokens produced by the tokenizer. If there isn’t a token following the current input token, the next input token is an <EOF-token>.
+        &self.next_spanned_token().token
+    }
+
+    pub fn next_spanned_token(&mut self) -> &SpannedToken<'a> {
+        self.tokens.peek();
+        self.diagnostics.append(&mut self.tokens.take_diagnostics());
+        self.tokens.peek()
+    }
+
+    pub fn consume_next_token(&mut self) -> SpannedToken<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-the-next-input-token
+        // Let the current input token be the current next input token, adjusting the next input token accordingly.
+        let out = self.tokens.consume();
+        self.diagnostics.append(&mut self.tokens.take_diagnostics());
+        out
+    }
+
+    pub fn reconsume_current_token(&mut self) {
+        // https://www.w3.org/TR/css-syntax-3/#reconsume-the-current-input-token
+        // The next time an algorithm instructs you to consume the next input token, instead do nothing (retain the current input token unchanged).
+        self.tokens.rewind();
+    }
+
+    pub fn consume_whitespace(&mut self) {
+        while self.next_token() == &CSSToken::WhitespaceToken {
+            self.consume_next_token();
+        }
+    }
+
+    pub fn parse_stylesheet(&mut self) -> Stylesheet<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-stylesheet
+
+        // Create a new stylesheet, with its location set to location (or null, if location was not passed).
+        // Consume a list of rules from input, with the top-level flag set, and set the stylesheet’s value to the result.
+        // Return the stylesheet.
+        Stylesheet {
+            rules: self.consume_list_of_rules(true),
+        }
+    }
+
+    pub fn parse_list_of_rules(&mut self) -> Vec<Rule<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-list-of-rules
+        // Consume a list of rules from the input, with the top-level flag unset.
+        // Return the returned list.
+        self.consume_list_of_rules(false)
+    }
+
+    pub fn parse_rule(&mut self) -> Result<Rule<'a>, SyntaxError> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-rule
+
+        // While the next input token from input is a <whitespace-token>, consume the next input token from input.
+        self.consume_whitespace();
+
+        let rule = match self.next_token() {
+            // If the next input token from input is an <EOF-token>, return a syntax error.
+            CSSToken::EOFToken => return Err(SyntaxError::UnexpectedEOF),
+            // Otherwise, if the next input token from input is an <at-keyword-token>, consume an at-rule from input, and let rule be the return value.
+            CSSToken::AtKeywordToken { .. } => Rule::AtRule(self.consume_at_rule()),
+            // Otherwise, consume a qualified rule from input and let rule be the return value. If nothing was returned, return a syntax error.
+            _ => Rule::QualifiedRule(
+                self.consume_qualified_rule()
+                    .ok_or(SyntaxError::InvalidRule)?,
+            ),
+        };
+
+        // While the next input token from input is a <whitespace-token>, consume the next input token from input.
+        self.consume_whitespace();
+
+        // If the next input token from input is an <EOF-token>, return rule. Otherwise, return a syntax error.
+        if self.next_token() == &CSSToken::EOFToken {
+            return Ok(rule);
+        }
+        Err(SyntaxError::ExpectedEOF)
+    }
+
+    pub fn parse_declaration(&mut self) -> Result<Declaration<'a>, SyntaxError> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-declaration
+
+        // While the next input token from input is a <whitespace-token>, consume the next input token.
+        self.consume_whitespace();
+
+        // If the next input token from input is not an <ident-token>, return a syntax error.
+        if !matches!(self.next_token(), CSSToken::IdentToken { .. }) {
+            return Err(SyntaxError::InvalidDeclaration);
+        }
+
+        // Consume a declaration from input. If anything was returned, return it. Otherwise, return a syntax error.
+        let mut values = vec![];
+        while self.next_token() != &CSSToken::EOFToken {
+            values.push(self.consume_component_value());
+        }
+        self.consume_declaration(values)
+            .ok_or(SyntaxError::InvalidDeclaration)
+    }
+
+    pub fn parse_list_of_declarations(&mut self) -> Vec<DeclarationOrAtRule<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-list-of-declarations
+        // Consume a list of declarations from input, and return the result.
+        self.consume_list_of_declarations()
+    }
+
+    ///Parses the contents of a style rule's block, which with https://www.w3.org/TR/css-nesting-1/ holds nested rules alongside declarations.
+    pub fn parse_block_contents(&mut self) -> Vec<DeclarationOrRule<'a>> {
+        // https://drafts.csswg.org/css-syntax-3/#parse-block-contents
+        // Consume a block’s contents from input, and return the result.
+        self.consume_block_contents()
+    }
+
+    pub fn parse_component_value(&mut self) -> Result<ComponentValue<'a>, SyntaxError> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-component-value
+
+        // While the next input token from input is a <whitespace-token>, consume the next input token from input.
+        self.consume_whitespace();
+
+        // If the next input token from input is an <EOF-token>, return a syntax error.
+        if self.next_token() == &CSSToken::EOFToken {
+            return Err(SyntaxError::UnexpectedEOF);
+        }
+
+        // Consume a component value from input and let value be the return value.
+        let value = self.consume_component_value();
+
+        // While the next input token from input is a <whitespace-token>, consume the next input token.
+        self.consume_whitespace();
+
+        // If the next input token from input is an <EOF-token>, return value. Otherwise, return a syntax error.
+        if self.next_token() == &CSSToken::EOFToken {
+            return Ok(value);
+        }
+        Err(SyntaxError::ExpectedEOF)
+    }
+
+    pub fn parse_list_of_component_values(&mut self) -> Vec<ComponentValue<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-list-of-component-values
+        // Repeatedly consume a component value from input until an <EOF-token> is returned, appending the returned values (except the final <EOF-token>) into a list. Return the list.
+        let mut out = vec![];
+        while self.next_token() != &CSSToken::EOFToken {
+            out.push(self.consume_component_value());
+        }
+        out
+    }
+
+    pub fn parse_comma_separated_list_of_component_values(
+        &mut self,
+    ) -> Vec<Vec<ComponentValue<'a>>> {
+        // https://www.w3.org/TR/css-syntax-3/#parse-comma-separated-list-of-component-values
+
+        // Let list of cvls be an initially empty list of component value lists.
+        let mut out = vec![];
+
+        // Repeatedly consume a component value from input until an <EOF-token> or <comma-token> is returned, appending the returned values (except the final <EOF-token> or <comma-token>) into a list. Append the list to list of cvls.
+        // If it was a <comma-token> that was returned, repeat this step.
+        loop {
+            let mut list = vec![];
+            while !matches!(self.next_token(), CSSToken::EOFToken | CSSToken::CommaToken) {
+                list.push(self.consume_component_value());
+            }
+            out.push(list);
+            if self.consume_next_token().token != CSSToken::CommaToken {
+                // Return list of cvls.
+                return out;
+            }
+        }
+    }
+
+    pub fn consume_list_of_rules(&mut self, top_level: bool) -> Vec<Rule<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-list-of-rules
+
+        // Create an initially empty list of rules.
+        let mut rules = vec![];
+        while let Some(rule) = self.consume_next_rule(top_level) {
+            rules.push(rule);
+        }
+        rules
+    }
+
+    ///Consumes the input of [`Parser::consume_list_of_rules`] up to the end of its next rule and returns it,
+    ///or returns `None` once the input ran out. This lets a caller stop in between rules, see [`crate::incremental`].
+    pub fn consume_next_rule(&mut self, top_level: bool) -> Option<Rule<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-list-of-rules
+
+        // Repeatedly consume the next input token:
+        loop {
+            match self.consume_next_token().token {
+                // <whitespace-token>
+                // Do nothing.
+                CSSToken::WhitespaceToken => {}
+                // <EOF-token>
+                // Return the list of rules.
+                CSSToken::EOFToken => return None,
+                // <CDO-token>
+                // <CDC-token>
+                CSSToken::CDOToken | CSSToken::CDCToken => {
+                    // If the top-level flag is set, do nothing.
+                    if top_level {
+                        continue;
+                    }
+                    // Otherwise, reconsume the current input token. Consume a qualified rule. If anything is returned, append it to the list of rules.
+                    self.reconsume_current_token();
+                    if let Some(rule) = self.consume_qualified_rule() {
+                        return Some(Rule::QualifiedRule(rule));
+                    }
+                }
+                // <at-keyword-token>
+                // Reconsume the current input token. Consume an at-rule, and append the returned value to the list of rules.
+                CSSToken::AtKeywordToken { .. } => {
+                    self.reconsume_current_token();
+                    return Some(Rule::AtRule(self.consume_at_rule()));
+                }
+                // anything else
+                // Reconsume the current input token. Consume a qualified rule. If anything is returned, append it to the list of rules.
+                _ => {
+                    self.reconsume_current_token();
+                    if let Some(rule) = self.consume_qualified_rule() {
+                        return Some(Rule::QualifiedRule(rule));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn consume_at_rule(&mut self) -> AtRule<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-at-rule
+
+        // Consume the next input token. Create a new at-rule with its name set to the value of the current input token, its prelude initially set to an empty list, and its value initially set to nothing.
+        let SpannedToken { token, span } = self.consume_next_token();
+        let name = match token {
+            CSSToken::AtKeywordToken { value } => value,
+            _ => unreachable!("consume_at_rule is only called on an <at-keyword-token>"),
+        };
+        let keyword_span = span;
+        let mut rule = AtRule {
+            name,
+            prelude: vec![],
+            block: None,
+            span,
+        };
+
+        // Repeatedly consume the next input token:
+        loop {
+            let SpannedToken { token, span } = self.consume_next_token();
+            match token {
+                // <semicolon-token>
+                // Return the at-rule.
+                CSSToken::SemicolonToken => {
+                    rule.span = rule.span.to(span);
+                    return rule;
+                }
+                // <EOF-token>
+                // This is a parse error. Return the at-rule.
+                CSSToken::EOFToken => {
+                    self.parse_error(
+                        CssDiagnosticKind::UnexpectedEOF,
+                        keyword_span,
+                        format!("`@{}` is missing a `;` or a `{{}}` block", rule.name),
+                    );
+                    return rule;
+                }
+                // <{-token>
+                // Consume a simple block and assign it to the at-rule’s block. Return the at-rule.
+                CSSToken::LeftCurlyBracketToken => {
+                    let mut block = self.consume_simple_block(SpannedToken { token, span });
+                    if let Some(input) = self.tokens.input() {
+                        retokenize_font_faces(&rule.name, &mut block, input);
+                    }
+                    rule.span = rule.span.to(block.span);
+                    rule.block = Some(block);
+                    return rule;
+                }
+                // anything else
+                // Reconsume the current input token. Consume a component value. Append the returned value to the at-rule’s prelude.
+                _ => {
+                    self.reconsume_current_token();
+                    let value = self.consume_component_value();
+                    rule.span = rule.span.to(value.span());
+                    rule.prelude.push(value);
+                }
+            }
+        }
+    }
+
+    pub fn consume_qualified_rule(&mut self) -> Option<QualifiedRule<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-qualified-rule
+
+        // Create a new qualified rule with its prelude initially set to an empty list, and its value initially set to nothing.
+        let mut prelude: Vec<ComponentValue<'a>> = vec![];
+
+        // Repeatedly consume the next input token:
+        loop {
+            let SpannedToken { token, span } = self.consume_next_token();
+            match token {
+                // <EOF-token>
+                // This is a parse error. Return nothing.
+                CSSToken::EOFToken => {
+                    let span = match (prelude.first(), prelude.last()) {
+                        (Some(first), Some(last)) => first.span().to(last.span()),
+                        _ => span,
+                    };
+                    self.parse_error(
+                        CssDiagnosticKind::UnexpectedEOF,
+                        span,
+                        "the rule is missing its `{}` block",
+                    );
+                    return None;
+                }
+                // <{-token>
+                // Consume a simple block and assign it to the qualified rule’s block. Return the qualified rule.
+                CSSToken::LeftCurlyBracketToken => {
+                    let block = self.consume_simple_block(SpannedToken { token, span });
+                    let span = prelude
+                        .first()
+                        .map_or(block.span, |v| v.span().to(block.span));
+                    return Some(QualifiedRule {
+                        prelude,
+                        block,
+                        span,
+                    });
+                }
+                // anything else
+                // Reconsume the current input token. Consume a component value. Append the returned value to the qualified rule’s prelude.
+                _ => {
+                    self.reconsume_current_token();
+                    prelude.push(self.consume_component_value());
+                }
+            }
+        }
+    }
+
+    pub fn consume_list_of_declarations(&mut self) -> Vec<DeclarationOrAtRule<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-list-of-declarations
+
+        // Create an initially empty list of declarations.
+        let mut declarations = vec![];
+
+        // Repeatedly consume the next input token:
+        loop {
+            let SpannedToken { token, span } = self.consume_next_token();
+            match token {
+                // <whitespace-token>
+                // <semicolon-token>
+                // Do nothing.
+                CSSToken::WhitespaceToken | CSSToken::SemicolonToken => {}
+                // <EOF-token>
+                // Return the list of declarations.
+                CSSToken::EOFToken => return declarations,
+                // <at-keyword-token>
+                // Reconsume the current input token. Consume an at-rule. Append the returned rule to the list of declarations.
+                CSSToken::AtKeywordToken { .. } => {
+                    self.reconsume_current_token();
+                    declarations.push(DeclarationOrAtRule::AtRule(self.consume_at_rule()));
+                }
+                // <ident-token>
+                ident @ CSSToken::IdentToken { .. } => {
+                    // Initialize a temporary list initially filled with the current input token.
+                    let mut temporary = vec![ComponentValue::PreservedToken(SpannedToken {
+                        token: ident,
+                        span,
+                    })];
+                    // As long as the next input token is anything other than a <semicolon-token> or <EOF-token>, consume a component value and append it to the temporary list.
+                    while !matches!(
+                        self.next_token(),
+                        CSSToken::SemicolonToken | CSSToken::EOFToken
+                    ) {
+                        temporary.push(self.consume_component_value());
+                    }
+                    // Consume a declaration from the temporary list. If anything was returned, append it to the list of declarations.
+                    if let Some(declaration) = self.consume_declaration(temporary) {
+                        declarations.push(DeclarationOrAtRule::Declaration(declaration));
+                    }
+                }
+                // anything else
+                _ => {
+                    // This is a parse error. Reconsume the current input token.
+                    self.parse_error(
+                        CssDiagnosticKind::UnexpectedToken,
+                        span,
+                        "expected a property name, declarations have to start with one",
+                    );
+                    self.reconsume_current_token();
+                    // As long as the next input token is anything other than a <semicolon-token> or <EOF-token>, consume a component value and throw away the returned value.
+                    while !matches!(
+                        self.next_token(),
+                        CSSToken::SemicolonToken | CSSToken::EOFToken
+                    ) {
+                        self.consume_component_value();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn consume_block_contents(&mut self) -> Vec<DeclarationOrRule<'a>> {
+        // https://drafts.csswg.org/css-syntax-3/#consume-block-contents
+
+        // Let rules be an empty list, containing either rules or lists of declarations.
+        let mut contents = vec![];
+
+        // Process input:
+        loop {
+            let SpannedToken { token, span } = self.consume_next_token();
+            match token {
+                // <whitespace-token>
+                // <semicolon-token>
+                // Discard a token from input.
+                CSSToken::WhitespaceToken | CSSToken::SemicolonToken => {}
+                // <EOF-token>
+                // Return the contents.
+                CSSToken::EOFToken => return contents,
+                // <at-keyword-token>
+                // Consume an at-rule from input, with nested set to true. If a rule was returned, append it to rules.
+                CSSToken::AtKeywordToken { .. } => {
+                    self.reconsume_current_token();
+                    contents.push(DeclarationOrRule::Rule(Rule::AtRule(
+                        self.consume_at_rule(),
+                    )));
+                }
+                // anything else
+                _ => {
+                    self.reconsume_current_token();
+                    if let Some(item) = self.consume_declaration_or_nested_rule(span) {
+                        contents.push(item);
+                    }
+                }
+            }
+        }
+    }
+
+    ///The spec tries to consume a declaration, and if that fails restores the input and consumes a qualified rule with nested set to true.
+    ///The stream can not be restored that far, so this collects component values until either a `;` ends a declaration
+    ///or a `{}` block ends a nested rule, deciding between the two like the spec would have.
+    fn consume_declaration_or_nested_rule(&mut self, start: Span) -> Option<DeclarationOrRule<'a>> {
+        let mut values: Vec<ComponentValue<'a>> = vec![];
+        loop {
+            if matches!(
+                self.next_token(),
+                CSSToken::SemicolonToken | CSSToken::EOFToken
+            ) {
+                break;
+            }
+            let value = self.consume_component_value();
+            let is_block = matches!(
+                &value,
+                ComponentValue::SimpleBlock(block) if block.associated_token == CSSToken::LeftCurlyBracketToken
+            );
+            if !is_block {
+                values.push(value);
+                continue;
+            }
+            // If decl’s name is a custom property name, its value can hold blocks, so it goes on until the `;`.
+            // Otherwise, a value that holds a `{}` block and also anything else makes it a qualified rule instead.
+            let name = declaration_name(&values);
+            if name.is_some_and(|v| v.starts_with("--")) {
+                values.push(value);
+                continue;
+            }
+            if name.is_some() {
+                let after_colon = values
+                    .iter()
+                    .skip_while(|v| v.token() != Some(&CSSToken::ColonToken))
+                    .skip(1)
+                    .any(|v| !v.is_whitespace());
+                self.consume_whitespace();
+                if !after_colon
+                    && matches!(
+                        self.next_token(),
+                        CSSToken::SemicolonToken | CSSToken::EOFToken
+                    )
+                {
+                    values.push(value);
+                    break;
+                }
+            }
+            let ComponentValue::SimpleBlock(block) = value else {
+                unreachable!("checked to be a block above");
+            };
+            let span = values
+                .first()
+                .map_or(block.span, |v| v.span().to(block.span));
+            return Some(DeclarationOrRule::Rule(Rule::QualifiedRule(
+                QualifiedRule {
+                    prelude: values,
+                    block,
+                    span,
+                },
+            )));
+        }
+        if !matches!(
+            values.first().and_then(ComponentValue::token),
+            Some(CSSToken::IdentToken { .. })
+        ) {
+            // A qualified rule that is cut off by the <semicolon-token> is a parse error, and nothing is returned.
+            let span = match (values.first(), values.last()) {
+                (Some(first), Some(last)) => first.span().to(last.span()),
+                _ => start,
+            };
+            self.parse_error(
+                CssDiagnosticKind::UnexpectedToken,
+                span,
+                "expected a declaration or a nested rule",
+            );
+            return None;
+        }
+        self.consume_declaration(values)
+            .map(DeclarationOrRule::Declaration)
+    }
+
+    pub fn consume_declaration(
+        &mut self,
+        input: Vec<ComponentValue<'a>>,
+    ) -> Option<Declaration<'a>> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-declaration
+        // The declaration is consumed from a list of component values, which is how both "parse a declaration" and "consume a list of declarations" hand it over.
+        let mut input = input.into_iter().peekable();
+
+        // Consume the next input token. Create a new declaration with its name set to the value of the current input token and its value initially set to an empty list.
+        let (name, mut span) = match input.next() {
+            Some(ComponentValue::PreservedToken(SpannedToken {
+                token: CSSToken::IdentToken { value },
+                span,
+            })) => (value, span),
+            _ => return None,
+        };
+
+        // While the next input token is a <whitespace-token>, consume the next input token.
+        while input.next_if(ComponentValue::is_whitespace).is_some() {}
+
+        // If the next input token is anything other than a <colon-token>, this is a parse error. Return nothing.
+        // Otherwise, consume the next input token.
+        if input
+            .next_if(|v| v.token() == Some(&CSSToken::ColonToken))
+            .is_none()
+        {
+            self.parse_error(
+                CssDiagnosticKind::InvalidDeclaration,
+                span,
+                format!("expected `:` after the property name `{name}`"),
+            );
+            return None;
+        }
+
+        // While the next input token is a <whitespace-token>, consume the next input token.
+        while input.next_if(ComponentValue::is_whitespace).is_some() {}
+
+        // As long as the next input token is anything other than an <EOF-token>, consume a component value and append it to the declaration’s value.
+        let mut value: Vec<ComponentValue<'a>> = input.collect();
+
+        // If decl’s name is an ASCII case-insensitive match for "unicode-range", consume the value of a unicode-range descriptor from input and set decl’s value to the returned value.
+        if name.eq_ignore_ascii_case("unicode-range") {
+            match self.tokens.input() {
+                Some(input) => value = retokenize_unicode_range(&value, input),
+                // A range like `U+1E00` was tokenized as the ident `U` followed by a number whose digits are lost,
+                // so it can not be read without the input.
+                None if value.iter().any(|v| {
+                    matches!(v.token(), Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("u"))
+                }) =>
+                {
+                    self.parse_error(
+                        CssDiagnosticKind::InvalidDeclaration,
+                        span,
+                        "`unicode-range` can only be parsed from the text of the stylesheet",
+                    );
+                    return None;
+                }
+                None => {}
+            }
+        }
+
+        // The declaration spans from its name to its last non-whitespace value, including the "!important".
+        if let Some(last) = value.iter().rev().find(|v| !v.is_whitespace()) {
+            span = span.to(last.span());
+        }
+
+        // If the last two non-<whitespace-token>s in the declaration’s value are a <delim-token> with the value "!" followed by an <ident-token> with a value that is an ASCII case-insensitive match for "important", remove them from the declaration’s value and set the declaration’s important flag to true.
+        let mut important = false;
+        let non_whitespace: Vec<usize> = value
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !v.is_whitespace())
+            .map(|(i, _)| i)
+            .collect();
+        if let [.., bang, ident] = non_whitespace[..]
+            && value[bang].token() == Some(&CSSToken::DelimToken { value: '!' })
+            && matches!(
+                value[ident].token(),
+                Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("important")
+            )
+        {
+            value.remove(ident);
+            value.remove(bang);
+            important = true;
+        }
+
+        // While the last token in the declaration’s value is a <whitespace-token>, remove that token.
+        while value.last().is_some_and(ComponentValue::is_whitespace) {
+            value.pop();
+        }
+
+        // Return the declaration.
+        Some(Declaration {
+            name,
+            value,
+            important,
+            span,
+        })
+    }
+
+    pub fn consume_component_value(&mut self) -> ComponentValue<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-component-value
+
+        // Consume the next input token.
+        let token = self.consume_next_token();
+        match token.token {
+            // If the current input token is a <{-token>, <[-token>, or <(-token>, consume a simple block and return it.
+            CSSToken::LeftCurlyBracketToken
+            | CSSToken::LeftSquareBracketToken
+            | CSSToken::LeftParenthesisToken => {
+                ComponentValue::SimpleBlock(self.consume_simple_block(token))
+            }
+            // Otherwise, if the current input token is a <function-token>, consume a function and return it.
+            CSSToken::FunctionToken { .. } => {
+                ComponentValue::Function(self.consume_function(token))
+            }
+            // Otherwise, return the current input token.
+            _ => ComponentValue::PreservedToken(token),
+        }
+    }
+
+    pub fn consume_simple_block(&mut self, current: SpannedToken<'a>) -> SimpleBlock<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-simple-block
+
+        // The ending token is the mirror variant of the current input token. (E.g. if it was called with <[-token>, the ending token is <]-token>.)
+        let (ending_token, ending_code_point) = match current.token {
+            CSSToken::LeftCurlyBracketToken => (CSSToken::RightCurlyBracketToken, '}'),
+            CSSToken::LeftSquareBracketToken => (CSSToken::RightSquareBracketToken, ']'),
+            CSSToken::LeftParenthesisToken => (CSSToken::RightParenthesisToken, ')'),
+            _ => unreachable!("consume_simple_block is only called on an opening bracket token"),
+        };
+
+        // Create a simple block with its associated token set to the current input token and with its value initially set to an empty list.
+        let opening_span = current.span;
+        let mut block = SimpleBlock {
+            associated_token: current.token,
+            value: vec![],
+            span: current.span,
+        };
+
+        // Repeatedly consume the next input token and process it as follows:
+        loop {
+            let SpannedToken { token, span } = self.consume_next_token();
+            block.span = block.span.to(span);
+            // ending token
+            // Return the block.
+            if token == ending_token {
+                return block;
+            }
+            // <EOF-token>
+            // This is a parse error. Return the block.
+            if token == CSSToken::EOFToken {
+                self.parse_error(
+                    CssDiagnosticKind::UnexpectedEOF,
+                    opening_span,
+                    format!("this block is missing its closing `{ending_code_point}`"),
+                );
+                return block;
+            }
+            // anything else
+            // Reconsume the current input token. Consume a component value and append it to the value of the block.
+            self.reconsume_current_token();
+            block.value.push(self.consume_component_value());
+        }
+    }
+
+    pub fn consume_function(&mut self, current: SpannedToken<'a>) -> Function<'a> {
+        // https://www.w3.org/TR/css-syntax-3/#consume-function
+
+        // Create a function with its name equal to the value of the current input token and with its value initially set to an empty list.
+        let name = match current.token {
+            CSSToken::FunctionToken { value } => value,
+            _ => unreachable!("consume_function is only called on a <function-token>"),
+        };
+        let opening_span = current.span;
+        let mut function = Function {
+            name,
+            value: vec![],
+            span: current.span,
+        };
+
+        // Repeatedly consume the next input token and process it as follows:
+        loop {
+            let SpannedToken { token, span } = self.consume_next_token();
+            function.span = function.span.to(span);
+            match token {
+                // <)-token>
+                // Return the function.
+                CSSToken::RightParenthesisToken => return function,
+                // <EOF-token>
+                // This is a parse error. Return the function.
+                CSSToken::EOFToken => {
+                    self.parse_error(
+                        CssDiagnosticKind::UnexpectedEOF,
+                        opening_span,
+                        format!("`{}(` is missing its closing `)`", function.name),
+                    );
+                    return function;
+                }
+                // anything else
+                // Reconsume the current input token. Consume a component value and append the returned value to the function’s value.
+                _ => {
+                    self.reconsume_current_token();
+                    function.value.push(self.consume_component_value());
+                }
+            }
+        }
+    }
+}
+
+///Tokenizes the value of a `unicode-range` declaration again from the part of `input` it was consumed from, with
+///<unicode-range-token>s allowed, see https://drafts.csswg.org/css-syntax-3/#consume-unicode-range-value
+///`U+1E00` would otherwise be an ident followed by the number `1E00`, whose digits are lost.
+fn retokenize_unicode_range<'a>(
+    value: &[ComponentValue<'a>],
+    input: &'a str,
+) -> Vec<ComponentValue<'a>> {
+    let (Some(first), Some(last)) = (value.first(), value.last()) else {
+        return vec![];
+    };
+    let span = first.span().to(last.span());
+    let mut tokenizer = Tokenizer::starting_at(&input[..span.end.offset], span.start);
+    tokenizer.allow_unicode_ranges();
+    Parser::from_token_stream(TokenStream::from_tokenizer(tokenizer))
+        .parse_list_of_component_values()
+}
+
+///Rule blocks are kept as component values and only consumed as declarations later, when `input` may be gone, so the
+///`unicode-range` descriptors of an `@font-face` block are tokenized again while it is still there, see
+///[`Parser::consume_declaration`]. The `@font-face` rules nested in group rules like `@media` are found in their block.
+fn retokenize_font_faces<'a>(name: &str, block: &mut SimpleBlock<'a>, input: &'a str) {
+    if name.eq_ignore_ascii_case("font-face") {
+        retokenize_unicode_ranges(block, input);
+        return;
+    }
+    if !GROUP_RULES.iter().any(|v| name.eq_ignore_ascii_case(v)) {
+        return;
+    }
+    // The name of the at-rule whose prelude is being walked through, if any.
+    let mut name = None;
+    for value in &mut block.value {
+        match value {
+            ComponentValue::PreservedToken(SpannedToken {
+                token: CSSToken::AtKeywordToken { value },
+                ..
+            }) => name = Some(value.clone()),
+            ComponentValue::PreservedToken(SpannedToken {
+                token: CSSToken::SemicolonToken,
+                ..
+            }) => name = None,
+            ComponentValue::SimpleBlock(nested)
+                if nested.associated_token == CSSToken::LeftCurlyBracketToken =>
+            {
+                if let Some(name) = name.take() {
+                    retokenize_font_faces(&name, nested, input);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+///Tokenizes the values of the `unicode-range` declarations in a block again, see [`retokenize_unicode_range`].
+fn retokenize_unicode_ranges<'a>(block: &mut SimpleBlock<'a>, input: &'a str) {
+    let mut i = 0;
+    while i < block.value.len() {
+        let is_descriptor = matches!(
+            block.value[i].token(),
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("unicode-range")
+        ) && declaration_name(&block.value[i..]).is_some();
+        if !is_descriptor {
+            i += 1;
+            continue;
+        }
+        let Some(colon) = block.value[i..]
+            .iter()
+            .position(|v| v.token() == Some(&CSSToken::ColonToken))
+            .map(|v| i + v + 1)
+        else {
+            return;
+        };
+        let end = block.value[colon..]
+            .iter()
+            .position(|v| v.token() == Some(&CSSToken::SemicolonToken))
+            .map_or(block.value.len(), |v| colon + v);
+        let values = retokenize_unicode_range(&block.value[colon..end], input);
+        let count = values.len();
+        block.value.splice(colon..end, values);
+        i = colon + count;
+    }
+}
+
+///Returns the name of a declaration if the values start like one, with an <ident-token> followed by a <colon-token>.
+fn declaration_name<'v>(values: &'v [ComponentValue]) -> Option<&'v str> {
+    let mut values = values.iter().filter(|v| !v.is_whitespace());
+    let Some(CSSToken::IdentToken { value }) = values.next()?.token() else {
+        return None;
+    };
+    (values.next()?.token() == Some(&CSSToken::ColonToken)).then_some(value.as_ref())
+}
+
+///A parsed stylesheet, the result of [`Parser::parse_stylesheet`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stylesheet<'a> {
+    pub rules: Vec<Rule<'a>>,
+}
+
+impl Stylesheet<'_> {
+    ///Detaches the stylesheet from the input it was parsed from, like for a stylesheet read from a file.
+    pub fn into_owned(self) -> Stylesheet<'static> {
+        Stylesheet {
+            rules: self.rules.into_iter().map(Rule::into_owned).collect(),
+        }
+    }
+}
+
+///A top-level or nested rule in a stylesheet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule<'a> {
+    QualifiedRule(QualifiedRule<'a>),
+    AtRule(AtRule<'a>),
+}
+
+impl<'a> Rule<'a> {
+    pub fn span(&self) -> Span {
+        match self {
+            Rule::QualifiedRule(rule) => rule.span,
+            Rule::AtRule(rule) => rule.span,
+        }
+    }
+
+    ///Detaches the rule from the input it was parsed from.
+    pub fn into_owned(self) -> Rule<'static> {
+        match self {
+            Rule::QualifiedRule(rule) => Rule::QualifiedRule(QualifiedRule {
+                prelude: owned_values(rule.prelude),
+                block: rule.block.into_owned(),
+                span: rule.span,
+            }),
+            Rule::AtRule(rule) => Rule::AtRule(AtRule {
+                name: Cow::Owned(rule.name.into_owned()),
+                prelude: owned_values(rule.prelude),
+                block: rule.block.map(SimpleBlock::into_owned),
+                span: rule.span,
+            }),
+        }
+    }
+}
+
+///A rule that starts with a prelude (usually a selector) and has a `{}` block, like `a { color: red }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualifiedRule<'a> {
+    pub prelude: Vec<ComponentValue<'a>>,
+    pub block: SimpleBlock<'a>,
+    pub span: Span,
+}
+
+///A rule that starts with an at-keyword, like `@media screen { ... }` or `@import "a.css";`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtRule<'a> {
+    pub name: Cow<'a, str>,
+    pub prelude: Vec<ComponentValue<'a>>,
+    pub block: Option<SimpleBlock<'a>>,
+    pub span: Span,
+}
+
+///A `name: value` pair, optionally marked with `!important`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration<'a> {
+    pub name: Cow<'a, str>,
+    pub value: Vec<ComponentValue<'a>>,
+    pub important: bool,
+    pub span: Span,
+}
+
+///An item of a declaration list, which may contain at-rules alongside declarations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclarationOrAtRule<'a> {
+    Declaration(Declaration<'a>),
+    AtRule(AtRule<'a>),
+}
+
+///An item of the contents of a block, see [`Parser::parse_block_contents`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclarationOrRule<'a> {
+    Declaration(Declaration<'a>),
+    Rule(Rule<'a>),
+}
+
+///https://www.w3.org/TR/css-syntax-3/#component-value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentValue<'a> {
+    PreservedToken(SpannedToken<'a>),
+    Function(Function<'a>),
+    SimpleBlock(SimpleBlock<'a>),
+}
+
+impl<'a> ComponentValue<'a> {
+    ///Returns the token of a preserved token, or `None` for functions and blocks.
+    pub fn token(&self) -> Option<&CSSToken<'a>> {
+        match self {
+            ComponentValue::PreservedToken(token) => Some(&token.token),
+            _ => None,
+        }
+    }
+
+    pub fn is_whitespace(&self) -> bool {
+        self.token() == Some(&CSSToken::WhitespaceToken)
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ComponentValue::PreservedToken(token) => token.span,
+            ComponentValue::Function(function) => function.span,
+            ComponentValue::SimpleBlock(block) => block.span,
+        }
+    }
+
+    ///Detaches the value from the input it was parsed from, see [`CSSToken::into_owned`].
+    pub fn into_owned(self) -> ComponentValue<'static> {
+        match self {
+            ComponentValue::PreservedToken(token) => ComponentValue::PreservedToken(SpannedToken {
+                token: token.token.into_owned(),
+                span: token.span,
+            }),
+            ComponentValue::Function(function) => ComponentValue::Function(Function {
+                name: Cow::Owned(function.name.into_owned()),
+                value: owned_values(function.value),
+                span: function.span,
+            }),
+            ComponentValue::SimpleBlock(block) => ComponentValue::SimpleBlock(block.into_owned()),
+        }
+    }
+}
+
+fn owned_values(values: Vec<ComponentValue>) -> Vec<ComponentValue<'static>> {
+    values.into_iter().map(ComponentValue::into_owned).collect()
+}
+
+///A function with its arguments, like `rgb(0, 0, 0)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function<'a> {
+    pub name: Cow<'a, str>,
+    pub value: Vec<ComponentValue<'a>>,
+    pub span: Span,
+}
+
+///A `{}`, `[]` or `()` block. The associated token is the opening bracket token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimpleBlock<'a> {
+    pub associated_token: CSSToken<'a>,
+    pub value: Vec<ComponentValue<'a>>,
+    pub span: Span,
+}
+
+impl SimpleBlock<'_> {
+    ///Detaches the block from the input it was parsed from.
+    pub fn into_owned(self) -> SimpleBlock<'static> {
+        SimpleBlock {
+            associated_token: self.associated_token.into_owned(),
+            value: owned_values(self.value),
+            span: self.span,
+        }
+    }
+}
+
+///The syntax errors returned by the "parse a ..." entry points of the [`Parser`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxError {
+    ///The input ended before anything could be parsed.
+    UnexpectedEOF,
+    ///Something was parsed, but the input has leftover tokens.
+    ExpectedEOF,
+    ///The input is not a valid rule.
+    InvalidRule,
+    ///The input is not a valid declaration.
+    InvalidDeclaration,
+}
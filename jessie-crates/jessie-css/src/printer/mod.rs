@@ -0,0 +1,511 @@
+//! Writes stylesheets back out as text, either [`minify`]d to ship them or [`pretty_print`]ed to read them.
+//! Both work on the parsed stylesheet, so comments are gone and the blocks of rules are parsed into declarations and rules.
+use crate::parser::{
+    ComponentValue, Declaration, DeclarationOrRule, Parser, Rule, SimpleBlock, Stylesheet,
+};
+use crate::properties::{self, is_custom_property, shorthands};
+use crate::serializer::{Serializer, serialize_component_values};
+use crate::values::color::spaces::ColorSpace;
+use crate::values::color::{Color, color_name};
+use crate::values::parse_value;
+use crate::{CSSToken, HashTokenFlag, SpannedToken};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+///The at-rules whose blocks are merged when they follow each other with the same prelude.
+const MERGED_AT_RULES: [&str; 4] = ["media", "supports", "container", "layer"];
+
+///The color functions that are replaced by a hex color or a named color when that is shorter.
+const LEGACY_COLOR_FUNCTIONS: [&str; 5] = ["rgb", "rgba", "hsl", "hsla", "hwb"];
+
+///Writes a stylesheet as small as it can be without changing what it does:
+///whitespace is only kept where it is needed, numbers lose the `0` before their `.` and colors are written in their shortest form.
+///Adjacent style rules with the same selectors and adjacent `@media`, `@supports`, `@container` and named `@layer` rules
+///with the same prelude are merged, declarations that a later valid declaration of the block overrides are dropped,
+///and so are the style rules that end up empty.
+pub fn minify(stylesheet: &Stylesheet) -> String {
+    let mut printer = Printer {
+        serializer: Serializer::minified(),
+        indent: None,
+        depth: 0,
+    };
+    printer.print_items(&minify_items(items(&stylesheet.rules)));
+    printer.serializer.finish()
+}
+
+///Writes a stylesheet with one declaration or rule per line, the contents of blocks indented by `indent`,
+///like `"  "` or `"\t"`, and an empty line between the rules of the stylesheet.
+pub fn pretty_print(stylesheet: &Stylesheet, indent: &str) -> String {
+    let mut printer = Printer {
+        serializer: Serializer::new(),
+        indent: Some(indent),
+        depth: 0,
+    };
+    printer.print_items(&items(&stylesheet.rules));
+    let mut out = printer.serializer.finish();
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+///A declaration or a rule, with the contents of the block of the rule parsed.
+#[derive(Debug, Clone)]
+enum Item<'a> {
+    Declaration(Declaration<'a>),
+    Rule {
+        ///The name of an at-rule, `None` for a style rule.
+        at_rule: Option<Cow<'a, str>>,
+        prelude: Vec<ComponentValue<'a>>,
+        ///`None` for an at-rule without a block, like `@import`.
+        contents: Option<Vec<Item<'a>>>,
+    },
+}
+
+fn items<'a>(rules: &[Rule<'a>]) -> Vec<Item<'a>> {
+    rules.iter().map(rule_item).collect()
+}
+
+fn rule_item<'a>(rule: &Rule<'a>) -> Item<'a> {
+    match rule {
+        Rule::QualifiedRule(rule) => Item::Rule {
+            at_rule: None,
+            prelude: rule.prelude.clone(),
+            contents: Some(block_items(&rule.block)),
+        },
+        Rule::AtRule(rule) => Item::Rule {
+            at_rule: Some(rule.name.clone()),
+            prelude: rule.prelude.clone(),
+            contents: rule.block.as_ref().map(block_items),
+        },
+    }
+}
+
+fn block_items<'a>(block: &SimpleBlock<'a>) -> Vec<Item<'a>> {
+    Parser::from_component_values(&block.value)
+        .parse_block_contents()
+        .into_iter()
+        .map(|v| match v {
+            DeclarationOrRule::Declaration(declaration) => Item::Declaration(declaration),
+            DeclarationOrRule::Rule(rule) => rule_item(&rule),
+        })
+        .collect()
+}
+
+fn minify_items(items: Vec<Item>) -> Vec<Item> {
+    let mut out: Vec<Item> = vec![];
+    for item in items {
+        if let Some(Item::Rule {
+            at_rule: last_name,
+            prelude: last_prelude,
+            contents: Some(last),
+        }) = out.last_mut()
+            && let Item::Rule {
+                at_rule,
+                prelude,
+                contents: Some(contents),
+            } = &item
+            && can_merge(
+                last_name.as_deref(),
+                last_prelude,
+                at_rule.as_deref(),
+                prelude,
+            )
+        {
+            last.extend(contents.iter().cloned());
+            continue;
+        }
+        out.push(item);
+    }
+    for item in &mut out {
+        if let Item::Rule {
+            contents: Some(contents),
+            ..
+        } = item
+        {
+            *contents = minify_items(std::mem::take(contents));
+        }
+    }
+    drop_overridden(&mut out);
+    out.retain(|item| {
+        !matches!(item, Item::Rule { at_rule: None, contents: Some(contents), .. } if contents.is_empty())
+    });
+    out
+}
+
+///Whether the blocks of two adjacent rules can be merged into the first one.
+///Two anonymous `@layer` blocks are two different layers, so they are not merged.
+fn can_merge(
+    first: Option<&str>,
+    first_prelude: &[ComponentValue],
+    second: Option<&str>,
+    second_prelude: &[ComponentValue],
+) -> bool {
+    let context = match (first, second) {
+        (None, None) => Context::Selector,
+        (Some(first), Some(second))
+            if first.eq_ignore_ascii_case(second)
+                && MERGED_AT_RULES
+                    .iter()
+                    .any(|v| first.eq_ignore_ascii_case(v))
+                && !(first.eq_ignore_ascii_case("layer")
+                    && first_prelude.iter().all(ComponentValue::is_whitespace)) =>
+        {
+            Context::Prelude
+        }
+        _ => return false,
+    };
+    let text = |prelude: &[ComponentValue]| {
+        serialize_component_values(&format_values(prelude, context, true))
+    };
+    text(first_prelude) == text(second_prelude)
+}
+
+///Drops the declarations that another declaration of the same block overrides: a later one of the same property,
+///or of a shorthand of it, that is `!important` if the earlier one is, or an earlier `!important` one if it is not.
+///Only the declarations the cascade keeps override others.
+fn drop_overridden(items: &mut Vec<Item>) {
+    let mut keep = vec![true; items.len()];
+    // Whether one of the later declarations that set a property is `!important`.
+    let mut later: HashMap<String, bool> = HashMap::new();
+    for (i, item) in items.iter().enumerate().rev() {
+        let Item::Declaration(declaration) = item else {
+            continue;
+        };
+        if later
+            .get(&property_name(&declaration.name))
+            .is_some_and(|important| *important || !declaration.important)
+        {
+            keep[i] = false;
+            continue;
+        }
+        for name in overridden_properties(declaration) {
+            *later.entry(name).or_default() |= declaration.important;
+        }
+    }
+    let mut important = vec![];
+    for (i, item) in items.iter().enumerate() {
+        let Item::Declaration(declaration) = item else {
+            continue;
+        };
+        if declaration.important {
+            important.extend(overridden_properties(declaration));
+        } else if important.contains(&property_name(&declaration.name)) {
+            keep[i] = false;
+        }
+    }
+    let mut keep = keep.into_iter();
+    items.retain(|_| keep.next().unwrap_or(true));
+}
+
+///The properties a declaration overrides: its own and the longhands of a shorthand, or none if the cascade drops it.
+fn overridden_properties(declaration: &Declaration) -> Vec<String> {
+    let Ok(longhands) = properties::validate_declaration(declaration.clone()) else {
+        return vec![];
+    };
+    let name = property_name(&declaration.name);
+    let shorthand = shorthands::lookup(&name).map_or(&[][..], |v| v.longhands);
+    longhands
+        .into_iter()
+        .map(|v| v.name.into_owned())
+        .chain(shorthand.iter().map(|v| v.to_string()))
+        .chain([name])
+        .collect()
+}
+
+///Custom property names are case-sensitive, the others are not.
+fn property_name(name: &str) -> String {
+    match is_custom_property(name) {
+        true => name.to_string(),
+        false => name.to_ascii_lowercase(),
+    }
+}
+
+///What component values are part of, which decides the whitespace they need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    ///The prelude of a style rule, or the argument of `selector()` or of a pseudo-class.
+    Selector,
+    ///The prelude of an at-rule.
+    Prelude,
+    ///The value of a declaration.
+    Value,
+    ///The value of a custom property, which is kept as it is.
+    CustomValue,
+}
+
+///Tidies the whitespace of component values, in the functions and blocks they contain too.
+///Whitespace is trimmed at the start and the end and before commas. When minifying, it is also removed after commas,
+///around `>`, `~` and `+` in selectors and around `:` and `/` elsewhere, and colors are shortened in values.
+///Otherwise, commas are followed by a space.
+fn format_values<'a>(
+    values: &[ComponentValue<'a>],
+    context: Context,
+    minify: bool,
+) -> Vec<ComponentValue<'a>> {
+    if context == Context::CustomValue {
+        return values.to_vec();
+    }
+    let values: Vec<ComponentValue<'a>> = values
+        .iter()
+        .map(|value| format_value(value, context, minify))
+        .collect();
+    // Whether the whitespace around `value` can go, `next` being the value after it.
+    let tight = |value: &ComponentValue, next: Option<&ComponentValue>| match value.token() {
+        Some(CSSToken::CommaToken) => true,
+        Some(CSSToken::DelimToken { value: '>' | '~' }) => minify && context == Context::Selector,
+        // `2n + 1` would need a comment between the `+` and the `1`.
+        Some(CSSToken::DelimToken { value: '+' }) => {
+            minify && context == Context::Selector && !next.is_some_and(is_numeric)
+        }
+        Some(CSSToken::ColonToken | CSSToken::DelimToken { value: '/' }) => {
+            minify && context != Context::Selector
+        }
+        _ => false,
+    };
+    let mut out = vec![];
+    for (i, value) in values.iter().enumerate() {
+        if !value.is_whitespace() {
+            out.push(value.clone());
+            if !minify && value.token() == Some(&CSSToken::CommaToken) && i + 1 < values.len() {
+                out.push(whitespace(value));
+            }
+            continue;
+        }
+        let previous = values[..i].iter().rev().find(|v| !v.is_whitespace());
+        let next = values[i + 1..].iter().find(|v| !v.is_whitespace());
+        let (Some(previous), Some(next)) = (previous, next) else {
+            continue;
+        };
+        let after_next = values[i + 1..].iter().filter(|v| !v.is_whitespace()).nth(1);
+        let tight_after = match previous.token() {
+            Some(CSSToken::CommaToken) => minify,
+            _ => tight(previous, Some(next)),
+        };
+        if tight(next, after_next)
+            || tight_after
+            || out.last().is_some_and(ComponentValue::is_whitespace)
+        {
+            continue;
+        }
+        out.push(value.clone());
+    }
+    out
+}
+
+fn format_value<'a>(
+    value: &ComponentValue<'a>,
+    context: Context,
+    minify: bool,
+) -> ComponentValue<'a> {
+    if minify
+        && context == Context::Value
+        && let Some(color) = shortest_color(value)
+    {
+        return color;
+    }
+    match value {
+        ComponentValue::Function(function) => {
+            let context = match context {
+                Context::Prelude if function.name.eq_ignore_ascii_case("selector") => {
+                    Context::Selector
+                }
+                context => context,
+            };
+            let mut function = function.clone();
+            function.value = format_values(&function.value, context, minify);
+            ComponentValue::Function(function)
+        }
+        ComponentValue::SimpleBlock(block) => {
+            let mut block = block.clone();
+            block.value = format_values(&block.value, context, minify);
+            ComponentValue::SimpleBlock(block)
+        }
+        value => value.clone(),
+    }
+}
+
+fn is_numeric(value: &ComponentValue) -> bool {
+    matches!(
+        value.token(),
+        Some(
+            CSSToken::NumberToken { .. }
+                | CSSToken::PercentageToken { .. }
+                | CSSToken::DimensionToken { .. }
+        )
+    )
+}
+
+fn whitespace<'a>(value: &ComponentValue) -> ComponentValue<'a> {
+    ComponentValue::PreservedToken(SpannedToken {
+        token: CSSToken::WhitespaceToken,
+        span: value.span(),
+    })
+}
+
+///Returns the shortest way to write a hex color or an sRGB color function, if it is shorter than the color as written.
+///Colors whose channels are not whole numbers out of 255 are kept as they are.
+fn shortest_color<'a>(value: &ComponentValue<'a>) -> Option<ComponentValue<'a>> {
+    let is_color = match value {
+        ComponentValue::PreservedToken(token) => matches!(token.token, CSSToken::HashToken { .. }),
+        ComponentValue::Function(function) => LEGACY_COLOR_FUNCTIONS
+            .iter()
+            .any(|v| function.name.eq_ignore_ascii_case(v)),
+        ComponentValue::SimpleBlock(_) => false,
+    };
+    if !is_color {
+        return None;
+    }
+    let Ok(Color::Absolute(color)) = parse_value::<Color>(std::slice::from_ref(value)) else {
+        return None;
+    };
+    let color = color.to_space(ColorSpace::Srgb);
+    let mut rgba = [0; 4];
+    for (byte, channel) in rgba
+        .iter_mut()
+        .zip(color.components.into_iter().chain([color.alpha]))
+    {
+        let channel = channel * 255.0;
+        if !(0.0..=255.0).contains(&channel.round()) || (channel - channel.round()).abs() > 1e-3 {
+            return None;
+        }
+        *byte = channel.round() as u8;
+    }
+    let opaque = rgba[3] == 255;
+    let mut hex: String = rgba[..if opaque { 3 } else { 4 }]
+        .iter()
+        .map(|v| format!("{v:02x}"))
+        .collect();
+    if hex.as_bytes().chunks(2).all(|v| v[0] == v[1]) {
+        hex = hex.chars().step_by(2).collect();
+    }
+    let token = match color_name([rgba[0], rgba[1], rgba[2]])
+        .filter(|name| opaque && name.len() <= hex.len())
+    {
+        Some(name) => CSSToken::IdentToken { value: name.into() },
+        None => CSSToken::HashToken {
+            flag: HashTokenFlag::Unrestricted,
+            value: hex.into(),
+        },
+    };
+    let written = serialize_component_values(std::slice::from_ref(value));
+    let shortest = ComponentValue::PreservedToken(SpannedToken {
+        token,
+        span: value.span(),
+    });
+    (serialize_component_values(std::slice::from_ref(&shortest)).len() < written.len())
+        .then_some(shortest)
+}
+
+struct Printer<'i> {
+    serializer: Serializer,
+    ///The indentation of pretty-printing, `None` when minifying.
+    indent: Option<&'i str>,
+    depth: usize,
+}
+
+impl Printer<'_> {
+    fn print_items(&mut self, items: &[Item]) {
+        for (i, item) in items.iter().enumerate() {
+            if let Some(indent) = self.indent {
+                if i > 0 {
+                    let empty_line = self.depth == 0;
+                    self.serializer
+                        .push_whitespace(if empty_line { "\n\n" } else { "\n" });
+                }
+                self.serializer.push_whitespace(&indent.repeat(self.depth));
+            }
+            match item {
+                Item::Declaration(declaration) => {
+                    self.print_declaration(declaration);
+                    // The `;` after the last declaration of a block is not needed.
+                    if self.indent.is_some() || i + 1 < items.len() {
+                        self.serializer.push_token(&CSSToken::SemicolonToken);
+                    }
+                }
+                Item::Rule {
+                    at_rule,
+                    prelude,
+                    contents,
+                } => self.print_rule(at_rule.as_deref(), prelude, contents.as_deref()),
+            }
+        }
+    }
+
+    fn print_rule(
+        &mut self,
+        at_rule: Option<&str>,
+        prelude: &[ComponentValue],
+        contents: Option<&[Item]>,
+    ) {
+        let minify = self.indent.is_none();
+        let context = match at_rule {
+            Some(name) => {
+                self.serializer
+                    .push_token(&CSSToken::AtKeywordToken { value: name.into() });
+                Context::Prelude
+            }
+            None => Context::Selector,
+        };
+        let prelude = format_values(prelude, context, minify);
+        // `@media(width > 1px)` and `@import"a.css"` are fine without a space.
+        let separated = match prelude.first() {
+            None => false,
+            Some(ComponentValue::SimpleBlock(_)) => !minify,
+            Some(value) => !minify || !matches!(value.token(), Some(CSSToken::StringToken { .. })),
+        };
+        if at_rule.is_some() && separated {
+            self.serializer.push_token(&CSSToken::WhitespaceToken);
+        }
+        self.serializer.push_component_values(&prelude);
+        let Some(contents) = contents else {
+            self.serializer.push_token(&CSSToken::SemicolonToken);
+            return;
+        };
+        if !minify && (at_rule.is_some() || !prelude.is_empty()) {
+            self.serializer.push_token(&CSSToken::WhitespaceToken);
+        }
+        self.serializer.push_token(&CSSToken::LeftCurlyBracketToken);
+        if let Some(indent) = self.indent
+            && !contents.is_empty()
+        {
+            self.depth += 1;
+            self.serializer.push_whitespace("\n");
+            self.print_items(contents);
+            self.depth -= 1;
+            self.serializer.push_whitespace("\n");
+            self.serializer.push_whitespace(&indent.repeat(self.depth));
+        } else {
+            self.print_items(contents);
+        }
+        self.serializer
+            .push_token(&CSSToken::RightCurlyBracketToken);
+    }
+
+    fn print_declaration(&mut self, declaration: &Declaration) {
+        let minify = self.indent.is_none();
+        let context = match is_custom_property(&declaration.name) {
+            true => Context::CustomValue,
+            false => Context::Value,
+        };
+        self.serializer.push_token(&CSSToken::IdentToken {
+            value: declaration.name.as_ref().into(),
+        });
+        self.serializer.push_token(&CSSToken::ColonToken);
+        let value = format_values(&declaration.value, context, minify);
+        if !minify && !value.is_empty() {
+            self.serializer.push_token(&CSSToken::WhitespaceToken);
+        }
+        self.serializer.push_component_values(&value);
+        if declaration.important {
+            if !minify {
+                self.serializer.push_token(&CSSToken::WhitespaceToken);
+            }
+            self.serializer
+                .push_token(&CSSToken::DelimToken { value: '!' });
+            self.serializer.push_token(&CSSToken::IdentToken {
+                value: "important".into(),
+            });
+        }
+    }
+}
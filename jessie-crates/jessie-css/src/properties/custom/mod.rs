@@ -0,0 +1,395 @@
+//! Custom properties like `--accent: #f80`, the `var()` function of https://www.w3.org/TR/css-variables-1/
+//! and the `@property` rule of https://www.w3.org/TR/css-properties-values-api-1/
+use crate::CSSToken;
+use crate::cascade::CssWideKeyword;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{AtRule, ComponentValue, DeclarationOrAtRule, Function, Parser, SimpleBlock};
+use crate::values::calc::{Calc, CalcCategory};
+use crate::values::color::Color;
+use crate::values::length::{Length, LengthPercentage, LengthUnit};
+use crate::values::{Parse, ValueParser, parse_value_with};
+use std::borrow::Cow;
+
+///Whether `name` is a custom property like `--accent`.
+pub fn is_custom_property(name: &str) -> bool {
+    name.starts_with("--")
+}
+
+///Whether a value contains a `var()`, including in nested functions and blocks.
+pub fn contains_var(value: &[ComponentValue]) -> bool {
+    value.iter().any(|v| match v {
+        ComponentValue::Function(function) => {
+            function.name.eq_ignore_ascii_case("var") || contains_var(&function.value)
+        }
+        ComponentValue::SimpleBlock(block) => contains_var(&block.value),
+        ComponentValue::PreservedToken(_) => false,
+    })
+}
+
+///Replaces the `var()` functions of a value with what `lookup` returns for their custom properties, or with their fallbacks.
+///Returns `None` if a custom property has no value and its `var()` has no fallback,
+///which makes the value invalid at computed-value time.
+pub fn substitute_var<'a>(
+    value: &[ComponentValue<'a>],
+    lookup: &mut dyn FnMut(&str) -> Option<Vec<ComponentValue<'a>>>,
+) -> Option<Vec<ComponentValue<'a>>> {
+    let mut out = Vec::with_capacity(value.len());
+    for v in value {
+        match v {
+            ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("var") => {
+                let (name, fallback) = parse_var(function)?;
+                match (lookup(name), fallback) {
+                    (Some(value), _) => out.extend_from_slice(trim_whitespace(&value)),
+                    (None, Some(fallback)) => {
+                        out.extend(substitute_var(trim_whitespace(fallback), lookup)?)
+                    }
+                    (None, None) => return None,
+                }
+            }
+            ComponentValue::Function(function) => {
+                out.push(ComponentValue::Function(Function {
+                    value: substitute_var(&function.value, lookup)?,
+                    ..function.clone()
+                }));
+            }
+            ComponentValue::SimpleBlock(block) => {
+                out.push(ComponentValue::SimpleBlock(SimpleBlock {
+                    value: substitute_var(&block.value, lookup)?,
+                    ..block.clone()
+                }));
+            }
+            ComponentValue::PreservedToken(_) => out.push(v.clone()),
+        }
+    }
+    Some(out)
+}
+
+///Removes the whitespace around a value, which does not matter where it is substituted.
+fn trim_whitespace<'v, 'a>(mut value: &'v [ComponentValue<'a>]) -> &'v [ComponentValue<'a>] {
+    while let [first, rest @ ..] = value
+        && first.is_whitespace()
+    {
+        value = rest;
+    }
+    while let [rest @ .., last] = value
+        && last.is_whitespace()
+    {
+        value = rest;
+    }
+    value
+}
+
+///Splits the arguments of `var(<custom-property-name>, <declaration-value>?)` into the name and the fallback, if any.
+fn parse_var<'v, 'a>(
+    function: &'v Function<'a>,
+) -> Option<(&'v str, Option<&'v [ComponentValue<'a>]>)> {
+    let comma = function
+        .value
+        .iter()
+        .position(|v| v.token() == Some(&CSSToken::CommaToken));
+    let (name, fallback) = match comma {
+        Some(i) => (&function.value[..i], Some(&function.value[i + 1..])),
+        None => (function.value.as_slice(), None),
+    };
+    let mut name = name.iter().filter(|v| !v.is_whitespace());
+    match (name.next()?.token()?, name.next()) {
+        (CSSToken::IdentToken { value }, None) if is_custom_property(value) => {
+            Some((value, fallback))
+        }
+        _ => None,
+    }
+}
+
+///The `syntax` descriptor of `@property`, see https://www.w3.org/TR/css-properties-values-api-1/#syntax-strings
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertySyntax {
+    ///`*`, which accepts any value like an unregistered custom property.
+    Universal,
+    ///Components separated by `|`, the first one that matches wins.
+    Components(Vec<SyntaxComponent>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxComponent {
+    pub name: SyntaxComponentName,
+    pub multiplier: Option<SyntaxMultiplier>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxComponentName {
+    DataType(SyntaxDataType),
+    ///A keyword that has to appear as is.
+    Ident(String),
+}
+
+///The data types a syntax can name, like `<length>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxDataType {
+    Length,
+    Number,
+    Percentage,
+    LengthPercentage,
+    Color,
+    Integer,
+    Angle,
+    CustomIdent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxMultiplier {
+    ///`+`, a space separated list of one or more values.
+    SpaceSeparated,
+    ///`#`, a comma separated list of one or more values.
+    CommaSeparated,
+}
+
+impl SyntaxDataType {
+    const NAMES: &[(&str, SyntaxDataType)] = &[
+        ("length", SyntaxDataType::Length),
+        ("number", SyntaxDataType::Number),
+        ("percentage", SyntaxDataType::Percentage),
+        ("length-percentage", SyntaxDataType::LengthPercentage),
+        ("color", SyntaxDataType::Color),
+        ("integer", SyntaxDataType::Integer),
+        ("angle", SyntaxDataType::Angle),
+        ("custom-ident", SyntaxDataType::CustomIdent),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(v, _)| *v == name)
+            .map(|(_, data_type)| *data_type)
+    }
+}
+
+impl PropertySyntax {
+    ///Parses a syntax string like `<length> | auto` or `<color>#`, returning `None` for syntaxes that are invalid or not supported.
+    pub fn parse(syntax: &str) -> Option<Self> {
+        let syntax = syntax.trim();
+        if syntax == "*" {
+            return Some(PropertySyntax::Universal);
+        }
+        let components = syntax
+            .split('|')
+            .map(|component| {
+                let component = component.trim();
+                let (component, multiplier) = match component.as_bytes().last()? {
+                    b'+' => (
+                        &component[..component.len() - 1],
+                        Some(SyntaxMultiplier::SpaceSeparated),
+                    ),
+                    b'#' => (
+                        &component[..component.len() - 1],
+                        Some(SyntaxMultiplier::CommaSeparated),
+                    ),
+                    _ => (component, None),
+                };
+                let name = match component.strip_prefix('<') {
+                    Some(data_type) => SyntaxComponentName::DataType(SyntaxDataType::from_name(
+                        data_type.strip_suffix('>')?,
+                    )?),
+                    None if is_custom_ident(component) => {
+                        SyntaxComponentName::Ident(component.to_string())
+                    }
+                    None => return None,
+                };
+                Some(SyntaxComponent { name, multiplier })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(PropertySyntax::Components(components))
+    }
+
+    ///Whether a value, without any `var()` left in it, matches the syntax.
+    pub fn matches(&self, value: &[ComponentValue]) -> bool {
+        let components = match self {
+            PropertySyntax::Universal => return true,
+            PropertySyntax::Components(components) => components,
+        };
+        components.iter().any(|component| {
+            parse_value_with(value, |input| {
+                component.parse_one(input)?;
+                while !input.is_exhausted() {
+                    match component.multiplier {
+                        Some(SyntaxMultiplier::SpaceSeparated) => {}
+                        Some(SyntaxMultiplier::CommaSeparated) => input.expect_comma()?,
+                        None => break,
+                    }
+                    component.parse_one(input)?;
+                }
+                Ok(())
+            })
+            .is_ok()
+        })
+    }
+}
+
+impl SyntaxComponent {
+    ///Parses one value of the component, ignoring its multiplier.
+    fn parse_one(&self, input: &mut ValueParser) -> Result<(), CssDiagnostic> {
+        let data_type = match &self.name {
+            SyntaxComponentName::Ident(ident) => return input.expect_keyword(ident),
+            SyntaxComponentName::DataType(data_type) => data_type,
+        };
+        match data_type {
+            SyntaxDataType::Length if input.at_math_function() => {
+                Calc::parse(input, CalcCategory::Length).map(drop)
+            }
+            SyntaxDataType::Length => Length::parse(input).map(drop),
+            SyntaxDataType::Number => input.expect_number().map(drop),
+            SyntaxDataType::Percentage => input.expect_percentage().map(drop),
+            SyntaxDataType::LengthPercentage => LengthPercentage::parse(input).map(drop),
+            SyntaxDataType::Color => Color::parse(input).map(drop),
+            SyntaxDataType::Integer => input.expect_integer().map(drop),
+            SyntaxDataType::Angle => input.expect_angle().map(drop),
+            SyntaxDataType::CustomIdent => {
+                let (ident, span) = input.expect_ident()?;
+                match is_custom_ident(ident) {
+                    true => Ok(()),
+                    false => Err(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        span,
+                        format!("`{ident}` is reserved and can not be used as a name"),
+                    )),
+                }
+            }
+        }
+    }
+}
+
+///Whether an identifier can be a <custom-ident>, which excludes the CSS-wide keywords and `default`.
+pub(crate) fn is_custom_ident(ident: &str) -> bool {
+    let value = [ComponentValue::PreservedToken(crate::SpannedToken {
+        token: CSSToken::IdentToken {
+            value: Cow::Borrowed(ident),
+        },
+        span: Default::default(),
+    })];
+    !ident.is_empty()
+        && !ident.starts_with(|v: char| v.is_ascii_digit())
+        && ident
+            .chars()
+            .all(|v| v.is_alphanumeric() || v == '-' || v == '_')
+        && !ident.eq_ignore_ascii_case("default")
+        && CssWideKeyword::parse(&value).is_none()
+}
+
+///A custom property registered with `@property`, which gives it a syntax, an initial value and whether it inherits.
+///Registered properties are validated against their syntax once `var()` is substituted, and can be animated as their type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyRegistration<'a> {
+    pub name: Cow<'a, str>,
+    pub syntax: PropertySyntax,
+    pub inherits: bool,
+    ///`None` for the guaranteed-invalid value, which only the universal syntax allows.
+    pub initial_value: Option<Vec<ComponentValue<'a>>>,
+}
+
+impl<'a> PropertyRegistration<'a> {
+    ///Parses an `@property --name { syntax: "..."; inherits: ...; initial-value: ... }` rule.
+    pub fn parse(rule: &AtRule<'a>) -> Result<Self, CssDiagnostic> {
+        let error = |message: &str| {
+            CssDiagnostic::new(CssDiagnosticKind::InvalidAtRule, rule.span, message)
+        };
+        let mut prelude = rule.prelude.iter().filter(|v| !v.is_whitespace());
+        let name = match (prelude.next().and_then(|v| v.token()), prelude.next()) {
+            (Some(CSSToken::IdentToken { value }), None) if is_custom_property(value) => {
+                value.clone()
+            }
+            _ => {
+                return Err(error(
+                    "`@property` needs a custom property name like `--accent`",
+                ));
+            }
+        };
+        let Some(block) = &rule.block else {
+            return Err(error("`@property` needs a block of descriptors"));
+        };
+
+        let mut syntax = None;
+        let mut inherits = None;
+        let mut initial_value = None;
+        for item in Parser::from_component_values(&block.value).parse_list_of_declarations() {
+            let DeclarationOrAtRule::Declaration(declaration) = item else {
+                continue;
+            };
+            let mut values = declaration.value.iter().filter(|v| !v.is_whitespace());
+            let single = (values.next().and_then(|v| v.token()), values.next());
+            match declaration.name.to_ascii_lowercase().as_str() {
+                "syntax" => {
+                    let Some(CSSToken::StringToken { string }) =
+                        single.0.filter(|_| single.1.is_none())
+                    else {
+                        return Err(error(
+                            "the `syntax` of `@property` is a string like \"<length>\"",
+                        ));
+                    };
+                    syntax = Some(PropertySyntax::parse(string).ok_or_else(|| {
+                        error(&format!("`{string}` is not a syntax `@property` supports"))
+                    })?);
+                }
+                "inherits" => {
+                    inherits = match single {
+                        (Some(CSSToken::IdentToken { value }), None)
+                            if value.eq_ignore_ascii_case("true") =>
+                        {
+                            Some(true)
+                        }
+                        (Some(CSSToken::IdentToken { value }), None)
+                            if value.eq_ignore_ascii_case("false") =>
+                        {
+                            Some(false)
+                        }
+                        _ => None,
+                    };
+                }
+                "initial-value" => initial_value = Some(declaration.value),
+                _ => {}
+            }
+        }
+
+        let Some(syntax) = syntax else {
+            return Err(error("`@property` needs a `syntax` descriptor"));
+        };
+        let Some(inherits) = inherits else {
+            return Err(error(
+                "`@property` needs an `inherits` descriptor of `true` or `false`",
+            ));
+        };
+        if syntax != PropertySyntax::Universal {
+            let Some(value) = initial_value.as_deref().filter(|v| syntax.matches(v)) else {
+                return Err(error(
+                    "`@property` needs an `initial-value` that matches its syntax unless the syntax is `*`",
+                ));
+            };
+            if !is_computationally_independent(value) {
+                return Err(error(
+                    "the `initial-value` of `@property` can not depend on other values, like `em` or `var()` do",
+                ));
+            }
+        }
+        Ok(PropertyRegistration {
+            name,
+            syntax,
+            inherits,
+            initial_value,
+        })
+    }
+}
+
+///Whether a value computes to the same thing on every element, which rules out `var()` and relative lengths.
+fn is_computationally_independent(value: &[ComponentValue]) -> bool {
+    value.iter().all(|v| match v {
+        ComponentValue::Function(function) => {
+            !function.name.eq_ignore_ascii_case("var")
+                && is_computationally_independent(&function.value)
+        }
+        ComponentValue::SimpleBlock(block) => is_computationally_independent(&block.value),
+        ComponentValue::PreservedToken(token) => match &token.token {
+            CSSToken::DimensionToken { unit, .. } => {
+                LengthUnit::from_unit(unit).is_none_or(|v| v == LengthUnit::Px)
+            }
+            _ => true,
+        },
+    })
+}
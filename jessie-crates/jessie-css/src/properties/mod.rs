@@ -0,0 +1,432 @@
+use crate::animation::easing::EasingFunction;
+use crate::animation::interpolate::AnimationType;
+use crate::animation::{
+    AnimationDirection, AnimationName, FillMode, IterationCount, PlayState, Time,
+    TransitionProperty,
+};
+use crate::cascade::CssWideKeyword;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::media::container::{ContainerName, ContainerType};
+use crate::parser::{ComponentValue, Declaration, Parser};
+use crate::values::color::Color;
+use crate::values::filter::FilterList;
+use crate::values::font::{FontFamily, FontSize, FontStretch, FontStyle, FontWeight, LineHeight};
+use crate::values::image::Image;
+use crate::values::length::{
+    CornerRadius, LengthPercentage, LengthPercentageOrAuto, LengthPercentageOrNone, LineWidth,
+};
+use crate::values::shadow::ShadowList;
+use crate::values::transform::{TransformList, TransformOrigin};
+use crate::values::{
+    AlphaValue, BorderStyle, BoxSizing, CommaSeparated, Display, FlexDirection, FlexWrap, Integer,
+    Overflow, Parse, Position, ValueParser, Visibility, ZIndex, expect_non_negative, parse_value,
+    parse_value_with,
+};
+pub use custom::is_custom_property;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+pub mod custom;
+pub mod shorthands;
+
+///Checks whether component values are a valid value of a property, see [`PropertyDefinition::validate`].
+pub type Validator = fn(&[ComponentValue]) -> Result<(), CssDiagnostic>;
+
+///What the cascade needs to know about a longhand property.
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyDefinition {
+    pub name: &'static str,
+    ///Whether an element without a cascaded value takes the value of its parent instead of the initial value.
+    pub inherited: bool,
+    ///The initial value, as CSS text.
+    pub initial: &'static str,
+    ///Fails with a diagnostic if a value does not follow the grammar of the property.
+    ///Properties without a typed value yet accept anything.
+    pub validate: Validator,
+    ///Whether [`PropertyDefinition::validate`] parses the value as a typed value, which is what `@supports` and
+    ///[`crate::supports::SupportReport`] count as supported.
+    pub typed: bool,
+    ///How the values of the property are interpolated by transitions and animations.
+    pub animation_type: AnimationType,
+}
+
+impl PropertyDefinition {
+    const fn new(
+        name: &'static str,
+        inherited: bool,
+        initial: &'static str,
+        validate: Validator,
+    ) -> Self {
+        Self {
+            name,
+            inherited,
+            initial,
+            validate,
+            typed: true,
+            animation_type: AnimationType::Discrete,
+        }
+    }
+
+    ///A property whose value is not typed yet, which accepts anything.
+    const fn untyped(name: &'static str, inherited: bool, initial: &'static str) -> Self {
+        Self {
+            typed: false,
+            ..Self::new(name, inherited, initial, any)
+        }
+    }
+
+    ///Makes the property interpolate as `animation_type` instead of being discrete.
+    const fn animated(self, animation_type: AnimationType) -> Self {
+        Self {
+            animation_type,
+            ..self
+        }
+    }
+
+    ///Returns the component values of the initial value. They are only parsed once.
+    pub fn initial_value(&self) -> &'static [ComponentValue<'static>] {
+        static INITIAL_VALUES: OnceLock<HashMap<&'static str, Vec<ComponentValue<'static>>>> =
+            OnceLock::new();
+        let values = INITIAL_VALUES.get_or_init(|| {
+            PROPERTIES
+                .iter()
+                .map(|property| {
+                    let value = Parser::new(property.initial).parse_list_of_component_values();
+                    (property.name, value)
+                })
+                .collect()
+        });
+        match values.get(self.name) {
+            Some(value) => value,
+            None => &[],
+        }
+    }
+}
+
+///The longhand properties the engine knows about.
+pub static PROPERTIES: &[PropertyDefinition] = &[
+    // Box model
+    PropertyDefinition::new("display", false, "inline", parsed::<Display>),
+    PropertyDefinition::new("box-sizing", false, "content-box", parsed::<BoxSizing>),
+    PropertyDefinition::new("width", false, "auto", size)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("height", false, "auto", size)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("min-width", false, "auto", size)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("min-height", false, "auto", size)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("max-width", false, "none", parsed::<LengthPercentageOrNone>)
+        .animated(AnimationType::LengthPercentageOrNone),
+    PropertyDefinition::new(
+        "max-height",
+        false,
+        "none",
+        parsed::<LengthPercentageOrNone>,
+    )
+    .animated(AnimationType::LengthPercentageOrNone),
+    PropertyDefinition::new("margin-top", false, "0", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("margin-right", false, "0", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new(
+        "margin-bottom",
+        false,
+        "0",
+        parsed::<LengthPercentageOrAuto>,
+    )
+    .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("margin-left", false, "0", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("padding-top", false, "0", padding)
+        .animated(AnimationType::LengthPercentage),
+    PropertyDefinition::new("padding-right", false, "0", padding)
+        .animated(AnimationType::LengthPercentage),
+    PropertyDefinition::new("padding-bottom", false, "0", padding)
+        .animated(AnimationType::LengthPercentage),
+    PropertyDefinition::new("padding-left", false, "0", padding)
+        .animated(AnimationType::LengthPercentage),
+    PropertyDefinition::new("border-top-width", false, "medium", parsed::<LineWidth>)
+        .animated(AnimationType::LineWidth),
+    PropertyDefinition::new("border-right-width", false, "medium", parsed::<LineWidth>)
+        .animated(AnimationType::LineWidth),
+    PropertyDefinition::new("border-bottom-width", false, "medium", parsed::<LineWidth>)
+        .animated(AnimationType::LineWidth),
+    PropertyDefinition::new("border-left-width", false, "medium", parsed::<LineWidth>)
+        .animated(AnimationType::LineWidth),
+    PropertyDefinition::new("border-top-style", false, "none", parsed::<BorderStyle>),
+    PropertyDefinition::new("border-right-style", false, "none", parsed::<BorderStyle>),
+    PropertyDefinition::new("border-bottom-style", false, "none", parsed::<BorderStyle>),
+    PropertyDefinition::new("border-left-style", false, "none", parsed::<BorderStyle>),
+    PropertyDefinition::new("border-top-color", false, "currentcolor", parsed::<Color>)
+        .animated(AnimationType::Color),
+    PropertyDefinition::new("border-right-color", false, "currentcolor", parsed::<Color>)
+        .animated(AnimationType::Color),
+    PropertyDefinition::new(
+        "border-bottom-color",
+        false,
+        "currentcolor",
+        parsed::<Color>,
+    )
+    .animated(AnimationType::Color),
+    PropertyDefinition::new("border-left-color", false, "currentcolor", parsed::<Color>)
+        .animated(AnimationType::Color),
+    PropertyDefinition::new("border-top-left-radius", false, "0", parsed::<CornerRadius>)
+        .animated(AnimationType::CornerRadius),
+    PropertyDefinition::new(
+        "border-top-right-radius",
+        false,
+        "0",
+        parsed::<CornerRadius>,
+    )
+    .animated(AnimationType::CornerRadius),
+    PropertyDefinition::new(
+        "border-bottom-right-radius",
+        false,
+        "0",
+        parsed::<CornerRadius>,
+    )
+    .animated(AnimationType::CornerRadius),
+    PropertyDefinition::new(
+        "border-bottom-left-radius",
+        false,
+        "0",
+        parsed::<CornerRadius>,
+    )
+    .animated(AnimationType::CornerRadius),
+    // Positioning
+    PropertyDefinition::new("position", false, "static", parsed::<Position>),
+    PropertyDefinition::new("top", false, "auto", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("right", false, "auto", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("bottom", false, "auto", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("left", false, "auto", parsed::<LengthPercentageOrAuto>)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("z-index", false, "auto", parsed::<ZIndex>)
+        .animated(AnimationType::ZIndex),
+    PropertyDefinition::new("overflow-x", false, "visible", parsed::<Overflow>),
+    PropertyDefinition::new("overflow-y", false, "visible", parsed::<Overflow>),
+    // Flexbox
+    PropertyDefinition::new("flex-direction", false, "row", parsed::<FlexDirection>),
+    PropertyDefinition::new("flex-wrap", false, "nowrap", parsed::<FlexWrap>),
+    PropertyDefinition::new("flex-grow", false, "0", non_negative_number)
+        .animated(AnimationType::Number),
+    PropertyDefinition::new("flex-shrink", false, "1", non_negative_number)
+        .animated(AnimationType::Number),
+    PropertyDefinition::new("flex-basis", false, "auto", size)
+        .animated(AnimationType::LengthPercentageOrAuto),
+    PropertyDefinition::new("order", false, "0", parsed::<Integer>)
+        .animated(AnimationType::Integer),
+    PropertyDefinition::untyped("justify-content", false, "normal"),
+    PropertyDefinition::untyped("align-items", false, "normal"),
+    PropertyDefinition::untyped("align-self", false, "auto"),
+    PropertyDefinition::untyped("align-content", false, "normal"),
+    PropertyDefinition::untyped("row-gap", false, "normal"),
+    PropertyDefinition::untyped("column-gap", false, "normal"),
+    // Color and visibility
+    PropertyDefinition::new("color", true, "black", parsed::<Color>).animated(AnimationType::Color),
+    PropertyDefinition::new("background-color", false, "transparent", parsed::<Color>)
+        .animated(AnimationType::Color),
+    PropertyDefinition::new(
+        "background-image",
+        false,
+        "none",
+        parsed::<CommaSeparated<Image>>,
+    ),
+    PropertyDefinition::new("opacity", false, "1", parsed::<AlphaValue>)
+        .animated(AnimationType::AlphaValue),
+    PropertyDefinition::new("visibility", true, "visible", parsed::<Visibility>),
+    // Fonts and text
+    PropertyDefinition::new("font-family", true, "sans-serif", parsed::<FontFamily>),
+    PropertyDefinition::new("font-size", true, "medium", parsed::<FontSize>)
+        .animated(AnimationType::FontSize),
+    PropertyDefinition::new("font-weight", true, "normal", parsed::<FontWeight>)
+        .animated(AnimationType::FontWeight),
+    PropertyDefinition::new("font-style", true, "normal", parsed::<FontStyle>),
+    PropertyDefinition::new("font-stretch", true, "normal", parsed::<FontStretch>),
+    PropertyDefinition::new("line-height", true, "normal", parsed::<LineHeight>),
+    PropertyDefinition::untyped("letter-spacing", true, "normal"),
+    PropertyDefinition::untyped("word-spacing", true, "normal"),
+    PropertyDefinition::untyped("text-align", true, "start"),
+    PropertyDefinition::untyped("text-transform", true, "none"),
+    PropertyDefinition::untyped("text-decoration-line", false, "none"),
+    PropertyDefinition::untyped("white-space", true, "normal"),
+    PropertyDefinition::new("text-shadow", true, "none", text_shadow)
+        .animated(AnimationType::ShadowList),
+    // Containment
+    PropertyDefinition::new("container-type", false, "normal", parsed::<ContainerType>),
+    PropertyDefinition::new("container-name", false, "none", parsed::<ContainerName>),
+    // Transforms and effects
+    PropertyDefinition::new("transform", false, "none", parsed::<TransformList>)
+        .animated(AnimationType::TransformList),
+    PropertyDefinition::new(
+        "transform-origin",
+        false,
+        "50% 50%",
+        parsed::<TransformOrigin>,
+    )
+    .animated(AnimationType::TransformOrigin),
+    PropertyDefinition::new("box-shadow", false, "none", parsed::<ShadowList>)
+        .animated(AnimationType::ShadowList),
+    PropertyDefinition::new("filter", false, "none", parsed::<FilterList>)
+        .animated(AnimationType::FilterList),
+    PropertyDefinition::new("backdrop-filter", false, "none", parsed::<FilterList>)
+        .animated(AnimationType::FilterList),
+    // Transitions and animations
+    PropertyDefinition::new("transition-property", false, "all", transition_property),
+    PropertyDefinition::new("transition-duration", false, "0s", times),
+    PropertyDefinition::new(
+        "transition-timing-function",
+        false,
+        "ease",
+        parsed::<CommaSeparated<EasingFunction>>,
+    ),
+    PropertyDefinition::new(
+        "transition-delay",
+        false,
+        "0s",
+        parsed::<CommaSeparated<Time>>,
+    ),
+    PropertyDefinition::new(
+        "animation-name",
+        false,
+        "none",
+        parsed::<CommaSeparated<AnimationName>>,
+    ),
+    PropertyDefinition::new("animation-duration", false, "0s", times),
+    PropertyDefinition::new(
+        "animation-timing-function",
+        false,
+        "ease",
+        parsed::<CommaSeparated<EasingFunction>>,
+    ),
+    PropertyDefinition::new(
+        "animation-delay",
+        false,
+        "0s",
+        parsed::<CommaSeparated<Time>>,
+    ),
+    PropertyDefinition::new(
+        "animation-iteration-count",
+        false,
+        "1",
+        parsed::<CommaSeparated<IterationCount>>,
+    ),
+    PropertyDefinition::new(
+        "animation-direction",
+        false,
+        "normal",
+        parsed::<CommaSeparated<AnimationDirection>>,
+    ),
+    PropertyDefinition::new(
+        "animation-fill-mode",
+        false,
+        "none",
+        parsed::<CommaSeparated<FillMode>>,
+    ),
+    PropertyDefinition::new(
+        "animation-play-state",
+        false,
+        "running",
+        parsed::<CommaSeparated<PlayState>>,
+    ),
+    // Interaction
+    PropertyDefinition::untyped("cursor", true, "auto"),
+    PropertyDefinition::untyped("pointer-events", true, "auto"),
+];
+
+///Looks up a longhand property by its ASCII case-insensitive name.
+pub fn lookup(name: &str) -> Option<&'static PropertyDefinition> {
+    PROPERTIES
+        .iter()
+        .find(|property| property.name.eq_ignore_ascii_case(name))
+}
+
+///Checks the value of a declaration against the grammar of its property, expanding shorthands into their longhands.
+///CSS-wide keywords and custom properties are always valid. The names of known properties are normalized to lowercase.
+///Values with a `var()` can only be checked once it is substituted, so they are valid too,
+///and shorthands with one are kept as they are for the cascade to expand after substitution.
+pub fn validate_declaration<'a>(
+    mut declaration: Declaration<'a>,
+) -> Result<Vec<Declaration<'a>>, CssDiagnostic> {
+    if is_custom_property(&declaration.name) {
+        return Ok(vec![declaration]);
+    }
+    let name = declaration.name.clone();
+    let with_property = |error: CssDiagnostic| CssDiagnostic {
+        message: format!("invalid `{name}`: {}", error.message),
+        ..error
+    };
+    let has_var = custom::contains_var(&declaration.value);
+    if let Some(property) = lookup(&declaration.name) {
+        if CssWideKeyword::parse(&declaration.value).is_none() && !has_var {
+            (property.validate)(&declaration.value).map_err(with_property)?;
+        }
+        declaration.name = Cow::Borrowed(property.name);
+        return Ok(vec![declaration]);
+    }
+    if let Some(shorthand) = shorthands::lookup(&declaration.name) {
+        if has_var {
+            declaration.name = Cow::Borrowed(shorthand.name);
+            return Ok(vec![declaration]);
+        }
+        return shorthand.expand(&declaration).map_err(with_property);
+    }
+    Err(CssDiagnostic::new(
+        CssDiagnosticKind::UnknownProperty,
+        declaration.span,
+        format!("unknown property `{}`", declaration.name),
+    ))
+}
+
+fn parsed<T: Parse>(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    parse_value::<T>(value).map(drop)
+}
+
+fn any(_: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    Ok(())
+}
+
+///`width`, `height` and their minimums, which can not be negative.
+fn size(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    parse_value_with(value, LengthPercentageOrAuto::parse_non_negative).map(drop)
+}
+
+fn padding(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    parse_value_with(value, LengthPercentage::parse_non_negative).map(drop)
+}
+
+fn non_negative_number(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    parse_value_with(value, |input: &mut ValueParser| {
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        expect_non_negative(input.expect_number()?, span)
+    })
+}
+
+///`transition-duration` and `animation-duration`, lists of times that can not be negative.
+fn times(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    parse_value_with(value, |input| {
+        input.parse_comma_separated(Time::parse_non_negative)
+    })
+    .map(drop)
+}
+
+///`text-shadow`, whose shadows have no spread and are never inset.
+fn text_shadow(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    parse_value_with(value, ShadowList::parse_text).map(drop)
+}
+
+///`none | <single-transition-property>#`, where `none` is only allowed on its own.
+fn transition_property(value: &[ComponentValue]) -> Result<(), CssDiagnostic> {
+    let properties = parse_value::<CommaSeparated<TransitionProperty>>(value)?;
+    if properties.0.len() > 1 && properties.0.contains(&TransitionProperty::None) {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            value.first().map(|v| v.span()).unwrap_or_default(),
+            "`none` can only be the whole value of `transition-property`",
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,533 @@
+use crate::animation::easing::EasingFunction;
+use crate::animation::{
+    AnimationDirection, AnimationName, FillMode, IterationCount, PlayState, Time,
+    TransitionProperty,
+};
+use crate::cascade::CssWideKeyword;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::media::container::{ContainerName, ContainerType};
+use crate::parser::{ComponentValue, Declaration};
+use crate::properties;
+use crate::span::Span;
+use crate::values::color::Color;
+use crate::values::font::{FontFamily, FontSize, FontStretch, FontStyle, FontWeight, LineHeight};
+use crate::values::length::{LengthPercentage, LengthPercentageOrAuto, LineWidth};
+use crate::values::{BorderStyle, Overflow, Parse, ValueParser, parse_value_with};
+use crate::{CSSToken, SpannedToken};
+use std::borrow::Cow;
+
+///The values of the longhands of a shorthand, in the order of [`ShorthandDefinition::longhands`].
+///`None` resets a longhand to its initial value.
+pub type Longhands<'a> = Vec<Option<Vec<ComponentValue<'a>>>>;
+
+///Splits the value of a shorthand into the values of its longhands.
+pub type Expander =
+    for<'i, 'a> fn(&mut ValueParser<'i, 'a>) -> Result<Longhands<'a>, CssDiagnostic>;
+
+///A shorthand property, which sets several longhands at once.
+#[derive(Debug, Clone, Copy)]
+pub struct ShorthandDefinition {
+    pub name: &'static str,
+    pub longhands: &'static [&'static str],
+    expand: Expander,
+}
+
+///The shorthand properties the engine knows about.
+pub static SHORTHANDS: &[ShorthandDefinition] = &[
+    ShorthandDefinition {
+        name: "margin",
+        longhands: &["margin-top", "margin-right", "margin-bottom", "margin-left"],
+        expand: |input| sides(input, LengthPercentageOrAuto::parse),
+    },
+    ShorthandDefinition {
+        name: "padding",
+        longhands: &[
+            "padding-top",
+            "padding-right",
+            "padding-bottom",
+            "padding-left",
+        ],
+        expand: |input| sides(input, LengthPercentage::parse_non_negative),
+    },
+    ShorthandDefinition {
+        name: "border-width",
+        longhands: &[
+            "border-top-width",
+            "border-right-width",
+            "border-bottom-width",
+            "border-left-width",
+        ],
+        expand: |input| sides(input, LineWidth::parse),
+    },
+    ShorthandDefinition {
+        name: "border-style",
+        longhands: &[
+            "border-top-style",
+            "border-right-style",
+            "border-bottom-style",
+            "border-left-style",
+        ],
+        expand: |input| sides(input, BorderStyle::parse),
+    },
+    ShorthandDefinition {
+        name: "border-color",
+        longhands: &[
+            "border-top-color",
+            "border-right-color",
+            "border-bottom-color",
+            "border-left-color",
+        ],
+        expand: |input| sides(input, Color::parse),
+    },
+    ShorthandDefinition {
+        name: "border-top",
+        longhands: &["border-top-width", "border-top-style", "border-top-color"],
+        expand: border_side,
+    },
+    ShorthandDefinition {
+        name: "border-right",
+        longhands: &[
+            "border-right-width",
+            "border-right-style",
+            "border-right-color",
+        ],
+        expand: border_side,
+    },
+    ShorthandDefinition {
+        name: "border-bottom",
+        longhands: &[
+            "border-bottom-width",
+            "border-bottom-style",
+            "border-bottom-color",
+        ],
+        expand: border_side,
+    },
+    ShorthandDefinition {
+        name: "border-left",
+        longhands: &[
+            "border-left-width",
+            "border-left-style",
+            "border-left-color",
+        ],
+        expand: border_side,
+    },
+    ShorthandDefinition {
+        name: "border",
+        longhands: &[
+            "border-top-width",
+            "border-top-style",
+            "border-top-color",
+            "border-right-width",
+            "border-right-style",
+            "border-right-color",
+            "border-bottom-width",
+            "border-bottom-style",
+            "border-bottom-color",
+            "border-left-width",
+            "border-left-style",
+            "border-left-color",
+        ],
+        expand: |input| {
+            let side = border_side(input)?;
+            Ok((0..4).flat_map(|_| side.clone()).collect())
+        },
+    },
+    ShorthandDefinition {
+        name: "border-radius",
+        longhands: &[
+            "border-top-left-radius",
+            "border-top-right-radius",
+            "border-bottom-right-radius",
+            "border-bottom-left-radius",
+        ],
+        expand: border_radius,
+    },
+    ShorthandDefinition {
+        name: "overflow",
+        longhands: &["overflow-x", "overflow-y"],
+        expand: overflow,
+    },
+    ShorthandDefinition {
+        name: "font",
+        longhands: &[
+            "font-style",
+            "font-weight",
+            "font-stretch",
+            "font-size",
+            "line-height",
+            "font-family",
+        ],
+        expand: font,
+    },
+    ShorthandDefinition {
+        name: "container",
+        longhands: &["container-name", "container-type"],
+        expand: container,
+    },
+    ShorthandDefinition {
+        name: "transition",
+        longhands: TRANSITION_LONGHANDS,
+        expand: transition,
+    },
+    ShorthandDefinition {
+        name: "animation",
+        longhands: ANIMATION_LONGHANDS,
+        expand: animation,
+    },
+];
+
+///Looks up a shorthand property by its ASCII case-insensitive name.
+pub fn lookup(name: &str) -> Option<&'static ShorthandDefinition> {
+    SHORTHANDS
+        .iter()
+        .find(|shorthand| shorthand.name.eq_ignore_ascii_case(name))
+}
+
+impl ShorthandDefinition {
+    ///Expands a declaration of this shorthand into declarations of its longhands, which keep its importance and span.
+    ///A CSS-wide keyword applies to every longhand.
+    pub fn expand<'a>(
+        &self,
+        declaration: &Declaration<'a>,
+    ) -> Result<Vec<Declaration<'a>>, CssDiagnostic> {
+        let values = match CssWideKeyword::parse(&declaration.value) {
+            Some(_) => vec![Some(declaration.value.clone()); self.longhands.len()],
+            None => parse_value_with(&declaration.value, self.expand)?,
+        };
+        let declarations = self
+            .longhands
+            .iter()
+            .zip(values)
+            .map(|(name, value)| Declaration {
+                name: Cow::Borrowed(name),
+                value: value.unwrap_or_else(|| match properties::lookup(name) {
+                    Some(property) => property.initial_value().to_vec(),
+                    None => vec![],
+                }),
+                important: declaration.important,
+                span: declaration.span,
+            })
+            .collect();
+        Ok(declarations)
+    }
+}
+
+///`<value>{1,4}`, setting the top, right, bottom and left sides in that order.
+///Missing sides copy the opposite side, and the left side copies the top side if only one value is given.
+fn sides<'i, 'a, T>(
+    input: &mut ValueParser<'i, 'a>,
+    parse: impl Fn(&mut ValueParser<'i, 'a>) -> Result<T, CssDiagnostic>,
+) -> Result<Longhands<'a>, CssDiagnostic> {
+    let mut values = vec![input.parse_with_values(&parse)?.1];
+    while values.len() < 4 && !input.is_exhausted() {
+        values.push(input.parse_with_values(&parse)?.1);
+    }
+    Ok(expand_sides(&values)
+        .into_iter()
+        .map(|v| Some(v.to_vec()))
+        .collect())
+}
+
+fn expand_sides<T: Copy>(values: &[T]) -> [T; 4] {
+    match *values {
+        [all] => [all; 4],
+        [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+        [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+        [top, right, bottom, left, ..] => [top, right, bottom, left],
+        [] => unreachable!("at least one value is parsed"),
+    }
+}
+
+///`<line-width> || <line-style> || <color>`, setting the width, style and color of a border.
+fn border_side<'a>(input: &mut ValueParser<'_, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let mut out: Longhands = vec![None, None, None];
+    while !input.is_exhausted() {
+        if out[0].is_none()
+            && let Ok((_, values)) = input.parse_with_values(LineWidth::parse)
+        {
+            out[0] = Some(values.to_vec());
+        } else if out[1].is_none()
+            && let Ok((_, values)) = input.parse_with_values(BorderStyle::parse)
+        {
+            out[1] = Some(values.to_vec());
+        } else if out[2].is_none()
+            && let Ok((_, values)) = input.parse_with_values(Color::parse)
+        {
+            out[2] = Some(values.to_vec());
+        } else {
+            let value = input.consume()?;
+            return Err(crate::values::unexpected(
+                value,
+                "a border width, style or color",
+            ));
+        }
+    }
+    if out.iter().all(Option::is_none) {
+        return Err(input
+            .consume()
+            .expect_err("an empty value has nothing left"));
+    }
+    Ok(out)
+}
+
+///`<length-percentage>{1,4} [ / <length-percentage>{1,4} ]?`, the horizontal radii followed by the vertical ones,
+///for the top left, top right, bottom right and bottom left corners.
+fn border_radius<'i, 'a>(input: &mut ValueParser<'i, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let radii = |input: &mut ValueParser<'i, 'a>| {
+        let mut values = vec![
+            input
+                .parse_with_values(LengthPercentage::parse_non_negative)?
+                .1,
+        ];
+        while values.len() < 4
+            && let Ok((_, value)) = input.parse_with_values(LengthPercentage::parse_non_negative)
+        {
+            values.push(value);
+        }
+        Ok::<_, CssDiagnostic>(expand_sides(&values))
+    };
+    let horizontal = radii(input)?;
+    let vertical = match input.try_parse(|v| v.expect_delim('/')) {
+        Ok(()) => Some(radii(input)?),
+        Err(_) => None,
+    };
+    let whitespace = ComponentValue::PreservedToken(SpannedToken {
+        token: CSSToken::WhitespaceToken,
+        span: Span::default(),
+    });
+    Ok((0..4)
+        .map(|corner| {
+            let mut value = horizontal[corner].to_vec();
+            if let Some(vertical) = &vertical {
+                value.push(whitespace.clone());
+                value.extend_from_slice(vertical[corner]);
+            }
+            Some(value)
+        })
+        .collect())
+}
+
+///`<overflow>{1,2}`, the horizontal overflow followed by the vertical one, which defaults to the horizontal one.
+fn overflow<'a>(input: &mut ValueParser<'_, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let x = input.parse_with_values(Overflow::parse)?.1;
+    let y = match input.is_exhausted() {
+        true => x,
+        false => input.parse_with_values(Overflow::parse)?.1,
+    };
+    Ok(vec![Some(x.to_vec()), Some(y.to_vec())])
+}
+
+///`[ <font-style> || <font-weight> || <font-width> ]? <font-size> [ / <line-height> ]? <font-family>`,
+///where the optional values can also be `normal`. Whatever is not given is reset to its initial value.
+fn font<'a>(input: &mut ValueParser<'_, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let mut out: Longhands = vec![None; 6];
+    for _ in 0..3 {
+        if input.try_parse(|v| v.expect_keyword("normal")).is_ok() {
+            continue;
+        }
+        if out[0].is_none()
+            && let Ok((_, values)) = input.parse_with_values(FontStyle::parse)
+        {
+            out[0] = Some(values.to_vec());
+        } else if out[1].is_none()
+            && let Ok((_, values)) = input.parse_with_values(|v| {
+                let span = v.peek().map(|v| v.span()).unwrap_or_default();
+                match FontWeight::parse(v)? {
+                    weight @ FontWeight::Absolute(_) => Ok(weight),
+                    _ => Err(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        span,
+                        "`bolder` and `lighter` are only allowed in `font-weight`",
+                    )),
+                }
+            })
+        {
+            out[1] = Some(values.to_vec());
+        } else if out[2].is_none()
+            && let Ok((_, values)) = input.parse_with_values(FontStretch::parse_keyword)
+        {
+            out[2] = Some(values.to_vec());
+        } else {
+            break;
+        }
+    }
+    out[3] = Some(input.parse_with_values(FontSize::parse)?.1.to_vec());
+    if input.try_parse(|v| v.expect_delim('/')).is_ok() {
+        out[4] = Some(input.parse_with_values(LineHeight::parse)?.1.to_vec());
+    }
+    out[5] = Some(input.parse_with_values(FontFamily::parse)?.1.to_vec());
+    Ok(out)
+}
+
+///`<'container-name'> [ / <'container-type'> ]?`, where a missing type resets it to `normal`.
+fn container<'a>(input: &mut ValueParser<'_, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let name = input.parse_with_values(ContainerName::parse)?.1;
+    let container_type = match input.try_parse(|v| v.expect_delim('/')) {
+        Ok(()) => Some(input.parse_with_values(ContainerType::parse)?.1.to_vec()),
+        Err(_) => None,
+    };
+    Ok(vec![Some(name.to_vec()), container_type])
+}
+
+///Whether the next value is a comma, which ends an item of a list valued shorthand.
+fn at_comma(input: &mut ValueParser) -> bool {
+    matches!(
+        input.peek().and_then(|v| v.token()),
+        Some(CSSToken::CommaToken)
+    )
+}
+
+///Joins the items of a list valued shorthand into the lists of its longhands,
+///filling in the initial value of a longhand for the items that do not set it.
+fn join_items<'a>(items: Vec<Longhands<'a>>, longhands: &[&str]) -> Longhands<'a> {
+    let comma = ComponentValue::PreservedToken(SpannedToken {
+        token: CSSToken::CommaToken,
+        span: Span::default(),
+    });
+    (0..longhands.len())
+        .map(|i| {
+            let mut value = vec![];
+            for (j, item) in items.iter().enumerate() {
+                if j > 0 {
+                    value.push(comma.clone());
+                }
+                match &item[i] {
+                    Some(item) => value.extend_from_slice(item),
+                    None => value.extend(
+                        properties::lookup(longhands[i])
+                            .map(|v| v.initial_value().to_vec())
+                            .unwrap_or_default(),
+                    ),
+                }
+            }
+            Some(value)
+        })
+        .collect()
+}
+
+const TRANSITION_LONGHANDS: &[&str] = &[
+    "transition-property",
+    "transition-duration",
+    "transition-timing-function",
+    "transition-delay",
+];
+
+///`<single-transition>#`, where a single transition is
+///`[ none | <single-transition-property> ] || <time> || <easing-function> || <time>`, the first time being the duration.
+///`none` is only allowed as the whole value.
+fn transition<'a>(input: &mut ValueParser<'_, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let items = input.parse_comma_separated(|input| {
+        let mut out: Longhands = vec![None; 4];
+        let mut none = None;
+        while !input.is_exhausted() && !at_comma(input) {
+            // Easing keywords like `ease` would otherwise be taken for property names.
+            if out[2].is_none()
+                && let Ok((_, values)) = input.parse_with_values(EasingFunction::parse)
+            {
+                out[2] = Some(values.to_vec());
+            } else if out[3].is_none()
+                && let Ok((_, values)) = input.parse_with_values(Time::parse)
+            {
+                match out[1] {
+                    None => out[1] = Some(values.to_vec()),
+                    Some(_) => out[3] = Some(values.to_vec()),
+                }
+            } else if out[0].is_none()
+                && let Ok((property, values)) = input.parse_with_values(TransitionProperty::parse)
+            {
+                if property == TransitionProperty::None {
+                    none = values.first().map(ComponentValue::span);
+                }
+                out[0] = Some(values.to_vec());
+            } else {
+                let value = input.consume()?;
+                return Err(crate::values::unexpected(
+                    value,
+                    "a property, a time or an easing function",
+                ));
+            }
+        }
+        if out.iter().all(Option::is_none) {
+            let value = input.consume()?;
+            return Err(crate::values::unexpected(value, "a transition"));
+        }
+        Ok((out, none))
+    })?;
+    if items.len() > 1
+        && let Some(span) = items.iter().find_map(|v| v.1)
+    {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            "`none` can only be the whole value of `transition`",
+        ));
+    }
+    let items = items.into_iter().map(|v| v.0).collect();
+    Ok(join_items(items, TRANSITION_LONGHANDS))
+}
+
+const ANIMATION_LONGHANDS: &[&str] = &[
+    "animation-name",
+    "animation-duration",
+    "animation-timing-function",
+    "animation-delay",
+    "animation-iteration-count",
+    "animation-direction",
+    "animation-fill-mode",
+    "animation-play-state",
+];
+
+///`<single-animation>#`, where a single animation is `<time> || <easing-function> || <time> || <single-animation-iteration-count>
+///|| <single-animation-direction> || <single-animation-fill-mode> || <single-animation-play-state> || [ none | <keyframes-name> ]`.
+///The first time is the duration, and the name comes last so keywords like `ease` are not taken for it.
+fn animation<'a>(input: &mut ValueParser<'_, 'a>) -> Result<Longhands<'a>, CssDiagnostic> {
+    let items = input.parse_comma_separated(|input| {
+        let mut out: Longhands = vec![None; 8];
+        while !input.is_exhausted() && !at_comma(input) {
+            if out[3].is_none()
+                && let Ok((_, values)) = input.parse_with_values(Time::parse)
+            {
+                match out[1] {
+                    None => out[1] = Some(values.to_vec()),
+                    Some(_) => out[3] = Some(values.to_vec()),
+                }
+            } else if out[2].is_none()
+                && let Ok((_, values)) = input.parse_with_values(EasingFunction::parse)
+            {
+                out[2] = Some(values.to_vec());
+            } else if out[4].is_none()
+                && let Ok((_, values)) = input.parse_with_values(IterationCount::parse)
+            {
+                out[4] = Some(values.to_vec());
+            } else if out[5].is_none()
+                && let Ok((_, values)) = input.parse_with_values(AnimationDirection::parse)
+            {
+                out[5] = Some(values.to_vec());
+            } else if out[6].is_none()
+                && let Ok((_, values)) = input.parse_with_values(FillMode::parse)
+            {
+                out[6] = Some(values.to_vec());
+            } else if out[7].is_none()
+                && let Ok((_, values)) = input.parse_with_values(PlayState::parse)
+            {
+                out[7] = Some(values.to_vec());
+            } else if out[0].is_none()
+                && let Ok((_, values)) = input.parse_with_values(AnimationName::parse)
+            {
+                out[0] = Some(values.to_vec());
+            } else {
+                let value = input.consume()?;
+                return Err(crate::values::unexpected(
+                    value,
+                    "a name, a time, an easing function or an animation keyword",
+                ));
+            }
+        }
+        if out.iter().all(Option::is_none) {
+            let value = input.consume()?;
+            return Err(crate::values::unexpected(value, "an animation"));
+        }
+        Ok(out)
+    })?;
+    Ok(join_items(items, ANIMATION_LONGHANDS))
+}
@@ -0,0 +1,1151 @@
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{ComponentValue, Parser, SimpleBlock};
+use crate::span::Span;
+use crate::{CSSToken, HashTokenFlag, NumberType};
+use std::borrow::Cow;
+use std::ops::Add;
+
+///Parses a comma separated list of selectors, like the prelude of a style rule.
+///Any invalid selector makes the whole list invalid, as https://www.w3.org/TR/selectors-4/#invalid requires.
+pub fn parse_selector_list(input: &str) -> Result<SelectorList<'_>, CssDiagnostic> {
+    let values = Parser::new(input).parse_list_of_component_values();
+    SelectorList::parse(&values)
+}
+
+///A list of selectors, matching an element if any of them does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorList<'a> {
+    pub selectors: Vec<Selector<'a>>,
+}
+
+///A complex selector of https://www.w3.org/TR/selectors-4/#complex, like `nav > a:hover`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector<'a> {
+    ///The compound selectors from left to right.
+    pub compounds: Vec<CompoundSelector<'a>>,
+    ///`combinators[i]` is the combinator between `compounds[i]` and `compounds[i + 1]`.
+    pub combinators: Vec<Combinator>,
+    pub span: Span,
+}
+
+///A selector that starts with a combinator, relative to the element of a `:has()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelativeSelector<'a> {
+    ///The combinator between the anchor element and the first compound selector, [`Combinator::Descendant`] if none was written.
+    pub combinator: Combinator,
+    pub selector: Selector<'a>,
+}
+
+///A sequence of simple selectors that are not separated by a combinator, like `a.link:hover`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundSelector<'a> {
+    pub selectors: Vec<SimpleSelector<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Combinator {
+    ///`a b`
+    Descendant,
+    ///`a > b`
+    Child,
+    ///`a + b`
+    NextSibling,
+    ///`a ~ b`
+    SubsequentSibling,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimpleSelector<'a> {
+    ///`div` or `svg|rect`
+    Type {
+        namespace: Option<NamespacePrefix<'a>>,
+        name: Cow<'a, str>,
+    },
+    ///`*` or `svg|*`
+    Universal {
+        namespace: Option<NamespacePrefix<'a>>,
+    },
+    ///`#main`
+    Id(Cow<'a, str>),
+    ///`.card`
+    Class(Cow<'a, str>),
+    ///`[href^="https"]`
+    Attribute(AttributeSelector<'a>),
+    ///`:hover` or `:not(.card)`
+    PseudoClass(PseudoClass<'a>),
+    ///`::before`
+    PseudoElement(PseudoElement),
+    ///`&`, the elements the parent style rule matches, see [`crate::nesting`].
+    ///Outside of a nested style rule it matches like `:scope` with no specificity.
+    Nesting,
+}
+
+///The namespace written in front of a `|` in type and attribute selectors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NamespacePrefix<'a> {
+    ///`|a`, only elements without a namespace.
+    None,
+    ///`*|a`, elements in any namespace.
+    Any,
+    ///`svg|a`
+    Named(Cow<'a, str>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeSelector<'a> {
+    pub namespace: Option<NamespacePrefix<'a>>,
+    pub name: Cow<'a, str>,
+    ///The value the attribute is compared against, or `None` for `[name]` which only checks the attribute exists.
+    pub matcher: Option<AttributeMatcher<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeMatcher<'a> {
+    pub operator: AttributeOperator,
+    pub value: Cow<'a, str>,
+    pub case: AttributeCase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeOperator {
+    ///`[a=b]`, the value is exactly `b`.
+    Equals,
+    ///`[a~=b]`, the value is a whitespace separated list containing `b`.
+    Includes,
+    ///`[a|=b]`, the value is `b` or starts with `b-`.
+    DashMatch,
+    ///`[a^=b]`, the value starts with `b`.
+    Prefix,
+    ///`[a$=b]`, the value ends with `b`.
+    Suffix,
+    ///`[a*=b]`, the value contains `b`.
+    Substring,
+}
+
+///The case sensitivity of an attribute value comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeCase {
+    ///No flag was given, so the document language decides.
+    Default,
+    ///`[a=b i]`
+    Insensitive,
+    ///`[a=b s]`
+    Sensitive,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PseudoClass<'a> {
+    Hover,
+    Active,
+    Focus,
+    FocusVisible,
+    FocusWithin,
+    Enabled,
+    Disabled,
+    Checked,
+    Indeterminate,
+    Required,
+    Optional,
+    ReadOnly,
+    ReadWrite,
+    PlaceholderShown,
+    Default,
+    Valid,
+    Invalid,
+    Link,
+    Visited,
+    AnyLink,
+    Target,
+    Root,
+    Empty,
+    Scope,
+    Defined,
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    FirstOfType,
+    LastOfType,
+    OnlyOfType,
+    ///`:not(a, b)`
+    Not(SelectorList<'a>),
+    ///`:is(a, b)`, invalid selectors in the list are ignored.
+    Is(SelectorList<'a>),
+    ///`:where(a, b)`, like `:is()` but without specificity.
+    Where(SelectorList<'a>),
+    ///`:has(> a, + b)`
+    Has(Vec<RelativeSelector<'a>>),
+    ///`:nth-child(2n + 1 of .card)`
+    NthChild {
+        anb: AnB,
+        of: Option<SelectorList<'a>>,
+    },
+    ///`:nth-last-child(2n + 1 of .card)`
+    NthLastChild {
+        anb: AnB,
+        of: Option<SelectorList<'a>>,
+    },
+    NthOfType(AnB),
+    NthLastOfType(AnB),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PseudoElement {
+    Before,
+    After,
+    FirstLine,
+    FirstLetter,
+    Marker,
+    Placeholder,
+    Selection,
+    Backdrop,
+    FileSelectorButton,
+}
+
+///The pseudo-classes that take no arguments, by name.
+pub static PSEUDO_CLASSES: &[(&str, PseudoClass<'static>)] = &[
+    ("hover", PseudoClass::Hover),
+    ("active", PseudoClass::Active),
+    ("focus", PseudoClass::Focus),
+    ("focus-visible", PseudoClass::FocusVisible),
+    ("focus-within", PseudoClass::FocusWithin),
+    ("enabled", PseudoClass::Enabled),
+    ("disabled", PseudoClass::Disabled),
+    ("checked", PseudoClass::Checked),
+    ("indeterminate", PseudoClass::Indeterminate),
+    ("required", PseudoClass::Required),
+    ("optional", PseudoClass::Optional),
+    ("read-only", PseudoClass::ReadOnly),
+    ("read-write", PseudoClass::ReadWrite),
+    ("placeholder-shown", PseudoClass::PlaceholderShown),
+    ("default", PseudoClass::Default),
+    ("valid", PseudoClass::Valid),
+    ("invalid", PseudoClass::Invalid),
+    ("link", PseudoClass::Link),
+    ("visited", PseudoClass::Visited),
+    ("any-link", PseudoClass::AnyLink),
+    ("target", PseudoClass::Target),
+    ("root", PseudoClass::Root),
+    ("empty", PseudoClass::Empty),
+    ("scope", PseudoClass::Scope),
+    ("defined", PseudoClass::Defined),
+    ("first-child", PseudoClass::FirstChild),
+    ("last-child", PseudoClass::LastChild),
+    ("only-child", PseudoClass::OnlyChild),
+    ("first-of-type", PseudoClass::FirstOfType),
+    ("last-of-type", PseudoClass::LastOfType),
+    ("only-of-type", PseudoClass::OnlyOfType),
+];
+
+///The pseudo-classes that are functions, like `:not()`.
+pub static FUNCTIONAL_PSEUDO_CLASSES: &[&str] = &[
+    "not",
+    "is",
+    "where",
+    "has",
+    "nth-child",
+    "nth-last-child",
+    "nth-of-type",
+    "nth-last-of-type",
+];
+
+///The pseudo-elements, by name.
+pub static PSEUDO_ELEMENTS: &[(&str, PseudoElement)] = &[
+    ("before", PseudoElement::Before),
+    ("after", PseudoElement::After),
+    ("first-line", PseudoElement::FirstLine),
+    ("first-letter", PseudoElement::FirstLetter),
+    ("marker", PseudoElement::Marker),
+    ("placeholder", PseudoElement::Placeholder),
+    ("selection", PseudoElement::Selection),
+    ("backdrop", PseudoElement::Backdrop),
+    ("file-selector-button", PseudoElement::FileSelectorButton),
+];
+
+///The `An+B` notation of https://www.w3.org/TR/css-syntax-3/#anb-microsyntax, matching every `a`th index starting at `b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnB {
+    pub a: i32,
+    pub b: i32,
+}
+
+///The specificity of https://www.w3.org/TR/selectors-4/#specificity-rules, compared component by component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Specificity {
+    pub ids: u32,
+    pub classes: u32,
+    pub types: u32,
+}
+
+impl Add for Specificity {
+    type Output = Specificity;
+
+    fn add(self, other: Specificity) -> Specificity {
+        Specificity {
+            ids: self.ids + other.ids,
+            classes: self.classes + other.classes,
+            types: self.types + other.types,
+        }
+    }
+}
+
+impl<'a> SelectorList<'a> {
+    ///Parses a comma separated list of selectors from component values, like the prelude of a qualified rule.
+    pub fn parse(values: &[ComponentValue<'a>]) -> Result<Self, CssDiagnostic> {
+        Self::parse_with(values, Span::default(), false)
+    }
+
+    fn parse_with(
+        values: &[ComponentValue<'a>],
+        end: Span,
+        nested: bool,
+    ) -> Result<Self, CssDiagnostic> {
+        let selectors = split_commas(values, end)
+            .into_iter()
+            .map(|(values, end)| SelectorParser::new(values, end, nested).parse_complete_selector())
+            .collect::<Result<_, _>>()?;
+        Ok(Self { selectors })
+    }
+
+    ///Parses the prelude of a style rule nested in another one, which is a list of relative selectors.
+    ///Every selector ends up containing a `&`: one that starts with a combinator is relative to `&`,
+    ///and one that does not contain `&` at all is a descendant of it, like https://www.w3.org/TR/css-nesting-1/#syntax asks for.
+    pub fn parse_nested(values: &[ComponentValue<'a>]) -> Result<Self, CssDiagnostic> {
+        let selectors = split_commas(values, Span::default())
+            .into_iter()
+            .map(|(values, end)| {
+                let mut parser = SelectorParser::new(values, end, false);
+                parser.skip_whitespace();
+                let combinator = parser.parse_combinator();
+                let mut selector = parser.parse_complete_selector()?;
+                let combinator = match combinator {
+                    Some(combinator) => combinator,
+                    None if selector.contains_nesting() => return Ok(selector),
+                    None => Combinator::Descendant,
+                };
+                selector.compounds.insert(
+                    0,
+                    CompoundSelector {
+                        selectors: vec![SimpleSelector::Nesting],
+                    },
+                );
+                selector.combinators.insert(0, combinator);
+                Ok(selector)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { selectors })
+    }
+
+    ///Whether any of the selectors contains `&`, including in the arguments of pseudo-classes.
+    pub fn contains_nesting(&self) -> bool {
+        self.selectors.iter().any(Selector::contains_nesting)
+    }
+
+    ///Parses a forgiving selector list, which drops the selectors that fail to parse instead of failing as a whole.
+    fn parse_forgiving(values: &[ComponentValue<'a>], end: Span) -> Self {
+        let selectors = split_commas(values, end)
+            .into_iter()
+            .filter_map(|(values, end)| {
+                SelectorParser::new(values, end, true)
+                    .parse_complete_selector()
+                    .ok()
+            })
+            .collect();
+        Self { selectors }
+    }
+
+    ///Returns the highest specificity of the selectors in the list, which is what `:is()` and `:not()` take on.
+    pub fn specificity(&self) -> Specificity {
+        self.selectors
+            .iter()
+            .map(Selector::specificity)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Selector<'_> {
+    pub fn specificity(&self) -> Specificity {
+        self.compounds
+            .iter()
+            .map(CompoundSelector::specificity)
+            .fold(Specificity::default(), Add::add)
+    }
+
+    ///Whether the selector contains `&`, including in the arguments of pseudo-classes.
+    pub fn contains_nesting(&self) -> bool {
+        self.compounds
+            .iter()
+            .flat_map(|v| &v.selectors)
+            .any(|v| match v {
+                SimpleSelector::Nesting => true,
+                SimpleSelector::PseudoClass(pseudo_class) => pseudo_class.contains_nesting(),
+                _ => false,
+            })
+    }
+
+    ///Returns the pseudo-element this selector selects, which is always in its last compound selector.
+    pub fn pseudo_element(&self) -> Option<PseudoElement> {
+        self.compounds
+            .last()?
+            .selectors
+            .iter()
+            .find_map(|v| match v {
+                SimpleSelector::PseudoElement(v) => Some(*v),
+                _ => None,
+            })
+    }
+}
+
+impl CompoundSelector<'_> {
+    pub fn specificity(&self) -> Specificity {
+        self.selectors
+            .iter()
+            .map(SimpleSelector::specificity)
+            .fold(Specificity::default(), Add::add)
+    }
+}
+
+impl SimpleSelector<'_> {
+    pub fn specificity(&self) -> Specificity {
+        let (ids, classes, types) = match self {
+            SimpleSelector::Id(_) => (1, 0, 0),
+            SimpleSelector::Class(_) | SimpleSelector::Attribute(_) => (0, 1, 0),
+            SimpleSelector::Type { .. } | SimpleSelector::PseudoElement(_) => (0, 0, 1),
+            SimpleSelector::Universal { .. } | SimpleSelector::Nesting => (0, 0, 0),
+            SimpleSelector::PseudoClass(v) => return v.specificity(),
+        };
+        Specificity {
+            ids,
+            classes,
+            types,
+        }
+    }
+}
+
+impl<'a> PseudoClass<'a> {
+    pub fn specificity(&self) -> Specificity {
+        let class = Specificity {
+            ids: 0,
+            classes: 1,
+            types: 0,
+        };
+        match self {
+            // The specificity of an :is(), :not(), or :has() pseudo-class is replaced by the specificity of the most specific complex selector in its selector list argument.
+            PseudoClass::Not(list) | PseudoClass::Is(list) => list.specificity(),
+            PseudoClass::Has(list) => list
+                .iter()
+                .map(|v| v.selector.specificity())
+                .max()
+                .unwrap_or_default(),
+            // The specificity of a :where() pseudo-class is replaced by zero.
+            PseudoClass::Where(_) => Specificity::default(),
+            // The specificity of an :nth-child() or :nth-last-child() selector is the specificity of the pseudo class itself (counting as one pseudo-class selector) plus the specificity of the most specific complex selector in its selector list argument (if any).
+            PseudoClass::NthChild { of, .. } | PseudoClass::NthLastChild { of, .. } => {
+                class
+                    + of.as_ref()
+                        .map(SelectorList::specificity)
+                        .unwrap_or_default()
+            }
+            _ => class,
+        }
+    }
+
+    fn contains_nesting(&self) -> bool {
+        match self {
+            PseudoClass::Not(list) | PseudoClass::Is(list) | PseudoClass::Where(list) => {
+                list.contains_nesting()
+            }
+            PseudoClass::Has(list) => list.iter().any(|v| v.selector.contains_nesting()),
+            PseudoClass::NthChild { of, .. } | PseudoClass::NthLastChild { of, .. } => {
+                of.as_ref().is_some_and(SelectorList::contains_nesting)
+            }
+            _ => false,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        PSEUDO_CLASSES
+            .iter()
+            .find(|(v, _)| v.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    }
+}
+
+impl PseudoElement {
+    fn from_name(name: &str) -> Option<Self> {
+        PSEUDO_ELEMENTS
+            .iter()
+            .find(|(v, _)| v.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+
+    ///Whether the pseudo-element can also be written with a single colon, as it could before CSS 3.
+    fn is_legacy(&self) -> bool {
+        matches!(
+            self,
+            PseudoElement::Before
+                | PseudoElement::After
+                | PseudoElement::FirstLine
+                | PseudoElement::FirstLetter
+        )
+    }
+}
+
+impl AnB {
+    ///Whether the 1-based `index` is `a * n + b` for some `n` of zero or more.
+    pub fn matches(&self, index: i32) -> bool {
+        // `a` and `b` can be anywhere in the range of `i32`, so the difference is taken in `i64` to not overflow.
+        let offset = i64::from(index) - i64::from(self.b);
+        let a = i64::from(self.a);
+        if a == 0 {
+            return offset == 0;
+        }
+        offset % a == 0 && offset / a >= 0
+    }
+
+    ///Parses the `An+B` microsyntax from component values, like the argument of `:nth-child()`.
+    pub fn parse(values: &[ComponentValue]) -> Option<Self> {
+        // https://www.w3.org/TR/css-syntax-3/#anb-syntax
+        let mut tokens = Vec::with_capacity(values.len());
+        for value in values {
+            tokens.push(value.token()?);
+        }
+        let mut tokens = tokens.as_slice();
+        skip_whitespace(&mut tokens);
+
+        let anb = match tokens.split_first()? {
+            (CSSToken::IdentToken { value }, rest) if value.eq_ignore_ascii_case("odd") => {
+                tokens = rest;
+                AnB { a: 2, b: 1 }
+            }
+            (CSSToken::IdentToken { value }, rest) if value.eq_ignore_ascii_case("even") => {
+                tokens = rest;
+                AnB { a: 2, b: 0 }
+            }
+            // <integer>
+            (
+                CSSToken::NumberToken {
+                    flag: NumberType::Integer,
+                    value,
+                    ..
+                },
+                rest,
+            ) => {
+                tokens = rest;
+                AnB {
+                    a: 0,
+                    b: to_integer(*value),
+                }
+            }
+            (
+                CSSToken::DimensionToken {
+                    flag: NumberType::Integer,
+                    value,
+                    unit,
+                    ..
+                },
+                rest,
+            ) => {
+                tokens = rest;
+                Self::parse_after_a(to_integer(*value), unit, &mut tokens)?
+            }
+            // '+'?† n, where † means no whitespace is allowed between the '+' and the n.
+            (CSSToken::DelimToken { value: '+' }, [CSSToken::IdentToken { value }, rest @ ..])
+                if !value.starts_with('-') =>
+            {
+                tokens = rest;
+                Self::parse_after_a(1, value, &mut tokens)?
+            }
+            (CSSToken::IdentToken { value }, rest) => {
+                tokens = rest;
+                match value.strip_prefix('-') {
+                    Some(unit) => Self::parse_after_a(-1, unit, &mut tokens)?,
+                    None => Self::parse_after_a(1, value, &mut tokens)?,
+                }
+            }
+            _ => return None,
+        };
+
+        skip_whitespace(&mut tokens);
+        tokens.is_empty().then_some(anb)
+    }
+
+    ///Parses what follows the `A`, where `unit` is the rest of the token that held the `A`, like `n-2` in `3n-2`.
+    fn parse_after_a(a: i32, unit: &str, tokens: &mut &[&CSSToken]) -> Option<Self> {
+        let unit = unit.to_ascii_lowercase();
+        let b = match unit.as_str() {
+            // <n-dimension>
+            "n" => {
+                skip_whitespace(tokens);
+                match *tokens {
+                    // <n-dimension> <signed-integer>
+                    [
+                        CSSToken::NumberToken {
+                            flag: NumberType::Integer,
+                            value,
+                            signed: true,
+                        },
+                        rest @ ..,
+                    ] => {
+                        *tokens = rest;
+                        to_integer(*value)
+                    }
+                    // <n-dimension> ['+' | '-'] <signless-integer>
+                    [
+                        CSSToken::DelimToken {
+                            value: sign @ ('+' | '-'),
+                        },
+                        rest @ ..,
+                    ] => {
+                        *tokens = rest;
+                        skip_whitespace(tokens);
+                        let b = Self::parse_signless_integer(tokens)?;
+                        if *sign == '-' { -b } else { b }
+                    }
+                    _ => 0,
+                }
+            }
+            // <ndash-dimension> <signless-integer>
+            "n-" => {
+                skip_whitespace(tokens);
+                -Self::parse_signless_integer(tokens)?
+            }
+            // <ndashdigit-dimension>
+            _ => {
+                let digits = unit.strip_prefix("n-")?;
+                if digits.is_empty() || !digits.bytes().all(|v| v.is_ascii_digit()) {
+                    return None;
+                }
+                -digits
+                    .parse::<i64>()
+                    .map_or(i32::MAX, |v| v.min(i32::MAX as i64) as i32)
+            }
+        };
+        Some(AnB { a, b })
+    }
+
+    fn parse_signless_integer(tokens: &mut &[&CSSToken]) -> Option<i32> {
+        match *tokens {
+            [
+                CSSToken::NumberToken {
+                    flag: NumberType::Integer,
+                    value,
+                    signed: false,
+                },
+                rest @ ..,
+            ] => {
+                *tokens = rest;
+                Some(to_integer(*value))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn skip_whitespace(tokens: &mut &[&CSSToken]) {
+    while let [CSSToken::WhitespaceToken, rest @ ..] = tokens {
+        *tokens = rest;
+    }
+}
+
+fn to_integer(value: f64) -> i32 {
+    value.clamp(i32::MIN as f64, i32::MAX as f64) as i32
+}
+
+///Splits component values at the top level commas, along with the span just past the end of each part for errors about an empty part.
+fn split_commas<'t, 'a>(
+    values: &'t [ComponentValue<'a>],
+    end: Span,
+) -> Vec<(&'t [ComponentValue<'a>], Span)> {
+    let mut out = vec![];
+    let mut start = 0;
+    for (i, value) in values.iter().enumerate() {
+        if value.token() == Some(&CSSToken::CommaToken) {
+            out.push((&values[start..i], value.span()));
+            start = i + 1;
+        }
+    }
+    let end = values
+        .last()
+        .map(|v| v.span().end)
+        .map_or(end, |v| Span { start: v, end: v });
+    out.push((&values[start..], end));
+    out
+}
+
+///Describes a component value for an error message.
+fn describe(value: &ComponentValue) -> String {
+    match value {
+        ComponentValue::PreservedToken(token) => format!("`{}`", token.token),
+        ComponentValue::Function(function) => format!("`{}()`", function.name),
+        ComponentValue::SimpleBlock(block) => format!("`{}` block", block.associated_token),
+    }
+}
+
+struct SelectorParser<'t, 'a> {
+    values: &'t [ComponentValue<'a>],
+    position: usize,
+    ///Where the values end, for errors about something that is missing.
+    end: Span,
+    ///Whether the selector is the argument of a pseudo-class, where pseudo-elements are not allowed.
+    nested: bool,
+}
+
+impl<'t, 'a> SelectorParser<'t, 'a> {
+    fn new(values: &'t [ComponentValue<'a>], end: Span, nested: bool) -> Self {
+        Self {
+            values,
+            position: 0,
+            end,
+            nested,
+        }
+    }
+
+    fn peek(&self) -> Option<&'t ComponentValue<'a>> {
+        self.values.get(self.position)
+    }
+
+    fn peek_token(&self) -> Option<&'t CSSToken<'a>> {
+        self.peek()?.token()
+    }
+
+    fn peek_nth_token(&self, n: usize) -> Option<&'t CSSToken<'a>> {
+        self.values.get(self.position + n)?.token()
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while self.peek().is_some_and(ComponentValue::is_whitespace) {
+            self.position += 1;
+        }
+        self.position != start
+    }
+
+    ///The span of the next component value, or the end of the values if there is none.
+    fn span(&self) -> Span {
+        self.peek().map_or(self.end, ComponentValue::span)
+    }
+
+    fn error(&self, message: impl Into<String>) -> CssDiagnostic {
+        CssDiagnostic::new(CssDiagnosticKind::InvalidSelector, self.span(), message)
+    }
+
+    fn unexpected(&self, context: &str) -> CssDiagnostic {
+        match self.peek() {
+            Some(value) => self.error(format!("unexpected {} {context}", describe(value))),
+            None => self.error(format!("expected something {context}")),
+        }
+    }
+
+    ///Parses a selector that has to span all of the values.
+    fn parse_complete_selector(&mut self) -> Result<Selector<'a>, CssDiagnostic> {
+        self.skip_whitespace();
+        let selector = self.parse_selector()?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(selector),
+            Some(_) => Err(self.unexpected("in selector")),
+        }
+    }
+
+    fn parse_relative_selector(&mut self) -> Result<RelativeSelector<'a>, CssDiagnostic> {
+        self.skip_whitespace();
+        let combinator = match self.parse_combinator() {
+            Some(combinator) => {
+                self.skip_whitespace();
+                combinator
+            }
+            None => Combinator::Descendant,
+        };
+        let selector = self.parse_selector()?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(RelativeSelector {
+                combinator,
+                selector,
+            }),
+            Some(_) => Err(self.unexpected("in relative selector")),
+        }
+    }
+
+    fn parse_combinator(&mut self) -> Option<Combinator> {
+        let combinator = match self.peek_token()? {
+            CSSToken::DelimToken { value: '>' } => Combinator::Child,
+            CSSToken::DelimToken { value: '+' } => Combinator::NextSibling,
+            CSSToken::DelimToken { value: '~' } => Combinator::SubsequentSibling,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(combinator)
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector<'a>, CssDiagnostic> {
+        // <complex-selector> = <compound-selector> [ <combinator>? <compound-selector> ]*
+        let start = self.span();
+        let Some(first) = self.parse_compound()? else {
+            return Err(match self.peek() {
+                Some(value) => {
+                    self.error(format!("expected a selector, found {}", describe(value)))
+                }
+                None => self.error("expected a selector"),
+            });
+        };
+        let mut compounds = vec![first];
+        let mut combinators = vec![];
+        let mut end = self.values[self.position - 1].span();
+        loop {
+            let whitespace = self.skip_whitespace();
+            let combinator = match self.parse_combinator() {
+                Some(combinator) => {
+                    self.skip_whitespace();
+                    combinator
+                }
+                // Whitespace between two compound selectors is the descendant combinator.
+                None if whitespace && self.peek().is_some() => Combinator::Descendant,
+                None => break,
+            };
+            let Some(compound) = self.parse_compound()? else {
+                return Err(match self.peek() {
+                    Some(value) => self.error(format!(
+                        "expected a selector after the combinator, found {}",
+                        describe(value)
+                    )),
+                    None => self.error("expected a selector after the combinator"),
+                });
+            };
+            compounds.push(compound);
+            combinators.push(combinator);
+            end = self.values[self.position - 1].span();
+        }
+        Ok(Selector {
+            compounds,
+            combinators,
+            span: start.to(end),
+        })
+    }
+
+    fn parse_compound(&mut self) -> Result<Option<CompoundSelector<'a>>, CssDiagnostic> {
+        // <compound-selector> = [ <type-selector>? <subclass-selector>* [ <pseudo-element-selector> <pseudo-class-selector>* ]* ]!
+        let mut selectors = vec![];
+        if let Some(selector) = self.parse_type_selector() {
+            selectors.push(selector);
+        }
+        let mut pseudo_element = false;
+        loop {
+            let selector = match self.peek() {
+                Some(ComponentValue::PreservedToken(token)) => match &token.token {
+                    CSSToken::HashToken {
+                        flag: HashTokenFlag::Id,
+                        value,
+                    } => {
+                        self.position += 1;
+                        SimpleSelector::Id(value.clone())
+                    }
+                    CSSToken::HashToken { value, .. } => {
+                        return Err(self.error(format!(
+                            "`#{value}` is not a valid id selector, ids can not start with a digit"
+                        )));
+                    }
+                    CSSToken::DelimToken { value: '.' } => {
+                        self.position += 1;
+                        match self.peek_token() {
+                            Some(CSSToken::IdentToken { value }) => {
+                                self.position += 1;
+                                SimpleSelector::Class(value.clone())
+                            }
+                            _ => return Err(self.error("expected a class name after `.`")),
+                        }
+                    }
+                    CSSToken::ColonToken => self.parse_pseudo()?,
+                    CSSToken::DelimToken { value: '&' } => {
+                        self.position += 1;
+                        SimpleSelector::Nesting
+                    }
+                    _ => break,
+                },
+                Some(ComponentValue::SimpleBlock(block))
+                    if block.associated_token == CSSToken::LeftSquareBracketToken =>
+                {
+                    self.position += 1;
+                    SimpleSelector::Attribute(Self::parse_attribute(block)?)
+                }
+                _ => break,
+            };
+            // Only pseudo-classes can follow a pseudo-element.
+            if pseudo_element && !matches!(selector, SimpleSelector::PseudoClass(_)) {
+                return Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidSelector,
+                    self.values[self.position - 1].span(),
+                    "only pseudo-classes can follow a pseudo-element",
+                ));
+            }
+            pseudo_element |= matches!(selector, SimpleSelector::PseudoElement(_));
+            selectors.push(selector);
+        }
+        Ok((!selectors.is_empty()).then_some(CompoundSelector { selectors }))
+    }
+
+    ///Parses a name that can have a namespace prefix, like `svg|rect`, returning the namespace and the name.
+    ///The name is `None` for `*`, which is only allowed if `universal` is set.
+    fn parse_qualified_name(
+        &mut self,
+        universal: bool,
+    ) -> Option<(Option<NamespacePrefix<'a>>, Option<Cow<'a, str>>)> {
+        let name = |token: Option<&'t CSSToken<'a>>| match token {
+            Some(CSSToken::IdentToken { value }) => Some(Some(value.clone())),
+            Some(CSSToken::DelimToken { value: '*' }) => Some(None),
+            _ => None,
+        };
+        let bar = Some(&CSSToken::DelimToken { value: '|' });
+        let (namespace, local, length) = match (
+            self.peek_token(),
+            self.peek_nth_token(1),
+            self.peek_nth_token(2),
+        ) {
+            // ns|name, *|name
+            (first, second, third)
+                if second == bar && name(third).is_some() && name(first).is_some() =>
+            {
+                let namespace = match name(first)? {
+                    Some(prefix) => NamespacePrefix::Named(prefix),
+                    None => NamespacePrefix::Any,
+                };
+                (Some(namespace), name(third)?, 3)
+            }
+            // |name
+            (first, second, _) if first == bar && name(second).is_some() => {
+                (Some(NamespacePrefix::None), name(second)?, 2)
+            }
+            (first, _, _) => (None, name(first)?, 1),
+        };
+        if local.is_none() && !universal {
+            return None;
+        }
+        self.position += length;
+        Some((namespace, local))
+    }
+
+    fn parse_type_selector(&mut self) -> Option<SimpleSelector<'a>> {
+        Some(match self.parse_qualified_name(true)? {
+            (namespace, Some(name)) => SimpleSelector::Type { namespace, name },
+            (namespace, None) => SimpleSelector::Universal { namespace },
+        })
+    }
+
+    fn parse_pseudo(&mut self) -> Result<SimpleSelector<'a>, CssDiagnostic> {
+        // The first colon.
+        self.position += 1;
+        let element = self.peek_token() == Some(&CSSToken::ColonToken);
+        if element {
+            self.position += 1;
+        }
+        let span = self.span();
+        let error = |message: String| {
+            Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidSelector,
+                span,
+                message,
+            ))
+        };
+        let selector = match self.peek() {
+            Some(ComponentValue::PreservedToken(token)) => match &token.token {
+                CSSToken::IdentToken { value } => {
+                    self.position += 1;
+                    let pseudo_element =
+                        PseudoElement::from_name(value).filter(|v| element || v.is_legacy());
+                    match (pseudo_element, element) {
+                        (Some(pseudo_element), _) => SimpleSelector::PseudoElement(pseudo_element),
+                        (None, true) => {
+                            return error(format!("unknown pseudo-element `::{value}`"));
+                        }
+                        (None, false) => match PseudoClass::from_name(value) {
+                            Some(pseudo_class) => SimpleSelector::PseudoClass(pseudo_class),
+                            None => return error(format!("unknown pseudo-class `:{value}`")),
+                        },
+                    }
+                }
+                _ => {
+                    return error("expected the name of a pseudo-class or a pseudo-element".into());
+                }
+            },
+            Some(ComponentValue::Function(function)) if !element => {
+                self.position += 1;
+                SimpleSelector::PseudoClass(self.parse_functional_pseudo_class(
+                    &function.name,
+                    &function.value,
+                    function.span,
+                )?)
+            }
+            Some(ComponentValue::Function(function)) => {
+                return error(format!("unknown pseudo-element `::{}()`", function.name));
+            }
+            _ => return error("expected the name of a pseudo-class or a pseudo-element".into()),
+        };
+        if self.nested && matches!(selector, SimpleSelector::PseudoElement(_)) {
+            return error("pseudo-elements can not be used inside a pseudo-class".into());
+        }
+        Ok(selector)
+    }
+
+    fn parse_functional_pseudo_class(
+        &self,
+        name: &str,
+        arguments: &[ComponentValue<'a>],
+        span: Span,
+    ) -> Result<PseudoClass<'a>, CssDiagnostic> {
+        let end = Span {
+            start: span.end,
+            end: span.end,
+        };
+        let name = name.to_ascii_lowercase();
+        Ok(match name.as_str() {
+            "not" => PseudoClass::Not(SelectorList::parse_with(arguments, end, true)?),
+            "is" => PseudoClass::Is(SelectorList::parse_forgiving(arguments, end)),
+            "where" => PseudoClass::Where(SelectorList::parse_forgiving(arguments, end)),
+            "has" => PseudoClass::Has(
+                split_commas(arguments, end)
+                    .into_iter()
+                    .map(|(values, end)| {
+                        SelectorParser::new(values, end, true).parse_relative_selector()
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            "nth-child" | "nth-last-child" => {
+                // An+B [of S]?
+                let of = arguments.iter().position(|v| {
+                    matches!(v.token(), Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("of"))
+                });
+                let (anb, of) = match of {
+                    Some(i) => (
+                        &arguments[..i],
+                        Some(SelectorList::parse_with(&arguments[i + 1..], end, true)?),
+                    ),
+                    None => (arguments, None),
+                };
+                let anb = Self::parse_anb(anb, span, &name)?;
+                if name == "nth-child" {
+                    PseudoClass::NthChild { anb, of }
+                } else {
+                    PseudoClass::NthLastChild { anb, of }
+                }
+            }
+            "nth-of-type" => PseudoClass::NthOfType(Self::parse_anb(arguments, span, &name)?),
+            "nth-last-of-type" => {
+                PseudoClass::NthLastOfType(Self::parse_anb(arguments, span, &name)?)
+            }
+            _ => {
+                return Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidSelector,
+                    span,
+                    format!("unknown pseudo-class `:{name}()`"),
+                ));
+            }
+        })
+    }
+
+    fn parse_anb(values: &[ComponentValue], span: Span, name: &str) -> Result<AnB, CssDiagnostic> {
+        AnB::parse(values).ok_or_else(|| {
+            CssDiagnostic::new(
+                CssDiagnosticKind::InvalidSelector,
+                span,
+                format!("expected an An+B expression like `2n+1`, `odd` or `3` in `:{name}()`"),
+            )
+        })
+    }
+
+    fn parse_attribute(block: &'t SimpleBlock<'a>) -> Result<AttributeSelector<'a>, CssDiagnostic> {
+        // <attribute-selector> = '[' <wq-name> ']' | '[' <wq-name> <attr-matcher> [ <string-token> | <ident-token> ] <attr-modifier>? ']'
+        let end = Span {
+            start: block.span.end,
+            end: block.span.end,
+        };
+        let mut parser = SelectorParser::new(&block.value, end, true);
+        parser.skip_whitespace();
+        let Some((namespace, Some(name))) = parser.parse_qualified_name(false) else {
+            return Err(parser.error("expected an attribute name"));
+        };
+        parser.skip_whitespace();
+        if parser.peek().is_none() {
+            return Ok(AttributeSelector {
+                namespace,
+                name,
+                matcher: None,
+            });
+        }
+
+        // <attr-matcher> = [ '~' | '|' | '^' | '$' | '*' ]? '='
+        let equals = Some(&CSSToken::DelimToken { value: '=' });
+        let operator = match (parser.peek_token(), parser.peek_nth_token(1)) {
+            (first, _) if first == equals => AttributeOperator::Equals,
+            (Some(CSSToken::DelimToken { value }), second) if second == equals => {
+                parser.position += 1;
+                match value {
+                    '~' => AttributeOperator::Includes,
+                    '|' => AttributeOperator::DashMatch,
+                    '^' => AttributeOperator::Prefix,
+                    '$' => AttributeOperator::Suffix,
+                    '*' => AttributeOperator::Substring,
+                    _ => {
+                        return Err(parser.error(format!("`{value}=` is not an attribute matcher")));
+                    }
+                }
+            }
+            _ => {
+                return Err(parser.unexpected_matcher());
+            }
+        };
+        parser.position += 1;
+        parser.skip_whitespace();
+
+        let value = match parser.peek_token() {
+            Some(CSSToken::IdentToken { value })
+            | Some(CSSToken::StringToken { string: value }) => {
+                parser.position += 1;
+                value.clone()
+            }
+            _ => {
+                return Err(
+                    parser.error("expected an identifier or a string as the attribute value")
+                );
+            }
+        };
+        parser.skip_whitespace();
+
+        // <attr-modifier> = i | s
+        let case = match parser.peek_token() {
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("i") => {
+                AttributeCase::Insensitive
+            }
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("s") => {
+                AttributeCase::Sensitive
+            }
+            _ => AttributeCase::Default,
+        };
+        if case != AttributeCase::Default {
+            parser.position += 1;
+            parser.skip_whitespace();
+        }
+        if parser.peek().is_some() {
+            return Err(parser.unexpected("in attribute selector"));
+        }
+        Ok(AttributeSelector {
+            namespace,
+            name,
+            matcher: Some(AttributeMatcher {
+                operator,
+                value,
+                case,
+            }),
+        })
+    }
+
+    fn unexpected_matcher(&self) -> CssDiagnostic {
+        match self.peek() {
+            Some(value) => self.error(format!(
+                "expected an attribute matcher like `=` or `^=`, found {}",
+                describe(value)
+            )),
+            None => self.error("expected an attribute matcher like `=` or `^=`"),
+        }
+    }
+}
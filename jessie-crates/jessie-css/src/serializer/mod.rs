@@ -0,0 +1,423 @@
+use crate::parser::ComponentValue;
+use crate::{CSSToken, HashTokenFlag, NumberType, Tokenizer};
+use std::fmt::{self, Write};
+
+///Turns tokens and component values back into CSS text, following https://www.w3.org/TR/css-syntax-3/#serialization
+///An empty comment is written between two tokens whenever writing them next to each other would tokenize differently,
+///so tokenizing the output gives back the same tokens.
+#[derive(Debug, Default)]
+pub struct Serializer {
+    out: String,
+    ///What the previously written token was, as far as deciding on a comment goes.
+    last: Option<Last>,
+    ///Whether numbers are written as short as they can be, see [`Serializer::minified`].
+    shortest_numbers: bool,
+}
+
+///The tokens that can merge with the token written after them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Last {
+    Ident {
+        ///An ident of `--` followed by a `>` would be a <CDC-token>.
+        double_dash: bool,
+    },
+    AtKeyword,
+    Hash,
+    Number,
+    Dimension,
+    Delim(char),
+    Whitespace,
+    Other,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///A serializer that writes numbers as short as they can be read back, like `.5` for `0.5` or `1e3px` for `1000px`.
+    ///The type of numbers is kept, but not the one of percentages and dimensions, which only `An+B` looks at.
+    pub fn minified() -> Self {
+        Self {
+            shortest_numbers: true,
+            ..Self::default()
+        }
+    }
+
+    ///Returns the CSS text written so far.
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    pub fn push_tokens<'t, 'a: 't>(&mut self, tokens: impl IntoIterator<Item = &'t CSSToken<'a>>) {
+        for token in tokens {
+            self.push_token(token);
+        }
+    }
+
+    pub fn push_token(&mut self, token: &CSSToken) {
+        // Two <whitespace-token>s in a row would be read back as one, and a `\` delim is written with the newline
+        // that follows it, so writing another one would only make the output longer.
+        if token == &CSSToken::WhitespaceToken
+            && matches!(self.last, Some(Last::Whitespace | Last::Delim('\\')))
+        {
+            self.last = Some(Last::Whitespace);
+            return;
+        }
+        if let Some(last) = self.last
+            && Self::needs_comment(last, token)
+        {
+            self.out.push_str("/**/");
+        }
+        write_token(token, self.shortest_numbers, &mut self.out);
+        self.last = Some(match token {
+            CSSToken::IdentToken { value } => Last::Ident {
+                double_dash: value == "--",
+            },
+            CSSToken::AtKeywordToken { .. } => Last::AtKeyword,
+            CSSToken::HashToken { .. } => Last::Hash,
+            CSSToken::NumberToken { .. } => Last::Number,
+            CSSToken::DimensionToken { .. } => Last::Dimension,
+            CSSToken::DelimToken { value } => Last::Delim(*value),
+            CSSToken::WhitespaceToken => Last::Whitespace,
+            _ => Last::Other,
+        });
+    }
+
+    ///Writes whitespace like a newline and an indentation, which separates any two tokens.
+    pub fn push_whitespace(&mut self, whitespace: &str) {
+        self.out.push_str(whitespace);
+        self.last = None;
+    }
+
+    pub fn push_component_values(&mut self, values: &[ComponentValue]) {
+        for value in values {
+            self.push_component_value(value);
+        }
+    }
+
+    ///Writes a component value, closing functions and blocks even if they were not closed in the input.
+    pub fn push_component_value(&mut self, value: &ComponentValue) {
+        match value {
+            ComponentValue::PreservedToken(token) => self.push_token(&token.token),
+            ComponentValue::Function(function) => {
+                self.push_token(&CSSToken::FunctionToken {
+                    value: function.name.as_ref().into(),
+                });
+                self.push_component_values(&function.value);
+                self.push_token(&CSSToken::RightParenthesisToken);
+            }
+            ComponentValue::SimpleBlock(block) => {
+                self.push_token(&block.associated_token);
+                self.push_component_values(&block.value);
+                match block.associated_token {
+                    CSSToken::LeftCurlyBracketToken => {
+                        self.push_token(&CSSToken::RightCurlyBracketToken)
+                    }
+                    CSSToken::LeftSquareBracketToken => {
+                        self.push_token(&CSSToken::RightSquareBracketToken)
+                    }
+                    _ => self.push_token(&CSSToken::RightParenthesisToken),
+                }
+            }
+        }
+    }
+
+    fn needs_comment(last: Last, next: &CSSToken) -> bool {
+        // https://www.w3.org/TR/css-syntax-3/#serialization
+        // The table of token pairs that need a comment between them, extended by a couple of pairs that would form a <CDC-token> or a <CDO-token>.
+        let ident_like = matches!(
+            next,
+            CSSToken::IdentToken { .. }
+                | CSSToken::FunctionToken { .. }
+                | CSSToken::URLToken { .. }
+                | CSSToken::BadURLToken
+        );
+        let numeric = matches!(
+            next,
+            CSSToken::NumberToken { .. }
+                | CSSToken::PercentageToken { .. }
+                | CSSToken::DimensionToken { .. }
+        );
+        let delim = |value: char| next == &CSSToken::DelimToken { value };
+        let cdc = next == &CSSToken::CDCToken;
+        match last {
+            Last::Ident { double_dash } => {
+                ident_like
+                    || numeric
+                    || cdc
+                    || delim('-')
+                    || next == &CSSToken::LeftParenthesisToken
+                    || (double_dash && delim('>'))
+            }
+            Last::AtKeyword | Last::Hash | Last::Dimension | Last::Delim('#' | '-') => {
+                ident_like || numeric || cdc || delim('-')
+            }
+            Last::Number => ident_like || numeric || cdc || delim('%'),
+            Last::Delim('@') => ident_like || cdc || delim('-'),
+            Last::Delim('.' | '+') => numeric,
+            Last::Delim('/') => delim('*'),
+            Last::Delim('<') => delim('!'),
+            Last::Delim(_) | Last::Whitespace | Last::Other => false,
+        }
+    }
+}
+
+///Serializes a list of tokens, see [`Serializer`].
+pub fn serialize_tokens(tokens: &[CSSToken]) -> String {
+    let mut serializer = Serializer::new();
+    serializer.push_tokens(tokens);
+    serializer.finish()
+}
+
+///Serializes a list of component values, see [`Serializer`].
+pub fn serialize_component_values(values: &[ComponentValue]) -> String {
+    let mut serializer = Serializer::new();
+    serializer.push_component_values(values);
+    serializer.finish()
+}
+
+///Writes a single token without looking at its neighbours, see [`Serializer::minified`] for `shortest_numbers`.
+fn write_token(token: &CSSToken, shortest_numbers: bool, out: &mut String) {
+    match token {
+        CSSToken::EOFToken => {}
+        CSSToken::WhitespaceToken => out.push(' '),
+        CSSToken::StringToken { string } => serialize_string(string, out),
+        // An unescaped newline is what makes a string bad, the tokenizer leaves it for the <whitespace-token> that follows.
+        CSSToken::BadStringToken => out.push_str("\"\n"),
+        CSSToken::HashToken { flag, value } => {
+            out.push('#');
+            match flag {
+                HashTokenFlag::Id => serialize_identifier(value, out),
+                HashTokenFlag::Unrestricted => serialize_name(value, out),
+            }
+        }
+        // The tokenizer only ever emits a `\` <delim-token> when it is followed by a newline.
+        CSSToken::DelimToken { value: '\\' } => out.push_str("\\\n"),
+        CSSToken::DelimToken { value } => out.push(*value),
+        CSSToken::LeftParenthesisToken => out.push('('),
+        CSSToken::RightParenthesisToken => out.push(')'),
+        // Only the sign of an integer can matter, for the `B` of `An+B`.
+        CSSToken::NumberToken {
+            flag,
+            value,
+            signed,
+        } if shortest_numbers => serialize_shortest_number(
+            Some(*flag),
+            *value,
+            *signed && *flag == NumberType::Integer,
+            out,
+        ),
+        CSSToken::NumberToken {
+            flag,
+            value,
+            signed,
+        } => serialize_number(*flag, *value, *signed, out),
+        CSSToken::PercentageToken {
+            flag,
+            value,
+            signed,
+        } => {
+            match shortest_numbers {
+                true => serialize_shortest_number(None, *value, false, out),
+                false => serialize_number(*flag, *value, *signed, out),
+            }
+            out.push('%');
+        }
+        CSSToken::DimensionToken {
+            flag,
+            value,
+            signed,
+            unit,
+        } => {
+            match shortest_numbers {
+                // The `An` of `An+B` has to be an integer, and is the only dimension with a unit starting with `n`.
+                true => {
+                    let flag = unit.starts_with(['n', 'N']).then_some(*flag);
+                    serialize_shortest_number(flag, *value, false, out);
+                }
+                false => serialize_number(*flag, *value, *signed, out),
+            }
+            // A unit like `e3` would be read back as the exponent of the number.
+            let mut chars = unit.chars();
+            let exponent = matches!(chars.next(), Some('e' | 'E'))
+                && match chars.next() {
+                    Some('+' | '-') => chars.next().is_some_and(|v| v.is_ascii_digit()),
+                    v => v.is_some_and(|v| v.is_ascii_digit()),
+                };
+            if exponent {
+                serialize_code_point_escape(unit.chars().next().unwrap(), out);
+                serialize_name(&unit[1..], out);
+            } else {
+                serialize_identifier(unit, out);
+            }
+        }
+        CSSToken::CommaToken => out.push(','),
+        CSSToken::CDCToken => out.push_str("-->"),
+        CSSToken::FunctionToken { value } => {
+            serialize_identifier(value, out);
+            out.push('(');
+        }
+        CSSToken::URLToken { value } => serialize_url(value, out),
+        // A `(` is not allowed in an unquoted url, so this is read back as a <bad-url-token>.
+        CSSToken::BadURLToken => out.push_str("url(()"),
+        // https://drafts.csswg.org/css-fonts-4/#unicode-range-desc
+        CSSToken::UnicodeRangeToken { start, end } if start == end => {
+            out.push_str(&format!("U+{start:X}"))
+        }
+        CSSToken::UnicodeRangeToken { start, end } => out.push_str(&format!("U+{start:X}-{end:X}")),
+        CSSToken::IdentToken { value } => serialize_identifier(value, out),
+        CSSToken::ColonToken => out.push(':'),
+        CSSToken::SemicolonToken => out.push(';'),
+        CSSToken::CDOToken => out.push_str("<!--"),
+        CSSToken::AtKeywordToken { value } => {
+            out.push('@');
+            serialize_identifier(value, out);
+        }
+        CSSToken::LeftSquareBracketToken => out.push('['),
+        CSSToken::RightSquareBracketToken => out.push(']'),
+        CSSToken::LeftCurlyBracketToken => out.push('{'),
+        CSSToken::RightCurlyBracketToken => out.push('}'),
+    }
+}
+
+///Writes a number so it is read back with the same value, type and sign, an integer never gets a `.` and a number always gets one.
+pub fn serialize_number(flag: NumberType, value: f64, signed: bool, out: &mut String) {
+    if signed && value.is_sign_positive() {
+        out.push('+');
+    }
+    let start = out.len();
+    write!(out, "{value}").unwrap();
+    if flag == NumberType::Number && !out[start..].contains('.') {
+        out.push_str(".0");
+    }
+}
+
+///Writes a number as short as it is read back with the same value, like `.5` for `0.5` or `1e3` for `1000`.
+///`flag` is the type the number has to be read back with, `None` if any will do.
+fn serialize_shortest_number(flag: Option<NumberType>, value: f64, signed: bool, out: &mut String) {
+    if signed && value.is_sign_positive() {
+        out.push('+');
+    }
+    let mut plain = value.to_string();
+    if let Some(digits) = plain.strip_prefix("0.") {
+        plain = format!(".{digits}");
+    } else if let Some(digits) = plain.strip_prefix("-0.") {
+        plain = format!("-.{digits}");
+    }
+    match flag {
+        // An exponent makes a number a <number>, and an integer never has a `.` when written as it is.
+        Some(NumberType::Integer) => return out.push_str(&plain),
+        Some(NumberType::Number) if !plain.contains('.') => plain.push_str(".0"),
+        _ => {}
+    }
+    let exponent = format!("{value:e}");
+    out.push_str(match exponent.len() < plain.len() {
+        true => &exponent,
+        false => &plain,
+    });
+}
+
+///Escapes a code point as its hexadecimal value followed by a space.
+pub fn serialize_code_point_escape(input: char, out: &mut String) {
+    // https://drafts.csswg.org/cssom/#escape-a-character-as-code-point
+    write!(out, "\\{:x} ", input as u32).unwrap();
+}
+
+///Escapes an identifier so it is read back as a single <ident-token> with the same value.
+pub fn serialize_identifier(input: &str, out: &mut String) {
+    // https://drafts.csswg.org/cssom/#serialize-an-identifier
+    let first = input.chars().next();
+    if input == "-" {
+        // If the character is the first character and is a "-" (U+002D), and there is no second character, then the escaped character.
+        out.push_str("\\-");
+        return;
+    }
+    for (i, v) in input.chars().enumerate() {
+        match v {
+            // If the character is NULL (U+0000), then the REPLACEMENT CHARACTER (U+FFFD).
+            '\0' => out.push('\u{fffd}'),
+            // If the character is in the range [\1-\1f] (U+0001 to U+001F) or is U+007F,
+            // if the character is the first character and is in the range [0-9] (U+0030 to U+0039),
+            // or if the character is the second character and is in the range [0-9] (U+0030 to U+0039) and the first character is a "-" (U+002D),
+            // then the character escaped as code point.
+            '\u{1}'..='\u{1f}' | '\u{7f}' => serialize_code_point_escape(v, out),
+            '0'..='9' if i == 0 || (i == 1 && first == Some('-')) => {
+                serialize_code_point_escape(v, out)
+            }
+            _ => serialize_name_code_point(v, out),
+        }
+    }
+}
+
+///Escapes the value of a <hash-token> that is not a valid identifier, which only needs its code points escaped.
+pub fn serialize_name(input: &str, out: &mut String) {
+    for v in input.chars() {
+        match v {
+            '\0' => out.push('\u{fffd}'),
+            '\u{1}'..='\u{1f}' | '\u{7f}' => serialize_code_point_escape(v, out),
+            _ => serialize_name_code_point(v, out),
+        }
+    }
+}
+
+fn serialize_name_code_point(input: char, out: &mut String) {
+    // If the character is not handled by one of the above rules and is greater than or equal to U+0080, is "-" (U+002D) or "_" (U+005F), or is in one of the ranges [0-9] (U+0030 to U+0039), [A-Z] (U+0041 to U+005A), or \[a-z] (U+0061 to U+007A), then the character itself.
+    // Otherwise, the escaped character.
+    if input >= '\u{80}' || input == '-' || input == '_' || input.is_ascii_alphanumeric() {
+        out.push(input);
+    } else {
+        out.push('\\');
+        out.push(input);
+    }
+}
+
+///Writes a string wrapped in double quotes, escaping what would end it early.
+pub fn serialize_string(input: &str, out: &mut String) {
+    // https://drafts.csswg.org/cssom/#serialize-a-string
+    out.push('"');
+    for v in input.chars() {
+        match v {
+            // If the character is NULL (U+0000), then the REPLACEMENT CHARACTER (U+FFFD).
+            '\0' => out.push('\u{fffd}'),
+            // If the character is in the range [\1-\1f] (U+0001 to U+001F) or is U+007F, the character escaped as code point.
+            '\u{1}'..='\u{1f}' | '\u{7f}' => serialize_code_point_escape(v, out),
+            // If the character is '"' (U+0022) or "\" (U+005C), the escaped character.
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(v);
+            }
+            // Otherwise, the character itself.
+            _ => out.push(v),
+        }
+    }
+    out.push('"');
+}
+
+///Writes an unquoted `url()`, so it is read back as a <url-token> rather than a `url(` <function-token> and a string.
+pub fn serialize_url(input: &str, out: &mut String) {
+    out.push_str("url(");
+    for v in input.chars() {
+        match v {
+            '\0' => out.push('\u{fffd}'),
+            // Whitespace ends an unquoted url and non-printable code points make it bad, so both are escaped as code points.
+            v if Tokenizer::is_whitespace(v) || v < ' ' || v == '\u{7f}' => {
+                serialize_code_point_escape(v, out)
+            }
+            '"' | '\'' | '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(v);
+            }
+            _ => out.push(v),
+        }
+    }
+    out.push(')');
+}
+
+impl fmt::Display for CSSToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_token(self, false, &mut out);
+        f.write_str(&out)
+    }
+}
@@ -0,0 +1,66 @@
+///A position in the input of a [`crate::Tokenizer`].
+///`offset` is a byte offset into the input, `line` and `column` start at 1 and `column` counts code points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceLocation {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Default for SourceLocation {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl SourceLocation {
+    ///Returns the location after `text`, if `text` is the input that follows this location.
+    pub fn advance(self, text: &str) -> SourceLocation {
+        let mut location = self;
+        for v in text.chars() {
+            if crate::Tokenizer::is_newline(v) {
+                location.line += 1;
+                location.column = 1;
+            } else {
+                location.column += 1;
+            }
+        }
+        location.offset += text.len();
+        location
+    }
+}
+
+///The part of the input a token or a parser node was consumed from. `end` is exclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: SourceLocation,
+    pub end: SourceLocation,
+}
+
+impl Span {
+    ///Returns the smallest span that covers both `self` and `other`.
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    ///Returns the part of `input` this span points to.
+    ///`input` should be the string the span was created from.
+    pub fn slice<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.start.offset..self.end.offset]
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,208 @@
+use crate::diagnostics::CssDiagnostic;
+use crate::parser::ComponentValue;
+use crate::span::{SourceLocation, Span};
+use crate::{CSSToken, SpannedToken, Tokenizer};
+use std::collections::VecDeque;
+
+///How many tokens can be looked at ahead of the next input token, see [`TokenStream::peek_nth`].
+pub const MAX_LOOKAHEAD: usize = 3;
+///How many consumed tokens are kept around to [`TokenStream::rewind`] to.
+pub const MAX_REWIND: usize = 3;
+
+///A stream of tokens that are only tokenized once something asks for them.
+///It keeps a bounded window of tokens around the next input token, so a parser can peek ahead and reconsume
+///without the whole stylesheet ever being held as a list of tokens.
+///Once the input runs out it keeps returning the same <EOF-token>.
+pub struct TokenStream<'a> {
+    source: TokenSource<'a>,
+    ///The consumed tokens that can still be rewound to, followed by the tokens that were peeked at but not consumed yet.
+    buffer: VecDeque<SpannedToken<'a>>,
+    ///The index of the next input token in `buffer`.
+    position: usize,
+    ///The <EOF-token> once the source ran out.
+    eof: Option<SpannedToken<'a>>,
+    diagnostics: Vec<CssDiagnostic>,
+}
+
+enum TokenSource<'a> {
+    Tokenizer(Tokenizer<'a>),
+    Tokens {
+        tokens: std::vec::IntoIter<SpannedToken<'a>>,
+        ///Where the last token ended, which is where a missing <EOF-token> is located.
+        end: SourceLocation,
+    },
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::from_tokenizer(Tokenizer::new(input))
+    }
+
+    pub fn from_tokenizer(tokenizer: Tokenizer<'a>) -> Self {
+        Self::from_source(TokenSource::Tokenizer(tokenizer))
+    }
+
+    ///Creates a stream over already tokenized input. An <EOF-token> is added if the list does not end with one.
+    pub fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Self {
+        Self::from_source(TokenSource::Tokens {
+            tokens: tokens.into_iter(),
+            end: SourceLocation::default(),
+        })
+    }
+
+    ///Creates a stream over the tokens of already parsed component values, like the contents of a rule's block.
+    ///Functions and blocks are turned back into their opening token, their contents and a closing token,
+    ///so parsing the stream again results in the same component values with the same spans.
+    pub fn from_component_values(values: &[ComponentValue<'a>]) -> Self {
+        let mut tokens = vec![];
+        flatten_component_values(values, &mut tokens);
+        Self::from_tokens(tokens)
+    }
+
+    fn from_source(source: TokenSource<'a>) -> Self {
+        Self {
+            source,
+            buffer: VecDeque::with_capacity(MAX_REWIND + MAX_LOOKAHEAD + 1),
+            position: 0,
+            eof: None,
+            diagnostics: vec![],
+        }
+    }
+
+    ///Returns the input the spans of the tokens point into, if the stream tokenizes it.
+    pub fn input(&self) -> Option<&'a str> {
+        match &self.source {
+            TokenSource::Tokenizer(tokenizer) => Some(tokenizer.input()),
+            TokenSource::Tokens { .. } => None,
+        }
+    }
+
+    ///Returns the parse errors of the tokenizer for the tokens pulled so far.
+    pub fn diagnostics(&self) -> &[CssDiagnostic] {
+        &self.diagnostics
+    }
+
+    ///Returns the parse errors of the tokenizer for the tokens pulled so far, leaving none behind.
+    pub fn take_diagnostics(&mut self) -> Vec<CssDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    ///Returns the next input token without consuming it.
+    pub fn peek(&mut self) -> &SpannedToken<'a> {
+        self.peek_nth(0)
+    }
+
+    ///Returns the token `n` tokens after the next input token without consuming anything, `peek_nth(0)` being the next input token.
+    ///# Panics
+    ///If `n` is larger than [`MAX_LOOKAHEAD`].
+    pub fn peek_nth(&mut self, n: usize) -> &SpannedToken<'a> {
+        assert!(
+            n <= MAX_LOOKAHEAD,
+            "can not look {n} tokens ahead, the limit is {MAX_LOOKAHEAD}"
+        );
+        while self.buffer.len() <= self.position + n {
+            let token = self.pull();
+            self.buffer.push_back(token);
+        }
+        &self.buffer[self.position + n]
+    }
+
+    ///Consumes the next input token and returns it.
+    pub fn consume(&mut self) -> SpannedToken<'a> {
+        let out = self.peek().clone();
+        self.position += 1;
+        // Forget the tokens that are too far behind to be rewound to.
+        while self.position > MAX_REWIND {
+            self.buffer.pop_front();
+            self.position -= 1;
+        }
+        out
+    }
+
+    ///Steps back over the last consumed token, so it is the next input token again.
+    ///# Panics
+    ///If more than [`MAX_REWIND`] tokens are rewound, or nothing was consumed yet.
+    pub fn rewind(&mut self) {
+        assert!(
+            self.position > 0,
+            "can not rewind more than {MAX_REWIND} tokens"
+        );
+        self.position -= 1;
+    }
+
+    fn pull(&mut self) -> SpannedToken<'a> {
+        if let Some(eof) = &self.eof {
+            return eof.clone();
+        }
+        let token = match &mut self.source {
+            TokenSource::Tokenizer(tokenizer) => {
+                let token = tokenizer.next_token();
+                self.diagnostics.append(&mut tokenizer.take_diagnostics());
+                token
+            }
+            TokenSource::Tokens { tokens, end } => match tokens.next() {
+                Some(token) => {
+                    *end = token.span.end;
+                    token
+                }
+                None => SpannedToken {
+                    token: CSSToken::EOFToken,
+                    span: Span {
+                        start: *end,
+                        end: *end,
+                    },
+                },
+            },
+        };
+        if token.token == CSSToken::EOFToken {
+            self.eof = Some(token.clone());
+        }
+        token
+    }
+}
+
+fn flatten_component_values<'a>(values: &[ComponentValue<'a>], out: &mut Vec<SpannedToken<'a>>) {
+    for value in values {
+        let (opening, contents, closing, span) = match value {
+            ComponentValue::PreservedToken(token) => {
+                out.push(token.clone());
+                continue;
+            }
+            ComponentValue::Function(function) => (
+                CSSToken::FunctionToken {
+                    value: function.name.clone(),
+                },
+                &function.value,
+                CSSToken::RightParenthesisToken,
+                function.span,
+            ),
+            ComponentValue::SimpleBlock(block) => (
+                block.associated_token.clone(),
+                &block.value,
+                match block.associated_token {
+                    CSSToken::LeftCurlyBracketToken => CSSToken::RightCurlyBracketToken,
+                    CSSToken::LeftSquareBracketToken => CSSToken::RightSquareBracketToken,
+                    _ => CSSToken::RightParenthesisToken,
+                },
+                block.span,
+            ),
+        };
+        // Only where the function or block starts and ends is known, so the opening and closing tokens are
+        // located at the edges of its span, which is all the parser uses to span it again.
+        out.push(SpannedToken {
+            token: opening,
+            span: Span {
+                start: span.start,
+                end: span.start,
+            },
+        });
+        flatten_component_values(contents, out);
+        out.push(SpannedToken {
+            token: closing,
+            span: Span {
+                start: span.end,
+                end: span.end,
+            },
+        });
+    }
+}
@@ -0,0 +1,109 @@
+//! Parsing of inline styles like the `style` attribute of https://drafts.csswg.org/css-style-attr/, for giving one
+//! instance of a component its own declarations. The declarations are checked against the property registry, so what
+//! is returned is what [`crate::cascade::Cascade::compute`] takes, where they win over every author style rule of the
+//! same importance.
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{ComponentValue, Declaration, DeclarationOrAtRule, Parser, SyntaxError};
+use crate::properties;
+use crate::span::{SourceLocation, Span};
+use crate::values::{Parse, parse_value};
+
+///The valid declarations of an inline style, along with why the others were dropped.
+///Other than the empty one of [`Default`], it can only be made by [`parse_declaration_list`], so the cascade does not
+///have to check the declarations again.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DeclarationList<'a> {
+    declarations: Vec<Declaration<'a>>,
+    diagnostics: Vec<CssDiagnostic>,
+}
+
+impl<'a> DeclarationList<'a> {
+    ///Returns the declarations in the order they were written in, with shorthands expanded into their longhands.
+    pub fn declarations(&self) -> &[Declaration<'a>] {
+        &self.declarations
+    }
+
+    ///Returns why the declarations that are not in [`DeclarationList::declarations`] were dropped, in source order.
+    pub fn diagnostics(&self) -> &[CssDiagnostic] {
+        &self.diagnostics
+    }
+
+    ///Returns the value of the last declaration of a property, which is the one that wins, as `T`.
+    ///`None` if the property is not declared or its value is not a `T`, like when it is a CSS-wide keyword.
+    pub fn get_as<T: Parse>(&self, name: &str) -> Option<T> {
+        let declaration = self
+            .declarations
+            .iter()
+            .rev()
+            .find(|v| v.name.eq_ignore_ascii_case(name))?;
+        parse_value(&declaration.value).ok()
+    }
+}
+
+///Parses the contents of a `style` attribute, like `padding: 4px; color: red`.
+///An invalid declaration is dropped with a diagnostic without affecting the ones around it.
+pub fn parse_declaration_list(input: &str) -> DeclarationList<'_> {
+    // https://drafts.csswg.org/css-style-attr/#syntax
+    // The value of the style attribute must match the syntax of the contents of a CSS declaration block.
+    let mut parser = Parser::new(input);
+    let items = parser.parse_list_of_declarations();
+    let mut list = DeclarationList {
+        declarations: vec![],
+        diagnostics: parser.take_diagnostics(),
+    };
+    for item in items {
+        let declaration = match item {
+            DeclarationOrAtRule::Declaration(declaration) => declaration,
+            DeclarationOrAtRule::AtRule(rule) => {
+                list.diagnostics.push(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidAtRule,
+                    rule.span,
+                    format!("`@{}` is not allowed in an inline style", rule.name),
+                ));
+                continue;
+            }
+        };
+        match properties::validate_declaration(declaration) {
+            Ok(mut declarations) => list.declarations.append(&mut declarations),
+            Err(diagnostic) => list.diagnostics.push(diagnostic),
+        }
+    }
+    list.diagnostics.sort_by_key(|v| v.span.start.offset);
+    list
+}
+
+///Parses a single declaration like `color: red !important`, expanding it into its longhands if it is a shorthand.
+pub fn parse_one_declaration(input: &str) -> Result<Vec<Declaration<'_>>, CssDiagnostic> {
+    let declaration = Parser::new(input)
+        .parse_declaration()
+        .map_err(|error| syntax_error(input, error))?;
+    properties::validate_declaration(declaration)
+}
+
+///Parses a single component value like `4px` or `calc(1px + 2em)`, ignoring whitespace around it.
+pub fn parse_one_component_value(input: &str) -> Result<ComponentValue<'_>, CssDiagnostic> {
+    Parser::new(input)
+        .parse_component_value()
+        .map_err(|error| syntax_error(input, error))
+}
+
+///Turns a [`SyntaxError`], which does not know where it happened, into a diagnostic spanning the whole input.
+fn syntax_error(input: &str, error: SyntaxError) -> CssDiagnostic {
+    let span = Span {
+        start: SourceLocation::default(),
+        end: SourceLocation::default().advance(input),
+    };
+    let (kind, message) = match error {
+        SyntaxError::UnexpectedEOF => (CssDiagnosticKind::UnexpectedEOF, "expected a value"),
+        SyntaxError::ExpectedEOF => (
+            CssDiagnosticKind::UnexpectedToken,
+            "expected a single value",
+        ),
+        SyntaxError::InvalidRule => (CssDiagnosticKind::UnexpectedToken, "expected a rule"),
+        SyntaxError::InvalidDeclaration => (
+            CssDiagnosticKind::InvalidDeclaration,
+            "expected a declaration like `color: red`",
+        ),
+    };
+    CssDiagnostic::new(kind, span, message)
+}
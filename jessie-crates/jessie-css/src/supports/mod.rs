@@ -0,0 +1,241 @@
+//! The feature queries of https://www.w3.org/TR/css-conditional-4/#at-supports that `@supports` rules and
+//! the `supports()` of `@import` are conditional on, which lets stylesheets shared with web browsers gate what the engine lacks.
+//! A feature is supported if the engine parses it into typed values, so [`SupportReport`] lists the same things.
+use crate::CSSToken;
+use crate::animation::interpolate::AnimationType;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::media::container::CONTAINER_FEATURES;
+use crate::media::{FeatureDefinition, MEDIA_FEATURES};
+use crate::parser::{ComponentValue, Declaration, Parser};
+use crate::properties::shorthands::{SHORTHANDS, ShorthandDefinition};
+use crate::properties::{self, PROPERTIES, PropertyDefinition};
+use crate::selectors::{FUNCTIONAL_PSEUDO_CLASSES, PSEUDO_CLASSES, PSEUDO_ELEMENTS, SelectorList};
+use crate::values::{ValueParser, parse_value_with, unexpected};
+
+///The at-rules the engine does something with. Other at-rules are parsed and ignored.
+pub static AT_RULES: &[&str] = &[
+    "charset",
+    "container",
+    "font-face",
+    "import",
+    "keyframes",
+    "layer",
+    "media",
+    "property",
+    "supports",
+];
+
+///A condition of a feature query, like `(display: grid) and selector(:has(a))`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupportsCondition<'a> {
+    Not(Box<SupportsCondition<'a>>),
+    And(Vec<SupportsCondition<'a>>),
+    Or(Vec<SupportsCondition<'a>>),
+    ///`(display: grid)`
+    Declaration(Declaration<'a>),
+    ///`selector(:has(a))`, with the component values of the selector.
+    Selector(Vec<ComponentValue<'a>>),
+    ///Anything else in parentheses and functions like `font-tech()`, which are false.
+    ///The engine does not load fonts, so it supports no font technology or format.
+    GeneralEnclosed,
+}
+
+impl<'a> SupportsCondition<'a> {
+    ///Parses `<supports-condition>`, the prelude of `@supports`.
+    pub fn parse(values: &[ComponentValue<'a>]) -> Result<Self, CssDiagnostic> {
+        parse_value_with(values, Self::parse_condition).map_err(invalid_condition)
+    }
+
+    ///Parses the argument of the `supports()` of `@import`, which can also be a declaration without parentheses.
+    pub fn parse_import(values: &[ComponentValue<'a>]) -> Result<Self, CssDiagnostic> {
+        Self::parse(values).or_else(|error| {
+            Parser::from_component_values(values)
+                .parse_declaration()
+                .map(SupportsCondition::Declaration)
+                .map_err(|_| error)
+        })
+    }
+
+    fn parse_condition(input: &mut ValueParser<'_, 'a>) -> Result<Self, CssDiagnostic> {
+        // https://www.w3.org/TR/css-conditional-4/#typedef-supports-condition
+        if input.try_parse(|v| v.expect_keyword("not")).is_ok() {
+            let condition = Self::parse_in_parens(input)?;
+            return Ok(SupportsCondition::Not(Box::new(condition)));
+        }
+        let first = Self::parse_in_parens(input)?;
+        let operator = match input.peek().and_then(|v| v.token()) {
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("and") => "and",
+            Some(CSSToken::IdentToken { value }) if value.eq_ignore_ascii_case("or") => "or",
+            _ => return Ok(first),
+        };
+        let mut conditions = vec![first];
+        while input.try_parse(|v| v.expect_keyword(operator)).is_ok() {
+            conditions.push(Self::parse_in_parens(input)?);
+        }
+        if let Some(value) = input.peek()
+            && let Some(CSSToken::IdentToken { value: ident }) = value.token()
+            && (ident.eq_ignore_ascii_case("and") || ident.eq_ignore_ascii_case("or"))
+        {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidAtRule,
+                value.span(),
+                "`and` and `or` can not be mixed without parentheses",
+            ));
+        }
+        Ok(match operator {
+            "and" => SupportsCondition::And(conditions),
+            _ => SupportsCondition::Or(conditions),
+        })
+    }
+
+    ///Parses `<supports-in-parens>`: a condition or a declaration in parentheses, or a function.
+    fn parse_in_parens(input: &mut ValueParser<'_, 'a>) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        match value {
+            ComponentValue::SimpleBlock(block)
+                if block.associated_token == CSSToken::LeftParenthesisToken =>
+            {
+                if let Ok(condition) = parse_value_with(&block.value, Self::parse_condition) {
+                    return Ok(condition);
+                }
+                Ok(Parser::from_component_values(&block.value)
+                    .parse_declaration()
+                    .map_or(SupportsCondition::GeneralEnclosed, |declaration| {
+                        SupportsCondition::Declaration(declaration)
+                    }))
+            }
+            ComponentValue::Function(function)
+                if function.name.eq_ignore_ascii_case("selector") =>
+            {
+                Ok(SupportsCondition::Selector(function.value.clone()))
+            }
+            ComponentValue::Function(_) => Ok(SupportsCondition::GeneralEnclosed),
+            _ => Err(unexpected(
+                value,
+                "a condition in parentheses like `(display: grid)`",
+            )),
+        }
+    }
+
+    ///Whether the engine supports what the condition asks for.
+    pub fn evaluate(&self) -> bool {
+        match self {
+            SupportsCondition::Not(condition) => !condition.evaluate(),
+            SupportsCondition::And(conditions) => conditions.iter().all(Self::evaluate),
+            SupportsCondition::Or(conditions) => conditions.iter().any(Self::evaluate),
+            SupportsCondition::Declaration(declaration) => supports_declaration(declaration),
+            // `selector()` takes a single complex selector.
+            SupportsCondition::Selector(values) => {
+                SelectorList::parse(values).is_ok_and(|v| v.selectors.len() == 1)
+            }
+            SupportsCondition::GeneralEnclosed => false,
+        }
+    }
+}
+
+fn invalid_condition(diagnostic: CssDiagnostic) -> CssDiagnostic {
+    CssDiagnostic {
+        kind: CssDiagnosticKind::InvalidAtRule,
+        ..diagnostic
+    }
+}
+
+///Whether a declaration would be kept by the cascade with a typed value.
+///Custom properties are always supported, and the properties whose values are not typed yet never are,
+///since their values can not be checked.
+pub fn supports_declaration(declaration: &Declaration) -> bool {
+    let typed = properties::lookup(&declaration.name).is_none_or(|v| v.typed);
+    typed && properties::validate_declaration(declaration.clone()).is_ok()
+}
+
+///What CSS the engine supports, for documentation and tooling. [`SupportReport::to_json`] writes it out.
+#[derive(Debug, Clone)]
+pub struct SupportReport {
+    pub properties: &'static [PropertyDefinition],
+    pub shorthands: &'static [ShorthandDefinition],
+    ///The pseudo-classes, with `()` after the ones that are functions.
+    pub pseudo_classes: Vec<String>,
+    pub pseudo_elements: Vec<&'static str>,
+    pub at_rules: &'static [&'static str],
+    pub media_features: &'static [FeatureDefinition],
+    pub container_features: &'static [FeatureDefinition],
+}
+
+impl Default for SupportReport {
+    fn default() -> Self {
+        Self {
+            properties: PROPERTIES,
+            shorthands: SHORTHANDS,
+            pseudo_classes: PSEUDO_CLASSES
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .chain(FUNCTIONAL_PSEUDO_CLASSES.iter().map(|v| format!("{v}()")))
+                .collect(),
+            pseudo_elements: PSEUDO_ELEMENTS.iter().map(|(name, _)| *name).collect(),
+            at_rules: AT_RULES,
+            media_features: MEDIA_FEATURES,
+            container_features: CONTAINER_FEATURES,
+        }
+    }
+}
+
+impl SupportReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Writes the report as a JSON object. Properties are listed with whether they are typed, inherited and animated,
+    ///and shorthands with their longhands.
+    pub fn to_json(&self) -> String {
+        let properties = self.properties.iter().map(|property| {
+            format!(
+                "{{\"name\":{},\"typed\":{},\"inherited\":{},\"initial\":{},\"animated\":{}}}",
+                json_string(property.name),
+                property.typed,
+                property.inherited,
+                json_string(property.initial),
+                property.animation_type != AnimationType::Discrete,
+            )
+        });
+        let shorthands = self.shorthands.iter().map(|shorthand| {
+            format!(
+                "{{\"name\":{},\"longhands\":{}}}",
+                json_string(shorthand.name),
+                json_array(shorthand.longhands.iter().map(|v| json_string(v)))
+            )
+        });
+        let features = |features: &[FeatureDefinition]| {
+            json_array(features.iter().map(|v| json_string(v.name)))
+        };
+        let names = |names: &[&str]| json_array(names.iter().map(|v| json_string(v)));
+        format!(
+            "{{\"properties\":{},\"shorthands\":{},\"pseudo_classes\":{},\"pseudo_elements\":{},\
+             \"at_rules\":{},\"media_features\":{},\"container_features\":{}}}",
+            json_array(properties),
+            json_array(shorthands),
+            json_array(self.pseudo_classes.iter().map(|v| json_string(v))),
+            names(&self.pseudo_elements),
+            names(self.at_rules),
+            features(self.media_features),
+            features(self.container_features),
+        )
+    }
+}
+
+fn json_array(values: impl Iterator<Item = String>) -> String {
+    format!("[{}]", values.collect::<Vec<_>>().join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,227 @@
+//! A small JSON reader for the fixtures of the tests that are driven by data, like the css-parsing-tests.
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+impl Json {
+    pub fn parse(input: &str) -> Result<Json, String> {
+        let mut reader = Reader {
+            input: input.as_bytes(),
+            position: 0,
+        };
+        let value = reader.value()?;
+        reader.whitespace();
+        match reader.position == reader.input.len() {
+            true => Ok(value),
+            false => Err(reader.error("expected the end of the input")),
+        }
+    }
+    pub fn string(value: impl Into<String>) -> Json {
+        Json::String(value.into())
+    }
+    pub fn array(values: impl IntoIterator<Item = Json>) -> Json {
+        Json::Array(values.into_iter().collect())
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+///Numbers are equal when they are within a rounding error of each other, since the fixtures write them in decimal.
+impl PartialEq for Json {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Json::Null, Json::Null) => true,
+            (Json::Bool(a), Json::Bool(b)) => a == b,
+            (Json::Number(a), Json::Number(b)) => {
+                (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+            }
+            (Json::String(a), Json::String(b)) => a == b,
+            (Json::Array(a), Json::Array(b)) => a == b,
+            (Json::Object(a), Json::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{value}"),
+            Json::Number(value) => write!(f, "{value}"),
+            Json::String(value) => write!(f, "{value:?}"),
+            Json::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(entries) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{key:?}: {value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+struct Reader<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+impl Reader<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at byte {}", self.position)
+    }
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.position) {
+            self.position += 1;
+        }
+    }
+    fn expect(&mut self, text: &str) -> Result<(), String> {
+        match self.input[self.position..].starts_with(text.as_bytes()) {
+            true => {
+                self.position += text.len();
+                Ok(())
+            }
+            false => Err(self.error(&format!("expected `{text}`"))),
+        }
+    }
+    fn value(&mut self) -> Result<Json, String> {
+        self.whitespace();
+        match self.input.get(self.position) {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => {
+                self.position += 1;
+                let mut values = vec![];
+                self.whitespace();
+                if self.expect("]").is_ok() {
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.whitespace();
+                    if self.expect("]").is_ok() {
+                        return Ok(Json::Array(values));
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Some(b'{') => {
+                self.position += 1;
+                let mut entries = vec![];
+                self.whitespace();
+                if self.expect("}").is_ok() {
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    self.whitespace();
+                    let key = self.string()?;
+                    self.whitespace();
+                    self.expect(":")?;
+                    entries.push((key, self.value()?));
+                    self.whitespace();
+                    if self.expect("}").is_ok() {
+                        return Ok(Json::Object(entries));
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.position;
+                while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+                    self.input.get(self.position)
+                {
+                    self.position += 1;
+                }
+                let text = std::str::from_utf8(&self.input[start..self.position]).unwrap();
+                text.parse()
+                    .map(Json::Number)
+                    .map_err(|_| self.error("invalid number"))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut out = vec![];
+        loop {
+            let Some(&byte) = self.input.get(self.position) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.position += 1;
+            match byte {
+                b'"' => return String::from_utf8(out).map_err(|_| self.error("invalid UTF-8")),
+                b'\\' => {
+                    let Some(&escape) = self.input.get(self.position) else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.position += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let first = self.hex()?;
+                            // A surrogate pair, or a lone surrogate which is replaced like CSS replaces it.
+                            let code_point = match first {
+                                0xD800..=0xDBFF if self.expect("\\u").is_ok() => {
+                                    let second = self.hex()?;
+                                    0x10000
+                                        + ((first - 0xD800) << 10)
+                                        + (second.wrapping_sub(0xDC00) & 0x3FF)
+                                }
+                                code_point => code_point,
+                            };
+                            char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => out.push(byte),
+            }
+        }
+    }
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits = self
+            .input
+            .get(self.position..self.position + 4)
+            .and_then(|v| std::str::from_utf8(v).ok())
+            .and_then(|v| u32::from_str_radix(v, 16).ok())
+            .ok_or_else(|| self.error("expected four hex digits"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+}
@@ -1,4 +1,39 @@
+#[cfg(test)]
+mod json;
+mod test_animations;
+mod test_borrowed_tokens;
+mod test_calc;
+mod test_cascade;
+mod test_colors;
 mod test_comment_consuming;
 mod test_consume_string_token;
+mod test_css_parsing_tests;
+mod test_custom_properties;
+mod test_diagnostics;
 mod test_escape_code_point;
+mod test_font_face;
+mod test_images;
+mod test_imports;
+mod test_incremental;
+mod test_matching;
+mod test_media_queries;
+mod test_nesting;
+mod test_parse_list_of_declarations;
+mod test_parse_stylesheet;
+mod test_printer;
+mod test_reconsume_code_point;
+mod test_selectors;
+mod test_serializer;
+mod test_shadows;
 mod test_string_to_number;
+mod test_style_attribute;
+mod test_supports;
+mod test_token_spans;
+mod test_token_stream;
+mod test_tokenizer_invariants;
+mod test_transforms;
+mod test_values;
+#[cfg(test)]
+mod tree;
+#[cfg(test)]
+mod util;
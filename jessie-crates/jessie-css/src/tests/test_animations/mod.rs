@@ -0,0 +1,386 @@
+#[cfg(test)]
+mod tests {
+    use crate::animation::easing::*;
+    use crate::animation::interpolate::*;
+    use crate::animation::timeline::ElementAnimations;
+    use crate::animation::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::matching::ElementState;
+    use crate::media::MediaEnvironment;
+    use crate::parser::Parser;
+    use crate::properties::validate_declaration;
+    use crate::serializer::serialize_component_values;
+    use crate::tests::tree::Tree;
+    use crate::tests::util::{cascade, interpolate, value};
+    use crate::values::color::Color;
+    use crate::values::length::LengthPercentage;
+    use crate::values::{CommaSeparated, ToCss, parse_value};
+
+    fn easing(source: &str) -> EasingFunction {
+        parse_value(&Parser::new(source).parse_list_of_component_values()).unwrap()
+    }
+    fn assert_close(found: f32, expected: f32) {
+        assert!((found - expected).abs() < 1e-3, "{found} != {expected}");
+    }
+    #[test]
+    fn test_easing_functions() {
+        assert_close(easing("linear").apply(0.3), 0.3);
+        assert_close(easing("ease").apply(0.0), 0.0);
+        assert_close(easing("ease").apply(1.0), 1.0);
+        assert_close(easing("ease-in-out").apply(0.5), 0.5);
+        assert_close(easing("cubic-bezier(0, 0, 1, 1)").apply(0.3), 0.3);
+        assert!(easing("ease-in").apply(0.3) < 0.3);
+        assert!(easing("cubic-bezier(0.3, 1.5, 0.7, 1.5)").apply(0.5) > 1.0);
+        assert_close(easing("steps(4)").apply(0.3), 0.25);
+        assert_close(easing("steps(4, jump-start)").apply(0.3), 0.5);
+        assert_close(easing("steps(3, jump-both)").apply(0.0), 0.25);
+        assert_close(easing("steps(5, jump-none)").apply(0.5), 0.5);
+        assert_close(easing("step-start").apply(0.0), 1.0);
+        assert_close(easing("step-end").apply(0.99), 0.0);
+        assert_close(easing("step-end").apply(1.0), 1.0);
+
+        let stops = easing("linear(0, 0.25 75%, 1)");
+        assert_close(stops.apply(0.375), 0.125);
+        assert_close(stops.apply(0.875), 0.625);
+        assert_eq!(stops.to_css_string(), "linear(0 0%, 0.25 75%, 1 100%)");
+        // Points without an input are spread evenly, and an input can not go back.
+        let EasingFunction::Linear(stops) = easing("linear(0, 0.5, 1 20%, 0.8 10%, 1)") else {
+            panic!("expected linear stops");
+        };
+        let inputs: Vec<f32> = stops.iter().map(|v| v.input).collect();
+        assert_eq!(inputs, vec![0.0, 0.1, 0.2, 0.2, 1.0]);
+        assert_eq!(easing("linear(0, 1 25% 75%, 0)").apply(0.5), 1.0);
+
+        for invalid in [
+            "cubic-bezier(2, 0, 0, 1)",
+            "cubic-bezier(0, 0, 1)",
+            "steps(0)",
+            "steps(1, jump-none)",
+            "steps(2, middle)",
+            "linear(1)",
+            "bounce",
+        ] {
+            let values = Parser::new(invalid).parse_list_of_component_values();
+            let error = parse_value::<EasingFunction>(&values).unwrap_err();
+            assert_eq!(error.kind, CssDiagnosticKind::InvalidValue, "{invalid}");
+        }
+    }
+    #[test]
+    fn test_interpolation() {
+        assert_eq!(interpolate("width", "10px", "30px", 0.5), "20px");
+        assert_eq!(interpolate("width", "10px", "50%", 0.5), "calc(5px + 25%)");
+        assert_eq!(interpolate("width", "10px", "50%", 1.0), "calc(0px + 50%)");
+        assert_eq!(
+            interpolate("padding-left", "1em", "0", 0.25),
+            "calc(0.75em + 0px)"
+        );
+        assert_eq!(interpolate("opacity", "0", "1", 0.25), "0.25");
+        assert_eq!(interpolate("opacity", "0", "1", 1.5), "1");
+        assert_eq!(interpolate("z-index", "1", "4", 0.5), "3");
+        assert_eq!(interpolate("font-weight", "normal", "bold", 0.5), "550");
+        assert_eq!(interpolate("font-size", "medium", "32px", 0.5), "24px");
+        assert_eq!(
+            interpolate("border-top-left-radius", "4px", "8px 16px", 0.5),
+            "6px 10px"
+        );
+        // Values that do not interpolate flip halfway through.
+        assert_eq!(interpolate("width", "auto", "10px", 0.4), "auto");
+        assert_eq!(interpolate("width", "auto", "10px", 0.5), "10px");
+        assert_eq!(interpolate("display", "block", "none", 0.6), "none");
+        assert_eq!(interpolate("font-weight", "bolder", "100", 0.4), "bolder");
+
+        // Colors go through Oklab, so halfway between black and white is not the sRGB midpoint.
+        let color = |source: &str| {
+            parse_value::<Color>(&Parser::new(source).parse_list_of_component_values()).unwrap()
+        };
+        let Some(Color::Absolute(gray)) = color("black").animate(&color("white"), 0.5) else {
+            panic!("expected an absolute color");
+        };
+        let rgba = gray.to_rgba();
+        assert!(rgba.red > 0.35 && rgba.red < 0.45, "{rgba:?}");
+        let Some(Color::Absolute(red)) = color("red").animate(&color("blue"), 0.0) else {
+            panic!("expected an absolute color");
+        };
+        assert_eq!(red.to_rgba().to_rgba8(), (255, 0, 0, 255));
+        let Some(Color::Mix(mix)) = color("currentcolor").animate(&color("red"), 0.25) else {
+            panic!("expected a color-mix()");
+        };
+        assert_eq!(mix.first, Color::CurrentColor);
+        assert_eq!(
+            (mix.first_percentage, mix.second_percentage),
+            (Some(0.75), Some(0.25))
+        );
+
+        let length = |source: &str| {
+            parse_value::<LengthPercentage>(&Parser::new(source).parse_list_of_component_values())
+                .unwrap()
+        };
+        assert_eq!(
+            length("calc(100% - 20px)").animate(&length("20px"), 0.5),
+            Some(length("calc(50% + 0px)"))
+        );
+    }
+    #[test]
+    fn test_animation_properties() {
+        let expand = |source: &str| {
+            let declaration = Parser::new(source).parse_declaration().unwrap();
+            validate_declaration(declaration).map(|declarations| {
+                declarations
+                    .into_iter()
+                    .map(|v| (v.name.to_string(), serialize_component_values(&v.value)))
+                    .collect::<Vec<_>>()
+            })
+        };
+        let transition = expand("transition: opacity 1s, width ease-in 2s 500ms").unwrap();
+        let lists: Vec<_> = transition
+            .iter()
+            .map(|(name, value)| {
+                let values = Parser::new(value).parse_list_of_component_values();
+                let list = match name.as_str() {
+                    "transition-property" => {
+                        parse_value::<CommaSeparated<TransitionProperty>>(&values)
+                            .map(|v| v.to_css_string())
+                    }
+                    "transition-timing-function" => {
+                        parse_value::<CommaSeparated<EasingFunction>>(&values)
+                            .map(|v| v.to_css_string())
+                    }
+                    _ => parse_value::<CommaSeparated<Time>>(&values).map(|v| v.to_css_string()),
+                };
+                (name.as_str(), list.unwrap())
+            })
+            .collect();
+        assert_eq!(
+            lists,
+            vec![
+                ("transition-property", "opacity, width".to_string()),
+                ("transition-duration", "1s, 2s".to_string()),
+                (
+                    "transition-timing-function",
+                    "cubic-bezier(0.25, 0.1, 0.25, 1), cubic-bezier(0.42, 0, 1, 1)".to_string()
+                ),
+                ("transition-delay", "0s, 0.5s".to_string()),
+            ]
+        );
+        assert!(expand("transition: none").is_ok());
+        assert!(expand("transition: none, opacity 1s").is_err());
+        assert!(expand("transition: opacity 1s,").is_err());
+        assert!(expand("transition: 1s 2s 3s").is_err());
+        assert!(expand("transition-duration: -1s").is_err());
+        assert!(expand("transition-property: none, width").is_err());
+
+        let animation = expand("animation: 1s infinite alternate ease-in spin").unwrap();
+        assert_eq!(
+            animation,
+            [
+                ("animation-name", "spin"),
+                ("animation-duration", "1s"),
+                ("animation-timing-function", "ease-in"),
+                ("animation-delay", "0s"),
+                ("animation-iteration-count", "infinite"),
+                ("animation-direction", "alternate"),
+                ("animation-fill-mode", "none"),
+                ("animation-play-state", "running"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+        // A keyword is only a name once its own longhand is taken.
+        let animation = expand("animation: paused 2s paused").unwrap();
+        assert_eq!(animation[0].1, "paused");
+        assert!(expand("animation: a b").is_err());
+        assert!(expand("animation-iteration-count: -1").is_err());
+
+        let mut tree = Tree::default();
+        let element = tree.add(None, "div");
+        let cascade = cascade(
+            "div { animation: fade 2s, 'slide in' 1s 3; animation-direction: reverse; transition: color 1s }",
+        );
+        let style = cascade.compute(&tree.element(element), None, None, None);
+        let animations = AnimationDefinition::from_style(&style);
+        assert_eq!(
+            animations
+                .iter()
+                .map(|v| (v.name.as_str(), v.duration, v.iteration_count, v.direction))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "fade",
+                    Time(2.0),
+                    IterationCount::Number(1.0),
+                    AnimationDirection::Reverse
+                ),
+                (
+                    "slide in",
+                    Time(1.0),
+                    IterationCount::Number(3.0),
+                    AnimationDirection::Reverse
+                ),
+            ]
+        );
+        let transitions = TransitionDefinition::from_style(&style);
+        assert!(TransitionDefinition::find(&transitions, "color").is_some());
+        assert!(TransitionDefinition::find(&transitions, "width").is_none());
+    }
+    #[test]
+    fn test_keyframes_parsing() {
+        let mut cascade = cascade(
+            "@keyframes fade {
+                 from { opacity: 0 }
+                 50%, 75% { opacity: 0.5; margin: 4px !important; animation-timing-function: linear; animation-name: other }
+                 to { opacity: 1; colour: red }
+                 120% { opacity: 0 }
+             }
+             @keyframes none {}
+             @keyframes slide { to { margin: 0 auto } }
+             @media (width < 600px) { @keyframes slide { to { width: 10px } } }",
+        );
+        assert_eq!(
+            cascade
+                .take_diagnostics()
+                .iter()
+                .map(|v| v.kind)
+                .collect::<Vec<_>>(),
+            vec![
+                CssDiagnosticKind::UnknownProperty,
+                CssDiagnosticKind::InvalidSelector,
+                CssDiagnosticKind::InvalidAtRule,
+            ]
+        );
+        let fade = cascade.keyframes("fade").unwrap();
+        assert_eq!(
+            fade.keyframes
+                .iter()
+                .map(|v| (v.offset, v.declarations.len(), v.easing.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0.0, 1, None),
+                (0.5, 1, Some(EasingFunction::LINEAR)),
+                (0.75, 1, Some(EasingFunction::LINEAR)),
+                (1.0, 1, None),
+            ]
+        );
+        let slide = cascade.keyframes("slide").unwrap();
+        assert_eq!(slide.keyframes[0].declarations.len(), 4);
+        cascade.set_environment(MediaEnvironment::new(400.0, 800.0));
+        let slide = cascade.keyframes("slide").unwrap();
+        assert_eq!(slide.keyframes[0].declarations[0].name, "width");
+        assert!(cascade.keyframes("spin").is_none());
+    }
+    #[test]
+    fn test_transitions() {
+        let mut tree = Tree::default();
+        let button = tree.add(None, "button");
+        let cascade = cascade(
+            "button { width: 100px; height: 10px; opacity: 1; transition: width 1s linear, opacity 200ms linear }
+             button:hover { width: 200px; height: 20px; opacity: 0.5 }",
+        );
+        let mut animations = ElementAnimations::new();
+        let frame = |tree: &Tree, animations: &mut ElementAnimations, now: f64| {
+            let mut style = cascade.compute(&tree.element(button), None, None, None);
+            animations.update(&style, &cascade, now);
+            animations.apply(&mut style, now);
+            style
+        };
+        let style = frame(&tree, &mut animations, 0.0);
+        assert_eq!(value(&style, "width").as_deref(), Some("100px"));
+        assert!(!animations.is_running(0.0));
+
+        tree.nodes[button].state = ElementState::HOVER;
+        let style = frame(&tree, &mut animations, 10.0);
+        assert_eq!(value(&style, "width").as_deref(), Some("100px"));
+        // `height` is not in `transition-property`, so it changes right away.
+        assert_eq!(value(&style, "height").as_deref(), Some("20px"));
+        let style = frame(&tree, &mut animations, 10.1);
+        assert_eq!(value(&style, "width").as_deref(), Some("110px"));
+        assert_eq!(value(&style, "opacity").as_deref(), Some("0.75"));
+        let style = frame(&tree, &mut animations, 10.5);
+        assert_eq!(value(&style, "width").as_deref(), Some("150px"));
+        assert_eq!(value(&style, "opacity").as_deref(), Some("0.5"));
+        assert_eq!(
+            animations.transitioning_properties().collect::<Vec<_>>(),
+            vec!["width"]
+        );
+        assert!(animations.is_running(10.5));
+
+        // Going back halfway through only takes half as long.
+        tree.nodes[button].state = ElementState::default();
+        let style = frame(&tree, &mut animations, 10.5);
+        assert_eq!(value(&style, "width").as_deref(), Some("150px"));
+        let style = frame(&tree, &mut animations, 10.75);
+        assert_eq!(value(&style, "width").as_deref(), Some("125px"));
+        assert!(animations.is_running(10.75));
+        assert!(!animations.is_running(11.0));
+        let style = frame(&tree, &mut animations, 11.0);
+        assert_eq!(value(&style, "width").as_deref(), Some("100px"));
+        assert_eq!(animations.transitioning_properties().count(), 0);
+    }
+    #[test]
+    fn test_animations() {
+        let mut tree = Tree::default();
+        let element = tree.add(None, "div");
+        let cascade = cascade(
+            "@keyframes grow {
+                 from { width: 0px }
+                 50% { width: 100px; animation-timing-function: steps(2) }
+                 to { width: 200px; height: var(--end) }
+             }
+             div { --end: 40px; height: 20px; animation: grow 2s linear 1s 2 alternate both }
+             div:hover { animation-play-state: paused }",
+        );
+        let definition = &AnimationDefinition::from_style(&cascade.compute(
+            &tree.element(element),
+            None,
+            None,
+            None,
+        ))[0];
+        assert_eq!(definition.phase(0.5), AnimationPhase::Before);
+        assert_eq!(definition.phase(2.0), AnimationPhase::Active);
+        assert_eq!(definition.phase(5.0), AnimationPhase::After);
+        assert_eq!(definition.progress(3.5), Some(0.75));
+        assert_eq!(definition.progress(5.0), Some(0.0));
+
+        let mut animations = ElementAnimations::new();
+        let frame = |tree: &Tree, animations: &mut ElementAnimations, now: f64| {
+            let mut style = cascade.compute(&tree.element(element), None, None, None);
+            animations.update(&style, &cascade, now);
+            animations.apply(&mut style, now);
+            (
+                value(&style, "width").unwrap(),
+                value(&style, "height").unwrap(),
+            )
+        };
+        let frames = [
+            (100.0, "0px", "20px"),
+            // The first half is linear and the second half steps.
+            (101.5, "50px", "25px"),
+            (102.5, "150px", "35px"),
+            (102.9, "150px", "39px"),
+            // The second iteration plays backwards.
+            (103.5, "150px", "35px"),
+            (106.0, "0px", "20px"),
+        ];
+        for (now, width, height) in frames {
+            assert_eq!(
+                frame(&tree, &mut animations, now),
+                (width.to_string(), height.to_string()),
+                "at {now}"
+            );
+        }
+        assert_eq!(
+            animations.animation_names().collect::<Vec<_>>(),
+            vec!["grow"]
+        );
+        assert!(!animations.is_running(106.0));
+
+        // A paused animation stands still, and carries on where it was when it resumes.
+        let mut animations = ElementAnimations::new();
+        frame(&tree, &mut animations, 0.0);
+        tree.nodes[element].state = ElementState::HOVER;
+        assert_eq!(frame(&tree, &mut animations, 1.5).0, "50px");
+        assert_eq!(frame(&tree, &mut animations, 10.0).0, "50px");
+        assert!(!animations.is_running(10.0));
+        tree.nodes[element].state = ElementState::default();
+        assert_eq!(frame(&tree, &mut animations, 10.0).0, "50px");
+        assert_eq!(frame(&tree, &mut animations, 10.25).0, "75px");
+    }
+}
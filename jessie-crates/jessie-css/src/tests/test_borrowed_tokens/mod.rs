@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::borrow::Cow;
+    fn value<'a>(token: &'a CSSToken) -> &'a Cow<'a, str> {
+        match token {
+            CSSToken::IdentToken { value }
+            | CSSToken::FunctionToken { value }
+            | CSSToken::AtKeywordToken { value }
+            | CSSToken::HashToken { value, .. }
+            | CSSToken::URLToken { value } => value,
+            CSSToken::StringToken { string } => string,
+            CSSToken::DimensionToken { unit, .. } => unit,
+            _ => panic!("{token:?} has no string value"),
+        }
+    }
+    #[test]
+    fn test_borrowed_tokens() {
+        let input =
+            "@media #main .card:hover { background: url( a.png ) \"plain\" rgb(1 2 3) 10px }";
+        let tokens: Vec<CSSToken> = Tokenizer::new(input)
+            .tokenize()
+            .into_iter()
+            .filter(|v| {
+                !matches!(
+                    v,
+                    CSSToken::WhitespaceToken
+                        | CSSToken::DelimToken { .. }
+                        | CSSToken::ColonToken
+                        | CSSToken::NumberToken { .. }
+                        | CSSToken::LeftCurlyBracketToken
+                        | CSSToken::RightCurlyBracketToken
+                        | CSSToken::RightParenthesisToken
+                        | CSSToken::EOFToken
+                )
+            })
+            .collect();
+        let values: Vec<&str> = tokens.iter().map(|v| value(v).as_ref()).collect();
+        assert_eq!(
+            values,
+            vec![
+                "media",
+                "main",
+                "card",
+                "hover",
+                "background",
+                "a.png",
+                "plain",
+                "rgb",
+                "px"
+            ]
+        );
+        for token in &tokens {
+            assert!(
+                matches!(value(token), Cow::Borrowed(_)),
+                "{token:?} should borrow from the input"
+            );
+        }
+    }
+    #[test]
+    fn test_escaped_tokens() {
+        let input = "\\62 ar \"a\\\nb\" url(\\61.png) \"\\\"\"";
+        let tokens: Vec<CSSToken> = Tokenizer::new(input)
+            .tokenize()
+            .into_iter()
+            .filter(|v| !matches!(v, CSSToken::WhitespaceToken | CSSToken::EOFToken))
+            .collect();
+        let values: Vec<&str> = tokens.iter().map(|v| value(v).as_ref()).collect();
+        assert_eq!(values, vec!["bar", "ab", "a.png", "\""]);
+        for token in &tokens {
+            assert!(
+                matches!(value(token), Cow::Owned(_)),
+                "{token:?} has to be unescaped into an owned string"
+            );
+        }
+    }
+    #[test]
+    fn test_into_owned() {
+        let token = {
+            let input = String::from("#main");
+            Tokenizer::new(&input).tokenize().remove(0).into_owned()
+        };
+        assert_eq!(
+            token,
+            CSSToken::HashToken {
+                flag: HashTokenFlag::Id,
+                value: "main".into()
+            }
+        );
+    }
+}
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::tests::util::parse;
+    use crate::values::calc::{Calc, CalcCategory, CalcNode};
+    use crate::values::color::Color;
+    use crate::values::length::*;
+    use crate::values::*;
+
+    fn calc(input: &str, category: CalcCategory) -> Result<Calc, String> {
+        let values = Parser::new(input).parse_list_of_component_values();
+        parse_value_with(&values, |input| Calc::parse(input, category)).map_err(|v| v.message)
+    }
+    fn number(input: &str) -> f32 {
+        calc(input, CalcCategory::Number).unwrap().value().unwrap()
+    }
+    fn px(input: &str, context: &LengthContext, basis: f32) -> f32 {
+        parse::<LengthPercentage>(input)
+            .unwrap()
+            .to_px(context, basis)
+    }
+    #[test]
+    fn test_calc_simplification() {
+        assert_eq!(
+            parse::<LengthPercentage>("calc(10px + 5px)"),
+            Ok(LengthPercentage::Length(Length::px(15.0)))
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("calc(2 * (1em + 1em))"),
+            Ok(LengthPercentage::Length(Length {
+                value: 4.0,
+                unit: LengthUnit::Em
+            }))
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("calc(50% * 2)"),
+            Ok(LengthPercentage::Percentage(1.0))
+        );
+        // Relative units stay in the tree until layout resolves them.
+        let calc = calc(
+            "calc((100% - 2rem) / 2 + 1rem)",
+            CalcCategory::LengthPercentage,
+        )
+        .unwrap();
+        assert_eq!(
+            calc.root,
+            CalcNode::Sum(vec![
+                CalcNode::Percentage(0.5),
+                CalcNode::Length(Length {
+                    value: 0.0,
+                    unit: LengthUnit::Rem
+                })
+            ])
+        );
+        assert_eq!(calc.value(), None);
+        let context = LengthContext::new(1000.0, 800.0);
+        assert_eq!(px("calc(100% - 2rem)", &context, 500.0), 468.0);
+        assert_eq!(px("calc(100% - 2rem)", &context, 300.0), 268.0);
+        assert_eq!(px("calc(50vw - 10% + 1em)", &context, 200.0), 496.0);
+    }
+    #[test]
+    fn test_comparison_functions() {
+        let context = |width: f32| LengthContext::new(width, 800.0);
+        assert_eq!(px("min(100%, 600px)", &context(0.0), 800.0), 600.0);
+        assert_eq!(px("min(100%, 600px)", &context(0.0), 500.0), 500.0);
+        assert_eq!(px("max(50%, 10em)", &context(0.0), 200.0), 160.0);
+        let fluid = "clamp(1rem, 2.5vw, 2rem)";
+        assert_eq!(px(fluid, &context(400.0), 0.0), 16.0);
+        assert_eq!(px(fluid, &context(1000.0), 0.0), 25.0);
+        assert_eq!(px(fluid, &context(2000.0), 0.0), 32.0);
+        assert_eq!(
+            px(
+                "calc(min(10px, 2em) + max(1px, 1vw))",
+                &context(1000.0),
+                0.0
+            ),
+            20.0
+        );
+        // The minimum wins when it is larger than the maximum.
+        assert_eq!(px("clamp(20px, 5px, 10px)", &context(0.0), 0.0), 20.0);
+    }
+    #[test]
+    fn test_numeric_functions() {
+        assert_eq!(number("round(2.5)"), 3.0);
+        assert_eq!(number("round(-2.5)"), -2.0);
+        assert_eq!(number("round(up, 7, 5)"), 10.0);
+        assert_eq!(number("round(to-zero, -7, 5)"), -5.0);
+        assert_eq!(
+            parse::<LengthPercentage>("round(down, 7px, 2px)"),
+            Ok(LengthPercentage::Length(Length::px(6.0)))
+        );
+        assert_eq!(number("mod(-7, 3)"), 2.0);
+        assert_eq!(number("rem(-7, 3)"), -1.0);
+        assert!((number("sin(30deg)") - 0.5).abs() < 1e-6);
+        assert!((number("cos(pi)") + 1.0).abs() < 1e-6);
+        assert!((number("tan(0.25turn * 0.5)") - 1.0).abs() < 1e-6);
+        let angle = |input: &str| calc(input, CalcCategory::Angle).unwrap().value().unwrap();
+        assert!((angle("asin(1)") - 90.0).abs() < 1e-4);
+        assert!((angle("atan2(1px, -1px)") - 135.0).abs() < 1e-4);
+        assert_eq!(number("pow(2, 10)"), 1024.0);
+        assert_eq!(number("sqrt(16)"), 4.0);
+        assert_eq!(number("hypot(3, 4)"), 5.0);
+        assert!((number("log(e)") - 1.0).abs() < 1e-6);
+        assert_eq!(number("log(8, 2)"), 3.0);
+        assert_eq!(number("exp(0)"), 1.0);
+        assert_eq!(number("abs(-2)"), 2.0);
+        assert_eq!(number("sign(-5px)"), -1.0);
+        assert_eq!(number("calc(1px / 4px)"), 0.25);
+        // NaN and infinities are clamped when the value is used.
+        assert_eq!(number("calc(NaN)"), 0.0);
+        assert_eq!(number("calc(1 / 0)"), f32::MAX);
+    }
+    #[test]
+    fn test_math_in_properties() {
+        assert_eq!(parse::<ZIndex>("calc(1 + 2.4)"), Ok(ZIndex::Integer(3)));
+        assert_eq!(parse::<AlphaValue>("calc(1 / 4)"), Ok(AlphaValue(0.25)));
+        assert_eq!(parse::<AlphaValue>("calc(20% + 5%)"), Ok(AlphaValue(0.25)));
+        assert_eq!(
+            parse::<LineWidth>("calc(1px + 2px)"),
+            Ok(LineWidth(LengthPercentage::Length(Length::px(3.0))))
+        );
+        assert!(matches!(
+            parse::<LineWidth>("max(1px, 0.1em)"),
+            Ok(LineWidth(LengthPercentage::Calc(_)))
+        ));
+        let rgb = |input: &str| match parse::<Color>(input) {
+            Ok(Color::Absolute(color)) => color.to_rgba().to_rgba8(),
+            other => panic!("{input} parsed to {other:?}"),
+        };
+        assert_eq!(rgb("rgb(calc(255 / 2) 0 0)"), (128, 0, 0, 255));
+        assert_eq!(rgb("hsl(calc(60deg * 2) 100% 50%)"), (0, 255, 0, 255));
+        // Math functions in properties that are never negative clamp instead of failing.
+        let context = LengthContext::new(0.0, 0.0);
+        let padding = parse_value_with(
+            &Parser::new("calc(-5px)").parse_list_of_component_values(),
+            LengthPercentage::parse_non_negative,
+        );
+        assert_eq!(padding, Ok(LengthPercentage::Length(Length::ZERO)));
+        let width = parse_value_with(
+            &Parser::new("calc(100% - 2000px)").parse_list_of_component_values(),
+            LengthPercentage::parse_non_negative,
+        )
+        .unwrap();
+        assert_eq!(width.to_px(&context, 500.0), 0.0);
+    }
+    #[test]
+    fn test_calc_errors() {
+        assert_eq!(
+            parse::<LengthPercentage>("calc(1px + 2)"),
+            Err("can not add a length and a number".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("calc(1px +2px)"),
+            Err("expected the end of the value, found `+2px`".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("calc(1px+ 2px)"),
+            Err("`+` needs whitespace on both sides in math functions".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("calc(1px * 2px)"),
+            Err(
+                "expected a length or a percentage, found `calc()` which is a product of units"
+                    .into()
+            )
+        );
+        assert_eq!(
+            parse::<LineWidth>("calc(5%)"),
+            Err("expected a length, found `calc()` which is a percentage".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("clamp(1px, 2px)"),
+            Err("`clamp()` takes 3 arguments, found 2".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("round(1px)"),
+            Err("`round()` needs a step to round anything but a number to".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("calc(1pt)"),
+            Err("`pt` is not a unit math functions support".into())
+        );
+        assert_eq!(
+            parse::<Number>("pow(2px, 2)"),
+            Err("the arguments of `pow()` have different types".into())
+        );
+        assert_eq!(
+            parse::<Number>("calc(foo)"),
+            Err(
+                "expected a number, a dimension, a percentage or a math function, found `foo`"
+                    .into()
+            )
+        );
+    }
+}
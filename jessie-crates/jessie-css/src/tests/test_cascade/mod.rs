@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::parser::Parser;
+    use crate::selectors::PseudoElement;
+    use crate::serializer::serialize_component_values;
+    use crate::style_attribute::parse_declaration_list;
+    use crate::tests::tree::Tree;
+
+    fn cascade<'a>(stylesheets: &[(&'a str, Origin)]) -> Cascade<'a> {
+        let mut cascade = Cascade::new();
+        for (source, origin) in stylesheets {
+            cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), *origin);
+        }
+        cascade
+    }
+    fn value(style: &ComputedStyle, name: &str) -> String {
+        serialize_component_values(style.get(name).unwrap())
+    }
+    #[test]
+    fn test_cascade_order() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "app");
+        let button = tree.add(Some(root), "button#save.primary");
+        let cascade = cascade(&[
+            (
+                "button { color: gray; display: inline-block; width: 10px !important }",
+                Origin::UserAgent,
+            ),
+            (
+                "button { width: 20px !important; height: 5px }",
+                Origin::User,
+            ),
+            (
+                "#save { color: red }
+                 button.primary { color: blue; height: 1px }
+                 .primary { color: green; WIDTH: 30px }",
+                Origin::Author,
+            ),
+        ]);
+        assert!(cascade.diagnostics().is_empty());
+        let element = tree.element(button);
+        let style = cascade.compute(&element, None, None, None);
+        // The id selector wins over the later, less specific ones.
+        assert_eq!(value(&style, "color"), "red");
+        assert_eq!(value(&style, "display"), "inline-block");
+        // Author normal declarations win over user ones, user agent `!important` over everything.
+        assert_eq!(value(&style, "height"), "1px");
+        assert_eq!(value(&style, "width"), "10px");
+        assert_eq!(value(&style, "position"), "static");
+
+        // Inline styles win over any selector of the same origin, but not over `!important`.
+        let declarations =
+            parse_declaration_list("color: purple; height: 2px; margin-top: 3px; bogus: 1");
+        let style = cascade.compute(&element, Some(&declarations), None, None);
+        assert_eq!(value(&style, "color"), "purple");
+        assert_eq!(value(&style, "height"), "2px");
+        assert_eq!(value(&style, "margin-top"), "3px");
+        assert_eq!(style.get("bogus"), None);
+    }
+    #[test]
+    fn test_cascade_layers() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "p");
+        let cascade = cascade(&[(
+            "@layer reset, theme;
+             p { color: red; width: 1px !important }
+             @layer theme {
+                 p { color: green; width: 2px !important; height: 2px }
+                 @layer dark { p { height: 3px; z-index: 3 } }
+             }
+             @layer reset { p { color: blue; width: 3px !important; z-index: 1 !important } }
+             @layer theme.dark { p { z-index: 4 !important } }
+             @layer { p { opacity: 0.5 } }
+             @layer { p { opacity: 0.25 } }",
+            Origin::Author,
+        )]);
+        assert!(cascade.diagnostics().is_empty());
+        let style = cascade.compute(&tree.element(root), None, None, None);
+        // Unlayered declarations win over layered ones.
+        assert_eq!(value(&style, "color"), "red");
+        // `!important` reverses the layer order, so the first layer wins.
+        assert_eq!(value(&style, "width"), "3px");
+        assert_eq!(value(&style, "z-index"), "1");
+        // A layer's own declarations win over the ones of its sublayers.
+        assert_eq!(value(&style, "height"), "2px");
+        // Every anonymous layer is a new layer.
+        assert_eq!(value(&style, "opacity"), "0.25");
+    }
+    #[test]
+    fn test_defaulting_keywords() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "app");
+        let child = tree.add(Some(root), "div.box");
+        let cascade = cascade(&[
+            ("div { display: block; margin-top: 1px }", Origin::UserAgent),
+            (
+                "app { color: red; margin-top: 4px; font-size: 20px; --accent: teal; --gap: 1px }
+                 @layer base { div { width: 5px } }
+                 div { border-top-width: inherit; font-size: initial; color: unset }
+                 .box { display: revert; margin-top: inherit; width: revert-layer; --gap: initial }",
+                Origin::Author,
+            ),
+        ]);
+        let parent = cascade.compute(&tree.element(root), None, None, None);
+        assert_eq!(value(&parent, "color"), "red");
+        assert_eq!(value(&parent, "margin-top"), "4px");
+        let style = cascade.compute(&tree.element(child), None, Some(&parent), None);
+        // Inherited properties take the value of the parent, others the initial value.
+        assert_eq!(value(&style, "color"), "red");
+        assert_eq!(value(&style, "border-top-width"), "medium");
+        assert_eq!(value(&style, "font-size"), "medium");
+        assert_eq!(value(&style, "margin-top"), "4px");
+        assert_eq!(value(&style, "background-color"), "transparent");
+        // `revert` rolls back to the user agent origin, `revert-layer` to the layers below.
+        assert_eq!(value(&style, "display"), "block");
+        assert_eq!(value(&style, "width"), "5px");
+        // Custom properties are inherited, `initial` removes them.
+        assert_eq!(value(&style, "--accent"), "teal");
+        assert_eq!(style.get("--gap"), None);
+        assert_eq!(value(&ComputedStyle::initial(), "display"), "inline");
+    }
+    #[test]
+    fn test_pseudo_elements() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "p");
+        let cascade = cascade(&[(
+            "p { color: red } p::before { color: blue; display: block }",
+            Origin::Author,
+        )]);
+        let element = tree.element(root);
+        let style = cascade.compute(&element, None, None, None);
+        assert_eq!(value(&style, "display"), "inline");
+        let before = cascade.compute_pseudo_element(&element, PseudoElement::Before, &style, None);
+        assert_eq!(value(&before, "color"), "blue");
+        assert_eq!(value(&before, "display"), "block");
+        let after = cascade.compute_pseudo_element(&element, PseudoElement::After, &style, None);
+        assert_eq!(value(&after, "color"), "red");
+    }
+    #[test]
+    fn test_cascade_diagnostics() {
+        let cascade = cascade(&[(
+            "p { colour: red; color green } p:bogus { color: red } @layer a b; @layer a, b { }",
+            Origin::Author,
+        )]);
+        let kinds: Vec<CssDiagnosticKind> = cascade.diagnostics().iter().map(|v| v.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                CssDiagnosticKind::InvalidDeclaration,
+                CssDiagnosticKind::UnknownProperty,
+                CssDiagnosticKind::InvalidSelector,
+                CssDiagnosticKind::InvalidAtRule,
+                CssDiagnosticKind::InvalidAtRule,
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::values::color::spaces::ColorSpace;
+    use crate::values::color::{AbsoluteColor, Color, HueInterpolation, interpolate};
+    use crate::values::parse_value;
+
+    fn parse(input: &str) -> Result<Color, String> {
+        let values = Parser::new(input).parse_list_of_component_values();
+        parse_value::<Color>(&values).map_err(|v| v.message)
+    }
+    fn absolute(input: &str) -> AbsoluteColor {
+        match parse(input) {
+            Ok(Color::Absolute(color)) => color,
+            other => panic!("{input} parsed to {other:?}"),
+        }
+    }
+    fn rgba8(input: &str) -> (u8, u8, u8, u8) {
+        absolute(input).to_rgba().to_rgba8()
+    }
+    fn assert_close(actual: [f32; 3], expected: [f32; 3], tolerance: f32) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= tolerance, "{actual:?} is not {expected:?}");
+        }
+    }
+    #[test]
+    fn test_color_functions() {
+        assert_eq!(rgba8("hsl(120 100% 50%)"), (0, 255, 0, 255));
+        assert_eq!(rgba8("hsla(240, 100%, 50%, 0.5)"), (0, 0, 255, 128));
+        assert_eq!(rgba8("hsl(0.5turn 100 25)"), (0, 128, 128, 255));
+        assert_eq!(rgba8("hwb(0 0% 0%)"), (255, 0, 0, 255));
+        assert_eq!(rgba8("hwb(none 50% 50%)"), (128, 128, 128, 255));
+        assert_eq!(rgba8("lab(100 0 0)"), (255, 255, 255, 255));
+        assert_eq!(rgba8("lch(54.29 106.84 40.85)"), (255, 0, 0, 255));
+        assert_eq!(rgba8("oklab(0% 0 0 / 50%)"), (0, 0, 0, 128));
+        assert_eq!(rgba8("oklch(62.8% 0.2577 29.23)"), (255, 0, 0, 255));
+        assert_eq!(rgba8("color(srgb 1 0.5 0)"), (255, 128, 0, 255));
+        assert_eq!(rgba8("color(srgb-linear 100% 0% 0%)"), (255, 0, 0, 255));
+        assert_eq!(rgba8("color(xyz 0.9505 1 1.089)"), (255, 255, 255, 255));
+        assert_eq!(
+            absolute("lab(50% 100% -100%)").components,
+            [50.0, 125.0, -125.0]
+        );
+        assert_eq!(absolute("oklch(1.5 -1 400)").components, [1.0, 0.0, 400.0]);
+        assert_eq!(
+            parse("color(hsl 1 2 3)"),
+            Err("`hsl` is not a color space `color()` accepts, like `display-p3` or `xyz`".into())
+        );
+        assert_eq!(
+            parse("hwb(0, 0%, 0%)"),
+            Err("expected a number or a percentage, found `,`".into())
+        );
+        assert_eq!(
+            parse("hsl(1px 0% 0%)"),
+            Err("expected a hue like `120deg`, found `1px`".into())
+        );
+    }
+    #[test]
+    fn test_color_conversions() {
+        let red = AbsoluteColor::from_rgba8(255, 0, 0, 255);
+        assert_close(
+            red.to_space(ColorSpace::Lab).components,
+            [54.29, 80.80, 69.89],
+            0.05,
+        );
+        assert_close(
+            red.to_space(ColorSpace::Oklch).components,
+            [0.628, 0.2577, 29.23],
+            0.01,
+        );
+        assert_close(
+            red.to_space(ColorSpace::DisplayP3).components,
+            [0.9175, 0.2003, 0.1387],
+            0.001,
+        );
+        let white = AbsoluteColor::from_rgba8(255, 255, 255, 255);
+        assert_close(
+            white.to_space(ColorSpace::Oklab).components,
+            [1.0, 0.0, 0.0],
+            0.001,
+        );
+        assert_close(
+            white.to_space(ColorSpace::XyzD50).components,
+            [0.9642, 1.0, 0.8252],
+            0.001,
+        );
+        // Every space converts back to where it came from.
+        let color = AbsoluteColor::from_rgba8(51, 153, 204, 255);
+        for space in [
+            ColorSpace::SrgbLinear,
+            ColorSpace::DisplayP3,
+            ColorSpace::A98Rgb,
+            ColorSpace::ProphotoRgb,
+            ColorSpace::Rec2020,
+            ColorSpace::XyzD50,
+            ColorSpace::Lab,
+            ColorSpace::Lch,
+            ColorSpace::Oklab,
+            ColorSpace::Oklch,
+            ColorSpace::Hsl,
+            ColorSpace::Hwb,
+        ] {
+            let back = color.to_space(space).to_space(ColorSpace::Srgb);
+            assert_close(back.components, color.components, 0.001);
+        }
+    }
+    #[test]
+    fn test_gamut_mapping() {
+        // Too much chroma for sRGB keeps its lightness and hue and loses chroma instead.
+        let mapped = absolute("oklch(70% 0.4 150)").to_rgba();
+        for channel in [mapped.red, mapped.green, mapped.blue] {
+            assert!((0.0..=1.0).contains(&channel), "{mapped:?}");
+        }
+        let oklch = AbsoluteColor::new(
+            ColorSpace::Srgb,
+            [mapped.red, mapped.green, mapped.blue],
+            1.0,
+        )
+        .to_space(ColorSpace::Oklch)
+        .components;
+        assert!(
+            (oklch[0] - 0.7).abs() < 0.02 && (oklch[2] - 150.0).abs() < 5.0,
+            "{oklch:?}"
+        );
+        assert!(oklch[1] < 0.4);
+        // Lightness out of range maps to white and black.
+        assert_eq!(rgba8("oklch(120% 0.1 0)"), (255, 255, 255, 255));
+        assert_eq!(rgba8("color(display-p3 0 0 0)"), (0, 0, 0, 255));
+
+        let linear = absolute("rgb(255 128 0 / 50%)").to_linear_premultiplied();
+        assert_close(
+            [linear.red, linear.green, linear.blue],
+            [0.5, 0.1078, 0.0],
+            0.001,
+        );
+        assert_eq!(linear.alpha, 0.5);
+    }
+    #[test]
+    fn test_color_mix() {
+        assert_eq!(rgba8("color-mix(in srgb, red, blue)"), (128, 0, 128, 255));
+        assert_eq!(
+            rgba8("color-mix(in srgb, red 75%, blue)"),
+            (191, 0, 64, 255)
+        );
+        assert_eq!(
+            rgba8("color-mix(in srgb, 25% red, blue 25%)"),
+            (128, 0, 128, 128)
+        );
+        assert_eq!(
+            rgba8("color-mix(in srgb, red, transparent)"),
+            (255, 0, 0, 128)
+        );
+        assert_eq!(
+            rgba8("color-mix(in srgb-linear, black, white)"),
+            (188, 188, 188, 255)
+        );
+        // Grays have no hue, so the mix keeps the hue of the other color.
+        let mix = absolute("color-mix(in oklch, white, oklch(50% 0.1 200))");
+        assert_close(mix.components, [0.75, 0.05, 200.0], 0.001);
+        let hue = |method: &str| {
+            absolute(&format!(
+                "color-mix(in hsl {method} hue, hsl(30 50% 50%), hsl(270 50% 50%))"
+            ))
+            .components[0]
+        };
+        assert!((hue("shorter") - 330.0).abs() < 0.01);
+        assert!((hue("longer") - 150.0).abs() < 0.01);
+        assert!((hue("increasing") - 150.0).abs() < 0.01);
+        assert!((hue("decreasing") - 330.0).abs() < 0.01);
+
+        let mix = parse("color-mix(in srgb, currentcolor, white)").unwrap();
+        assert!(matches!(mix, Color::Mix(_)));
+        assert_eq!(
+            mix.resolve(&AbsoluteColor::BLACK).to_rgba().to_rgba8(),
+            (128, 128, 128, 255)
+        );
+        let from = AbsoluteColor::from_rgba8(255, 0, 0, 255);
+        let to = AbsoluteColor::from_rgba8(0, 0, 255, 0);
+        let halfway = interpolate(ColorSpace::Srgb, HueInterpolation::Shorter, &from, &to, 0.5);
+        assert_eq!(halfway.to_rgba().to_rgba8(), (255, 0, 0, 128));
+        assert_eq!(
+            parse("color-mix(in srgb, red 0%, blue 0%)"),
+            Err("the percentages of `color-mix()` can not both be 0%".into())
+        );
+        assert_eq!(
+            parse("color-mix(in srgb, red 150%, blue)"),
+            Err("the percentages of `color-mix()` are between 0% and 100%".into())
+        );
+        assert_eq!(
+            parse("color-mix(in cmyk, red, blue)"),
+            Err("`cmyk` is not a color space, like `srgb` or `oklch`".into())
+        );
+    }
+}
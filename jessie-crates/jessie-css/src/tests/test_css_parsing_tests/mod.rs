@@ -0,0 +1,392 @@
+#[cfg(test)]
+mod tests {
+    //! Runs fixtures in the format of https://github.com/SimonSapin/css-parsing-tests: each file is a JSON array
+    //! alternating an input and what parsing it should give. The files in `fixtures/` are cases written for
+    //! jessie-css under the names of the files of that suite, not the files themselves, so they only show the
+    //! divergences someone thought of testing. Inputs where jessie-css is known to differ from the expected result are
+    //! listed in `KNOWN_DIVERGENCES`, so the tests fail both on a new divergence and once one is fixed.
+    use crate::diagnostics::CssDiagnostic;
+    use crate::font_face::FontFace;
+    use crate::parser::{
+        AtRule, ComponentValue, DeclarationOrAtRule, Parser, QualifiedRule, Rule, SyntaxError,
+    };
+    use crate::selectors::AnB;
+    use crate::span::Span;
+    use crate::tests::json::Json;
+    use crate::values::color::{AbsoluteColor, Color};
+    use crate::values::parse_value;
+    use crate::{CSSToken, HashTokenFlag, NumberType};
+
+    ///The fixture files with the inputs of each that jessie-css parses differently, and why.
+    const KNOWN_DIVERGENCES: &[(&str, &str, &str)] = &[
+        // https://www.w3.org/TR/css-syntax-3/#input-preprocessing is not done.
+        (
+            "component_value_list",
+            "\0",
+            "U+0000 is not replaced with U+FFFD",
+        ),
+        (
+            "component_value_list",
+            "a\r\nb\x0Cc",
+            "U+000D and U+000C are not turned into newlines",
+        ),
+        (
+            "color3",
+            "rgb(255, 0%, 0)",
+            "the legacy `rgb()` syntax accepts numbers and percentages mixed",
+        ),
+        (
+            "color3",
+            "hsl(10, 100, 50)",
+            "the legacy `hsl()` syntax accepts numbers for the saturation and lightness",
+        ),
+    ];
+
+    ///The fixture file of `fixtures/` with a name.
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "component_value_list" => include_str!("fixtures/component_value_list.json"),
+            "one_rule" => include_str!("fixtures/one_rule.json"),
+            "declaration_list" => include_str!("fixtures/declaration_list.json"),
+            "stylesheet" => include_str!("fixtures/stylesheet.json"),
+            "an+b" => include_str!("fixtures/an+b.json"),
+            "color3" => include_str!("fixtures/color3.json"),
+            "color4" => include_str!("fixtures/color4.json"),
+            "urange" => include_str!("fixtures/urange.json"),
+            _ => unreachable!("no fixture named {name}"),
+        }
+    }
+
+    ///Parses every input of a fixture file with `parse`, and checks the results against the expected ones
+    ///and `KNOWN_DIVERGENCES`.
+    fn run(name: &str, parse: impl Fn(&str) -> Json) {
+        run_with(name, Json::clone, parse);
+    }
+
+    ///Like [`run`], comparing with `expected` applied to the expected results.
+    fn run_with(name: &str, expected: impl Fn(&Json) -> Json, parse: impl Fn(&str) -> Json) {
+        let cases = Json::parse(fixture(name)).unwrap_or_else(|v| panic!("{name}.json: {v}"));
+        let cases = cases.as_array().expect("a fixture file is an array");
+        assert!(
+            cases.len().is_multiple_of(2),
+            "{name}.json has an input without a result"
+        );
+        let mut report = vec![];
+        let mut checked = 0;
+        for case in cases.chunks(2) {
+            let input = case[0].as_str().expect("the inputs are strings");
+            let found = parse(input);
+            let expected = expected(&case[1]);
+            let known = KNOWN_DIVERGENCES
+                .iter()
+                .any(|&(file, known, _)| file == name && known == input);
+            match (found == expected, known) {
+                (false, false) => report.push(format!(
+                    "{input:?}\n    expected: {expected}\n    found:    {found}"
+                )),
+                (true, true) => report.push(format!(
+                    "{input:?} is listed in KNOWN_DIVERGENCES but parses as expected"
+                )),
+                _ => {}
+            }
+            checked += 1;
+        }
+        assert!(checked > 0, "{name}.json has no cases");
+        assert!(report.is_empty(), "{name}.json:\n{}", report.join("\n"));
+    }
+
+    ///The representation of a number as it was written, which the fixtures compare along with its value.
+    fn number_repr(text: &str) -> &str {
+        // https://www.w3.org/TR/css-syntax-3/#consume-number
+        let bytes = text.as_bytes();
+        let digits = |mut i: usize| {
+            while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                i += 1;
+            }
+            i
+        };
+        let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+        end = digits(end);
+        if bytes.get(end) == Some(&b'.') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit) {
+            end = digits(end + 1);
+        }
+        if let Some(b'e' | b'E') = bytes.get(end) {
+            let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+            if bytes.get(end + 1 + sign).is_some_and(u8::is_ascii_digit) {
+                end = digits(end + 1 + sign);
+            }
+        }
+        &text[..end]
+    }
+
+    fn number_type(flag: &NumberType) -> Json {
+        Json::string(match flag {
+            NumberType::Integer => "integer",
+            NumberType::Number => "number",
+        })
+    }
+
+    fn component_value(value: &ComponentValue, input: &str) -> Json {
+        let name = |kind: &str, value: &str| Json::array([Json::string(kind), Json::string(value)]);
+        let token = match value {
+            ComponentValue::PreservedToken(token) => token,
+            ComponentValue::Function(function) => {
+                let mut out = vec![
+                    Json::string("function"),
+                    Json::string(function.name.as_ref()),
+                ];
+                out.extend(component_values(&function.value, input));
+                return Json::Array(out);
+            }
+            ComponentValue::SimpleBlock(block) => {
+                let kind = match block.associated_token {
+                    CSSToken::LeftCurlyBracketToken => "{}",
+                    CSSToken::LeftSquareBracketToken => "[]",
+                    _ => "()",
+                };
+                let mut out = vec![Json::string(kind)];
+                out.extend(component_values(&block.value, input));
+                return Json::Array(out);
+            }
+        };
+        let text = token.span.slice(input);
+        match &token.token {
+            CSSToken::WhitespaceToken => Json::string(" "),
+            CSSToken::IdentToken { value } => name("ident", value),
+            CSSToken::AtKeywordToken { value } => name("at-keyword", value),
+            CSSToken::HashToken { flag, value } => Json::array([
+                Json::string("hash"),
+                Json::string(value.as_ref()),
+                Json::string(match flag {
+                    HashTokenFlag::Id => "id",
+                    HashTokenFlag::Unrestricted => "unrestricted",
+                }),
+            ]),
+            CSSToken::StringToken { string } => name("string", string),
+            CSSToken::BadStringToken => name("error", "bad-string"),
+            CSSToken::URLToken { value } => name("url", value),
+            CSSToken::BadURLToken => name("error", "bad-url"),
+            CSSToken::DelimToken { value } => Json::string(value.to_string()),
+            CSSToken::NumberToken { flag, value, .. } => Json::array([
+                Json::string("number"),
+                Json::string(number_repr(text)),
+                Json::Number(*value),
+                number_type(flag),
+            ]),
+            CSSToken::PercentageToken { flag, value, .. } => Json::array([
+                Json::string("percentage"),
+                Json::string(number_repr(text)),
+                Json::Number(*value),
+                number_type(flag),
+            ]),
+            CSSToken::DimensionToken {
+                flag, value, unit, ..
+            } => Json::array([
+                Json::string("dimension"),
+                Json::string(number_repr(text)),
+                Json::Number(*value),
+                number_type(flag),
+                Json::string(unit.as_ref()),
+            ]),
+            CSSToken::UnicodeRangeToken { start, end } => Json::array([
+                Json::string("unicode-range"),
+                Json::Number(*start as f64),
+                Json::Number(*end as f64),
+            ]),
+            CSSToken::CommaToken => Json::string(","),
+            CSSToken::ColonToken => Json::string(":"),
+            CSSToken::SemicolonToken => Json::string(";"),
+            CSSToken::CDOToken => Json::string("<!--"),
+            CSSToken::CDCToken => Json::string("-->"),
+            CSSToken::RightParenthesisToken => name("error", ")"),
+            CSSToken::RightSquareBracketToken => name("error", "]"),
+            CSSToken::RightCurlyBracketToken => name("error", "}"),
+            token => panic!("{token:?} is never a preserved token"),
+        }
+    }
+
+    fn component_values(values: &[ComponentValue], input: &str) -> Vec<Json> {
+        values.iter().map(|v| component_value(v, input)).collect()
+    }
+
+    fn qualified_rule(rule: &QualifiedRule, input: &str) -> Json {
+        Json::array([
+            Json::string("qualified rule"),
+            Json::Array(component_values(&rule.prelude, input)),
+            Json::Array(component_values(&rule.block.value, input)),
+        ])
+    }
+
+    fn at_rule(rule: &AtRule, input: &str) -> Json {
+        Json::array([
+            Json::string("at-rule"),
+            Json::string(rule.name.as_ref()),
+            Json::Array(component_values(&rule.prelude, input)),
+            match &rule.block {
+                Some(block) => Json::Array(component_values(&block.value, input)),
+                None => Json::Null,
+            },
+        ])
+    }
+
+    fn rule(rule: &Rule, input: &str) -> Json {
+        match rule {
+            Rule::QualifiedRule(rule) => qualified_rule(rule, input),
+            Rule::AtRule(rule) => at_rule(rule, input),
+        }
+    }
+
+    fn error(kind: &str) -> Json {
+        Json::array([Json::string("error"), Json::string(kind)])
+    }
+
+    ///jessie-css leaves invalid rules and declarations out of lists and reports them as diagnostics instead, so a list
+    ///ends with a single `["error", "invalid"]` if there is a diagnostic outside of all of its items.
+    fn with_errors(items: Vec<(Span, Json)>, diagnostics: &[CssDiagnostic]) -> Json {
+        let dropped = diagnostics.iter().any(|diagnostic| {
+            let offset = diagnostic.span.start.offset;
+            !items
+                .iter()
+                .any(|(span, _)| (span.start.offset..span.end.offset).contains(&offset))
+        });
+        let mut out: Vec<_> = items.into_iter().map(|(_, item)| item).collect();
+        if dropped {
+            out.push(error("invalid"));
+        }
+        Json::Array(out)
+    }
+
+    ///Moves the `["error", "invalid"]` items of an expected list to its end like [`with_errors`] does, keeping one.
+    fn errors_last(expected: &Json) -> Json {
+        let Some(items) = expected.as_array() else {
+            return expected.clone();
+        };
+        let mut out: Vec<_> = items
+            .iter()
+            .filter(|v| **v != error("invalid"))
+            .cloned()
+            .collect();
+        if out.len() < items.len() {
+            out.push(error("invalid"));
+        }
+        Json::Array(out)
+    }
+
+    #[test]
+    fn test_component_value_list() {
+        run("component_value_list", |input| {
+            Json::Array(component_values(
+                &Parser::new(input).parse_list_of_component_values(),
+                input,
+            ))
+        });
+    }
+    #[test]
+    fn test_one_rule() {
+        run("one_rule", |input| match Parser::new(input).parse_rule() {
+            Ok(parsed) => rule(&parsed, input),
+            Err(SyntaxError::UnexpectedEOF) => error("empty"),
+            Err(SyntaxError::ExpectedEOF) => error("extra-input"),
+            Err(SyntaxError::InvalidRule | SyntaxError::InvalidDeclaration) => error("invalid"),
+        });
+    }
+    #[test]
+    fn test_declaration_list() {
+        run_with("declaration_list", errors_last, |input| {
+            let mut parser = Parser::new(input);
+            let items = parser
+                .parse_list_of_declarations()
+                .iter()
+                .map(|item| match item {
+                    DeclarationOrAtRule::Declaration(declaration) => (
+                        declaration.span,
+                        Json::array([
+                            Json::string("declaration"),
+                            Json::string(declaration.name.as_ref()),
+                            Json::Array(component_values(&declaration.value, input)),
+                            Json::Bool(declaration.important),
+                        ]),
+                    ),
+                    DeclarationOrAtRule::AtRule(rule) => (rule.span, at_rule(rule, input)),
+                })
+                .collect();
+            with_errors(items, parser.diagnostics())
+        });
+    }
+    #[test]
+    fn test_stylesheet() {
+        run_with("stylesheet", errors_last, |input| {
+            let mut parser = Parser::new(input);
+            let items = parser
+                .parse_stylesheet()
+                .rules
+                .iter()
+                .map(|v| (v.span(), rule(v, input)))
+                .collect();
+            with_errors(items, parser.diagnostics())
+        });
+    }
+    #[test]
+    fn test_an_plus_b() {
+        run("an+b", |input| {
+            match AnB::parse(&Parser::new(input).parse_list_of_component_values()) {
+                Some(AnB { a, b }) => Json::array([Json::Number(a.into()), Json::Number(b.into())]),
+                None => Json::Null,
+            }
+        });
+    }
+
+    ///Colors are compared as 8-bit sRGB channels and an alpha between 0 and 1.
+    fn color(input: &str) -> Json {
+        let values = Parser::new(input).parse_list_of_component_values();
+        match parse_value::<Color>(&values) {
+            Ok(Color::CurrentColor) => Json::string("currentColor"),
+            Ok(color) => {
+                let rgba = color.resolve(&AbsoluteColor::BLACK).to_rgba();
+                let (red, green, blue, _) = rgba.to_rgba8();
+                Json::array([
+                    Json::Number(red.into()),
+                    Json::Number(green.into()),
+                    Json::Number(blue.into()),
+                    Json::Number(rgba.alpha.into()),
+                ])
+            }
+            Err(_) => Json::Null,
+        }
+    }
+    #[test]
+    fn test_color3() {
+        run("color3", color);
+    }
+    #[test]
+    fn test_color4() {
+        run("color4", color);
+    }
+    #[test]
+    fn test_urange() {
+        run("urange", |input| {
+            // Unicode ranges are only tokenized in the value of the `unicode-range` descriptor.
+            let source =
+                format!("@font-face {{ font-family: x; src: url(x); unicode-range: {input} }}");
+            let mut parser = Parser::new(&source);
+            let Ok(Rule::AtRule(rule)) = parser.parse_rule() else {
+                panic!("expected `@font-face`");
+            };
+            let mut diagnostics = vec![];
+            match FontFace::parse(&rule, &mut diagnostics) {
+                Ok(face) if diagnostics.is_empty() => Json::Array(
+                    face.unicode_range
+                        .iter()
+                        .map(|v| {
+                            Json::array([
+                                Json::Number((*v.start()).into()),
+                                Json::Number((*v.end()).into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+                _ => Json::Null,
+            }
+        });
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::parser::Parser;
+    use crate::properties::custom::PropertySyntax;
+    use crate::style_attribute::parse_declaration_list;
+    use crate::tests::tree::Tree;
+    use crate::tests::util::{cascade, value};
+
+    #[test]
+    fn test_var_substitution() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "app");
+        let button = tree.add(Some(root), "button");
+        let dark = tree.add(None, "app.dark");
+        let dark_button = tree.add(Some(dark), "button");
+        let cascade = cascade(
+            "app { --accent: #f80; --gap: 4px; --pad: var(--gap) 8px; color: green }
+             app.dark { --accent: white }
+             button {
+                 color: var(--accent);
+                 margin: var(--pad);
+                 width: calc(var(--gap) * 2);
+                 background-color: var(--missing, var(--accent));
+                 height: var(--missing);
+                 border-top-width: var(--accent);
+                 padding: var(--missing)
+             }",
+        );
+        assert!(cascade.diagnostics().is_empty());
+        let root_style = cascade.compute(&tree.element(root), None, None, None);
+        assert_eq!(value(&root_style, "--pad").as_deref(), Some("4px 8px"));
+        let style = cascade.compute(&tree.element(button), None, Some(&root_style), None);
+        assert_eq!(value(&style, "--accent").as_deref(), Some("#f80"));
+        assert_eq!(value(&style, "color").as_deref(), Some("#f80"));
+        assert_eq!(value(&style, "margin-top").as_deref(), Some("4px"));
+        assert_eq!(value(&style, "margin-right").as_deref(), Some("8px"));
+        assert_eq!(value(&style, "margin-left").as_deref(), Some("8px"));
+        assert_eq!(value(&style, "width").as_deref(), Some("calc(4px * 2)"));
+        assert_eq!(value(&style, "background-color").as_deref(), Some("#f80"));
+        // Values that are invalid after substitution behave like `unset`.
+        assert_eq!(value(&style, "height").as_deref(), Some("auto"));
+        assert_eq!(value(&style, "border-top-width").as_deref(), Some("medium"));
+        assert_eq!(value(&style, "padding-top").as_deref(), Some("0"));
+
+        let dark_style = cascade.compute(&tree.element(dark), None, None, None);
+        let style = cascade.compute(&tree.element(dark_button), None, Some(&dark_style), None);
+        assert_eq!(value(&style, "color").as_deref(), Some("white"));
+        assert_eq!(value(&style, "background-color").as_deref(), Some("white"));
+
+        // Inline styles and `!important` take part like any other declaration.
+        let declarations = parse_declaration_list("--gap: 1px");
+        let style = cascade.compute(
+            &tree.element(button),
+            Some(&declarations),
+            Some(&root_style),
+            None,
+        );
+        assert_eq!(value(&style, "width").as_deref(), Some("calc(1px * 2)"));
+        // `--pad` was substituted where it was declared, so it keeps the `--gap` of the parent.
+        assert_eq!(value(&style, "margin-top").as_deref(), Some("4px"));
+    }
+    #[test]
+    fn test_var_cycles_and_defaulting() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "app");
+        let child = tree.add(Some(root), "view");
+        let cascade = cascade(
+            "app { --a: var(--b); --b: var(--a); --c: var(--a, 1px); --d: var(--d, 2px);
+                   --e: var(--f, 3px); --f: var(--e); --kept: 4px; --reset: 5px }
+             view { --kept: inherit; --reset: initial; --g: var(--reset, 6px); --h: var(--c) }",
+        );
+        let root_style = cascade.compute(&tree.element(root), None, None, None);
+        for name in ["--a", "--b", "--d", "--e", "--f"] {
+            assert_eq!(value(&root_style, name), None, "{name} is in a cycle");
+        }
+        assert_eq!(value(&root_style, "--c").as_deref(), Some("1px"));
+        let style = cascade.compute(&tree.element(child), None, Some(&root_style), None);
+        assert_eq!(value(&style, "--kept").as_deref(), Some("4px"));
+        assert_eq!(value(&style, "--reset"), None);
+        assert_eq!(value(&style, "--g").as_deref(), Some("6px"));
+        assert_eq!(value(&style, "--h").as_deref(), Some("1px"));
+    }
+    #[test]
+    fn test_property_registration() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "app");
+        let button = tree.add(Some(root), "button");
+        let other = tree.add(Some(root), "view");
+        let cascade = cascade(
+            r#"@property --size { syntax: "<length>"; inherits: false; initial-value: 10px }
+               @property --tint { syntax: "<color> | none"; inherits: true; initial-value: red }
+               @property --relative { syntax: "<length>"; inherits: false; initial-value: 1em }
+               @property --no-inherits { syntax: "*" }
+               @property accent { syntax: "*"; inherits: true }
+               @property --unsupported { syntax: "<image>"; inherits: true }
+               app { --size: 20px; --tint: blue }
+               button { --size: blue; width: var(--size); color: var(--tint) }"#,
+        );
+        let messages: Vec<_> = cascade
+            .diagnostics()
+            .iter()
+            .map(|v| (v.kind, v.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (
+                    CssDiagnosticKind::InvalidAtRule,
+                    "the `initial-value` of `@property` can not depend on other values, like `em` or `var()` do"
+                ),
+                (
+                    CssDiagnosticKind::InvalidAtRule,
+                    "`@property` needs an `inherits` descriptor of `true` or `false`"
+                ),
+                (
+                    CssDiagnosticKind::InvalidAtRule,
+                    "`@property` needs a custom property name like `--accent`"
+                ),
+                (
+                    CssDiagnosticKind::InvalidAtRule,
+                    "`<image>` is not a syntax `@property` supports"
+                ),
+            ]
+        );
+        assert!(cascade.registration("--size").is_some_and(|v| !v.inherits));
+        assert!(cascade.registration("--relative").is_none());
+
+        let root_style = cascade.compute(&tree.element(root), None, None, None);
+        assert_eq!(value(&root_style, "--size").as_deref(), Some("20px"));
+        // `--size` does not inherit, and its own value does not match its syntax.
+        let style = cascade.compute(&tree.element(button), None, Some(&root_style), None);
+        assert_eq!(value(&style, "--size").as_deref(), Some("10px"));
+        assert_eq!(value(&style, "width").as_deref(), Some("10px"));
+        assert_eq!(value(&style, "color").as_deref(), Some("blue"));
+        let style = cascade.compute(&tree.element(other), None, Some(&root_style), None);
+        assert_eq!(value(&style, "--size").as_deref(), Some("10px"));
+        assert_eq!(value(&style, "--tint").as_deref(), Some("blue"));
+        let style = ComputedStyle::initial();
+        assert_eq!(value(&style, "--size"), None);
+    }
+    #[test]
+    fn test_property_syntax() {
+        let matches = |syntax: &str, input: &str| {
+            let values = Parser::new(input).parse_list_of_component_values();
+            PropertySyntax::parse(syntax).unwrap().matches(&values)
+        };
+        assert!(matches("<length>", "calc(1px + 1em)"));
+        assert!(!matches("<length>", "50%"));
+        assert!(matches("<length-percentage>", "50%"));
+        assert!(matches("<length>+", "1px 2px 3px"));
+        assert!(!matches("<length>+", "1px, 2px"));
+        assert!(matches("<color>#", "red, #fff, rgb(0 0 0)"));
+        assert!(matches("auto | <integer>", "auto"));
+        assert!(matches("auto | <integer>", "3"));
+        assert!(!matches("auto | <integer>", "3.5"));
+        assert!(matches("<angle>", "0.5turn"));
+        assert!(matches("<custom-ident>", "fade-in"));
+        assert!(!matches("<custom-ident>", "inherit"));
+        assert!(matches("*", "anything { goes }"));
+        assert_eq!(PropertySyntax::parse("<foo>"), None);
+        assert_eq!(PropertySyntax::parse("<length> |"), None);
+        assert_eq!(PropertySyntax::parse("initial"), None);
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::*;
+    use crate::parser::*;
+    use crate::*;
+    #[test]
+    fn test_tokenizer_diagnostics() {
+        let input = "'abc\n url(a\"b) \\\n /* abc";
+        let mut tokenizer = Tokenizer::new(input);
+        tokenizer.tokenize();
+        let kinds: Vec<CssDiagnosticKind> =
+            tokenizer.diagnostics().iter().map(|v| v.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CssDiagnosticKind::NewlineInString,
+                CssDiagnosticKind::BadURL,
+                CssDiagnosticKind::InvalidEscape,
+                CssDiagnosticKind::EOFInComment
+            ]
+        );
+        let comment = &tokenizer.diagnostics()[3];
+        assert_eq!(comment.span.start.line, 3);
+        assert_eq!(comment.span.start.column, 2);
+        assert_eq!(comment.span.end.offset, input.len());
+        assert!(tokenizer.is_parse_error());
+        assert_eq!(tokenizer.take_diagnostics().len(), 4);
+        assert!(!tokenizer.is_parse_error());
+    }
+    #[test]
+    fn test_parser_diagnostics() {
+        let input = include_str!("./broken.css");
+        let mut parser = Parser::new(input);
+        let stylesheet = parser.parse_stylesheet();
+        let Rule::QualifiedRule(card) = &stylesheet.rules[0] else {
+            panic!("expected .card to be a qualified rule")
+        };
+        let mut declarations = Parser::from_tokens(
+            card.block
+                .value
+                .iter()
+                .filter_map(|v| match v {
+                    ComponentValue::PreservedToken(token) => Some(token.clone()),
+                    _ => None,
+                })
+                .collect(),
+        );
+        declarations.parse_list_of_declarations();
+        let kinds: Vec<CssDiagnosticKind> = parser
+            .diagnostics()
+            .iter()
+            .chain(declarations.diagnostics())
+            .map(|v| v.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CssDiagnosticKind::NewlineInString,
+                CssDiagnosticKind::BadURL,
+                CssDiagnosticKind::UnexpectedEOF,
+                CssDiagnosticKind::UnexpectedEOF,
+                CssDiagnosticKind::UnexpectedToken,
+                CssDiagnosticKind::InvalidDeclaration,
+            ]
+        );
+        assert_eq!(
+            declarations.diagnostics()[1].to_string(),
+            "5:3: invalid declaration: expected `:` after the property name `color`"
+        );
+    }
+    #[test]
+    fn test_render_diagnostic() {
+        let input = include_str!("./broken.css");
+        let mut parser = Parser::new(input);
+        parser.parse_stylesheet();
+        assert_eq!(
+            parser.diagnostics()[2].render(input, "broken.css"),
+            concat!(
+                "error: unexpected end of input\n",
+                " --> broken.css:7:18\n",
+                "  |\n",
+                "7 | .title { margin: calc(1px + 2px\n",
+                "  |                  ^^^^^ `calc(` is missing its closing `)`\n",
+            )
+        );
+    }
+}
@@ -6,6 +6,14 @@ mod tests {
         assert_eq!(
             Tokenizer::new("1f338").consume_escaped_code_point(),
             '\u{1f338}'
-        )
+        );
+        // At most six hex digits are consumed.
+        let mut tokenizer = Tokenizer::new("1000411");
+        assert_eq!(tokenizer.consume_escaped_code_point(), '\u{100041}');
+        assert_eq!(tokenizer.consume_code_point(), Some('1'));
+        assert_eq!(
+            Tokenizer::new("\0").consume_escaped_code_point(),
+            '\u{fffd}'
+        );
     }
 }
@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::font_face::*;
+    use crate::media::MediaEnvironment;
+    use crate::parser::{ComponentValue, Parser, Rule, SimpleBlock};
+    use crate::serializer::serialize_component_values;
+    use crate::{CSSToken, Tokenizer};
+
+    fn font_face(source: &str) -> (Result<FontFace, CssDiagnosticKind>, Vec<CssDiagnosticKind>) {
+        let mut diagnostics = vec![];
+        let face = match Parser::new(source).parse_rule() {
+            Ok(Rule::AtRule(rule)) => FontFace::parse(&rule, &mut diagnostics).map_err(|v| v.kind),
+            rule => panic!("expected an at-rule, found {rule:?}"),
+        };
+        (face, diagnostics.into_iter().map(|v| v.kind).collect())
+    }
+    #[test]
+    fn test_unicode_range_tokens() {
+        let mut tokenizer = Tokenizer::new("U+0-7F, u+4??, U+1E00-1EFF U+26 u+a?????? U+1-");
+        tokenizer.allow_unicode_ranges();
+        let ranges: Vec<_> = tokenizer
+            .filter_map(|v| match v {
+                CSSToken::UnicodeRangeToken { start, end } => Some((start, end)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (0, 0x7F),
+                (0x400, 0x4FF),
+                (0x1E00, 0x1EFF),
+                (0x26, 0x26),
+                (0xA00000, 0xAFFFFF),
+                (0x1, 0x1),
+            ]
+        );
+        // A `u` that does not start a unicode range is put back wherever the tokenizer is in its input.
+        let mut tokenizer = Tokenizer::new(" /**/url()");
+        tokenizer.allow_unicode_ranges();
+        assert_eq!(
+            tokenizer.tokenize(),
+            [
+                CSSToken::WhitespaceToken,
+                CSSToken::URLToken { value: "".into() },
+                CSSToken::EOFToken
+            ]
+        );
+        // Without unicode ranges `u+a` is a selector.
+        assert_eq!(
+            Tokenizer::new("u+a").collect::<Vec<_>>()[..3],
+            [
+                CSSToken::IdentToken { value: "u".into() },
+                CSSToken::DelimToken { value: '+' },
+                CSSToken::IdentToken { value: "a".into() },
+            ]
+        );
+
+        let stylesheet = Parser::new(
+            "u+a { x: y }
+             @font-face {
+                 unicode-range: U+1E00-1EFF, U+20AC;
+                 font-family: x
+             }",
+        )
+        .parse_stylesheet();
+        let Rule::AtRule(rule) = &stylesheet.rules[1] else {
+            panic!("expected `@font-face`");
+        };
+        let block = rule.block.as_ref().unwrap();
+        assert_eq!(
+            serialize_component_values(&block.value),
+            " unicode-range: U+1E00-1EFF, U+20AC; font-family: x "
+        );
+        // The retokenized values keep pointing at where they are in the input.
+        let range = &block.value[4];
+        assert_eq!(range.span().start.line, 3);
+        assert_eq!(range.span().start.column, 33);
+        assert_eq!(range.span().len(), "U+1E00-1EFF".len());
+
+        // Only `@font-face` blocks are tokenized again, including the ones nested in group rules.
+        let stylesheet = Parser::new(
+            "a { unicode-range: U+1E00 }
+             @media all { b {} @supports (x) { @font-face { unicode-range: U+1E00 } } }
+             @page { unicode-range: U+1E00 }",
+        )
+        .parse_stylesheet();
+        let blocks: Vec<_> = stylesheet
+            .rules
+            .iter()
+            .map(|rule| match rule {
+                Rule::QualifiedRule(rule) => &rule.block,
+                Rule::AtRule(rule) => rule.block.as_ref().unwrap(),
+            })
+            .collect();
+        let ranges = |values: &[ComponentValue]| {
+            values
+                .iter()
+                .filter(|v| matches!(v.token(), Some(CSSToken::UnicodeRangeToken { .. })))
+                .count()
+        };
+        assert_eq!(ranges(&blocks[0].value), 0);
+        assert_eq!(ranges(&blocks[2].value), 0);
+        fn last_block<'v, 'a>(values: &'v [ComponentValue<'a>]) -> Option<&'v SimpleBlock<'a>> {
+            values.iter().rev().find_map(|v| match v {
+                ComponentValue::SimpleBlock(block) => Some(block),
+                _ => None,
+            })
+        }
+        let supports = last_block(&blocks[1].value).unwrap();
+        let font_face = last_block(&supports.value).unwrap();
+        assert_eq!(ranges(&font_face.value), 1);
+
+        // Any `unicode-range` declaration consumed from the input is tokenized again.
+        let declaration = Parser::new("unicode-range: U+1E00-1EFF !important")
+            .parse_declaration()
+            .unwrap();
+        assert!(declaration.important);
+        assert_eq!(
+            declaration.value[0].token(),
+            Some(&CSSToken::UnicodeRangeToken {
+                start: 0x1E00,
+                end: 0x1EFF
+            })
+        );
+        // Without the input, the digits of `U+1E00` are gone, which is an error rather than a different value.
+        let tokens = Tokenizer::new("unicode-range: U+1E00").tokenize_with_spans();
+        let mut parser = Parser::from_tokens(tokens);
+        assert!(parser.parse_declaration().is_err());
+        let diagnostics = parser.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, CssDiagnosticKind::InvalidDeclaration);
+    }
+    #[test]
+    fn test_parse_font_face() {
+        let (face, diagnostics) = font_face(
+            "@font-face {
+                 font-family: Brand Sans;
+                 src: local('Brand Sans Bold'), url(brand.woff2) format('woff2') tech(variations, color-COLRv1),
+                      url('brand.woff') format(woff), url(x.ttf) bogus(), url(a.svg) tech(x) format(svg);
+                 font-weight: 700 300;
+                 font-style: oblique 20deg -10deg;
+                 font-stretch: condensed 125%;
+                 unicode-range: U+0-FF, U+1E00-1EFF;
+                 font-display: swap;
+                 size-adjust: 90%;
+             }",
+        );
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let face = face.unwrap();
+        assert_eq!(face.family, "Brand Sans");
+        assert_eq!(
+            face.sources,
+            [
+                FontSource::Local("Brand Sans Bold".into()),
+                FontSource::Url {
+                    url: "brand.woff2".into(),
+                    format: Some("woff2".into()),
+                    tech: vec!["variations".into(), "color-colrv1".into()],
+                },
+                FontSource::Url {
+                    url: "brand.woff".into(),
+                    format: Some("woff".into()),
+                    tech: vec![],
+                },
+            ]
+        );
+        assert_eq!(face.weight, (300.0, 700.0));
+        assert_eq!(face.style, FontFaceStyle::Oblique(-10.0, 20.0));
+        assert_eq!(face.stretch, (75.0, 125.0));
+        assert_eq!(face.unicode_range, [0..=0xFF, 0x1E00..=0x1EFF]);
+        assert_eq!(face.display, FontDisplay::Swap);
+        assert!(face.covers('é') && face.covers('ẞ') && !face.covers('Ω'));
+
+        // Invalid descriptors are dropped and the defaults stay.
+        let (face, diagnostics) = font_face(
+            "@font-face {
+                 font-family: 'Brand';
+                 src: url(a.woff);
+                 font-weight: bolder;
+                 font-style: sideways;
+                 unicode-range: U+110000;
+                 font-display: later;
+                 color: red;
+                 font-stretch: 50% !important;
+             }",
+        );
+        assert_eq!(
+            diagnostics,
+            [
+                CssDiagnosticKind::InvalidValue,
+                CssDiagnosticKind::InvalidValue,
+                CssDiagnosticKind::InvalidValue,
+                CssDiagnosticKind::InvalidValue,
+                CssDiagnosticKind::UnknownProperty,
+                CssDiagnosticKind::InvalidDeclaration,
+            ]
+        );
+        let face = face.unwrap();
+        assert_eq!(face.weight, (400.0, 400.0));
+        assert_eq!(face.style, FontFaceStyle::Normal);
+        assert_eq!(face.stretch, (100.0, 100.0));
+        assert!(face.unicode_range.is_empty());
+        assert_eq!(face.display, FontDisplay::Auto);
+
+        for source in [
+            "@font-face { src: url(a.woff) }",
+            "@font-face { font-family: serif; src: url(a.woff) }",
+            "@font-face { font-family: a; src: url(a.woff) format(woff) format(ttf) }",
+            "@font-face { font-family: a }",
+            "@font-face a { font-family: a; src: url(a.woff) }",
+            "@font-face;",
+        ] {
+            assert_eq!(
+                font_face(source).0.unwrap_err(),
+                CssDiagnosticKind::InvalidAtRule,
+                "{source}"
+            );
+        }
+    }
+    #[test]
+    fn test_cascade_font_faces() {
+        let source = "@font-face { font-family: a; src: url(a.woff) }
+             @media (width < 600px) { @font-face { font-family: b; src: url(b.woff); unicode-range: U+1E00-1EFF } }
+             @font-face { src: url(c.woff) }
+             .x { @font-face { font-family: d; src: url(d.woff) } }";
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), Origin::Author);
+        assert_eq!(
+            cascade
+                .diagnostics()
+                .iter()
+                .map(|v| v.kind)
+                .collect::<Vec<_>>(),
+            [
+                CssDiagnosticKind::InvalidAtRule,
+                CssDiagnosticKind::InvalidAtRule
+            ]
+        );
+        let families = |cascade: &Cascade| {
+            cascade
+                .font_faces()
+                .map(|v| v.family.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(families(&cascade), ["a"]);
+        cascade.set_environment(MediaEnvironment::new(400.0, 800.0));
+        assert_eq!(families(&cascade), ["a", "b"]);
+        // The blocks nested in other rules are tokenized again too.
+        let b = cascade.font_faces().nth(1).unwrap();
+        assert_eq!(b.unicode_range, [0x1E00..=0x1EFF]);
+    }
+}
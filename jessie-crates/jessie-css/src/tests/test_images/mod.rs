@@ -0,0 +1,264 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::properties;
+    use crate::tests::util::{parse, round_trip};
+    use crate::values::CommaSeparated;
+    use crate::values::color::{AbsoluteColor, Color};
+    use crate::values::image::*;
+    use crate::values::length::*;
+
+    fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color::Absolute(AbsoluteColor::from_rgba8(red, green, blue, 255))
+    }
+    #[test]
+    fn test_linear_gradients() {
+        let Ok(Image::Gradient(gradient)) =
+            parse::<Image>("linear-gradient(to top right, red, 30%, blue 50% 70%)")
+        else {
+            panic!("expected a gradient");
+        };
+        assert_eq!(
+            *gradient,
+            Gradient {
+                kind: GradientKind::Linear {
+                    direction: LineDirection::To(
+                        Some(HorizontalSide::Right),
+                        Some(VerticalSide::Top)
+                    ),
+                    items: vec![
+                        GradientItem::ColorStop(rgb(255, 0, 0), None),
+                        GradientItem::Hint(LengthPercentage::Percentage(0.3)),
+                        GradientItem::ColorStop(
+                            rgb(0, 0, 255),
+                            Some(LengthPercentage::Percentage(0.5))
+                        ),
+                        GradientItem::ColorStop(
+                            rgb(0, 0, 255),
+                            Some(LengthPercentage::Percentage(0.7))
+                        ),
+                    ],
+                },
+                repeating: false,
+            }
+        );
+        assert_eq!(
+            round_trip::<Image>("linear-gradient(to bottom, red, blue)"),
+            "linear-gradient(color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+        assert_eq!(
+            round_trip::<Image>(
+                "Repeating-Linear-Gradient(0.5turn, currentcolor 0, transparent 10px)"
+            ),
+            "repeating-linear-gradient(180deg, currentcolor 0px, color(srgb 0 0 0 / 0) 10px)"
+        );
+        assert_eq!(
+            round_trip::<Image>("linear-gradient(0, red, red)"),
+            "linear-gradient(0deg, color(srgb 1 0 0), color(srgb 1 0 0))"
+        );
+        assert_eq!(
+            round_trip::<Image>("linear-gradient(to left top, red, blue)"),
+            "linear-gradient(to left top, color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+
+        let direction = |input: &str| match parse::<Image>(input) {
+            Ok(Image::Gradient(gradient)) => match gradient.kind {
+                GradientKind::Linear { direction, .. } => direction.to_degrees(200.0, 200.0),
+                kind => panic!("expected a linear gradient, found {kind:?}"),
+            },
+            image => panic!("expected a gradient, found {image:?}"),
+        };
+        assert_eq!(direction("linear-gradient(red, blue)"), 180.0);
+        assert_eq!(direction("linear-gradient(to left, red, blue)"), 270.0);
+        assert_eq!(direction("linear-gradient(to top right, red, blue)"), 45.0);
+        assert_eq!(
+            direction("linear-gradient(to bottom left, red, blue)"),
+            225.0
+        );
+        assert_eq!(direction("linear-gradient(-90deg, red, blue)"), -90.0);
+
+        for (input, error) in [
+            (
+                "linear-gradient(red)",
+                "a gradient needs at least two color stops",
+            ),
+            (
+                "linear-gradient(10%, red, blue)",
+                "a color hint has to be between two color stops",
+            ),
+            (
+                "linear-gradient(red, 10%, 20%, blue)",
+                "a color hint has to be between two color stops",
+            ),
+            (
+                "linear-gradient(red, blue, 50%)",
+                "a color hint has to be between two color stops",
+            ),
+            (
+                "linear-gradient(to red, blue)",
+                "expected a side like `right` or a corner like `top right`, found `red`",
+            ),
+            (
+                "linear-gradient(45deg red, blue)",
+                "expected `,`, found `red`",
+            ),
+            (
+                "linear-gradient(red 1% 2% 3%, blue)",
+                "expected `,`, found `3%`",
+            ),
+            (
+                "image(red)",
+                "expected `none`, a `url()` or a gradient, found `image(red)`",
+            ),
+            (
+                "red",
+                "expected `none`, a `url()` or a gradient, found `red`",
+            ),
+        ] {
+            assert_eq!(parse::<Image>(input), Err(error.into()), "{input}");
+        }
+    }
+    #[test]
+    fn test_radial_and_conic_gradients() {
+        assert_eq!(
+            parse::<Gradient>("image(red)"),
+            Err("`image()` is not a gradient function".into())
+        );
+        assert_eq!(
+            round_trip::<Image>("radial-gradient(red, blue)"),
+            "radial-gradient(color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+        assert_eq!(
+            round_trip::<Image>("radial-gradient(circle, red, blue)"),
+            "radial-gradient(circle, color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+        assert_eq!(
+            round_trip::<Image>("radial-gradient(closest-side circle at left 20%, red, blue)"),
+            "radial-gradient(circle closest-side at 0% 20%, color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+        assert_eq!(
+            round_trip::<Image>("repeating-radial-gradient(10px, red, blue 20%)"),
+            "repeating-radial-gradient(10px, color(srgb 1 0 0), color(srgb 0 0 1) 20%)"
+        );
+        assert_eq!(
+            round_trip::<Image>("radial-gradient(ellipse 10px 50% at top, red, blue)"),
+            "radial-gradient(10px 50% at 50% 0%, color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+        assert_eq!(
+            round_trip::<Image>("radial-gradient(farthest-side, red, blue)"),
+            "radial-gradient(farthest-side, color(srgb 1 0 0), color(srgb 0 0 1))"
+        );
+        assert_eq!(
+            round_trip::<Image>("conic-gradient(from 90deg at 25% 75%, red, 0.25turn, blue 50%)"),
+            "conic-gradient(from 90deg at 25% 75%, color(srgb 1 0 0), 90deg, color(srgb 0 0 1) 50%)"
+        );
+        assert_eq!(
+            round_trip::<Image>("repeating-conic-gradient(red 0 10deg, blue 10deg 20deg)"),
+            "repeating-conic-gradient(color(srgb 1 0 0) 0deg, color(srgb 1 0 0) 10deg, color(srgb 0 0 1) 10deg, color(srgb 0 0 1) 20deg)"
+        );
+        let Ok(Image::Gradient(gradient)) =
+            parse::<Image>("radial-gradient(5em circle, red, blue)")
+        else {
+            panic!("expected a gradient");
+        };
+        let GradientKind::Radial {
+            shape, position, ..
+        } = gradient.kind
+        else {
+            panic!("expected a radial gradient");
+        };
+        assert_eq!(
+            shape,
+            EndingShape::Circle(CircleSize::Radius(Length {
+                value: 5.0,
+                unit: LengthUnit::Em
+            }))
+        );
+        assert_eq!(position, BoxPosition::CENTER);
+
+        for (input, error) in [
+            (
+                "radial-gradient(circle 10px 20px, red, blue)",
+                "a circle has a single radius",
+            ),
+            (
+                "radial-gradient(ellipse 10px, red, blue)",
+                "an ellipse has a horizontal and a vertical radius",
+            ),
+            (
+                "radial-gradient(10%, red, blue)",
+                "the radius of a circle can not be a percentage",
+            ),
+            (
+                "radial-gradient(-10px, red, blue)",
+                "negative values are not allowed here",
+            ),
+            (
+                "radial-gradient(at, red, blue)",
+                "`at` is not a named color",
+            ),
+            (
+                "conic-gradient(red 10px, blue)",
+                "expected `,`, found `10px`",
+            ),
+        ] {
+            assert_eq!(parse::<Image>(input), Err(error.into()), "{input}");
+        }
+    }
+    #[test]
+    fn test_color_stop_fixup() {
+        let stops = |input: &str| {
+            let Ok(Image::Gradient(gradient)) = parse::<Image>(input) else {
+                panic!("expected a gradient");
+            };
+            let GradientKind::Linear { items, .. } = gradient.kind else {
+                panic!("expected a linear gradient");
+            };
+            let context = LengthContext::new(800.0, 600.0);
+            resolve_color_stops(&items, |v| v.to_px(&context, 200.0) / 200.0)
+                .into_iter()
+                .map(|v| match v {
+                    GradientItem::ColorStop(_, position) => position.unwrap(),
+                    GradientItem::Hint(position) => -position,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(stops("linear-gradient(red, green, blue)"), [0.0, 0.5, 1.0]);
+        assert_eq!(
+            stops("linear-gradient(red 20%, green, blue, white 80%)"),
+            [0.2, 0.4, 0.6, 0.8]
+        );
+        assert_eq!(
+            stops("linear-gradient(red 50%, green 20px, blue)"),
+            [0.5, 0.5, 1.0]
+        );
+        assert_eq!(
+            stops("linear-gradient(red, 25%, green 40px, blue)"),
+            [0.0, -0.25, 0.25, 1.0]
+        );
+        assert_eq!(
+            stops("linear-gradient(red 10%, green, 80%, blue 50%)"),
+            [0.1, 0.45, -0.8, 0.8]
+        );
+    }
+    #[test]
+    fn test_background_image() {
+        let property = properties::lookup("background-image").unwrap();
+        let validate = |input: &str| {
+            (property.validate)(&Parser::new(input).parse_list_of_component_values())
+                .map_err(|v| v.message)
+        };
+        assert!(validate("none").is_ok());
+        assert!(validate("url(a.png), linear-gradient(red, blue), none").is_ok());
+        assert!(validate("url('a b.png')").is_ok());
+        assert!(validate("red").is_err());
+        assert_eq!(
+            parse::<CommaSeparated<Image>>("url( \"a b.png\" ), url(c.png)"),
+            Ok(CommaSeparated(vec![
+                Image::Url("a b.png".into()),
+                Image::Url("c.png".into())
+            ]))
+        );
+        assert_eq!(round_trip::<Image>("url('a b.png')"), "url(a\\20 b.png)");
+    }
+}
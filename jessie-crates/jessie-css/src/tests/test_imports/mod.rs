@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::import::*;
+    use crate::media::MediaEnvironment;
+    use crate::parser::{Parser, Rule};
+    use crate::serializer::serialize_component_values;
+    use crate::tests::tree::Tree;
+
+    static FILES: &[(&str, &str)] = &[
+        (
+            "main.css",
+            "@charset \"utf-8\";
+             @layer reset, theme;
+             @import url(\"base/reset.css\") layer(reset);
+             @import 'theme.css' layer(theme) (width < 600px);
+             @import url(missing.css);
+             p { color: black }
+             @import 'late.css';",
+        ),
+        (
+            "base/reset.css",
+            "@import '../main.css';
+             @import './fonts.css' layer;
+             p { color: red; width: 1px; height: 1px }",
+        ),
+        ("base/fonts.css", "p { height: 2px }"),
+        ("theme.css", "p { color: blue; width: 3px }"),
+    ];
+
+    fn import_rule(source: &str) -> Result<ImportRule<'_>, CssDiagnosticKind> {
+        match Parser::new(source).parse_rule() {
+            Ok(Rule::AtRule(rule)) => ImportRule::parse(&rule).map_err(|v| v.kind),
+            rule => panic!("expected an at-rule, found {rule:?}"),
+        }
+    }
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(resolve_path("a.css", ""), "a.css");
+        assert_eq!(resolve_path("b.css", "theme/a.css"), "theme/b.css");
+        assert_eq!(resolve_path("../b.css", "theme/dark/a.css"), "theme/b.css");
+        assert_eq!(resolve_path("./x/./b.css", "theme/a.css"), "theme/x/b.css");
+        assert_eq!(resolve_path("/b.css", "theme/a.css"), "b.css");
+        assert_eq!(resolve_path("../../b.css", "a.css"), "b.css");
+    }
+    #[test]
+    fn test_parse_import_rule() {
+        let rule = import_rule("@import url(a.css)").unwrap();
+        assert_eq!(rule.url, "a.css");
+        assert_eq!((rule.layer, rule.supports), (None, None));
+        assert!(rule.media.is_empty());
+
+        let rule = import_rule(
+            "@import url( 'b.css' ) layer(base.reset) supports(display: grid) screen, print",
+        )
+        .unwrap();
+        assert_eq!(rule.url, "b.css");
+        assert_eq!(
+            serialize_component_values(&rule.layer.unwrap()),
+            "base.reset"
+        );
+        assert_eq!(
+            serialize_component_values(&rule.supports.unwrap()),
+            "display: grid"
+        );
+        assert_eq!(serialize_component_values(&rule.media), "screen, print");
+
+        let rule = import_rule("@import \"c.css\" layer (width > 1px)").unwrap();
+        assert_eq!(rule.layer, Some(vec![]));
+        assert_eq!(serialize_component_values(&rule.media), "(width > 1px)");
+
+        for source in [
+            "@import",
+            "@import 12px",
+            "@import url('a.css' 'b.css')",
+            "@import 'a.css' layer()",
+            "@import 'a.css' { }",
+        ] {
+            assert_eq!(
+                import_rule(source).unwrap_err(),
+                CssDiagnosticKind::InvalidAtRule,
+                "{source}"
+            );
+        }
+    }
+    #[test]
+    fn test_load_stylesheet() {
+        let mut loader = EmbeddedLoader::new(FILES);
+        assert!(matches!(
+            load_stylesheet(&mut loader, "nope.css"),
+            Err(LoadError::NotFound)
+        ));
+        let main = load_stylesheet(&mut loader, "./main.css").unwrap();
+        assert_eq!(main.url, "main.css");
+        assert_eq!(
+            main.imports.iter().map(|v| v.index).collect::<Vec<_>>(),
+            [2, 3, 4]
+        );
+        assert_eq!(
+            main.diagnostics.iter().map(|v| v.kind).collect::<Vec<_>>(),
+            [
+                CssDiagnosticKind::ImportFailed,
+                CssDiagnosticKind::InvalidAtRule
+            ]
+        );
+        assert!(main.imports[2].stylesheet.is_none());
+
+        let reset = main.imports[0].stylesheet.as_ref().unwrap();
+        assert_eq!(reset.url, "base/reset.css");
+        assert_eq!(
+            reset.diagnostics.iter().map(|v| v.kind).collect::<Vec<_>>(),
+            [CssDiagnosticKind::ImportCycle]
+        );
+        assert_eq!(
+            reset.diagnostics[0].message,
+            "`main.css` imports itself through main.css -> base/reset.css -> main.css"
+        );
+        assert_eq!(
+            main.stylesheets()
+                .iter()
+                .map(|v| v.url.as_str())
+                .collect::<Vec<_>>(),
+            ["main.css", "base/reset.css", "theme.css", "base/fonts.css"]
+        );
+        let rendered = main.render_diagnostics();
+        assert!(rendered.contains("--> main.css:5:14"), "{rendered}");
+        assert!(rendered.contains("--> base/reset.css:1:1"), "{rendered}");
+    }
+    #[test]
+    fn test_imported_cascade() {
+        let mut tree = Tree::default();
+        let p = tree.add(None, "p");
+        let main = load_stylesheet(&mut EmbeddedLoader::new(FILES), "main.css").unwrap();
+        let mut cascade = Cascade::new();
+        cascade.add_loaded_stylesheet(&main, Origin::Author);
+        assert!(cascade.diagnostics().is_empty());
+        assert!(cascade.loaded_diagnostics().is_empty());
+        let values = |cascade: &Cascade| {
+            let style = cascade.compute(&tree.element(p), None, None, None);
+            ["color", "width", "height"]
+                .map(|name| serialize_component_values(style.get(name).unwrap()))
+        };
+        // The unlayered rules of `main.css` win over the imported ones, and `theme` over `reset`.
+        // The anonymous layer of `fonts.css` comes before the rules of `reset.css` itself.
+        assert_eq!(values(&cascade), ["black", "1px", "1px"]);
+        cascade.set_environment(MediaEnvironment::new(400.0, 800.0));
+        assert_eq!(values(&cascade), ["black", "3px", "1px"]);
+
+        // `add_stylesheet` ignores `@import`.
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(&Parser::new(FILES[0].1).parse_stylesheet(), Origin::Author);
+        assert_eq!(values(&cascade)[1], "auto");
+    }
+    #[test]
+    fn test_imported_cascade_diagnostics() {
+        let files: &[(&str, &str)] = &[
+            (
+                "app.css",
+                "@import 'theme.css' layer(a, b);\n@import 'theme.css';\np { color: 1px }",
+            ),
+            ("theme.css", "p { width: red }"),
+        ];
+        let app = load_stylesheet(&mut EmbeddedLoader::new(files), "app.css").unwrap();
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new("p { bogus: 1 }").parse_stylesheet(),
+            Origin::User,
+        );
+        cascade.add_loaded_stylesheet(&app, Origin::Author);
+        // The errors of the stylesheets that were loaded are kept apart, with the stylesheet their span points into.
+        assert_eq!(
+            cascade
+                .diagnostics()
+                .iter()
+                .map(|v| v.kind)
+                .collect::<Vec<_>>(),
+            [CssDiagnosticKind::UnknownProperty]
+        );
+        let diagnostics = cascade.take_loaded_diagnostics();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|v| (
+                    v.url.as_str(),
+                    v.diagnostic.kind,
+                    v.diagnostic.span.start.line
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("app.css", CssDiagnosticKind::InvalidAtRule, 1),
+                ("theme.css", CssDiagnosticKind::InvalidValue, 1),
+                ("app.css", CssDiagnosticKind::InvalidValue, 3),
+            ]
+        );
+        let theme = app.imports[1].stylesheet.as_ref().unwrap();
+        let rendered = diagnostics[1]
+            .diagnostic
+            .render(&theme.source, &diagnostics[1].url);
+        assert!(rendered.contains("--> theme.css:1:12"), "{rendered}");
+        assert!(cascade.loaded_diagnostics().is_empty());
+    }
+    ///Serves `{n}.css` importing `{n + 1}.css` twice down to `30.css`, along with `b.css` and `d.css` importing each other for `main.css`,
+    ///and counts the loads.
+    #[derive(Default)]
+    struct CountingLoader {
+        loads: Vec<String>,
+    }
+    impl StylesheetLoader for CountingLoader {
+        fn load(&mut self, url: &str) -> Result<std::borrow::Cow<'static, str>, LoadError> {
+            self.loads.push(url.to_string());
+            let source = match url {
+                "main.css" => "@import 'b.css'; @import 'd.css';".to_string(),
+                "b.css" => "@import 'd.css'; b { x: y }".to_string(),
+                "d.css" => "@import 'b.css'; d { x: y }".to_string(),
+                url => match url.strip_suffix(".css").and_then(|v| v.parse::<u32>().ok()) {
+                    Some(30) => "p { x: y }".to_string(),
+                    Some(n) => format!("@import '{0}.css'; @import '{0}.css';", n + 1),
+                    None => return Err(LoadError::NotFound),
+                },
+            };
+            Ok(source.into())
+        }
+    }
+    #[test]
+    fn test_shared_imports() {
+        let mut loader = CountingLoader::default();
+        let root = load_stylesheet(&mut loader, "0.css").unwrap();
+        // Every stylesheet is loaded once however many paths lead to it.
+        assert_eq!(loader.loads.len(), 31);
+        let first = root.imports[0].stylesheet.as_ref().unwrap();
+        let second = root.imports[1].stylesheet.as_ref().unwrap();
+        assert!(std::sync::Arc::ptr_eq(first, second));
+        assert_eq!(root.stylesheets().len(), 31);
+
+        // Which imports form a cycle depends on the path, so the stylesheets in one are loaded again along another path.
+        let mut loader = CountingLoader::default();
+        let main = load_stylesheet(&mut loader, "main.css").unwrap();
+        assert_eq!(loader.loads, ["main.css", "b.css", "d.css", "d.css"]);
+        let b = main.imports[0].stylesheet.as_ref().unwrap();
+        let d = b.imports[0].stylesheet.as_ref().unwrap();
+        assert!(d.imports[0].stylesheet.is_none());
+        assert_eq!(d.diagnostics[0].kind, CssDiagnosticKind::ImportCycle);
+        // `b.css` only cycles back to itself, so it is the same along every path.
+        let d = main.imports[1].stylesheet.as_ref().unwrap();
+        assert!(std::sync::Arc::ptr_eq(
+            d.imports[0].stylesheet.as_ref().unwrap(),
+            b
+        ));
+    }
+    #[test]
+    fn test_file_system_loader() {
+        let root = std::env::temp_dir().join(format!("jessie-css-imports-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("theme")).unwrap();
+        std::fs::write(root.join("app.css"), "@import 'theme/dark.css'; a { x: y }").unwrap();
+        std::fs::write(root.join("theme/dark.css"), "@import '../app.css';").unwrap();
+        let app = load_stylesheet(&mut FileSystemLoader::new(&root), "app.css");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let app = app.unwrap();
+        let dark = app.imports[0].stylesheet.as_ref().unwrap();
+        assert_eq!(dark.url, "theme/dark.css");
+        assert_eq!(dark.diagnostics[0].kind, CssDiagnosticKind::ImportCycle);
+    }
+}
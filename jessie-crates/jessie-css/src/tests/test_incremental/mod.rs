@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use crate::incremental::*;
+    use crate::parser::Parser;
+    use crate::tests::tree::Tree;
+    use proptest::prelude::*;
+
+    const STYLESHEET: &str = "\
+a { color: red }
+.card { margin: 1px; padding: 2px }
+@media (width > 10px) { b { color: blue } }
+#id { width: 3px }
+";
+
+    fn edit(range: std::ops::Range<usize>, replacement: &str) -> TextEdit {
+        TextEdit {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    ///Asserts that the stylesheet is the one parsing all of its source gives, spans and parse errors included.
+    fn assert_reparsed(stylesheet: &IncrementalStylesheet) {
+        let mut parser = Parser::new(stylesheet.source());
+        let expected = parser.parse_stylesheet().into_owned();
+        assert_eq!(
+            stylesheet.stylesheet(),
+            &expected,
+            "{:?}",
+            stylesheet.source()
+        );
+        let mut diagnostics = parser.take_diagnostics();
+        diagnostics.sort_by_key(|v| v.span.start.offset);
+        assert_eq!(
+            stylesheet.diagnostics(),
+            diagnostics,
+            "{:?}",
+            stylesheet.source()
+        );
+    }
+
+    #[test]
+    fn test_edit_one_declaration() {
+        let mut stylesheet = IncrementalStylesheet::new(STYLESHEET);
+        let offset = STYLESHEET.find("1px").unwrap();
+        let change = stylesheet.edit(edit(offset..offset + 3, "10px"));
+        assert_reparsed(&stylesheet);
+        assert_eq!(change.inserted, 1..2);
+        assert_eq!(change.removed.len(), 1);
+        assert_eq!(
+            change.removed[0].span().slice(STYLESHEET),
+            ".card { margin: 1px; padding: 2px }"
+        );
+        assert_eq!(
+            stylesheet.stylesheet().rules[1]
+                .span()
+                .slice(stylesheet.source()),
+            ".card { margin: 10px; padding: 2px }"
+        );
+        // The rules after the edit are moved, including to the next line.
+        let offset = STYLESHEET.find("red").unwrap();
+        stylesheet.edit(edit(offset..offset + 3, "green;\n  background: red"));
+        assert_reparsed(&stylesheet);
+        let rule = &stylesheet.stylesheet().rules[3];
+        assert_eq!(rule.span().slice(stylesheet.source()), "#id { width: 3px }");
+        assert_eq!((rule.span().start.line, rule.span().start.column), (5, 1));
+    }
+
+    #[test]
+    fn test_unchanged_rules_are_not_reported() {
+        let mut stylesheet = IncrementalStylesheet::new(STYLESHEET);
+        let offset = STYLESHEET.find(".card").unwrap();
+        let change = stylesheet.edit(edit(offset..offset, "\n/* cards */\n"));
+        assert_reparsed(&stylesheet);
+        assert_eq!(change.removed, vec![]);
+        assert!(change.inserted.is_empty());
+        // Replacing text with the same text changes nothing either.
+        let offset = stylesheet.source().find("blue").unwrap();
+        let change = stylesheet.edit(edit(offset..offset + 4, "blue"));
+        assert_eq!(change.removed, vec![]);
+        assert!(change.inserted.is_empty());
+    }
+
+    #[test]
+    fn test_edits_that_change_the_rules_after_them() {
+        let mut stylesheet = IncrementalStylesheet::new(STYLESHEET);
+        // An unclosed comment turns the rest of the stylesheet into a comment.
+        let offset = STYLESHEET.find(".card").unwrap();
+        let change = stylesheet.edit(edit(offset..offset, "/*"));
+        assert_reparsed(&stylesheet);
+        assert_eq!(stylesheet.stylesheet().rules.len(), 1);
+        assert_eq!(change.removed.len(), 3);
+        assert_eq!(change.inserted, 1..1);
+        // Closing it again brings them back.
+        let change = stylesheet.edit(edit(offset..offset + 2, ""));
+        assert_reparsed(&stylesheet);
+        assert_eq!(stylesheet.stylesheet().rules.len(), 4);
+        assert_eq!(change.inserted, 1..4);
+        // A `{` left open makes the rules that follow part of its block.
+        let offset = STYLESHEET.find(" }\n.card").unwrap();
+        stylesheet.edit(edit(offset..offset + 2, ""));
+        assert_reparsed(&stylesheet);
+        assert_eq!(stylesheet.stylesheet().rules.len(), 1);
+        // An edit after the last rule can still change it.
+        let mut stylesheet = IncrementalStylesheet::new("@import 'a.css'\n");
+        stylesheet.edit(edit(16..16, "b { color: red }"));
+        assert_reparsed(&stylesheet);
+        assert_eq!(stylesheet.stylesheet().rules.len(), 1);
+        let change = stylesheet.edit(edit(15..15, ";"));
+        assert_reparsed(&stylesheet);
+        assert_eq!(stylesheet.stylesheet().rules.len(), 2);
+        assert_eq!(change.inserted, 0..2);
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "div");
+        let a = tree.add(Some(root), "a");
+        let card = tree.add(Some(root), "div.card");
+        let b = tree.add(Some(card), "b");
+
+        let mut stylesheet = IncrementalStylesheet::new(STYLESHEET);
+        let offset = STYLESHEET.find("blue").unwrap();
+        let change = stylesheet.edit(edit(offset..offset + 4, "green"));
+        let invalidation = change.invalidation(stylesheet.stylesheet());
+        assert!(invalidation.affects(&tree.element(b)));
+        assert!(!invalidation.affects(&tree.element(a)));
+        assert!(!invalidation.affects(&tree.element(card)));
+
+        // A rule that moved to another selector affects the elements of both.
+        let offset = stylesheet.source().find(".card").unwrap();
+        let change = stylesheet.edit(edit(offset..offset + 5, "a"));
+        let invalidation = change.invalidation(stylesheet.stylesheet());
+        assert!(invalidation.affects(&tree.element(a)));
+        assert!(invalidation.affects(&tree.element(card)));
+        assert!(!invalidation.affects(&tree.element(b)));
+
+        // Nested rules are matched with the selectors they desugar to.
+        let source = ".card { & b { color: red } }";
+        let mut stylesheet = IncrementalStylesheet::new(source);
+        let offset = source.find("red").unwrap();
+        let change = stylesheet.edit(edit(offset..offset + 3, "blue"));
+        let invalidation = change.invalidation(stylesheet.stylesheet());
+        assert!(invalidation.affects(&tree.element(b)));
+        assert!(!invalidation.affects(&tree.element(a)));
+
+        let change = stylesheet.edit(edit(0..0, "@keyframes spin { to { rotate: 1turn } }\n"));
+        assert_eq!(
+            change.invalidation(stylesheet.stylesheet()),
+            Invalidation::All
+        );
+    }
+
+    ///Stylesheets made of rules and pieces of rules, so edits break and fix them in many different ways.
+    fn stylesheet() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            Just("a { color: red }"),
+            Just(".b{margin:1px 2px}"),
+            Just("@media (width > 1px) { c { d: e } }"),
+            Just("@import 'x.css';"),
+            Just("@font-face { unicode-range: U+0-7F }"),
+            Just("\n"),
+            Just(" "),
+            Just("/* x */"),
+            Just("<!-- -->"),
+            Just("{"),
+            Just("}"),
+            Just(";"),
+            Just("/*"),
+            Just("'"),
+            Just("url("),
+            Just("é"),
+        ];
+        proptest::collection::vec(piece, 0..12).prop_map(|v| v.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn test_edits_match_a_full_reparse(
+            source in stylesheet(),
+            edits in proptest::collection::vec((any::<prop::sample::Index>(), 0..8usize, stylesheet()), 1..4),
+        ) {
+            let mut stylesheet = IncrementalStylesheet::new(source);
+            for (start, len, replacement) in edits {
+                let source = stylesheet.source();
+                let boundaries: Vec<usize> = (0..=source.len()).filter(|v| source.is_char_boundary(*v)).collect();
+                let start = start.index(boundaries.len());
+                let end = boundaries[(start + len).min(boundaries.len() - 1)];
+                let start = boundaries[start];
+                let before = stylesheet.stylesheet().rules.clone();
+                let change = stylesheet.edit(edit(start..end, &replacement));
+                assert_reparsed(&stylesheet);
+                // The rules that were not reported are the ones that were there before, if only moved.
+                prop_assert_eq!(
+                    stylesheet.stylesheet().rules.len() - change.inserted.len(),
+                    before.len() - change.removed.len()
+                );
+            }
+        }
+    }
+}
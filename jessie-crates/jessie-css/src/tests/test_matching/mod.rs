@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use crate::matching::*;
+    use crate::selectors::*;
+    use crate::tests::tree::Tree;
+    struct Document {
+        tree: Tree,
+        nav: usize,
+        items: [usize; 3],
+        link: usize,
+        main: usize,
+        button: usize,
+        input: usize,
+    }
+    fn document() -> Document {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "app");
+        let body = tree.add(Some(root), "body.dark");
+        let nav = tree.add(Some(body), "nav#menu");
+        let list = tree.add(Some(nav), "ul");
+        let items = [
+            tree.add(Some(list), "li.item"),
+            tree.add(Some(list), "li.item.active"),
+            tree.add(Some(list), "li.item"),
+        ];
+        let link = tree.add(Some(items[0]), "a");
+        tree.nodes[link].attributes = vec![
+            ("href", "https://jessie.rs"),
+            ("lang", "en-US"),
+            ("rel", "noopener external"),
+        ];
+        tree.nodes[link].state = ElementState::LINK | ElementState::HOVER;
+        let main = tree.add(Some(body), "Main");
+        tree.add(Some(main), "p");
+        let button = tree.add(Some(main), "button");
+        tree.nodes[button].state = ElementState::DISABLED;
+        let input = tree.add(Some(main), "input");
+        tree.nodes[input].state = ElementState::FOCUS;
+        Document {
+            tree,
+            nav,
+            items,
+            link,
+            main,
+            button,
+            input,
+        }
+    }
+    fn matches(document: &Document, selector: &str, index: usize) -> bool {
+        let list = parse_selector_list(selector).unwrap();
+        matches_selector_list(&list, &document.tree.element(index), None)
+    }
+    #[test]
+    fn test_match_selectors() {
+        let document = &document();
+        let [first, active, last] = document.items;
+        assert!(matches(document, "li", first));
+        assert!(matches(document, "main", document.main));
+        assert!(matches(document, "#menu", document.nav));
+        assert!(matches(document, ".dark nav > ul li.item", active));
+        assert!(!matches(document, "nav > li", active));
+        assert!(matches(document, "li + .active", active));
+        assert!(!matches(document, "li + .active", last));
+        assert!(matches(document, ".active ~ li", last));
+        assert!(!matches(document, ".active ~ li", first));
+        assert!(matches(
+            document,
+            "body nav ul > li:first-child > a:hover",
+            document.link
+        ));
+        assert!(matches(document, "nav :nth-child(2 of .item)", active));
+        assert!(matches(document, "li:nth-last-child(1)", last));
+        assert!(matches(document, "li:nth-child(odd)", last));
+        assert!(!matches(document, "li:nth-child(odd)", active));
+        assert!(!matches(document, "li:nth-child(-99999999999)", first));
+        assert!(!matches(
+            document,
+            "li:nth-child(-2147483648n+2147483647)",
+            last
+        ));
+        assert!(matches(document, "li:nth-child(99999999999n+1)", first));
+        assert!(matches(document, "li:not(.active, :first-child)", last));
+        assert!(matches(document, ":is(ol, ul) > :where(li)", first));
+        assert!(matches(
+            document,
+            "p:first-of-type:only-of-type:empty",
+            document.button - 1
+        ));
+        assert!(matches(
+            document,
+            "button:disabled:last-of-type",
+            document.button
+        ));
+        assert!(matches(document, "main:focus-within", document.main));
+        assert!(!matches(document, "nav:focus-within", document.nav));
+        assert!(matches(document, "input:focus:enabled", document.input));
+        assert!(matches(
+            document,
+            "a:any-link:link:not(:visited)",
+            document.link
+        ));
+        assert!(matches(document, "app:root", 0));
+        assert!(matches(document, "a::before", document.link));
+    }
+    #[test]
+    fn test_match_attributes() {
+        let document = &document();
+        let link = document.link;
+        assert!(matches(document, "[href]", link));
+        assert!(matches(document, "[href^=https]", link));
+        assert!(matches(document, "[href$='.rs']", link));
+        assert!(matches(document, "[href*=jessie]", link));
+        assert!(!matches(document, "[href*=JESSIE]", link));
+        assert!(matches(document, "[href*=JESSIE i]", link));
+        assert!(matches(document, "[lang|=en]", link));
+        assert!(!matches(document, "[lang|=e]", link));
+        assert!(matches(document, "[rel~=external]", link));
+        assert!(!matches(document, "[rel~=ext]", link));
+        assert!(!matches(document, "[href^='']", link));
+        assert!(!matches(document, "[title]", link));
+    }
+    #[test]
+    fn test_match_has() {
+        let document = &document();
+        assert!(matches(document, "nav:has(a)", document.nav));
+        assert!(matches(document, "nav:has(> ul > li a)", document.nav));
+        assert!(!matches(document, "nav:has(> li)", document.nav));
+        assert!(matches(document, "li:has(+ .active)", document.items[0]));
+        assert!(matches(
+            document,
+            "li:has(~ li .missing, ~ .active)",
+            document.items[0]
+        ));
+        assert!(!matches(document, "li:has(~ .active)", document.items[2]));
+        assert!(matches(document, "nav:has(+ main button)", document.nav));
+    }
+    #[test]
+    fn test_ancestor_filter() {
+        let document = &document();
+        let tree = &document.tree;
+        let link = tree.element(document.link);
+        let mut filter = AncestorFilter::new();
+        let mut ancestors: Vec<_> = std::iter::successors(link.parent(), |v| v.parent()).collect();
+        ancestors.reverse();
+        for ancestor in &ancestors {
+            filter.push(ancestor);
+        }
+        assert_eq!(filter.depth(), 5);
+        let matches = |selector: &str, filter: &AncestorFilter| {
+            let list = parse_selector_list(selector).unwrap();
+            matches_selector_list(&list, &link, Some(filter))
+        };
+        assert!(matches(".dark #menu LI > a", &filter));
+        assert!(matches("nav ul > li:first-child a", &filter));
+        assert!(!matches(".light a", &filter));
+        assert!(!matches("section a", &filter));
+
+        // Popping the ancestors removes them from the filter again.
+        for _ in 0..4 {
+            filter.pop();
+        }
+        assert!(!matches("nav a", &filter));
+        assert!(matches("app a", &filter));
+    }
+}
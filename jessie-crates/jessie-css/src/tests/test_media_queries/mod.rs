@@ -0,0 +1,331 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::media::container::{ContainerQuery, ContainerType, QueryContainer};
+    use crate::media::*;
+    use crate::parser::Parser;
+    use crate::tests::tree::Tree;
+    use crate::tests::util::value;
+    use crate::values::length::{Length, LengthContext};
+
+    fn parse(source: &str) -> (MediaQueryList, Vec<crate::diagnostics::CssDiagnostic>) {
+        let values = Parser::new(source).parse_list_of_component_values();
+        let mut diagnostics = vec![];
+        let list = MediaQueryList::parse(&values, &mut diagnostics);
+        (list, diagnostics)
+    }
+    fn matches(source: &str, environment: &MediaEnvironment) -> bool {
+        let (list, diagnostics) = parse(source);
+        assert!(diagnostics.is_empty(), "{source}: {diagnostics:?}");
+        list.matches(environment)
+    }
+    fn phone() -> MediaEnvironment {
+        MediaEnvironment {
+            resolution: 3.0,
+            pointer: Pointer::Coarse,
+            hover: Hover::None,
+            ..MediaEnvironment::new(390.0, 844.0)
+        }
+    }
+    #[test]
+    fn test_media_query_parsing() {
+        let (list, diagnostics) = parse("screen and (min-width: 600px), not print");
+        assert!(diagnostics.is_empty());
+        assert_eq!(
+            list.queries,
+            vec![
+                MediaQuery {
+                    negated: false,
+                    media_type: MediaType::Screen,
+                    condition: Some(QueryCondition::Feature(FeatureQuery {
+                        name: "width".into(),
+                        comparisons: vec![(
+                            Comparison::GreaterOrEqual,
+                            QueryValue::Length(Length::px(600.0))
+                        )],
+                    })),
+                },
+                MediaQuery {
+                    negated: true,
+                    media_type: MediaType::Print,
+                    condition: None,
+                },
+            ]
+        );
+        let (list, _) = parse("(400px <= width < 700px)");
+        assert_eq!(
+            list.queries[0].condition,
+            Some(QueryCondition::Feature(FeatureQuery {
+                name: "width".into(),
+                comparisons: vec![
+                    (
+                        Comparison::GreaterOrEqual,
+                        QueryValue::Length(Length::px(400.0))
+                    ),
+                    (Comparison::Less, QueryValue::Length(Length::px(700.0))),
+                ],
+            }))
+        );
+        let (list, _) = parse("(aspect-ratio > 16/9) and (resolution >= 2x)");
+        let Some(QueryCondition::And(conditions)) = &list.queries[0].condition else {
+            panic!("expected `and`, found {:?}", list.queries[0].condition);
+        };
+        assert_eq!(conditions.len(), 2);
+        // `<` and `=` with whitespace in between are not `<=`.
+        let (list, diagnostics) = parse("(width < = 600px)");
+        assert_eq!(
+            list.queries[0].condition,
+            Some(QueryCondition::Unknown),
+            "{diagnostics:?}"
+        );
+        // An empty list matches everything.
+        assert!(parse("").0.queries.is_empty());
+    }
+    #[test]
+    fn test_invalid_media_queries() {
+        // Invalid queries are `not all`, without affecting the rest of the list.
+        let (list, diagnostics) = parse("screen and, (width > 10px), and");
+        assert_eq!(list.queries.len(), 3);
+        assert_eq!(list.queries[0], MediaQuery::NEVER);
+        assert_eq!(list.queries[2], MediaQuery::NEVER);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|v| v.kind == CssDiagnosticKind::InvalidMediaQuery)
+        );
+        assert!(list.matches(&MediaEnvironment::default()));
+        for source in [
+            "(width > 10px) and (height > 10px) or (hover)",
+            "screen and (width > 10px) or (hover)",
+            "only",
+            "screen (width > 10px)",
+        ] {
+            let (list, diagnostics) = parse(source);
+            assert_eq!(list.queries, vec![MediaQuery::NEVER], "{source}");
+            assert_eq!(diagnostics.len(), 1, "{source}");
+        }
+        // Unknown features and invalid values in parentheses are neither true nor false.
+        for (source, message) in [
+            (
+                "(widht > 10px)",
+                "unknown feature `widht`, it never matches",
+            ),
+            (
+                "(orientation > portrait)",
+                "`orientation` is not a range feature and can only be compared with `:`",
+            ),
+            (
+                "(400px < width > 700px)",
+                "both comparisons of a range have to point the same way, like `400px <= width < 800px`",
+            ),
+            (
+                "(pointer: precise)",
+                "`pointer` is one of `none`, `coarse`, `fine`, found `precise`",
+            ),
+            (
+                "(min-hover: none)",
+                "`hover` is not a range feature and can only be compared with `:`",
+            ),
+        ] {
+            let (list, diagnostics) = parse(source);
+            assert_eq!(list.queries[0].condition, Some(QueryCondition::Unknown));
+            assert_eq!(diagnostics.len(), 1, "{source}");
+            assert_eq!(diagnostics[0].message, message);
+            assert!(!list.matches(&MediaEnvironment::default()));
+        }
+    }
+    #[test]
+    fn test_media_query_evaluation() {
+        let desktop = MediaEnvironment::new(1280.0, 800.0);
+        let phone = phone();
+        assert!(matches("(400px <= width <= 1280px)", &desktop));
+        assert!(!matches("(400px <= width < 1280px)", &desktop));
+        assert!(matches("(700px >= width)", &phone));
+        assert!(matches(
+            "(max-width: 390px) and (min-height: 844px)",
+            &phone
+        ));
+        assert!(matches("(width: 80em)", &desktop));
+        assert!(matches("(width > calc(50vw + 1px))", &desktop));
+        assert!(matches("(orientation: landscape)", &desktop));
+        assert!(matches("(orientation: portrait)", &phone));
+        assert!(matches("(aspect-ratio: 16/10)", &desktop));
+        assert!(matches("(min-aspect-ratio: 1)", &desktop));
+        assert!(matches("(resolution >= 2dppx)", &phone));
+        assert!(matches("(resolution: 288dpi)", &phone));
+        assert!(!matches("(min-resolution: 2x)", &desktop));
+        // The pointer and hover capabilities tell phones from desktops.
+        assert!(matches("(pointer: fine) and (hover)", &desktop));
+        assert!(matches("(pointer: coarse) and (not (hover))", &phone));
+        assert!(matches("(any-hover: none)", &phone));
+        assert!(matches("(pointer)", &phone));
+        // User preferences.
+        let dark = MediaEnvironment {
+            color_scheme: ColorScheme::Dark,
+            reduced_motion: true,
+            ..desktop
+        };
+        assert!(matches("(prefers-color-scheme: dark)", &dark));
+        assert!(!matches("(prefers-color-scheme: dark)", &desktop));
+        assert!(matches("(prefers-reduced-motion)", &dark));
+        assert!(!matches("(prefers-reduced-motion)", &desktop));
+        assert!(matches("(prefers-reduced-motion: no-preference)", &desktop));
+        // Media types.
+        assert!(matches("screen, print", &desktop));
+        assert!(matches("not print and (hover: none)", &desktop));
+        assert!(!matches("print", &desktop));
+        assert!(!matches("tv", &desktop));
+        assert!(matches("not tv", &desktop));
+        assert!(matches("only screen and (width > 0)", &desktop));
+        // Unknown results are false, even when negated, but `or` can still be true.
+        let (list, _) = parse("not (unknown-feature)");
+        assert!(!list.matches(&desktop));
+        let (list, _) = parse("(unknown-feature) or (width > 0px)");
+        assert!(list.matches(&desktop));
+        let (list, _) = parse("(unknown-feature) and (width <= 0px)");
+        assert!(!list.matches(&desktop));
+        assert_eq!(
+            list.queries[0]
+                .condition
+                .as_ref()
+                .and_then(|v| v.evaluate(&MediaEnvironment::new(0.0, 0.0))),
+            None
+        );
+    }
+    #[test]
+    fn test_media_rules_in_cascade() {
+        let mut tree = Tree::default();
+        let element = tree.add(None, "button");
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new(
+                "button { width: 10px; color: black }
+                 @media (width < 600px) {
+                     button { width: 100% }
+                     @media (prefers-color-scheme: dark) { button { color: white } }
+                 }
+                 @layer base {
+                     @media (pointer: coarse) { button { height: 48px } }
+                 }
+                 @media (widht < 600px) { button { width: 20px } }
+                 @media screen;",
+            )
+            .parse_stylesheet(),
+            Origin::Author,
+        );
+        let diagnostics = cascade.take_diagnostics();
+        assert_eq!(
+            diagnostics.iter().map(|v| v.kind).collect::<Vec<_>>(),
+            vec![
+                CssDiagnosticKind::InvalidMediaQuery,
+                CssDiagnosticKind::InvalidAtRule
+            ]
+        );
+        let style = cascade.compute(&tree.element(element), None, None, None);
+        assert_eq!(value(&style, "width").as_deref(), Some("10px"));
+        assert_eq!(value(&style, "height").as_deref(), Some("auto"));
+        cascade.set_environment(MediaEnvironment {
+            color_scheme: ColorScheme::Dark,
+            ..phone()
+        });
+        let style = cascade.compute(&tree.element(element), None, None, None);
+        assert_eq!(value(&style, "width").as_deref(), Some("100%"));
+        assert_eq!(value(&style, "color").as_deref(), Some("white"));
+        assert_eq!(value(&style, "height").as_deref(), Some("48px"));
+        cascade.set_environment(phone());
+        let style = cascade.compute(&tree.element(element), None, None, None);
+        assert_eq!(value(&style, "color").as_deref(), Some("black"));
+    }
+    #[test]
+    fn test_container_queries() {
+        let container = |container_type, width, height, names: &[&str]| QueryContainer {
+            names: names.iter().map(|v| v.to_string()).collect(),
+            container_type,
+            width,
+            height,
+            context: LengthContext::new(1024.0, 768.0),
+        };
+        let mut tree = Tree::default();
+        let page = tree.add(None, "page");
+        let sidebar = tree.add(Some(page), "sidebar");
+        let card = tree.add(Some(sidebar), "card");
+        let title = tree.add(Some(card), "title");
+        tree.nodes[page].container = Some(container(ContainerType::Size, 1024.0, 768.0, &["page"]));
+        tree.nodes[sidebar].container = Some(container(ContainerType::InlineSize, 300.0, 0.0, &[]));
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new(
+                "@container (width < 400px) { card { width: 1px } }
+                 @container (height > 500px) { card { height: 2px } }
+                 @container page (width > 400px) { title { margin-top: 3px } }
+                 @container sidebar (width > 0) { title { margin-left: 4px } }
+                 @container style(--dark: true) { card { color: red } }
+                 @container (orientation: landscape) { page { color: red } }
+                 @container and (width > 0) { card { color: red } }",
+            )
+            .parse_stylesheet(),
+            Origin::Author,
+        );
+        let diagnostics = cascade.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].kind, CssDiagnosticKind::InvalidMediaQuery);
+        let style = cascade.compute(&tree.element(card), None, None, None);
+        // The width comes from the sidebar, but only the page knows its height.
+        assert_eq!(value(&style, "width").as_deref(), Some("1px"));
+        assert_eq!(value(&style, "height").as_deref(), Some("2px"));
+        assert_eq!(value(&style, "color").as_deref(), Some("black"));
+        let style = cascade.compute(&tree.element(title), None, None, None);
+        assert_eq!(value(&style, "margin-top").as_deref(), Some("3px"));
+        assert_eq!(value(&style, "margin-left").as_deref(), Some("0"));
+        // An element is not its own container.
+        let style = cascade.compute(&tree.element(page), None, None, None);
+        assert_eq!(value(&style, "color").as_deref(), Some("black"));
+
+        let values = Parser::new("card (inline-size >= 20em)").parse_list_of_component_values();
+        let query = ContainerQuery::parse(&values, &mut vec![]).unwrap();
+        assert_eq!(query.name.as_deref(), Some("card"));
+        assert!(
+            query
+                .condition
+                .evaluate(&container(ContainerType::InlineSize, 320.0, 0.0, &[]))
+                == Some(true)
+        );
+    }
+    #[test]
+    fn test_container_properties() {
+        let mut tree = Tree::default();
+        let element = tree.add(None, "sidebar");
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new("sidebar { container: sidebar nav / inline-size } other { container-name: none; container-type: page }")
+                .parse_stylesheet(),
+            Origin::Author,
+        );
+        assert_eq!(cascade.take_diagnostics().len(), 1);
+        let style = cascade.compute(&tree.element(element), None, None, None);
+        assert_eq!(
+            value(&style, "container-name").as_deref(),
+            Some("sidebar nav")
+        );
+        assert_eq!(
+            value(&style, "container-type").as_deref(),
+            Some("inline-size")
+        );
+        let container =
+            QueryContainer::from_style(&style, 200.0, 100.0, LengthContext::new(800.0, 600.0))
+                .unwrap();
+        assert_eq!(container.names, vec!["sidebar", "nav"]);
+        assert_eq!(container.container_type, ContainerType::InlineSize);
+        assert!(
+            QueryContainer::from_style(
+                &ComputedStyle::initial(),
+                1.0,
+                1.0,
+                LengthContext::new(1.0, 1.0)
+            )
+            .is_none()
+        );
+    }
+}
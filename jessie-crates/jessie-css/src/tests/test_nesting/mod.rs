@@ -0,0 +1,276 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+    use crate::media::MediaEnvironment;
+    use crate::nesting::{FlatRule, flatten};
+    use crate::parser::{DeclarationOrRule, Parser, Rule};
+    use crate::selectors::{
+        PseudoClass, SelectorList, SimpleSelector, Specificity, parse_selector_list,
+    };
+    use crate::serializer::serialize_component_values;
+    use crate::tests::tree::Tree;
+
+    type Shape = Vec<String>;
+
+    ///The selectors without their spans, so they can be compared to parsed ones.
+    fn shape(list: &SelectorList) -> Shape {
+        list.selectors
+            .iter()
+            .map(|v| {
+                let mut debug = format!("{:?}", (&v.compounds, &v.combinators));
+                while let Some(start) = debug.find(", span: Span {") {
+                    let mut depth = 0;
+                    let mut end = start;
+                    for (i, c) in debug[start..].char_indices() {
+                        match c {
+                            '{' => depth += 1,
+                            '}' if depth == 1 => {
+                                end = start + i + 1;
+                                break;
+                            }
+                            '}' => depth -= 1,
+                            _ => {}
+                        }
+                    }
+                    debug.replace_range(start..end, "");
+                }
+                debug
+            })
+            .collect()
+    }
+    fn expected(source: &str) -> Shape {
+        shape(&parse_selector_list(source).unwrap())
+    }
+    ///Flattens a stylesheet into its style rules, as their selectors and the names of their declarations.
+    fn style_rules(rules: &[FlatRule]) -> Vec<(Shape, Vec<String>)> {
+        rules
+            .iter()
+            .map(|rule| match rule {
+                FlatRule::Style(rule) => (
+                    shape(&rule.selectors),
+                    rule.declarations
+                        .iter()
+                        .map(|v| format!("{}: {}", v.name, serialize_component_values(&v.value)))
+                        .collect(),
+                ),
+                _ => panic!("expected a style rule, found {rule:?}"),
+            })
+            .collect()
+    }
+    fn flat(source: &str) -> (Vec<FlatRule<'_>>, Vec<CssDiagnostic>) {
+        let mut diagnostics = vec![];
+        let stylesheet = Parser::new(source).parse_stylesheet();
+        let rules = flatten(&stylesheet.rules, &mut diagnostics);
+        (rules, diagnostics)
+    }
+    #[test]
+    fn test_parse_block_contents() {
+        let contents = Parser::new(
+            "color: red; & > .title { color: blue } a:hover{ x: y } --x: { a } b; font: 12px serif
+             .b { } 12px; div span{} width: {}; @media (width > 1px) { }",
+        )
+        .parse_block_contents();
+        let items: Vec<String> = contents
+            .iter()
+            .map(|v| match v {
+                DeclarationOrRule::Declaration(v) => {
+                    format!("{}: {}", v.name, serialize_component_values(&v.value))
+                }
+                DeclarationOrRule::Rule(Rule::QualifiedRule(v)) => {
+                    format!("{}{{}}", serialize_component_values(&v.prelude))
+                }
+                DeclarationOrRule::Rule(Rule::AtRule(v)) => format!("@{}", v.name),
+            })
+            .collect();
+        assert_eq!(
+            items,
+            [
+                "color: red",
+                "& > .title {}",
+                "a:hover{}",
+                "--x: { a } b",
+                // Without a `;` the declaration goes on into the rule after it, which makes it a rule with an invalid selector.
+                "font: 12px serif .b {}",
+                "div span{}",
+                "width: {}",
+                "@media",
+            ]
+        );
+        let mut parser = Parser::new("color: red; 12px; .a { }");
+        assert_eq!(parser.parse_block_contents().len(), 2);
+        assert_eq!(
+            parser
+                .take_diagnostics()
+                .iter()
+                .map(|v| v.kind)
+                .collect::<Vec<_>>(),
+            [CssDiagnosticKind::UnexpectedToken]
+        );
+    }
+    #[test]
+    fn test_flatten() {
+        let (rules, diagnostics) = flat(
+            ".card {
+                 color: red;
+                 & > .title { color: blue }
+                 &:hover { color: green }
+                 .icon { width: 1px }
+                 + .card { margin: 0 }
+                 color: black;
+                 :not(&) { opacity: 0 }
+             }",
+        );
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert_eq!(
+            style_rules(&rules[..6]),
+            [
+                (expected(".card"), vec!["color: red".to_string()]),
+                (expected(".card > .title"), vec!["color: blue".to_string()]),
+                (expected(".card:hover"), vec!["color: green".to_string()]),
+                (expected(".card .icon"), vec!["width: 1px".to_string()]),
+                (expected(".card + .card"), vec!["margin: 0".to_string()]),
+                // Declarations after nested rules come after them.
+                (expected(".card"), vec!["color: black".to_string()]),
+            ]
+        );
+        let FlatRule::Style(rule) = &rules[6] else {
+            unreachable!()
+        };
+        let SimpleSelector::PseudoClass(PseudoClass::Not(not)) =
+            &rule.selectors.selectors[0].compounds[0].selectors[0]
+        else {
+            panic!("expected `:not()`");
+        };
+        assert_eq!(shape(not), expected(".card"));
+
+        // `&` that can not be written out is `:is()` of the parent selectors.
+        let (rules, _) = flat(
+            ".a .b { & .c { x: 1 } .d & { x: 2 } .e { .f & { x: 3 } } }
+             .a, #b { .c { x: 4 } x: 5; &.d { x: 6 } }
+             .a::before, .b { &:hover { x: 7 } }
+             & .x { x: 8 }",
+        );
+        assert_eq!(
+            style_rules(&rules),
+            [
+                (expected(".a .b .c"), vec!["x: 1".to_string()]),
+                (expected(".d :is(.a .b)"), vec!["x: 2".to_string()]),
+                (expected(".f :is(.a .b .e)"), vec!["x: 3".to_string()]),
+                (expected(":is(.a, #b) .c"), vec!["x: 4".to_string()]),
+                (expected(":is(.a, #b)"), vec!["x: 5".to_string()]),
+                (expected(":is(.a, #b).d"), vec!["x: 6".to_string()]),
+                (expected(":is(.b):hover"), vec!["x: 7".to_string()]),
+                (expected("& .x"), vec!["x: 8".to_string()]),
+            ]
+        );
+        let FlatRule::Style(rule) = &rules[3] else {
+            unreachable!()
+        };
+        assert_eq!(
+            rule.selectors.specificity(),
+            Specificity {
+                ids: 1,
+                classes: 1,
+                types: 0
+            }
+        );
+    }
+    #[test]
+    fn test_flatten_at_rules() {
+        let (rules, diagnostics) = flat(
+            ".a {
+                 @media (width < 600px) { color: red; .b & { color: blue } }
+                 @keyframes fade { }
+                 @layer base { color: green }
+                 .c, 12px { color: gray; .d { color: white } }
+             }
+             @media print { .e { .f { color: black } } }",
+        );
+        assert_eq!(
+            diagnostics.iter().map(|v| v.kind).collect::<Vec<_>>(),
+            [
+                CssDiagnosticKind::InvalidAtRule,
+                CssDiagnosticKind::InvalidSelector
+            ]
+        );
+        let groups: Vec<_> = rules
+            .iter()
+            .map(|v| match v {
+                FlatRule::Group(group) => (
+                    group.name.to_string(),
+                    serialize_component_values(&group.prelude),
+                    style_rules(&group.rules),
+                ),
+                _ => panic!("expected a group rule, found {v:?}"),
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (
+                    "media".to_string(),
+                    " (width < 600px) ".to_string(),
+                    vec![
+                        (expected(".a"), vec!["color: red".to_string()]),
+                        (expected(".b .a"), vec!["color: blue".to_string()]),
+                    ]
+                ),
+                (
+                    "layer".to_string(),
+                    " base ".to_string(),
+                    vec![(expected(".a"), vec!["color: green".to_string()])]
+                ),
+                (
+                    "media".to_string(),
+                    " print ".to_string(),
+                    vec![(expected(".e .f"), vec!["color: black".to_string()])]
+                ),
+            ]
+        );
+    }
+    #[test]
+    fn test_nested_cascade() {
+        let mut tree = Tree::default();
+        let card = tree.add(None, "div#main.card");
+        let title = tree.add(Some(card), "h1.title.big");
+        let source = ".title { color: red; width: 1px }
+             #main, .card {
+                 padding: 1px;
+                 .title { color: blue; @media (width < 600px) { width: 2px } }
+                 &:hover > h1 { color: green }
+             }
+             .card .title.big { color: gray; width: 3px }
+             .card { @layer { padding: 2px } }";
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), Origin::Author);
+        assert!(cascade.diagnostics().is_empty());
+        let color = |cascade: &Cascade, tree: &Tree, name: &str| {
+            let card = cascade.compute(&tree.element(card), None, None, None);
+            let style = cascade.compute(&tree.element(title), None, Some(&card), None);
+            (
+                serialize_component_values(card.get("padding-top").unwrap()),
+                serialize_component_values(style.get(name).unwrap()),
+            )
+        };
+        // `:is(#main, .card) .title` is more specific than `.card .title.big`.
+        assert_eq!(
+            color(&cascade, &tree, "color"),
+            ("1px".into(), "blue".into())
+        );
+        assert_eq!(
+            color(&cascade, &tree, "width"),
+            ("1px".into(), "3px".into())
+        );
+        tree.nodes[card].state = crate::matching::ElementState::HOVER;
+        assert_eq!(
+            color(&cascade, &tree, "color"),
+            ("1px".into(), "green".into())
+        );
+        cascade.set_environment(MediaEnvironment::new(400.0, 800.0));
+        assert_eq!(
+            color(&cascade, &tree, "width"),
+            ("1px".into(), "2px".into())
+        );
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::*;
+    use crate::*;
+    fn tokens<'a>(values: &'a [ComponentValue<'a>]) -> Vec<Option<&'a CSSToken<'a>>> {
+        values.iter().map(ComponentValue::token).collect()
+    }
+    #[test]
+    fn test_parse_list_of_declarations() {
+        let input = "padding: 4px ! IMPORTANT; 12: 3; color : red ;margin; @page :first {}";
+        let mut parser = Parser::new(input);
+        let declarations = parser.parse_list_of_declarations();
+        let [
+            DeclarationOrAtRule::Declaration(padding),
+            DeclarationOrAtRule::Declaration(color),
+            DeclarationOrAtRule::AtRule(page),
+        ] = &declarations[..]
+        else {
+            panic!("unexpected declarations: {:?}", declarations)
+        };
+
+        assert_eq!(padding.name, "padding");
+        assert_eq!(
+            tokens(&padding.value),
+            vec![Some(&CSSToken::DimensionToken {
+                flag: NumberType::Integer,
+                value: 4.,
+                signed: false,
+                unit: "px".into()
+            })]
+        );
+        assert!(padding.important);
+        assert_eq!(padding.span.slice(input), "padding: 4px ! IMPORTANT");
+
+        assert_eq!(color.name, "color");
+        assert_eq!(
+            tokens(&color.value),
+            vec![Some(&CSSToken::IdentToken {
+                value: "red".into()
+            })]
+        );
+        assert!(!color.important);
+        assert_eq!(color.span.slice(input), "color : red");
+
+        assert_eq!(page.name, "page");
+        assert_eq!(
+            tokens(&page.prelude),
+            vec![
+                Some(&CSSToken::WhitespaceToken),
+                Some(&CSSToken::ColonToken),
+                Some(&CSSToken::IdentToken {
+                    value: "first".into()
+                }),
+                Some(&CSSToken::WhitespaceToken),
+            ]
+        );
+        assert!(page.block.as_ref().is_some_and(|v| v.value.is_empty()));
+        assert_eq!(page.span.slice(input), "@page :first {}");
+
+        assert!(parser.is_parse_error());
+    }
+    #[test]
+    fn test_parse_declaration() {
+        let declaration = Parser::new("  --gap:  8px  ").parse_declaration().unwrap();
+        assert_eq!(declaration.name, "--gap");
+        assert_eq!(
+            tokens(&declaration.value),
+            vec![Some(&CSSToken::DimensionToken {
+                flag: NumberType::Integer,
+                value: 8.,
+                signed: false,
+                unit: "px".into()
+            })]
+        );
+        assert!(!declaration.important);
+        assert_eq!(
+            Parser::new("color red").parse_declaration(),
+            Err(SyntaxError::InvalidDeclaration)
+        );
+    }
+}
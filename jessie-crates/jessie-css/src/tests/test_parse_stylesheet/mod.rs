@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::*;
+    use crate::*;
+    fn tokens<'a>(values: &'a [ComponentValue<'a>]) -> Vec<Option<&'a CSSToken<'a>>> {
+        values.iter().map(ComponentValue::token).collect()
+    }
+    #[test]
+    fn test_parse_stylesheet() {
+        let input = include_str!("./stylesheet.css");
+        let ident = |value: &'static str| CSSToken::IdentToken {
+            value: value.into(),
+        };
+        let delim = |value: char| CSSToken::DelimToken { value };
+        let stylesheet = Parser::new(input).parse_stylesheet();
+        let [Rule::AtRule(import), Rule::QualifiedRule(card)] = &stylesheet.rules[..] else {
+            panic!("unexpected rules: {:?}", stylesheet.rules)
+        };
+
+        assert_eq!(import.name, "import");
+        assert_eq!(
+            tokens(&import.prelude),
+            vec![
+                Some(&CSSToken::WhitespaceToken),
+                Some(&CSSToken::StringToken {
+                    string: "theme.css".into()
+                })
+            ]
+        );
+        assert_eq!(import.block, None);
+        assert_eq!(import.span.slice(input), "@import \"theme.css\";");
+
+        assert_eq!(
+            tokens(&card.prelude),
+            vec![
+                Some(&delim('.')),
+                Some(&ident("card")),
+                Some(&CSSToken::WhitespaceToken),
+                Some(&delim('>')),
+                Some(&CSSToken::WhitespaceToken),
+                Some(&delim('.')),
+                Some(&ident("title")),
+                Some(&CSSToken::WhitespaceToken),
+            ]
+        );
+        assert_eq!(card.block.associated_token, CSSToken::LeftCurlyBracketToken);
+        assert_eq!(
+            tokens(&card.block.value),
+            vec![
+                Some(&CSSToken::WhitespaceToken),
+                Some(&ident("color")),
+                Some(&CSSToken::ColonToken),
+                Some(&CSSToken::WhitespaceToken),
+                Some(&ident("red")),
+                Some(&CSSToken::SemicolonToken),
+                Some(&CSSToken::WhitespaceToken),
+            ]
+        );
+        assert_eq!(
+            card.span.slice(input),
+            ".card > .title {\n    color: red;\n}"
+        );
+        assert_eq!(card.span.start.line, 2);
+        assert_eq!(card.span.start.column, 1);
+        assert_eq!(card.span.end.line, 4);
+        assert_eq!(card.span.end.column, 2);
+    }
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            Parser::new(" a {} b {} ").parse_rule(),
+            Err(SyntaxError::ExpectedEOF)
+        );
+        assert_eq!(
+            Parser::new("   ").parse_rule(),
+            Err(SyntaxError::UnexpectedEOF)
+        );
+        assert_eq!(
+            Parser::new("a b c").parse_rule(),
+            Err(SyntaxError::InvalidRule)
+        );
+        assert!(matches!(
+            Parser::new(" @media screen { a {} } ").parse_rule(),
+            Ok(Rule::AtRule(AtRule { block: Some(_), .. }))
+        ));
+    }
+    #[test]
+    fn test_parse_unclosed_function() {
+        let mut parser = Parser::new("rgb(0, 0");
+        let Ok(ComponentValue::Function(function)) = parser.parse_component_value() else {
+            panic!("expected a function")
+        };
+        let zero = CSSToken::NumberToken {
+            flag: NumberType::Integer,
+            value: 0.,
+            signed: false,
+        };
+        assert_eq!(function.name, "rgb");
+        assert_eq!(
+            tokens(&function.value),
+            vec![
+                Some(&zero),
+                Some(&CSSToken::CommaToken),
+                Some(&CSSToken::WhitespaceToken),
+                Some(&zero),
+            ]
+        );
+        assert_eq!(function.span.slice("rgb(0, 0"), "rgb(0, 0");
+        assert!(parser.is_parse_error());
+    }
+}
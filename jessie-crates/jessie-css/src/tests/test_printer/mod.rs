@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::parser::Parser;
+    use crate::printer::*;
+    use crate::serializer::serialize_component_values;
+    use crate::tests::tree::Tree;
+    use crate::values::color::Color;
+
+    fn minified(source: &str) -> String {
+        minify(&Parser::new(source).parse_stylesheet())
+    }
+    #[test]
+    fn test_minify() {
+        for (source, expected) in [
+            (
+                "/* a comment */ a , b > c ~ d + e { color : #ff0000 ; margin : 0.50px auto !important ; }",
+                "a,b>c~d+e{color:red;margin:.5px auto!important}",
+            ),
+            // The whitespace of descendant combinators and `An+B` stays.
+            (
+                "li:nth-child( 2n + 1 ) , a :hover { width: calc(1px + 0.5em) }",
+                "li:nth-child(2n + 1),a :hover{width:calc(1px + .5em)}",
+            ),
+            (
+                "@media screen and (min-width : 600px) , print { a { color: rgb(255 255 255) } }",
+                "@media screen and (min-width:600px),print{a{color:#fff}}",
+            ),
+            (
+                "@import 'a.css' supports(display: flex); @import url(b.css) layer(x);",
+                "@import\"a.css\" supports(display:flex);@import url(b.css) layer(x);",
+            ),
+            (
+                "a { color: #FFFFFF80; background-color: hsl(120 100% 50%); border-color: rgb(10 20 30 / 50%) }",
+                "a{color:#FFFFFF80;background-color:#0f0;border-color:rgb(10 20 30/50%)}",
+            ),
+            (
+                "a { --x : { a , b } ; --y: 0.5; font: 12px / 1.5 sans-serif }",
+                "a{--x:{ a , b };--y:.5;font:12px/1.5 sans-serif}",
+            ),
+            (
+                "@font-face { font-family: x; src: url(a.woff) format('woff'); unicode-range: U+0-FF }",
+                "@font-face{font-family:x;src:url(a.woff) format(\"woff\");unicode-range:U+0-FF}",
+            ),
+            // Numbers get the shortest form with the same value, and the same type unless they have a unit.
+            (
+                "a { width: 1e3px; margin: 10.00px +2.0% -0.50em 0.0px; flex-grow: 1.5e10; flex-shrink: 10.0; opacity: 1.0 }",
+                "a{width:1e3px;margin:10px 2% -.5em 0px;flex-grow:1.5e10;flex-shrink:1e1;opacity:1.0}",
+            ),
+            (
+                "a { z-index: +5; line-height: +0.5; width: 0.000001px; --x: 1000000.0 }",
+                "a{z-index:+5;line-height:.5;width:1e-6px;--x:1e6}",
+            ),
+            // The `A` of `An+B` stays an integer and the `B` keeps its sign.
+            (
+                "li:nth-child(+10n +1) { --x: 2.0n 1.0N; color: red }",
+                "li:nth-child(10n +1){--x:2.0n 1.0N;color:red}",
+            ),
+        ] {
+            assert_eq!(minified(source), expected, "{source}");
+            // Minifying again changes nothing.
+            assert_eq!(minified(expected), expected, "{expected}");
+        }
+    }
+    #[test]
+    fn test_minify_rules() {
+        for (source, expected) in [
+            // Adjacent rules with the same prelude are merged, and overridden declarations dropped.
+            (
+                "a { color: red; color: blue } a { margin-top: 1px; margin: 0 } b { color: red }",
+                "a{color:blue;margin:0}b{color:red}",
+            ),
+            (
+                "a { color: red !important; color: blue; width: 1px; width: nope; float: left; float: right }",
+                "a{color:red!important;width:1px;width:nope;float:left;float:right}",
+            ),
+            (
+                "a { margin: 1px; margin-top: 2px; color: red; color: var(--x) }",
+                "a{margin:1px;margin-top:2px;color:var(--x)}",
+            ),
+            (
+                "@media (x) { a { color: red } } @media (x) { a { width: 1px } } b { } c { d { } }",
+                "@media(x){a{color:red;width:1px}}",
+            ),
+            // Anonymous layers are all different.
+            (
+                "@layer { a { color: red } } @layer { a { color: blue } } @layer x { } @layer x { b { color: red } }",
+                "@layer{a{color:red}}@layer{a{color:blue}}@layer x{b{color:red}}",
+            ),
+            (
+                "a { color: red; &:hover { color: blue } width: 1px; color: green }",
+                "a{&:hover{color:blue}width:1px;color:green}",
+            ),
+        ] {
+            assert_eq!(minified(source), expected, "{source}");
+        }
+    }
+    #[test]
+    fn test_minify_cascade() {
+        let source = "@layer base { p { color: red; width: 1px } }
+             p.a, p > p { color: rgb(0 0 255); margin: 1px 2px; margin-left: 0.5px !important }
+             p.a, p > p { margin-left: 3px; height: calc(10px + 0.5em) }
+             @media (width < 600px) { p { width: 2px } }
+             @media (width < 600px) { p { height: 2px } }
+             @supports (display: flex) { p { padding: 0.0px 4px } }";
+        let minified = minified(source);
+        let mut tree = Tree::default();
+        let root = tree.add(None, "p");
+        let p = tree.add(Some(root), "p.a");
+        let values = |source: &str| {
+            let mut cascade = Cascade::new();
+            cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), Origin::Author);
+            let style = cascade.compute(&tree.element(p), None, None, None);
+            let values = [
+                "width",
+                "height",
+                "margin-left",
+                "margin-right",
+                "padding-left",
+            ]
+            .map(|name| serialize_component_values(style.get(name).unwrap()));
+            (style.get_as::<Color>("color"), values)
+        };
+        assert!(minified.len() < source.len() * 2 / 3, "{minified}");
+        assert_eq!(values(&minified), values(source));
+    }
+    #[test]
+    fn test_pretty_print() {
+        let source = "@import 'a.css';@media (x){a,b{color:red;&:hover{color:blue!important}}}@font-face{font-family:x}b{}";
+        assert_eq!(
+            pretty_print(&Parser::new(source).parse_stylesheet(), "  "),
+            "@import \"a.css\";
+
+@media (x) {
+  a, b {
+    color: red;
+    &:hover {
+      color: blue !important;
+    }
+  }
+}
+
+@font-face {
+  font-family: x;
+}
+
+b {}
+"
+        );
+        assert_eq!(
+            pretty_print(&Parser::new("a{b{c:d}}").parse_stylesheet(), "\t"),
+            "a {\n\tb {\n\t\tc: d;\n\t}\n}\n"
+        );
+        assert_eq!(pretty_print(&Parser::new("").parse_stylesheet(), "\t"), "");
+    }
+}
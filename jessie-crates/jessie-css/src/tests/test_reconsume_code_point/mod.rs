@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    #[test]
+    fn test_reconsume_code_point() {
+        // Reconsuming right after the lookahead buffer got refilled used to overflow the stack, so try every alignment.
+        for padding in 0..16 {
+            let input = format!("{}+1 -a .5 -12px \\62 _x", " ".repeat(padding));
+            let mut tokens = Tokenizer::new(&input).tokenize();
+            if padding > 0 {
+                assert_eq!(tokens.remove(0), CSSToken::WhitespaceToken);
+            }
+            assert_eq!(
+                tokens,
+                vec![
+                    CSSToken::NumberToken {
+                        flag: NumberType::Integer,
+                        value: 1.,
+                        signed: true,
+                    },
+                    CSSToken::WhitespaceToken,
+                    CSSToken::IdentToken { value: "-a".into() },
+                    CSSToken::WhitespaceToken,
+                    CSSToken::NumberToken {
+                        flag: NumberType::Number,
+                        value: 0.5,
+                        signed: false,
+                    },
+                    CSSToken::WhitespaceToken,
+                    CSSToken::DimensionToken {
+                        flag: NumberType::Integer,
+                        value: -12.,
+                        signed: true,
+                        unit: "px".into()
+                    },
+                    CSSToken::WhitespaceToken,
+                    CSSToken::IdentToken {
+                        value: "b_x".into()
+                    },
+                    CSSToken::EOFToken
+                ]
+            );
+        }
+    }
+}
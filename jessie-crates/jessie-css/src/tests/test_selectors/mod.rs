@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests {
+    use crate::selectors::*;
+    fn specificity(input: &str) -> (u32, u32, u32) {
+        let list = parse_selector_list(input).unwrap();
+        let v = list.selectors[0].specificity();
+        (v.ids, v.classes, v.types)
+    }
+    fn anb(input: &str) -> Option<(i32, i32)> {
+        match parse_selector_list(&format!(":nth-child({input})")) {
+            Ok(list) => match &list.selectors[0].compounds[0].selectors[0] {
+                SimpleSelector::PseudoClass(PseudoClass::NthChild { anb, .. }) => {
+                    Some((anb.a, anb.b))
+                }
+                v => panic!("unexpected selector {v:?}"),
+            },
+            Err(_) => None,
+        }
+    }
+    #[test]
+    fn test_parse_selectors() {
+        let input = "nav > ul li.item#first + a[href^=\"https\" i]:hover ~ svg|*, *|p::before";
+        let list = parse_selector_list(input).unwrap();
+        let [nav, p] = &list.selectors[..] else {
+            panic!("expected two selectors, got {list:?}")
+        };
+        assert_eq!(
+            nav.combinators,
+            vec![
+                Combinator::Child,
+                Combinator::Descendant,
+                Combinator::NextSibling,
+                Combinator::SubsequentSibling
+            ]
+        );
+        assert_eq!(
+            nav.compounds[2].selectors,
+            vec![
+                SimpleSelector::Type {
+                    namespace: None,
+                    name: "li".into()
+                },
+                SimpleSelector::Class("item".into()),
+                SimpleSelector::Id("first".into()),
+            ]
+        );
+        assert_eq!(
+            nav.compounds[3].selectors,
+            vec![
+                SimpleSelector::Type {
+                    namespace: None,
+                    name: "a".into()
+                },
+                SimpleSelector::Attribute(AttributeSelector {
+                    namespace: None,
+                    name: "href".into(),
+                    matcher: Some(AttributeMatcher {
+                        operator: AttributeOperator::Prefix,
+                        value: "https".into(),
+                        case: AttributeCase::Insensitive
+                    })
+                }),
+                SimpleSelector::PseudoClass(PseudoClass::Hover),
+            ]
+        );
+        assert_eq!(
+            nav.compounds[4].selectors,
+            vec![SimpleSelector::Universal {
+                namespace: Some(NamespacePrefix::Named("svg".into()))
+            }]
+        );
+        assert_eq!(nav.span.slice(input), &input[..input.find(',').unwrap()]);
+        assert_eq!(p.pseudo_element(), Some(PseudoElement::Before));
+        assert_eq!(
+            p.compounds[0].selectors[0],
+            SimpleSelector::Type {
+                namespace: Some(NamespacePrefix::Any),
+                name: "p".into()
+            }
+        );
+    }
+    #[test]
+    fn test_attribute_selectors() {
+        let matcher = |input: &str| {
+            let list = parse_selector_list(input).unwrap();
+            let SimpleSelector::Attribute(attribute) = &list.selectors[0].compounds[0].selectors[0]
+            else {
+                panic!("expected an attribute selector")
+            };
+            attribute
+                .matcher
+                .as_ref()
+                .map(|v| (v.operator, v.value.to_string(), v.case))
+        };
+        assert_eq!(matcher("[disabled]"), None);
+        assert_eq!(
+            matcher("[ lang |= en ]"),
+            Some((
+                AttributeOperator::DashMatch,
+                "en".into(),
+                AttributeCase::Default
+            ))
+        );
+        assert_eq!(
+            matcher("[class~=a s]"),
+            Some((
+                AttributeOperator::Includes,
+                "a".into(),
+                AttributeCase::Sensitive
+            ))
+        );
+        assert_eq!(
+            matcher("[a$='b']"),
+            Some((
+                AttributeOperator::Suffix,
+                "b".into(),
+                AttributeCase::Default
+            ))
+        );
+        assert_eq!(
+            matcher("[a*=b]"),
+            Some((
+                AttributeOperator::Substring,
+                "b".into(),
+                AttributeCase::Default
+            ))
+        );
+        assert_eq!(
+            matcher("[xlink|href=b]"),
+            Some((
+                AttributeOperator::Equals,
+                "b".into(),
+                AttributeCase::Default
+            ))
+        );
+        assert!(parse_selector_list("[a=]").is_err());
+        assert!(parse_selector_list("[a b]").is_err());
+        assert!(parse_selector_list("[*]").is_err());
+    }
+    #[test]
+    fn test_functional_pseudo_classes() {
+        let list =
+            parse_selector_list(":is(.a, ::before, #b):has(> img, + .c):nth-last-child(odd of .d)")
+                .unwrap();
+        let selectors = &list.selectors[0].compounds[0].selectors;
+        let SimpleSelector::PseudoClass(PseudoClass::Is(is)) = &selectors[0] else {
+            panic!("expected :is()")
+        };
+        // :is() is forgiving, so the invalid pseudo-element is dropped.
+        assert_eq!(is.selectors.len(), 2);
+        let SimpleSelector::PseudoClass(PseudoClass::Has(has)) = &selectors[1] else {
+            panic!("expected :has()")
+        };
+        assert_eq!(
+            has.iter().map(|v| v.combinator).collect::<Vec<_>>(),
+            vec![Combinator::Child, Combinator::NextSibling]
+        );
+        let SimpleSelector::PseudoClass(PseudoClass::NthLastChild { anb, of: Some(of) }) =
+            &selectors[2]
+        else {
+            panic!("expected :nth-last-child()")
+        };
+        assert_eq!(*anb, AnB { a: 2, b: 1 });
+        assert_eq!(of.selectors.len(), 1);
+
+        assert!(parse_selector_list(":not(.a, ::before)").is_err());
+        assert!(parse_selector_list(":hover::before:hover").is_ok());
+        assert!(parse_selector_list("::before.a").is_err());
+        assert!(parse_selector_list(":unknown").is_err());
+        assert!(parse_selector_list("a >").is_err());
+        assert!(parse_selector_list("a,").is_err());
+        assert!(parse_selector_list("#1a").is_err());
+    }
+    #[test]
+    fn test_specificity() {
+        assert_eq!(specificity("*"), (0, 0, 0));
+        assert_eq!(specificity("li"), (0, 0, 1));
+        assert_eq!(specificity("ul li"), (0, 0, 2));
+        assert_eq!(specificity("ul ol+li"), (0, 0, 3));
+        assert_eq!(specificity("h1 + *[rel=up]"), (0, 1, 1));
+        assert_eq!(specificity("ul ol li.red"), (0, 1, 3));
+        assert_eq!(specificity("li.red.level"), (0, 2, 1));
+        assert_eq!(specificity("#x34y"), (1, 0, 0));
+        assert_eq!(specificity("#s12:not(FOO)"), (1, 0, 1));
+        assert_eq!(specificity(".foo :is(.bar, #baz)"), (1, 1, 0));
+        assert_eq!(specificity(":where(#a, .b) p"), (0, 0, 1));
+        assert_eq!(specificity("a::before"), (0, 0, 2));
+        assert_eq!(specificity(":nth-child(2n of #a, .b)"), (1, 1, 0));
+        assert_eq!(specificity("div:has(> #a)"), (1, 0, 1));
+        assert!(
+            parse_selector_list("#a").unwrap().selectors[0].specificity()
+                > parse_selector_list(".a.b.c.d.e.f.g.h.i.j.k")
+                    .unwrap()
+                    .selectors[0]
+                    .specificity()
+        );
+    }
+    #[test]
+    fn test_anb() {
+        assert_eq!(anb("odd"), Some((2, 1)));
+        assert_eq!(anb("EVEN"), Some((2, 0)));
+        assert_eq!(anb("5"), Some((0, 5)));
+        assert_eq!(anb("-5"), Some((0, -5)));
+        assert_eq!(anb("n"), Some((1, 0)));
+        assert_eq!(anb("+n"), Some((1, 0)));
+        assert_eq!(anb("-n"), Some((-1, 0)));
+        assert_eq!(anb("3n"), Some((3, 0)));
+        assert_eq!(anb("3n+1"), Some((3, 1)));
+        assert_eq!(anb("3n + 1"), Some((3, 1)));
+        assert_eq!(anb("3n -1"), Some((3, -1)));
+        assert_eq!(anb("3n- 1"), Some((3, -1)));
+        assert_eq!(anb("3n-1"), Some((3, -1)));
+        assert_eq!(anb("-n-12"), Some((-1, -12)));
+        assert_eq!(anb("+n- 2"), Some((1, -2)));
+        assert_eq!(anb(" -2N + 3 "), Some((-2, 3)));
+        assert_eq!(anb("3n 1"), None);
+        assert_eq!(anb("3n + -1"), None);
+        assert_eq!(anb("+ n"), None);
+        assert_eq!(anb("1.5n"), None);
+        assert_eq!(anb("n-1-"), None);
+        assert_eq!(anb("--n"), None);
+
+        let odd = AnB { a: 2, b: 1 };
+        assert!(odd.matches(1) && odd.matches(3) && !odd.matches(2));
+        let first_three = AnB { a: -1, b: 3 };
+        assert!(first_three.matches(3) && first_three.matches(1) && !first_three.matches(4));
+        assert!(AnB { a: 0, b: 2 }.matches(2));
+        // Values that do not fit an `i32` are clamped, which must not overflow when matching.
+        assert_eq!(anb("-99999999999"), Some((0, i32::MIN)));
+        assert!(!AnB { a: 0, b: i32::MIN }.matches(i32::MAX));
+        let extreme = AnB {
+            a: i32::MIN,
+            b: i32::MAX,
+        };
+        assert!(extreme.matches(i32::MAX) && extreme.matches(-1) && !extreme.matches(1));
+        assert!(AnB { a: i32::MAX, b: 1 }.matches(1));
+        assert!(AnB { a: -1, b: i32::MIN }.matches(i32::MIN));
+    }
+}
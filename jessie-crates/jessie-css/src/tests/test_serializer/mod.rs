@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::*;
+    use crate::serializer::*;
+    use crate::*;
+    use proptest::prelude::*;
+    fn round_trip(input: &str) -> String {
+        let tokens = Tokenizer::new(input).tokenize();
+        let serialized = serialize_tokens(&tokens);
+        assert_eq!(
+            Tokenizer::new(&serialized).tokenize(),
+            tokens,
+            "{input:?} was serialized as {serialized:?}"
+        );
+        serialized
+    }
+    #[test]
+    fn test_serialize_tokens() {
+        assert_eq!(
+            round_trip(".card > a:hover { color: #fff; margin: 1px 2.5em -3%; }"),
+            ".card > a:hover { color: #fff; margin: 1px 2.5em -3%; }"
+        );
+        assert_eq!(round_trip("1.0 2 1e3 +4"), "1.0 2 1000.0 +4");
+        assert_eq!(round_trip("a/**/b 1/**/px"), "a/**/b 1/**/px");
+        assert_eq!(round_trip("-/**/-> --/**/>"), "-/**/-> --/**/>");
+        assert_eq!(round_trip("1\\65 3 1e/**/3"), "1\\65 3 1e/**/3");
+        assert_eq!(round_trip("\\31 a \\-"), "\\31 a \\-");
+        assert_eq!(round_trip("\"a\\\"\\\\b\\a \""), "\"a\\\"\\\\b\\a \"");
+        assert_eq!(round_trip("url( a\\)\\ b.png )"), "url(a\\)\\20 b.png)");
+        assert_eq!(round_trip("\"a\n b"), "\"\n b");
+        assert_eq!(round_trip("url(a\"b)"), "url(()");
+        round_trip("<!--/**/--> </**/!-- \\\n @/**/- #/**/a ./**/5 +/**/5 //**/* 1/**/%");
+    }
+    #[test]
+    fn test_serialize_component_values() {
+        let input = "calc(1px + 2px) [a] {b: c";
+        let values = Parser::new(input).parse_list_of_component_values();
+        assert_eq!(
+            serialize_component_values(&values),
+            "calc(1px + 2px) [a] {b: c}"
+        );
+    }
+    #[test]
+    fn test_display_token() {
+        let token = CSSToken::DimensionToken {
+            flag: NumberType::Number,
+            value: 2.,
+            signed: false,
+            unit: "px".into(),
+        };
+        assert_eq!(token.to_string(), "2.0px");
+    }
+    proptest! {
+        #[test]
+        fn test_serialize_round_trip(input in "([a-z0-9eE_\\- \n\t.,:;#@%+*/<>!()\\[\\]{}'\"\\\\]|url\\(|\\\\[0-9a-f]{1,6}|é|𝄞){0,40}") {
+            round_trip(&input);
+        }
+    }
+}
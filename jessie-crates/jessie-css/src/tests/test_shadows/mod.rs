@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Declaration, Parser};
+    use crate::properties;
+    use crate::tests::util::{interpolate, parse, round_trip};
+    use crate::values::color::{AbsoluteColor, Color};
+    use crate::values::filter::*;
+    use crate::values::length::*;
+    use crate::values::shadow::*;
+
+    fn validate(input: &str) -> Result<(), String> {
+        let declaration: Declaration = Parser::new(input).parse_declaration().unwrap();
+        properties::validate_declaration(declaration)
+            .map(drop)
+            .map_err(|v| v.message)
+    }
+    #[test]
+    fn test_shadows() {
+        let em = |value| Length {
+            value,
+            unit: LengthUnit::Em,
+        };
+        assert_eq!(
+            parse::<ShadowList>("inset 1px 2px 3px -4px red, 1em 2em currentcolor"),
+            Ok(ShadowList(vec![
+                Shadow {
+                    color: Color::Absolute(AbsoluteColor::from_rgba8(255, 0, 0, 255)),
+                    offset_x: Length::px(1.0),
+                    offset_y: Length::px(2.0),
+                    blur: Length::px(3.0),
+                    spread: Length::px(-4.0),
+                    inset: true,
+                },
+                Shadow {
+                    color: Color::CurrentColor,
+                    offset_x: em(1.0),
+                    offset_y: em(2.0),
+                    blur: Length::ZERO,
+                    spread: Length::ZERO,
+                    inset: false,
+                },
+            ]))
+        );
+        assert_eq!(parse::<ShadowList>("none"), Ok(ShadowList(vec![])));
+        assert_eq!(round_trip::<ShadowList>("none"), "none");
+        assert_eq!(
+            round_trip::<ShadowList>("0 0 2px"),
+            "currentcolor 0px 0px 2px"
+        );
+        assert_eq!(
+            round_trip::<ShadowList>("1px 1px 0 2px #000 inset, red 0 0"),
+            "color(srgb 0 0 0) 1px 1px 0px 2px inset, color(srgb 1 0 0) 0px 0px"
+        );
+
+        for (input, error) in [
+            ("1px", "the value ended too early"),
+            ("1px 2px -3px", "negative values are not allowed here"),
+            (
+                "red blue 1px 1px",
+                "expected a length like `10px`, found `blue`",
+            ),
+            (
+                "inset inset 1px 1px",
+                "expected a length like `10px`, found `inset`",
+            ),
+            ("none, 1px 1px", "expected the end of the value, found `,`"),
+            ("1px 1px,", "the value ended too early"),
+        ] {
+            assert_eq!(parse::<ShadowList>(input), Err(error.into()), "{input}");
+        }
+
+        assert!(validate("box-shadow: inset 0 0 4px 2px black, 0 1px red").is_ok());
+        assert!(validate("text-shadow: 0 1px 2px black, red 1em 1em").is_ok());
+        assert_eq!(
+            validate("text-shadow: 0 1px 2px 3px black"),
+            Err("invalid `text-shadow`: expected the end of the value, found `3px`".into())
+        );
+        assert_eq!(
+            validate("text-shadow: inset 0 1px"),
+            Err("invalid `text-shadow`: expected a length like `10px`, found `inset`".into())
+        );
+        assert!(properties::lookup("text-shadow").unwrap().inherited);
+    }
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            parse::<FilterList>(
+                "blur(2px) brightness(150%) grayscale(2) hue-rotate(0.5turn) url(#glow) drop-shadow(1px 1px red)"
+            ),
+            Ok(FilterList(vec![
+                FilterFunction::Blur(Length::px(2.0)),
+                FilterFunction::Brightness(1.5),
+                FilterFunction::Grayscale(1.0),
+                FilterFunction::HueRotate(180.0),
+                FilterFunction::Url("#glow".into()),
+                FilterFunction::DropShadow(Shadow {
+                    color: Color::Absolute(AbsoluteColor::from_rgba8(255, 0, 0, 255)),
+                    offset_x: Length::px(1.0),
+                    offset_y: Length::px(1.0),
+                    blur: Length::ZERO,
+                    spread: Length::ZERO,
+                    inset: false,
+                }),
+            ]))
+        );
+        assert!(parse::<FilterList>("url(#glow)").unwrap().has_url());
+        assert_eq!(
+            round_trip::<FilterList>(
+                "blur() brightness() contrast(0) grayscale() hue-rotate() invert(50%) opacity(0.2) saturate(3) sepia()"
+            ),
+            "blur(0px) brightness(1) contrast(0) grayscale(1) hue-rotate(0deg) invert(0.5) opacity(0.2) saturate(3) sepia(1)"
+        );
+        assert_eq!(round_trip::<FilterList>("none"), "none");
+
+        for (input, error) in [
+            ("blur(-1px)", "negative values are not allowed here"),
+            ("saturate(-50%)", "negative values are not allowed here"),
+            (
+                "hue-rotate(90)",
+                "expected an angle like `90deg`, found `90`",
+            ),
+            (
+                "drop-shadow(1px 1px 1px 1px)",
+                "expected the end of the value, found `1px`",
+            ),
+            (
+                "drop-shadow(inset 1px 1px)",
+                "expected a length like `10px`, found `inset`",
+            ),
+            (
+                "blur(1px), invert()",
+                "expected a filter function like `blur(4px)`, found `,`",
+            ),
+            ("shimmer(1)", "`shimmer()` is not a filter function"),
+            (
+                "none blur(1px)",
+                "expected the end of the value, found `blur(1px)`",
+            ),
+        ] {
+            assert_eq!(parse::<FilterList>(input), Err(error.into()), "{input}");
+        }
+        assert!(validate("filter: grayscale(1) blur(4px)").is_ok());
+        assert!(validate("backdrop-filter: blur(10px) saturate(180%)").is_ok());
+    }
+    #[test]
+    fn test_shadow_and_filter_interpolation() {
+        assert_eq!(
+            interpolate("box-shadow", "0 0 black", "10px 20px 4px 2px black", 0.5),
+            "oklab(0 0 0) 5px 10px 2px 1px"
+        );
+        // The shorter list is padded with transparent shadows.
+        assert_eq!(
+            interpolate("box-shadow", "none", "inset 4px 4px 2em black", 0.25),
+            "oklab(0 0 0 / 0.25) 1px 1px 0.5em inset"
+        );
+        assert_eq!(
+            interpolate("box-shadow", "1px 1px black", "inset 1px 1px black", 0.4),
+            "1px 1px black"
+        );
+        assert_eq!(
+            interpolate("text-shadow", "1em 1em black", "2px 2px black", 0.6),
+            "2px 2px black"
+        );
+        assert_eq!(
+            interpolate("filter", "none", "blur(10px) opacity(0)", 0.5),
+            "blur(5px) opacity(0.5)"
+        );
+        assert_eq!(
+            interpolate("filter", "grayscale(0) brightness(2)", "grayscale(1)", 1.5),
+            "grayscale(1) brightness(0.5)"
+        );
+        assert_eq!(
+            interpolate("backdrop-filter", "blur(4px)", "sepia(1)", 0.4),
+            "blur(4px)"
+        );
+        assert_eq!(interpolate("filter", "url(#a)", "none", 0.6), "none");
+    }
+}
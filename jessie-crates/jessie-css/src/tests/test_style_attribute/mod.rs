@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use crate::CSSToken;
+    use crate::cascade::{Cascade, Origin};
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::parser::Parser;
+    use crate::serializer::serialize_component_values;
+    use crate::style_attribute::*;
+    use crate::tests::tree::Tree;
+    use crate::values::Display;
+    use crate::values::length::*;
+
+    #[test]
+    fn test_parse_declaration_list() {
+        let list = parse_declaration_list(
+            "padding: 4px; color: red; width: blue; bogus: 1; @media print {}; 12px; display: FLEX !important",
+        );
+        let names: Vec<_> = list.declarations().iter().map(|v| &*v.name).collect();
+        assert_eq!(
+            names,
+            [
+                "padding-top",
+                "padding-right",
+                "padding-bottom",
+                "padding-left",
+                "color",
+                "display"
+            ]
+        );
+        assert!(list.declarations()[5].important);
+        let kinds: Vec<_> = list.diagnostics().iter().map(|v| v.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                CssDiagnosticKind::InvalidValue,
+                CssDiagnosticKind::UnknownProperty,
+                CssDiagnosticKind::InvalidAtRule,
+                CssDiagnosticKind::UnexpectedToken,
+            ]
+        );
+
+        assert_eq!(
+            list.get_as::<LengthPercentage>("padding-left"),
+            Some(LengthPercentage::Length(Length::px(4.0)))
+        );
+        assert_eq!(list.get_as::<Display>("display"), Some(Display::Flex));
+        assert_eq!(list.get_as::<Display>("width"), None);
+        // The last declaration of a property is the one that counts.
+        let list = parse_declaration_list("margin-top: 1px; margin-top: 2px; margin-top: red");
+        assert_eq!(
+            list.get_as::<LengthPercentage>("margin-top"),
+            Some(LengthPercentage::Length(Length::px(2.0)))
+        );
+        assert_eq!(parse_declaration_list(""), DeclarationList::default());
+    }
+    #[test]
+    fn test_parse_one() {
+        let declarations = parse_one_declaration("  Color: red !important ").unwrap();
+        assert_eq!(declarations.len(), 1);
+        assert_eq!(declarations[0].name, "color");
+        assert!(declarations[0].important);
+        assert_eq!(parse_one_declaration("margin: 0").unwrap().len(), 4);
+        let error = parse_one_declaration("color red").unwrap_err();
+        assert_eq!(error.kind, CssDiagnosticKind::InvalidDeclaration);
+        assert_eq!(error.span.end.offset, 9);
+        assert_eq!(
+            parse_one_declaration("color: 4px").unwrap_err().kind,
+            CssDiagnosticKind::InvalidValue
+        );
+
+        let value = parse_one_component_value(" 4px ").unwrap();
+        assert!(matches!(
+            value.token(),
+            Some(CSSToken::DimensionToken { unit, .. }) if unit == "px"
+        ));
+        let value = parse_one_component_value("calc(1px + 2em)").unwrap();
+        assert_eq!(
+            serialize_component_values(std::slice::from_ref(&value)),
+            "calc(1px + 2em)"
+        );
+        assert_eq!(
+            parse_one_component_value("   ").unwrap_err().kind,
+            CssDiagnosticKind::UnexpectedEOF
+        );
+        assert_eq!(
+            parse_one_component_value("1px 2px").unwrap_err().kind,
+            CssDiagnosticKind::UnexpectedToken
+        );
+    }
+    #[test]
+    fn test_inline_styles_in_the_cascade() {
+        let mut tree = Tree::default();
+        let button = tree.add(None, "button#save");
+        let mut cascade = Cascade::new();
+        for (source, origin) in [
+            ("button { color: gray !important }", Origin::UserAgent),
+            ("button { width: 20px !important }", Origin::User),
+            (
+                "#save#save { color: blue !important; height: 1px !important; padding: 1px }",
+                Origin::Author,
+            ),
+        ] {
+            cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), origin);
+        }
+        let list = parse_declaration_list(
+            "color: red !important; height: 2px; width: 3px !important; padding: 4px",
+        );
+        let style = cascade.compute(&tree.element(button), Some(&list), None, None);
+        let value = |name| serialize_component_values(style.get(name).unwrap());
+        // Inline styles are author styles that win over any selector, but an `!important` declaration of a rule wins
+        // over a normal inline one, and user and user agent `!important` declarations win over all of them.
+        assert_eq!(value("color"), "gray");
+        assert_eq!(value("height"), "1px");
+        assert_eq!(value("width"), "20px");
+        assert_eq!(value("padding-left"), "4px");
+
+        let list = parse_declaration_list("height: 2px !important");
+        let style = cascade.compute(&tree.element(button), Some(&list), None, None);
+        assert_eq!(
+            serialize_component_values(style.get("height").unwrap()),
+            "2px"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::import::*;
+    use crate::parser::Parser;
+    use crate::serializer::serialize_component_values;
+    use crate::supports::*;
+    use crate::tests::tree::Tree;
+
+    fn supports(source: &str) -> Result<bool, CssDiagnosticKind> {
+        let values = Parser::new(source).parse_list_of_component_values();
+        SupportsCondition::parse(&values)
+            .map(|v| v.evaluate())
+            .map_err(|v| v.kind)
+    }
+    #[test]
+    fn test_evaluate_supports() {
+        for (source, expected) in [
+            ("(display: grid)", true),
+            ("(display: ruby)", false),
+            ("(display: flex)", true),
+            ("(DISPLAY: Flex !important)", true),
+            ("(color: oklch(70% 0.1 200))", true),
+            ("(color: nope)", false),
+            ("(width: -1px)", false),
+            ("(margin: 1px auto)", true),
+            ("(margin: 1px auto 2px 3px 4px)", false),
+            ("(--anything: { x })", true),
+            ("(color: var(--x))", true),
+            ("(color: inherit)", true),
+            ("(float: left)", false),
+            // Properties without a typed value are cascaded, but their values are not checked.
+            ("(cursor: pointer)", false),
+            ("selector(:has(> a))", true),
+            ("selector(a:hover::before)", true),
+            ("selector(:blank)", false),
+            ("selector(a, b)", false),
+            ("font-tech(color-colrv1)", false),
+            ("(display grid)", false),
+            ("not (display: ruby)", true),
+            ("not selector(:has(a))", false),
+            ("(display: flex) and selector(:has(a))", true),
+            ("(display: grid) and selector(:has(a))", true),
+            ("(display: ruby) and selector(:has(a))", false),
+            ("(display: ruby) or (display: flex)", true),
+            (
+                "(display: ruby) or unknown(x) or (not (display: block))",
+                false,
+            ),
+            (
+                "((display: ruby) or (display: flex)) and (not (float: left))",
+                true,
+            ),
+        ] {
+            assert_eq!(supports(source), Ok(expected), "{source}");
+        }
+        for source in [
+            "",
+            "display: flex",
+            "(display: flex) and (color: red) or (width: 1px)",
+            "not (display: flex) and (color: red)",
+            "(display: flex) and",
+            "(display: flex) (color: red)",
+        ] {
+            assert_eq!(
+                supports(source),
+                Err(CssDiagnosticKind::InvalidAtRule),
+                "{source}"
+            );
+        }
+        // `supports()` in `@import` also takes a declaration on its own.
+        let values = Parser::new("display: flex").parse_list_of_component_values();
+        assert!(SupportsCondition::parse_import(&values).unwrap().evaluate());
+    }
+    #[test]
+    fn test_supports_rules() {
+        let mut tree = Tree::default();
+        let p = tree.add(None, "p");
+        let source = "@supports (display: flex) { p { width: 1px } }
+             @supports (display: ruby) { p { width: 2px; height: 2px } }
+             @supports not (display: ruby) { @supports selector(:has(a)) { p { height: 3px } } }
+             @supports display: grid { p { width: 4px } }
+             @supports (display: flex);
+             p { @supports (display: flex) { color: red } }";
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), Origin::Author);
+        assert_eq!(
+            cascade
+                .diagnostics()
+                .iter()
+                .map(|v| v.kind)
+                .collect::<Vec<_>>(),
+            [
+                CssDiagnosticKind::InvalidAtRule,
+                CssDiagnosticKind::InvalidAtRule
+            ]
+        );
+        let style = cascade.compute(&tree.element(p), None, None, None);
+        assert_eq!(
+            ["width", "height", "color"]
+                .map(|name| serialize_component_values(style.get(name).unwrap())),
+            ["1px", "3px", "red"]
+        );
+    }
+    #[test]
+    fn test_import_supports() {
+        static FILES: &[(&str, &str)] = &[
+            (
+                "main.css",
+                "@import 'flex.css' supports(display: flex);
+                 @import 'grid.css' supports((display: grid) and (display: ruby));
+                 @import 'bad.css' supports(display flex);",
+            ),
+            ("flex.css", "p { width: 1px }"),
+            ("grid.css", "p { width: 2px }"),
+        ];
+        let main = load_stylesheet(&mut EmbeddedLoader::new(FILES), "main.css").unwrap();
+        assert_eq!(
+            main.diagnostics.iter().map(|v| v.kind).collect::<Vec<_>>(),
+            [CssDiagnosticKind::InvalidAtRule]
+        );
+        assert_eq!(main.imports.len(), 2);
+        assert!(main.imports[0].stylesheet.is_some());
+        // `grid.css` is not even loaded.
+        assert!(main.imports[1].stylesheet.is_none());
+
+        let mut tree = Tree::default();
+        let p = tree.add(None, "p");
+        let mut cascade = Cascade::new();
+        cascade.add_loaded_stylesheet(&main, Origin::Author);
+        let style = cascade.compute(&tree.element(p), None, None, None);
+        assert_eq!(
+            serialize_component_values(style.get("width").unwrap()),
+            "1px"
+        );
+    }
+    #[test]
+    fn test_support_report() {
+        let report = SupportReport::new();
+        assert!(report.pseudo_classes.contains(&"has()".to_string()));
+        assert!(report.pseudo_classes.contains(&"hover".to_string()));
+        assert!(report.pseudo_elements.contains(&"before"));
+        let json = report.to_json();
+        assert!(json.starts_with("{\"properties\":[{\"name\":\"display\",\"typed\":true,\"inherited\":false,\"initial\":\"inline\",\"animated\":false},"), "{json}");
+        assert!(json.contains("{\"name\":\"cursor\",\"typed\":false,\"inherited\":true,\"initial\":\"auto\",\"animated\":false}"), "{json}");
+        assert!(json.contains("{\"name\":\"margin\",\"longhands\":[\"margin-top\",\"margin-right\",\"margin-bottom\",\"margin-left\"]}"), "{json}");
+        assert!(json.contains("\"at_rules\":[\"charset\","), "{json}");
+        assert!(json.ends_with("\"container_features\":[\"width\",\"height\",\"inline-size\",\"block-size\",\"aspect-ratio\",\"orientation\"]}"), "{json}");
+    }
+}
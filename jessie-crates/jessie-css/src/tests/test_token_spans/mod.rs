@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::span::*;
+    use crate::*;
+    #[test]
+    fn test_token_spans() {
+        let input = include_str!("./spans.css");
+        let tokens = Tokenizer::new(input).tokenize_with_spans();
+        let width = tokens
+            .iter()
+            .find(|v| {
+                v.token
+                    == CSSToken::IdentToken {
+                        value: "width".into(),
+                    }
+            })
+            .unwrap();
+        assert_eq!(
+            width.span,
+            Span {
+                start: SourceLocation {
+                    offset: 17,
+                    line: 2,
+                    column: 3
+                },
+                end: SourceLocation {
+                    offset: 22,
+                    line: 2,
+                    column: 8
+                }
+            }
+        );
+        // The comment is not part of the first token, and columns count code points rather than bytes.
+        assert_eq!(tokens[0].token, CSSToken::WhitespaceToken);
+        assert_eq!(tokens[0].span.start.column, 8);
+        assert_eq!(tokens[0].span.start.offset, 10);
+        let sliced: Vec<&str> = tokens.iter().map(|v| v.span.slice(input)).collect();
+        assert_eq!(
+            sliced,
+            vec![
+                " ", "a", " ", "{", "\n  ", "width", ":", " ", "1.5em", "\n", "}", "\n", ""
+            ]
+        );
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.token, CSSToken::EOFToken);
+        assert_eq!(eof.span.start.line, 4);
+        assert!(eof.span.is_empty());
+    }
+}
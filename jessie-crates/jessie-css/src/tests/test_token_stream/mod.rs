@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::stream::*;
+    use crate::*;
+    fn ident(value: &'static str) -> CSSToken<'static> {
+        CSSToken::IdentToken {
+            value: value.into(),
+        }
+    }
+    #[test]
+    fn test_tokenizer_iterator() {
+        let mut tokenizer = Tokenizer::new("a b");
+        assert_eq!(tokenizer.next(), Some(ident("a")));
+        assert_eq!(tokenizer.next(), Some(CSSToken::WhitespaceToken));
+        assert_eq!(tokenizer.next(), Some(ident("b")));
+        assert_eq!(tokenizer.next(), Some(CSSToken::EOFToken));
+        assert_eq!(tokenizer.next(), None);
+        assert_eq!(tokenizer.next(), None);
+
+        let idents = Tokenizer::new("a, b, c")
+            .filter(|v| matches!(v, CSSToken::IdentToken { .. }))
+            .count();
+        assert_eq!(idents, 3);
+    }
+    #[test]
+    fn test_token_stream() {
+        let mut stream = TokenStream::new("a b c d e");
+        assert_eq!(stream.peek().token, ident("a"));
+        assert_eq!(
+            stream.peek_nth(MAX_LOOKAHEAD).token,
+            CSSToken::WhitespaceToken
+        );
+        assert_eq!(stream.peek_nth(2).token, ident("b"));
+        assert_eq!(stream.consume().token, ident("a"));
+        assert_eq!(stream.consume().token, CSSToken::WhitespaceToken);
+        assert_eq!(stream.consume().token, ident("b"));
+        stream.rewind();
+        stream.rewind();
+        assert_eq!(stream.peek().token, CSSToken::WhitespaceToken);
+        for _ in 0..8 {
+            stream.consume();
+        }
+        let eof = stream.consume();
+        assert_eq!(eof.token, CSSToken::EOFToken);
+        assert_eq!(eof.span.start.offset, 9);
+        assert_eq!(stream.consume(), eof);
+        assert_eq!(stream.peek_nth(MAX_LOOKAHEAD), &eof);
+    }
+    #[test]
+    #[should_panic]
+    fn test_token_stream_rewind_limit() {
+        let mut stream = TokenStream::new("a b c d e");
+        for _ in 0..MAX_REWIND + 1 {
+            stream.consume();
+        }
+        for _ in 0..MAX_REWIND + 1 {
+            stream.rewind();
+        }
+    }
+    #[test]
+    fn test_token_stream_is_lazy() {
+        let mut stream = TokenStream::new("a \"b\n c");
+        assert_eq!(stream.consume().token, ident("a"));
+        assert!(stream.diagnostics().is_empty());
+        stream.peek_nth(1);
+        assert_eq!(stream.take_diagnostics().len(), 1);
+    }
+    #[test]
+    fn test_token_stream_from_tokens() {
+        let tokens = Tokenizer::new("a b").tokenize_with_spans();
+        let end = tokens[2].span.end;
+        let mut stream = TokenStream::from_tokens(tokens[..3].to_vec());
+        stream.consume();
+        stream.consume();
+        stream.consume();
+        let eof = stream.consume();
+        assert_eq!(eof.token, CSSToken::EOFToken);
+        assert_eq!(eof.span.start, end);
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::*;
+    use crate::serializer::*;
+    use crate::*;
+    use proptest::prelude::*;
+
+    ///Inputs made of the pieces of CSS the tokenizer branches on, so the generated strings hit the escapes, urls,
+    ///numbers and comments far more often than random strings would.
+    fn css() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            "[a-zA-Z0-9_\\- \t\n\r\u{c}.,:;#@%+*/<>!=|~^$&?()\\[\\]{}'\"]",
+            "\\\\([0-9a-fA-F]{1,8}[ \t\n]?|\n|\r\n|.)?",
+            "(url|URL|uRl)\\( *",
+            "[+-]?[0-9]{0,4}(\\.[0-9]{0,4})?([eE][+-]?[0-9]{0,4})?",
+            "/\\*|\\*/|<!--|-->|U\\+[0-9a-f?]{1,7}",
+            "\u{0}|\u{7f}|\u{fffd}|é|𝄞|\u{200d}",
+            any::<char>().prop_map(String::from),
+        ];
+        proptest::collection::vec(piece, 0..40).prop_map(|v| v.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn test_tokenizer_ends_with_one_eof(input in css()) {
+            let tokens = Tokenizer::new(&input).tokenize();
+            prop_assert_eq!(tokens.last(), Some(&CSSToken::EOFToken));
+            prop_assert_eq!(tokens.iter().filter(|v| **v == CSSToken::EOFToken).count(), 1);
+            let mut tokenizer = Tokenizer::new(&input);
+            tokenizer.allow_unicode_ranges();
+            let tokens = tokenizer.tokenize();
+            prop_assert_eq!(tokens.last(), Some(&CSSToken::EOFToken));
+        }
+        #[test]
+        fn test_token_spans_cover_the_input(input in css()) {
+            let tokens = Tokenizer::new(&input).tokenize_with_spans();
+            let mut offset = 0;
+            for token in &tokens {
+                // Comments are skipped, so a token starts at or after the end of the previous one.
+                prop_assert!(token.span.start.offset >= offset, "{:?} overlaps the previous token", token);
+                prop_assert!(token.span.start.offset <= token.span.end.offset);
+                prop_assert!(input.is_char_boundary(token.span.end.offset));
+                offset = token.span.end.offset;
+            }
+            prop_assert_eq!(offset, input.len());
+        }
+        #[test]
+        fn test_parser_never_panics(input in css()) {
+            Parser::new(&input).parse_stylesheet();
+            Parser::new(&input).parse_list_of_declarations();
+            Parser::new(&input).parse_block_contents();
+            let _ = Parser::new(&input).parse_rule();
+            let _ = Parser::new(&input).parse_declaration();
+            let _ = Parser::new(&input).parse_component_value();
+        }
+        #[test]
+        fn test_token_serialization_round_trips(input in css()) {
+            // U+0000 is serialized as U+FFFD, which is what the tokenizer would read in its place if it did the input
+            // preprocessing, see `KNOWN_DIVERGENCES` in the css-parsing-tests.
+            let input = input.replace('\0', "\u{fffd}");
+            let mut tokens = Tokenizer::new(&input).tokenize();
+            // A comment between two <whitespace-token>s is the only way to get two of them in a row, and they are
+            // written as one.
+            tokens.dedup_by(|a, b| *a == CSSToken::WhitespaceToken && *b == CSSToken::WhitespaceToken);
+            let serialized = serialize_tokens(&tokens);
+            prop_assert_eq!(Tokenizer::new(&serialized).tokenize(), tokens, "{:?} was serialized as {:?}", input, serialized);
+        }
+        #[test]
+        fn test_component_value_serialization_is_stable(input in css()) {
+            // Blocks and functions left open are closed when they are serialized, so the first serialization can
+            // differ from the input, but serializing it again must not change it anymore.
+            let once = serialize_component_values(&Parser::new(&input).parse_list_of_component_values());
+            let twice = serialize_component_values(&Parser::new(&once).parse_list_of_component_values());
+            prop_assert_eq!(once, twice);
+        }
+        #[test]
+        fn test_numbers_match_rust(
+            input in "[+-]?([0-9]{1,18}|[0-9]{0,18}\\.[0-9]{1,18})([eE][+-]?[0-9]{1,3})?",
+        ) {
+            let expected: f64 = input.parse().unwrap();
+            let tokens = Tokenizer::new(&input).tokenize();
+            let value = match tokens[..] {
+                [CSSToken::NumberToken { value, .. }, CSSToken::EOFToken] => value,
+                _ => return Err(TestCaseError::fail(format!("{input:?} is not a number: {tokens:?}"))),
+            };
+            // Numbers that overflow are clamped, see `Tokenizer::string_to_number`.
+            prop_assert_eq!(value, expected.clamp(-f64::MAX, f64::MAX), "{}", input);
+        }
+    }
+}
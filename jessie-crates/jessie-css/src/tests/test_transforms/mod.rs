@@ -0,0 +1,309 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::{Cascade, Origin};
+    use crate::parser::Parser;
+    use crate::tests::tree::Tree;
+    use crate::tests::util::{interpolate, parse, round_trip};
+    use crate::values::ToCss;
+    use crate::values::length::*;
+    use crate::values::transform::*;
+
+    fn assert_matrix(found: Matrix, expected: [f32; 6]) {
+        let found = found.to_2d().unwrap();
+        assert!(
+            found
+                .iter()
+                .zip(expected)
+                .all(|(a, b)| (a - b).abs() < 1e-4),
+            "{found:?} != {expected:?}"
+        );
+    }
+    #[test]
+    fn test_parse_transforms() {
+        let px = |value| LengthPercentage::Length(Length::px(value));
+        assert_eq!(parse::<TransformList>("none"), Ok(TransformList(vec![])));
+        assert_eq!(
+            parse::<TransformList>(
+                "translateX(10px) translate(50%, 2em) rotate(0.25turn) scaleY(2)"
+            ),
+            Ok(TransformList(vec![
+                TransformFunction::Translate(px(10.0), px(0.0), Length::ZERO),
+                TransformFunction::Translate(
+                    LengthPercentage::Percentage(0.5),
+                    LengthPercentage::Length(Length {
+                        value: 2.0,
+                        unit: LengthUnit::Em
+                    }),
+                    Length::ZERO
+                ),
+                TransformFunction::Rotate([0.0, 0.0, 1.0], 90.0),
+                TransformFunction::Scale(1.0, 2.0, 1.0),
+            ]))
+        );
+        assert_eq!(
+            parse::<TransformList>(
+                "rotate3d(1, 1, 0, 45deg) perspective(none) skewY(0) matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 5, 6, 7, 1)"
+            ),
+            Ok(TransformList(vec![
+                TransformFunction::Rotate([1.0, 1.0, 0.0], 45.0),
+                TransformFunction::Perspective(None),
+                TransformFunction::Skew(0.0, 0.0),
+                TransformFunction::Matrix3d([
+                    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 5.0, 6.0, 7.0, 1.0
+                ]),
+            ]))
+        );
+
+        // Transforms are written as the shortest function for their primitive.
+        assert_eq!(
+            round_trip::<TransformList>("translateY(5px)"),
+            "translate(0px, 5px)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("translate3d(1px, 2%, 3px)"),
+            "translate3d(1px, 2%, 3px)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("translateZ(0) scale(2, 2)"),
+            "translate(0px) scale(2)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("scale3d(50%, 1, 2)"),
+            "scale3d(0.5, 1, 2)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("rotateX(1rad) rotateY(-10deg)"),
+            "rotateX(57.29578deg) rotateY(-10deg)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("rotate3d(0, 2, 1, 30deg)"),
+            "rotate3d(0, 2, 1, 30deg)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("skewX(10deg) skew(1deg, 2deg)"),
+            "skew(10deg) skew(1deg, 2deg)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("perspective(200px) matrix(1, 2, 3, 4, 5, 6)"),
+            "perspective(200px) matrix(1, 2, 3, 4, 5, 6)"
+        );
+        assert_eq!(
+            round_trip::<TransformList>("rotate(calc(45deg * 2))"),
+            "rotate(90deg)"
+        );
+
+        for (input, error) in [
+            ("rotate(45)", "expected an angle like `90deg`, found `45`"),
+            (
+                "translate(10px, 20px, 30px)",
+                "expected the end of the value, found `,`",
+            ),
+            (
+                "translateZ(10%)",
+                "expected a length like `10px`, found `10%`",
+            ),
+            ("perspective(-1px)", "negative values are not allowed here"),
+            ("matrix(1, 2, 3)", "the value ended too early"),
+            (
+                "rotate(45deg) none",
+                "expected the end of the value, found `none`",
+            ),
+            ("wobble(1)", "`wobble()` is not a transform function"),
+            ("", "the value ended too early"),
+        ] {
+            assert_eq!(parse::<TransformList>(input), Err(error.into()), "{input}");
+        }
+    }
+    #[test]
+    fn test_transform_origin() {
+        let origin = |input: &str| parse::<TransformOrigin>(input).map(|v| v.to_css_string());
+        assert_eq!(origin("left"), Ok("0% 50%".into()));
+        assert_eq!(origin("top"), Ok("50% 0%".into()));
+        assert_eq!(origin("10px"), Ok("10px 50%".into()));
+        assert_eq!(origin("right bottom"), Ok("100% 100%".into()));
+        assert_eq!(origin("bottom right 5px"), Ok("100% 100% 5px".into()));
+        assert_eq!(origin("center top"), Ok("50% 0%".into()));
+        assert_eq!(origin("top center"), Ok("50% 0%".into()));
+        assert_eq!(origin("left 10px"), Ok("0% 10px".into()));
+        assert_eq!(origin("1em 2em 3em"), Ok("1em 2em 3em".into()));
+        assert_eq!(
+            origin("top 10px"),
+            Err("expected a horizontal position followed by a vertical one".into())
+        );
+        assert_eq!(
+            origin("left right"),
+            Err("expected a horizontal position followed by a vertical one".into())
+        );
+        assert_eq!(
+            origin("10px 20px 30%"),
+            Err("expected the end of the value, found `30%`".into())
+        );
+
+        let origin = parse::<TransformOrigin>("25% bottom 4px").unwrap();
+        assert_eq!(
+            origin.to_px(&LengthContext::new(800.0, 600.0), 200.0, 100.0),
+            (50.0, 100.0, 4.0)
+        );
+    }
+    #[test]
+    fn test_transform_matrices() {
+        let context = LengthContext::new(800.0, 600.0);
+        let matrix = |input: &str| {
+            parse::<TransformList>(input)
+                .unwrap()
+                .to_matrix(&context, 200.0, 100.0)
+        };
+        assert_eq!(matrix("none"), Matrix::IDENTITY);
+        assert_matrix(
+            matrix("translate(50%, 10px)"),
+            [1.0, 0.0, 0.0, 1.0, 100.0, 10.0],
+        );
+        assert_matrix(matrix("rotate(90deg)"), [0.0, 1.0, -1.0, 0.0, 0.0, 0.0]);
+        assert_matrix(
+            matrix("rotate3d(0, 0, 2, 90deg)"),
+            [0.0, 1.0, -1.0, 0.0, 0.0, 0.0],
+        );
+        assert_matrix(matrix("skewX(45deg)"), [1.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
+        assert_matrix(
+            matrix("matrix(1, 2, 3, 4, 5, 6)"),
+            [1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+        );
+        // Functions apply from right to left, so this scales first and then moves.
+        assert_matrix(
+            matrix("translate(10px) scale(2)"),
+            [2.0, 0.0, 0.0, 2.0, 10.0, 0.0],
+        );
+        assert_matrix(
+            matrix("scale(2) translate(10px)"),
+            [2.0, 0.0, 0.0, 2.0, 20.0, 0.0],
+        );
+        assert_eq!(
+            matrix("translate(10px) scale(2)").transform_point(5.0, 5.0),
+            (20.0, 10.0)
+        );
+
+        let rotate = matrix("rotateY(90deg)");
+        assert!(!rotate.is_2d());
+        assert!(rotate.to_2d().is_none());
+        let (x, _) = rotate.transform_point(10.0, 0.0);
+        assert!(x.abs() < 1e-4, "{x}");
+        let perspective = matrix("perspective(100px)");
+        assert_eq!(perspective.0[11], -0.01);
+        assert_eq!(matrix("perspective(0)").0[11], -1.0);
+        assert!(!parse::<TransformList>("translateZ(1px)").unwrap().0[0].is_2d());
+    }
+    #[test]
+    fn test_transform_interpolation() {
+        // Lists of the same functions interpolate function by function.
+        assert_eq!(
+            interpolate(
+                "transform",
+                "translate(10px) rotate(0deg)",
+                "translate(20px, 50%) rotate(90deg)",
+                0.5
+            ),
+            "translate(15px, calc(0px + 25%)) rotate(45deg)"
+        );
+        assert_eq!(
+            interpolate("transform", "none", "scale(3) skew(20deg)", 0.5),
+            "scale(2) skew(10deg)"
+        );
+        assert_eq!(
+            interpolate("transform", "rotate(90deg)", "none", 0.25),
+            "rotate(67.5deg)"
+        );
+        assert_eq!(
+            interpolate(
+                "transform",
+                "rotate(30deg)",
+                "rotate(30deg) translate(10px)",
+                0.5
+            ),
+            "rotate(30deg) translate(5px)"
+        );
+        assert_eq!(
+            interpolate("transform", "rotateX(0deg)", "rotateY(90deg)", 0.5),
+            "rotateY(45deg)"
+        );
+        // Other lists interpolate as matrices.
+        assert_eq!(
+            interpolate("transform", "scale(2)", "translate(10px) scale(4)", 0.5),
+            "matrix(3, 0, 0, 3, 5, 0)"
+        );
+        assert_eq!(
+            interpolate(
+                "transform",
+                "rotate(45deg)",
+                "matrix(1, 0, 0, 1, 0, 0)",
+                1.0
+            ),
+            "matrix(1, 0, 0, 1, 0, 0)"
+        );
+        let halfway = interpolate(
+            "transform",
+            "matrix(1, 0, 0, 1, 0, 0)",
+            "matrix(0, 1, -1, 0, 0, 0)",
+            0.5,
+        );
+        let matrix = parse::<TransformList>(&halfway).unwrap();
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert_matrix(
+            matrix.to_matrix(&LengthContext::new(0.0, 0.0), 0.0, 0.0),
+            [half, half, -half, half, 0.0, 0.0],
+        );
+        // Flipped axes rotate instead of shrinking to nothing.
+        let halfway = interpolate("transform", "scale(-1, 1)", "skew(0deg) scale(1, -1)", 0.5);
+        let matrix = parse::<TransformList>(&halfway).unwrap();
+        assert_matrix(
+            matrix.to_matrix(&LengthContext::new(0.0, 0.0), 0.0, 0.0),
+            [0.0, -1.0, -1.0, 0.0, 0.0, 0.0],
+        );
+        // Lists that depend on layout or are 3D are discrete.
+        assert_eq!(
+            interpolate("transform", "translate(10%)", "rotate(10deg)", 0.4),
+            "translate(10%)"
+        );
+        assert_eq!(
+            interpolate("transform", "rotateX(10deg)", "rotateY(10deg)", 0.6),
+            "rotateY(10deg)"
+        );
+        assert_eq!(
+            interpolate("transform-origin", "left top", "10px 20px 30px", 0.5),
+            "calc(0% + 5px) calc(0% + 10px) 15px"
+        );
+    }
+    #[test]
+    fn test_transform_properties() {
+        let source = ".a { transform: rotate(10deg) translateX(5px); transform-origin: right }
+             .b { transform: rotate(10deg) red; transform-origin: nowhere }";
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), Origin::Author);
+        assert_eq!(cascade.diagnostics().len(), 2);
+        let mut tree = Tree::default();
+        let a = tree.add(None, "p.a");
+        let b = tree.add(None, "p.b");
+        let style = cascade.compute(&tree.element(a), None, None, None);
+        assert_eq!(
+            style.get_as::<TransformList>("transform"),
+            parse::<TransformList>("rotate(10deg) translate(5px)").ok()
+        );
+        assert_eq!(
+            style
+                .get_as::<TransformOrigin>("transform-origin")
+                .map(|v| v.to_css_string()),
+            Some("100% 50%".into())
+        );
+        let style = cascade.compute(&tree.element(b), None, None, None);
+        assert_eq!(
+            style.get_as::<TransformList>("transform"),
+            Some(TransformList(vec![]))
+        );
+        assert_eq!(
+            style.get_as::<TransformOrigin>("transform-origin"),
+            Some(TransformOrigin {
+                position: BoxPosition::CENTER,
+                z: Length::ZERO
+            })
+        );
+    }
+}
@@ -0,0 +1,285 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::{Cascade, ComputedStyle, Origin};
+    use crate::diagnostics::CssDiagnosticKind;
+    use crate::parser::{Declaration, Parser};
+    use crate::properties::{self, PROPERTIES, shorthands};
+    use crate::serializer::serialize_component_values;
+    use crate::tests::tree::Tree;
+    use crate::tests::util::parse;
+    use crate::values::color::{AbsoluteColor, Color};
+    use crate::values::font::*;
+    use crate::values::length::*;
+    use crate::values::*;
+
+    fn expand(input: &str) -> Vec<(String, String)> {
+        let declaration: Declaration = Parser::new(input).parse_declaration().unwrap();
+        properties::validate_declaration(declaration)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.name.to_string(), serialize_component_values(&v.value)))
+            .collect()
+    }
+    #[test]
+    fn test_lengths() {
+        let context = LengthContext {
+            font_size: 20.0,
+            ..LengthContext::new(800.0, 600.0)
+        };
+        let length = |input: &str| parse::<Length>(input).unwrap().to_px(&context);
+        assert_eq!(length("12px"), 12.0);
+        assert_eq!(length("1.5EM"), 30.0);
+        assert_eq!(length("2rem"), 32.0);
+        assert_eq!(length("2ch"), 20.0);
+        assert_eq!(length("10vw"), 80.0);
+        assert_eq!(length("10vh"), 60.0);
+        assert_eq!(length("10vmin"), 60.0);
+        assert_eq!(length("10vmax"), 80.0);
+        assert_eq!(length("0"), 0.0);
+        assert_eq!(
+            parse::<LengthPercentage>("25%")
+                .unwrap()
+                .to_px(&context, 200.0),
+            50.0
+        );
+        assert_eq!(
+            parse::<LengthPercentageOrAuto>("auto"),
+            Ok(LengthPercentageOrAuto::Auto)
+        );
+        assert_eq!(
+            parse::<Length>("3pt"),
+            Err("`pt` is not a supported length unit, expected one of `px`, `em`, `rem`, `ch`, `vw`, `vh`, `vmin`, `vmax`".into())
+        );
+        assert_eq!(
+            parse::<LengthPercentage>("red"),
+            Err("expected a length like `10px` or a percentage, found `red`".into())
+        );
+        assert_eq!(
+            parse::<Length>("12"),
+            Err("expected a length like `10px`, found `12`".into())
+        );
+        assert_eq!(
+            parse::<Length>("1px 2px"),
+            Err("expected the end of the value, found `2px`".into())
+        );
+        assert_eq!(
+            parse::<LineWidth>("thick"),
+            Ok(LineWidth(LengthPercentage::Length(Length::px(5.0))))
+        );
+        assert_eq!(
+            parse::<LineWidth>("-1px"),
+            Err("negative values are not allowed here".into())
+        );
+    }
+    #[test]
+    fn test_colors() {
+        let rgba8 = |input: &str| match parse::<Color>(input).unwrap() {
+            Color::Absolute(color) => color.to_rgba().to_rgba8(),
+            _ => panic!("{input} is not an absolute color"),
+        };
+        assert_eq!(rgba8("#f80"), (255, 136, 0, 255));
+        assert_eq!(rgba8("#ff880080"), (255, 136, 0, 128));
+        assert_eq!(rgba8("rebeccapurple"), (102, 51, 153, 255));
+        assert_eq!(rgba8("RED"), (255, 0, 0, 255));
+        assert_eq!(rgba8("transparent"), (0, 0, 0, 0));
+        assert_eq!(rgba8("rgb(255, 128, 0)"), (255, 128, 0, 255));
+        assert_eq!(rgba8("rgba(100%, 0%, 0%, 0.5)"), (255, 0, 0, 128));
+        assert_eq!(rgba8("rgb(0 255 0 / 25%)"), (0, 255, 0, 64));
+        assert_eq!(parse::<Color>("currentColor"), Ok(Color::CurrentColor));
+        assert_eq!(
+            Color::CurrentColor.resolve(&AbsoluteColor::from_rgba8(255, 0, 0, 255)),
+            AbsoluteColor::from_rgba8(255, 0, 0, 255)
+        );
+        assert_eq!(
+            parse::<Color>("#12345"),
+            Err("`#12345` is not a hex color, which has 3, 4, 6 or 8 hexadecimal digits".into())
+        );
+        assert_eq!(
+            parse::<Color>("reddish"),
+            Err("`reddish` is not a named color".into())
+        );
+        assert_eq!(
+            parse::<Color>("rgb(1, 2 3)"),
+            Err("expected `,`, found `3`".into())
+        );
+    }
+    #[test]
+    fn test_keywords_and_numbers() {
+        assert_eq!(parse::<Display>("inline-FLEX"), Ok(Display::InlineFlex));
+        assert_eq!(parse::<Position>("sticky"), Ok(Position::Sticky));
+        assert_eq!(parse::<Overflow>("clip"), Ok(Overflow::Clip));
+        assert_eq!(
+            parse::<Position>("floating"),
+            Err("expected one of `static`, `relative`, `absolute`, `fixed`, `sticky`, found `floating`".into())
+        );
+        assert_eq!(parse::<ZIndex>("-3"), Ok(ZIndex::Integer(-3)));
+        assert_eq!(parse::<ZIndex>("auto"), Ok(ZIndex::Auto));
+        assert_eq!(
+            parse::<ZIndex>("1.5"),
+            Err("expected `auto` or an integer, found `1.5`".into())
+        );
+        assert_eq!(parse::<AlphaValue>("150%"), Ok(AlphaValue(1.0)));
+        assert_eq!(parse::<AlphaValue>("0.25"), Ok(AlphaValue(0.25)));
+    }
+    #[test]
+    fn test_fonts() {
+        assert_eq!(
+            parse::<FontFamily>("\"Fira Sans\", Open  Sans, monospace"),
+            Ok(FontFamily(vec![
+                FamilyName::Named("Fira Sans".into()),
+                FamilyName::Named("Open Sans".into()),
+                FamilyName::Generic(GenericFamily::Monospace),
+            ]))
+        );
+        let context = LengthContext::new(800.0, 600.0);
+        let size = |input: &str| parse::<FontSize>(input).unwrap().to_px(&context, 20.0);
+        assert_eq!(size("large"), 19.2);
+        assert_eq!(size("150%"), 30.0);
+        assert_eq!(size("smaller"), 20.0 / 1.2);
+        assert_eq!(parse::<FontWeight>("bold"), Ok(FontWeight::Absolute(700.0)));
+        assert_eq!(parse::<FontWeight>("bolder").unwrap().resolve(400.0), 700.0);
+        assert_eq!(
+            parse::<FontWeight>("1001"),
+            Err("a font weight is between 1 and 1000, found 1001".into())
+        );
+        assert_eq!(
+            parse::<FontStyle>("oblique 10deg"),
+            Ok(FontStyle::Oblique(10.0))
+        );
+        assert_eq!(parse::<FontStyle>("oblique"), Ok(FontStyle::Oblique(14.0)));
+        assert_eq!(parse::<FontStretch>("condensed"), Ok(FontStretch(75.0)));
+        assert_eq!(parse::<FontStretch>("110%"), Ok(FontStretch(110.0)));
+        assert_eq!(
+            parse::<LineHeight>("1.5").unwrap().to_px(&context, 10.0),
+            15.0
+        );
+    }
+    #[test]
+    fn test_shorthands() {
+        let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            expand("margin: 1px auto"),
+            pairs(&[
+                ("margin-top", "1px"),
+                ("margin-right", "auto"),
+                ("margin-bottom", "1px"),
+                ("margin-left", "auto"),
+            ])
+        );
+        assert_eq!(
+            expand("PADDING: 1px 2px 3px"),
+            pairs(&[
+                ("padding-top", "1px"),
+                ("padding-right", "2px"),
+                ("padding-bottom", "3px"),
+                ("padding-left", "2px"),
+            ])
+        );
+        assert_eq!(
+            expand("border-left: red dashed"),
+            pairs(&[
+                ("border-left-width", "medium"),
+                ("border-left-style", "dashed"),
+                ("border-left-color", "red"),
+            ])
+        );
+        assert_eq!(expand("border: 2px solid rgb(0, 0, 0)").len(), 12);
+        assert_eq!(
+            expand("border-radius: 1px 2px / 3px")[..2],
+            pairs(&[
+                ("border-top-left-radius", "1px 3px"),
+                ("border-top-right-radius", "2px 3px"),
+            ])
+        );
+        assert_eq!(
+            expand("overflow: hidden"),
+            pairs(&[("overflow-x", "hidden"), ("overflow-y", "hidden")])
+        );
+        assert_eq!(
+            expand("font: italic bold 12px/1.5 \"Fira Sans\", serif"),
+            pairs(&[
+                ("font-style", "italic"),
+                ("font-weight", "bold"),
+                ("font-stretch", "normal"),
+                ("font-size", "12px"),
+                ("line-height", "1.5"),
+                ("font-family", "\"Fira Sans\", serif"),
+            ])
+        );
+        assert_eq!(
+            expand("margin: inherit !important")[3],
+            ("margin-left".to_string(), "inherit".to_string())
+        );
+        let error = |input: &str| {
+            let declaration = Parser::new(input).parse_declaration().unwrap();
+            properties::validate_declaration(declaration)
+                .unwrap_err()
+                .message
+        };
+        assert_eq!(
+            error("margin: 1px 2px 3px 4px 5px"),
+            "invalid `margin`: expected the end of the value, found `5px`"
+        );
+        assert_eq!(
+            error("font: bolder 12px serif"),
+            "invalid `font`: expected a font size like `medium` or `16px`, found `bolder`"
+        );
+        assert_eq!(
+            error("padding-top: -1px"),
+            "invalid `padding-top`: negative values are not allowed here"
+        );
+        assert!(shorthands::lookup("border-width").is_some());
+    }
+    #[test]
+    fn test_initial_values_are_valid() {
+        for property in PROPERTIES {
+            assert_eq!(
+                (property.validate)(property.initial_value()),
+                Ok(()),
+                "{}",
+                property.name
+            );
+        }
+    }
+    #[test]
+    fn test_typed_computed_values() {
+        let mut tree = Tree::default();
+        let root = tree.add(None, "div");
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new(
+                "div { margin: 4px 8px; display: flex; width: red; border: thin solid blue }",
+            )
+            .parse_stylesheet(),
+            Origin::Author,
+        );
+        let diagnostics = cascade.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, CssDiagnosticKind::InvalidValue);
+        let style = cascade.compute(&tree.element(root), None, None, None);
+        assert_eq!(style.get_as::<Display>("display"), Some(Display::Flex));
+        assert_eq!(
+            style.get_as::<LengthPercentageOrAuto>("margin-left"),
+            Some(LengthPercentageOrAuto::LengthPercentage(
+                LengthPercentage::Length(Length::px(8.0))
+            ))
+        );
+        assert_eq!(
+            style.get_as::<LengthPercentageOrAuto>("width"),
+            Some(LengthPercentageOrAuto::Auto)
+        );
+        assert_eq!(
+            style.get_as::<LineWidth>("border-bottom-width"),
+            Some(LineWidth(LengthPercentage::Length(Length::px(1.0))))
+        );
+        assert_eq!(
+            ComputedStyle::initial().get_as::<Position>("position"),
+            Some(Position::Static)
+        );
+    }
+}
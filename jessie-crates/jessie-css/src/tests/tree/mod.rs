@@ -0,0 +1,105 @@
+//! An arena of elements implementing [`crate::matching::Element`], shared by the tests that match selectors.
+use crate::matching::{Element, ElementState};
+use crate::media::container::QueryContainer;
+
+#[derive(Default)]
+pub struct Node {
+    pub name: &'static str,
+    pub id: Option<&'static str>,
+    pub classes: Vec<&'static str>,
+    pub attributes: Vec<(&'static str, &'static str)>,
+    pub state: ElementState,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub container: Option<QueryContainer>,
+}
+#[derive(Default)]
+pub struct Tree {
+    pub nodes: Vec<Node>,
+}
+impl Tree {
+    pub fn add(&mut self, parent: Option<usize>, selector: &'static str) -> usize {
+        // `name#id.class.class`
+        let mut parts = selector.split('.');
+        let first = parts.next().unwrap();
+        let (name, id) = match first.split_once('#') {
+            Some((name, id)) => (name, Some(id)),
+            None => (first, None),
+        };
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            name,
+            id,
+            classes: parts.collect(),
+            parent,
+            ..Default::default()
+        });
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+        index
+    }
+    pub fn element(&self, index: usize) -> TestElement<'_> {
+        TestElement { tree: self, index }
+    }
+}
+#[derive(Clone, Copy)]
+pub struct TestElement<'t> {
+    tree: &'t Tree,
+    index: usize,
+}
+impl PartialEq for TestElement<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.tree, other.tree) && self.index == other.index
+    }
+}
+impl<'t> TestElement<'t> {
+    fn node(&self) -> &'t Node {
+        &self.tree.nodes[self.index]
+    }
+    fn sibling(&self, offset: isize) -> Option<Self> {
+        let siblings = &self.tree.nodes[self.node().parent?].children;
+        let position = siblings.iter().position(|&v| v == self.index)?;
+        let index = *siblings.get(position.checked_add_signed(offset)?)?;
+        Some(self.tree.element(index))
+    }
+}
+impl Element for TestElement<'_> {
+    fn parent(&self) -> Option<Self> {
+        Some(self.tree.element(self.node().parent?))
+    }
+    fn prev_sibling(&self) -> Option<Self> {
+        self.sibling(-1)
+    }
+    fn next_sibling(&self) -> Option<Self> {
+        self.sibling(1)
+    }
+    fn first_child(&self) -> Option<Self> {
+        Some(self.tree.element(*self.node().children.first()?))
+    }
+    fn local_name(&self) -> &str {
+        self.node().name
+    }
+    fn id(&self) -> Option<&str> {
+        self.node().id
+    }
+    fn classes(&self) -> impl Iterator<Item = &str> {
+        self.node().classes.iter().copied()
+    }
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.node()
+            .attributes
+            .iter()
+            .find(|v| v.0 == name)
+            .map(|v| v.1)
+    }
+    fn state(&self) -> ElementState {
+        self.node().state
+    }
+    fn is_empty(&self) -> bool {
+        self.node().children.is_empty()
+    }
+    fn query_container(&self) -> Option<QueryContainer> {
+        self.node().container.clone()
+    }
+}
@@ -0,0 +1,40 @@
+//! Helpers for parsing values and cascading stylesheets, shared by the tests of values, animations and the cascade.
+use crate::animation::interpolate::interpolate_property;
+use crate::cascade::{Cascade, ComputedStyle, Origin};
+use crate::parser::Parser;
+use crate::serializer::serialize_component_values;
+use crate::values::{Parse, ToCss, parse_value};
+use std::fmt::Debug;
+
+///Parses the whole input as a `T`, with the message of the diagnostic if it is not one.
+pub fn parse<T: Parse>(input: &str) -> Result<T, String> {
+    let values = Parser::new(input).parse_list_of_component_values();
+    parse_value::<T>(&values).map_err(|v| v.message)
+}
+
+///Parses the input as a `T` and writes it back, checking that what is written parses to the same value.
+pub fn round_trip<T: Parse + ToCss + PartialEq + Debug>(input: &str) -> String {
+    let value = parse::<T>(input).unwrap();
+    let text = value.to_css_string();
+    assert_eq!(parse::<T>(&text), Ok(value), "{text}");
+    text
+}
+
+///Interpolates between two values of a property, see [`interpolate_property`].
+pub fn interpolate(name: &str, from: &str, to: &str, progress: f32) -> String {
+    let from = Parser::new(from).parse_list_of_component_values();
+    let to = Parser::new(to).parse_list_of_component_values();
+    serialize_component_values(&interpolate_property(name, &from, &to, progress))
+}
+
+///A cascade of a single author stylesheet.
+pub fn cascade(source: &str) -> Cascade<'_> {
+    let mut cascade = Cascade::new();
+    cascade.add_stylesheet(&Parser::new(source).parse_stylesheet(), Origin::Author);
+    cascade
+}
+
+///The serialized value of a property in a computed style.
+pub fn value(style: &ComputedStyle, name: &str) -> Option<String> {
+    style.get(name).map(serialize_component_values)
+}
@@ -0,0 +1,851 @@
+//! The math functions of https://www.w3.org/TR/css-values-4/#math, like `calc(100% - 2rem)` and `clamp(1rem, 2.5vw, 2rem)`.
+//! They are simplified as far as they go when they are parsed, and whatever depends on layout is resolved by [`Calc::to_px`].
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{ComponentValue, Function};
+use crate::values::length::{Length, LengthContext, LengthUnit};
+use crate::values::{
+    Parse, ToCss, ValueParser, angle_to_degrees, keyword_enum, unexpected, write_number,
+};
+
+///What a math function has to resolve to where it is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CalcCategory {
+    Number,
+    Percentage,
+    Length,
+    ///A length where percentages are relative to another length, like in `width`.
+    LengthPercentage,
+    Angle,
+}
+
+impl CalcCategory {
+    fn calc_type(&self) -> CalcType {
+        match self {
+            CalcCategory::Number => CalcType::NUMBER,
+            CalcCategory::Percentage => CalcType::PERCENTAGE,
+            CalcCategory::Length | CalcCategory::LengthPercentage => CalcType::LENGTH,
+            CalcCategory::Angle => CalcType::ANGLE,
+        }
+    }
+
+    ///The type percentages have, which is a length where they are relative to one.
+    fn percentage_type(&self) -> CalcType {
+        match self {
+            CalcCategory::LengthPercentage => CalcType::LENGTH,
+            _ => CalcType::PERCENTAGE,
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            CalcCategory::Number => "a number",
+            CalcCategory::Percentage => "a percentage",
+            CalcCategory::Length => "a length",
+            CalcCategory::LengthPercentage => "a length or a percentage",
+            CalcCategory::Angle => "an angle",
+        }
+    }
+}
+
+///The type of a calculation as the powers of its base types, `1px * 1px` being a length squared.
+///Intermediate results can have any type as long as the whole calculation has the type it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct CalcType {
+    length: i8,
+    angle: i8,
+    percentage: i8,
+}
+
+impl CalcType {
+    const NUMBER: CalcType = CalcType {
+        length: 0,
+        angle: 0,
+        percentage: 0,
+    };
+    const LENGTH: CalcType = CalcType {
+        length: 1,
+        ..CalcType::NUMBER
+    };
+    const ANGLE: CalcType = CalcType {
+        angle: 1,
+        ..CalcType::NUMBER
+    };
+    const PERCENTAGE: CalcType = CalcType {
+        percentage: 1,
+        ..CalcType::NUMBER
+    };
+
+    fn multiply(self, other: CalcType) -> CalcType {
+        CalcType {
+            length: self.length + other.length,
+            angle: self.angle + other.angle,
+            percentage: self.percentage + other.percentage,
+        }
+    }
+
+    fn invert(self) -> CalcType {
+        CalcType {
+            length: -self.length,
+            angle: -self.angle,
+            percentage: -self.percentage,
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match *self {
+            CalcType::NUMBER => "a number",
+            CalcType::LENGTH => "a length",
+            CalcType::ANGLE => "an angle",
+            CalcType::PERCENTAGE => "a percentage",
+            _ => "a product of units",
+        }
+    }
+}
+
+keyword_enum! {
+    ///How `round()` rounds.
+    pub enum RoundingStrategy {
+        Nearest = "nearest",
+        Up = "up",
+        Down = "down",
+        ToZero = "to-zero",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MathFunction {
+    Min,
+    Max,
+    Clamp,
+    Round(RoundingStrategy),
+    Mod,
+    Rem,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Pow,
+    Sqrt,
+    Hypot,
+    Log,
+    Exp,
+    Abs,
+    Sign,
+}
+
+impl MathFunction {
+    const NAMES: &[(&str, MathFunction)] = &[
+        ("min", MathFunction::Min),
+        ("max", MathFunction::Max),
+        ("clamp", MathFunction::Clamp),
+        ("round", MathFunction::Round(RoundingStrategy::Nearest)),
+        ("mod", MathFunction::Mod),
+        ("rem", MathFunction::Rem),
+        ("sin", MathFunction::Sin),
+        ("cos", MathFunction::Cos),
+        ("tan", MathFunction::Tan),
+        ("asin", MathFunction::Asin),
+        ("acos", MathFunction::Acos),
+        ("atan", MathFunction::Atan),
+        ("atan2", MathFunction::Atan2),
+        ("pow", MathFunction::Pow),
+        ("sqrt", MathFunction::Sqrt),
+        ("hypot", MathFunction::Hypot),
+        ("log", MathFunction::Log),
+        ("exp", MathFunction::Exp),
+        ("abs", MathFunction::Abs),
+        ("sign", MathFunction::Sign),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(v, _)| name.eq_ignore_ascii_case(v))
+            .map(|(_, function)| *function)
+    }
+
+    pub fn name(&self) -> &'static str {
+        let function = match self {
+            MathFunction::Round(_) => MathFunction::Round(RoundingStrategy::Nearest),
+            other => *other,
+        };
+        Self::NAMES
+            .iter()
+            .find(|(_, v)| *v == function)
+            .map(|(name, _)| *name)
+            .unwrap_or_default()
+    }
+
+    ///The smallest and largest number of arguments the function takes.
+    fn arguments(&self) -> (usize, usize) {
+        match self {
+            MathFunction::Min | MathFunction::Max | MathFunction::Hypot => (1, usize::MAX),
+            MathFunction::Clamp => (3, 3),
+            MathFunction::Round(_) | MathFunction::Log => (1, 2),
+            MathFunction::Mod | MathFunction::Rem | MathFunction::Atan2 | MathFunction::Pow => {
+                (2, 2)
+            }
+            _ => (1, 1),
+        }
+    }
+}
+
+///Whether `name` is the name of a math function, like `calc` or `min`.
+pub fn is_math_function(name: &str) -> bool {
+    name.eq_ignore_ascii_case("calc") || MathFunction::from_name(name).is_some()
+}
+
+///A node of a calculation tree. Subtraction is the sum with a negated value and division the product with an inverted one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcNode {
+    Number(f32),
+    ///A percentage divided by 100, so `50%` is `0.5`.
+    Percentage(f32),
+    Length(Length),
+    ///An angle in degrees.
+    Angle(f32),
+    Sum(Vec<CalcNode>),
+    Product(Vec<CalcNode>),
+    Negate(Box<CalcNode>),
+    Invert(Box<CalcNode>),
+    Function(MathFunction, Vec<CalcNode>),
+}
+
+///A math function like `calc(100% - 2rem)`, simplified as far as it goes without knowing what its relative units are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calc {
+    pub root: CalcNode,
+    pub category: CalcCategory,
+    ///Whether negative results are clamped to 0, for the properties that do not accept negative values.
+    pub non_negative: bool,
+}
+
+impl Calc {
+    ///Parses a math function that resolves to `category`.
+    pub fn parse(input: &mut ValueParser, category: CalcCategory) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        let function = match value {
+            ComponentValue::Function(function) if is_math_function(&function.name) => function,
+            _ => return Err(unexpected(value, "a math function like `calc()`")),
+        };
+        let root = parse_function(function)?;
+        let percentage = category.percentage_type();
+        let found = root.calc_type(percentage).map_err(|message| {
+            CssDiagnostic::new(CssDiagnosticKind::InvalidValue, function.span, message)
+        })?;
+        if found != category.calc_type() {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                function.span,
+                format!(
+                    "expected {}, found `{}()` which is {}",
+                    category.describe(),
+                    function.name,
+                    found.describe()
+                ),
+            ));
+        }
+        Ok(Calc {
+            root: root.simplify(percentage),
+            category,
+            non_negative: false,
+        })
+    }
+
+    ///The calculation `from * (1 - progress) + to * progress`, simplified,
+    ///which is how values with different units are interpolated.
+    pub fn mix(from: CalcNode, to: CalcNode, progress: f32, category: CalcCategory) -> Self {
+        let root = CalcNode::Sum(vec![from.scale(1.0 - progress), to.scale(progress)]);
+        Calc {
+            root: root.simplify(category.percentage_type()),
+            category,
+            non_negative: false,
+        }
+    }
+
+    ///Returns the value of a calculation that does not depend on layout, or `None` if it does.
+    ///Lengths are in pixels, angles in degrees and percentages divided by 100.
+    pub fn value(&self) -> Option<f32> {
+        let percentage = self.category.percentage_type();
+        if !self.root.is_absolute(percentage) {
+            return None;
+        }
+        let value = self.root.evaluate(&|length| length.value, 1.0);
+        let value = match self.category {
+            CalcCategory::Angle => value.to_degrees(),
+            _ => value,
+        };
+        Some(self.finish(value))
+    }
+
+    ///Resolves a length to pixels, `basis` being the length that percentages are relative to.
+    pub fn to_px(&self, context: &LengthContext, basis: f32) -> f32 {
+        self.finish(self.root.evaluate(&|length| length.to_px(context), basis))
+    }
+
+    ///Clamps the result to a value that can be used, see https://www.w3.org/TR/css-values-4/#calc-ieee
+    fn finish(&self, value: f32) -> f32 {
+        let value = match value.is_nan() {
+            true => 0.0,
+            false => value.clamp(f32::MIN, f32::MAX),
+        };
+        match self.non_negative {
+            true => value.max(0.0),
+            false => value,
+        }
+    }
+}
+
+impl ToCss for Calc {
+    fn to_css(&self, out: &mut String) {
+        match &self.root {
+            CalcNode::Function(..) => self.root.write(out, false),
+            root => {
+                out.push_str("calc(");
+                root.write(out, false);
+                out.push(')');
+            }
+        }
+    }
+}
+
+impl CalcNode {
+    ///Writes the node as the inside of a `calc()`. `nested` sums and products are wrapped in parentheses.
+    fn write(&self, out: &mut String, nested: bool) {
+        let open = |out: &mut String| {
+            if nested {
+                out.push('(');
+            }
+        };
+        let close = |out: &mut String| {
+            if nested {
+                out.push(')');
+            }
+        };
+        match self {
+            CalcNode::Number(value) if value.is_nan() => out.push_str("NaN"),
+            CalcNode::Number(value) if value.is_infinite() => match *value > 0.0 {
+                true => out.push_str("infinity"),
+                false => out.push_str("-infinity"),
+            },
+            CalcNode::Number(value) => write_number(*value, out),
+            CalcNode::Percentage(value) => {
+                write_number(value * 100.0, out);
+                out.push('%');
+            }
+            CalcNode::Length(length) => length.to_css(out),
+            CalcNode::Angle(value) => {
+                write_number(*value, out);
+                out.push_str("deg");
+            }
+            CalcNode::Sum(terms) => {
+                open(out);
+                for (i, term) in terms.iter().enumerate() {
+                    match term {
+                        CalcNode::Negate(term) if i > 0 => {
+                            out.push_str(" - ");
+                            term.write(out, true);
+                        }
+                        term => {
+                            if i > 0 {
+                                out.push_str(" + ");
+                            }
+                            term.write(out, true);
+                        }
+                    }
+                }
+                close(out);
+            }
+            CalcNode::Product(factors) => {
+                open(out);
+                for (i, factor) in factors.iter().enumerate() {
+                    match factor {
+                        CalcNode::Invert(factor) if i > 0 => {
+                            out.push_str(" / ");
+                            factor.write(out, true);
+                        }
+                        factor => {
+                            if i > 0 {
+                                out.push_str(" * ");
+                            }
+                            factor.write(out, true);
+                        }
+                    }
+                }
+                close(out);
+            }
+            CalcNode::Negate(node) => {
+                open(out);
+                out.push_str("-1 * ");
+                node.write(out, true);
+                close(out);
+            }
+            CalcNode::Invert(node) => {
+                open(out);
+                out.push_str("1 / ");
+                node.write(out, true);
+                close(out);
+            }
+            CalcNode::Function(function, arguments) => {
+                out.push_str(function.name());
+                out.push('(');
+                if let MathFunction::Round(strategy) = function {
+                    out.push_str(strategy.keyword());
+                    out.push_str(", ");
+                }
+                for (i, argument) in arguments.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    argument.write(out, false);
+                }
+                out.push(')');
+            }
+        }
+    }
+}
+
+fn error(function: &Function, message: impl Into<String>) -> CssDiagnostic {
+    CssDiagnostic::new(CssDiagnosticKind::InvalidValue, function.span, message)
+}
+
+fn parse_function(function: &Function) -> Result<CalcNode, CssDiagnostic> {
+    let mut input = ValueParser::new(&function.value);
+    if function.name.eq_ignore_ascii_case("calc") {
+        let node = parse_sum(&mut input)?;
+        input.expect_exhausted()?;
+        return Ok(node);
+    }
+    let Some(mut math_function) = MathFunction::from_name(&function.name) else {
+        return Err(error(
+            function,
+            format!("`{}()` is not a math function", function.name),
+        ));
+    };
+    if let MathFunction::Round(_) = math_function
+        && let Ok(strategy) = input.try_parse(RoundingStrategy::parse)
+    {
+        math_function = MathFunction::Round(strategy);
+        input.expect_comma()?;
+    }
+    let mut arguments = vec![parse_sum(&mut input)?];
+    while !input.is_exhausted() {
+        input.expect_comma()?;
+        arguments.push(parse_sum(&mut input)?);
+    }
+    let (min, max) = math_function.arguments();
+    if !(min..=max).contains(&arguments.len()) {
+        let expected = match (min, max) {
+            (min, usize::MAX) => format!("at least {min}"),
+            (min, max) if min == max => min.to_string(),
+            (min, max) => format!("{min} or {max}"),
+        };
+        return Err(error(
+            function,
+            format!(
+                "`{}()` takes {expected} arguments, found {}",
+                function.name,
+                arguments.len()
+            ),
+        ));
+    }
+    Ok(CalcNode::Function(math_function, arguments))
+}
+
+///Parses `<calc-product> [ [ '+' | '-' ] <calc-product> ]*`.
+fn parse_sum(input: &mut ValueParser) -> Result<CalcNode, CssDiagnostic> {
+    let mut terms = vec![parse_product(input)?];
+    while let Some(operator) = parse_sum_operator(input)? {
+        let term = parse_product(input)?;
+        terms.push(match operator {
+            '-' => CalcNode::Negate(Box::new(term)),
+            _ => term,
+        });
+    }
+    Ok(match terms.len() {
+        1 => terms.remove(0),
+        _ => CalcNode::Sum(terms),
+    })
+}
+
+///Consumes a `+` or `-`, which have to have whitespace on both sides so `1px -2px` is not a subtraction.
+fn parse_sum_operator(input: &mut ValueParser) -> Result<Option<char>, CssDiagnostic> {
+    let whitespace_at = |input: &ValueParser, position: usize| {
+        input
+            .values
+            .get(position)
+            .is_some_and(ComponentValue::is_whitespace)
+    };
+    // Looking for a `*` or `/` after the previous value may already have skipped the whitespace.
+    let whitespace_before = whitespace_at(input, input.position)
+        || input
+            .position
+            .checked_sub(1)
+            .is_some_and(|v| whitespace_at(input, v));
+    let Some(value) = input.peek() else {
+        return Ok(None);
+    };
+    let operator = match value.token() {
+        Some(CSSToken::DelimToken { value: '+' }) => '+',
+        Some(CSSToken::DelimToken { value: '-' }) => '-',
+        _ => return Ok(None),
+    };
+    input.position += 1;
+    if !whitespace_before || !whitespace_at(input, input.position) {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            value.span(),
+            format!("`{operator}` needs whitespace on both sides in math functions"),
+        ));
+    }
+    Ok(Some(operator))
+}
+
+///Parses `<calc-value> [ [ '*' | '/' ] <calc-value> ]*`.
+fn parse_product(input: &mut ValueParser) -> Result<CalcNode, CssDiagnostic> {
+    let mut factors = vec![parse_calc_value(input)?];
+    loop {
+        match input.peek().and_then(|v| v.token()) {
+            Some(CSSToken::DelimToken { value: '*' }) => {
+                input.consume()?;
+                factors.push(parse_calc_value(input)?);
+            }
+            Some(CSSToken::DelimToken { value: '/' }) => {
+                input.consume()?;
+                factors.push(CalcNode::Invert(Box::new(parse_calc_value(input)?)));
+            }
+            _ => break,
+        }
+    }
+    Ok(match factors.len() {
+        1 => factors.remove(0),
+        _ => CalcNode::Product(factors),
+    })
+}
+
+///Parses a number, a dimension, a percentage, a constant, a parenthesized sum or a nested math function.
+fn parse_calc_value(input: &mut ValueParser) -> Result<CalcNode, CssDiagnostic> {
+    let value = input.consume()?;
+    let expected = "a number, a dimension, a percentage or a math function";
+    match value {
+        ComponentValue::PreservedToken(token) => match &token.token {
+            CSSToken::NumberToken { value, .. } => Ok(CalcNode::Number(*value as f32)),
+            CSSToken::PercentageToken { value, .. } => {
+                Ok(CalcNode::Percentage(*value as f32 / 100.0))
+            }
+            CSSToken::DimensionToken {
+                value: number,
+                unit,
+                ..
+            } => {
+                let number = *number as f32;
+                if let Some(unit) = LengthUnit::from_unit(unit) {
+                    return Ok(CalcNode::Length(Length {
+                        value: number,
+                        unit,
+                    }));
+                }
+                angle_to_degrees(number, unit)
+                    .map(CalcNode::Angle)
+                    .ok_or_else(|| {
+                        CssDiagnostic::new(
+                            CssDiagnosticKind::InvalidValue,
+                            token.span,
+                            format!("`{unit}` is not a unit math functions support"),
+                        )
+                    })
+            }
+            CSSToken::IdentToken { value: ident } => {
+                let constant = match ident.to_ascii_lowercase().as_str() {
+                    "e" => std::f32::consts::E,
+                    "pi" => std::f32::consts::PI,
+                    "infinity" => f32::INFINITY,
+                    "-infinity" => f32::NEG_INFINITY,
+                    "nan" => f32::NAN,
+                    _ => return Err(unexpected(value, expected)),
+                };
+                Ok(CalcNode::Number(constant))
+            }
+            _ => Err(unexpected(value, expected)),
+        },
+        ComponentValue::SimpleBlock(block)
+            if block.associated_token == CSSToken::LeftParenthesisToken =>
+        {
+            let mut input = ValueParser::new(&block.value);
+            let node = parse_sum(&mut input)?;
+            input.expect_exhausted()?;
+            Ok(node)
+        }
+        ComponentValue::Function(function) if is_math_function(&function.name) => {
+            parse_function(function)
+        }
+        _ => Err(unexpected(value, expected)),
+    }
+}
+
+impl CalcNode {
+    ///Returns the type of the node, `percentage` being the type percentages have.
+    fn calc_type(&self, percentage: CalcType) -> Result<CalcType, String> {
+        match self {
+            CalcNode::Number(_) => Ok(CalcType::NUMBER),
+            CalcNode::Percentage(_) => Ok(percentage),
+            CalcNode::Length(_) => Ok(CalcType::LENGTH),
+            CalcNode::Angle(_) => Ok(CalcType::ANGLE),
+            CalcNode::Sum(terms) => {
+                let first = terms[0].calc_type(percentage)?;
+                for term in &terms[1..] {
+                    let other = term.calc_type(percentage)?;
+                    if other != first {
+                        return Err(format!(
+                            "can not add {} and {}",
+                            first.describe(),
+                            other.describe()
+                        ));
+                    }
+                }
+                Ok(first)
+            }
+            CalcNode::Product(factors) => factors.iter().try_fold(CalcType::NUMBER, |out, v| {
+                Ok(out.multiply(v.calc_type(percentage)?))
+            }),
+            CalcNode::Negate(node) => node.calc_type(percentage),
+            CalcNode::Invert(node) => Ok(node.calc_type(percentage)?.invert()),
+            CalcNode::Function(function, arguments) => {
+                let types = arguments
+                    .iter()
+                    .map(|v| v.calc_type(percentage))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let name = function.name();
+                let first = types[0];
+                if types.iter().any(|v| *v != first) {
+                    return Err(format!("the arguments of `{name}()` have different types"));
+                }
+                let numbers = || match first {
+                    CalcType::NUMBER => Ok(CalcType::NUMBER),
+                    _ => Err(format!("`{name}()` only takes numbers")),
+                };
+                match function {
+                    MathFunction::Round(_) if types.len() == 1 && first != CalcType::NUMBER => {
+                        Err("`round()` needs a step to round anything but a number to".to_string())
+                    }
+                    MathFunction::Sign => Ok(CalcType::NUMBER),
+                    MathFunction::Sin | MathFunction::Cos | MathFunction::Tan => match first {
+                        CalcType::NUMBER | CalcType::ANGLE => Ok(CalcType::NUMBER),
+                        _ => Err(format!("`{name}()` takes a number or an angle")),
+                    },
+                    MathFunction::Asin | MathFunction::Acos | MathFunction::Atan => {
+                        numbers().map(|_| CalcType::ANGLE)
+                    }
+                    MathFunction::Atan2 => Ok(CalcType::ANGLE),
+                    MathFunction::Pow
+                    | MathFunction::Sqrt
+                    | MathFunction::Log
+                    | MathFunction::Exp => numbers(),
+                    _ => Ok(first),
+                }
+            }
+        }
+    }
+
+    ///Whether the node can be evaluated without knowing what relative lengths and percentages are relative to.
+    fn is_absolute(&self, percentage: CalcType) -> bool {
+        match self {
+            CalcNode::Number(_) | CalcNode::Angle(_) => true,
+            CalcNode::Percentage(_) => percentage != CalcType::LENGTH,
+            CalcNode::Length(length) => length.unit == LengthUnit::Px,
+            CalcNode::Sum(nodes) | CalcNode::Product(nodes) | CalcNode::Function(_, nodes) => {
+                nodes.iter().all(|v| v.is_absolute(percentage))
+            }
+            CalcNode::Negate(node) | CalcNode::Invert(node) => node.is_absolute(percentage),
+        }
+    }
+
+    ///Evaluates the node with lengths resolved by `length` and percentages multiplied with `basis`. Angles are in radians.
+    fn evaluate(&self, length: &dyn Fn(&Length) -> f32, basis: f32) -> f32 {
+        let evaluate = |node: &CalcNode| node.evaluate(length, basis);
+        match self {
+            CalcNode::Number(number) => *number,
+            CalcNode::Percentage(percentage) => percentage * basis,
+            CalcNode::Length(value) => length(value),
+            CalcNode::Angle(degrees) => degrees.to_radians(),
+            CalcNode::Sum(terms) => terms.iter().map(evaluate).sum(),
+            CalcNode::Product(factors) => factors.iter().map(evaluate).product(),
+            CalcNode::Negate(node) => -evaluate(node),
+            CalcNode::Invert(node) => 1.0 / evaluate(node),
+            CalcNode::Function(function, arguments) => {
+                let values: Vec<f32> = arguments.iter().map(evaluate).collect();
+                let a = values[0];
+                let b = values.get(1).copied();
+                match function {
+                    MathFunction::Min => values.into_iter().fold(f32::INFINITY, f32::min),
+                    MathFunction::Max => values.into_iter().fold(f32::NEG_INFINITY, f32::max),
+                    // The minimum wins over the maximum, like it does for `min-width` and `max-width`.
+                    MathFunction::Clamp => a.max(values[1].min(values[2])),
+                    MathFunction::Round(strategy) => round(*strategy, a, b.unwrap_or(1.0)),
+                    MathFunction::Mod => {
+                        let b = b.unwrap_or(f32::NAN);
+                        a - b * (a / b).floor()
+                    }
+                    MathFunction::Rem => a % b.unwrap_or(f32::NAN),
+                    MathFunction::Sin => a.sin(),
+                    MathFunction::Cos => a.cos(),
+                    MathFunction::Tan => a.tan(),
+                    MathFunction::Asin => a.asin(),
+                    MathFunction::Acos => a.acos(),
+                    MathFunction::Atan => a.atan(),
+                    MathFunction::Atan2 => a.atan2(b.unwrap_or(f32::NAN)),
+                    MathFunction::Pow => a.powf(b.unwrap_or(f32::NAN)),
+                    MathFunction::Sqrt => a.sqrt(),
+                    MathFunction::Hypot => values.iter().map(|v| v * v).sum::<f32>().sqrt(),
+                    MathFunction::Log => match b {
+                        Some(base) => a.ln() / base.ln(),
+                        None => a.ln(),
+                    },
+                    MathFunction::Exp => a.exp(),
+                    MathFunction::Abs => a.abs(),
+                    MathFunction::Sign if a == 0.0 || a.is_nan() => a,
+                    MathFunction::Sign => a.signum(),
+                }
+            }
+        }
+    }
+
+    ///Simplifies the node like https://www.w3.org/TR/css-values-4/#calc-simplification does,
+    ///folding everything that does not depend on layout and adding up values with the same unit.
+    fn simplify(self, percentage: CalcType) -> CalcNode {
+        let node = match self {
+            CalcNode::Sum(terms) => {
+                let mut out = Vec::new();
+                for term in terms {
+                    match term.simplify(percentage) {
+                        CalcNode::Sum(inner) => {
+                            inner.into_iter().for_each(|v| add_term(&mut out, v))
+                        }
+                        other => add_term(&mut out, other),
+                    }
+                }
+                match out.len() {
+                    1 => out.remove(0),
+                    _ => CalcNode::Sum(out),
+                }
+            }
+            CalcNode::Product(factors) => {
+                let mut number = 1.0;
+                let mut others = Vec::new();
+                let mut add_factor = |factor: CalcNode| match factor {
+                    CalcNode::Number(v) => number *= v,
+                    other => others.push(other),
+                };
+                for factor in factors {
+                    match factor.simplify(percentage) {
+                        CalcNode::Product(inner) => inner.into_iter().for_each(&mut add_factor),
+                        other => add_factor(other),
+                    }
+                }
+                match others.len() {
+                    0 => CalcNode::Number(number),
+                    1 => others.remove(0).scale(number),
+                    _ => {
+                        if number != 1.0 {
+                            others.insert(0, CalcNode::Number(number));
+                        }
+                        CalcNode::Product(others)
+                    }
+                }
+            }
+            CalcNode::Negate(node) => node.simplify(percentage).scale(-1.0),
+            CalcNode::Invert(node) => match node.simplify(percentage) {
+                CalcNode::Number(number) => CalcNode::Number(1.0 / number),
+                other => CalcNode::Invert(Box::new(other)),
+            },
+            CalcNode::Function(function, arguments) => CalcNode::Function(
+                function,
+                arguments
+                    .into_iter()
+                    .map(|v| v.simplify(percentage))
+                    .collect(),
+            ),
+            leaf => return leaf,
+        };
+        node.fold(percentage)
+    }
+
+    ///Replaces a node that does not depend on layout with its value.
+    fn fold(self, percentage: CalcType) -> CalcNode {
+        if !self.is_absolute(percentage) {
+            return self;
+        }
+        let value = || self.evaluate(&|length| length.value, 1.0);
+        match self.calc_type(percentage) {
+            Ok(CalcType::NUMBER) => CalcNode::Number(value()),
+            Ok(CalcType::LENGTH) => CalcNode::Length(Length::px(value())),
+            Ok(CalcType::ANGLE) => CalcNode::Angle(value().to_degrees()),
+            Ok(CalcType::PERCENTAGE) => CalcNode::Percentage(value()),
+            _ => self,
+        }
+    }
+
+    ///Multiplies the node with a number, distributing it over sums.
+    fn scale(self, factor: f32) -> CalcNode {
+        match self {
+            CalcNode::Number(v) => CalcNode::Number(v * factor),
+            CalcNode::Percentage(v) => CalcNode::Percentage(v * factor),
+            CalcNode::Angle(v) => CalcNode::Angle(v * factor),
+            CalcNode::Length(length) => CalcNode::Length(Length {
+                value: length.value * factor,
+                ..length
+            }),
+            CalcNode::Sum(terms) => {
+                CalcNode::Sum(terms.into_iter().map(|v| v.scale(factor)).collect())
+            }
+            other if factor == 1.0 => other,
+            other => CalcNode::Product(vec![CalcNode::Number(factor), other]),
+        }
+    }
+}
+
+///Adds a term to a sum, adding it to a term with the same unit if there is one.
+fn add_term(terms: &mut Vec<CalcNode>, term: CalcNode) {
+    for existing in terms.iter_mut() {
+        match (existing, &term) {
+            (CalcNode::Number(a), CalcNode::Number(b))
+            | (CalcNode::Percentage(a), CalcNode::Percentage(b))
+            | (CalcNode::Angle(a), CalcNode::Angle(b)) => {
+                *a += b;
+                return;
+            }
+            (CalcNode::Length(a), CalcNode::Length(b)) if a.unit == b.unit => {
+                a.value += b.value;
+                return;
+            }
+            _ => {}
+        }
+    }
+    terms.push(term);
+}
+
+///Rounds `value` to a multiple of `step`, see https://www.w3.org/TR/css-values-4/#round-func
+fn round(strategy: RoundingStrategy, value: f32, step: f32) -> f32 {
+    if step == 0.0 {
+        return f32::NAN;
+    }
+    if step.is_infinite() {
+        return match value.is_infinite() {
+            true => f32::NAN,
+            false => value,
+        };
+    }
+    let multiple = value / step;
+    let rounded = match strategy {
+        // Halfway values round up, towards positive infinity.
+        RoundingStrategy::Nearest => (multiple + 0.5).floor(),
+        RoundingStrategy::Up => multiple.ceil(),
+        RoundingStrategy::Down => multiple.floor(),
+        RoundingStrategy::ToZero => multiple.trunc(),
+    };
+    rounded * step
+}
@@ -0,0 +1,821 @@
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::{ComponentValue, Function};
+use crate::span::Span;
+use crate::values::{Parse, ToCss, ValueParser, unexpected, write_number};
+use spaces::{ColorSpace, convert, gamut_map_srgb};
+pub mod spaces;
+
+///A <color> of https://www.w3.org/TR/css-color-4/ and https://www.w3.org/TR/css-color-5/#color-mix
+#[derive(Debug, Clone, PartialEq)]
+pub enum Color {
+    ///The value of the `color` property of the element, resolved when the color is used.
+    CurrentColor,
+    Absolute(AbsoluteColor),
+    ///A `color-mix()` of a color that is only known once `currentcolor` is resolved.
+    Mix(Box<ColorMix>),
+}
+
+///A color with its components in a color space, see [`ColorSpace`] for what the components are.
+///Components that were `none` are 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AbsoluteColor {
+    pub space: ColorSpace,
+    pub components: [f32; 3],
+    pub alpha: f32,
+}
+
+///A color in the sRGB color space with its channels gamma encoded in `0..=1`, ready to be drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+///A color in the linear sRGB color space with its channels premultiplied by its alpha, which is what blending works on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearRgba {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+///A `color-mix()`, mixing `first` and `second` in `space`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorMix {
+    pub space: ColorSpace,
+    pub hue: HueInterpolation,
+    pub first: Color,
+    pub first_percentage: Option<f32>,
+    pub second: Color,
+    pub second_percentage: Option<f32>,
+}
+
+///How hues are interpolated, see https://www.w3.org/TR/css-color-4/#hue-interpolation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HueInterpolation {
+    #[default]
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+impl Rgba {
+    pub const fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    ///Returns the channels as bytes, which is what the renderers draw with.
+    pub fn to_rgba8(&self) -> (u8, u8, u8, u8) {
+        let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (
+            channel(self.red),
+            channel(self.green),
+            channel(self.blue),
+            channel(self.alpha),
+        )
+    }
+}
+
+impl AbsoluteColor {
+    pub const TRANSPARENT: AbsoluteColor = AbsoluteColor::new(ColorSpace::Srgb, [0.0; 3], 0.0);
+    pub const BLACK: AbsoluteColor = AbsoluteColor::new(ColorSpace::Srgb, [0.0; 3], 1.0);
+
+    pub const fn new(space: ColorSpace, components: [f32; 3], alpha: f32) -> Self {
+        Self {
+            space,
+            components,
+            alpha,
+        }
+    }
+
+    pub fn from_rgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        let channel = |v: u8| v as f32 / 255.0;
+        Self::new(
+            ColorSpace::Srgb,
+            [channel(red), channel(green), channel(blue)],
+            channel(alpha),
+        )
+    }
+
+    ///Converts the color to another color space. The result can be out of the gamut of `space`.
+    pub fn to_space(&self, space: ColorSpace) -> Self {
+        Self::new(
+            space,
+            convert(self.components, self.space, space),
+            self.alpha,
+        )
+    }
+
+    ///Returns the color in sRGB, mapping colors that sRGB can not show to the closest one it can.
+    pub fn to_rgba(&self) -> Rgba {
+        let [red, green, blue] = gamut_map_srgb(self);
+        Rgba::new(red, green, blue, self.alpha.clamp(0.0, 1.0))
+    }
+
+    ///Returns the color in premultiplied linear sRGB, mapping colors that sRGB can not show to the closest one it can.
+    pub fn to_linear_premultiplied(&self) -> LinearRgba {
+        let alpha = self.alpha.clamp(0.0, 1.0);
+        let [red, green, blue] = convert(
+            gamut_map_srgb(self),
+            ColorSpace::Srgb,
+            ColorSpace::SrgbLinear,
+        )
+        .map(|v| v * alpha);
+        LinearRgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::Absolute(AbsoluteColor::TRANSPARENT);
+
+    ///Resolves `currentcolor` to `current`, the value of the `color` property.
+    pub fn resolve(&self, current: &AbsoluteColor) -> AbsoluteColor {
+        match self {
+            Color::CurrentColor => *current,
+            Color::Absolute(color) => *color,
+            Color::Mix(mix) => mix_colors(
+                mix.space,
+                mix.hue,
+                &mix.first.resolve(current),
+                mix.first_percentage,
+                &mix.second.resolve(current),
+                mix.second_percentage,
+            ),
+        }
+    }
+}
+
+impl Parse for Color {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        match value {
+            ComponentValue::PreservedToken(token) => match &token.token {
+                CSSToken::HashToken { value: hex, .. } => parse_hex(hex).map(Color::Absolute).ok_or_else(|| {
+                    CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        token.span,
+                        format!("`#{hex}` is not a hex color, which has 3, 4, 6 or 8 hexadecimal digits"),
+                    )
+                }),
+                CSSToken::IdentToken { value: name } => {
+                    if name.eq_ignore_ascii_case("currentcolor") {
+                        return Ok(Color::CurrentColor);
+                    }
+                    if name.eq_ignore_ascii_case("transparent") {
+                        return Ok(Color::TRANSPARENT);
+                    }
+                    named_color(name)
+                        .map(|[red, green, blue]| Color::Absolute(AbsoluteColor::from_rgba8(red, green, blue, 255)))
+                        .ok_or_else(|| {
+                            CssDiagnostic::new(
+                                CssDiagnosticKind::InvalidValue,
+                                token.span,
+                                format!("`{name}` is not a named color"),
+                            )
+                        })
+                }
+                _ => Err(unexpected(value, "a color")),
+            },
+            ComponentValue::Function(function) => {
+                let mut arguments = ValueParser::new(&function.value);
+                let color = parse_color_function(function, &mut arguments)?;
+                arguments.expect_exhausted()?;
+                Ok(color)
+            }
+            _ => Err(unexpected(value, "a color")),
+        }
+    }
+}
+
+impl ToCss for Color {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            Color::CurrentColor => out.push_str("currentcolor"),
+            Color::Absolute(color) => color.to_css(out),
+            Color::Mix(mix) => {
+                out.push_str("color-mix(in ");
+                out.push_str(mix.space.name());
+                let hue = match mix.hue {
+                    HueInterpolation::Shorter => "",
+                    HueInterpolation::Longer => " longer hue",
+                    HueInterpolation::Increasing => " increasing hue",
+                    HueInterpolation::Decreasing => " decreasing hue",
+                };
+                out.push_str(hue);
+                for (color, percentage) in [
+                    (&mix.first, mix.first_percentage),
+                    (&mix.second, mix.second_percentage),
+                ] {
+                    out.push_str(", ");
+                    color.to_css(out);
+                    if let Some(percentage) = percentage {
+                        out.push(' ');
+                        write_number(percentage * 100.0, out);
+                        out.push('%');
+                    }
+                }
+                out.push(')');
+            }
+        }
+    }
+}
+
+impl ToCss for AbsoluteColor {
+    ///Writes the color in its own color space, so nothing is lost to rounding or gamut mapping.
+    fn to_css(&self, out: &mut String) {
+        let mut components = self.components;
+        match self.space {
+            space if space.is_predefined() => {
+                out.push_str("color(");
+                out.push_str(space.name());
+                out.push(' ');
+            }
+            space => {
+                // The saturation, lightness, whiteness and blackness are numbers up to 100 in `hsl()` and `hwb()`.
+                if matches!(space, ColorSpace::Hsl | ColorSpace::Hwb) {
+                    components[1] *= 100.0;
+                    components[2] *= 100.0;
+                }
+                out.push_str(space.name());
+                out.push('(');
+            }
+        }
+        for (i, component) in components.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            write_number(*component, out);
+        }
+        if self.alpha != 1.0 {
+            out.push_str(" / ");
+            write_number(self.alpha, out);
+        }
+        out.push(')');
+    }
+}
+
+///Parses the digits of a hex color like `#f80` or `#ff8800cc`.
+fn parse_hex(hex: &str) -> Option<AbsoluteColor> {
+    if !hex.chars().all(|v| v.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let [red, green, blue, alpha] = match hex.len() {
+        3 | 4 => {
+            let double = |i: usize| digit(i).map(|v| v * 17);
+            let alpha = if hex.len() == 4 { double(3)? } else { 255 };
+            [double(0)?, double(1)?, double(2)?, alpha]
+        }
+        6 | 8 => {
+            let alpha = if hex.len() == 8 { pair(6)? } else { 255 };
+            [pair(0)?, pair(2)?, pair(4)?, alpha]
+        }
+        _ => return None,
+    };
+    Some(AbsoluteColor::from_rgba8(red, green, blue, alpha))
+}
+
+///How a component of a color function is written.
+#[derive(Clone, Copy)]
+enum Channel {
+    ///A hue, as a number of degrees or an angle.
+    Hue,
+    ///A number or a percentage, `percentage` being the value of `100%` and `number` what numbers are multiplied with.
+    Component { percentage: f32, number: f32 },
+}
+
+impl Channel {
+    const fn new(percentage: f32, number: f32) -> Self {
+        Channel::Component { percentage, number }
+    }
+}
+
+fn parse_color_function(
+    function: &Function,
+    input: &mut ValueParser,
+) -> Result<Color, CssDiagnostic> {
+    let name = function.name.to_ascii_lowercase();
+    let rgb = Channel::new(1.0, 1.0 / 255.0);
+    let (space, channels, legacy) = match name.as_str() {
+        "rgb" | "rgba" => (ColorSpace::Srgb, [rgb; 3], true),
+        "hsl" | "hsla" => (
+            ColorSpace::Hsl,
+            [
+                Channel::Hue,
+                Channel::new(1.0, 0.01),
+                Channel::new(1.0, 0.01),
+            ],
+            true,
+        ),
+        "hwb" => (
+            ColorSpace::Hwb,
+            [
+                Channel::Hue,
+                Channel::new(1.0, 0.01),
+                Channel::new(1.0, 0.01),
+            ],
+            false,
+        ),
+        "lab" => (
+            ColorSpace::Lab,
+            [
+                Channel::new(100.0, 1.0),
+                Channel::new(125.0, 1.0),
+                Channel::new(125.0, 1.0),
+            ],
+            false,
+        ),
+        "lch" => (
+            ColorSpace::Lch,
+            [
+                Channel::new(100.0, 1.0),
+                Channel::new(150.0, 1.0),
+                Channel::Hue,
+            ],
+            false,
+        ),
+        "oklab" => (
+            ColorSpace::Oklab,
+            [
+                Channel::new(1.0, 1.0),
+                Channel::new(0.4, 1.0),
+                Channel::new(0.4, 1.0),
+            ],
+            false,
+        ),
+        "oklch" => (
+            ColorSpace::Oklch,
+            [Channel::new(1.0, 1.0), Channel::new(0.4, 1.0), Channel::Hue],
+            false,
+        ),
+        "color" => {
+            let (ident, span) = input.expect_ident()?;
+            let space = ColorSpace::from_name(ident)
+                .filter(ColorSpace::is_predefined)
+                .ok_or_else(|| {
+                    CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        span,
+                        format!("`{ident}` is not a color space `color()` accepts, like `display-p3` or `xyz`"),
+                    )
+                })?;
+            (space, [Channel::new(1.0, 1.0); 3], false)
+        }
+        "color-mix" => return parse_color_mix(input),
+        _ => {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                function.span,
+                format!("expected a color, found the function `{}()`", function.name),
+            ));
+        }
+    };
+    let mut components = [0.0; 3];
+    components[0] = parse_channel(input, channels[0])?;
+    // The legacy syntax separates everything with commas, and only `rgb()` and `hsl()` have it.
+    let legacy = legacy && input.try_parse(ValueParser::expect_comma).is_ok();
+    for i in 1..3 {
+        if legacy && i == 2 {
+            input.expect_comma()?;
+        }
+        components[i] = parse_channel(input, channels[i])?;
+    }
+    let has_alpha = match legacy {
+        true => input.try_parse(ValueParser::expect_comma).is_ok(),
+        false => input.try_parse(|v| v.expect_delim('/')).is_ok(),
+    };
+    let alpha = match has_alpha {
+        true => parse_alpha(input)?,
+        false => 1.0,
+    };
+    Ok(Color::Absolute(AbsoluteColor::new(
+        space,
+        clamp_components(space, components),
+        alpha,
+    )))
+}
+
+///Clamps the components that are clamped when a color is parsed, like the channels of `rgb()` and the lightness of `lab()`.
+fn clamp_components(space: ColorSpace, [a, b, c]: [f32; 3]) -> [f32; 3] {
+    match space {
+        ColorSpace::Srgb => [a, b, c].map(|v| v.clamp(0.0, 1.0)),
+        ColorSpace::Hsl => [a, b.max(0.0), c.clamp(0.0, 1.0)],
+        ColorSpace::Hwb => [a, b.clamp(0.0, 1.0), c.clamp(0.0, 1.0)],
+        ColorSpace::Lab => [a.clamp(0.0, 100.0), b, c],
+        ColorSpace::Lch => [a.clamp(0.0, 100.0), b.max(0.0), c],
+        ColorSpace::Oklab => [a.clamp(0.0, 1.0), b, c],
+        ColorSpace::Oklch => [a.clamp(0.0, 1.0), b.max(0.0), c],
+        _ => [a, b, c],
+    }
+}
+
+fn parse_channel(input: &mut ValueParser, channel: Channel) -> Result<f32, CssDiagnostic> {
+    if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+        return Ok(0.0);
+    }
+    let value = input.peek();
+    let out = match channel {
+        Channel::Hue => input
+            .try_parse(ValueParser::expect_number)
+            .or_else(|_| input.expect_angle()),
+        Channel::Component { percentage, number } => input
+            .try_parse(ValueParser::expect_number)
+            .map(|v| v * number)
+            .or_else(|_| input.expect_percentage().map(|v| v * percentage)),
+    };
+    out.map_err(|error| match value {
+        Some(ComponentValue::Function(_)) | None => error,
+        Some(value) if matches!(channel, Channel::Hue) => unexpected(value, "a hue like `120deg`"),
+        Some(value) => unexpected(value, "a number or a percentage"),
+    })
+}
+
+///Parses an <alpha-value>, a number or a percentage clamped to `0..=1`.
+pub(crate) fn parse_alpha(input: &mut ValueParser) -> Result<f32, CssDiagnostic> {
+    if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+        return Ok(0.0);
+    }
+    let value = input.peek();
+    let alpha = input
+        .expect_number_or_percentage()
+        .map_err(|error| match value {
+            Some(value) if !matches!(value, ComponentValue::Function(_)) => {
+                unexpected(value, "an alpha value")
+            }
+            _ => error,
+        })?;
+    Ok(alpha.clamp(0.0, 1.0))
+}
+
+///Parses the arguments of `color-mix(in <space> [<hue-interpolation-method>]?, <color> <percentage>?, <color> <percentage>?)`.
+fn parse_color_mix(input: &mut ValueParser) -> Result<Color, CssDiagnostic> {
+    input.expect_keyword("in")?;
+    let (ident, span) = input.expect_ident()?;
+    let space = ColorSpace::from_name(ident).ok_or_else(|| {
+        CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            format!("`{ident}` is not a color space, like `srgb` or `oklch`"),
+        )
+    })?;
+    let mut hue = HueInterpolation::default();
+    if space.hue_index().is_some()
+        && let Ok(method) = input.try_parse(|input| {
+            let (ident, span) = input.expect_ident()?;
+            let method = match ident.to_ascii_lowercase().as_str() {
+                "shorter" => HueInterpolation::Shorter,
+                "longer" => HueInterpolation::Longer,
+                "increasing" => HueInterpolation::Increasing,
+                "decreasing" => HueInterpolation::Decreasing,
+                _ => {
+                    return Err(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        span,
+                        "",
+                    ));
+                }
+            };
+            input.expect_keyword("hue")?;
+            Ok(method)
+        })
+    {
+        hue = method;
+    }
+    input.expect_comma()?;
+    let (first, first_percentage) = parse_mix_component(input)?;
+    input.expect_comma()?;
+    let (second, second_percentage) = parse_mix_component(input)?;
+
+    if let (Some(first), Some(second)) = (first_percentage, second_percentage)
+        && first + second == 0.0
+    {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            Span::default(),
+            "the percentages of `color-mix()` can not both be 0%",
+        ));
+    }
+    let mix = ColorMix {
+        space,
+        hue,
+        first,
+        first_percentage,
+        second,
+        second_percentage,
+    };
+    Ok(match (&mix.first, &mix.second) {
+        (Color::Absolute(first), Color::Absolute(second)) => Color::Absolute(mix_colors(
+            space,
+            hue,
+            first,
+            first_percentage,
+            second,
+            second_percentage,
+        )),
+        _ => Color::Mix(Box::new(mix)),
+    })
+}
+
+///Parses `<color> && <percentage [0,100]>?`.
+fn parse_mix_component(input: &mut ValueParser) -> Result<(Color, Option<f32>), CssDiagnostic> {
+    let percentage_next = |input: &mut ValueParser| {
+        matches!(
+            input.peek().and_then(|v| v.token()),
+            Some(CSSToken::PercentageToken { .. })
+        )
+    };
+    let mut percentage = None;
+    if percentage_next(input) {
+        percentage = Some(parse_mix_percentage(input)?);
+    }
+    let color = Color::parse(input)?;
+    if percentage.is_none() && percentage_next(input) {
+        percentage = Some(parse_mix_percentage(input)?);
+    }
+    Ok((color, percentage))
+}
+
+fn parse_mix_percentage(input: &mut ValueParser) -> Result<f32, CssDiagnostic> {
+    let span = input.peek().map(|v| v.span()).unwrap_or_default();
+    let percentage = input.expect_percentage()?;
+    if !(0.0..=1.0).contains(&percentage) {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            "the percentages of `color-mix()` are between 0% and 100%",
+        ));
+    }
+    Ok(percentage)
+}
+
+///Mixes two colors like https://www.w3.org/TR/css-color-5/#color-mix-result does. The percentages are in `0..=1`.
+pub fn mix_colors(
+    space: ColorSpace,
+    hue: HueInterpolation,
+    first: &AbsoluteColor,
+    first_percentage: Option<f32>,
+    second: &AbsoluteColor,
+    second_percentage: Option<f32>,
+) -> AbsoluteColor {
+    let (p1, p2) = match (first_percentage, second_percentage) {
+        (None, None) => (0.5, 0.5),
+        (Some(p1), None) => (p1, 1.0 - p1),
+        (None, Some(p2)) => (1.0 - p2, p2),
+        (Some(p1), Some(p2)) => (p1, p2),
+    };
+    let sum = p1 + p2;
+    if sum <= 0.0 {
+        return AbsoluteColor::TRANSPARENT.to_space(space);
+    }
+    // Percentages that add up to less than 100% make the result transparent by the difference.
+    let alpha_multiplier = sum.min(1.0);
+    let mut out = interpolate(space, hue, first, second, p2 / sum);
+    out.alpha *= alpha_multiplier;
+    out
+}
+
+///Interpolates between two colors in `space` with premultiplied alpha, `progress` going from 0 at `from` to 1 at `to`.
+pub fn interpolate(
+    space: ColorSpace,
+    hue: HueInterpolation,
+    from: &AbsoluteColor,
+    to: &AbsoluteColor,
+    progress: f32,
+) -> AbsoluteColor {
+    let mut a = from.to_space(space);
+    let mut b = to.to_space(space);
+    let hue_index = space.hue_index();
+    if let Some(i) = hue_index {
+        // The hue of a gray is powerless, so it takes on the hue of the other color.
+        let achromatic = |c: &AbsoluteColor| match space {
+            ColorSpace::Hwb => c.components[1] + c.components[2] >= 1.0,
+            ColorSpace::Hsl => {
+                c.components[1].abs() < 1e-4 || !(1e-4..=1.0 - 1e-4).contains(&c.components[2])
+            }
+            _ => c.components[1].abs() < 1e-4,
+        };
+        match (achromatic(&a), achromatic(&b)) {
+            (true, false) => a.components[i] = b.components[i],
+            (false, true) => b.components[i] = a.components[i],
+            _ => {}
+        }
+        let (h1, h2) = fix_hues(
+            a.components[i].rem_euclid(360.0),
+            b.components[i].rem_euclid(360.0),
+            hue,
+        );
+        a.components[i] = h1;
+        b.components[i] = h2;
+    }
+    let lerp = |x: f32, y: f32| x + (y - x) * progress;
+    let alpha = lerp(a.alpha, b.alpha);
+    let components = std::array::from_fn(|i| {
+        if Some(i) == hue_index {
+            lerp(a.components[i], b.components[i]).rem_euclid(360.0)
+        } else if alpha == 0.0 {
+            lerp(a.components[i], b.components[i])
+        } else {
+            lerp(a.components[i] * a.alpha, b.components[i] * b.alpha) / alpha
+        }
+    });
+    AbsoluteColor::new(space, components, alpha)
+}
+
+///Adjusts two hues in `0..360` so interpolating between them takes the arc `method` asks for.
+fn fix_hues(mut h1: f32, mut h2: f32, method: HueInterpolation) -> (f32, f32) {
+    let difference = h2 - h1;
+    match method {
+        HueInterpolation::Shorter if difference > 180.0 => h1 += 360.0,
+        HueInterpolation::Shorter if difference < -180.0 => h2 += 360.0,
+        HueInterpolation::Longer if difference > 0.0 && difference < 180.0 => h1 += 360.0,
+        HueInterpolation::Longer if difference > -180.0 && difference <= 0.0 => h2 += 360.0,
+        HueInterpolation::Increasing if h2 < h1 => h2 += 360.0,
+        HueInterpolation::Decreasing if h1 < h2 => h1 += 360.0,
+        _ => {}
+    }
+    (h1, h2)
+}
+
+///Looks up a named color of https://www.w3.org/TR/css-color-4/#named-colors
+pub fn named_color(name: &str) -> Option<[u8; 3]> {
+    NAMED_COLORS
+        .iter()
+        .find(|(v, _)| v.eq_ignore_ascii_case(name))
+        .map(|(_, rgb)| *rgb)
+}
+
+///Finds the shortest name of a color, if it has one.
+pub fn color_name(rgb: [u8; 3]) -> Option<&'static str> {
+    NAMED_COLORS
+        .iter()
+        .filter(|(_, v)| *v == rgb)
+        .map(|(name, _)| *name)
+        .min_by_key(|v| v.len())
+}
+
+static NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("rebeccapurple", [102, 51, 153]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
@@ -0,0 +1,531 @@
+//! The color spaces of https://www.w3.org/TR/css-color-4/ and the conversions between them.
+//! Every space converts through CIE XYZ, using the matrices of https://www.w3.org/TR/css-color-4/#color-conversion-code
+use crate::values::color::AbsoluteColor;
+
+type Matrix = [[f32; 3]; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    Srgb,
+    SrgbLinear,
+    DisplayP3,
+    A98Rgb,
+    ProphotoRgb,
+    Rec2020,
+    XyzD50,
+    XyzD65,
+    ///CIE Lab, relative to a D50 white point.
+    Lab,
+    ///The polar form of [`ColorSpace::Lab`], as lightness, chroma and hue in degrees.
+    Lch,
+    Oklab,
+    ///The polar form of [`ColorSpace::Oklab`], as lightness, chroma and hue in degrees.
+    Oklch,
+    ///The cylindrical form of sRGB as hue in degrees, saturation and lightness in `0..=1`.
+    Hsl,
+    ///The cylindrical form of sRGB as hue in degrees, whiteness and blackness in `0..=1`.
+    Hwb,
+}
+
+impl ColorSpace {
+    const NAMES: &[(&str, ColorSpace)] = &[
+        ("srgb", ColorSpace::Srgb),
+        ("srgb-linear", ColorSpace::SrgbLinear),
+        ("display-p3", ColorSpace::DisplayP3),
+        ("a98-rgb", ColorSpace::A98Rgb),
+        ("prophoto-rgb", ColorSpace::ProphotoRgb),
+        ("rec2020", ColorSpace::Rec2020),
+        ("xyz", ColorSpace::XyzD65),
+        ("xyz-d50", ColorSpace::XyzD50),
+        ("xyz-d65", ColorSpace::XyzD65),
+        ("lab", ColorSpace::Lab),
+        ("lch", ColorSpace::Lch),
+        ("oklab", ColorSpace::Oklab),
+        ("oklch", ColorSpace::Oklch),
+        ("hsl", ColorSpace::Hsl),
+        ("hwb", ColorSpace::Hwb),
+    ];
+
+    ///Looks up a color space by the name `color()` and `color-mix()` use for it.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(v, _)| name.eq_ignore_ascii_case(v))
+            .map(|(_, space)| *space)
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, space)| space == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default()
+    }
+
+    ///Whether the space has a hue component, which is always the last one for [`ColorSpace::Lch`] and [`ColorSpace::Oklch`]
+    ///and the first one for [`ColorSpace::Hsl`] and [`ColorSpace::Hwb`].
+    pub fn hue_index(&self) -> Option<usize> {
+        match self {
+            ColorSpace::Lch | ColorSpace::Oklch => Some(2),
+            ColorSpace::Hsl | ColorSpace::Hwb => Some(0),
+            _ => None,
+        }
+    }
+
+    ///Whether `color()` accepts the space, which is every space with rectangular components.
+    pub fn is_predefined(&self) -> bool {
+        matches!(
+            self,
+            ColorSpace::Srgb
+                | ColorSpace::SrgbLinear
+                | ColorSpace::DisplayP3
+                | ColorSpace::A98Rgb
+                | ColorSpace::ProphotoRgb
+                | ColorSpace::Rec2020
+                | ColorSpace::XyzD50
+                | ColorSpace::XyzD65
+        )
+    }
+}
+
+///Converts the components of a color from `from` to `to`.
+pub fn convert(components: [f32; 3], from: ColorSpace, to: ColorSpace) -> [f32; 3] {
+    if from == to {
+        return components;
+    }
+    from_xyz_d65(to_xyz_d65(components, from), to)
+}
+
+fn to_xyz_d65(c: [f32; 3], from: ColorSpace) -> [f32; 3] {
+    match from {
+        ColorSpace::Srgb => multiply(&LINEAR_SRGB_TO_XYZ, c.map(srgb_to_linear)),
+        ColorSpace::SrgbLinear => multiply(&LINEAR_SRGB_TO_XYZ, c),
+        ColorSpace::DisplayP3 => multiply(&LINEAR_P3_TO_XYZ, c.map(srgb_to_linear)),
+        ColorSpace::A98Rgb => multiply(&LINEAR_A98_TO_XYZ, c.map(a98_to_linear)),
+        ColorSpace::ProphotoRgb => multiply(
+            &D50_TO_D65,
+            multiply(&LINEAR_PROPHOTO_TO_XYZ_D50, c.map(prophoto_to_linear)),
+        ),
+        ColorSpace::Rec2020 => multiply(&LINEAR_REC2020_TO_XYZ, c.map(rec2020_to_linear)),
+        ColorSpace::XyzD50 => multiply(&D50_TO_D65, c),
+        ColorSpace::XyzD65 => c,
+        ColorSpace::Lab => multiply(&D50_TO_D65, lab_to_xyz_d50(c)),
+        ColorSpace::Lch => to_xyz_d65(polar_to_rectangular(c), ColorSpace::Lab),
+        ColorSpace::Oklab => oklab_to_xyz(c),
+        ColorSpace::Oklch => oklab_to_xyz(polar_to_rectangular(c)),
+        ColorSpace::Hsl => to_xyz_d65(hsl_to_srgb(c), ColorSpace::Srgb),
+        ColorSpace::Hwb => to_xyz_d65(hwb_to_srgb(c), ColorSpace::Srgb),
+    }
+}
+
+fn from_xyz_d65(c: [f32; 3], to: ColorSpace) -> [f32; 3] {
+    match to {
+        ColorSpace::Srgb => multiply(&XYZ_TO_LINEAR_SRGB, c).map(linear_to_srgb),
+        ColorSpace::SrgbLinear => multiply(&XYZ_TO_LINEAR_SRGB, c),
+        ColorSpace::DisplayP3 => multiply(&XYZ_TO_LINEAR_P3, c).map(linear_to_srgb),
+        ColorSpace::A98Rgb => multiply(&XYZ_TO_LINEAR_A98, c).map(linear_to_a98),
+        ColorSpace::ProphotoRgb => {
+            multiply(&XYZ_D50_TO_LINEAR_PROPHOTO, multiply(&D65_TO_D50, c)).map(linear_to_prophoto)
+        }
+        ColorSpace::Rec2020 => multiply(&XYZ_TO_LINEAR_REC2020, c).map(linear_to_rec2020),
+        ColorSpace::XyzD50 => multiply(&D65_TO_D50, c),
+        ColorSpace::XyzD65 => c,
+        ColorSpace::Lab => xyz_d50_to_lab(multiply(&D65_TO_D50, c)),
+        ColorSpace::Lch => rectangular_to_polar(from_xyz_d65(c, ColorSpace::Lab)),
+        ColorSpace::Oklab => xyz_to_oklab(c),
+        ColorSpace::Oklch => rectangular_to_polar(xyz_to_oklab(c)),
+        ColorSpace::Hsl => srgb_to_hsl(from_xyz_d65(c, ColorSpace::Srgb)),
+        ColorSpace::Hwb => srgb_to_hwb(from_xyz_d65(c, ColorSpace::Srgb)),
+    }
+}
+
+fn multiply(m: &Matrix, c: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * c[0] + row[1] * c[1] + row[2] * c[2])
+}
+
+///Applies a transfer function to the magnitude of a component, keeping its sign for out of gamut values.
+fn signed(c: f32, f: impl Fn(f32) -> f32) -> f32 {
+    f(c.abs()).copysign(c)
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    signed(c, |c| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    signed(c, |c| {
+        if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    })
+}
+
+fn a98_to_linear(c: f32) -> f32 {
+    signed(c, |c| c.powf(563.0 / 256.0))
+}
+
+fn linear_to_a98(c: f32) -> f32 {
+    signed(c, |c| c.powf(256.0 / 563.0))
+}
+
+fn prophoto_to_linear(c: f32) -> f32 {
+    signed(c, |c| {
+        if c <= 16.0 / 512.0 {
+            c / 16.0
+        } else {
+            c.powf(1.8)
+        }
+    })
+}
+
+fn linear_to_prophoto(c: f32) -> f32 {
+    signed(c, |c| {
+        if c < 1.0 / 512.0 {
+            c * 16.0
+        } else {
+            c.powf(1.0 / 1.8)
+        }
+    })
+}
+
+const REC2020_ALPHA: f32 = 1.099_296_8;
+const REC2020_BETA: f32 = 0.018_053_97;
+
+fn rec2020_to_linear(c: f32) -> f32 {
+    signed(c, |c| {
+        if c < REC2020_BETA * 4.5 {
+            c / 4.5
+        } else {
+            ((c + REC2020_ALPHA - 1.0) / REC2020_ALPHA).powf(1.0 / 0.45)
+        }
+    })
+}
+
+fn linear_to_rec2020(c: f32) -> f32 {
+    signed(c, |c| {
+        if c < REC2020_BETA {
+            c * 4.5
+        } else {
+            REC2020_ALPHA * c.powf(0.45) - (REC2020_ALPHA - 1.0)
+        }
+    })
+}
+
+const D50_WHITE: [f32; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+const LAB_EPSILON: f32 = 216.0 / 24389.0;
+const LAB_KAPPA: f32 = 24389.0 / 27.0;
+
+fn xyz_d50_to_lab(c: [f32; 3]) -> [f32; 3] {
+    let f = |i: usize| {
+        let v = c[i] / D50_WHITE[i];
+        if v > LAB_EPSILON {
+            v.cbrt()
+        } else {
+            (LAB_KAPPA * v + 16.0) / 116.0
+        }
+    };
+    let [x, y, z] = [f(0), f(1), f(2)];
+    [116.0 * y - 16.0, 500.0 * (x - y), 200.0 * (y - z)]
+}
+
+fn lab_to_xyz_d50([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let fy = (l + 16.0) / 116.0;
+    let fx = a / 500.0 + fy;
+    let fz = fy - b / 200.0;
+    let x = if fx.powi(3) > LAB_EPSILON {
+        fx.powi(3)
+    } else {
+        (116.0 * fx - 16.0) / LAB_KAPPA
+    };
+    let y = if l > LAB_KAPPA * LAB_EPSILON {
+        fy.powi(3)
+    } else {
+        l / LAB_KAPPA
+    };
+    let z = if fz.powi(3) > LAB_EPSILON {
+        fz.powi(3)
+    } else {
+        (116.0 * fz - 16.0) / LAB_KAPPA
+    };
+    [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]]
+}
+
+fn xyz_to_oklab(c: [f32; 3]) -> [f32; 3] {
+    multiply(&LMS_TO_OKLAB, multiply(&XYZ_TO_LMS, c).map(f32::cbrt))
+}
+
+fn oklab_to_xyz(c: [f32; 3]) -> [f32; 3] {
+    multiply(&LMS_TO_XYZ, multiply(&OKLAB_TO_LMS, c).map(|v| v.powi(3)))
+}
+
+fn rectangular_to_polar([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let chroma = (a * a + b * b).sqrt();
+    let hue = b.atan2(a).to_degrees().rem_euclid(360.0);
+    [l, chroma, hue]
+}
+
+fn polar_to_rectangular([l, chroma, hue]: [f32; 3]) -> [f32; 3] {
+    let hue = hue.to_radians();
+    [l, chroma * hue.cos(), chroma * hue.sin()]
+}
+
+fn hsl_to_srgb([hue, saturation, lightness]: [f32; 3]) -> [f32; 3] {
+    let hue = hue.rem_euclid(360.0);
+    let f = |n: f32| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    [f(0.0), f(8.0), f(4.0)]
+}
+
+fn srgb_to_hsl([red, green, blue]: [f32; 3]) -> [f32; 3] {
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let lightness = (min + max) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return [0.0, 0.0, lightness];
+    }
+    let saturation = if lightness == 0.0 || lightness == 1.0 {
+        0.0
+    } else {
+        (max - lightness) / lightness.min(1.0 - lightness)
+    };
+    let hue = if max == red {
+        (green - blue) / delta + if green < blue { 6.0 } else { 0.0 }
+    } else if max == green {
+        (blue - red) / delta + 2.0
+    } else {
+        (red - green) / delta + 4.0
+    };
+    [hue * 60.0, saturation, lightness]
+}
+
+fn hwb_to_srgb([hue, whiteness, blackness]: [f32; 3]) -> [f32; 3] {
+    if whiteness + blackness >= 1.0 {
+        let gray = whiteness / (whiteness + blackness);
+        return [gray; 3];
+    }
+    hsl_to_srgb([hue, 1.0, 0.5]).map(|v| v * (1.0 - whiteness - blackness) + whiteness)
+}
+
+fn srgb_to_hwb(c: [f32; 3]) -> [f32; 3] {
+    let [hue, ..] = srgb_to_hsl(c);
+    let whiteness = c[0].min(c[1]).min(c[2]);
+    let blackness = 1.0 - c[0].max(c[1]).max(c[2]);
+    [hue, whiteness, blackness]
+}
+
+///How far apart two colors look, see https://www.w3.org/TR/css-color-4/#color-difference-OK
+fn delta_e_ok(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let [l1, a1, b1] = a;
+    let [l2, a2, b2] = b;
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+///Maps a color into the sRGB gamut with https://www.w3.org/TR/css-color-4/#binsearch, which lowers its OKLCh chroma
+///until clipping it no longer makes a visible difference. Returns the sRGB components.
+pub fn gamut_map_srgb(color: &AbsoluteColor) -> [f32; 3] {
+    const JND: f32 = 0.02;
+    const EPSILON: f32 = 0.0001;
+    let in_gamut = |c: [f32; 3]| c.iter().all(|v| (-EPSILON..=1.0 + EPSILON).contains(v));
+    let clip = |c: [f32; 3]| c.map(|v| v.clamp(0.0, 1.0));
+
+    let srgb = convert(color.components, color.space, ColorSpace::Srgb);
+    if in_gamut(srgb) {
+        return clip(srgb);
+    }
+    let mut current = convert(color.components, color.space, ColorSpace::Oklch);
+    if current[0] >= 1.0 {
+        return [1.0; 3];
+    }
+    if current[0] <= 0.0 {
+        return [0.0; 3];
+    }
+    let to_srgb = |c: [f32; 3]| convert(c, ColorSpace::Oklch, ColorSpace::Srgb);
+    let difference = |clipped: [f32; 3], current: [f32; 3]| {
+        delta_e_ok(
+            convert(clipped, ColorSpace::Srgb, ColorSpace::Oklab),
+            convert(current, ColorSpace::Oklch, ColorSpace::Oklab),
+        )
+    };
+    let mut clipped = clip(to_srgb(current));
+    if difference(clipped, current) < JND {
+        return clipped;
+    }
+    let mut min = 0.0;
+    let mut max = current[1];
+    let mut min_in_gamut = true;
+    while max - min > EPSILON {
+        let chroma = (min + max) / 2.0;
+        current[1] = chroma;
+        let srgb = to_srgb(current);
+        if min_in_gamut && in_gamut(srgb) {
+            min = chroma;
+            continue;
+        }
+        clipped = clip(srgb);
+        let e = difference(clipped, current);
+        if e < JND {
+            if JND - e < EPSILON {
+                return clipped;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    clipped
+}
+
+const LINEAR_SRGB_TO_XYZ: Matrix = [
+    [506752.0 / 1228815.0, 87881.0 / 245763.0, 12673.0 / 70218.0],
+    [87098.0 / 409605.0, 175762.0 / 245763.0, 12673.0 / 175545.0],
+    [7918.0 / 409605.0, 87881.0 / 737289.0, 1001167.0 / 1053270.0],
+];
+const XYZ_TO_LINEAR_SRGB: Matrix = [
+    [12831.0 / 3959.0, -329.0 / 214.0, -1974.0 / 3959.0],
+    [
+        -851781.0 / 878810.0,
+        1648619.0 / 878810.0,
+        36519.0 / 878810.0,
+    ],
+    [705.0 / 12673.0, -2585.0 / 12673.0, 705.0 / 667.0],
+];
+const LINEAR_P3_TO_XYZ: Matrix = [
+    [
+        608311.0 / 1250200.0,
+        189793.0 / 714400.0,
+        198249.0 / 1000160.0,
+    ],
+    [
+        35783.0 / 156275.0,
+        247089.0 / 357200.0,
+        198249.0 / 2500400.0,
+    ],
+    [0.0, 32229.0 / 714400.0, 5220557.0 / 5000800.0],
+];
+const XYZ_TO_LINEAR_P3: Matrix = [
+    [
+        446124.0 / 178915.0,
+        -333277.0 / 357830.0,
+        -72051.0 / 178915.0,
+    ],
+    [-14852.0 / 17905.0, 63121.0 / 35810.0, 423.0 / 17905.0],
+    [11844.0 / 330415.0, -50337.0 / 660830.0, 316169.0 / 330415.0],
+];
+const LINEAR_A98_TO_XYZ: Matrix = [
+    [
+        573536.0 / 994567.0,
+        263643.0 / 1420810.0,
+        187206.0 / 994567.0,
+    ],
+    [
+        591459.0 / 1989134.0,
+        6239551.0 / 9945670.0,
+        374412.0 / 4972835.0,
+    ],
+    [
+        53769.0 / 1989134.0,
+        351524.0 / 4972835.0,
+        4929758.0 / 4972835.0,
+    ],
+];
+const XYZ_TO_LINEAR_A98: Matrix = [
+    [
+        1829569.0 / 896150.0,
+        -506331.0 / 896150.0,
+        -308931.0 / 896150.0,
+    ],
+    [
+        -851781.0 / 878810.0,
+        1648619.0 / 878810.0,
+        36519.0 / 878810.0,
+    ],
+    [
+        16779.0 / 1248040.0,
+        -147721.0 / 1248040.0,
+        1266979.0 / 1248040.0,
+    ],
+];
+const LINEAR_PROPHOTO_TO_XYZ_D50: Matrix = [
+    [0.797_766_6, 0.135_181_3, 0.031_347_73],
+    [0.288_074_83, 0.711_835_2, 0.000_089_936_94],
+    [0.0, 0.0, 0.825_104_6],
+];
+const XYZ_D50_TO_LINEAR_PROPHOTO: Matrix = [
+    [1.345_786_9, -0.255_572_1, -0.051_101_865],
+    [-0.544_630_7, 1.508_247_7, 0.020_527_447],
+    [0.0, 0.0, 1.211_967_5],
+];
+const LINEAR_REC2020_TO_XYZ: Matrix = [
+    [
+        63426534.0 / 99577255.0,
+        20160776.0 / 139408157.0,
+        47086771.0 / 278816314.0,
+    ],
+    [
+        26158966.0 / 99577255.0,
+        472592308.0 / 697040785.0,
+        8267143.0 / 139408157.0,
+    ],
+    [0.0, 19567812.0 / 697040785.0, 295819943.0 / 278816314.0],
+];
+const XYZ_TO_LINEAR_REC2020: Matrix = [
+    [
+        30757411.0 / 17917100.0,
+        -6372589.0 / 17917100.0,
+        -4539589.0 / 17917100.0,
+    ],
+    [
+        -19765991.0 / 29648200.0,
+        47925759.0 / 29648200.0,
+        467509.0 / 29648200.0,
+    ],
+    [
+        792561.0 / 44930125.0,
+        -1921689.0 / 44930125.0,
+        42328811.0 / 44930125.0,
+    ],
+];
+///The Bradford chromatic adaptation from the D65 white point to D50.
+const D65_TO_D50: Matrix = [
+    [1.047_929_8, 0.022_946_87, -0.050_192_266],
+    [0.029_627_81, 0.990_434_4, -0.017_073_8],
+    [-0.009_243_041, 0.015_055_191, 0.751_874_3],
+];
+const D50_TO_D65: Matrix = [
+    [0.955_473_4, -0.023_098_455, 0.063_259_244],
+    [-0.028_369_71, 1.009_995_4, 0.021_041_44],
+    [0.012_314_015, -0.020_507_65, 1.330_365_9],
+];
+const XYZ_TO_LMS: Matrix = [
+    [0.819_022_4, 0.361_906_26, -0.128_873_78],
+    [0.032_983_655, 0.929_286_85, 0.036_144_666],
+    [0.048_177_19, 0.264_239_53, 0.633_547_8],
+];
+const LMS_TO_OKLAB: Matrix = [
+    [0.210_454_27, 0.793_617_8, -0.004_072_043],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_042, 0.782_771_7, -0.808_675_77],
+];
+const LMS_TO_XYZ: Matrix = [
+    [1.226_88, -0.557_815, 0.281_391_05],
+    [-0.040_575_746, 1.112_286_8, -0.071_711_06],
+    [-0.076_372_94, -0.421_493_33, 1.586_924],
+];
+const OKLAB_TO_LMS: Matrix = [
+    [1.0, 0.396_337_78, 0.215_803_76],
+    [1.0, -0.105_561_346, -0.063_854_17],
+    [1.0, -0.089_484_18, -1.291_485_5],
+];
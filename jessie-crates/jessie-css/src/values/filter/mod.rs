@@ -0,0 +1,190 @@
+//! The `filter` and `backdrop-filter` properties of https://www.w3.org/TR/filter-effects-1/#FilterProperty
+//! and https://www.w3.org/TR/filter-effects-2/#BackdropFilterProperty, which are lists of filter functions.
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::ComponentValue;
+use crate::serializer::serialize_url;
+use crate::values::length::Length;
+use crate::values::shadow::Shadow;
+use crate::values::{
+    Parse, ToCss, ValueParser, expect_non_negative, parse_value_with, unexpected, write_number,
+};
+
+///A filter function like `blur(4px)`. Amounts are numbers, so `50%` is `0.5`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterFunction {
+    Blur(Length),
+    Brightness(f32),
+    Contrast(f32),
+    ///Amounts over 1 are clamped to 1, which is the same for `invert()`, `opacity()` and `sepia()`.
+    Grayscale(f32),
+    ///An angle in degrees.
+    HueRotate(f32),
+    Invert(f32),
+    Opacity(f32),
+    Saturate(f32),
+    Sepia(f32),
+    DropShadow(Shadow),
+    ///A reference to an SVG filter, which the engine does not apply.
+    Url(String),
+}
+
+impl FilterFunction {
+    ///The function of the same kind that does nothing, which a list is padded with to interpolate with a longer one.
+    ///See https://www.w3.org/TR/filter-effects-1/#interpolation-of-filters
+    pub fn initial(&self) -> Option<Self> {
+        Some(match self {
+            FilterFunction::Blur(_) => FilterFunction::Blur(Length::ZERO),
+            FilterFunction::Brightness(_) => FilterFunction::Brightness(1.0),
+            FilterFunction::Contrast(_) => FilterFunction::Contrast(1.0),
+            FilterFunction::Grayscale(_) => FilterFunction::Grayscale(0.0),
+            FilterFunction::HueRotate(_) => FilterFunction::HueRotate(0.0),
+            FilterFunction::Invert(_) => FilterFunction::Invert(0.0),
+            FilterFunction::Opacity(_) => FilterFunction::Opacity(1.0),
+            FilterFunction::Saturate(_) => FilterFunction::Saturate(1.0),
+            FilterFunction::Sepia(_) => FilterFunction::Sepia(0.0),
+            FilterFunction::DropShadow(_) => FilterFunction::DropShadow(Shadow::transparent(false)),
+            FilterFunction::Url(_) => return None,
+        })
+    }
+
+    fn parse_function(name: &str, input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        // A left out amount is 1, which changes the most for the functions that are clamped and nothing for the others.
+        fn amount(input: &mut ValueParser, clamp: bool) -> Result<f32, CssDiagnostic> {
+            if input.is_exhausted() {
+                return Ok(1.0);
+            }
+            let span = input.peek().map(|v| v.span()).unwrap_or_default();
+            let amount = input.expect_number_or_percentage()?;
+            expect_non_negative(amount, span)?;
+            Ok(if clamp { amount.min(1.0) } else { amount })
+        }
+        Ok(match name {
+            "brightness" => FilterFunction::Brightness(amount(input, false)?),
+            "contrast" => FilterFunction::Contrast(amount(input, false)?),
+            "grayscale" => FilterFunction::Grayscale(amount(input, true)?),
+            "invert" => FilterFunction::Invert(amount(input, true)?),
+            "opacity" => FilterFunction::Opacity(amount(input, true)?),
+            "saturate" => FilterFunction::Saturate(amount(input, false)?),
+            "sepia" => FilterFunction::Sepia(amount(input, true)?),
+            "blur" if input.is_exhausted() => FilterFunction::Blur(Length::ZERO),
+            "blur" => FilterFunction::Blur(Length::parse_non_negative(input)?),
+            "hue-rotate" if input.is_exhausted() => FilterFunction::HueRotate(0.0),
+            "hue-rotate" => FilterFunction::HueRotate(input.expect_angle_or_zero()?),
+            "drop-shadow" => FilterFunction::DropShadow(Shadow::parse_text(input)?),
+            _ => unreachable!("not a filter function"),
+        })
+    }
+}
+
+///The names of the filter functions, lowercase.
+const FUNCTIONS: &[&str] = &[
+    "blur",
+    "brightness",
+    "contrast",
+    "drop-shadow",
+    "grayscale",
+    "hue-rotate",
+    "invert",
+    "opacity",
+    "saturate",
+    "sepia",
+];
+
+impl Parse for FilterFunction {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if let Ok(url) = input.try_parse(ValueParser::expect_url) {
+            return Ok(FilterFunction::Url(url));
+        }
+        let value = input.consume()?;
+        let ComponentValue::Function(function) = value else {
+            return Err(unexpected(value, "a filter function like `blur(4px)`"));
+        };
+        let name = function.name.to_ascii_lowercase();
+        if !FUNCTIONS.contains(&name.as_str()) {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                function.span,
+                format!("`{}()` is not a filter function", function.name),
+            ));
+        }
+        parse_value_with(&function.value, |input| Self::parse_function(&name, input))
+    }
+}
+
+impl ToCss for FilterFunction {
+    fn to_css(&self, out: &mut String) {
+        let (name, amount) = match self {
+            FilterFunction::Brightness(amount) => ("brightness(", amount),
+            FilterFunction::Contrast(amount) => ("contrast(", amount),
+            FilterFunction::Grayscale(amount) => ("grayscale(", amount),
+            FilterFunction::Invert(amount) => ("invert(", amount),
+            FilterFunction::Opacity(amount) => ("opacity(", amount),
+            FilterFunction::Saturate(amount) => ("saturate(", amount),
+            FilterFunction::Sepia(amount) => ("sepia(", amount),
+            FilterFunction::Blur(radius) => {
+                out.push_str("blur(");
+                radius.to_css(out);
+                out.push(')');
+                return;
+            }
+            FilterFunction::HueRotate(angle) => {
+                out.push_str("hue-rotate(");
+                write_number(*angle, out);
+                out.push_str("deg)");
+                return;
+            }
+            FilterFunction::DropShadow(shadow) => {
+                out.push_str("drop-shadow(");
+                shadow.to_css(out);
+                out.push(')');
+                return;
+            }
+            FilterFunction::Url(url) => {
+                serialize_url(url, out);
+                return;
+            }
+        };
+        out.push_str(name);
+        write_number(*amount, out);
+        out.push(')');
+    }
+}
+
+///The value of `filter` and `backdrop-filter`, `none` being an empty list. The functions are applied in order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FilterList(pub Vec<FilterFunction>);
+
+impl FilterList {
+    ///Whether any of the functions refers to an SVG filter, which https://www.w3.org/TR/filter-effects-1/#FilterProperty
+    ///says makes the whole list apply no filter when the reference can not be used.
+    pub fn has_url(&self) -> bool {
+        self.0.iter().any(|v| matches!(v, FilterFunction::Url(_)))
+    }
+}
+
+impl Parse for FilterList {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+            return Ok(FilterList::default());
+        }
+        let mut functions = vec![FilterFunction::parse(input)?];
+        while !input.is_exhausted() {
+            functions.push(FilterFunction::parse(input)?);
+        }
+        Ok(FilterList(functions))
+    }
+}
+
+impl ToCss for FilterList {
+    fn to_css(&self, out: &mut String) {
+        if self.0.is_empty() {
+            out.push_str("none");
+        }
+        for (i, function) in self.0.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            function.to_css(out);
+        }
+    }
+}
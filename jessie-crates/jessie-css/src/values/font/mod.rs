@@ -0,0 +1,355 @@
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::values::length::{LengthContext, LengthPercentage};
+use crate::values::{
+    Parse, ToCss, ValueParser, expect_non_negative, keyword_enum, unexpected, write_number,
+};
+
+keyword_enum! {
+    ///The generic font families of https://www.w3.org/TR/css-fonts-4/#generic-font-families
+    pub enum GenericFamily {
+        Serif = "serif",
+        SansSerif = "sans-serif",
+        Monospace = "monospace",
+        Cursive = "cursive",
+        Fantasy = "fantasy",
+        SystemUi = "system-ui",
+        UiSerif = "ui-serif",
+        UiSansSerif = "ui-sans-serif",
+        UiMonospace = "ui-monospace",
+        UiRounded = "ui-rounded",
+        Emoji = "emoji",
+        Math = "math",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FamilyName {
+    Generic(GenericFamily),
+    ///A font family by its name, like `"Fira Sans"` or `Fira Sans`.
+    Named(String),
+}
+
+///The `font-family` property, a comma separated list of families in the order they should be tried in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontFamily(pub Vec<FamilyName>);
+
+impl Parse for FontFamily {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        input
+            .parse_comma_separated(parse_family_name)
+            .map(FontFamily)
+    }
+}
+
+fn parse_family_name(input: &mut ValueParser) -> Result<FamilyName, CssDiagnostic> {
+    let value = input.consume()?;
+    match value.token() {
+        Some(CSSToken::StringToken { string }) => Ok(FamilyName::Named(string.to_string())),
+        Some(CSSToken::IdentToken { value: ident }) => {
+            // A single identifier can be a generic family, several make up the name of a family.
+            let mut idents = vec![ident.to_string()];
+            while let Some(CSSToken::IdentToken { value }) = input.peek().and_then(|v| v.token()) {
+                idents.push(value.to_string());
+                input.consume()?;
+            }
+            if let [ident] = &idents[..]
+                && let Some(generic) = GenericFamily::from_keyword(ident)
+            {
+                return Ok(FamilyName::Generic(generic));
+            }
+            Ok(FamilyName::Named(idents.join(" ")))
+        }
+        _ => Err(unexpected(value, "a font family name")),
+    }
+}
+
+///The absolute size keywords of `font-size`, from `xx-small` to `xxx-large`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbsoluteSize {
+    XxSmall,
+    XSmall,
+    Small,
+    Medium,
+    Large,
+    XLarge,
+    XxLarge,
+    XxxLarge,
+}
+
+impl AbsoluteSize {
+    const KEYWORDS: &[(&str, AbsoluteSize, f32)] = &[
+        ("xx-small", AbsoluteSize::XxSmall, 3.0 / 5.0),
+        ("x-small", AbsoluteSize::XSmall, 3.0 / 4.0),
+        ("small", AbsoluteSize::Small, 8.0 / 9.0),
+        ("medium", AbsoluteSize::Medium, 1.0),
+        ("large", AbsoluteSize::Large, 6.0 / 5.0),
+        ("x-large", AbsoluteSize::XLarge, 3.0 / 2.0),
+        ("xx-large", AbsoluteSize::XxLarge, 2.0),
+        ("xxx-large", AbsoluteSize::XxxLarge, 3.0),
+    ];
+
+    ///The size in pixels, scaled from a `medium` of 16px like https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping does.
+    pub fn to_px(&self) -> f32 {
+        let scale = Self::KEYWORDS
+            .iter()
+            .find(|(_, size, _)| size == self)
+            .map(|(_, _, scale)| *scale)
+            .unwrap_or(1.0);
+        16.0 * scale
+    }
+}
+
+///The `font-size` property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontSize {
+    Absolute(AbsoluteSize),
+    Larger,
+    Smaller,
+    LengthPercentage(LengthPercentage),
+}
+
+impl FontSize {
+    ///Resolves the font size to pixels. Relative sizes are relative to `parent`, the font size of the parent element,
+    ///which `context` should hold as its font size too.
+    pub fn to_px(&self, context: &LengthContext, parent: f32) -> f32 {
+        match self {
+            FontSize::Absolute(size) => size.to_px(),
+            FontSize::Larger => parent * 1.2,
+            FontSize::Smaller => parent / 1.2,
+            FontSize::LengthPercentage(value) => value.to_px(context, parent),
+        }
+    }
+}
+
+impl Parse for FontSize {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if let Some(CSSToken::IdentToken { value }) = input.peek().and_then(|v| v.token()) {
+            let size = match value.to_ascii_lowercase().as_str() {
+                "larger" => FontSize::Larger,
+                "smaller" => FontSize::Smaller,
+                keyword => match AbsoluteSize::KEYWORDS
+                    .iter()
+                    .find(|(name, ..)| *name == keyword)
+                {
+                    Some((_, size, _)) => FontSize::Absolute(*size),
+                    None => {
+                        let value = input.consume()?;
+                        return Err(unexpected(value, "a font size like `medium` or `16px`"));
+                    }
+                },
+            };
+            input.consume()?;
+            return Ok(size);
+        }
+        LengthPercentage::parse_non_negative(input).map(FontSize::LengthPercentage)
+    }
+}
+
+impl ToCss for FontSize {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            FontSize::Absolute(size) => {
+                let keyword = AbsoluteSize::KEYWORDS
+                    .iter()
+                    .find(|(_, v, _)| v == size)
+                    .map(|(name, ..)| *name)
+                    .unwrap_or_default();
+                out.push_str(keyword);
+            }
+            FontSize::Larger => out.push_str("larger"),
+            FontSize::Smaller => out.push_str("smaller"),
+            FontSize::LengthPercentage(value) => value.to_css(out),
+        }
+    }
+}
+
+///The `font-weight` property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontWeight {
+    ///A weight in `1..=1000`, `normal` being 400 and `bold` 700.
+    Absolute(f32),
+    Bolder,
+    Lighter,
+}
+
+impl FontWeight {
+    pub const NORMAL: f32 = 400.0;
+    pub const BOLD: f32 = 700.0;
+
+    ///Resolves the weight, `bolder` and `lighter` being relative to `parent`, the weight of the parent element,
+    ///see https://www.w3.org/TR/css-fonts-4/#relative-weights
+    pub fn resolve(&self, parent: f32) -> f32 {
+        match self {
+            FontWeight::Absolute(weight) => *weight,
+            FontWeight::Bolder if parent < 350.0 => 400.0,
+            FontWeight::Bolder if parent < 550.0 => 700.0,
+            FontWeight::Bolder => parent.max(900.0),
+            FontWeight::Lighter if parent < 100.0 => parent,
+            FontWeight::Lighter if parent < 550.0 => 100.0,
+            FontWeight::Lighter if parent < 750.0 => 400.0,
+            FontWeight::Lighter => 700.0,
+        }
+    }
+}
+
+impl Parse for FontWeight {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        match value.token() {
+            Some(CSSToken::IdentToken { value: ident }) => {
+                match ident.to_ascii_lowercase().as_str() {
+                    "normal" => Ok(FontWeight::Absolute(FontWeight::NORMAL)),
+                    "bold" => Ok(FontWeight::Absolute(FontWeight::BOLD)),
+                    "bolder" => Ok(FontWeight::Bolder),
+                    "lighter" => Ok(FontWeight::Lighter),
+                    _ => Err(unexpected(value, "a font weight like `bold` or `600`")),
+                }
+            }
+            Some(CSSToken::NumberToken { value: weight, .. }) => {
+                let weight = *weight as f32;
+                if !(1.0..=1000.0).contains(&weight) {
+                    return Err(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        value.span(),
+                        format!("a font weight is between 1 and 1000, found {weight}"),
+                    ));
+                }
+                Ok(FontWeight::Absolute(weight))
+            }
+            _ => Err(unexpected(value, "a font weight like `bold` or `600`")),
+        }
+    }
+}
+
+impl ToCss for FontWeight {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            FontWeight::Absolute(weight) => write_number(*weight, out),
+            FontWeight::Bolder => out.push_str("bolder"),
+            FontWeight::Lighter => out.push_str("lighter"),
+        }
+    }
+}
+
+///The `font-style` property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    ///A slanted font, with the slant angle in degrees.
+    Oblique(f32),
+}
+
+impl Parse for FontStyle {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let (ident, span) = input.expect_ident()?;
+        match ident.to_ascii_lowercase().as_str() {
+            "normal" => Ok(FontStyle::Normal),
+            "italic" => Ok(FontStyle::Italic),
+            "oblique" => {
+                let angle = input.try_parse(|input| {
+                    let span = input.peek().map(|v| v.span()).unwrap_or_default();
+                    let angle = input.expect_angle()?;
+                    match (-90.0..=90.0).contains(&angle) {
+                        true => Ok(angle),
+                        false => Err(CssDiagnostic::new(
+                            CssDiagnosticKind::InvalidValue,
+                            span,
+                            "an oblique angle is between -90deg and 90deg",
+                        )),
+                    }
+                });
+                Ok(FontStyle::Oblique(angle.unwrap_or(14.0)))
+            }
+            _ => Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                span,
+                format!("expected `normal`, `italic` or `oblique`, found `{ident}`"),
+            )),
+        }
+    }
+}
+
+///The `font-stretch` property, as a percentage of the normal width where 100 is normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontStretch(pub f32);
+
+impl FontStretch {
+    const KEYWORDS: &[(&str, f32)] = &[
+        ("ultra-condensed", 50.0),
+        ("extra-condensed", 62.5),
+        ("condensed", 75.0),
+        ("semi-condensed", 87.5),
+        ("normal", 100.0),
+        ("semi-expanded", 112.5),
+        ("expanded", 125.0),
+        ("extra-expanded", 150.0),
+        ("ultra-expanded", 200.0),
+    ];
+
+    ///Parses only the keyword values, which is all the `font` shorthand accepts.
+    pub fn parse_keyword(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        if let Some(CSSToken::IdentToken { value: ident }) = value.token()
+            && let Some((_, stretch)) = Self::KEYWORDS
+                .iter()
+                .find(|(name, _)| ident.eq_ignore_ascii_case(name))
+        {
+            return Ok(FontStretch(*stretch));
+        }
+        Err(unexpected(value, "a font width like `condensed`"))
+    }
+}
+
+impl Parse for FontStretch {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if let Ok(stretch) = input.try_parse(FontStretch::parse_keyword) {
+            return Ok(stretch);
+        }
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let percentage = input.expect_percentage().map_err(|_| {
+            CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                span,
+                "expected a font width like `condensed` or `75%`",
+            )
+        })?;
+        expect_non_negative(percentage, span)?;
+        Ok(FontStretch(percentage * 100.0))
+    }
+}
+
+///The `line-height` property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineHeight {
+    Normal,
+    ///A multiple of the font size of the element, which is inherited as the number instead of the resulting length.
+    Number(f32),
+    LengthPercentage(LengthPercentage),
+}
+
+impl LineHeight {
+    ///Resolves the line height to pixels for an element with the font size `font_size`, `normal` being 1.2 times the font size.
+    pub fn to_px(&self, context: &LengthContext, font_size: f32) -> f32 {
+        match self {
+            LineHeight::Normal => font_size * 1.2,
+            LineHeight::Number(number) => font_size * number,
+            LineHeight::LengthPercentage(value) => value.to_px(context, font_size),
+        }
+    }
+}
+
+impl Parse for LineHeight {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("normal")).is_ok() {
+            return Ok(LineHeight::Normal);
+        }
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        if let Ok(number) = input.try_parse(ValueParser::expect_number) {
+            expect_non_negative(number, span)?;
+            return Ok(LineHeight::Number(number));
+        }
+        LengthPercentage::parse_non_negative(input).map(LineHeight::LengthPercentage)
+    }
+}
@@ -0,0 +1,655 @@
+//! The <image> values of https://www.w3.org/TR/css-images-4/, which are urls and the gradient functions.
+//! Gradients keep their color stops as they were written, and [`resolve_color_stops`] fills in the positions
+//! left out once the length of the gradient line is known.
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::ComponentValue;
+use crate::serializer::serialize_url;
+use crate::values::color::Color;
+use crate::values::length::{BoxPosition, Length, LengthPercentage};
+use crate::values::{
+    Parse, ToCss, ValueParser, keyword_enum, parse_value_with, unexpected, write_number,
+};
+
+///One layer of `background-image`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Image {
+    None,
+    Url(String),
+    Gradient(Box<Gradient>),
+}
+
+impl Parse for Image {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+            return Ok(Image::None);
+        }
+        if let Ok(url) = input.try_parse(ValueParser::expect_url) {
+            return Ok(Image::Url(url));
+        }
+        let value = input.peek();
+        Gradient::parse(input)
+            .map(|v| Image::Gradient(Box::new(v)))
+            .map_err(|error| match value {
+                Some(ComponentValue::Function(function))
+                    if GradientShape::from_name(&function.name).is_some() =>
+                {
+                    error
+                }
+                Some(value) => unexpected(value, "`none`, a `url()` or a gradient"),
+                None => error,
+            })
+    }
+}
+
+impl ToCss for Image {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            Image::None => out.push_str("none"),
+            Image::Url(url) => serialize_url(url, out),
+            Image::Gradient(gradient) => gradient.to_css(out),
+        }
+    }
+}
+
+///Which of the gradient functions a gradient is, without `repeating-`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GradientShape {
+    Linear,
+    Radial,
+    Conic,
+}
+
+impl GradientShape {
+    ///Looks up a gradient function by its name, also returning whether it is a `repeating-` one.
+    fn from_name(name: &str) -> Option<(Self, bool)> {
+        let name = name.to_ascii_lowercase();
+        let (name, repeating) = match name.strip_prefix("repeating-") {
+            Some(name) => (name, true),
+            None => (name.as_str(), false),
+        };
+        let shape = match name {
+            "linear-gradient" => GradientShape::Linear,
+            "radial-gradient" => GradientShape::Radial,
+            "conic-gradient" => GradientShape::Conic,
+            _ => return None,
+        };
+        Some((shape, repeating))
+    }
+}
+
+///A gradient function like `linear-gradient(to right, red, blue)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    ///Whether the color stops repeat along the gradient line, like in `repeating-linear-gradient()`.
+    pub repeating: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GradientKind {
+    Linear {
+        direction: LineDirection,
+        items: Vec<GradientItem<LengthPercentage>>,
+    },
+    Radial {
+        shape: EndingShape,
+        position: BoxPosition,
+        items: Vec<GradientItem<LengthPercentage>>,
+    },
+    ///Conic gradients go around `position` clockwise, starting at the angle `from` in degrees.
+    Conic {
+        from: f32,
+        position: BoxPosition,
+        items: Vec<GradientItem<AnglePercentage>>,
+    },
+}
+
+impl Parse for Gradient {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        let ComponentValue::Function(function) = value else {
+            return Err(unexpected(value, "a gradient like `linear-gradient()`"));
+        };
+        let Some((shape, repeating)) = GradientShape::from_name(&function.name) else {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                function.span,
+                format!("`{}()` is not a gradient function", function.name),
+            ));
+        };
+        let kind = parse_value_with(&function.value, |input| match shape {
+            GradientShape::Linear => {
+                // A color stop never starts with `to`, so the errors of a direction that does are reported.
+                let starts_direction = at_ident(input, |v| v.eq_ignore_ascii_case("to"));
+                let direction = match input.try_parse(LineDirection::parse) {
+                    Ok(direction) => {
+                        input.expect_comma()?;
+                        direction
+                    }
+                    Err(error) if starts_direction => return Err(error),
+                    Err(_) => LineDirection::To(None, Some(VerticalSide::Bottom)),
+                };
+                let items = parse_items(input, LengthPercentage::parse)?;
+                Ok(GradientKind::Linear { direction, items })
+            }
+            GradientShape::Radial => {
+                // Nor like an ending shape.
+                let starts_shape = at_dimension(input)
+                    || at_ident(input, |v| {
+                        v.eq_ignore_ascii_case("circle")
+                            || v.eq_ignore_ascii_case("ellipse")
+                            || ShapeExtent::from_keyword(v).is_some()
+                    });
+                let shape = match input.try_parse(EndingShape::parse) {
+                    Err(error) if starts_shape => return Err(error),
+                    shape => shape,
+                };
+                let position = input.try_parse(parse_at_position);
+                if shape.is_ok() || position.is_ok() {
+                    input.expect_comma()?;
+                }
+                let shape = shape.unwrap_or(EndingShape::Ellipse(EllipseSize::Extent(
+                    ShapeExtent::FarthestCorner,
+                )));
+                let position = position.unwrap_or(BoxPosition::CENTER);
+                let items = parse_items(input, LengthPercentage::parse)?;
+                Ok(GradientKind::Radial {
+                    shape,
+                    position,
+                    items,
+                })
+            }
+            GradientShape::Conic => {
+                let from = input.try_parse(|input| {
+                    input.expect_keyword("from")?;
+                    input.expect_angle_or_zero()
+                });
+                let position = input.try_parse(parse_at_position);
+                if from.is_ok() || position.is_ok() {
+                    input.expect_comma()?;
+                }
+                let items = parse_items(input, AnglePercentage::parse)?;
+                Ok(GradientKind::Conic {
+                    from: from.unwrap_or(0.0),
+                    position: position.unwrap_or(BoxPosition::CENTER),
+                    items,
+                })
+            }
+        })?;
+        Ok(Gradient { kind, repeating })
+    }
+}
+
+///Whether the next value is a length, a percentage or an angle, which a color stop never starts with.
+fn at_dimension(input: &mut ValueParser) -> bool {
+    if input.at_math_function() {
+        return true;
+    }
+    match input.peek().and_then(|v| v.token()) {
+        Some(CSSToken::DimensionToken { .. } | CSSToken::PercentageToken { .. }) => true,
+        Some(CSSToken::NumberToken { value, .. }) => *value == 0.0,
+        _ => false,
+    }
+}
+
+///Whether the next value is an identifier that `matches`.
+fn at_ident(input: &mut ValueParser, matches: impl Fn(&str) -> bool) -> bool {
+    matches!(input.peek().and_then(|v| v.token()), Some(CSSToken::IdentToken { value }) if matches(value))
+}
+
+fn parse_at_position(input: &mut ValueParser) -> Result<BoxPosition, CssDiagnostic> {
+    input.expect_keyword("at")?;
+    BoxPosition::parse(input)
+}
+
+impl ToCss for Gradient {
+    fn to_css(&self, out: &mut String) {
+        if self.repeating {
+            out.push_str("repeating-");
+        }
+        let mut prelude = String::new();
+        match &self.kind {
+            GradientKind::Linear { direction, items } => {
+                out.push_str("linear-gradient(");
+                if *direction != LineDirection::To(None, Some(VerticalSide::Bottom)) {
+                    direction.to_css(&mut prelude);
+                }
+                write_items(&prelude, items, out);
+            }
+            GradientKind::Radial {
+                shape,
+                position,
+                items,
+            } => {
+                out.push_str("radial-gradient(");
+                shape.to_css(&mut prelude);
+                write_at_position(position, &mut prelude);
+                write_items(&prelude, items, out);
+            }
+            GradientKind::Conic {
+                from,
+                position,
+                items,
+            } => {
+                out.push_str("conic-gradient(");
+                if *from != 0.0 {
+                    prelude.push_str("from ");
+                    write_number(*from, &mut prelude);
+                    prelude.push_str("deg");
+                }
+                write_at_position(position, &mut prelude);
+                write_items(&prelude, items, out);
+            }
+        }
+        out.push(')');
+    }
+}
+
+fn write_at_position(position: &BoxPosition, prelude: &mut String) {
+    if *position == BoxPosition::CENTER {
+        return;
+    }
+    if !prelude.is_empty() {
+        prelude.push(' ');
+    }
+    prelude.push_str("at ");
+    position.to_css(prelude);
+}
+
+///Writes the part before the color stops, if any, and the color stops.
+fn write_items<T: ToCss>(prelude: &str, items: &[GradientItem<T>], out: &mut String) {
+    if !prelude.is_empty() {
+        out.push_str(prelude);
+        out.push_str(", ");
+    }
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        item.to_css(out);
+    }
+}
+
+keyword_enum! {
+    pub enum HorizontalSide {
+        Left = "left",
+        Right = "right",
+    }
+}
+
+keyword_enum! {
+    pub enum VerticalSide {
+        Top = "top",
+        Bottom = "bottom",
+    }
+}
+
+///The direction of a `linear-gradient()`, an angle in degrees or the side or corner the gradient goes `to`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineDirection {
+    Angle(f32),
+    ///At least one of the sides is `Some`.
+    To(Option<HorizontalSide>, Option<VerticalSide>),
+}
+
+impl LineDirection {
+    ///The angle of the gradient line in degrees for a box of the given size, `0deg` pointing up and `90deg` to the right.
+    ///Corners depend on the size, since the line is perpendicular to the diagonal between the other two corners.
+    pub fn to_degrees(&self, width: f32, height: f32) -> f32 {
+        let corner = height.atan2(width).to_degrees();
+        match self {
+            LineDirection::Angle(angle) => *angle,
+            LineDirection::To(None, Some(VerticalSide::Top)) | LineDirection::To(None, None) => 0.0,
+            LineDirection::To(Some(HorizontalSide::Right), None) => 90.0,
+            LineDirection::To(None, Some(VerticalSide::Bottom)) => 180.0,
+            LineDirection::To(Some(HorizontalSide::Left), None) => 270.0,
+            LineDirection::To(Some(HorizontalSide::Right), Some(VerticalSide::Top)) => corner,
+            LineDirection::To(Some(HorizontalSide::Right), Some(VerticalSide::Bottom)) => {
+                180.0 - corner
+            }
+            LineDirection::To(Some(HorizontalSide::Left), Some(VerticalSide::Bottom)) => {
+                180.0 + corner
+            }
+            LineDirection::To(Some(HorizontalSide::Left), Some(VerticalSide::Top)) => {
+                360.0 - corner
+            }
+        }
+    }
+}
+
+impl Parse for LineDirection {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("to")).is_err() {
+            return input.expect_angle_or_zero().map(LineDirection::Angle);
+        }
+        // The sides can be in either order, like `to top left`.
+        let horizontal = input.try_parse(HorizontalSide::parse).ok();
+        let vertical = input.try_parse(VerticalSide::parse).ok();
+        let horizontal = match horizontal {
+            None if vertical.is_some() => input.try_parse(HorizontalSide::parse).ok(),
+            horizontal => horizontal,
+        };
+        if horizontal.is_none() && vertical.is_none() {
+            let value = input.consume()?;
+            return Err(unexpected(
+                value,
+                "a side like `right` or a corner like `top right`",
+            ));
+        }
+        Ok(LineDirection::To(horizontal, vertical))
+    }
+}
+
+impl ToCss for LineDirection {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            LineDirection::Angle(angle) => {
+                write_number(*angle, out);
+                out.push_str("deg");
+            }
+            LineDirection::To(horizontal, vertical) => {
+                out.push_str("to");
+                if let Some(horizontal) = horizontal {
+                    out.push(' ');
+                    horizontal.to_css(out);
+                }
+                if let Some(vertical) = vertical {
+                    out.push(' ');
+                    vertical.to_css(out);
+                }
+            }
+        }
+    }
+}
+
+keyword_enum! {
+    ///How far a `radial-gradient()` reaches, measured from its center.
+    pub enum ShapeExtent {
+        ClosestSide = "closest-side",
+        FarthestSide = "farthest-side",
+        ClosestCorner = "closest-corner",
+        FarthestCorner = "farthest-corner",
+    }
+}
+
+///The shape of a `radial-gradient()`, which is where its gradient line ends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EndingShape {
+    Circle(CircleSize),
+    Ellipse(EllipseSize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircleSize {
+    Extent(ShapeExtent),
+    Radius(Length),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EllipseSize {
+    Extent(ShapeExtent),
+    ///The horizontal and vertical radius.
+    Radii(LengthPercentage, LengthPercentage),
+}
+
+impl Parse for EndingShape {
+    ///Parses `[ <radial-shape> || <radial-size> ]`, see https://www.w3.org/TR/css-images-4/#radial-gradients
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let parse_shape = |input: &mut ValueParser| {
+            input.try_parse(|input| {
+                let (ident, span) = input.expect_ident()?;
+                match ident.to_ascii_lowercase().as_str() {
+                    "circle" => Ok(true),
+                    "ellipse" => Ok(false),
+                    _ => Err(CssDiagnostic::new(
+                        CssDiagnosticKind::InvalidValue,
+                        span,
+                        "expected `circle` or `ellipse`",
+                    )),
+                }
+            })
+        };
+        let mut circle = parse_shape(input).ok();
+        let extent = input.try_parse(ShapeExtent::parse).ok();
+        let mut radii = vec![];
+        if extent.is_none() {
+            while radii.len() < 2 && at_dimension(input) {
+                radii.push(LengthPercentage::parse_non_negative(input)?);
+            }
+        }
+        if circle.is_none() {
+            circle = parse_shape(input).ok();
+        }
+        let error = |message: &str| {
+            Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                span,
+                message,
+            ))
+        };
+        match (circle, extent, radii.as_slice()) {
+            (None, None, []) => error("expected the shape or the size of a radial gradient"),
+            (Some(true), Some(extent), _) => Ok(EndingShape::Circle(CircleSize::Extent(extent))),
+            (Some(true), None, []) => Ok(EndingShape::Circle(CircleSize::Extent(
+                ShapeExtent::FarthestCorner,
+            ))),
+            (None | Some(false), Some(extent), _) => {
+                Ok(EndingShape::Ellipse(EllipseSize::Extent(extent)))
+            }
+            (Some(false), None, []) => Ok(EndingShape::Ellipse(EllipseSize::Extent(
+                ShapeExtent::FarthestCorner,
+            ))),
+            (None | Some(true), None, [LengthPercentage::Length(radius)]) => {
+                Ok(EndingShape::Circle(CircleSize::Radius(*radius)))
+            }
+            (Some(false), None, [_]) => error("an ellipse has a horizontal and a vertical radius"),
+            (_, None, [_]) => error("the radius of a circle can not be a percentage"),
+            (None | Some(false), None, [x, y]) => Ok(EndingShape::Ellipse(EllipseSize::Radii(
+                x.clone(),
+                y.clone(),
+            ))),
+            _ => error("a circle has a single radius"),
+        }
+    }
+}
+
+impl ToCss for EndingShape {
+    ///Writes nothing for the default `ellipse farthest-corner`, and leaves out the shape when the size implies it.
+    fn to_css(&self, out: &mut String) {
+        match self {
+            EndingShape::Circle(CircleSize::Extent(ShapeExtent::FarthestCorner)) => {
+                out.push_str("circle")
+            }
+            EndingShape::Circle(CircleSize::Extent(extent)) => {
+                out.push_str("circle ");
+                extent.to_css(out);
+            }
+            EndingShape::Circle(CircleSize::Radius(radius)) => radius.to_css(out),
+            EndingShape::Ellipse(EllipseSize::Extent(ShapeExtent::FarthestCorner)) => {}
+            EndingShape::Ellipse(EllipseSize::Extent(extent)) => extent.to_css(out),
+            EndingShape::Ellipse(EllipseSize::Radii(x, y)) => {
+                x.to_css(out);
+                out.push(' ');
+                y.to_css(out);
+            }
+        }
+    }
+}
+
+///The position of a color stop of a `conic-gradient()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnglePercentage {
+    ///An angle in degrees.
+    Angle(f32),
+    ///The percentage divided by 100, so `50%` is `0.5`.
+    Percentage(f32),
+}
+
+impl AnglePercentage {
+    ///The position as a fraction of a full turn.
+    pub fn to_turns(&self) -> f32 {
+        match self {
+            AnglePercentage::Angle(angle) => angle / 360.0,
+            AnglePercentage::Percentage(percentage) => *percentage,
+        }
+    }
+}
+
+impl Parse for AnglePercentage {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if let Ok(percentage) = input.try_parse(ValueParser::expect_percentage) {
+            return Ok(AnglePercentage::Percentage(percentage));
+        }
+        input.expect_angle_or_zero().map(AnglePercentage::Angle)
+    }
+}
+
+impl ToCss for AnglePercentage {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            AnglePercentage::Angle(angle) => {
+                write_number(*angle, out);
+                out.push_str("deg");
+            }
+            AnglePercentage::Percentage(percentage) => {
+                write_number(percentage * 100.0, out);
+                out.push('%');
+            }
+        }
+    }
+}
+
+///A color stop or a color hint of a gradient, with positions of type `T`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GradientItem<T> {
+    ColorStop(Color, Option<T>),
+    ///Where the color halfway between the color stops around it is, which is in the middle by default.
+    Hint(T),
+}
+
+impl<T: ToCss> ToCss for GradientItem<T> {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            GradientItem::ColorStop(color, position) => {
+                color.to_css(out);
+                if let Some(position) = position {
+                    out.push(' ');
+                    position.to_css(out);
+                }
+            }
+            GradientItem::Hint(position) => position.to_css(out),
+        }
+    }
+}
+
+///Parses `<color-stop-list>`. A color stop with two positions, like `red 10% 20%`, is split into two color stops.
+fn parse_items<T: Clone>(
+    input: &mut ValueParser,
+    mut parse_position: impl FnMut(&mut ValueParser) -> Result<T, CssDiagnostic>,
+) -> Result<Vec<GradientItem<T>>, CssDiagnostic> {
+    let span = input.peek().map(|v| v.span()).unwrap_or_default();
+    let groups = input.parse_comma_separated(|input| {
+        if let Ok(hint) = input.try_parse(&mut parse_position) {
+            return Ok(vec![GradientItem::Hint(hint)]);
+        }
+        let color = Color::parse(input)?;
+        let Ok(first) = input.try_parse(&mut parse_position) else {
+            return Ok(vec![GradientItem::ColorStop(color, None)]);
+        };
+        match input.try_parse(&mut parse_position) {
+            Ok(second) => Ok(vec![
+                GradientItem::ColorStop(color.clone(), Some(first)),
+                GradientItem::ColorStop(color, Some(second)),
+            ]),
+            Err(_) => Ok(vec![GradientItem::ColorStop(color, Some(first))]),
+        }
+    })?;
+    if !input.is_exhausted() {
+        let value = input.consume()?;
+        return Err(unexpected(value, "`,`"));
+    }
+    // https://www.w3.org/TR/css-images-4/#typedef-color-stop-list
+    let is_hint = |group: &Vec<GradientItem<T>>| matches!(group[..], [GradientItem::Hint(_)]);
+    let error = |message: &str| {
+        Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            message,
+        ))
+    };
+    if groups.iter().filter(|v| !is_hint(v)).count() < 2 {
+        return error("a gradient needs at least two color stops");
+    }
+    if groups.first().is_some_and(is_hint)
+        || groups.last().is_some_and(is_hint)
+        || groups.windows(2).any(|v| is_hint(&v[0]) && is_hint(&v[1]))
+    {
+        return error("a color hint has to be between two color stops");
+    }
+    Ok(groups.into_iter().flatten().collect())
+}
+
+///Fills in the positions of the color stops like https://www.w3.org/TR/css-images-4/#color-stop-fixup asks for,
+///`resolve` turning a position into a fraction of the gradient line. The positions are returned in the same order
+///as the items, and never decrease.
+pub fn resolve_color_stops<T>(
+    items: &[GradientItem<T>],
+    resolve: impl Fn(&T) -> f32,
+) -> Vec<GradientItem<f32>> {
+    let last_stop = items
+        .iter()
+        .rposition(|v| matches!(v, GradientItem::ColorStop(..)))
+        .unwrap_or_default();
+    let mut positions: Vec<Option<f32>> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match item {
+            GradientItem::ColorStop(_, Some(position)) | GradientItem::Hint(position) => {
+                Some(resolve(position))
+            }
+            // The first color stop defaults to 0% and the last one to 100%.
+            GradientItem::ColorStop(_, None) if i == 0 => Some(0.0),
+            GradientItem::ColorStop(_, None) if i == last_stop => Some(1.0),
+            GradientItem::ColorStop(_, None) => None,
+        })
+        .collect();
+    // Positions before a larger one are moved up to it.
+    let mut largest = f32::MIN;
+    for position in positions.iter_mut().flatten() {
+        largest = largest.max(*position);
+        *position = largest;
+    }
+    // The color stops left without a position are spread evenly between the ones around them.
+    let mut i = 0;
+    while i < positions.len() {
+        if positions[i].is_some() {
+            i += 1;
+            continue;
+        }
+        let end = (i..positions.len())
+            .find(|&j| positions[j].is_some())
+            .unwrap_or(positions.len() - 1);
+        let start = positions[i - 1].unwrap_or_default();
+        let stop = positions[end].unwrap_or(start);
+        let count = (end - i + 1) as f32;
+        for (n, position) in positions[i..end].iter_mut().enumerate() {
+            *position = Some(start + (stop - start) * (n + 1) as f32 / count);
+        }
+        i = end;
+    }
+    items
+        .iter()
+        .zip(positions)
+        .map(|(item, position)| {
+            let position = position.unwrap_or_default();
+            match item {
+                GradientItem::ColorStop(color, _) => {
+                    GradientItem::ColorStop(color.clone(), Some(position))
+                }
+                GradientItem::Hint(_) => GradientItem::Hint(position),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,507 @@
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::values::calc::{Calc, CalcCategory, CalcNode};
+use crate::values::{Parse, ToCss, ValueParser, expect_non_negative, unexpected, write_number};
+
+///The length units of https://www.w3.org/TR/css-values-4/#lengths that the engine supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    Px,
+    ///The font size of the element.
+    Em,
+    ///The font size of the root element.
+    Rem,
+    ///The advance of the `0` glyph, approximated as half an `em`.
+    Ch,
+    ///1% of the viewport width.
+    Vw,
+    ///1% of the viewport height.
+    Vh,
+    Vmin,
+    Vmax,
+}
+
+impl LengthUnit {
+    const UNITS: &[(&str, LengthUnit)] = &[
+        ("px", LengthUnit::Px),
+        ("em", LengthUnit::Em),
+        ("rem", LengthUnit::Rem),
+        ("ch", LengthUnit::Ch),
+        ("vw", LengthUnit::Vw),
+        ("vh", LengthUnit::Vh),
+        ("vmin", LengthUnit::Vmin),
+        ("vmax", LengthUnit::Vmax),
+    ];
+
+    pub fn from_unit(unit: &str) -> Option<Self> {
+        Self::UNITS
+            .iter()
+            .find(|(name, _)| unit.eq_ignore_ascii_case(name))
+            .map(|(_, unit)| *unit)
+    }
+
+    pub fn unit(&self) -> &'static str {
+        Self::UNITS
+            .iter()
+            .find(|(_, unit)| unit == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default()
+    }
+}
+
+///What relative lengths are relative to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LengthContext {
+    ///The font size of the element in pixels.
+    pub font_size: f32,
+    ///The font size of the root element in pixels.
+    pub root_font_size: f32,
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+}
+
+impl LengthContext {
+    ///A context for a viewport with the default font size of 16px.
+    pub fn new(viewport_width: f32, viewport_height: f32) -> Self {
+        Self {
+            font_size: 16.0,
+            root_font_size: 16.0,
+            viewport_width,
+            viewport_height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Length {
+    pub value: f32,
+    pub unit: LengthUnit,
+}
+
+impl Length {
+    pub const ZERO: Length = Length::px(0.0);
+
+    pub const fn px(value: f32) -> Self {
+        Self {
+            value,
+            unit: LengthUnit::Px,
+        }
+    }
+
+    ///Resolves the length to pixels.
+    pub fn to_px(&self, context: &LengthContext) -> f32 {
+        let scale = match self.unit {
+            LengthUnit::Px => 1.0,
+            LengthUnit::Em => context.font_size,
+            LengthUnit::Rem => context.root_font_size,
+            LengthUnit::Ch => context.font_size / 2.0,
+            LengthUnit::Vw => context.viewport_width / 100.0,
+            LengthUnit::Vh => context.viewport_height / 100.0,
+            LengthUnit::Vmin => context.viewport_width.min(context.viewport_height) / 100.0,
+            LengthUnit::Vmax => context.viewport_width.max(context.viewport_height) / 100.0,
+        };
+        self.value * scale
+    }
+
+    ///Parses a length that is not negative, like the value of `border-width`.
+    pub fn parse_non_negative(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let length = Length::parse(input)?;
+        expect_non_negative(length.value, span)?;
+        Ok(length)
+    }
+}
+
+impl Parse for Length {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.at_math_function() {
+            let span = input.peek().map(|v| v.span()).unwrap_or_default();
+            return match Calc::parse(input, CalcCategory::Length)?.root {
+                CalcNode::Length(length) => Ok(length),
+                _ => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidValue,
+                    span,
+                    "expected a length with a single unit here",
+                )),
+            };
+        }
+        let value = input.consume()?;
+        match value.token() {
+            Some(CSSToken::DimensionToken {
+                value: number,
+                unit,
+                ..
+            }) => match LengthUnit::from_unit(unit) {
+                Some(unit) => Ok(Length {
+                    value: *number as f32,
+                    unit,
+                }),
+                None => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidValue,
+                    value.span(),
+                    format!(
+                        "`{unit}` is not a supported length unit, expected one of `{}`",
+                        LengthUnit::UNITS
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join("`, `")
+                    ),
+                )),
+            },
+            // A unitless zero is a length too.
+            Some(CSSToken::NumberToken { value: number, .. }) if *number == 0.0 => Ok(Length::ZERO),
+            _ => Err(unexpected(value, "a length like `10px`")),
+        }
+    }
+}
+
+impl ToCss for Length {
+    fn to_css(&self, out: &mut String) {
+        write_number(self.value, out);
+        out.push_str(self.unit.unit());
+    }
+}
+
+///A length or a percentage of some other length, which depends on the property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LengthPercentage {
+    Length(Length),
+    ///The percentage divided by 100, so `50%` is `0.5`.
+    Percentage(f32),
+    ///A math function that mixes units, like `calc(100% - 2rem)`.
+    Calc(Box<Calc>),
+}
+
+impl LengthPercentage {
+    ///Resolves the value to pixels, `basis` being the length that percentages are relative to.
+    pub fn to_px(&self, context: &LengthContext, basis: f32) -> f32 {
+        match self {
+            LengthPercentage::Length(length) => length.to_px(context),
+            LengthPercentage::Percentage(percentage) => percentage * basis,
+            LengthPercentage::Calc(calc) => calc.to_px(context, basis),
+        }
+    }
+
+    ///Unwraps a math function that simplified to a single length or percentage.
+    pub fn from_calc(calc: Calc) -> Self {
+        let clamp = |value: f32| match calc.non_negative {
+            true => value.max(0.0),
+            false => value,
+        };
+        match calc.root {
+            CalcNode::Length(length) => LengthPercentage::Length(Length {
+                value: clamp(length.value),
+                ..length
+            }),
+            CalcNode::Percentage(percentage) => LengthPercentage::Percentage(clamp(percentage)),
+            _ => LengthPercentage::Calc(Box::new(calc)),
+        }
+    }
+
+    ///Parses a length or percentage that is not negative, like the value of `padding-top`.
+    ///Math functions are clamped to 0 instead, since whether they are negative can depend on layout.
+    pub fn parse_non_negative(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.at_math_function() {
+            let mut calc = Calc::parse(input, CalcCategory::LengthPercentage)?;
+            calc.non_negative = true;
+            return Ok(LengthPercentage::from_calc(calc));
+        }
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let out = LengthPercentage::parse(input)?;
+        if let LengthPercentage::Length(Length { value, .. })
+        | LengthPercentage::Percentage(value) = out
+        {
+            expect_non_negative(value, span)?;
+        }
+        Ok(out)
+    }
+}
+
+impl Parse for LengthPercentage {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.at_math_function() {
+            return Calc::parse(input, CalcCategory::LengthPercentage)
+                .map(LengthPercentage::from_calc);
+        }
+        if let Ok(percentage) = input.try_parse(ValueParser::expect_percentage) {
+            return Ok(LengthPercentage::Percentage(percentage));
+        }
+        let value = input.peek();
+        Length::parse(input)
+            .map(LengthPercentage::Length)
+            .map_err(|error| match value {
+                // Keep the more specific error for unknown units.
+                Some(value) if !matches!(value.token(), Some(CSSToken::DimensionToken { .. })) => {
+                    unexpected(value, "a length like `10px` or a percentage")
+                }
+                _ => error,
+            })
+    }
+}
+
+impl ToCss for LengthPercentage {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            LengthPercentage::Length(length) => length.to_css(out),
+            LengthPercentage::Percentage(percentage) => {
+                write_number(percentage * 100.0, out);
+                out.push('%');
+            }
+            LengthPercentage::Calc(calc) => calc.to_css(out),
+        }
+    }
+}
+
+///A length, a percentage or `auto`, like the value of `width` or `margin-top`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LengthPercentageOrAuto {
+    Auto,
+    LengthPercentage(LengthPercentage),
+}
+
+impl LengthPercentageOrAuto {
+    ///Parses a value whose lengths and percentages are not negative, like the value of `width`.
+    pub fn parse_non_negative(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("auto")).is_ok() {
+            return Ok(LengthPercentageOrAuto::Auto);
+        }
+        LengthPercentage::parse_non_negative(input).map(LengthPercentageOrAuto::LengthPercentage)
+    }
+}
+
+impl Parse for LengthPercentageOrAuto {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("auto")).is_ok() {
+            return Ok(LengthPercentageOrAuto::Auto);
+        }
+        LengthPercentage::parse(input).map(LengthPercentageOrAuto::LengthPercentage)
+    }
+}
+
+impl ToCss for LengthPercentageOrAuto {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            LengthPercentageOrAuto::Auto => out.push_str("auto"),
+            LengthPercentageOrAuto::LengthPercentage(value) => value.to_css(out),
+        }
+    }
+}
+
+///A length, a percentage or `none`, like the value of `max-width`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LengthPercentageOrNone {
+    None,
+    LengthPercentage(LengthPercentage),
+}
+
+impl Parse for LengthPercentageOrNone {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+            return Ok(LengthPercentageOrNone::None);
+        }
+        LengthPercentage::parse_non_negative(input).map(LengthPercentageOrNone::LengthPercentage)
+    }
+}
+
+impl ToCss for LengthPercentageOrNone {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            LengthPercentageOrNone::None => out.push_str("none"),
+            LengthPercentageOrNone::LengthPercentage(value) => value.to_css(out),
+        }
+    }
+}
+
+///The width of a border, `thin`, `medium` and `thick` being 1px, 3px and 5px. It is never a percentage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineWidth(pub LengthPercentage);
+
+impl Parse for LineWidth {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.at_math_function() {
+            let mut calc = Calc::parse(input, CalcCategory::Length)?;
+            calc.non_negative = true;
+            return Ok(LineWidth(LengthPercentage::from_calc(calc)));
+        }
+        let keyword = input.try_parse(|v| {
+            let (ident, span) = v.expect_ident()?;
+            match ident.to_ascii_lowercase().as_str() {
+                "thin" => Ok(1.0),
+                "medium" => Ok(3.0),
+                "thick" => Ok(5.0),
+                _ => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidValue,
+                    span,
+                    format!("expected `thin`, `medium`, `thick` or a length, found `{ident}`"),
+                )),
+            }
+        });
+        let length = match keyword {
+            Ok(px) => Length::px(px),
+            Err(error)
+                if matches!(
+                    input.peek().and_then(|v| v.token()),
+                    Some(CSSToken::IdentToken { .. })
+                ) =>
+            {
+                return Err(error);
+            }
+            Err(_) => Length::parse_non_negative(input)?,
+        };
+        Ok(LineWidth(LengthPercentage::Length(length)))
+    }
+}
+
+impl ToCss for LineWidth {
+    fn to_css(&self, out: &mut String) {
+        self.0.to_css(out);
+    }
+}
+
+///The radius of a rounded corner, like the value of `border-top-left-radius`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CornerRadius {
+    pub horizontal: LengthPercentage,
+    pub vertical: LengthPercentage,
+}
+
+impl Parse for CornerRadius {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let horizontal = LengthPercentage::parse_non_negative(input)?;
+        let vertical = input
+            .try_parse(LengthPercentage::parse_non_negative)
+            .unwrap_or_else(|_| horizontal.clone());
+        Ok(CornerRadius {
+            horizontal,
+            vertical,
+        })
+    }
+}
+
+impl ToCss for CornerRadius {
+    fn to_css(&self, out: &mut String) {
+        self.horizontal.to_css(out);
+        if self.vertical != self.horizontal {
+            out.push(' ');
+            self.vertical.to_css(out);
+        }
+    }
+}
+
+///A point in a box, like the `at` of `radial-gradient()` or the first two values of `transform-origin`.
+///Keywords are stored as the percentages they stand for, so `right top` is `100% 0%`.
+///Only the one and two value forms of https://www.w3.org/TR/css-values-4/#position are supported, not the ones with offsets from an edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxPosition {
+    pub x: LengthPercentage,
+    pub y: LengthPercentage,
+}
+
+///One value of a [`BoxPosition`], before it is known which axis it is for.
+enum PositionPart {
+    Horizontal(f32),
+    Vertical(f32),
+    Center,
+    Value(LengthPercentage),
+}
+
+impl PositionPart {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if let Ok((ident, span)) = input.try_parse(ValueParser::expect_ident) {
+            return match ident.to_ascii_lowercase().as_str() {
+                "left" => Ok(PositionPart::Horizontal(0.0)),
+                "right" => Ok(PositionPart::Horizontal(1.0)),
+                "top" => Ok(PositionPart::Vertical(0.0)),
+                "bottom" => Ok(PositionPart::Vertical(1.0)),
+                "center" => Ok(PositionPart::Center),
+                _ => Err(CssDiagnostic::new(
+                    CssDiagnosticKind::InvalidValue,
+                    span,
+                    format!("expected a position like `left` or `10px`, found `{ident}`"),
+                )),
+            };
+        }
+        LengthPercentage::parse(input).map(PositionPart::Value)
+    }
+
+    fn is_keyword(&self) -> bool {
+        !matches!(self, PositionPart::Value(_))
+    }
+
+    ///The value along the horizontal axis, or `None` if the part is for the vertical one.
+    fn x(self) -> Option<LengthPercentage> {
+        match self {
+            PositionPart::Horizontal(percentage) => Some(LengthPercentage::Percentage(percentage)),
+            PositionPart::Center => Some(LengthPercentage::Percentage(0.5)),
+            PositionPart::Value(value) => Some(value),
+            PositionPart::Vertical(_) => None,
+        }
+    }
+
+    fn y(self) -> Option<LengthPercentage> {
+        match self {
+            PositionPart::Vertical(percentage) => Some(LengthPercentage::Percentage(percentage)),
+            PositionPart::Center => Some(LengthPercentage::Percentage(0.5)),
+            PositionPart::Value(value) => Some(value),
+            PositionPart::Horizontal(_) => None,
+        }
+    }
+}
+
+impl BoxPosition {
+    pub const CENTER: BoxPosition = BoxPosition {
+        x: LengthPercentage::Percentage(0.5),
+        y: LengthPercentage::Percentage(0.5),
+    };
+
+    ///Resolves the position to pixels from the top left corner of a box.
+    pub fn to_px(&self, context: &LengthContext, width: f32, height: f32) -> (f32, f32) {
+        (self.x.to_px(context, width), self.y.to_px(context, height))
+    }
+}
+
+impl Parse for BoxPosition {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let first = PositionPart::parse(input)?;
+        let Ok(second) = input.try_parse(PositionPart::parse) else {
+            // A single value is centered along the other axis.
+            return Ok(match first {
+                PositionPart::Vertical(percentage) => BoxPosition {
+                    x: LengthPercentage::Percentage(0.5),
+                    y: LengthPercentage::Percentage(percentage),
+                },
+                first => BoxPosition {
+                    x: first.x().unwrap_or(LengthPercentage::Percentage(0.5)),
+                    y: LengthPercentage::Percentage(0.5),
+                },
+            });
+        };
+        // Two keywords can be in either order, like `top left`, but a length is always horizontal first.
+        let (x, y) = match (&first, &second) {
+            (PositionPart::Vertical(_), _) | (_, PositionPart::Horizontal(_))
+                if first.is_keyword() && second.is_keyword() =>
+            {
+                (second, first)
+            }
+            _ => (first, second),
+        };
+        match (x.x(), y.y()) {
+            (Some(x), Some(y)) => Ok(BoxPosition { x, y }),
+            _ => Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                span,
+                "expected a horizontal position followed by a vertical one",
+            )),
+        }
+    }
+}
+
+impl ToCss for BoxPosition {
+    fn to_css(&self, out: &mut String) {
+        self.x.to_css(out);
+        out.push(' ');
+        self.y.to_css(out);
+    }
+}
@@ -0,0 +1,597 @@
+use crate::CSSToken;
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::ComponentValue;
+use crate::serializer::serialize_component_values;
+use crate::span::Span;
+use calc::{Calc, CalcCategory, is_math_function};
+pub mod calc;
+pub mod color;
+pub mod filter;
+pub mod font;
+pub mod image;
+pub mod length;
+pub mod shadow;
+pub mod transform;
+
+///A typed property value that can be parsed from component values.
+pub trait Parse: Sized {
+    ///Parses the value from the next component values of `input`, leaving the rest for whatever comes after it.
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic>;
+}
+
+///A typed value that can be written back as CSS text, which parses back to an equal value.
+pub trait ToCss {
+    fn to_css(&self, out: &mut String);
+
+    fn to_css_string(&self) -> String {
+        let mut out = String::new();
+        self.to_css(&mut out);
+        out
+    }
+}
+
+///Writes a number the way CSS expects it, without an exponent.
+pub fn write_number(value: f32, out: &mut String) {
+    use std::fmt::Write;
+    // Keeps `-0` from showing up as a sign in the output.
+    let value = if value == 0.0 { 0.0 } else { value };
+    let _ = write!(out, "{value}");
+}
+
+///Parses the whole value of a declaration as `T`, failing if anything is left over.
+pub fn parse_value<T: Parse>(values: &[ComponentValue]) -> Result<T, CssDiagnostic> {
+    parse_value_with(values, T::parse)
+}
+
+///Parses the whole value of a declaration with `parse`, failing if anything is left over.
+pub fn parse_value_with<'i, 'a, T>(
+    values: &'i [ComponentValue<'a>],
+    parse: impl FnOnce(&mut ValueParser<'i, 'a>) -> Result<T, CssDiagnostic>,
+) -> Result<T, CssDiagnostic> {
+    let mut input = ValueParser::new(values);
+    let out = parse(&mut input)?;
+    input.expect_exhausted()?;
+    Ok(out)
+}
+
+///A cursor over the component values of a property value. Whitespace is skipped between values.
+#[derive(Clone)]
+pub struct ValueParser<'i, 'a> {
+    values: &'i [ComponentValue<'a>],
+    position: usize,
+}
+
+impl<'i, 'a> ValueParser<'i, 'a> {
+    pub fn new(values: &'i [ComponentValue<'a>]) -> Self {
+        Self {
+            values,
+            position: 0,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .values
+            .get(self.position)
+            .is_some_and(ComponentValue::is_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    ///Whether only whitespace is left.
+    pub fn is_exhausted(&mut self) -> bool {
+        self.skip_whitespace();
+        self.position == self.values.len()
+    }
+
+    ///Returns the next non-whitespace component value without consuming it.
+    pub fn peek(&mut self) -> Option<&'i ComponentValue<'a>> {
+        self.skip_whitespace();
+        self.values.get(self.position)
+    }
+
+    ///Consumes the next non-whitespace component value, failing at the end of the value.
+    pub fn consume(&mut self) -> Result<&'i ComponentValue<'a>, CssDiagnostic> {
+        self.skip_whitespace();
+        match self.values.get(self.position) {
+            Some(value) => {
+                self.position += 1;
+                Ok(value)
+            }
+            None => Err(self.error_at_end("the value ended too early")),
+        }
+    }
+
+    ///Runs `parse`, putting back everything it consumed if it fails.
+    pub fn try_parse<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, CssDiagnostic>,
+    ) -> Result<T, CssDiagnostic> {
+        let position = self.position;
+        let out = parse(self);
+        if out.is_err() {
+            self.position = position;
+        }
+        out
+    }
+
+    ///Runs `parse` and also returns the component values it consumed, which is how shorthands are split into their longhands.
+    pub fn parse_with_values<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, CssDiagnostic>,
+    ) -> Result<(T, &'i [ComponentValue<'a>]), CssDiagnostic> {
+        self.skip_whitespace();
+        let start = self.position;
+        let out = self.try_parse(parse)?;
+        let mut values = &self.values[start..self.position];
+        while let [rest @ .., last] = values
+            && last.is_whitespace()
+        {
+            values = rest;
+        }
+        Ok((out, values))
+    }
+
+    ///Fails if anything but whitespace is left.
+    pub fn expect_exhausted(&mut self) -> Result<(), CssDiagnostic> {
+        match self.peek() {
+            None => Ok(()),
+            Some(value) => Err(unexpected(value, "the end of the value")),
+        }
+    }
+
+    ///Consumes an <ident-token> and returns its value.
+    pub fn expect_ident(&mut self) -> Result<(&'i str, Span), CssDiagnostic> {
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::IdentToken { value: ident }) => Ok((ident, value.span())),
+            _ => Err(unexpected(value, "a keyword")),
+        }
+    }
+
+    ///Consumes an <ident-token> that is an ASCII case-insensitive match for `keyword`.
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<(), CssDiagnostic> {
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::IdentToken { value: ident }) if ident.eq_ignore_ascii_case(keyword) => {
+                Ok(())
+            }
+            _ => Err(unexpected(value, &format!("`{keyword}`"))),
+        }
+    }
+
+    ///Consumes a <delim-token> with the value `delim`.
+    pub fn expect_delim(&mut self, delim: char) -> Result<(), CssDiagnostic> {
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::DelimToken { value }) if *value == delim => Ok(()),
+            _ => Err(unexpected(value, &format!("`{delim}`"))),
+        }
+    }
+
+    pub fn expect_comma(&mut self) -> Result<(), CssDiagnostic> {
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::CommaToken) => Ok(()),
+            _ => Err(unexpected(value, "`,`")),
+        }
+    }
+
+    ///Parses a comma separated list of one or more values with `parse`.
+    pub fn parse_comma_separated<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut Self) -> Result<T, CssDiagnostic>,
+    ) -> Result<Vec<T>, CssDiagnostic> {
+        let mut values = vec![parse(self)?];
+        while self.try_parse(Self::expect_comma).is_ok() {
+            values.push(parse(self)?);
+        }
+        Ok(values)
+    }
+
+    ///Whether the next value is a math function like `calc()`.
+    pub fn at_math_function(&mut self) -> bool {
+        matches!(self.peek(), Some(ComponentValue::Function(function)) if is_math_function(&function.name))
+    }
+
+    ///Consumes a math function that does not depend on layout, like `calc(1 / 3)`, and returns its value.
+    fn expect_absolute_math_function(
+        &mut self,
+        category: CalcCategory,
+    ) -> Result<f32, CssDiagnostic> {
+        Calc::parse(self, category).map(|v| v.value().unwrap_or_default())
+    }
+
+    ///Consumes a <number-token> or a math function that resolves to a number.
+    pub fn expect_number(&mut self) -> Result<f32, CssDiagnostic> {
+        if self.at_math_function() {
+            return self.expect_absolute_math_function(CalcCategory::Number);
+        }
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::NumberToken { value, .. }) => Ok(*value as f32),
+            _ => Err(unexpected(value, "a number")),
+        }
+    }
+
+    ///Consumes a <number-token> without a fractional part, or a math function that resolves to a number, rounded to the nearest integer.
+    pub fn expect_integer(&mut self) -> Result<i32, CssDiagnostic> {
+        if self.at_math_function() {
+            return self
+                .expect_absolute_math_function(CalcCategory::Number)
+                .map(|v| v.round() as i32);
+        }
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::NumberToken {
+                flag: crate::NumberType::Integer,
+                value,
+                ..
+            }) => Ok(*value as i32),
+            _ => Err(unexpected(value, "an integer")),
+        }
+    }
+
+    ///Consumes a <percentage-token> or a math function that resolves to a percentage, and returns its value divided by 100.
+    pub fn expect_percentage(&mut self) -> Result<f32, CssDiagnostic> {
+        if self.at_math_function() {
+            return self.expect_absolute_math_function(CalcCategory::Percentage);
+        }
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::PercentageToken { value, .. }) => Ok(*value as f32 / 100.0),
+            _ => Err(unexpected(value, "a percentage")),
+        }
+    }
+
+    ///Consumes a number or a percentage, returning percentages divided by 100.
+    pub fn expect_number_or_percentage(&mut self) -> Result<f32, CssDiagnostic> {
+        if let Ok(number) = self.try_parse(Self::expect_number) {
+            return Ok(number);
+        }
+        let value = self.peek();
+        self.expect_percentage().map_err(|error| match value {
+            Some(value) if !matches!(value, ComponentValue::Function(_)) => {
+                unexpected(value, "a number or a percentage")
+            }
+            _ => error,
+        })
+    }
+
+    ///Consumes an <angle>, or a math function that resolves to one, and returns it in degrees.
+    pub fn expect_angle(&mut self) -> Result<f32, CssDiagnostic> {
+        if self.at_math_function() {
+            return self.expect_absolute_math_function(CalcCategory::Angle);
+        }
+        let value = self.consume()?;
+        match value.token() {
+            Some(CSSToken::DimensionToken {
+                value: angle, unit, ..
+            }) => angle_to_degrees(*angle as f32, unit),
+            _ => None,
+        }
+        .ok_or_else(|| unexpected(value, "an angle like `90deg`"))
+    }
+
+    ///Consumes an <angle> like [`ValueParser::expect_angle`], or a unitless zero,
+    ///which https://www.w3.org/TR/css-values-4/#angles allows in transforms and gradients for legacy reasons.
+    pub fn expect_angle_or_zero(&mut self) -> Result<f32, CssDiagnostic> {
+        if let Some(CSSToken::NumberToken { value, .. }) = self.peek().and_then(|v| v.token())
+            && *value == 0.0
+        {
+            self.consume()?;
+            return Ok(0.0);
+        }
+        self.expect_angle()
+    }
+
+    ///Consumes a <url>, which is a <url-token> or a `url()` with a string, and returns the url.
+    pub fn expect_url(&mut self) -> Result<String, CssDiagnostic> {
+        let value = self.consume()?;
+        match value {
+            ComponentValue::PreservedToken(token) => match &token.token {
+                CSSToken::URLToken { value } => Ok(value.to_string()),
+                _ => Err(unexpected(value, "a `url()`")),
+            },
+            ComponentValue::Function(function) if function.name.eq_ignore_ascii_case("url") => {
+                parse_value_with(&function.value, |input| {
+                    let value = input.consume()?;
+                    match value.token() {
+                        Some(CSSToken::StringToken { string }) => Ok(string.to_string()),
+                        _ => Err(unexpected(value, "a url string")),
+                    }
+                })
+            }
+            _ => Err(unexpected(value, "a `url()`")),
+        }
+    }
+
+    ///An error located at the last value, or nowhere for an empty value.
+    fn error_at_end(&self, message: &str) -> CssDiagnostic {
+        let span = self
+            .values
+            .iter()
+            .rev()
+            .find(|v| !v.is_whitespace())
+            .map(ComponentValue::span)
+            .unwrap_or_default();
+        CssDiagnostic::new(CssDiagnosticKind::InvalidValue, span, message)
+    }
+}
+
+///Converts an angle to degrees, or returns `None` if `unit` is not one of the <angle> units.
+pub fn angle_to_degrees(angle: f32, unit: &str) -> Option<f32> {
+    let unit = unit.to_ascii_lowercase();
+    match unit.as_str() {
+        "deg" => Some(angle),
+        "rad" => Some(angle.to_degrees()),
+        "grad" => Some(angle * 0.9),
+        "turn" => Some(angle * 360.0),
+        _ => None,
+    }
+}
+
+///An error for finding `value` where `expected` should have been.
+pub fn unexpected(value: &ComponentValue, expected: &str) -> CssDiagnostic {
+    CssDiagnostic::new(
+        CssDiagnosticKind::InvalidValue,
+        value.span(),
+        format!(
+            "expected {expected}, found `{}`",
+            serialize_component_values(std::slice::from_ref(value))
+        ),
+    )
+}
+
+///Fails with a diagnostic at `span` if `value` is negative, for the properties that do not accept negative values.
+pub fn expect_non_negative(value: f32, span: Span) -> Result<(), CssDiagnostic> {
+    if value < 0.0 {
+        return Err(CssDiagnostic::new(
+            CssDiagnosticKind::InvalidValue,
+            span,
+            "negative values are not allowed here",
+        ));
+    }
+    Ok(())
+}
+
+///Defines an enum of keywords, parsed ASCII case-insensitively.
+macro_rules! keyword_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($variant:ident = $keyword:literal,)+ }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)+
+        }
+
+        impl $name {
+            pub const KEYWORDS: &[&str] = &[$($keyword,)+];
+
+            pub fn from_keyword(keyword: &str) -> Option<Self> {
+                $(if keyword.eq_ignore_ascii_case($keyword) {
+                    return Some($name::$variant);
+                })+
+                None
+            }
+
+            pub fn keyword(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $keyword,)+
+                }
+            }
+        }
+
+        impl $crate::values::ToCss for $name {
+            fn to_css(&self, out: &mut String) {
+                out.push_str(self.keyword());
+            }
+        }
+
+        impl $crate::values::Parse for $name {
+            fn parse(input: &mut $crate::values::ValueParser) -> Result<Self, $crate::diagnostics::CssDiagnostic> {
+                let (ident, span) = input.expect_ident()?;
+                Self::from_keyword(ident).ok_or_else(|| {
+                    $crate::diagnostics::CssDiagnostic::new(
+                        $crate::diagnostics::CssDiagnosticKind::InvalidValue,
+                        span,
+                        format!(
+                            "expected one of `{}`, found `{ident}`",
+                            Self::KEYWORDS.join("`, `")
+                        ),
+                    )
+                })
+            }
+        }
+    };
+}
+pub(crate) use keyword_enum;
+
+keyword_enum! {
+    ///The `display` property. Only the single keyword forms are supported.
+    pub enum Display {
+        None = "none",
+        Contents = "contents",
+        Block = "block",
+        Inline = "inline",
+        InlineBlock = "inline-block",
+        FlowRoot = "flow-root",
+        Flex = "flex",
+        InlineFlex = "inline-flex",
+        Grid = "grid",
+        InlineGrid = "inline-grid",
+        ListItem = "list-item",
+        Table = "table",
+    }
+}
+
+keyword_enum! {
+    pub enum Position {
+        Static = "static",
+        Relative = "relative",
+        Absolute = "absolute",
+        Fixed = "fixed",
+        Sticky = "sticky",
+    }
+}
+
+keyword_enum! {
+    ///The `overflow-x` and `overflow-y` properties.
+    pub enum Overflow {
+        Visible = "visible",
+        Hidden = "hidden",
+        Clip = "clip",
+        Scroll = "scroll",
+        Auto = "auto",
+    }
+}
+
+keyword_enum! {
+    pub enum BoxSizing {
+        ContentBox = "content-box",
+        BorderBox = "border-box",
+    }
+}
+
+keyword_enum! {
+    pub enum Visibility {
+        Visible = "visible",
+        Hidden = "hidden",
+        Collapse = "collapse",
+    }
+}
+
+keyword_enum! {
+    ///The `border-*-style` properties.
+    pub enum BorderStyle {
+        None = "none",
+        Hidden = "hidden",
+        Dotted = "dotted",
+        Dashed = "dashed",
+        Solid = "solid",
+        Double = "double",
+        Groove = "groove",
+        Ridge = "ridge",
+        Inset = "inset",
+        Outset = "outset",
+    }
+}
+
+keyword_enum! {
+    pub enum FlexDirection {
+        Row = "row",
+        RowReverse = "row-reverse",
+        Column = "column",
+        ColumnReverse = "column-reverse",
+    }
+}
+
+keyword_enum! {
+    pub enum FlexWrap {
+        Nowrap = "nowrap",
+        Wrap = "wrap",
+        WrapReverse = "wrap-reverse",
+    }
+}
+
+///The `z-index` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZIndex {
+    Auto,
+    Integer(i32),
+}
+
+impl Parse for ZIndex {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("auto")).is_ok() {
+            return Ok(ZIndex::Auto);
+        }
+        let value = input.peek();
+        input
+            .expect_integer()
+            .map(ZIndex::Integer)
+            .map_err(|error| match value {
+                Some(value) => unexpected(value, "`auto` or an integer"),
+                None => error,
+            })
+    }
+}
+
+impl ToCss for ZIndex {
+    fn to_css(&self, out: &mut String) {
+        match self {
+            ZIndex::Auto => out.push_str("auto"),
+            ZIndex::Integer(value) => out.push_str(&value.to_string()),
+        }
+    }
+}
+
+///The `opacity` property, a number or a percentage clamped to `0..=1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlphaValue(pub f32);
+
+impl Parse for AlphaValue {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let alpha = input.expect_number_or_percentage()?;
+        Ok(AlphaValue(alpha.clamp(0.0, 1.0)))
+    }
+}
+
+impl ToCss for AlphaValue {
+    fn to_css(&self, out: &mut String) {
+        write_number(self.0, out);
+    }
+}
+
+///A plain <number>, like the value of `flex-grow`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Number(pub f32);
+
+impl Parse for Number {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        input.expect_number().map(Number)
+    }
+}
+
+impl ToCss for Number {
+    fn to_css(&self, out: &mut String) {
+        write_number(self.0, out);
+    }
+}
+
+///A plain <integer>, like the value of `order`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Integer(pub i32);
+
+impl Parse for Integer {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        input.expect_integer().map(Integer)
+    }
+}
+
+impl ToCss for Integer {
+    fn to_css(&self, out: &mut String) {
+        out.push_str(&self.0.to_string());
+    }
+}
+
+///A comma separated list of one or more `T`, like the value of `transition-duration`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommaSeparated<T>(pub Vec<T>);
+
+impl<T: Parse> Parse for CommaSeparated<T> {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        input.parse_comma_separated(T::parse).map(CommaSeparated)
+    }
+}
+
+impl<T: ToCss> ToCss for CommaSeparated<T> {
+    fn to_css(&self, out: &mut String) {
+        for (i, value) in self.0.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            value.to_css(out);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! The shadows of `box-shadow` from https://www.w3.org/TR/css-backgrounds-3/#box-shadow and of `text-shadow`
+//! from https://www.w3.org/TR/css-text-decor-4/#text-shadow-property, which `drop-shadow()` filters use too.
+use crate::diagnostics::CssDiagnostic;
+use crate::values::color::Color;
+use crate::values::length::Length;
+use crate::values::{Parse, ToCss, ValueParser, expect_non_negative};
+
+///A single shadow. Text shadows and `drop-shadow()` have no spread and are never inset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadow {
+    ///Defaults to `currentcolor`.
+    pub color: Color,
+    pub offset_x: Length,
+    pub offset_y: Length,
+    ///The blur radius, which is not negative.
+    pub blur: Length,
+    pub spread: Length,
+    pub inset: bool,
+}
+
+impl Shadow {
+    ///A shadow that draws nothing, which a list is padded with to interpolate with a longer one.
+    pub fn transparent(inset: bool) -> Self {
+        Shadow {
+            color: Color::TRANSPARENT,
+            offset_x: Length::ZERO,
+            offset_y: Length::ZERO,
+            blur: Length::ZERO,
+            spread: Length::ZERO,
+            inset,
+        }
+    }
+
+    ///Parses `<color>? && <length>{2,3}`, the shadow of `text-shadow` and `drop-shadow()`.
+    pub fn parse_text(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        Self::parse_with(input, false)
+    }
+
+    ///Parses the color, the lengths and, for box shadows, `inset`, which can be in any order.
+    fn parse_with(input: &mut ValueParser, box_shadow: bool) -> Result<Self, CssDiagnostic> {
+        let mut color = None;
+        let mut lengths = None;
+        let mut inset = false;
+        loop {
+            if box_shadow && !inset && input.try_parse(|v| v.expect_keyword("inset")).is_ok() {
+                inset = true;
+                continue;
+            }
+            if color.is_none()
+                && let Ok(value) = input.try_parse(Color::parse)
+            {
+                color = Some(value);
+                continue;
+            }
+            if lengths.is_none() {
+                lengths = Some(Self::parse_lengths(input, box_shadow)?);
+                continue;
+            }
+            break;
+        }
+        let Some([offset_x, offset_y, blur, spread]) = lengths else {
+            unreachable!("the loop only ends once the lengths are parsed");
+        };
+        Ok(Shadow {
+            color: color.unwrap_or(Color::CurrentColor),
+            offset_x,
+            offset_y,
+            blur,
+            spread,
+            inset,
+        })
+    }
+
+    fn parse_lengths(
+        input: &mut ValueParser,
+        box_shadow: bool,
+    ) -> Result<[Length; 4], CssDiagnostic> {
+        let offset_x = Length::parse(input)?;
+        let offset_y = Length::parse(input)?;
+        let span = input.peek().map(|v| v.span()).unwrap_or_default();
+        let blur = input.try_parse(Length::parse).unwrap_or(Length::ZERO);
+        expect_non_negative(blur.value, span)?;
+        let spread = match box_shadow {
+            true => input.try_parse(Length::parse).unwrap_or(Length::ZERO),
+            false => Length::ZERO,
+        };
+        Ok([offset_x, offset_y, blur, spread])
+    }
+}
+
+impl Parse for Shadow {
+    ///Parses the shadow of `box-shadow`, which can also have a spread and be `inset`.
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        Self::parse_with(input, true)
+    }
+}
+
+impl ToCss for Shadow {
+    fn to_css(&self, out: &mut String) {
+        self.color.to_css(out);
+        out.push(' ');
+        self.offset_x.to_css(out);
+        out.push(' ');
+        self.offset_y.to_css(out);
+        if self.blur != Length::ZERO || self.spread != Length::ZERO {
+            out.push(' ');
+            self.blur.to_css(out);
+        }
+        if self.spread != Length::ZERO {
+            out.push(' ');
+            self.spread.to_css(out);
+        }
+        if self.inset {
+            out.push_str(" inset");
+        }
+    }
+}
+
+///The value of `box-shadow` and `text-shadow`, `none` being an empty list. The first shadow is drawn on top.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShadowList(pub Vec<Shadow>);
+
+impl ShadowList {
+    ///Parses the value of `text-shadow`, whose shadows have no spread and are never inset.
+    pub fn parse_text(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        Self::parse_with(input, Shadow::parse_text)
+    }
+
+    fn parse_with(
+        input: &mut ValueParser,
+        parse: fn(&mut ValueParser) -> Result<Shadow, CssDiagnostic>,
+    ) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+            return Ok(ShadowList::default());
+        }
+        input.parse_comma_separated(parse).map(ShadowList)
+    }
+}
+
+impl Parse for ShadowList {
+    ///Parses the value of `box-shadow`. Text shadows are box shadows too, so this also parses `text-shadow`.
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        Self::parse_with(input, Shadow::parse)
+    }
+}
+
+impl ToCss for ShadowList {
+    fn to_css(&self, out: &mut String) {
+        if self.0.is_empty() {
+            out.push_str("none");
+        }
+        for (i, shadow) in self.0.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            shadow.to_css(out);
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! The `transform` and `transform-origin` properties of https://www.w3.org/TR/css-transforms-1/ and the 3D functions of
+//! https://www.w3.org/TR/css-transforms-2/. Transform functions are kept as the primitives that the latter interpolates,
+//! so `translateX(10px)` is a [`TransformFunction::Translate`] with a `y` and `z` of 0.
+use crate::diagnostics::{CssDiagnostic, CssDiagnosticKind};
+use crate::parser::ComponentValue;
+use crate::values::length::{BoxPosition, Length, LengthContext, LengthPercentage};
+use crate::values::{Parse, ToCss, ValueParser, parse_value_with, unexpected, write_number};
+
+///A transform function like `rotate(45deg)`. Angles are in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformFunction {
+    ///`translate()`, `translateX()`, `translateY()`, `translateZ()` and `translate3d()`.
+    Translate(LengthPercentage, LengthPercentage, Length),
+    ///`scale()`, `scaleX()`, `scaleY()`, `scaleZ()` and `scale3d()`.
+    Scale(f32, f32, f32),
+    ///`rotate()`, `rotateX()`, `rotateY()`, `rotateZ()` and `rotate3d()`, with the axis the angle is around.
+    Rotate([f32; 3], f32),
+    ///`skew()`, `skewX()` and `skewY()`.
+    Skew(f32, f32),
+    ///`perspective()`, `None` being `perspective(none)`.
+    Perspective(Option<Length>),
+    Matrix([f32; 6]),
+    Matrix3d([f32; 16]),
+}
+
+impl TransformFunction {
+    ///The function of the same kind that does nothing, which a list is padded with to interpolate with a longer one.
+    pub fn identity(&self) -> Self {
+        match self {
+            TransformFunction::Translate(..) => TransformFunction::Translate(
+                LengthPercentage::Length(Length::ZERO),
+                LengthPercentage::Length(Length::ZERO),
+                Length::ZERO,
+            ),
+            TransformFunction::Scale(..) => TransformFunction::Scale(1.0, 1.0, 1.0),
+            TransformFunction::Rotate(axis, _) => TransformFunction::Rotate(*axis, 0.0),
+            TransformFunction::Skew(..) => TransformFunction::Skew(0.0, 0.0),
+            TransformFunction::Perspective(_) => TransformFunction::Perspective(None),
+            TransformFunction::Matrix(_) => {
+                TransformFunction::Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+            }
+            TransformFunction::Matrix3d(_) => TransformFunction::Matrix3d(Matrix::IDENTITY.0),
+        }
+    }
+
+    ///Whether the function only moves things in the plane of the screen.
+    pub fn is_2d(&self) -> bool {
+        match self {
+            TransformFunction::Translate(_, _, z) => z.value == 0.0,
+            TransformFunction::Scale(_, _, z) => *z == 1.0,
+            TransformFunction::Rotate([x, y, _], angle) => {
+                (*x == 0.0 && *y == 0.0) || *angle == 0.0
+            }
+            TransformFunction::Skew(..) | TransformFunction::Matrix(_) => true,
+            TransformFunction::Perspective(perspective) => perspective.is_none(),
+            TransformFunction::Matrix3d(matrix) => Matrix(*matrix).is_2d(),
+        }
+    }
+
+    ///Resolves the function to a matrix, percentages being relative to the size of the reference box.
+    ///See https://www.w3.org/TR/css-transforms-2/#mathematical-description
+    pub fn to_matrix(&self, context: &LengthContext, width: f32, height: f32) -> Matrix {
+        let mut m = Matrix::IDENTITY.0;
+        match self {
+            TransformFunction::Translate(x, y, z) => {
+                m[12] = x.to_px(context, width);
+                m[13] = y.to_px(context, height);
+                m[14] = z.to_px(context);
+            }
+            TransformFunction::Scale(x, y, z) => {
+                m[0] = *x;
+                m[5] = *y;
+                m[10] = *z;
+            }
+            TransformFunction::Rotate(axis, angle) => {
+                let length = axis.iter().map(|v| v * v).sum::<f32>().sqrt();
+                // An axis that can not be normalized does not rotate anything.
+                if length == 0.0 {
+                    return Matrix::IDENTITY;
+                }
+                let [x, y, z] = axis.map(|v| v / length);
+                let half = angle.to_radians() / 2.0;
+                let sc = half.sin() * half.cos();
+                let sq = half.sin() * half.sin();
+                m[0] = 1.0 - 2.0 * (y * y + z * z) * sq;
+                m[1] = 2.0 * (x * y * sq + z * sc);
+                m[2] = 2.0 * (x * z * sq - y * sc);
+                m[4] = 2.0 * (x * y * sq - z * sc);
+                m[5] = 1.0 - 2.0 * (x * x + z * z) * sq;
+                m[6] = 2.0 * (y * z * sq + x * sc);
+                m[8] = 2.0 * (x * z * sq + y * sc);
+                m[9] = 2.0 * (y * z * sq - x * sc);
+                m[10] = 1.0 - 2.0 * (x * x + y * y) * sq;
+            }
+            TransformFunction::Skew(x, y) => {
+                m[1] = y.to_radians().tan();
+                m[4] = x.to_radians().tan();
+            }
+            // Distances below 1px are clamped to 1px like https://www.w3.org/TR/css-transforms-2/#perspective-property asks for.
+            TransformFunction::Perspective(Some(distance)) => {
+                m[11] = -1.0 / distance.to_px(context).max(1.0);
+            }
+            TransformFunction::Perspective(None) => {}
+            TransformFunction::Matrix([a, b, c, d, e, f]) => {
+                m[0] = *a;
+                m[1] = *b;
+                m[4] = *c;
+                m[5] = *d;
+                m[12] = *e;
+                m[13] = *f;
+            }
+            TransformFunction::Matrix3d(matrix) => m = *matrix,
+        }
+        Matrix(m)
+    }
+
+    fn parse_function(name: &str, input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let zero = || LengthPercentage::Length(Length::ZERO);
+        let comma = |input: &mut ValueParser| input.expect_comma();
+        Ok(match name {
+            "translate" => {
+                let x = LengthPercentage::parse(input)?;
+                let y = match input.try_parse(comma) {
+                    Ok(()) => LengthPercentage::parse(input)?,
+                    Err(_) => zero(),
+                };
+                TransformFunction::Translate(x, y, Length::ZERO)
+            }
+            "translatex" => {
+                TransformFunction::Translate(LengthPercentage::parse(input)?, zero(), Length::ZERO)
+            }
+            "translatey" => {
+                TransformFunction::Translate(zero(), LengthPercentage::parse(input)?, Length::ZERO)
+            }
+            "translatez" => TransformFunction::Translate(zero(), zero(), Length::parse(input)?),
+            "translate3d" => {
+                let x = LengthPercentage::parse(input)?;
+                comma(input)?;
+                let y = LengthPercentage::parse(input)?;
+                comma(input)?;
+                TransformFunction::Translate(x, y, Length::parse(input)?)
+            }
+            "scale" => {
+                let x = input.expect_number_or_percentage()?;
+                let y = match input.try_parse(comma) {
+                    Ok(()) => input.expect_number_or_percentage()?,
+                    Err(_) => x,
+                };
+                TransformFunction::Scale(x, y, 1.0)
+            }
+            "scalex" => TransformFunction::Scale(input.expect_number_or_percentage()?, 1.0, 1.0),
+            "scaley" => TransformFunction::Scale(1.0, input.expect_number_or_percentage()?, 1.0),
+            "scalez" => TransformFunction::Scale(1.0, 1.0, input.expect_number_or_percentage()?),
+            "scale3d" => {
+                let [x, y, z] = parse_numbers(input, ValueParser::expect_number_or_percentage)?;
+                TransformFunction::Scale(x, y, z)
+            }
+            "rotate" | "rotatez" => {
+                TransformFunction::Rotate([0.0, 0.0, 1.0], input.expect_angle_or_zero()?)
+            }
+            "rotatex" => TransformFunction::Rotate([1.0, 0.0, 0.0], input.expect_angle_or_zero()?),
+            "rotatey" => TransformFunction::Rotate([0.0, 1.0, 0.0], input.expect_angle_or_zero()?),
+            "rotate3d" => {
+                let axis = parse_numbers(input, ValueParser::expect_number)?;
+                comma(input)?;
+                TransformFunction::Rotate(axis, input.expect_angle_or_zero()?)
+            }
+            "skew" => {
+                let x = input.expect_angle_or_zero()?;
+                let y = match input.try_parse(comma) {
+                    Ok(()) => input.expect_angle_or_zero()?,
+                    Err(_) => 0.0,
+                };
+                TransformFunction::Skew(x, y)
+            }
+            "skewx" => TransformFunction::Skew(input.expect_angle_or_zero()?, 0.0),
+            "skewy" => TransformFunction::Skew(0.0, input.expect_angle_or_zero()?),
+            "perspective" => {
+                if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+                    return Ok(TransformFunction::Perspective(None));
+                }
+                TransformFunction::Perspective(Some(Length::parse_non_negative(input)?))
+            }
+            "matrix" => {
+                TransformFunction::Matrix(parse_numbers(input, ValueParser::expect_number)?)
+            }
+            "matrix3d" => {
+                TransformFunction::Matrix3d(parse_numbers(input, ValueParser::expect_number)?)
+            }
+            _ => unreachable!("not a transform function"),
+        })
+    }
+}
+
+///The names of the transform functions, lowercase.
+const FUNCTIONS: &[&str] = &[
+    "translate",
+    "translatex",
+    "translatey",
+    "translatez",
+    "translate3d",
+    "scale",
+    "scalex",
+    "scaley",
+    "scalez",
+    "scale3d",
+    "rotate",
+    "rotatex",
+    "rotatey",
+    "rotatez",
+    "rotate3d",
+    "skew",
+    "skewx",
+    "skewy",
+    "perspective",
+    "matrix",
+    "matrix3d",
+];
+
+///Parses `N` comma separated values with `parse`.
+fn parse_numbers<'i, 'a, const N: usize>(
+    input: &mut ValueParser<'i, 'a>,
+    mut parse: impl FnMut(&mut ValueParser<'i, 'a>) -> Result<f32, CssDiagnostic>,
+) -> Result<[f32; N], CssDiagnostic> {
+    let mut out = [0.0; N];
+    for (i, value) in out.iter_mut().enumerate() {
+        if i > 0 {
+            input.expect_comma()?;
+        }
+        *value = parse(input)?;
+    }
+    Ok(out)
+}
+
+impl Parse for TransformFunction {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let value = input.consume()?;
+        let ComponentValue::Function(function) = value else {
+            return Err(unexpected(
+                value,
+                "a transform function like `rotate(45deg)`",
+            ));
+        };
+        let name = function.name.to_ascii_lowercase();
+        if !FUNCTIONS.contains(&name.as_str()) {
+            return Err(CssDiagnostic::new(
+                CssDiagnosticKind::InvalidValue,
+                function.span,
+                format!("`{}()` is not a transform function", function.name),
+            ));
+        }
+        parse_value_with(&function.value, |input| Self::parse_function(&name, input))
+    }
+}
+
+///Writes the values separated by commas.
+fn write_list<T>(values: &[T], out: &mut String, mut write: impl FnMut(&T, &mut String)) {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write(value, out);
+    }
+}
+
+fn write_angle(degrees: f32, out: &mut String) {
+    write_number(degrees, out);
+    out.push_str("deg");
+}
+
+impl ToCss for TransformFunction {
+    fn to_css(&self, out: &mut String) {
+        let numbers = |name: &str, values: &[f32], out: &mut String| {
+            out.push_str(name);
+            out.push('(');
+            write_list(values, out, |v, out| write_number(*v, out));
+            out.push(')');
+        };
+        match self {
+            TransformFunction::Translate(x, y, z) if *z == Length::ZERO => {
+                out.push_str("translate(");
+                x.to_css(out);
+                if *y != LengthPercentage::Length(Length::ZERO) {
+                    out.push_str(", ");
+                    y.to_css(out);
+                }
+                out.push(')');
+            }
+            TransformFunction::Translate(x, y, z) => {
+                out.push_str("translate3d(");
+                x.to_css(out);
+                out.push_str(", ");
+                y.to_css(out);
+                out.push_str(", ");
+                z.to_css(out);
+                out.push(')');
+            }
+            TransformFunction::Scale(x, y, 1.0) if x == y => numbers("scale", &[*x], out),
+            TransformFunction::Scale(x, y, 1.0) => numbers("scale", &[*x, *y], out),
+            TransformFunction::Scale(x, y, z) => numbers("scale3d", &[*x, *y, *z], out),
+            TransformFunction::Rotate(axis, angle) => {
+                let name = match axis {
+                    [0.0, 0.0, 1.0] => "rotate(",
+                    [1.0, 0.0, 0.0] => "rotateX(",
+                    [0.0, 1.0, 0.0] => "rotateY(",
+                    [x, y, z] => {
+                        out.push_str("rotate3d(");
+                        write_list(&[*x, *y, *z], out, |v, out| write_number(*v, out));
+                        ", "
+                    }
+                };
+                out.push_str(name);
+                write_angle(*angle, out);
+                out.push(')');
+            }
+            TransformFunction::Skew(x, y) => {
+                out.push_str("skew(");
+                write_angle(*x, out);
+                if *y != 0.0 {
+                    out.push_str(", ");
+                    write_angle(*y, out);
+                }
+                out.push(')');
+            }
+            TransformFunction::Perspective(distance) => {
+                out.push_str("perspective(");
+                match distance {
+                    Some(distance) => distance.to_css(out),
+                    None => out.push_str("none"),
+                }
+                out.push(')');
+            }
+            TransformFunction::Matrix(values) => numbers("matrix", values, out),
+            TransformFunction::Matrix3d(values) => numbers("matrix3d", values, out),
+        }
+    }
+}
+
+///The value of `transform`, `none` being an empty list.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransformList(pub Vec<TransformFunction>);
+
+impl TransformList {
+    ///Resolves the functions to a single matrix, see [`TransformFunction::to_matrix`].
+    ///The matrix transforms around the top left corner of the box, so the renderer
+    ///translates to the `transform-origin` and back around it, see [`TransformOrigin::to_px`].
+    pub fn to_matrix(&self, context: &LengthContext, width: f32, height: f32) -> Matrix {
+        self.0.iter().fold(Matrix::IDENTITY, |matrix, function| {
+            matrix.multiply(&function.to_matrix(context, width, height))
+        })
+    }
+}
+
+impl Parse for TransformList {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        if input.try_parse(|v| v.expect_keyword("none")).is_ok() {
+            return Ok(TransformList::default());
+        }
+        let mut functions = vec![TransformFunction::parse(input)?];
+        while let Some(ComponentValue::Function(_)) = input.peek() {
+            functions.push(TransformFunction::parse(input)?);
+        }
+        Ok(TransformList(functions))
+    }
+}
+
+impl ToCss for TransformList {
+    fn to_css(&self, out: &mut String) {
+        if self.0.is_empty() {
+            out.push_str("none");
+        }
+        for (i, function) in self.0.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            function.to_css(out);
+        }
+    }
+}
+
+///A 4x4 matrix in the column-major order of `matrix3d()`, which transforms points as column vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix(pub [f32; 16]);
+
+impl Matrix {
+    pub const IDENTITY: Matrix = Matrix([
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 1.0, 0.0, //
+        0.0, 0.0, 0.0, 1.0,
+    ]);
+
+    ///The matrix of `matrix(a, b, c, d, e, f)`.
+    pub fn from_2d([a, b, c, d, e, f]: [f32; 6]) -> Self {
+        Matrix([
+            a, b, 0.0, 0.0, //
+            c, d, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            e, f, 0.0, 1.0,
+        ])
+    }
+
+    ///The `a` to `f` of `matrix()`, or `None` if the matrix is 3D.
+    pub fn to_2d(&self) -> Option<[f32; 6]> {
+        let m = &self.0;
+        self.is_2d()
+            .then_some([m[0], m[1], m[4], m[5], m[12], m[13]])
+    }
+
+    ///Whether the matrix can be written as `matrix()`, see https://www.w3.org/TR/css-transforms-2/#2d-matrix
+    pub fn is_2d(&self) -> bool {
+        let m = &self.0;
+        [m[2], m[3], m[6], m[7], m[8], m[9], m[11], m[14]] == [0.0; 8]
+            && m[10] == 1.0
+            && m[15] == 1.0
+    }
+
+    ///Returns `self × other`, which applies `other` first.
+    pub fn multiply(&self, other: &Matrix) -> Matrix {
+        let mut out = [0.0; 16];
+        for column in 0..4 {
+            for row in 0..4 {
+                out[column * 4 + row] = (0..4)
+                    .map(|k| self.0[k * 4 + row] * other.0[column * 4 + k])
+                    .sum();
+            }
+        }
+        Matrix(out)
+    }
+
+    ///Transforms a point in the plane of the screen, projecting it back onto the plane.
+    pub fn transform_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let m = &self.0;
+        let w = m[3] * x + m[7] * y + m[15];
+        let w = if w == 0.0 { 1.0 } else { w };
+        (
+            (m[0] * x + m[4] * y + m[12]) / w,
+            (m[1] * x + m[5] * y + m[13]) / w,
+        )
+    }
+}
+
+///The value of `transform-origin`, which the functions of `transform` transform around.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformOrigin {
+    pub position: BoxPosition,
+    pub z: Length,
+}
+
+impl TransformOrigin {
+    ///Resolves the origin to pixels from the top left corner of the reference box.
+    pub fn to_px(&self, context: &LengthContext, width: f32, height: f32) -> (f32, f32, f32) {
+        let (x, y) = self.position.to_px(context, width, height);
+        (x, y, self.z.to_px(context))
+    }
+}
+
+impl Parse for TransformOrigin {
+    fn parse(input: &mut ValueParser) -> Result<Self, CssDiagnostic> {
+        let position = BoxPosition::parse(input)?;
+        let z = input.try_parse(Length::parse).unwrap_or(Length::ZERO);
+        Ok(TransformOrigin { position, z })
+    }
+}
+
+impl ToCss for TransformOrigin {
+    fn to_css(&self, out: &mut String) {
+        self.position.to_css(out);
+        if self.z != Length::ZERO {
+            out.push(' ');
+            self.z.to_css(out);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use jessie_css::animation::timeline::ElementAnimations;
+use jessie_css::cascade::{Cascade, ComputedStyle};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+///Runs the transitions and animations of every element, keyed by whatever the layout identifies its elements with.
+///The event loop moves the clock forward each frame with [`AnimationDriver::tick`] and keeps drawing frames while [`AnimationDriver::is_animating`],
+///and the computed styles are passed through [`AnimationDriver::style`].
+pub struct AnimationDriver<K> {
+    elements: HashMap<K, ElementAnimations>,
+    ///The time of the current frame in seconds.
+    now: f64,
+}
+
+impl<K> Default for AnimationDriver<K> {
+    fn default() -> Self {
+        Self {
+            elements: HashMap::new(),
+            now: 0.0,
+        }
+    }
+}
+
+impl<K: Hash + Eq> AnimationDriver<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Moves the clock to the time of a new frame, dropping the transitions that have finished.
+    pub fn tick(&mut self, now: f64) {
+        self.now = now;
+        for animations in self.elements.values_mut() {
+            animations.tick(now);
+        }
+    }
+
+    ///The time of the current frame in seconds.
+    pub fn now(&self) -> f64 {
+        self.now
+    }
+
+    ///Starts the transitions and animations the new computed style of an element asks for, and applies the running ones to it.
+    pub fn style(&mut self, key: K, style: &mut ComputedStyle, cascade: &Cascade) {
+        let animations = self.elements.entry(key).or_default();
+        animations.update(style, cascade, self.now);
+        animations.apply(style, self.now);
+    }
+
+    ///Whether any element is still animating, in which case the next frame has to be drawn.
+    pub fn is_animating(&self) -> bool {
+        self.elements.values().any(|v| v.is_running(self.now))
+    }
+
+    ///Forgets an element that was removed, so its animations do not start again if it comes back.
+    pub fn remove(&mut self, key: &K) {
+        self.elements.remove(key);
+    }
+}
@@ -0,0 +1,50 @@
+use jessie_css::media::{Hover, MediaEnvironment, Pointer};
+
+///The platforms jessie-lib runs on, matching the platform specific configurations of [`crate::appinfo::AppInfo`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Platform {
+    Linux,
+    Windows,
+    MacOS,
+    Android,
+    IOS,
+}
+
+impl Platform {
+    ///The platform the app was built for.
+    pub fn current() -> Self {
+        if cfg!(target_os = "android") {
+            Platform::Android
+        } else if cfg!(target_os = "ios") {
+            Platform::IOS
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else {
+            Platform::Linux
+        }
+    }
+
+    ///Whether the platform is a phone that is used with a finger instead of a mouse.
+    pub fn is_touch(&self) -> bool {
+        matches!(self, Platform::Android | Platform::IOS)
+    }
+}
+
+///Fills the environment `@media` queries are evaluated against, for [`jessie_css::cascade::Cascade::set_environment`].
+///`width` and `height` are the size of the window in physical pixels, and `dpi_scale` the number of physical pixels per CSS pixel.
+pub fn media_environment(
+    platform: Platform,
+    width: f32,
+    height: f32,
+    dpi_scale: f32,
+) -> MediaEnvironment {
+    let mut environment = MediaEnvironment::new(width / dpi_scale, height / dpi_scale);
+    environment.resolution = dpi_scale;
+    if platform.is_touch() {
+        environment.pointer = Pointer::Coarse;
+        environment.hover = Hover::None;
+    }
+    environment
+}
@@ -1,4 +1,8 @@
+use crate::animation::AnimationDriver;
+use crate::environment::{Platform, media_environment};
 use crate::eventloop::renderer::Renderer;
+use jessie_css::cascade::{Cascade, ComputedStyle};
+use jessie_css::matching::Element;
 use std::any::Any;
 
 use miniquad::*;
@@ -12,6 +16,10 @@ pub struct Quad {
 pub struct MiniquadRenderer {
     renderer: Box<dyn RenderingBackend>,
     quadbuf: ResizableBuffer<Quad>,
+    ///The stylesheets of the app, with `@media` queries evaluated against the window.
+    cascade: Cascade<'static>,
+    ///The transitions and animations of the elements on screen, ticked every frame.
+    animations: AnimationDriver<usize>,
 }
 
 impl EventHandler for MiniquadRenderer {
@@ -20,12 +28,29 @@ impl EventHandler for MiniquadRenderer {
         self.renderer.end_render_pass();
     }
 
-    fn update(&mut self) {}
+    fn update(&mut self) {
+        self.animations.tick(date::now());
+        // The event loop blocks until the next event, so frames have to be asked for while something moves.
+        if self.animations.is_animating() {
+            window::schedule_update();
+        }
+    }
+
+    fn resize_event(&mut self, width: f32, height: f32) {
+        self.cascade.set_environment(media_environment(
+            Platform::current(),
+            width,
+            height,
+            window::dpi_scale(),
+        ));
+        window::schedule_update();
+    }
 }
 
 impl Renderer for MiniquadRenderer {
     fn run() {
-        let conf = conf::Conf::default();
+        let mut conf = conf::Conf::default();
+        conf.platform.blocking_event_loop = true;
 
         miniquad::start(conf, move || Box::new(Self::default()));
     }
@@ -36,7 +61,39 @@ impl Default for MiniquadRenderer {
         let mut renderer = window::new_rendering_backend();
         let quadbuf: ResizableBuffer<Quad> =
             ResizableBuffer::new(200, &mut renderer, BufferType::VertexBuffer);
-        Self { renderer, quadbuf }
+        let (width, height) = window::screen_size();
+        let mut cascade = Cascade::new();
+        cascade.set_environment(media_environment(
+            Platform::current(),
+            width,
+            height,
+            window::dpi_scale(),
+        ));
+        Self {
+            renderer,
+            quadbuf,
+            cascade,
+            animations: AnimationDriver::new(),
+        }
+    }
+}
+
+impl MiniquadRenderer {
+    ///The cascade the styles of the app are computed with, for adding its stylesheets.
+    pub fn cascade(&mut self) -> &mut Cascade<'static> {
+        &mut self.cascade
+    }
+
+    ///Computes the style of the element identified by `key` for the current frame, with its running transitions and animations applied.
+    pub fn compute_style<E: Element>(
+        &mut self,
+        key: usize,
+        element: &E,
+        parent: Option<&ComputedStyle<'static>>,
+    ) -> ComputedStyle<'static> {
+        let mut style = self.cascade.compute(element, None, parent, None);
+        self.animations.style(key, &mut style, &self.cascade);
+        style
     }
 }
 
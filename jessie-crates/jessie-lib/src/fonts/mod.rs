@@ -0,0 +1,133 @@
+use jessie_css::cascade::{Cascade, ComputedStyle};
+use jessie_css::font_face::{FontFace, FontFaceStyle};
+use jessie_css::values::font::{FamilyName, FontFamily, FontStretch, FontStyle, FontWeight};
+
+///The font faces an app declares with `@font-face`, which text picks its font from.
+///Generic families like `sans-serif` are left to the platform, as are families no face is declared for.
+#[derive(Debug, Clone, Default)]
+pub struct FontRegistry {
+    faces: Vec<FontFace>,
+}
+
+impl FontRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Collects the font faces of the stylesheets added to a cascade.
+    pub fn from_cascade(cascade: &Cascade) -> Self {
+        Self {
+            faces: cascade.font_faces().cloned().collect(),
+        }
+    }
+
+    ///Adds a face, which wins over the faces added before it that have the same descriptors.
+    pub fn add(&mut self, face: FontFace) {
+        self.faces.push(face);
+    }
+
+    pub fn faces(&self) -> &[FontFace] {
+        &self.faces
+    }
+
+    ///Picks the face to draw `character` with for a `font-family` list, trying the families in order
+    ///and picking between the faces of a family with the font matching of https://www.w3.org/TR/css-fonts-4/#font-style-matching
+    ///`stretch` is a percentage of the normal width. Returns `None` if no family has a face for the character,
+    ///in which case the platform's fonts should be used.
+    pub fn find(
+        &self,
+        family: &FontFamily,
+        weight: f32,
+        style: FontStyle,
+        stretch: f32,
+        character: char,
+    ) -> Option<&FontFace> {
+        family.0.iter().find_map(|name| {
+            let FamilyName::Named(name) = name else {
+                return None;
+            };
+            let faces: Vec<&FontFace> = self
+                .faces
+                .iter()
+                .filter(|v| v.family.eq_ignore_ascii_case(name) && v.covers(character))
+                .collect();
+            // Width first, then style, then weight, each narrowing the faces down to the closest ones.
+            let faces = closest(faces, |v| stretch_distance(v.stretch, stretch));
+            let faces = closest(faces, |v| style_distance(v.style, style));
+            let faces = closest(faces, |v| weight_distance(v.weight, weight));
+            faces.last().copied()
+        })
+    }
+
+    ///Picks the face for the computed style of an element, see [`FontRegistry::find`].
+    pub fn find_for_style(&self, style: &ComputedStyle, character: char) -> Option<&FontFace> {
+        let family = style.get_as::<FontFamily>("font-family")?;
+        let weight = style
+            .get_as::<FontWeight>("font-weight")
+            .map_or(FontWeight::NORMAL, |v| v.resolve(FontWeight::NORMAL));
+        let font_style = style
+            .get_as::<FontStyle>("font-style")
+            .unwrap_or(FontStyle::Normal);
+        let stretch = style
+            .get_as::<FontStretch>("font-stretch")
+            .map_or(100.0, |v| v.0);
+        self.find(&family, weight, font_style, stretch, character)
+    }
+}
+
+///Keeps the faces with the smallest distance. Distances are compared as tuples, a face in the range being `(0, 0.0)`.
+fn closest(faces: Vec<&FontFace>, distance: impl Fn(&FontFace) -> (u8, f32)) -> Vec<&FontFace> {
+    let best = faces
+        .iter()
+        .map(|v| distance(v))
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    faces
+        .into_iter()
+        .filter(|v| Some(distance(v)) == best)
+        .collect()
+}
+
+///How far a range of values is from `desired`, with the values below it tried first when `below_first`.
+fn range_distance((low, high): (f32, f32), desired: f32, below_first: bool) -> (u8, f32) {
+    match (high < desired, low > desired) {
+        (false, false) => (0, 0.0),
+        (true, _) => (if below_first { 1 } else { 2 }, desired - high),
+        (_, true) => (if below_first { 2 } else { 1 }, low - desired),
+    }
+}
+
+///Narrower widths are tried first for normal or narrower text, wider ones for wider text.
+fn stretch_distance(stretch: (f32, f32), desired: f32) -> (u8, f32) {
+    range_distance(stretch, desired, desired <= 100.0)
+}
+
+///Between 400 and 500 the weights up to 500 are tried first, then lighter ones, then the ones above 500.
+///Lighter weights are tried first below 400, and heavier ones above 500.
+fn weight_distance(weight: (f32, f32), desired: f32) -> (u8, f32) {
+    if (400.0..=500.0).contains(&desired) {
+        return match range_distance(weight, desired, false) {
+            (1, distance) if weight.0 > 500.0 => (3, distance),
+            distance => distance,
+        };
+    }
+    range_distance(weight, desired, desired < 400.0)
+}
+
+///Italic text falls back to oblique and then normal faces, oblique text to italic and then normal faces,
+///and normal text to oblique and then italic faces. Oblique faces are also ordered by how close their angle is.
+fn style_distance(style: FontFaceStyle, desired: FontStyle) -> (u8, f32) {
+    let angle = |range: (f32, f32), desired: f32| range_distance(range, desired, desired <= 0.0).1;
+    match (desired, style) {
+        (FontStyle::Normal, FontFaceStyle::Normal) => (0, 0.0),
+        (FontStyle::Normal, FontFaceStyle::Oblique(low, high)) => (1, angle((low, high), 0.0)),
+        (FontStyle::Normal, FontFaceStyle::Italic) => (2, 0.0),
+        (FontStyle::Italic, FontFaceStyle::Italic) => (0, 0.0),
+        (FontStyle::Italic, FontFaceStyle::Oblique(low, high)) => (1, angle((low, high), 14.0)),
+        (FontStyle::Italic, FontFaceStyle::Normal) => (2, 0.0),
+        (FontStyle::Oblique(desired), FontFaceStyle::Oblique(low, high)) => {
+            (0, angle((low, high), desired))
+        }
+        (FontStyle::Oblique(_), FontFaceStyle::Italic) => (1, 0.0),
+        (FontStyle::Oblique(_), FontFaceStyle::Normal) => (2, 0.0),
+    }
+}
@@ -1,10 +1,14 @@
 use crate::appinfo::AppInfo;
 use crate::layoutinfo::LayoutInfo;
 use crate::layoutprovider::LayoutProvider;
+pub mod animation;
 pub mod appinfo;
+pub mod environment;
 pub mod eventloop;
 pub mod expectation;
+pub mod fonts;
 pub mod layoutinfo;
 pub mod layoutprovider;
+mod tests;
 pub use crate::eventloop::run::run;
 pub use jessie_macros::app;
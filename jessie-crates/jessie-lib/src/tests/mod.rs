@@ -0,0 +1,3 @@
+#[cfg(test)]
+mod test_animation;
+mod test_fonts;
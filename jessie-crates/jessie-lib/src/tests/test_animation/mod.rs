@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::animation::AnimationDriver;
+    use jessie_css::cascade::{Cascade, Origin};
+    use jessie_css::matching::{Element, ElementState};
+    use jessie_css::parser::Parser;
+    use jessie_css::serializer::serialize_component_values;
+
+    ///A lone `button` that is hovered or not.
+    #[derive(Clone, PartialEq)]
+    struct Button(ElementState);
+    impl Element for Button {
+        fn parent(&self) -> Option<Self> {
+            None
+        }
+        fn prev_sibling(&self) -> Option<Self> {
+            None
+        }
+        fn next_sibling(&self) -> Option<Self> {
+            None
+        }
+        fn first_child(&self) -> Option<Self> {
+            None
+        }
+        fn local_name(&self) -> &str {
+            "button"
+        }
+        fn id(&self) -> Option<&str> {
+            None
+        }
+        fn classes(&self) -> impl Iterator<Item = &str> {
+            std::iter::empty()
+        }
+        fn attribute(&self, _: &str) -> Option<&str> {
+            None
+        }
+        fn state(&self) -> ElementState {
+            self.0
+        }
+        fn is_empty(&self) -> bool {
+            true
+        }
+    }
+    #[test]
+    fn test_tick() {
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new(
+                "button { width: 100px; transition: width 1s linear }
+                 button:hover { width: 200px }",
+            )
+            .parse_stylesheet(),
+            Origin::Author,
+        );
+        let mut driver = AnimationDriver::new();
+        let frame = |driver: &mut AnimationDriver<usize>, button: &Button, now: f64| {
+            driver.tick(now);
+            let mut style = cascade.compute(button, None, None, None);
+            driver.style(0, &mut style, &cascade);
+            serialize_component_values(style.get("width").unwrap())
+        };
+        assert_eq!(
+            frame(&mut driver, &Button(ElementState::default()), 0.0),
+            "100px"
+        );
+        assert!(!driver.is_animating());
+
+        let hovered = Button(ElementState::HOVER);
+        assert_eq!(frame(&mut driver, &hovered, 10.0), "100px");
+        assert!(driver.is_animating());
+        assert_eq!(frame(&mut driver, &hovered, 10.5), "150px");
+        assert_eq!(driver.now(), 10.5);
+        assert!(driver.is_animating());
+        assert_eq!(frame(&mut driver, &hovered, 11.0), "200px");
+        assert!(!driver.is_animating());
+
+        // A removed element starts over without a transition.
+        driver.remove(&0);
+        assert_eq!(
+            frame(&mut driver, &Button(ElementState::default()), 12.0),
+            "100px"
+        );
+        assert!(!driver.is_animating());
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use crate::fonts::FontRegistry;
+    use jessie_css::cascade::{Cascade, Origin};
+    use jessie_css::parser::Parser;
+    use jessie_css::values::font::{FontFamily, FontStyle};
+    use jessie_css::values::parse_value;
+
+    ///A registry of faces of the family `x` with the given descriptors, whose `src` is the name of the face.
+    fn registry(faces: &[(&str, &str)]) -> FontRegistry {
+        let source: String = faces
+            .iter()
+            .map(|(name, descriptors)| {
+                format!("@font-face {{ font-family: x; src: local({name}); {descriptors} }}")
+            })
+            .collect();
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(&Parser::new(&source).parse_stylesheet(), Origin::Author);
+        FontRegistry::from_cascade(&cascade)
+    }
+    ///The name of the face picked for the text, see [`registry`].
+    fn find(
+        registry: &FontRegistry,
+        family: &str,
+        weight: f32,
+        style: FontStyle,
+        stretch: f32,
+        character: char,
+    ) -> Option<String> {
+        let family: FontFamily =
+            parse_value(&Parser::new(family).parse_list_of_component_values()).unwrap();
+        let face = registry.find(&family, weight, style, stretch, character)?;
+        Some(format!("{:?}", face.sources[0]))
+    }
+    fn pick(faces: &[(&str, &str)], weight: f32, style: FontStyle, stretch: f32) -> String {
+        let face = find(&registry(faces), "x", weight, style, stretch, 'a').unwrap();
+        // `Local("name")`
+        face[7..face.len() - 2].to_string()
+    }
+    #[test]
+    fn test_family_fallback() {
+        let mut cascade = Cascade::new();
+        cascade.add_stylesheet(
+            &Parser::new(
+                "@font-face { font-family: Brand; src: local(brand); unicode-range: U+0-7F }
+                 @font-face { font-family: Fallback; src: local(fallback); unicode-range: U+0-FF }",
+            )
+            .parse_stylesheet(),
+            Origin::Author,
+        );
+        let registry = FontRegistry::from_cascade(&cascade);
+        let find = |family, character| {
+            find(
+                &registry,
+                family,
+                400.0,
+                FontStyle::Normal,
+                100.0,
+                character,
+            )
+        };
+        assert_eq!(
+            find("Missing, brand, Fallback", 'a').as_deref(),
+            Some("Local(\"brand\")")
+        );
+        // A face is only picked for the characters of its `unicode-range`.
+        assert_eq!(
+            find("Missing, brand, Fallback", 'é').as_deref(),
+            Some("Local(\"fallback\")")
+        );
+        assert_eq!(find("Brand, Fallback", 'Ω'), None);
+        // Generic families are left to the platform.
+        assert_eq!(
+            find("sans-serif, Fallback", 'a').as_deref(),
+            Some("Local(\"fallback\")")
+        );
+        assert_eq!(find("sans-serif", 'a'), None);
+    }
+    #[test]
+    fn test_weight_matching() {
+        let weight =
+            |faces: &[(&str, &str)], desired| pick(faces, desired, FontStyle::Normal, 100.0);
+        let faces = [
+            ("light", "font-weight: 300"),
+            ("book", "font-weight: 450"),
+            ("semibold", "font-weight: 600"),
+        ];
+        assert_eq!(weight(&faces, 400.0), "book");
+        assert_eq!(weight(&faces, 450.0), "book");
+        // Between 400 and 500, the weights up to 500 are tried first, then lighter ones, then the ones above 500.
+        assert_eq!(weight(&faces, 460.0), "book");
+        assert_eq!(weight(&faces[..1], 500.0), "light");
+        assert_eq!(weight(&[faces[0], faces[2]], 500.0), "light");
+        assert_eq!(weight(&faces[2..], 500.0), "semibold");
+        // Below 400 lighter weights are tried first, above 500 heavier ones.
+        assert_eq!(weight(&faces, 350.0), "light");
+        assert_eq!(weight(&faces[1..], 350.0), "book");
+        assert_eq!(weight(&faces, 550.0), "semibold");
+        assert_eq!(weight(&faces[..2], 700.0), "book");
+        // A variable font covers its whole range.
+        assert_eq!(
+            weight(&[("variable", "font-weight: 100 900"), faces[1]], 700.0),
+            "variable"
+        );
+    }
+    #[test]
+    fn test_style_matching() {
+        let style = |faces: &[(&str, &str)], desired| pick(faces, 400.0, desired, 100.0);
+        let faces = [
+            ("italic", "font-style: italic"),
+            ("oblique", "font-style: oblique 10deg"),
+            ("upright", "font-style: normal"),
+        ];
+        assert_eq!(style(&faces, FontStyle::Italic), "italic");
+        assert_eq!(style(&faces[1..], FontStyle::Italic), "oblique");
+        assert_eq!(style(&faces[2..], FontStyle::Italic), "upright");
+        assert_eq!(style(&faces, FontStyle::Oblique(10.0)), "oblique");
+        assert_eq!(
+            style(&[faces[0], faces[2]], FontStyle::Oblique(10.0)),
+            "italic"
+        );
+        assert_eq!(style(&faces, FontStyle::Normal), "upright");
+        assert_eq!(style(&faces[..2], FontStyle::Normal), "oblique");
+        // Oblique faces are ordered by how close their angle is.
+        let obliques = [
+            ("slight", "font-style: oblique 5deg"),
+            ("steep", "font-style: oblique 20deg"),
+        ];
+        assert_eq!(style(&obliques, FontStyle::Oblique(18.0)), "steep");
+        // Italic text tries the angles from 14deg up first.
+        assert_eq!(style(&obliques, FontStyle::Italic), "steep");
+        assert_eq!(style(&obliques[..1], FontStyle::Italic), "slight");
+    }
+    #[test]
+    fn test_stretch_matching() {
+        let stretch =
+            |faces: &[(&str, &str)], desired| pick(faces, 400.0, FontStyle::Normal, desired);
+        let faces = [
+            ("condensed", "font-stretch: 75%"),
+            ("normal", "font-stretch: 100%"),
+            ("expanded", "font-stretch: 125%"),
+        ];
+        assert_eq!(stretch(&faces, 100.0), "normal");
+        // Narrower widths are tried first for normal or narrower text, however close the wider ones are.
+        assert_eq!(stretch(&[faces[0], faces[2]], 100.0), "condensed");
+        assert_eq!(stretch(&faces[1..], 90.0), "normal");
+        assert_eq!(stretch(&faces[1..], 80.0), "normal");
+        assert_eq!(stretch(&faces[2..], 80.0), "expanded");
+        // And wider widths for wider text.
+        assert_eq!(stretch(&faces, 105.0), "expanded");
+        assert_eq!(stretch(&faces[..2], 150.0), "normal");
+        // Width is matched before weight.
+        assert_eq!(
+            pick(
+                &[
+                    ("bold", "font-weight: 400; font-stretch: 75%"),
+                    ("wide", "font-weight: 700; font-stretch: 100%"),
+                ],
+                400.0,
+                FontStyle::Normal,
+                100.0
+            ),
+            "wide"
+        );
+    }
+}